@@ -34,6 +34,7 @@ pub enum OckamError {
     NoSuchProtocol,
     SystemAddressNotBound,
     SystemInvalidConfiguration,
+    AliasTaken,
 }
 
 impl ockam_core::compat::error::Error for OckamError {}
@@ -50,6 +51,7 @@ impl From<OckamError> for Error {
         // TODO: improve this mapping
         let kind = match err {
             SystemAddressNotBound | SystemInvalidConfiguration | InvalidParameter => Kind::Misuse,
+            AliasTaken => Kind::AlreadyExists,
             _ => Kind::Protocol,
         };
 
@@ -1,24 +1,400 @@
-use crate::Context;
-use core::str::from_utf8;
-use ockam_core::compat::{boxed::Box, vec::Vec};
-use ockam_core::{Address, Any, LocalMessage, Result, Route, Routed, TransportMessage, Worker};
-use tracing::info;
+use crate::{Context, Message, OckamError};
+use core::fmt;
+use core::str::FromStr;
+use core::time::Duration;
+use ockam_core::compat::{
+    boxed::Box,
+    collections::{BTreeMap, VecDeque},
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use ockam_core::{
+    AccessControl, Address, AllowAll, Any, Decodable, LocalMessage, Mailbox, Mailboxes, Result,
+    Route, Routed, TransportMessage, Worker,
+};
+use ockam_node::access_control::LocalOriginOnly;
+use ockam_node::{DelayedEvent, WorkerBuilder};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+/// The payload of ephemeral forwarder registrations.
+const EPHEMERAL: &str = "register";
+
+/// What a forwarding service does when a static forwarder is registered
+/// under an alias which another registrant holds.
+#[derive(Serialize, Deserialize, minicbor::Decode, minicbor::Encode)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[rustfmt::skip]
+pub enum TakeoverPolicy {
+    /// Reject the registration.
+    #[n(0)] Fail,
+    /// Forward to the new registrant instead of the current one.
+    #[n(1)] Replace,
+    /// Forward to the new registrant once the current one releases the
+    /// alias, and to the registrants queued before it.
+    #[n(2)] Queue,
+}
+
+impl Default for TakeoverPolicy {
+    fn default() -> Self {
+        TakeoverPolicy::Fail
+    }
+}
+
+impl fmt::Display for TakeoverPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TakeoverPolicy::Fail => "fail",
+            TakeoverPolicy::Replace => "replace",
+            TakeoverPolicy::Queue => "queue",
+        })
+    }
+}
+
+impl FromStr for TakeoverPolicy {
+    type Err = OckamError;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        match s {
+            "fail" => Ok(TakeoverPolicy::Fail),
+            "replace" => Ok(TakeoverPolicy::Replace),
+            "queue" => Ok(TakeoverPolicy::Queue),
+            _ => Err(OckamError::InvalidParameter),
+        }
+    }
+}
+
+/// A registration sent to a forwarding service
+///
+/// Registrations are strings which the service sends back to confirm
+/// them. They are the alias to register, optionally followed by
+/// `;takeover=<policy>` and `;ttl=<seconds>`, or by `;release` to give
+/// the alias up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Registration {
+    pub(crate) alias: String,
+    pub(crate) action: Action,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Action {
+    Register {
+        policy: TakeoverPolicy,
+        /// Seconds after which the registration expires, unless renewed.
+        ttl: Option<u64>,
+    },
+    Release,
+}
+
+impl Registration {
+    pub(crate) fn register(alias: impl Into<String>, policy: TakeoverPolicy) -> Self {
+        Self {
+            alias: alias.into(),
+            action: Action::Register { policy, ttl: None },
+        }
+    }
+
+    /// Let the registration expire unless it is renewed within `ttl`,
+    /// rounded up to whole seconds.
+    pub(crate) fn with_ttl(mut self, ttl: Duration) -> Self {
+        if let Action::Register { ttl: t, .. } = &mut self.action {
+            let secs = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+            *t = Some(secs.max(1))
+        }
+        self
+    }
+
+    pub(crate) fn release(alias: impl Into<String>) -> Self {
+        Self {
+            alias: alias.into(),
+            action: Action::Release,
+        }
+    }
+
+    /// The payload to send, which is the bare alias for the default policy
+    /// without a TTL, as expected by forwarding services that have neither.
+    pub(crate) fn payload(&self) -> String {
+        let (policy, ttl) = match self.action {
+            Action::Register { policy, ttl } => (policy, ttl),
+            Action::Release => return format!("{};release", self.alias),
+        };
+        let mut payload = self.alias.clone();
+        if policy != TakeoverPolicy::Fail {
+            payload.push_str(&format!(";takeover={}", policy))
+        }
+        if let Some(ttl) = ttl {
+            payload.push_str(&format!(";ttl={}", ttl))
+        }
+        payload
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        let (alias, params) = s.split_once(';').unwrap_or((s, ""));
+        if alias.is_empty() {
+            return None;
+        }
+        if params == "release" {
+            return Some(Self::release(alias));
+        }
+        let mut policy = TakeoverPolicy::Fail;
+        let mut ttl = None;
+        for param in params.split(';').filter(|p| !p.is_empty()) {
+            match param.split_once('=')? {
+                ("takeover", p) => policy = p.parse().ok()?,
+                ("ttl", t) => ttl = Some(t.parse().ok().filter(|t| *t > 0)?),
+                _ => return None,
+            }
+        }
+        Some(Self {
+            alias: alias.to_string(),
+            action: Action::Register { policy, ttl },
+        })
+    }
+}
+
+/// What a forwarding service replies to a registration it did not confirm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Reply {
+    /// The alias is held by another registrant.
+    AliasTaken,
+    /// The registration is queued until the alias is released.
+    Queued,
+}
+
+impl Reply {
+    const ALIAS_TAKEN: &'static str = "!alias_taken";
+    const QUEUED: &'static str = "!queued";
+
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        match s {
+            Self::ALIAS_TAKEN => Some(Reply::AliasTaken),
+            Self::QUEUED => Some(Reply::Queued),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Reply::AliasTaken => Self::ALIAS_TAKEN,
+            Reply::Queued => Self::QUEUED,
+        }
+    }
+}
 
 /// Alias worker to register remote workers under local names.
 ///
 /// To talk with this worker, you can use the
 /// [`RemoteForwarder`](crate::remote::RemoteForwarder) which is a
 /// compatible client for this server.
+///
+/// When a static forwarder is registered under an alias which another
+/// registrant holds, the [`TakeoverPolicy`] of the registration decides
+/// what happens. The same registrant registering again only refreshes
+/// its registration. Registrations with a TTL release their alias when
+/// they are not refreshed in time.
 #[non_exhaustive]
-pub struct ForwardingService;
+pub struct ForwardingService {
+    aliases: BTreeMap<String, Holder>,
+    // Where expired registrations are reported
+    expiry: Address,
+}
+
+/// The registrant holding an alias, and those waiting for it
+struct Holder {
+    forward_route: Route,
+    control: Address,
+    expiry: Option<DelayedEvent<Expired>>,
+    queue: VecDeque<Pending>,
+}
+
+/// A registration waiting for its alias to be released
+struct Pending {
+    forward_route: Route,
+    payload: Vec<u8>,
+    ttl: Option<u64>,
+}
+
+/// Sent to the forwarding service when a registration was not refreshed
+/// within its TTL
+#[derive(Serialize, Deserialize, Message, Clone)]
+struct Expired {
+    alias: String,
+    forward_route: Route,
+}
 
 impl ForwardingService {
     /// Start a forwarding service. The address of the forwarding service will be
     /// `"forwarding_service"`.
     pub async fn create(ctx: &Context) -> Result<()> {
-        ctx.start_worker("forwarding_service", Self).await?;
+        Self::create_with_access_control(ctx, "forwarding_service", Arc::new(AllowAll)).await
+    }
+
+    /// Start a forwarding service at the given address, which only accepts
+    /// the registrations that the access control allows.
+    ///
+    /// The forwarders it creates accept messages from anyone, like those of
+    /// [`create`](Self::create).
+    pub async fn create_with_access_control(
+        ctx: &Context,
+        address: impl Into<Address>,
+        access_control: Arc<dyn AccessControl>,
+    ) -> Result<()> {
+        let expiry = Address::random_local();
+        let service = Self {
+            aliases: BTreeMap::new(),
+            expiry: expiry.clone(),
+        };
+        let mailboxes = Mailboxes::new(
+            Mailbox::new(address.into(), access_control),
+            vec![Mailbox::new(expiry, Arc::new(LocalOriginOnly))],
+        );
+        WorkerBuilder::with_mailboxes(mailboxes, service)
+            .start(ctx)
+            .await?;
         Ok(())
     }
+
+    /// Schedule the expiry of a registration, if it has a TTL.
+    async fn expire(
+        &self,
+        ctx: &Context,
+        holder: &mut Holder,
+        alias: &str,
+        ttl: Option<u64>,
+    ) -> Result<()> {
+        holder.expiry = match ttl {
+            Some(ttl) => {
+                let msg = Expired {
+                    alias: alias.to_string(),
+                    forward_route: holder.forward_route.clone(),
+                };
+                let mut event = DelayedEvent::create(ctx, self.expiry.clone(), msg).await?;
+                event.schedule(Duration::from_secs(ttl)).await?;
+                Some(event)
+            }
+            None => None,
+        };
+        Ok(())
+    }
+
+    async fn register(
+        &mut self,
+        ctx: &Context,
+        forward_route: Route,
+        alias: String,
+        (policy, ttl): (TakeoverPolicy, Option<u64>),
+        payload: Vec<u8>,
+    ) -> Result<()> {
+        let mut holder = match self.aliases.remove(&alias) {
+            Some(holder) => holder,
+            None => {
+                let control = Address::random_local();
+                let addrs = vec![Address::from_string(&alias), control.clone()];
+                let started =
+                    Forwarder::create(ctx, addrs, control.clone(), forward_route.clone(), payload)
+                        .await;
+                if let Err(e) = started {
+                    warn!(%alias, %e, "Failed to start forwarder");
+                    return reply(ctx, forward_route, Reply::AliasTaken).await;
+                }
+                let mut holder = Holder {
+                    forward_route,
+                    control,
+                    expiry: None,
+                    queue: VecDeque::new(),
+                };
+                self.expire(ctx, &mut holder, &alias, ttl).await?;
+                self.aliases.insert(alias, holder);
+                return Ok(());
+            }
+        };
+
+        let result = if holder.forward_route == forward_route {
+            debug!(%alias, "Refreshing forwarder registration");
+            self.expire(ctx, &mut holder, &alias, ttl).await?;
+            takeover(ctx, &mut holder, forward_route, payload).await
+        } else {
+            match policy {
+                TakeoverPolicy::Fail => {
+                    info!(%alias, "Rejecting registration of a taken alias");
+                    reply(ctx, forward_route, Reply::AliasTaken).await
+                }
+                TakeoverPolicy::Replace => {
+                    info!(%alias, to = %forward_route, "Replacing forwarder registration");
+                    // The replaced registrant must not take the alias back
+                    // with its next refresh.
+                    reply(ctx, holder.forward_route.clone(), Reply::AliasTaken).await?;
+                    takeover(ctx, &mut holder, forward_route, payload).await?;
+                    self.expire(ctx, &mut holder, &alias, ttl).await
+                }
+                TakeoverPolicy::Queue => {
+                    info!(%alias, "Queueing registration of a taken alias");
+                    let pending = Pending {
+                        forward_route: forward_route.clone(),
+                        payload,
+                        ttl,
+                    };
+                    // Refreshing a queued registration keeps its place.
+                    match holder
+                        .queue
+                        .iter_mut()
+                        .find(|p| p.forward_route == forward_route)
+                    {
+                        Some(p) => *p = pending,
+                        None => holder.queue.push_back(pending),
+                    }
+                    reply(ctx, forward_route, Reply::Queued).await
+                }
+            }
+        };
+        self.aliases.insert(alias, holder);
+        result
+    }
+
+    async fn release(&mut self, ctx: &Context, forward_route: Route, alias: String) -> Result<()> {
+        let mut holder = match self.aliases.remove(&alias) {
+            Some(holder) => holder,
+            None => return Ok(()),
+        };
+        if holder.forward_route != forward_route {
+            holder.queue.retain(|p| p.forward_route != forward_route);
+            self.aliases.insert(alias, holder);
+            return Ok(());
+        }
+        match holder.queue.pop_front() {
+            Some(next) => {
+                info!(%alias, to = %next.forward_route, "Handing over a released alias");
+                takeover(ctx, &mut holder, next.forward_route, next.payload).await?;
+                self.expire(ctx, &mut holder, &alias, next.ttl).await?;
+                self.aliases.insert(alias, holder);
+                Ok(())
+            }
+            None => {
+                info!(%alias, "Alias released");
+                ctx.stop_worker(Address::from_string(&alias)).await
+            }
+        }
+    }
+}
+
+/// Make the forwarder of an alias forward to another route.
+async fn takeover(
+    ctx: &Context,
+    holder: &mut Holder,
+    forward_route: Route,
+    payload: Vec<u8>,
+) -> Result<()> {
+    holder.forward_route = forward_route.clone();
+    let msg = Takeover {
+        forward_route,
+        payload,
+    };
+    ctx.send(holder.control.clone(), msg).await
+}
+
+async fn reply(ctx: &Context, forward_route: Route, reply: Reply) -> Result<()> {
+    ctx.send(forward_route, reply.as_str().to_string()).await
 }
 
 #[crate::worker]
@@ -26,21 +402,81 @@ impl Worker for ForwardingService {
     type Context = Context;
     type Message = Any;
 
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        // Static forwarders don't outlive the service they are registered at
+        for alias in core::mem::take(&mut self.aliases).into_keys() {
+            let _ = ctx.stop_worker(Address::from_string(&alias)).await;
+        }
+        Ok(())
+    }
+
     async fn handle_message(
         &mut self,
         ctx: &mut Self::Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
+        if msg.msg_addr() == self.expiry {
+            let expired = Expired::decode(msg.payload())?;
+            let current = self.aliases.get(&expired.alias);
+            if current.map_or(false, |h| h.forward_route == expired.forward_route) {
+                info!(alias = %expired.alias, "Forwarder registration expired");
+                return self
+                    .release(ctx, expired.forward_route, expired.alias)
+                    .await;
+            }
+            return Ok(());
+        }
+
         let forward_route = msg.return_route();
         let payload = msg.into_transport_message().payload;
-        Forwarder::create(ctx, forward_route, payload).await?;
 
-        Ok(())
+        let registration = match String::decode(&payload) {
+            Ok(s) if s == EPHEMERAL => None,
+            Ok(s) => match Registration::parse(&s) {
+                Some(r) => Some(r),
+                None => {
+                    warn!(registration = %s, "Ignoring invalid forwarder registration");
+                    return Ok(());
+                }
+            },
+            Err(_) => {
+                warn!("Ignoring undecodable forwarder registration");
+                return Ok(());
+            }
+        };
+
+        match registration {
+            None => {
+                let control = Address::random_local();
+                let addrs = vec![Address::random_local(), control.clone()];
+                Forwarder::create(ctx, addrs, control, forward_route, payload).await
+            }
+            Some(Registration {
+                alias,
+                action: Action::Register { policy, ttl },
+            }) => {
+                self.register(ctx, forward_route, alias, (policy, ttl), payload)
+                    .await
+            }
+            Some(Registration {
+                alias,
+                action: Action::Release,
+            }) => self.release(ctx, forward_route, alias).await,
+        }
     }
 }
 
+/// Sent by the forwarding service to make a forwarder forward to another
+/// route
+#[derive(Serialize, Deserialize, Message)]
+struct Takeover {
+    forward_route: Route,
+    payload: Vec<u8>,
+}
+
 struct Forwarder {
     forward_route: Route,
+    control: Address,
     // this option will be `None` after this worker is initialized, because
     // while initializing, the worker will send the payload contained in this
     // field to the `forward_route`, to indicate a successful connection
@@ -50,30 +486,28 @@ struct Forwarder {
 impl Forwarder {
     async fn create(
         ctx: &Context,
+        addresses: Vec<Address>,
+        control: Address,
         forward_route: Route,
         registration_payload: Vec<u8>,
     ) -> Result<()> {
-        let random_address = Address::random_local();
-
-        // TODO: assume that the first byte is length, ignore it.
-        // We have to improve this actually parse the payload.
-        let address = match registration_payload.get(1..) {
-            Some(address) => match from_utf8(address) {
-                Ok(v) => Address::from_string(v),
-                Err(_e) => random_address,
-            },
-            None => random_address,
-        };
         info!("Created new alias for {}", forward_route);
 
         let forwarder = Self {
             forward_route,
-            payload: Some(registration_payload.clone()),
+            control,
+            payload: Some(registration_payload),
         };
-        ctx.start_worker(address, forwarder).await?;
+        ctx.start_worker(addresses, forwarder).await?;
 
         Ok(())
     }
+
+    /// Confirm the registration to the registrant.
+    async fn confirm(&self, ctx: &Context, payload: Vec<u8>) -> Result<()> {
+        let msg = TransportMessage::v1(self.forward_route.clone(), ctx.address(), payload);
+        ctx.forward(LocalMessage::new(msg, Vec::new())).await
+    }
 }
 
 #[crate::worker]
@@ -86,10 +520,7 @@ impl Worker for Forwarder {
             .payload
             .take()
             .expect("payload must be available on init");
-        let msg = TransportMessage::v1(self.forward_route.clone(), ctx.address(), payload);
-
-        ctx.forward(LocalMessage::new(msg, Vec::new())).await?;
-        Ok(())
+        self.confirm(ctx, payload).await
     }
 
     async fn handle_message(
@@ -97,6 +528,14 @@ impl Worker for Forwarder {
         ctx: &mut Self::Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
+        if msg.msg_addr() == self.control {
+            let payload = msg.into_transport_message().payload;
+            let takeover = Takeover::decode(&payload)?;
+            debug!(to = %takeover.forward_route, "Forwarder taken over");
+            self.forward_route = takeover.forward_route;
+            return self.confirm(ctx, takeover.payload).await;
+        }
+
         let mut message = msg.into_local_message();
         let transport_message = message.transport_mut();
 
@@ -112,3 +551,35 @@ impl Worker for Forwarder {
         ctx.forward(message).await
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn registrations_roundtrip() {
+        for r in [
+            Registration::register("alias", TakeoverPolicy::Fail),
+            Registration::register("alias", TakeoverPolicy::Replace),
+            Registration::register("alias", TakeoverPolicy::Queue),
+            Registration::register("alias", TakeoverPolicy::Queue)
+                .with_ttl(Duration::from_secs(30)),
+            Registration::release("alias"),
+        ] {
+            assert_eq!(Registration::parse(&r.payload()), Some(r))
+        }
+        assert_eq!(
+            Registration::register("a", TakeoverPolicy::Fail).payload(),
+            "a"
+        );
+        assert_eq!(
+            Registration::register("a", TakeoverPolicy::Fail)
+                .with_ttl(Duration::from_millis(1500))
+                .payload(),
+            "a;ttl=2"
+        );
+        assert_eq!(Registration::parse("a;takeover=steal"), None);
+        assert_eq!(Registration::parse("a;ttl=0"), None);
+        assert_eq!(Registration::parse(";release"), None);
+    }
+}
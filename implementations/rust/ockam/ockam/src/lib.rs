@@ -38,7 +38,7 @@ mod system;
 mod unique;
 
 pub use error::OckamError;
-pub use forwarder::ForwardingService;
+pub use forwarder::{ForwardingService, TakeoverPolicy};
 pub use metadata::OckamMessage;
 pub use system::{SystemBuilder, SystemHandler, WorkerSystem};
 pub use unique::unique_with_prefix;
@@ -97,6 +97,8 @@ pub mod compat {
 pub mod key_exchange {
     //! Module containing types required for key exchange.
     pub use ockam_key_exchange_core::NewKeyExchanger;
+    #[cfg(feature = "pq-hybrid")]
+    pub use ockam_key_exchange_xx::HybridNewKeyExchanger;
     #[cfg(feature = "noise_xx")]
     pub use ockam_key_exchange_xx::XXNewKeyExchanger;
 }
@@ -126,5 +128,10 @@ pub use ockam_transport_tcp::{TcpTransport, TCP};
 #[cfg(feature = "ockam_transport_tcp")]
 /// Tcp
 pub mod tcp {
-    pub use ockam_transport_tcp::{InletOptions, OutletOptions};
+    pub use ockam_transport_tcp::{
+        InletOptions, OutletOptions, PortalCompression, PortalMessage, PortalTraffic, RateLimit,
+        TcpConnectionInfo, TcpConnectionMode, TcpListenerInfo, MAX_MESSAGE_SIZE,
+    };
+    #[cfg(feature = "tls")]
+    pub use ockam_transport_tcp::{InletTls, OutletTls};
 }
@@ -1,9 +1,12 @@
 //! Registration with Ockam Hub, and forwarding to local workers.
 #![deny(missing_docs)]
 
+use crate::forwarder::{Action, Registration, Reply, TakeoverPolicy};
 use crate::{Context, Message, OckamError};
 use core::time::Duration;
 use ockam_core::compat::rand::random;
+#[cfg(feature = "std")]
+use ockam_core::compat::sync::{Arc, Mutex};
 use ockam_core::compat::{
     boxed::Box,
     string::{String, ToString},
@@ -22,6 +25,7 @@ pub struct RemoteForwarderInfo {
     forwarding_route: Route,
     remote_address: String,
     worker_address: Address,
+    queued: bool,
 }
 
 impl RemoteForwarderInfo {
@@ -37,6 +41,104 @@ impl RemoteForwarderInfo {
     pub fn worker_address(&self) -> &Address {
         &self.worker_address
     }
+    /// Whether the alias is held by another registrant, and messages are
+    /// only forwarded once it is released.
+    pub fn is_queued(&self) -> bool {
+        self.queued
+    }
+}
+
+/// When the registration of a static RemoteForwarder with a TTL expires
+///
+/// The RemoteForwarder sets it every time the forwarding service confirms
+/// its registration, so that its creator can tell how long the alias is
+/// held without further heartbeats.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct RegistrationExpiry(Arc<Mutex<Option<std::time::Instant>>>);
+
+#[cfg(feature = "std")]
+impl RegistrationExpiry {
+    /// An expiry which is not known until the registration is confirmed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// The time left until the registration expires, once confirmed.
+    pub fn remaining(&self) -> Option<Duration> {
+        let at = (*self.0.lock().unwrap())?;
+        Some(at.saturating_duration_since(std::time::Instant::now()))
+    }
+    fn refresh(&self, ttl: Duration) {
+        *self.0.lock().unwrap() = Some(std::time::Instant::now() + ttl)
+    }
+}
+
+/// Options of static RemoteForwarders at forwarding services of rust nodes
+#[derive(Debug, Clone, Default)]
+pub struct RemoteForwarderOptions {
+    takeover: TakeoverPolicy,
+    heartbeat_interval: Option<Duration>,
+    ttl: Option<Duration>,
+    #[cfg(feature = "std")]
+    expiry: Option<RegistrationExpiry>,
+}
+
+impl RemoteForwarderOptions {
+    /// Options without heartbeats and TTL, failing when the alias is taken.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// What happens when another RemoteForwarder holds the alias.
+    pub fn with_takeover(mut self, policy: TakeoverPolicy) -> Self {
+        self.takeover = policy;
+        self
+    }
+    /// Refresh the registration at this interval.
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval);
+        self
+    }
+    /// Let the forwarding service release the alias when the registration
+    /// is not refreshed within this time. The TTL is rounded up to whole
+    /// seconds.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+    /// Keep the expiry of the registration up to date in `expiry`, when it
+    /// has a TTL.
+    #[cfg(feature = "std")]
+    pub fn with_expiry(mut self, expiry: RegistrationExpiry) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+    /// Returns the takeover policy.
+    pub fn takeover(&self) -> TakeoverPolicy {
+        self.takeover
+    }
+    /// Returns the heartbeat interval.
+    pub fn heartbeat_interval(&self) -> Option<Duration> {
+        self.heartbeat_interval
+    }
+    /// Returns the registration TTL.
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+}
+
+/// What a `RemoteForwarder` tells its creator once registered
+#[derive(Serialize, Deserialize, Message)]
+enum Outcome {
+    Registered(RemoteForwarderInfo),
+    AliasTaken,
+}
+
+/// Wait for a `RemoteForwarder` to be registered.
+async fn registered(ctx: &mut Context) -> Result<RemoteForwarderInfo> {
+    match ctx.receive::<Outcome>().await?.take().body() {
+        Outcome::Registered(info) => Ok(info),
+        Outcome::AliasTaken => Err(OckamError::AliasTaken.into()),
+    }
 }
 
 /// All addresses `RemoteForwarder` is registered for
@@ -68,10 +170,17 @@ pub struct RemoteForwarder {
     addresses: Addresses,
     registration_route: Route,
     registration_payload: String,
+    // The alias to release when stopped, for forwarding services with
+    // takeover policies.
+    alias: Option<String>,
     callback_address: Option<Address>,
     // We only use Heartbeat for static RemoteForwarder
     heartbeat: Option<DelayedEvent<Vec<u8>>>,
     heartbeat_interval: Duration,
+    // The TTL of the registration, which only heartbeats refresh
+    ttl: Option<Duration>,
+    #[cfg(feature = "std")]
+    expiry: Option<RegistrationExpiry>,
 }
 
 impl RemoteForwarder {
@@ -87,9 +196,13 @@ impl RemoteForwarder {
             addresses,
             registration_route,
             registration_payload,
+            alias: None,
             callback_address: Some(callback_address),
             heartbeat,
             heartbeat_interval,
+            ttl: None,
+            #[cfg(feature = "std")]
+            expiry: None,
         }
     }
 
@@ -127,13 +240,7 @@ impl RemoteForwarder {
         );
         ctx.start_worker(addresses.into_set(), forwarder).await?;
 
-        let resp = child_ctx
-            .receive::<RemoteForwarderInfo>()
-            .await?
-            .take()
-            .body();
-
-        Ok(resp)
+        registered(&mut child_ctx).await
     }
 
     /// Create and start new ephemeral RemoteForwarder at random address with given Ockam Hub route
@@ -164,13 +271,7 @@ impl RemoteForwarder {
         );
         ctx.start_worker(addresses.main_address, forwarder).await?;
 
-        let resp = child_ctx
-            .receive::<RemoteForwarderInfo>()
-            .await?
-            .take()
-            .body();
-
-        Ok(resp)
+        registered(&mut child_ctx).await
     }
 
     /// Create and start new static RemoteForwarder without heart beats
@@ -182,6 +283,32 @@ impl RemoteForwarder {
         hub_route: impl Into<Route>,
         alias: impl Into<String>,
     ) -> Result<RemoteForwarderInfo> {
+        Self::create_static_with_options(ctx, hub_route, alias, RemoteForwarderOptions::new()).await
+    }
+
+    /// Create and start new static RemoteForwarder at a forwarding service
+    /// of a rust node
+    ///
+    /// The takeover policy of the options decides what happens when another
+    /// RemoteForwarder holds the alias. With [`TakeoverPolicy::Fail`] an
+    /// error of kind `AlreadyExists` is returned, and with
+    /// [`TakeoverPolicy::Queue`] the returned info is
+    /// [queued](RemoteForwarderInfo::is_queued). The alias is released when
+    /// the RemoteForwarder stops, or when it has a TTL and sends no
+    /// heartbeat in time. The heartbeat interval must be shorter than the
+    /// TTL.
+    pub async fn create_static_with_options(
+        ctx: &Context,
+        hub_route: impl Into<Route>,
+        alias: impl Into<String>,
+        options: RemoteForwarderOptions,
+    ) -> Result<RemoteForwarderInfo> {
+        if let (Some(interval), Some(ttl)) = (options.heartbeat_interval, options.ttl) {
+            if interval >= ttl {
+                return Err(OckamError::InvalidParameter.into());
+            }
+        }
+        let alias = alias.into();
         let address: Address = random();
         let mut child_ctx = ctx.new_detached(address).await?;
 
@@ -193,29 +320,90 @@ impl RemoteForwarder {
             .append("forwarding_service")
             .into();
 
+        let mut registration = Registration::register(alias.clone(), options.takeover);
+        if let Some(ttl) = options.ttl {
+            registration = registration.with_ttl(ttl)
+        }
+        let ttl = match registration.action {
+            Action::Register { ttl, .. } => ttl.map(Duration::from_secs),
+            Action::Release => None,
+        };
+
+        let heartbeat = match options.heartbeat_interval {
+            Some(_) => {
+                let heartbeat =
+                    DelayedEvent::create(ctx, addresses.heartbeat_address.clone(), vec![]).await?;
+                Some(heartbeat)
+            }
+            None => None,
+        };
         // let remote_address = Address::random_local().without_type().to_string();
-        let forwarder = Self::new(
+        let mut forwarder = Self::new(
             addresses.clone(),
             registration_route,
-            alias.into(),
+            registration.payload(),
             child_ctx.address(),
-            None,
-            Duration::from_secs(10),
+            heartbeat,
+            options
+                .heartbeat_interval
+                .unwrap_or(Duration::from_secs(10)),
         );
+        forwarder.alias = Some(alias);
+        forwarder.ttl = ttl;
+        #[cfg(feature = "std")]
+        {
+            forwarder.expiry = options.expiry;
+        }
 
         debug!(
-            "Starting static RemoteForwarder without heartbeats at {}",
-            &addresses.main_address
+            heartbeats = options.heartbeat_interval.is_some(),
+            "Starting static RemoteForwarder at {}", &addresses.main_address
         );
-        ctx.start_worker(addresses.main_address, forwarder).await?;
+        ctx.start_worker(addresses.into_set(), forwarder).await?;
 
-        let resp = child_ctx
-            .receive::<RemoteForwarderInfo>()
-            .await?
-            .take()
-            .body();
+        registered(&mut child_ctx).await
+    }
+}
 
-        Ok(resp)
+impl RemoteForwarder {
+    /// Handle a registration response which does not confirm the registration.
+    async fn rejected(&mut self, ctx: &Context, msg: &Routed<Any>, payload: &str) -> Result<()> {
+        let (alias, reply) = match (&self.alias, Reply::parse(payload)) {
+            (Some(alias), Some(reply)) => (alias.clone(), reply),
+            _ => return Err(OckamError::InvalidHubResponse.into()),
+        };
+        match reply {
+            Reply::Queued => {
+                info!(%alias, "RemoteForwarder registration queued");
+                if let Some(callback_address) = self.callback_address.take() {
+                    let forwarding_route = msg
+                        .return_route()
+                        .modify()
+                        .pop_back()
+                        .append(alias.as_str())
+                        .into();
+                    let info = RemoteForwarderInfo {
+                        forwarding_route,
+                        remote_address: alias,
+                        worker_address: ctx.address(),
+                        queued: true,
+                    };
+                    ctx.send(callback_address, Outcome::Registered(info))
+                        .await?;
+                }
+                Ok(())
+            }
+            Reply::AliasTaken => {
+                info!(%alias, "RemoteForwarder alias is taken");
+                // There is nothing to release, and nothing to refresh.
+                self.alias = None;
+                self.heartbeat = None;
+                if let Some(callback_address) = self.callback_address.take() {
+                    ctx.send(callback_address, Outcome::AliasTaken).await?;
+                }
+                ctx.stop_worker(self.addresses.main_address.clone()).await
+            }
+        }
     }
 }
 
@@ -237,6 +425,19 @@ impl Worker for RemoteForwarder {
         Ok(())
     }
 
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        if let Some(alias) = self.alias.take() {
+            debug!(%alias, "RemoteForwarder releasing its alias");
+            ctx.send_from_address(
+                self.registration_route.clone(),
+                Registration::release(alias).payload(),
+                self.addresses.main_address.clone(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
     async fn handle_message(
         &mut self,
         ctx: &mut Context,
@@ -266,7 +467,12 @@ impl Worker for RemoteForwarder {
                 Vec::<u8>::decode(msg.payload()).map_err(|_| OckamError::InvalidHubResponse)?;
             let payload = String::from_utf8(payload).map_err(|_| OckamError::InvalidHubResponse)?;
             if payload != self.registration_payload {
-                return Err(OckamError::InvalidHubResponse.into());
+                return self.rejected(ctx, &msg, &payload).await;
+            }
+
+            #[cfg(feature = "std")]
+            if let (Some(expiry), Some(ttl)) = (&self.expiry, self.ttl) {
+                expiry.refresh(ttl)
             }
 
             if let Some(callback_address) = self.callback_address.take() {
@@ -278,15 +484,16 @@ impl Worker for RemoteForwarder {
                     None => return Err(OckamError::InvalidHubResponse.into()),
                 };
 
-                ctx.send(
-                    callback_address,
-                    RemoteForwarderInfo {
-                        forwarding_route: route,
-                        remote_address: address,
-                        worker_address: ctx.address(),
-                    },
-                )
-                .await?;
+                let info = RemoteForwarderInfo {
+                    forwarding_route: route,
+                    remote_address: address,
+                    worker_address: ctx.address(),
+                    queued: false,
+                };
+                ctx.send(callback_address, Outcome::Registered(info))
+                    .await?;
+            } else {
+                debug!("RemoteForwarder registration confirmed");
             }
 
             if let Some(heartbeat) = &mut self.heartbeat {
@@ -305,9 +512,12 @@ impl Worker for RemoteForwarder {
             ctx.forward(message).await?;
 
             // We received message from the other node, our registration is still alive, let's reset
-            // heartbeat timer
-            if let Some(heartbeat) = &mut self.heartbeat {
-                heartbeat.schedule(self.heartbeat_interval).await?;
+            // heartbeat timer. Forwarding services only refresh registrations with a TTL on
+            // heartbeats though, so these are sent regardless of traffic.
+            if self.ttl.is_none() {
+                if let Some(heartbeat) = &mut self.heartbeat {
+                    heartbeat.schedule(self.heartbeat_interval).await?;
+                }
             }
         }
 
@@ -319,7 +529,10 @@ impl Worker for RemoteForwarder {
 mod test {
     use super::*;
     use crate::workers::Echoer;
-    use ockam_core::route;
+    use crate::ForwardingService;
+    use ockam_core::compat::sync::Arc;
+    use ockam_core::errcode::Kind;
+    use ockam_core::{route, DenyAll};
     use ockam_transport_tcp::{TcpTransport, TCP};
     use std::env;
 
@@ -392,4 +605,154 @@ mod test {
 
         ctx.stop().await
     }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__taken_alias__should_follow_takeover_policy(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        ctx.start_worker("echoer", Echoer).await?;
+        ForwardingService::create(ctx).await?;
+
+        let first = RemoteForwarder::create_static_with_options(
+            ctx,
+            route![],
+            "alias",
+            RemoteForwarderOptions::new().with_takeover(TakeoverPolicy::Fail),
+        )
+        .await?;
+        assert!(!first.is_queued());
+        assert_eq!(first.remote_address(), "alias");
+
+        let taken = RemoteForwarder::create_static_with_options(
+            ctx,
+            route![],
+            "alias",
+            RemoteForwarderOptions::new().with_takeover(TakeoverPolicy::Fail),
+        )
+        .await;
+        assert_eq!(taken.unwrap_err().code().kind, Kind::AlreadyExists);
+
+        let queued = RemoteForwarder::create_static_with_options(
+            ctx,
+            route![],
+            "alias",
+            RemoteForwarderOptions::new().with_takeover(TakeoverPolicy::Queue),
+        )
+        .await?;
+        assert!(queued.is_queued());
+
+        let replaced = RemoteForwarder::create_static_with_options(
+            ctx,
+            route![],
+            "alias",
+            RemoteForwarderOptions::new().with_takeover(TakeoverPolicy::Replace),
+        )
+        .await?;
+        assert!(!replaced.is_queued());
+
+        let resp = ctx
+            .send_and_receive::<_, _, String>(route!["alias", "echoer"], "Hello".to_string())
+            .await?;
+        assert_eq!(resp, "Hello");
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__registration_ttl__should_expire_without_heartbeats(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        ctx.start_worker("echoer", Echoer).await?;
+        ForwardingService::create(ctx).await?;
+
+        let invalid = RemoteForwarderOptions::new()
+            .with_heartbeat_interval(Duration::from_secs(2))
+            .with_ttl(Duration::from_secs(1));
+        assert!(
+            RemoteForwarder::create_static_with_options(ctx, route![], "kept", invalid)
+                .await
+                .is_err()
+        );
+
+        let refreshed = RemoteForwarderOptions::new()
+            .with_heartbeat_interval(Duration::from_millis(300))
+            .with_ttl(Duration::from_secs(1));
+        RemoteForwarder::create_static_with_options(ctx, route![], "kept", refreshed).await?;
+        let expiring = RemoteForwarderOptions::new().with_ttl(Duration::from_secs(1));
+        RemoteForwarder::create_static_with_options(ctx, route![], "expiring", expiring).await?;
+
+        ctx.sleep(Duration::from_millis(1500)).await;
+
+        let resp = ctx
+            .send_and_receive::<_, _, String>(route!["kept", "echoer"], "Hello".to_string())
+            .await?;
+        assert_eq!(resp, "Hello");
+
+        // The alias was released, so it can be registered again.
+        let info = RemoteForwarder::create_static_with_options(
+            ctx,
+            route![],
+            "expiring",
+            Default::default(),
+        )
+        .await?;
+        assert!(!info.is_queued());
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__registration_ttl__should_be_refreshed_while_busy(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        ctx.start_worker("echoer", Echoer).await?;
+        ForwardingService::create(ctx).await?;
+
+        let expiry = RegistrationExpiry::new();
+        let options = RemoteForwarderOptions::new()
+            .with_heartbeat_interval(Duration::from_millis(300))
+            .with_ttl(Duration::from_secs(1))
+            .with_expiry(expiry.clone());
+        RemoteForwarder::create_static_with_options(ctx, route![], "busy", options).await?;
+        assert!(expiry.remaining().unwrap() <= Duration::from_secs(1));
+
+        // Traffic doesn't delay the heartbeats refreshing the registration
+        for _ in 0..15 {
+            let resp = ctx
+                .send_and_receive::<_, _, String>(route!["busy", "echoer"], "Hello".to_string())
+                .await?;
+            assert_eq!(resp, "Hello");
+            ctx.sleep(Duration::from_millis(100)).await;
+        }
+        assert!(expiry.remaining().unwrap() > Duration::ZERO);
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn forwarding__access_control__should_guard_registrations(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        ForwardingService::create_with_access_control(ctx, "denying", Arc::new(DenyAll)).await?;
+        ctx.send(route!["denying"], "denied".to_string()).await?;
+
+        ForwardingService::create(ctx).await?;
+        RemoteForwarder::create_static_without_heartbeats(ctx, route![], "allowed").await?;
+
+        ctx.sleep(Duration::from_millis(200)).await;
+        let workers = ctx.list_workers().await?;
+        assert!(!workers.contains(&"denied".into()));
+        assert!(workers.contains(&"allowed".into()));
+
+        // Static forwarders are stopped along with their service
+        ctx.stop_worker("forwarding_service").await?;
+        ctx.sleep(Duration::from_millis(200)).await;
+        assert!(!ctx.list_workers().await?.contains(&"allowed".into()));
+
+        ctx.stop().await
+    }
 }
@@ -0,0 +1,100 @@
+//! Cost of a policy check, interpreted and compiled.
+//!
+//! Policies are checked for every message a guarded worker receives, so a
+//! node handling 10k messages per second has 100µs per check, including
+//! the lookup of the policy in its storage. Whether the compiled checks fit
+//! in that budget is what this measures, with:
+//!
+//! ```sh
+//! cargo bench -p ockam_abac --bench policy
+//! ```
+//!
+//! and comparing the `check/compiled` times against 100µs.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ockam_abac::mem::Memory;
+use ockam_abac::{
+    eq, gt, int, lt, not, policy, string, AbacPolicyStorage, Action, CompiledPolicy, Conditional,
+    Resource, Subject,
+};
+use ockam_core::compat::future::poll_once;
+
+/// A policy of `n` alternatives, each comparing three attributes, which
+/// only the last alternative satisfies.
+fn condition(n: usize) -> Conditional {
+    let alternatives = (0..n)
+        .map(|i| {
+            eq("role", string(format!("role-{}", i)))
+                .all(vec![gt("level", int(2)), not(lt("age", int(18)))])
+        })
+        .collect();
+    Conditional::Or(alternatives)
+}
+
+fn subject(n: usize) -> Subject {
+    Subject::from(1).with_attributes([
+        ("role".into(), string(format!("role-{}", n - 1))),
+        ("level".into(), int(3)),
+        ("age".into(), int(30)),
+    ])
+}
+
+fn evaluate(c: &mut Criterion) {
+    let (resource, action) = (
+        Resource::from("/service/echo"),
+        Action::from("handle_message"),
+    );
+    let mut group = c.benchmark_group("evaluate");
+    group.throughput(Throughput::Elements(1));
+    for n in [1, 10, 100] {
+        let condition = condition(n);
+        let compiled = CompiledPolicy::new(&condition);
+        let subject = subject(n);
+        group.bench_with_input(BenchmarkId::new("interpreted", n), &n, |b, _| {
+            b.iter(|| assert!(condition.evaluate(&subject, &resource, &action)))
+        });
+        group.bench_with_input(BenchmarkId::new("compiled", n), &n, |b, _| {
+            b.iter(|| assert!(compiled.evaluate(&subject)))
+        });
+    }
+    group.finish();
+}
+
+/// A check as done by `PolicyAccessControl`: the policy is read from the
+/// storage, its named policy references resolved, then evaluated.
+fn check(c: &mut Criterion) {
+    let (resource, action) = (
+        Resource::from("/service/echo"),
+        Action::from("handle_message"),
+    );
+    let mut group = c.benchmark_group("check");
+    group.throughput(Throughput::Elements(1));
+    for n in [1, 10, 100] {
+        let storage = Memory::new();
+        poll_once(storage.set_named_policy("alternatives", &condition(n))).unwrap();
+        let p = policy("alternatives").and(&eq("age", int(30)));
+        poll_once(storage.set_policy(resource.clone(), action.clone(), &p)).unwrap();
+        let subject = subject(n);
+        group.bench_with_input(BenchmarkId::new("interpreted", n), &n, |b, _| {
+            b.iter(|| {
+                let p = poll_once(storage.get_policy(&resource, &action))
+                    .unwrap()
+                    .unwrap();
+                let p = poll_once(p.resolve(&storage)).unwrap();
+                assert!(p.evaluate(&subject, &resource, &action))
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("compiled", n), &n, |b, _| {
+            b.iter(|| {
+                let p = poll_once(storage.get_compiled_policy(&resource, &action))
+                    .unwrap()
+                    .unwrap();
+                assert!(p.evaluate(&subject))
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, evaluate, check);
+criterion_main!(benches);
@@ -0,0 +1,112 @@
+//! [`AccessControl`] implementation which evaluates ABAC policies
+//! against the credential attributes of the message sender.
+
+use core::fmt::{self, Debug, Formatter};
+
+use crate::{
+    AbacPolicyStorage, Action, AttributeSchema, Key, PolicyLookup, Resource, Subject, Value,
+};
+use ockam_core::compat::{
+    boxed::Box,
+    string::String,
+    sync::{Arc, RwLock},
+};
+use ockam_core::{async_trait, AccessControl, LocalMessage, Result};
+use ockam_identity::authenticated_storage::AuthenticatedStorage;
+use ockam_identity::credential::AttributesStorageUtils;
+use ockam_identity::IdentitySecureChannelLocalInfo;
+
+/// Allows only messages received over a secure channel whose remote
+/// identity has credential attributes satisfying the policy set for a
+/// given [`Resource`] and [`Action`].
+///
+/// The policy is looked up for every message, so that updating it in the
+/// [`AbacPolicyStorage`] takes effect immediately. It is evaluated in the
+/// compiled form kept by the storage, see
+/// [`AbacPolicyStorage::get_compiled_policy`]. Policies set for
+/// wildcard patterns of the resource path apply too, see [`PolicyLookup`].
+/// Messages are denied if no policy applies, or if its named policy
+/// references can't be resolved.
+///
+/// Credential attribute values are untyped bytes, they are made available
+/// to policies as [`Value::S`] strings, or as values of the type declared
+/// in an [`AttributeSchema`], see [`PolicyAccessControl::with_schema`].
+pub struct PolicyAccessControl<S> {
+    resource: Resource,
+    action: Action,
+    policies: Arc<dyn AbacPolicyStorage>,
+    storage: S,
+    schema: Option<Arc<RwLock<AttributeSchema>>>,
+}
+
+impl<S> Debug for PolicyAccessControl<S> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("PolicyAccessControl")
+            .field("resource", &self.resource)
+            .field("action", &self.action)
+            .finish()
+    }
+}
+
+impl<S: AuthenticatedStorage> PolicyAccessControl<S> {
+    /// Create a new `PolicyAccessControl` for the given [`Resource`] and
+    /// [`Action`], reading credential attributes from `storage`.
+    pub fn new(
+        resource: Resource,
+        action: Action,
+        policies: Arc<dyn AbacPolicyStorage>,
+        storage: S,
+    ) -> Self {
+        Self {
+            resource,
+            action,
+            policies,
+            storage,
+            schema: None,
+        }
+    }
+
+    /// Make credential attributes available to policies as values of their
+    /// declared type. Values which don't parse as their type are left out,
+    /// so conditions on them are not satisfied.
+    pub fn with_schema(mut self, schema: Arc<RwLock<AttributeSchema>>) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+}
+
+#[async_trait]
+impl<S: AuthenticatedStorage> AccessControl for PolicyAccessControl<S> {
+    async fn is_authorized(&self, local_msg: &LocalMessage) -> Result<bool> {
+        let info = match IdentitySecureChannelLocalInfo::find_info(local_msg) {
+            Ok(info) => info,
+            Err(_) => return Ok(false), // Not received over a secure channel
+        };
+
+        // Policies with unknown or cyclic named policies deny everything
+        let lookup = PolicyLookup::new(self.policies.clone());
+        let policy = match lookup.find_compiled(&self.resource, &self.action).await? {
+            Some((_, p)) => p,
+            None => return Ok(false), // No policy to satisfy
+        };
+
+        let id = info.their_identity_id();
+        let attributes = AttributesStorageUtils::get_attributes(id, &self.storage)
+            .await?
+            .unwrap_or_default();
+
+        let schema = self.schema.as_ref().map(|s| s.read().unwrap());
+        let mut subject = Subject::from(id.clone());
+        subject.extend(attributes.into_iter().filter_map(|(k, v)| {
+            let k = Key::from(k.as_str());
+            let v = String::from_utf8(v).ok()?;
+            let v = match &schema {
+                Some(schema) => schema.parse(&k, v)?,
+                None => Value::S(v),
+            };
+            Some((k, v))
+        }));
+
+        Ok(policy.evaluate(&subject))
+    }
+}
@@ -0,0 +1,144 @@
+//! Policies compiled once, to be evaluated for every message.
+
+use crate::{Attributes, Conditional, Key, Subject, Value};
+use ockam_core::compat::vec::Vec;
+
+use alloc::vec;
+
+/// An instruction of a [`CompiledPolicy`].
+#[derive(Debug, Clone)]
+enum Instr {
+    Eq(Key, Value),
+    Lt(Key, Value),
+    Gt(Key, Value),
+    /// Negate the next expression.
+    Not,
+    /// Whether all the expressions of the next `n` instructions are true.
+    All(usize),
+    /// Whether any expression of the next `n` instructions is true.
+    Any(usize),
+    Const(bool),
+}
+
+/// A [`Conditional`] flattened into a list of instructions.
+///
+/// Evaluating it walks a contiguous list instead of a tree of boxed nodes,
+/// and stops as soon as the result of `And` and `Or` is known. It
+/// evaluates like the `Conditional` it was compiled from, whose named
+/// policy references have to be resolved first: remaining references
+/// evaluate to false.
+#[derive(Debug, Clone)]
+pub struct CompiledPolicy {
+    code: Vec<Instr>,
+}
+
+impl CompiledPolicy {
+    /// Compile a [`Conditional`].
+    pub fn new(c: &Conditional) -> Self {
+        let mut code = Vec::new();
+        compile(c, &mut code);
+        Self { code }
+    }
+
+    /// A policy which denies everything, e.g. in place of a policy whose
+    /// references can't be resolved.
+    pub fn deny() -> Self {
+        Self {
+            code: vec![Instr::Const(false)],
+        }
+    }
+
+    /// Evaluate the policy for the attributes of the given [`Subject`].
+    pub fn evaluate(&self, subject: &Subject) -> bool {
+        self.eval(0, subject.attributes()).0
+    }
+
+    /// Evaluate the expression at `i`, returning its value and the index
+    /// of the instruction following it.
+    fn eval(&self, i: usize, attrs: &Attributes) -> (bool, usize) {
+        match &self.code[i] {
+            Instr::Eq(k, v) => (attrs.get(k).map_or(false, |a| a == v), i + 1),
+            Instr::Lt(k, v) => (attrs.get(k).map_or(false, |a| a < v), i + 1),
+            Instr::Gt(k, v) => (attrs.get(k).map_or(false, |a| a > v), i + 1),
+            Instr::Not => {
+                let (b, next) = self.eval(i + 1, attrs);
+                (!b, next)
+            }
+            Instr::All(n) | Instr::Any(n) => {
+                let all = matches!(self.code[i], Instr::All(_));
+                let end = i + 1 + n;
+                let mut j = i + 1;
+                while j < end {
+                    let (b, next) = self.eval(j, attrs);
+                    if b != all {
+                        return (b, end);
+                    }
+                    j = next
+                }
+                (all, end)
+            }
+            Instr::Const(b) => (*b, i + 1),
+        }
+    }
+}
+
+fn compile(c: &Conditional, code: &mut Vec<Instr>) {
+    match c {
+        Conditional::Eq(k, v) => code.push(Instr::Eq(k.clone(), v.clone())),
+        Conditional::Lt(k, v) => code.push(Instr::Lt(k.clone(), v.clone())),
+        Conditional::Gt(k, v) => code.push(Instr::Gt(k.clone(), v.clone())),
+        Conditional::Not(c) => {
+            code.push(Instr::Not);
+            compile(c, code)
+        }
+        Conditional::And(cs) | Conditional::Or(cs) => {
+            let at = code.len();
+            code.push(Instr::Const(false)); // Replaced once the span is known
+            for c in cs {
+                compile(c, code)
+            }
+            let n = code.len() - at - 1;
+            code[at] = match c {
+                Conditional::And(_) => Instr::All(n),
+                _ => Instr::Any(n),
+            }
+        }
+        Conditional::True => code.push(Instr::Const(true)),
+        Conditional::False | Conditional::Policy(_) => code.push(Instr::Const(false)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eq, f, gt, int, lt, not, policy, string, t, Action, Resource};
+
+    #[test]
+    fn compiled_policies_evaluate_like_conditionals() {
+        let subjects = [
+            Subject::from(1),
+            Subject::from(2).with_attributes([("name".into(), string("John"))]),
+            Subject::from(3)
+                .with_attributes([("name".into(), string("Jane")), ("age".into(), int(30))]),
+            Subject::from(4).with_attributes([("age".into(), int(12))]),
+        ];
+        let conditions = [
+            t(),
+            f(),
+            policy("unresolved"),
+            eq("name", string("John")),
+            gt("age", int(17)).or(&eq("name", string("John"))),
+            not(lt("age", int(18))).and(&eq("name", string("Jane"))),
+            t().all(vec![not(f()), Conditional::Or(vec![]), gt("age", int(1))]),
+            Conditional::And(vec![]),
+        ];
+        let (r, a) = (Resource::from("r"), Action::from("a"));
+        for c in &conditions {
+            let compiled = CompiledPolicy::new(c);
+            for s in &subjects {
+                assert_eq!(compiled.evaluate(s), c.evaluate(s, &r, &a), "{:?} {}", c, s);
+            }
+        }
+        assert!(!CompiledPolicy::deny().evaluate(&subjects[2]));
+    }
+}
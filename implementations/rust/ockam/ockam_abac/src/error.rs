@@ -16,6 +16,14 @@ pub enum AbacError {
     Read = 3,
     /// Abac trait storage write error,
     Write = 4,
+    /// A referenced named policy does not exist
+    UnknownPolicy = 5,
+    /// Named policies refer to each other in a cycle
+    PolicyCycle = 6,
+    /// An action group is not one of the `ACTION_GROUPS`
+    UnknownActionGroup = 7,
+    /// An attribute type is not `string`, `int` or `bool`
+    InvalidAttributeType = 8,
 }
 
 impl From<AbacError> for Error {
@@ -26,6 +34,10 @@ impl From<AbacError> for Error {
             InvalidMetadataType => Kind::Invalid,
             Read => Kind::Io,
             Write => Kind::Io,
+            UnknownPolicy => Kind::NotFound,
+            PolicyCycle => Kind::Invalid,
+            UnknownActionGroup => Kind::NotFound,
+            InvalidAttributeType => Kind::Invalid,
         };
 
         Self::new(Origin::Channel, kind, e)
@@ -41,6 +53,10 @@ impl core::fmt::Display for AbacError {
             Self::InvalidMetadataType => "invalid AbacMetadata type".fmt(f),
             Self::Read => "storage read error".fmt(f),
             Self::Write => "storage write error".fmt(f),
+            Self::UnknownPolicy => "unknown named policy".fmt(f),
+            Self::PolicyCycle => "cyclic named policy references".fmt(f),
+            Self::UnknownActionGroup => "unknown action group".fmt(f),
+            Self::InvalidAttributeType => "invalid attribute type".fmt(f),
         }
     }
 }
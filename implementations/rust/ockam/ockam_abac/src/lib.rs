@@ -20,10 +20,18 @@ pub mod error;
 /// An example abac backend
 pub mod mem;
 
+mod access_control;
+mod compiled;
+mod lookup;
 mod policy;
+mod schema;
 mod traits;
 mod types;
 
+pub use access_control::*;
+pub use compiled::*;
+pub use lookup::*;
 pub use policy::*;
+pub use schema::*;
 pub use traits::*;
 pub use types::*;
@@ -0,0 +1,149 @@
+//! Lookup of the policy applying to a resource, including the policies
+//! set for wildcard patterns of its path.
+
+use core::fmt::{self, Debug, Formatter};
+
+use crate::{AbacPolicyStorage, Action, CompiledPolicy, Conditional, Resource};
+use alloc::{format, vec};
+use ockam_core::compat::{string::String, sync::Arc, vec::Vec};
+use ockam_core::Result;
+
+/// Finds the policy applying to a [`Resource`] and [`Action`].
+///
+/// Resource paths are made of `/` separated segments. Besides the policy
+/// set for the exact resource, policies can be set for patterns ending
+/// with a wildcard segment:
+///
+/// - `*` matches a single segment: `/service/*` applies to
+///   `/service/echo` but not to `/service/echo/x`.
+/// - `**` matches one or more segments: `/project/foo/**` applies to
+///   `/project/foo/a` and `/project/foo/a/b`, but not to `/project/foo`.
+///
+/// The most specific policy wins: the one of the exact resource, then the
+/// `*` pattern of its parent, then the `**` patterns of its ancestors,
+/// from the closest to `/**`. Resources which do not start with a `/` only
+/// match exactly.
+#[derive(Clone)]
+pub struct PolicyLookup {
+    policies: Arc<dyn AbacPolicyStorage>,
+}
+
+impl Debug for PolicyLookup {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("PolicyLookup")
+    }
+}
+
+impl PolicyLookup {
+    /// Create a new `PolicyLookup` reading policies from `policies`.
+    pub fn new(policies: Arc<dyn AbacPolicyStorage>) -> Self {
+        Self { policies }
+    }
+
+    /// Return the most specific policy applying to the resource and
+    /// action, along with the resource or pattern it was set for.
+    pub async fn find(&self, r: &Resource, a: &Action) -> Result<Option<(Resource, Conditional)>> {
+        for candidate in Self::candidates(r) {
+            if let Some(c) = self.policies.get_policy(&candidate, a).await? {
+                return Ok(Some((candidate, c)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Like [`PolicyLookup::find`], returning the compiled policy, see
+    /// [`AbacPolicyStorage::get_compiled_policy`].
+    pub async fn find_compiled(
+        &self,
+        r: &Resource,
+        a: &Action,
+    ) -> Result<Option<(Resource, Arc<CompiledPolicy>)>> {
+        for candidate in Self::candidates(r) {
+            if let Some(c) = self.policies.get_compiled_policy(&candidate, a).await? {
+                return Ok(Some((candidate, c)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The resource and the patterns matching it, most specific first.
+    pub fn candidates(r: &Resource) -> Vec<Resource> {
+        let mut candidates = vec![r.clone()];
+        let path = match r.path().strip_prefix('/') {
+            Some(p) if !p.is_empty() => p.trim_end_matches('/'),
+            _ => return candidates,
+        };
+        let segments: Vec<&str> = path.split('/').collect();
+        let parent =
+            |n: usize| -> String { segments[..n].iter().map(|s| format!("/{}", s)).collect() };
+        let n = segments.len();
+        candidates.push(Resource::from(format!("{}/*", parent(n - 1)).as_str()));
+        for k in (0..n).rev() {
+            candidates.push(Resource::from(format!("{}/**", parent(k)).as_str()));
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PolicyLookup;
+    use crate::mem::Memory;
+    use crate::{eq, int, t, AbacPolicyStorage, Action, Conditional, Resource};
+    use ockam_core::compat::future::poll_once;
+    use ockam_core::compat::sync::Arc;
+
+    fn paths(r: &str) -> Vec<String> {
+        PolicyLookup::candidates(&Resource::from(r))
+            .iter()
+            .map(|r| r.path().clone())
+            .collect()
+    }
+
+    #[test]
+    fn candidates_go_from_specific_to_generic() {
+        assert_eq!(
+            paths("/project/foo/bar"),
+            [
+                "/project/foo/bar",
+                "/project/foo/*",
+                "/project/foo/**",
+                "/project/**",
+                "/**"
+            ]
+        );
+        assert_eq!(paths("/api"), ["/api", "/*", "/**"]);
+        assert_eq!(paths("authority.revocation"), ["authority.revocation"]);
+        assert_eq!(paths("/"), ["/"]);
+    }
+
+    #[test]
+    fn most_specific_policy_wins() {
+        let mem = Arc::new(Memory::new());
+        let lookup = PolicyLookup::new(mem.clone());
+        let read = Action::from("r");
+        let echo = Resource::from("/service/echo");
+        let nested = Resource::from("/service/echo/x");
+
+        poll_once(mem.set_policy(Resource::from("/service/**"), read.clone(), &t())).unwrap();
+        let (found, _) = poll_once(lookup.find(&echo, &read)).unwrap().unwrap();
+        assert_eq!(found, Resource::from("/service/**"));
+
+        let is_eq = eq("a", int(1));
+        poll_once(mem.set_policy(Resource::from("/service/*"), read.clone(), &is_eq)).unwrap();
+        let (found, c) = poll_once(lookup.find(&echo, &read)).unwrap().unwrap();
+        assert_eq!(found, Resource::from("/service/*"));
+        assert!(matches!(c, Conditional::Eq(..)));
+
+        // `*` only matches a single segment
+        let (found, _) = poll_once(lookup.find(&nested, &read)).unwrap().unwrap();
+        assert_eq!(found, Resource::from("/service/**"));
+
+        poll_once(mem.set_policy(echo.clone(), read.clone(), &t())).unwrap();
+        let (found, _) = poll_once(lookup.find(&echo, &read)).unwrap().unwrap();
+        assert_eq!(found, echo);
+
+        let other = Resource::from("/outlet/db");
+        assert!(poll_once(lookup.find(&other, &read)).unwrap().is_none());
+    }
+}
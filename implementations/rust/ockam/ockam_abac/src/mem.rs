@@ -4,14 +4,20 @@ use core::fmt::{self, Debug, Formatter};
 
 use super::error::AbacError;
 use super::{
-    AbacAttributeStorage, AbacAuthorization, AbacPolicyStorage, Action, Attributes, Conditional,
-    Identity, Key, Resource, Subject, Value,
+    AbacAttributeStorage, AbacAuthorization, AbacPolicyStorage, Action, Attributes, CompiledPolicy,
+    Conditional, Identity, Key, Resource, Subject, Value,
 };
 use ockam_core::Result;
 use ockam_core::{
     async_trait,
-    compat::{boxed::Box, collections::BTreeMap, sync::Arc, sync::RwLock},
+    compat::{
+        boxed::Box, collections::BTreeMap, string::String, sync::Arc, sync::RwLock, vec::Vec,
+    },
 };
+use serde::{Deserialize, Serialize};
+
+/// Number of previous versions kept for every policy entry
+pub const MAX_POLICY_HISTORY: usize = 16;
 
 /// `Memory` is an in-memory ABAC backend implementation for use by
 /// tests and code examples.
@@ -34,6 +40,35 @@ impl Memory {
             inner: Arc::new(RwLock::new(Inner::new())),
         }
     }
+
+    /// Return the policies of this backend, e.g. to persist them.
+    pub fn policy_snapshot(&self) -> Result<PolicySnapshot> {
+        match self.inner.read() {
+            Ok(mem) => Ok(mem.policy_snapshot()),
+            Err(_) => Err(AbacError::Read.into()),
+        }
+    }
+
+    /// Replace the policies of this backend with those of a snapshot.
+    pub fn restore_policies(&self, snapshot: PolicySnapshot) -> Result<()> {
+        match self.inner.write() {
+            Ok(mut mem) => {
+                mem.restore_policies(snapshot);
+                Ok(())
+            }
+            Err(_) => Err(AbacError::Write.into()),
+        }
+    }
+}
+
+/// The policy entries, their previous versions and the named policy
+/// fragments of a [`Memory`] backend.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicySnapshot {
+    /// policy entries, with their previous versions oldest first
+    entries: Vec<(Resource, Action, Conditional, Vec<Conditional>)>,
+    /// named policy fragments
+    named_policies: BTreeMap<String, Conditional>,
 }
 
 /// `Inner` provides implementations of the [`AbacAttributeStorage`],
@@ -45,6 +80,13 @@ pub struct Inner {
     subjects: BTreeMap<Identity, BTreeMap<Key, Value>>,
     /// policies map a resource to a set of actions subject to conditions
     policies: BTreeMap<Resource, BTreeMap<Action, Conditional>>,
+    /// previous versions of the policies, oldest first
+    history: BTreeMap<Resource, BTreeMap<Action, Vec<Conditional>>>,
+    /// named policy fragments referenced by policies
+    named_policies: BTreeMap<String, Conditional>,
+    /// policies compiled with their references resolved, kept up to date
+    /// as policies and fragments change
+    compiled: BTreeMap<Resource, BTreeMap<Action, Arc<CompiledPolicy>>>,
 }
 
 impl Inner {
@@ -76,6 +118,23 @@ impl Inner {
     /// Implementation for [`AbacPolicyStorage::del_policy`]
     fn del_policy(&mut self, resource: &Resource) {
         self.policies.remove(resource);
+        self.history.remove(resource);
+        self.compiled.remove(resource);
+    }
+
+    /// Implementation for [`AbacPolicyStorage::del_policy_action`]
+    fn del_policy_action(&mut self, resource: &Resource, action: &Action) {
+        fn remove<T>(map: &mut BTreeMap<Resource, BTreeMap<Action, T>>, r: &Resource, a: &Action) {
+            if let Some(actions) = map.get_mut(r) {
+                actions.remove(a);
+                if actions.is_empty() {
+                    map.remove(r);
+                }
+            }
+        }
+        remove(&mut self.policies, resource, action);
+        remove(&mut self.history, resource, action);
+        remove(&mut self.compiled, resource, action);
     }
 
     /// Implementation for [`AbacPolicyStorage::get_policy`]
@@ -88,16 +147,143 @@ impl Inner {
 
     /// Implementation for [`AbacPolicyStorage::set_policy`]
     fn set_policy(&mut self, resource: Resource, action: Action, policy: &Conditional) {
+        self.compile(&resource, &action, policy);
+        let previous = self
+            .policies
+            .entry(resource.clone())
+            .or_insert_with(BTreeMap::new)
+            .insert(action.clone(), policy.clone());
+        if let Some(previous) = previous {
+            let history = self
+                .history
+                .entry(resource)
+                .or_insert_with(BTreeMap::new)
+                .entry(action)
+                .or_insert_with(Vec::new);
+            history.push(previous);
+            if history.len() > MAX_POLICY_HISTORY {
+                history.remove(0);
+            }
+        }
+    }
+
+    /// Implementation for [`AbacPolicyStorage::get_policy_history`]
+    fn get_policy_history(&self, resource: &Resource, action: &Action) -> Vec<Conditional> {
+        self.history
+            .get(resource)
+            .and_then(|h| h.get(action))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Implementation for [`AbacPolicyStorage::rollback_policy`]
+    fn rollback_policy(&mut self, resource: &Resource, action: &Action) -> Option<Conditional> {
+        let previous = self
+            .history
+            .get_mut(resource)
+            .and_then(|h| h.get_mut(action))
+            .and_then(|h| h.pop())?;
         self.policies
-            .entry(resource)
+            .entry(resource.clone())
             .or_insert_with(BTreeMap::new)
-            .insert(action, policy.clone());
+            .insert(action.clone(), previous.clone());
+        self.compile(resource, action, &previous);
+        Some(previous)
+    }
+
+    /// Implementation for [`AbacPolicyStorage::get_named_policy`]
+    fn get_named_policy(&self, name: &str) -> Option<Conditional> {
+        self.named_policies.get(name).cloned()
+    }
+
+    /// Implementation for [`AbacPolicyStorage::set_named_policy`]
+    fn set_named_policy(&mut self, name: &str, policy: &Conditional) {
+        self.named_policies.insert(name.into(), policy.clone());
+        self.compile_all();
+    }
+
+    /// Implementation for [`AbacPolicyStorage::del_named_policy`]
+    fn del_named_policy(&mut self, name: &str) {
+        self.named_policies.remove(name);
+        self.compile_all();
+    }
+
+    /// Implementation for [`AbacPolicyStorage::get_compiled_policy`]
+    fn get_compiled_policy(
+        &self,
+        resource: &Resource,
+        action: &Action,
+    ) -> Option<Arc<CompiledPolicy>> {
+        self.compiled
+            .get(resource)
+            .and_then(|p| p.get(action))
+            .cloned()
+    }
+
+    /// Implementation for [`Memory::policy_snapshot`]
+    fn policy_snapshot(&self) -> PolicySnapshot {
+        let mut entries = Vec::new();
+        for (resource, actions) in &self.policies {
+            for (action, policy) in actions {
+                let history = self.get_policy_history(resource, action);
+                entries.push((resource.clone(), action.clone(), policy.clone(), history));
+            }
+        }
+        PolicySnapshot {
+            entries,
+            named_policies: self.named_policies.clone(),
+        }
+    }
+
+    /// Implementation for [`Memory::restore_policies`]
+    fn restore_policies(&mut self, snapshot: PolicySnapshot) {
+        self.policies.clear();
+        self.history.clear();
+        self.compiled.clear();
+        self.named_policies = snapshot.named_policies;
+        for (resource, action, policy, history) in snapshot.entries {
+            self.set_policy(resource.clone(), action.clone(), &policy);
+            if !history.is_empty() {
+                self.history
+                    .entry(resource)
+                    .or_insert_with(BTreeMap::new)
+                    .insert(action, history);
+            }
+        }
+    }
+
+    /// Compile a policy entry with its references resolved.
+    fn compile(&mut self, resource: &Resource, action: &Action, policy: &Conditional) {
+        let compiled = match policy.expand(&self.named_policies, &mut Vec::new()) {
+            Ok(policy) => CompiledPolicy::new(&policy),
+            Err(_) => CompiledPolicy::deny(),
+        };
+        self.compiled
+            .entry(resource.clone())
+            .or_insert_with(BTreeMap::new)
+            .insert(action.clone(), Arc::new(compiled));
+    }
+
+    /// Compile all policy entries again, after a fragment changed.
+    fn compile_all(&mut self) {
+        let policies = core::mem::take(&mut self.policies);
+        for (resource, actions) in &policies {
+            for (action, policy) in actions {
+                self.compile(resource, action, policy)
+            }
+        }
+        self.policies = policies;
     }
 
     /// Implementation for [`AbacAuthorization::is_authorized`]
     fn is_authorized(&self, subject: &Subject, resource: &Resource, action: &Action) -> bool {
         if let Some(attributes) = self.subjects.get(subject.identifier()) {
             if let Some(policy) = self.get_policy(resource, action) {
+                // Unknown or cyclic references deny access
+                let policy = match policy.expand(&self.named_policies, &mut Vec::new()) {
+                    Ok(policy) => policy,
+                    Err(_) => return false,
+                };
                 let subject = subject.clone().with_attributes(attributes.clone());
                 return policy.evaluate(&subject, resource, action);
             }
@@ -148,6 +334,16 @@ impl AbacPolicyStorage for Memory {
         }
     }
 
+    async fn del_policy_action(&self, resource: &Resource, action: &Action) -> Result<()> {
+        match self.inner.write() {
+            Ok(mut mem) => {
+                mem.del_policy_action(resource, action);
+                Ok(())
+            }
+            Err(_) => Err(AbacError::Write.into()),
+        }
+    }
+
     /// Return the [`Conditional`] policy entry for a given ABAC
     /// [`Resource`] and [`Action`] .
     async fn get_policy(
@@ -175,6 +371,83 @@ impl AbacPolicyStorage for Memory {
             Err(_) => Err(AbacError::Write.into()),
         }
     }
+
+    async fn set_policies(
+        &self,
+        resource: Resource,
+        actions: &[Action],
+        policy: &Conditional,
+    ) -> Result<()> {
+        match self.inner.write() {
+            Ok(mut mem) => {
+                for action in actions {
+                    mem.set_policy(resource.clone(), action.clone(), policy);
+                }
+                Ok(())
+            }
+            Err(_) => Err(AbacError::Write.into()),
+        }
+    }
+
+    async fn get_policy_history(
+        &self,
+        resource: &Resource,
+        action: &Action,
+    ) -> Result<Vec<Conditional>> {
+        match self.inner.read() {
+            Ok(mem) => Ok(mem.get_policy_history(resource, action)),
+            Err(_) => Err(AbacError::Read.into()),
+        }
+    }
+
+    async fn rollback_policy(
+        &self,
+        resource: &Resource,
+        action: &Action,
+    ) -> Result<Option<Conditional>> {
+        match self.inner.write() {
+            Ok(mut mem) => Ok(mem.rollback_policy(resource, action)),
+            Err(_) => Err(AbacError::Write.into()),
+        }
+    }
+
+    async fn get_named_policy(&self, name: &str) -> Result<Option<Conditional>> {
+        match self.inner.read() {
+            Ok(mem) => Ok(mem.get_named_policy(name)),
+            Err(_) => Err(AbacError::Read.into()),
+        }
+    }
+
+    async fn set_named_policy(&self, name: &str, policy: &Conditional) -> Result<()> {
+        match self.inner.write() {
+            Ok(mut mem) => {
+                mem.set_named_policy(name, policy);
+                Ok(())
+            }
+            Err(_) => Err(AbacError::Write.into()),
+        }
+    }
+
+    async fn del_named_policy(&self, name: &str) -> Result<()> {
+        match self.inner.write() {
+            Ok(mut mem) => {
+                mem.del_named_policy(name);
+                Ok(())
+            }
+            Err(_) => Err(AbacError::Write.into()),
+        }
+    }
+
+    async fn get_compiled_policy(
+        &self,
+        resource: &Resource,
+        action: &Action,
+    ) -> Result<Option<Arc<CompiledPolicy>>> {
+        match self.inner.read() {
+            Ok(mem) => Ok(mem.get_compiled_policy(resource, action)),
+            Err(_) => Err(AbacError::Read.into()),
+        }
+    }
 }
 
 #[async_trait]
@@ -194,8 +467,14 @@ impl AbacAuthorization for Memory {
 
 #[cfg(test)]
 mod tests {
-    use crate::mem::Memory;
-    use crate::{eq, gt, int, string, Action, Resource, Subject};
+    use crate::error::AbacError;
+    use crate::mem::{Memory, MAX_POLICY_HISTORY};
+    use crate::{
+        eq, f, gt, int, not, policy, string, t, AbacPolicyStorage, Action, Conditional, Resource,
+        Subject,
+    };
+    use ockam_core::compat::future::poll_once;
+    use ockam_core::Error;
 
     #[test]
     fn example1() {
@@ -244,4 +523,144 @@ mod tests {
             .unwrap()
             .is_authorized(&Subject::from(2), &resource, &read)); // not John and no adult
     }
+
+    #[test]
+    fn named_policies() {
+        let read = Action::from("r");
+        let resource = Resource::from("/foo/bar/baz");
+
+        let mem = Memory::new();
+        {
+            let mut inner = mem.inner.write().unwrap();
+            inner.set_named_policy("adult", &gt("age", int(17)));
+            inner.set_named_policy("admin", &eq("role", string("admin")).and(&policy("adult")));
+            inner.set_policy(resource.clone(), read.clone(), &policy("admin"));
+            inner.set_subject_attributes(
+                Subject::from(1),
+                [("role".into(), string("admin")), ("age".into(), int(25))],
+            );
+            inner.set_subject_attributes(
+                Subject::from(2),
+                [("role".into(), string("admin")), ("age".into(), int(12))],
+            );
+        }
+
+        let inner = mem.inner.read().unwrap();
+        assert!(inner.is_authorized(&Subject::from(1), &resource, &read));
+        assert!(!inner.is_authorized(&Subject::from(2), &resource, &read));
+        drop(inner);
+
+        // A cycle denies access instead of recursing forever
+        mem.inner
+            .write()
+            .unwrap()
+            .set_named_policy("adult", &policy("admin"));
+        let inner = mem.inner.read().unwrap();
+        assert!(!inner.is_authorized(&Subject::from(1), &resource, &read));
+    }
+
+    #[test]
+    fn policy_history() {
+        let read = Action::from("r");
+        let resource = Resource::from("/foo/bar/baz");
+        let mem = Memory::new();
+
+        assert!(poll_once(mem.rollback_policy(&resource, &read))
+            .unwrap()
+            .is_none());
+
+        poll_once(mem.set_policy(resource.clone(), read.clone(), &t())).unwrap();
+        poll_once(mem.set_policy(resource.clone(), read.clone(), &eq("a", int(1)))).unwrap();
+        poll_once(mem.set_policy(resource.clone(), read.clone(), &f())).unwrap();
+        let history = poll_once(mem.get_policy_history(&resource, &read)).unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(matches!(history[0], Conditional::True));
+        assert!(matches!(history[1], Conditional::Eq(..)));
+
+        // A rollback restores the latest previous version
+        let restored = poll_once(mem.rollback_policy(&resource, &read)).unwrap();
+        assert!(matches!(restored, Some(Conditional::Eq(..))));
+        let current = poll_once(mem.get_policy(&resource, &read)).unwrap();
+        assert!(matches!(current, Some(Conditional::Eq(..))));
+        let history = poll_once(mem.get_policy_history(&resource, &read)).unwrap();
+        assert_eq!(history.len(), 1);
+
+        // The history is bounded
+        for _ in 0..2 * MAX_POLICY_HISTORY {
+            poll_once(mem.set_policy(resource.clone(), read.clone(), &t())).unwrap();
+        }
+        let history = poll_once(mem.get_policy_history(&resource, &read)).unwrap();
+        assert_eq!(history.len(), MAX_POLICY_HISTORY);
+
+        poll_once(mem.del_policy(&resource)).unwrap();
+        let history = poll_once(mem.get_policy_history(&resource, &read)).unwrap();
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn policy_actions_are_deleted_alone() {
+        let read = Action::from("r");
+        let write = Action::from("w");
+        let resource = Resource::from("/foo/bar/baz");
+        let mem = Memory::new();
+        poll_once(mem.set_policy(resource.clone(), read.clone(), &t())).unwrap();
+        poll_once(mem.set_policy(resource.clone(), read.clone(), &f())).unwrap();
+        poll_once(mem.set_policy(resource.clone(), write.clone(), &t())).unwrap();
+
+        poll_once(mem.del_policy_action(&resource, &read)).unwrap();
+        assert!(poll_once(mem.get_policy(&resource, &read))
+            .unwrap()
+            .is_none());
+        assert!(poll_once(mem.get_compiled_policy(&resource, &read))
+            .unwrap()
+            .is_none());
+        assert!(poll_once(mem.get_policy_history(&resource, &read))
+            .unwrap()
+            .is_empty());
+        assert!(poll_once(mem.get_policy(&resource, &write))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn policy_snapshots_are_restored() {
+        let read = Action::from("r");
+        let resource = Resource::from("/foo/bar/baz");
+        let mem = Memory::new();
+        poll_once(mem.set_named_policy("a", &t())).unwrap();
+        poll_once(mem.set_policy(resource.clone(), read.clone(), &f())).unwrap();
+        poll_once(mem.set_policy(resource.clone(), read.clone(), &policy("a"))).unwrap();
+
+        let restored = Memory::new();
+        restored
+            .restore_policies(mem.policy_snapshot().unwrap())
+            .unwrap();
+        let current = poll_once(restored.get_policy(&resource, &read)).unwrap();
+        assert!(matches!(current, Some(Conditional::Policy(..))));
+        let history = poll_once(restored.get_policy_history(&resource, &read)).unwrap();
+        assert!(matches!(history[..], [Conditional::False]));
+        let compiled = poll_once(restored.get_compiled_policy(&resource, &read))
+            .unwrap()
+            .unwrap();
+        assert!(compiled.evaluate(&Subject::from(1)));
+    }
+
+    #[test]
+    fn resolve_named_policies() {
+        let mem = Memory::new();
+        let condition = policy("a").or(&policy("b"));
+
+        let missing = poll_once(condition.resolve(&mem)).unwrap_err();
+        assert_eq!(missing.code(), Error::from(AbacError::UnknownPolicy).code());
+
+        poll_once(mem.set_named_policy("a", &t())).unwrap();
+        poll_once(mem.set_named_policy("b", &not(policy("a")))).unwrap();
+        let resolved = poll_once(condition.resolve(&mem)).unwrap();
+        assert!(resolved.references().is_empty());
+        assert!(resolved.evaluate(&Subject::from(1), &Resource::from("r"), &Action::from("a")));
+
+        poll_once(mem.set_named_policy("a", &policy("b"))).unwrap();
+        let cycle = poll_once(condition.resolve(&mem)).unwrap_err();
+        assert_eq!(cycle.code(), Error::from(AbacError::PolicyCycle).code());
+    }
 }
@@ -1,6 +1,8 @@
-use crate::{Action, Key, Resource, Subject, Value};
+use crate::error::AbacError;
+use crate::{AbacPolicyStorage, Action, Key, Resource, Subject, Value};
 
-use ockam_core::compat::{boxed::Box, vec::Vec};
+use ockam_core::compat::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+use ockam_core::Result;
 use serde::{Deserialize, Serialize};
 
 use alloc::vec;
@@ -24,6 +26,9 @@ pub enum Conditional {
     True,
     /// Always false
     False,
+    /// Reference to a named policy fragment, see
+    /// [`AbacPolicyStorage::set_named_policy`]
+    Policy(String),
 }
 
 impl Conditional {
@@ -42,9 +47,82 @@ impl Conditional {
             Conditional::Or(cs) => cs.iter().any(|c| c.evaluate(subject, resource, action)),
             Conditional::True => true,
             Conditional::False => false,
+            // References have to be resolved before evaluation
+            Conditional::Policy(_) => false,
         }
     }
 
+    /// Replace all named policy references with the fragments they refer
+    /// to in the given storage.
+    ///
+    /// Fails if a referenced fragment does not exist, or if fragments
+    /// refer to each other in a cycle.
+    pub async fn resolve<S>(&self, storage: &S) -> Result<Conditional>
+    where
+        S: AbacPolicyStorage + ?Sized,
+    {
+        let mut fragments = BTreeMap::new();
+        let mut pending = self.references();
+        while let Some(name) = pending.pop() {
+            if fragments.contains_key(&name) {
+                continue;
+            }
+            let fragment = storage
+                .get_named_policy(&name)
+                .await?
+                .ok_or(AbacError::UnknownPolicy)?;
+            pending.extend(fragment.references());
+            fragments.insert(name, fragment);
+        }
+        self.expand(&fragments, &mut Vec::new())
+    }
+
+    /// Names of the policy fragments referenced by this `Conditional`.
+    pub fn references(&self) -> Vec<String> {
+        match self {
+            Conditional::Policy(name) => vec![name.clone()],
+            Conditional::Not(c) => c.references(),
+            Conditional::And(cs) | Conditional::Or(cs) => {
+                cs.iter().flat_map(|c| c.references()).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Expand references using the given fragments. `stack` holds the
+    /// names of the fragments being expanded, to detect cycles.
+    pub(crate) fn expand(
+        &self,
+        fragments: &BTreeMap<String, Conditional>,
+        stack: &mut Vec<String>,
+    ) -> Result<Conditional> {
+        let expanded = match self {
+            Conditional::Policy(name) => {
+                if stack.contains(name) {
+                    return Err(AbacError::PolicyCycle.into());
+                }
+                let fragment = fragments.get(name).ok_or(AbacError::UnknownPolicy)?;
+                stack.push(name.clone());
+                let expanded = fragment.expand(fragments, stack)?;
+                stack.pop();
+                expanded
+            }
+            Conditional::Not(c) => Conditional::Not(Box::new(c.expand(fragments, stack)?)),
+            Conditional::And(cs) => Conditional::And(
+                cs.iter()
+                    .map(|c| c.expand(fragments, stack))
+                    .collect::<Result<_>>()?,
+            ),
+            Conditional::Or(cs) => Conditional::Or(
+                cs.iter()
+                    .map(|c| c.expand(fragments, stack))
+                    .collect::<Result<_>>()?,
+            ),
+            other => other.clone(),
+        };
+        Ok(expanded)
+    }
+
     /// Create a new `Conditional::And` with the given `Conditional`.
     pub fn and(&self, other: &Conditional) -> Conditional {
         Conditional::And(vec![self.clone(), other.clone()])
@@ -97,3 +175,8 @@ pub fn t() -> Conditional {
 pub fn f() -> Conditional {
     Conditional::False
 }
+
+/// Create a new [`Conditional::Policy`] reference.
+pub fn policy<N: Into<String>>(name: N) -> Conditional {
+    Conditional::Policy(name.into())
+}
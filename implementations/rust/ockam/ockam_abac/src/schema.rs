@@ -0,0 +1,242 @@
+//! Declared types of subject attributes, which policies are checked
+//! against before they are set.
+
+use core::fmt;
+use core::str::FromStr;
+
+use crate::error::AbacError;
+use crate::{Conditional, Key, Value};
+use ockam_core::compat::{collections::BTreeMap, string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+/// The type of the values of an attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttributeType {
+    /// [`Value::S`] values
+    String,
+    /// [`Value::I`] values
+    Int,
+    /// [`Value::B`] values
+    Bool,
+}
+
+impl AttributeType {
+    /// The type of the given value.
+    pub fn of(v: &Value) -> Self {
+        match v {
+            Value::S(_) => AttributeType::String,
+            Value::I(_) => AttributeType::Int,
+            Value::B(_) => AttributeType::Bool,
+        }
+    }
+
+    /// Parse a credential attribute value into a value of this type.
+    ///
+    /// Integers are written in decimal, and booleans as `true` or `false`.
+    pub fn parse(&self, s: String) -> Option<Value> {
+        match self {
+            AttributeType::String => Some(Value::S(s)),
+            AttributeType::Int => s.parse().ok().map(Value::I),
+            AttributeType::Bool => s.parse().ok().map(Value::B),
+        }
+    }
+}
+
+impl fmt::Display for AttributeType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttributeType::String => "string".fmt(f),
+            AttributeType::Int => "int".fmt(f),
+            AttributeType::Bool => "bool".fmt(f),
+        }
+    }
+}
+
+impl FromStr for AttributeType {
+    type Err = AbacError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "string" => Ok(AttributeType::String),
+            "int" => Ok(AttributeType::Int),
+            "bool" => Ok(AttributeType::Bool),
+            _ => Err(AbacError::InvalidAttributeType),
+        }
+    }
+}
+
+/// A condition of a policy which does not fit an [`AttributeSchema`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaViolation {
+    /// The attribute is not declared
+    UnknownAttribute(Key),
+    /// The attribute is compared to a value of another type
+    TypeMismatch {
+        /// The compared attribute
+        key: Key,
+        /// The declared type of the attribute
+        expected: AttributeType,
+        /// The type of the value it is compared to
+        found: AttributeType,
+    },
+    /// A boolean attribute is compared with `Lt` or `Gt`
+    Unordered(Key),
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaViolation::UnknownAttribute(k) => write!(f, "unknown attribute `{}`", &**k),
+            SchemaViolation::TypeMismatch {
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "attribute `{}` is a {} but is compared to a {}",
+                &**key, expected, found
+            ),
+            SchemaViolation::Unordered(k) => {
+                write!(f, "attribute `{}` is a bool and has no order", &**k)
+            }
+        }
+    }
+}
+
+/// Declared types of the subject attributes policies refer to.
+///
+/// An empty schema accepts every policy. Once an attribute is declared,
+/// policies may only compare declared attributes, to values of their type.
+#[derive(Debug, Clone, Default)]
+pub struct AttributeSchema {
+    types: BTreeMap<Key, AttributeType>,
+}
+
+impl AttributeSchema {
+    /// Create an empty `AttributeSchema`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare the type of an attribute, returning its previous type.
+    pub fn declare<K: Into<Key>>(&mut self, k: K, t: AttributeType) -> Option<AttributeType> {
+        self.types.insert(k.into(), t)
+    }
+
+    /// Remove the declaration of an attribute.
+    pub fn remove(&mut self, k: &Key) -> Option<AttributeType> {
+        self.types.remove(k)
+    }
+
+    /// The declared type of an attribute.
+    pub fn get(&self, k: &Key) -> Option<AttributeType> {
+        self.types.get(k).copied()
+    }
+
+    /// Parse a credential attribute value into a value of its declared
+    /// type. Undeclared attributes are strings, see [`AttributeType::parse`].
+    pub fn parse(&self, k: &Key, s: String) -> Option<Value> {
+        self.get(k).unwrap_or(AttributeType::String).parse(s)
+    }
+
+    /// Whether no attribute is declared.
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// Check a policy against the declared attributes, returning all the
+    /// conditions which do not fit.
+    ///
+    /// Named policy references are not followed, resolve them first with
+    /// [`Conditional::resolve`] to check the fragments too.
+    pub fn check(&self, c: &Conditional) -> Vec<SchemaViolation> {
+        let mut violations = Vec::new();
+        if !self.is_empty() {
+            self.check_into(c, &mut violations)
+        }
+        violations
+    }
+
+    fn check_into(&self, c: &Conditional, violations: &mut Vec<SchemaViolation>) {
+        let (key, value, ordered) = match c {
+            Conditional::Eq(k, v) => (k, v, false),
+            Conditional::Lt(k, v) | Conditional::Gt(k, v) => (k, v, true),
+            Conditional::Not(c) => {
+                self.check_into(c, violations);
+                return;
+            }
+            Conditional::And(cs) | Conditional::Or(cs) => {
+                for c in cs {
+                    self.check_into(c, violations)
+                }
+                return;
+            }
+            Conditional::True | Conditional::False | Conditional::Policy(_) => return,
+        };
+        let found = AttributeType::of(value);
+        let expected = match self.get(key) {
+            Some(t) => t,
+            None => {
+                violations.push(SchemaViolation::UnknownAttribute(key.clone()));
+                return;
+            }
+        };
+        if expected != found {
+            violations.push(SchemaViolation::TypeMismatch {
+                key: key.clone(),
+                expected,
+                found,
+            })
+        } else if ordered && expected == AttributeType::Bool {
+            violations.push(SchemaViolation::Unordered(key.clone()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eq, gt, int, policy, string};
+
+    #[test]
+    fn policies_are_checked_against_declared_attributes() {
+        let mut schema = AttributeSchema::new();
+        let condition = eq("role", int(1)).or(&gt("admin", Value::B(true)));
+        assert!(schema.check(&condition).is_empty());
+
+        schema.declare("role", AttributeType::String);
+        schema.declare("admin", AttributeType::Bool);
+        assert_eq!(
+            schema.check(&condition),
+            [
+                SchemaViolation::TypeMismatch {
+                    key: Key::from("role"),
+                    expected: AttributeType::String,
+                    found: AttributeType::Int,
+                },
+                SchemaViolation::Unordered(Key::from("admin")),
+            ]
+        );
+
+        let condition = eq("role", string("admin")).and(&policy("other"));
+        assert!(schema.check(&condition).is_empty());
+        assert_eq!(
+            schema.check(&eq("rol", string("admin"))),
+            [SchemaViolation::UnknownAttribute(Key::from("rol"))]
+        );
+        assert_eq!("int".parse::<AttributeType>().unwrap(), AttributeType::Int);
+    }
+
+    #[test]
+    fn attribute_values_are_parsed_into_declared_types() {
+        let mut schema = AttributeSchema::new();
+        schema.declare("level", AttributeType::Int);
+        schema.declare("admin", AttributeType::Bool);
+        let parse = |k: &str, v: &str| schema.parse(&Key::from(k), v.into());
+        assert_eq!(parse("level", "3"), Some(int(3)));
+        assert_eq!(parse("level", "three"), None);
+        assert_eq!(parse("admin", "true"), Some(Value::B(true)));
+        assert_eq!(parse("admin", "yes"), None);
+        assert_eq!(parse("role", "3"), Some(string("3")));
+    }
+}
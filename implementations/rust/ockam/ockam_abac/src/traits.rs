@@ -1,8 +1,9 @@
+use crate::compiled::CompiledPolicy;
 use crate::policy::Conditional;
 use crate::types::*;
 
 use ockam_core::Result;
-use ockam_core::{async_trait, compat::boxed::Box};
+use ockam_core::{async_trait, compat::boxed::Box, compat::sync::Arc, compat::vec::Vec};
 
 /// The `AbacAuthorization` trait provides an interface for making an
 /// authorization decision based on a given [`Subject`], [`Resource`],
@@ -28,6 +29,11 @@ pub trait AbacPolicyStorage: Send + Sync + 'static {
     /// [`Resource`].  [`Resource`].
     async fn del_policy(&self, r: &Resource) -> Result<()>;
 
+    /// Delete the policy entry for a given ABAC [`Resource`] and
+    /// [`Action`], with its previous versions, keeping the entries of
+    /// the other actions.
+    async fn del_policy_action(&self, r: &Resource, a: &Action) -> Result<()>;
+
     /// Return the [`Conditional`] policy entry for a given ABAC
     /// [`Resource`] and [`Action`] .
     async fn get_policy(&self, r: &Resource, a: &Action) -> Result<Option<Conditional>>;
@@ -36,8 +42,63 @@ pub trait AbacPolicyStorage: Send + Sync + 'static {
     /// [`Resource`] and [`Action`] .
     ///
     /// Any pre-existing [`Action`] entries associated with the
-    /// [`Resource`] will be replaced.
+    /// [`Resource`] will be replaced. The replaced entry is kept as a
+    /// previous version, see [`AbacPolicyStorage::get_policy_history`].
     async fn set_policy(&self, r: Resource, a: Action, c: &Conditional) -> Result<()>;
+
+    /// Set the same [`Conditional`] policy entry for several [`Action`]s
+    /// of a given ABAC [`Resource`], as [`AbacPolicyStorage::set_policy`].
+    ///
+    /// Either all the entries are set or none is.
+    async fn set_policies(&self, r: Resource, a: &[Action], c: &Conditional) -> Result<()>;
+
+    /// Return the previous versions of the [`Conditional`] policy entry
+    /// for a given ABAC [`Resource`] and [`Action`], oldest first.
+    ///
+    /// The current entry is not part of the history.
+    async fn get_policy_history(&self, r: &Resource, a: &Action) -> Result<Vec<Conditional>>;
+
+    /// Replace the [`Conditional`] policy entry for a given ABAC
+    /// [`Resource`] and [`Action`] with its latest previous version.
+    ///
+    /// Returns the restored entry, or `None` if there is no previous
+    /// version, in which case the current entry is left untouched.
+    async fn rollback_policy(&self, r: &Resource, a: &Action) -> Result<Option<Conditional>>;
+
+    /// Return the named policy fragment, referenced by other policies
+    /// with [`Conditional::Policy`].
+    async fn get_named_policy(&self, name: &str) -> Result<Option<Conditional>>;
+
+    /// Set a named policy fragment, replacing any previous one with the
+    /// same name.
+    async fn set_named_policy(&self, name: &str, c: &Conditional) -> Result<()>;
+
+    /// Delete the named policy fragment.
+    async fn del_named_policy(&self, name: &str) -> Result<()>;
+
+    /// Return the [`CompiledPolicy`] of the [`Conditional`] policy entry
+    /// for a given ABAC [`Resource`] and [`Action`], with its named policy
+    /// references resolved.
+    ///
+    /// An entry whose references can't be resolved compiles to
+    /// [`CompiledPolicy::deny`]. This implementation resolves and compiles
+    /// the entry on every call, storages should rather keep the compiled
+    /// entries up to date as policies are set.
+    async fn get_compiled_policy(
+        &self,
+        r: &Resource,
+        a: &Action,
+    ) -> Result<Option<Arc<CompiledPolicy>>> {
+        let policy = match self.get_policy(r, a).await? {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+        let compiled = match policy.resolve(self).await {
+            Ok(p) => CompiledPolicy::new(&p),
+            Err(_) => CompiledPolicy::deny(),
+        };
+        Ok(Some(Arc::new(compiled)))
+    }
 }
 
 /// The `AbacAttributeStorage` trait provides an interface for the
@@ -1,7 +1,10 @@
+use crate::error::AbacError;
 use ockam_core::compat::{
     collections::BTreeMap,
     string::{String, ToString},
+    vec::Vec,
 };
+use ockam_core::Result;
 use ockam_identity::IdentityIdentifier;
 
 use serde::{Deserialize, Serialize};
@@ -191,6 +194,41 @@ impl Extend<Attribute> for Action {
     }
 }
 
+/// Well-known groups of actions, which a policy can be set for at once.
+///
+/// Groups are named with a leading `@`. Their actions are the ones of the
+/// node API, the lowercase request methods.
+pub const ACTION_GROUPS: &[(&str, &[&str])] = &[
+    ("@read-only", &["get"]),
+    ("@admin", &["get", "post", "put", "delete", "patch"]),
+];
+
+/// Expand action names and [`ACTION_GROUPS`] into the [`Action`]s they
+/// stand for, in order and without duplicates.
+pub fn expand_actions<'a, I>(names: I) -> Result<Vec<Action>>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut actions: Vec<Action> = Vec::new();
+    for name in names {
+        let group: &[&str] = if name.starts_with('@') {
+            match ACTION_GROUPS.iter().find(|(g, _)| *g == name) {
+                Some((_, group)) => group,
+                None => return Err(AbacError::UnknownActionGroup.into()),
+            }
+        } else {
+            core::slice::from_ref(&name)
+        };
+        for a in group {
+            let a = Action::from(*a);
+            if !actions.contains(&a) {
+                actions.push(a)
+            }
+        }
+    }
+    Ok(actions)
+}
+
 /// HTTP verbs
 ///
 /// TODO if we can move ockam_api::Method to ockam or ockam_core we
@@ -279,3 +317,17 @@ pub fn int(n: i64) -> Value {
 pub fn bool(b: bool) -> Value {
     Value::B(b)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_groups_are_expanded() {
+        let actions = expand_actions(["r", "@read-only", "r"]).unwrap();
+        assert_eq!(actions, [Action::from("r"), Action::from("get")]);
+        let actions = expand_actions(["@admin", "get"]).unwrap();
+        assert_eq!(actions.len(), 5);
+        assert!(expand_actions(["@nope"]).is_err());
+    }
+}
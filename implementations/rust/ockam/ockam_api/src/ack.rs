@@ -0,0 +1,88 @@
+use ockam::{Any, Context, Result, Routed, Worker};
+use ockam_core::Message;
+use serde::{Deserialize, Serialize};
+use tracing as log;
+
+/// Sent back by an [`Acker`] once it handed a message over to the next hop
+#[derive(Serialize, Deserialize, Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Acknowledgement {
+    Delivered,
+    NotDelivered,
+}
+
+/// Forwards messages to the rest of their onward route and acknowledges them
+/// to their sender.
+///
+/// Inserted before the last hop of a route, it confirms the delivery to a
+/// worker which doesn't reply. Replies of the worker go to the sender, along
+/// the original return route.
+pub struct Acker;
+
+#[ockam::worker]
+impl Worker for Acker {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let return_route = msg.return_route();
+        let mut local_msg = msg.into_local_message();
+        let transport = local_msg.transport_mut();
+        transport.onward_route.step()?;
+
+        let ack = if transport.onward_route.iter().next().is_none() {
+            log::debug!(to = %return_route, "nothing to forward to");
+            Acknowledgement::NotDelivered
+        } else {
+            match ctx.forward(local_msg).await {
+                Ok(()) => Acknowledgement::Delivered,
+                Err(err) => {
+                    log::debug!(%err, to = %return_route, "failed to forward message");
+                    Acknowledgement::NotDelivered
+                }
+            }
+        };
+        ctx.send(return_route, ack).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::route;
+    use ockam_core::Address;
+
+    /// Never replies
+    struct Sink;
+
+    #[ockam::worker]
+    impl Worker for Sink {
+        type Context = Context;
+        type Message = Any;
+
+        async fn handle_message(&mut self, _: &mut Context, _: Routed<Any>) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[ockam_macros::test]
+    async fn acknowledges_delivery(ctx: &mut Context) -> Result<()> {
+        ctx.start_worker("ack", Acker).await?;
+        ctx.start_worker("sink", Sink).await?;
+        let mut child = ctx.new_detached(Address::random_local()).await?;
+
+        child
+            .send(route!["ack", "sink"], "hello".to_string())
+            .await?;
+        let ack = child.receive::<Acknowledgement>().await?.take();
+        assert_eq!(ack.return_route().recipient(), "ack".into());
+        assert_eq!(ack.body(), Acknowledgement::Delivered);
+
+        child
+            .send(route!["ack", "missing"], "hello".to_string())
+            .await?;
+        let ack = child.receive::<Acknowledgement>().await?.take().body();
+        assert_eq!(ack, Acknowledgement::NotDelivered);
+
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,269 @@
+//! Append-only log of security-relevant events.
+//!
+//! Records are stored as JSON lines in a file of the node directory and
+//! can be queried via `GET /node/audit`.
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::{iter, thread};
+
+use ockam_core::{async_trait, AccessControl, LocalMessage, Result};
+use ockam_identity::{IdentitySecureChannelLocalInfo, SecureChannelTrustInfo, TrustPolicy};
+use ockam_node::tokio::sync::oneshot;
+use ockam_node::tokio::task;
+
+use crate::error::ApiError;
+use crate::nodes::models::audit::{AuditKind, AuditQuery, AuditRecord};
+
+/// Name of the audit log file in the node directory.
+pub const AUDIT_LOG_FILE: &str = "audit.jsonl";
+
+/// An append-only audit log
+///
+/// Records are written to the file by a background thread, through a single
+/// buffered writer which is flushed whenever no more records are pending.
+#[derive(Clone)]
+pub struct AuditLog {
+    path: Arc<PathBuf>,
+    writer: mpsc::Sender<Command>,
+}
+
+enum Command {
+    /// Append a JSON line.
+    Append(Vec<u8>),
+    /// Notify once the previous records are written to the file.
+    Flush(oneshot::Sender<()>),
+}
+
+impl fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AuditLog").field(&self.path).finish()
+    }
+}
+
+impl AuditLog {
+    /// Create an audit log stored in the given file.
+    ///
+    /// The writer thread stops once every copy of the log is dropped.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        let path = Arc::new(path.as_ref().to_path_buf());
+        let (writer, commands) = mpsc::channel();
+        let file = path.to_path_buf();
+        thread::spawn(move || write(&file, commands));
+        Self { path, writer }
+    }
+
+    /// Append a record to the log.
+    ///
+    /// The record is queued for the writer thread, failures to write it are
+    /// logged by that thread.
+    pub fn append(&self, record: &AuditRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)
+            .map_err(|e| ApiError::generic(&format!("Invalid audit record: {e}")))?;
+        line.push(b'\n');
+        self.writer
+            .send(Command::Append(line))
+            .map_err(|_| ApiError::generic("The audit log writer has stopped"))
+    }
+
+    /// Append a record to the log, logging failures instead of returning them.
+    ///
+    /// Used on paths where failing to write the audit log must not change
+    /// the outcome of the event itself.
+    pub fn record(&self, record: AuditRecord) {
+        if let Err(err) = self.append(&record) {
+            error!(%err, kind = ?record.kind, "failed to append audit record");
+        }
+    }
+
+    /// Return the records matching the query, oldest first.
+    pub async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditRecord<'static>>> {
+        // Records appended before the query are included.
+        let (flushed, done) = oneshot::channel();
+        if self.writer.send(Command::Flush(flushed)).is_ok() {
+            let _ = done.await;
+        }
+        let path = self.path.clone();
+        let query = query.clone();
+        task::spawn_blocking(move || read(&path, &query))
+            .await
+            .map_err(|e| ApiError::generic(&format!("Failed to read audit log: {e}")))?
+    }
+}
+
+/// Write the records sent to the log until all its senders are dropped.
+fn write(path: &Path, commands: mpsc::Receiver<Command>) {
+    let mut file: Option<BufWriter<File>> = None;
+    while let Ok(first) = commands.recv() {
+        let mut flushed = Vec::new();
+        for command in iter::once(first).chain(commands.try_iter()) {
+            match command {
+                Command::Append(line) => {
+                    if let Err(err) = append(path, &mut file, &line) {
+                        error!(%err, "failed to write audit log");
+                        file = None
+                    }
+                }
+                Command::Flush(tx) => flushed.push(tx),
+            }
+        }
+        if let Some(f) = &mut file {
+            if let Err(err) = f.flush() {
+                error!(%err, "failed to write audit log");
+                file = None
+            }
+        }
+        for tx in flushed {
+            let _ = tx.send(());
+        }
+    }
+}
+
+/// Append a line to the log file, which is opened on first use.
+fn append(path: &Path, file: &mut Option<BufWriter<File>>, line: &[u8]) -> io::Result<()> {
+    let f = match file {
+        Some(f) => f,
+        None => {
+            let f = OpenOptions::new().create(true).append(true).open(path)?;
+            file.insert(BufWriter::new(f))
+        }
+    };
+    f.write_all(line)
+}
+
+fn read(path: &Path, query: &AuditQuery) -> Result<Vec<AuditRecord<'static>>> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(ApiError::generic(&format!("Failed to open audit log: {e}"))),
+    };
+    let mut records = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line =
+            line.map_err(|e| ApiError::generic(&format!("Failed to read audit log: {e}")))?;
+        match serde_json::from_str::<AuditRecord>(&line) {
+            Ok(r) if query.matches(&r) => records.push(r),
+            Ok(_) => {}
+            // A partially written last line is skipped.
+            Err(err) => warn!(%err, "invalid audit record"),
+        }
+    }
+    if let Some(limit) = query.limit {
+        let skip = records.len().saturating_sub(limit as usize);
+        records.drain(..skip);
+    }
+    Ok(records)
+}
+
+/// A [`TrustPolicy`] recording which identities are accepted or rejected
+/// by the wrapped policy.
+pub struct AuditTrustPolicy<P> {
+    inner: P,
+    audit: AuditLog,
+    listener: String,
+}
+
+impl<P: TrustPolicy> AuditTrustPolicy<P> {
+    pub fn new(inner: P, audit: AuditLog, listener: impl ToString) -> Self {
+        Self {
+            inner,
+            audit,
+            listener: listener.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl<P: TrustPolicy> TrustPolicy for AuditTrustPolicy<P> {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        let trusted = self.inner.check(trust_info).await?;
+        let kind = if trusted {
+            AuditKind::SecureChannelAccepted
+        } else {
+            AuditKind::SecureChannelRejected
+        };
+        self.audit.record(
+            AuditRecord::new(kind)
+                .with_identity(trust_info.their_identity_id())
+                .with_address(&self.listener),
+        );
+        Ok(trusted)
+    }
+}
+
+/// An [`AccessControl`] recording the messages denied by the wrapped one.
+pub struct AuditAccessControl<A> {
+    inner: A,
+    audit: AuditLog,
+    detail: String,
+}
+
+impl<A> fmt::Debug for AuditAccessControl<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditAccessControl")
+            .field("detail", &self.detail)
+            .finish()
+    }
+}
+
+impl<A: AccessControl> AuditAccessControl<A> {
+    /// Wrap `inner`, describing denials with `detail`, e.g. the resource guarded.
+    pub fn new(inner: A, audit: AuditLog, detail: impl Into<String>) -> Self {
+        Self {
+            inner,
+            audit,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl<A: AccessControl> AccessControl for AuditAccessControl<A> {
+    async fn is_authorized(&self, local_msg: &LocalMessage) -> Result<bool> {
+        let authorized = self.inner.is_authorized(local_msg).await?;
+        if !authorized {
+            let mut record =
+                AuditRecord::new(AuditKind::PolicyDenied).with_detail(self.detail.clone());
+            if let Ok(info) = IdentitySecureChannelLocalInfo::find_info(local_msg) {
+                record = record.with_identity(info.their_identity_id())
+            }
+            self.audit.record(record)
+        }
+        Ok(authorized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_node::Context;
+
+    #[ockam_macros::test]
+    async fn records_are_filtered_by_time(ctx: &mut Context) -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join(AUDIT_LOG_FILE));
+        for t in [10, 20, 30] {
+            let mut r = AuditRecord::new(AuditKind::MemberAdded).with_identity(format!("P{t}"));
+            r.timestamp = t;
+            log.append(&r).unwrap();
+        }
+
+        let all = log.query(&AuditQuery::default()).await?;
+        assert_eq!(all.len(), 3);
+
+        let some = log
+            .query(&AuditQuery::new(Some(20), Some(30), None))
+            .await?;
+        assert_eq!(some.len(), 1);
+        assert_eq!(some[0].identity.as_deref(), Some("P20"));
+
+        let last = log.query(&AuditQuery::new(None, None, Some(2))).await?;
+        assert_eq!(last.len(), 2);
+        assert_eq!(last[0].timestamp, 20);
+
+        ctx.stop().await
+    }
+}
@@ -1,2 +1,3 @@
 #[cfg(feature = "direct-authenticator")]
 pub mod direct;
+pub mod signer;
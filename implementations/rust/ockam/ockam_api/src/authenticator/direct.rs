@@ -1,24 +1,70 @@
 pub mod types;
 
 use core::{fmt, str};
-use minicbor::{Decoder, Encode};
+use minicbor::{Decode, Decoder, Encode};
 use ockam_core::api::{self, assert_request_match, assert_response_match};
-use ockam_core::api::{Error, Method, Request, RequestBuilder, Response, ResponseBuilder, Status};
+use ockam_core::api::{
+    Error, ErrorCode, Method, Request, RequestBuilder, Response, ResponseBuilder, Status,
+};
 use ockam_core::errcode::{Kind, Origin};
-use ockam_core::{self, Address, Result, Route, Routed, Worker};
+use ockam_core::{self, async_trait, Address, AsyncTryClone, Result, Route, Routed, Worker};
 use ockam_identity::authenticated_storage::AuthenticatedStorage;
-use ockam_identity::credential::{Credential, SchemaId};
-use ockam_identity::{Identity, IdentityIdentifier, IdentitySecureChannelLocalInfo, IdentityVault};
+use ockam_identity::credential::{
+    Attributes, Credential, SchemaId, Timestamp, MAX_CREDENTIAL_VALIDITY,
+};
+use ockam_identity::{
+    Identity, IdentityIdentifier, IdentitySecureChannelLocalInfo, IdentityVault,
+    SecureChannelTrustInfo, TrustPolicy,
+};
+use ockam_node::tokio::task::JoinSet;
 use ockam_node::Context;
 use serde_json as json;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tracing::{trace, warn};
 use types::AddMember;
 
-use self::types::Enroller;
+use self::types::{
+    CreateToken, EnrollMany, EnrollResult, EnrollResults, Enroller, MemberList, OneTimeCode,
+};
+use super::signer;
+use crate::audit::AuditLog;
+use crate::nodes::models::audit::{AuditKind, AuditRecord};
+use crate::revocation::Revocations;
 
 const MEMBER: &str = "member";
+const ATTRIBUTES: &str = "attributes";
+const TOKENS: &str = "tokens";
+/// Key of the creation times of the codes pending in the storage, used to
+/// remove them once they have expired.
+const PENDING_TOKENS: &str = "pending";
+/// Key of the number of members an enroller has enrolled.
+const ENROLLED: &str = "enrolled";
+/// Key of the time the last credential issued to a member expires, in
+/// seconds since the Unix epoch.
+const ISSUED_UNTIL: &str = "issued_until";
+
+/// Storage identifier and key of the index of members, which the storage
+/// can't enumerate by itself.
+const MEMBERS_ID: &str = "members";
+const MEMBERS_KEY: &str = "list";
+
+/// How many times an update of the index of members is attempted.
+const MAX_INDEX_ATTEMPTS: usize = 8;
+
+/// How long a one-time code can be redeemed after its creation.
+const MAX_TOKEN_DURATION: Duration = Duration::from_secs(600);
+
+/// How long before it expires a member can renew its credential.
+const RENEWAL_WINDOW: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// Why an enroller may not enroll any more members.
+const MEMBER_LIMIT: &str = "enroller member limit reached";
+
+/// Most members an enroller can add with a single request.
+const MAX_ENROLL_BATCH: usize = 1000;
 
 /// Schema identifier for a project membership credential.
 ///
@@ -36,6 +82,42 @@ pub struct Server<S, V: IdentityVault> {
     ident: Identity<V>,
     epath: PathBuf,
     enrollers: HashMap<IdentityIdentifier, Enroller>,
+    tokens: HashMap<[u8; 32], Token>,
+    shared_tokens: bool,
+    signer: Option<signer::Client>,
+    audit: Option<AuditLog>,
+    revocations: Option<Revocations>,
+    renewal_window: Duration,
+}
+
+/// A pending one-time code and the attributes it grants.
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+struct Token {
+    #[n(1)] attrs: BTreeMap<String, String>,
+    #[n(2)] generated_by: IdentityIdentifier,
+    #[n(3)] created: Timestamp,
+}
+
+impl Token {
+    fn is_expired(&self) -> bool {
+        is_token_expired(self.created)
+    }
+}
+
+fn is_token_expired(created: Timestamp) -> bool {
+    match Timestamp::now().and_then(|now| now.elapsed(created)) {
+        Some(d) => d > MAX_TOKEN_DURATION,
+        None => true,
+    }
+}
+
+/// The current time in seconds since the Unix epoch.
+fn now() -> Result<u64> {
+    Timestamp::now()
+        .map(u64::from)
+        .ok_or_else(|| ockam_core::Error::new(Origin::Application, Kind::Internal, "invalid time"))
 }
 
 #[ockam_core::worker]
@@ -54,7 +136,9 @@ where
         } else {
             let mut dec = Decoder::new(m.as_body());
             let req: Request = dec.decode()?;
-            let res = api::forbidden(&req, "secure channel required").to_vec()?;
+            let res = api::forbidden(&req, "secure channel required")
+                .with_code(ErrorCode::SecureChannelRequired)
+                .to_vec()?;
             c.send(m.return_route(), res).await
         }
     }
@@ -75,6 +159,62 @@ where
             ident: identity,
             epath: enrollers.as_ref().to_path_buf(),
             enrollers: HashMap::new(),
+            tokens: HashMap::new(),
+            shared_tokens: false,
+            signer: None,
+            audit: None,
+            revocations: None,
+            renewal_window: RENEWAL_WINDOW,
+        }
+    }
+
+    /// Keep pending one-time codes in the storage instead of in memory.
+    ///
+    /// Together with [`Server::with_signer`] this makes the server stateless:
+    /// several authenticators sharing the same storage, e.g. behind a
+    /// forwarder, can then serve the same project, and a code created by one
+    /// of them can be redeemed at any other.
+    pub fn with_shared_tokens(mut self) -> Self {
+        self.shared_tokens = true;
+        self
+    }
+
+    /// Have credentials signed by a remote [`signer::Server`] instead of the
+    /// identity of this server.
+    pub fn with_signer(mut self, signer: signer::Client) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Record enrollments and issued credentials in the given audit log.
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Let members renew their credential this long before it expires,
+    /// instead of [`RENEWAL_WINDOW`].
+    pub fn with_renewal_window(mut self, window: Duration) -> Self {
+        self.renewal_window = window;
+        self
+    }
+
+    /// Refuse credentials to members revoked in the given list.
+    pub fn with_revocations(mut self, revocations: Revocations) -> Self {
+        self.revocations = Some(revocations);
+        self
+    }
+
+    fn is_revoked(&self, member: &IdentityIdentifier) -> bool {
+        self.revocations
+            .as_ref()
+            .map(|r| r.is_revoked(member))
+            .unwrap_or(false)
+    }
+
+    fn record(&self, record: AuditRecord) {
+        if let Some(audit) = &self.audit {
+            audit.record(record)
         }
     }
 
@@ -97,30 +237,177 @@ where
                 // Enroller wants to add a member.
                 ["members"] => match self.check_enroller(&req, from).await {
                     Ok(None) => {
+                        if let Err(e) = api::validate_body("add_member", &dec) {
+                            return Ok(api::invalid_body(&req, &e).to_vec()?);
+                        }
                         let add: AddMember = dec.decode()?;
+                        let attrs = add
+                            .attributes()
+                            .into_iter()
+                            .flatten()
+                            .map(|(k, v)| (k.as_str(), v.as_str()));
+                        if let Some(msg) = self.disallowed_attribute(from, attrs) {
+                            return Ok(api::forbidden(&req, &msg).to_vec()?);
+                        }
+                        if self.allowance(from).await? == Some(0) {
+                            return Ok(api::forbidden(&req, MEMBER_LIMIT).to_vec()?);
+                        }
                         let tru = minicbor::to_vec(true)?;
                         self.store
                             .set(add.member().key_id(), MEMBER.to_string(), tru)
                             .await?;
+                        if let Some(attrs) = add.attributes() {
+                            let attrs = minicbor::to_vec(attrs)?;
+                            self.store
+                                .set(add.member().key_id(), ATTRIBUTES.to_string(), attrs)
+                                .await?;
+                        }
+                        self.index_member(add.member()).await?;
+                        self.count_enrolled(from, 1).await?;
+                        self.record(
+                            AuditRecord::new(AuditKind::MemberAdded)
+                                .with_identity(add.member())
+                                .with_detail(format!("added by {from}")),
+                        );
                         Response::ok(req.id()).to_vec()?
                     }
                     Ok(Some(e)) => e.to_vec()?,
                     Err(error) => api::internal_error(&req, &error.to_string()).to_vec()?,
                 },
+                // Enroller wants to add several members at once.
+                ["enroll_many"] => match self.check_enroller(&req, from).await {
+                    Ok(None) => {
+                        if let Err(e) = api::validate_body("enroll_many", &dec) {
+                            return Ok(api::invalid_body(&req, &e).to_vec()?);
+                        }
+                        let batch: EnrollMany = dec.decode()?;
+                        if batch.members().len() > MAX_ENROLL_BATCH {
+                            let msg = format!("at most {MAX_ENROLL_BATCH} members per request");
+                            return Ok(api::bad_request(&req, &msg).to_vec()?);
+                        }
+                        let results = self.enroll_many(from, &batch).await?;
+                        Response::ok(req.id()).body(results).to_vec()?
+                    }
+                    Ok(Some(e)) => e.to_vec()?,
+                    Err(error) => api::internal_error(&req, &error.to_string()).to_vec()?,
+                },
+                // Enroller wants a one-time code for a future member.
+                ["tokens"] => match self.check_enroller(&req, from).await {
+                    Ok(None) => {
+                        if let Err(e) = api::validate_body("create_token", &dec) {
+                            return Ok(api::invalid_body(&req, &e).to_vec()?);
+                        }
+                        let tok: CreateToken = dec.decode()?;
+                        let attrs = tok.attributes().iter().map(|(k, v)| (&**k, &**v));
+                        if let Some(msg) = self.disallowed_attribute(from, attrs) {
+                            return Ok(api::forbidden(&req, &msg).to_vec()?);
+                        }
+                        if self.allowance(from).await? == Some(0) {
+                            return Ok(api::forbidden(&req, MEMBER_LIMIT).to_vec()?);
+                        }
+                        let attrs = tok
+                            .attributes()
+                            .iter()
+                            .map(|(k, v)| (k.to_string(), v.to_string()))
+                            .collect();
+                        let otc = OneTimeCode::new();
+                        let token = Token {
+                            attrs,
+                            generated_by: from.clone(),
+                            created: Timestamp::now().ok_or_else(|| {
+                                ockam_core::Error::new(
+                                    Origin::Application,
+                                    Kind::Internal,
+                                    "invalid system time",
+                                )
+                            })?,
+                        };
+                        self.put_token(otc.code(), token).await?;
+                        self.count_enrolled(from, 1).await?;
+                        self.record(AuditRecord::new(AuditKind::TokenCreated).with_identity(from));
+                        Response::ok(req.id()).body(otc).to_vec()?
+                    }
+                    Ok(Some(e)) => e.to_vec()?,
+                    Err(error) => api::internal_error(&req, &error.to_string()).to_vec()?,
+                },
+                // Someone wants to become a member by redeeming a one-time code.
+                ["redeem"] => {
+                    if let Err(e) = api::validate_body("one_time_code", &dec) {
+                        return Ok(api::invalid_body(&req, &e).to_vec()?);
+                    }
+                    let otc: OneTimeCode = dec.decode()?;
+                    match self.take_token(otc.code()).await? {
+                        Some(t) if !t.is_expired() => {
+                            trace! {
+                                target: "ockam_api::authenticator::direct::server",
+                                member    = %from,
+                                generator = %t.generated_by,
+                                "redeeming one-time code"
+                            }
+                            let tru = minicbor::to_vec(true)?;
+                            self.store
+                                .set(from.key_id(), MEMBER.to_string(), tru)
+                                .await?;
+                            self.index_member(from).await?;
+                            let attrs = minicbor::to_vec(&t.attrs)?;
+                            self.store
+                                .set(from.key_id(), ATTRIBUTES.to_string(), attrs)
+                                .await?;
+                            self.record(
+                                AuditRecord::new(AuditKind::TokenRedeemed)
+                                    .with_identity(from)
+                                    .with_detail(format!("generated by {}", t.generated_by)),
+                            );
+                            Response::ok(req.id()).to_vec()?
+                        }
+                        Some(_) => api::forbidden(&req, "expired one-time code")
+                            .with_code(ErrorCode::ExpiredOneTimeCode)
+                            .to_vec()?,
+                        None => api::forbidden(&req, "unknown one-time code")
+                            .with_code(ErrorCode::UnknownOneTimeCode)
+                            .to_vec()?,
+                    }
+                }
                 // Member wants a credential.
                 ["credential"] => match self.check_member(&req, from).await {
                     Ok(None) => {
-                        let crd = Credential::builder(from.clone())
-                            .with_schema(PROJECT_MEMBER_SCHEMA)
-                            .with_attribute(PROJECT_ID, &self.project)
-                            .with_attribute(ROLE, b"member");
-
-                        let crd = self.ident.issue_credential(crd).await?;
-                        Response::ok(req.id()).body(crd).to_vec()?
+                        let res = self.issue_credential(&req, from).await?;
+                        self.record(
+                            AuditRecord::new(AuditKind::CredentialIssued).with_identity(from),
+                        );
+                        res
                     }
                     Ok(Some(e)) => e.to_vec()?,
                     Err(error) => api::internal_error(&req, &error.to_string()).to_vec()?,
                 },
+                // Member wants a fresh credential within the renewal window.
+                ["renew"] => match self.check_member(&req, from).await {
+                    Ok(None) => match self.renewal_refusal(from).await? {
+                        None => {
+                            let res = self.issue_credential(&req, from).await?;
+                            self.record(
+                                AuditRecord::new(AuditKind::CredentialRenewed).with_identity(from),
+                            );
+                            res
+                        }
+                        Some(msg) => api::forbidden(&req, msg)
+                            .with_code(ErrorCode::InvalidCredential)
+                            .to_vec()?,
+                    },
+                    Ok(Some(e)) => e.to_vec()?,
+                    Err(error) => api::internal_error(&req, &error.to_string()).to_vec()?,
+                },
+                _ => api::unknown_path(&req).to_vec()?,
+            },
+            Some(Method::Get) => match req.path_segments::<2>().as_slice() {
+                // Member wants the identifiers of all members.
+                ["members"] => match self.check_member(&req, from).await {
+                    Ok(None) => Response::ok(req.id())
+                        .body(self.members().await?)
+                        .to_vec()?,
+                    Ok(Some(e)) => e.to_vec()?,
+                    Err(error) => api::internal_error(&req, &error.to_string()).to_vec()?,
+                },
                 _ => api::unknown_path(&req).to_vec()?,
             },
             _ => api::invalid_method(&req).to_vec()?,
@@ -129,6 +416,303 @@ where
         Ok(res)
     }
 
+    /// Enroll the members of a batch concurrently, each with its own
+    /// attributes, then index the enrolled ones at once.
+    ///
+    /// A member listed twice is only enrolled the first time.
+    async fn enroll_many(
+        &self,
+        enroller: &IdentityIdentifier,
+        batch: &EnrollMany<'_>,
+    ) -> Result<EnrollResults> {
+        let mut errors: Vec<Option<String>> = vec![None; batch.members().len()];
+        let mut seen = BTreeSet::new();
+        let mut tasks = JoinSet::new();
+        let mut allowance = self.allowance(enroller).await?;
+        for (i, m) in batch.members().iter().enumerate() {
+            if !seen.insert(m.member()) {
+                errors[i] = Some("duplicate member".to_string());
+                continue;
+            }
+            let attrs = m.attributes().iter().map(|(k, v)| (&**k, &**v));
+            if let Some(msg) = self.disallowed_attribute(enroller, attrs) {
+                errors[i] = Some(msg);
+                continue;
+            }
+            if allowance == Some(0) {
+                errors[i] = Some(MEMBER_LIMIT.to_string());
+                continue;
+            }
+            allowance = allowance.map(|n| n - 1);
+            let store = self.store.async_try_clone().await?;
+            let member = m.member().clone();
+            let attrs: BTreeMap<String, String> = m
+                .attributes()
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            tasks.spawn(async move { (i, enroll(&store, &member, &attrs).await) });
+        }
+        while let Some(r) = tasks.join_next().await {
+            match r {
+                Ok((_, Ok(()))) => {}
+                Ok((i, Err(e))) => errors[i] = Some(e.to_string()),
+                Err(e) => {
+                    return Err(ockam_core::Error::new(
+                        Origin::Application,
+                        Kind::Internal,
+                        e,
+                    ))
+                }
+            }
+        }
+
+        let results: Vec<EnrollResult> = batch
+            .members()
+            .iter()
+            .zip(errors)
+            .map(|(m, e)| EnrollResult::new(m.member().clone(), e))
+            .collect();
+        let enrolled: Vec<IdentityIdentifier> = results
+            .iter()
+            .filter(|r| r.is_ok())
+            .map(|r| r.member().clone())
+            .collect();
+        self.index_members(&enrolled).await?;
+        self.count_enrolled(enroller, enrolled.len() as u64).await?;
+        for m in &enrolled {
+            self.record(
+                AuditRecord::new(AuditKind::MemberAdded)
+                    .with_identity(m)
+                    .with_detail(format!("added by {enroller}")),
+            );
+        }
+        Ok(EnrollResults::new(results))
+    }
+
+    /// Issue a member credential with the attributes the member was enrolled with.
+    async fn issue_credential(
+        &mut self,
+        req: &Request<'_>,
+        member: &IdentityIdentifier,
+    ) -> Result<Vec<u8>> {
+        let attrs: BTreeMap<String, String> =
+            match self.store.get(member.key_id(), ATTRIBUTES).await? {
+                Some(data) => minicbor::decode(&data)?,
+                None => BTreeMap::new(),
+            };
+        self.index_member(member).await?;
+        let mut crd = Attributes::new();
+        crd.put(PROJECT_ID, &self.project).put(ROLE, b"member");
+        for (k, v) in &attrs {
+            // Attributes set by an enroller never override the built-in ones.
+            if k != PROJECT_ID && k != ROLE {
+                crd.put(k, v.as_bytes());
+            }
+        }
+
+        // Both the signer and the identity issue credentials valid for
+        // `MAX_CREDENTIAL_VALIDITY`.
+        let until = now()? + MAX_CREDENTIAL_VALIDITY.as_secs();
+        let res = match &mut self.signer {
+            Some(signer) => {
+                let crd = signer
+                    .sign(member.clone(), Some(PROJECT_MEMBER_SCHEMA), crd)
+                    .await?;
+                Response::ok(req.id()).body(crd).to_vec()?
+            }
+            None => {
+                let mut builder =
+                    Credential::builder(member.clone()).with_schema(PROJECT_MEMBER_SCHEMA);
+                for (k, v) in crd.iter() {
+                    builder = builder.with_attribute(k, v)
+                }
+                let crd = self.ident.issue_credential(builder).await?;
+                Response::ok(req.id()).body(crd).to_vec()?
+            }
+        };
+        self.store
+            .set(
+                member.key_id(),
+                ISSUED_UNTIL.to_string(),
+                minicbor::to_vec(until)?,
+            )
+            .await?;
+        Ok(res)
+    }
+
+    /// Why a member may not renew its credential, if it may not.
+    ///
+    /// Only the last credential issued to the member can be renewed, once
+    /// it is within the renewal window and before it expires. Members
+    /// without a current credential get one from `/credential` instead.
+    async fn renewal_refusal(&self, member: &IdentityIdentifier) -> Result<Option<&'static str>> {
+        let until: u64 = match self.store.get(member.key_id(), ISSUED_UNTIL).await? {
+            Some(data) => minicbor::decode(&data)?,
+            None => return Ok(Some("no credential to renew")),
+        };
+        let now = now()?;
+        let refusal = if until <= now {
+            Some("expired credential")
+        } else if until - now > self.renewal_window.as_secs() {
+            Some("credential not due for renewal yet")
+        } else {
+            None
+        };
+        Ok(refusal)
+    }
+
+    /// Find the first attribute the enroller is not allowed to assign.
+    ///
+    /// Relies on the enrollers having been loaded by `check_enroller`.
+    fn disallowed_attribute<'b, I>(&self, enroller: &IdentityIdentifier, attrs: I) -> Option<String>
+    where
+        I: IntoIterator<Item = (&'b str, &'b str)>,
+    {
+        let e = self.enrollers.get(enroller)?;
+        attrs
+            .into_iter()
+            .find(|(k, v)| !e.may_assign(k, v))
+            .map(|(k, v)| format!("enroller may not assign attribute {k}={v}"))
+    }
+
+    /// How many more members the enroller may enroll, if it is limited.
+    ///
+    /// Relies on the enrollers having been loaded by `check_enroller`.
+    async fn allowance(&self, enroller: &IdentityIdentifier) -> Result<Option<u64>> {
+        if let Some(max) = self.enrollers.get(enroller).and_then(|e| e.max_members) {
+            let n = self.enrolled_by(enroller).await?;
+            return Ok(Some(max.saturating_sub(n)));
+        }
+        Ok(None)
+    }
+
+    /// The number of members the enroller has enrolled so far.
+    async fn enrolled_by(&self, enroller: &IdentityIdentifier) -> Result<u64> {
+        if let Some(data) = self.store.get(enroller.key_id(), ENROLLED).await? {
+            return Ok(minicbor::decode(&data)?);
+        }
+        Ok(0)
+    }
+
+    /// Add to the number of members the enroller has enrolled.
+    async fn count_enrolled(&self, enroller: &IdentityIdentifier, n: u64) -> Result<()> {
+        if n == 0 {
+            return Ok(());
+        }
+        let total = minicbor::to_vec(self.enrolled_by(enroller).await? + n)?;
+        self.store
+            .set(enroller.key_id(), ENROLLED.to_string(), total)
+            .await
+    }
+
+    /// Add a member to the index of members, if it isn't there yet.
+    async fn index_member(&self, member: &IdentityIdentifier) -> Result<()> {
+        self.index_members(std::slice::from_ref(member)).await
+    }
+
+    /// Add the members which aren't there yet to the index of members.
+    ///
+    /// Authenticators sharing a storage may overwrite each other's updates,
+    /// so the index is read back after every update, and updated again
+    /// until it contains the new members. Should a member still go missing
+    /// from it, it is indexed again on its next credential request.
+    async fn index_members(&self, new: &[IdentityIdentifier]) -> Result<()> {
+        for _ in 0..MAX_INDEX_ATTEMPTS {
+            let mut members = self.indexed_members().await?;
+            let count = members.len();
+            for m in new {
+                if !members.contains(m) {
+                    members.push(m.clone())
+                }
+            }
+            if members.len() == count {
+                return Ok(());
+            }
+            let data = minicbor::to_vec(MemberList::new(members))?;
+            self.store
+                .set(MEMBERS_ID, MEMBERS_KEY.to_string(), data)
+                .await?;
+        }
+        warn!("new members may be missing from the index after concurrent updates");
+        Ok(())
+    }
+
+    async fn indexed_members(&self) -> Result<Vec<IdentityIdentifier>> {
+        match self.store.get(MEMBERS_ID, MEMBERS_KEY).await? {
+            Some(data) => Ok(minicbor::decode::<MemberList>(&data)?.members().to_vec()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// The indexed identities which are still members.
+    async fn members(&self) -> Result<MemberList> {
+        let mut members = Vec::new();
+        for member in self.indexed_members().await? {
+            if let Some(data) = self.store.get(member.key_id(), MEMBER).await? {
+                if minicbor::decode(&data)? {
+                    members.push(member)
+                }
+            }
+        }
+        Ok(MemberList::new(members))
+    }
+
+    async fn put_token(&mut self, code: &[u8; 32], token: Token) -> Result<()> {
+        if self.shared_tokens {
+            let data = minicbor::to_vec(&token)?;
+            self.store.set(TOKENS, hex::encode(code), data).await?;
+            self.index_token(code, token.created).await
+        } else {
+            self.tokens.retain(|_, t| !t.is_expired());
+            self.tokens.insert(*code, token);
+            Ok(())
+        }
+    }
+
+    /// Add a code to the index of pending codes and remove the expired ones
+    /// from the storage.
+    ///
+    /// Like the index of members this may race with other authenticators
+    /// sharing the storage. A code missing from the index is then only kept
+    /// in the storage after it has expired, it can't be redeemed anymore.
+    async fn index_token(&self, code: &[u8; 32], created: Timestamp) -> Result<()> {
+        let mut pending: BTreeMap<String, Timestamp> =
+            match self.store.get(TOKENS, PENDING_TOKENS).await? {
+                Some(data) => minicbor::decode(&data)?,
+                None => BTreeMap::new(),
+            };
+        let expired: Vec<String> = pending
+            .iter()
+            .filter(|(_, created)| is_token_expired(**created))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            self.store.del(TOKENS, &key).await?;
+            pending.remove(&key);
+        }
+        pending.insert(hex::encode(code), created);
+        let data = minicbor::to_vec(&pending)?;
+        self.store
+            .set(TOKENS, PENDING_TOKENS.to_string(), data)
+            .await
+    }
+
+    /// Remove a pending one-time code, returning its token if it exists.
+    ///
+    /// With shared tokens the code is taken atomically from the storage, so
+    /// that authenticators sharing it can't both redeem it.
+    async fn take_token(&mut self, code: &[u8; 32]) -> Result<Option<Token>> {
+        if self.shared_tokens {
+            match self.store.take(TOKENS, &hex::encode(code)).await? {
+                Some(data) => Ok(Some(minicbor::decode(&data)?)),
+                None => Ok(None),
+            }
+        } else {
+            Ok(self.tokens.remove(code))
+        }
+    }
+
     async fn check_enroller<'a>(
         &mut self,
         req: &'a Request<'_>,
@@ -142,8 +726,19 @@ where
 
         self.enrollers = enrollers;
 
-        if self.enrollers.contains_key(enroller) {
-            return Ok(None);
+        if let Some(e) = self.enrollers.get(enroller) {
+            if !e.is_expired() {
+                return Ok(None);
+            }
+            warn! {
+                target: "ockam_api::authenticator::direct::server",
+                enroller = %enroller,
+                id       = %req.id(),
+                method   = ?req.method(),
+                path     = %req.path(),
+                "expired enroller"
+            }
+            return Ok(Some(api::forbidden(req, "expired enroller")));
         }
 
         warn! {
@@ -164,6 +759,18 @@ where
         req: &'a Request<'_>,
         member: &IdentityIdentifier,
     ) -> Result<Option<ResponseBuilder<Error<'a>>>> {
+        if self.is_revoked(member) {
+            warn! {
+                target: "ockam_api::authenticator::direct::server",
+                member   = %member,
+                id       = %req.id(),
+                method   = ?req.method(),
+                path     = %req.path(),
+                "revoked member"
+            }
+            return Ok(Some(api::forbidden(req, "revoked member")));
+        }
+
         if let Some(data) = self.store.get(member.key_id(), MEMBER).await? {
             if minicbor::decode(&data)? {
                 return Ok(None);
@@ -184,6 +791,20 @@ where
     }
 }
 
+/// Store a member and the attributes of its credentials.
+async fn enroll<S: AuthenticatedStorage>(
+    store: &S,
+    member: &IdentityIdentifier,
+    attrs: &BTreeMap<String, String>,
+) -> Result<()> {
+    let tru = minicbor::to_vec(true)?;
+    store.set(member.key_id(), MEMBER.to_string(), tru).await?;
+    let attrs = minicbor::to_vec(attrs)?;
+    store
+        .set(member.key_id(), ATTRIBUTES.to_string(), attrs)
+        .await
+}
+
 pub struct Client {
     ctx: Context,
     route: Route,
@@ -209,7 +830,12 @@ impl Client {
     }
 
     pub async fn add_member(&mut self, id: IdentityIdentifier) -> Result<()> {
-        let req = Request::post("/members").body(AddMember::new(id));
+        self.add_member_with(AddMember::new(id)).await
+    }
+
+    /// Add a member along with the attributes to credential it with.
+    pub async fn add_member_with(&mut self, add: AddMember) -> Result<()> {
+        let req = Request::post("/members").body(add);
         self.buf = self.request("add-member", "add_member", &req).await?;
         assert_response_match(None, &self.buf);
         let mut d = Decoder::new(&self.buf);
@@ -221,6 +847,46 @@ impl Client {
         }
     }
 
+    /// Add several members at once, returning whether each one was added.
+    pub async fn enroll_many(&mut self, batch: EnrollMany<'_>) -> Result<EnrollResults> {
+        let req = Request::post("/enroll_many").body(batch);
+        self.buf = self.request("enroll-many", "enroll_many", &req).await?;
+        assert_response_match("enroll_results", &self.buf);
+        let mut d = Decoder::new(&self.buf);
+        let res = response("enroll-many", &mut d)?;
+        if res.status() == Some(Status::Ok) {
+            Ok(d.decode()?)
+        } else {
+            Err(error("enroll-many", &res, &mut d))
+        }
+    }
+
+    pub async fn create_token(&mut self, tok: CreateToken<'_>) -> Result<OneTimeCode> {
+        let req = Request::post("/tokens").body(tok);
+        self.buf = self.request("create-token", "create_token", &req).await?;
+        assert_response_match("one_time_code", &self.buf);
+        let mut d = Decoder::new(&self.buf);
+        let res = response("create-token", &mut d)?;
+        if res.status() == Some(Status::Ok) {
+            Ok(d.decode()?)
+        } else {
+            Err(error("create-token", &res, &mut d))
+        }
+    }
+
+    pub async fn redeem(&mut self, otc: OneTimeCode) -> Result<()> {
+        let req = Request::post("/redeem").body(otc);
+        self.buf = self.request("redeem", "one_time_code", &req).await?;
+        assert_response_match(None, &self.buf);
+        let mut d = Decoder::new(&self.buf);
+        let res = response("redeem", &mut d)?;
+        if res.status() == Some(Status::Ok) {
+            Ok(())
+        } else {
+            Err(error("redeem", &res, &mut d))
+        }
+    }
+
     pub async fn credential(&mut self) -> Result<Credential<'_>> {
         let req = Request::post("/credential");
         self.buf = self.request("new-credential", None, &req).await?;
@@ -234,6 +900,37 @@ impl Client {
         }
     }
 
+    /// Get a fresh member credential shortly before the current one expires.
+    ///
+    /// The authority refuses to renew credentials which have expired, or
+    /// which are not within its renewal window yet.
+    pub async fn renew(&mut self) -> Result<Credential<'_>> {
+        let req = Request::post("/renew");
+        self.buf = self.request("renew-credential", None, &req).await?;
+        assert_response_match("credential", &self.buf);
+        let mut d = Decoder::new(&self.buf);
+        let res = response("renew-credential", &mut d)?;
+        if res.status() == Some(Status::Ok) {
+            Ok(d.decode()?)
+        } else {
+            Err(error("renew-credential", &res, &mut d))
+        }
+    }
+
+    /// The identifiers of all members, only available to members.
+    pub async fn members(&mut self) -> Result<MemberList> {
+        let req = Request::get("/members");
+        self.buf = self.request("list-members", None, &req).await?;
+        assert_response_match("member_list", &self.buf);
+        let mut d = Decoder::new(&self.buf);
+        let res = response("list-members", &mut d)?;
+        if res.status() == Some(Status::Ok) {
+            Ok(d.decode()?)
+        } else {
+            Err(error("list-members", &res, &mut d))
+        }
+    }
+
     /// Encode request header and body (if any) and send the package to the server.
     async fn request<T>(
         &mut self,
@@ -260,6 +957,40 @@ impl Client {
     }
 }
 
+/// The members of a project known to a node, as last fetched from its
+/// authority with [`Client::members`]
+///
+/// As a [`TrustPolicy`] it only trusts members.
+#[derive(Debug, Clone, Default)]
+pub struct Members {
+    ids: Arc<RwLock<BTreeSet<IdentityIdentifier>>>,
+}
+
+impl Members {
+    pub fn contains(&self, identity: &IdentityIdentifier) -> bool {
+        let ids = self.ids.read().unwrap_or_else(|e| e.into_inner());
+        ids.contains(identity)
+    }
+
+    /// Replace the members, returning `false` if they didn't change.
+    pub fn update(&self, list: &MemberList) -> bool {
+        let mut ids = self.ids.write().unwrap_or_else(|e| e.into_inner());
+        let updated: BTreeSet<IdentityIdentifier> = list.members().iter().cloned().collect();
+        if *ids == updated {
+            return false;
+        }
+        *ids = updated;
+        true
+    }
+}
+
+#[async_trait]
+impl TrustPolicy for Members {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        Ok(self.contains(trust_info.their_identity_id()))
+    }
+}
+
 /// Decode and log response header.
 fn response(label: &str, dec: &mut Decoder<'_>) -> Result<Response> {
     let res: Response = dec.decode()?;
@@ -1,6 +1,9 @@
 use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+use ockam_identity::credential::Timestamp;
 use ockam_identity::IdentityIdentifier;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
@@ -11,7 +14,8 @@ use ockam_core::TypeTag;
 pub struct AddMember {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<2820828>,
-    #[n(1)] member: IdentityIdentifier
+    #[n(1)] member: IdentityIdentifier,
+    #[n(2)] attrs: Option<BTreeMap<String, String>>
 }
 
 impl AddMember {
@@ -20,13 +24,262 @@ impl AddMember {
             #[cfg(feature = "tag")]
             tag: TypeTag,
             member,
+            attrs: None,
         }
     }
 
+    /// Attributes the member will be credentialed with.
+    pub fn with_attribute<K: Into<String>, V: Into<String>>(mut self, k: K, v: V) -> Self {
+        self.attrs
+            .get_or_insert_with(BTreeMap::new)
+            .insert(k.into(), v.into());
+        self
+    }
+
     pub fn member(&self) -> &IdentityIdentifier {
         &self.member
     }
+
+    /// The attributes to assign, if any were given.
+    pub fn attributes(&self) -> Option<&BTreeMap<String, String>> {
+        self.attrs.as_ref()
+    }
+}
+
+#[derive(Debug, Default, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateToken<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5865364>,
+    #[b(1)] attrs: BTreeMap<CowStr<'a>, CowStr<'a>>
+}
+
+impl<'a> CreateToken<'a> {
+    pub fn new() -> Self {
+        CreateToken::default()
+    }
+
+    /// Attributes the member redeeming the token will be credentialed with.
+    pub fn with_attribute<S: Into<CowStr<'a>>>(mut self, k: S, v: S) -> Self {
+        self.attrs.insert(k.into(), v.into());
+        self
+    }
+
+    pub fn attributes(&self) -> &BTreeMap<CowStr<'a>, CowStr<'a>> {
+        &self.attrs
+    }
+}
+
+/// The identifiers of the members of a project.
+#[derive(Debug, Clone, Default, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct MemberList {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6271835>,
+    #[n(1)] members: Vec<IdentityIdentifier>
+}
+
+impl MemberList {
+    pub fn new(members: Vec<IdentityIdentifier>) -> Self {
+        MemberList {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            members,
+        }
+    }
+
+    pub fn members(&self) -> &[IdentityIdentifier] {
+        &self.members
+    }
+}
+
+/// Members to enroll at once.
+#[derive(Debug, Default, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EnrollMany<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3871526>,
+    #[b(1)] members: Vec<EnrollMember<'a>>
+}
+
+impl<'a> EnrollMany<'a> {
+    pub fn new() -> Self {
+        EnrollMany::default()
+    }
+
+    pub fn with_member(mut self, m: EnrollMember<'a>) -> Self {
+        self.members.push(m);
+        self
+    }
+
+    pub fn members(&self) -> &[EnrollMember<'a>] {
+        &self.members
+    }
+}
+
+/// A member of an [`EnrollMany`] request.
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EnrollMember<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5529014>,
+    #[n(1)] member: IdentityIdentifier,
+    #[b(2)] attrs: BTreeMap<CowStr<'a>, CowStr<'a>>
+}
+
+impl<'a> EnrollMember<'a> {
+    pub fn new(member: IdentityIdentifier) -> Self {
+        EnrollMember {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            member,
+            attrs: BTreeMap::new(),
+        }
+    }
+
+    /// Attributes the member will be credentialed with.
+    pub fn with_attribute<S: Into<CowStr<'a>>>(mut self, k: S, v: S) -> Self {
+        self.attrs.insert(k.into(), v.into());
+        self
+    }
+
+    pub fn member(&self) -> &IdentityIdentifier {
+        &self.member
+    }
+
+    pub fn attributes(&self) -> &BTreeMap<CowStr<'a>, CowStr<'a>> {
+        &self.attrs
+    }
+}
+
+/// The outcome of an [`EnrollMany`] request, one result per member in the
+/// order of the request.
+#[derive(Debug, Clone, Default, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EnrollResults {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7160384>,
+    #[n(1)] results: Vec<EnrollResult>
+}
+
+impl EnrollResults {
+    pub fn new(results: Vec<EnrollResult>) -> Self {
+        EnrollResults {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            results,
+        }
+    }
+
+    pub fn results(&self) -> &[EnrollResult] {
+        &self.results
+    }
+}
+
+/// Whether a member of an [`EnrollMany`] request was enrolled.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EnrollResult {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2486153>,
+    #[n(1)] member: IdentityIdentifier,
+    #[n(2)] error: Option<String>
+}
+
+impl EnrollResult {
+    pub fn new(member: IdentityIdentifier, error: Option<String>) -> Self {
+        EnrollResult {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            member,
+            error,
+        }
+    }
+
+    pub fn member(&self) -> &IdentityIdentifier {
+        &self.member
+    }
+
+    /// Why the member was not enrolled.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// A one-time code to enroll a member.
+#[derive(Debug, Clone, Copy, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OneTimeCode {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5112299>,
+    #[cbor(n(1), with = "minicbor::bytes")] code: [u8; 32]
+}
+
+impl OneTimeCode {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        use ockam_core::compat::rand::random;
+        OneTimeCode {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            code: random(),
+        }
+    }
+
+    pub fn code(&self) -> &[u8; 32] {
+        &self.code
+    }
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
-pub struct Enroller {}
+pub struct Enroller {
+    /// The attributes this enroller may assign to members, by key.
+    ///
+    /// If absent, any attribute may be assigned. An empty list of values
+    /// allows any value for the key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<BTreeMap<String, Vec<String>>>,
+
+    /// The most members this enroller may enroll, one-time codes included.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_members: Option<u64>,
+
+    /// Unix time (in seconds) from which this enroller has no rights.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+impl Enroller {
+    /// Have the rights of this enroller expired?
+    pub fn is_expired(&self) -> bool {
+        match self.expires_at {
+            None => false,
+            Some(t) => match Timestamp::now() {
+                Some(now) => u64::from(now) >= t,
+                None => true,
+            },
+        }
+    }
+
+    /// Is this enroller allowed to assign the given attribute?
+    pub fn may_assign(&self, key: &str, value: &str) -> bool {
+        match &self.attributes {
+            None => true,
+            Some(allowed) => match allowed.get(key) {
+                Some(values) => values.is_empty() || values.iter().any(|v| v == value),
+                None => false,
+            },
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Issue credentials on behalf of authenticators.
+//!
+//! Authenticators running in stateless mode do not hold the authority
+//! identity. They ask a [`Server`], reachable over a secure channel, to sign
+//! the credentials of the members they have checked.
+
+pub mod types;
+
+use core::fmt;
+use minicbor::{Decoder, Encode};
+use ockam_core::api::{self, assert_request_match, assert_response_match};
+use ockam_core::api::{Error, ErrorCode, Method, Request, RequestBuilder, Response, Status};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{self, Address, Result, Route, Routed, Worker};
+use ockam_identity::credential::{Attributes, Credential, SchemaId};
+use ockam_identity::{Identity, IdentityIdentifier, IdentitySecureChannelLocalInfo, IdentityVault};
+use ockam_node::Context;
+use tracing::{trace, warn};
+
+use self::types::Sign;
+
+/// Signs credentials for a set of authorized authenticators.
+pub struct Server<V: IdentityVault> {
+    ident: Identity<V>,
+    authorized: Vec<IdentityIdentifier>,
+}
+
+#[ockam_core::worker]
+impl<V> Worker for Server<V>
+where
+    V: IdentityVault,
+{
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        let mut dec = Decoder::new(m.as_body());
+        let req: Request = dec.decode()?;
+        let res = match IdentitySecureChannelLocalInfo::find_info(m.local_message()) {
+            Ok(i) if self.authorized.contains(i.their_identity_id()) => {
+                self.on_request(&req, &mut dec).await?
+            }
+            Ok(i) => {
+                warn! {
+                    target: "ockam_api::authenticator::signer::server",
+                    from = %i.their_identity_id(),
+                    "unauthorised authenticator"
+                }
+                api::forbidden(&req, "unauthorized authenticator").to_vec()?
+            }
+            Err(_) => api::forbidden(&req, "secure channel required")
+                .with_code(ErrorCode::SecureChannelRequired)
+                .to_vec()?,
+        };
+        c.send(m.return_route(), res).await
+    }
+}
+
+impl<V> Server<V>
+where
+    V: IdentityVault,
+{
+    /// Sign credentials with `identity` for the `authorized` authenticators.
+    pub fn new(identity: Identity<V>, authorized: Vec<IdentityIdentifier>) -> Self {
+        Server {
+            ident: identity,
+            authorized,
+        }
+    }
+
+    async fn on_request(&mut self, req: &Request<'_>, dec: &mut Decoder<'_>) -> Result<Vec<u8>> {
+        trace! {
+            target: "ockam_api::authenticator::signer::server",
+            id     = %req.id(),
+            method = ?req.method(),
+            path   = %req.path(),
+            body   = %req.has_body(),
+            "request"
+        }
+
+        let res = match req.method() {
+            Some(Method::Post) => match req.path_segments::<2>().as_slice() {
+                ["sign"] => {
+                    if let Err(e) = api::validate_body("sign", dec) {
+                        return Ok(api::invalid_body(req, &e).to_vec()?);
+                    }
+                    let sign: Sign = dec.decode()?;
+                    let mut crd = Credential::builder(sign.subject().clone());
+                    if let Some(schema) = sign.schema() {
+                        crd = crd.with_schema(schema)
+                    }
+                    for (k, v) in sign.attributes().iter() {
+                        crd = crd.with_attribute(k, v)
+                    }
+                    let crd = self.ident.issue_credential(crd).await?;
+                    Response::ok(req.id()).body(crd).to_vec()?
+                }
+                _ => api::unknown_path(req).to_vec()?,
+            },
+            _ => api::invalid_method(req).to_vec()?,
+        };
+
+        Ok(res)
+    }
+}
+
+pub struct Client {
+    ctx: Context,
+    route: Route,
+    buf: Vec<u8>,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("route", &self.route)
+            .finish()
+    }
+}
+
+impl Client {
+    pub async fn new(r: Route, ctx: &Context) -> Result<Self> {
+        let ctx = ctx.new_detached(Address::random_local()).await?;
+        Ok(Client {
+            ctx,
+            route: r,
+            buf: Vec::new(),
+        })
+    }
+
+    pub async fn sign(
+        &mut self,
+        subject: IdentityIdentifier,
+        schema: Option<SchemaId>,
+        attrs: Attributes<'_>,
+    ) -> Result<Credential<'_>> {
+        let req = Request::post("/sign").body(Sign::new(subject, schema, attrs));
+        self.buf = self.request("sign", "sign", &req).await?;
+        assert_response_match("credential", &self.buf);
+        let mut d = Decoder::new(&self.buf);
+        let res: Response = d.decode()?;
+        trace! {
+            target: "ockam_api::authenticator::signer::client",
+            re     = %res.re(),
+            id     = %res.id(),
+            status = ?res.status(),
+            body   = %res.has_body(),
+            "<- sign"
+        }
+        if res.status() == Some(Status::Ok) {
+            Ok(d.decode()?)
+        } else {
+            let msg = if res.has_body() {
+                d.decode::<Error>()?.message().map(|m| m.to_string())
+            } else {
+                None
+            };
+            Err(ockam_core::Error::new(
+                Origin::Application,
+                Kind::Protocol,
+                msg.unwrap_or_else(|| "failed to sign credential".to_string()),
+            ))
+        }
+    }
+
+    /// Encode request header and body (if any) and send the package to the server.
+    async fn request<T>(
+        &mut self,
+        label: &str,
+        schema: impl Into<Option<&str>>,
+        req: &RequestBuilder<'_, T>,
+    ) -> Result<Vec<u8>>
+    where
+        T: Encode<()>,
+    {
+        let mut buf = Vec::new();
+        req.encode(&mut buf)?;
+        assert_request_match(schema, &buf);
+        trace! {
+            target: "ockam_api::authenticator::signer::client",
+            id     = %req.header().id(),
+            method = ?req.header().method(),
+            path   = %req.header().path(),
+            body   = %req.header().has_body(),
+            "-> {label}"
+        };
+        let vec: Vec<u8> = self.ctx.send_and_receive(self.route.clone(), buf).await?;
+        Ok(vec)
+    }
+}
@@ -0,0 +1,46 @@
+use minicbor::{Decode, Encode};
+use ockam_identity::credential::{Attributes, SchemaId};
+use ockam_identity::IdentityIdentifier;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request to sign a credential for a subject.
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Sign<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<8254039>,
+    #[n(1)] subject: IdentityIdentifier,
+    #[n(2)] schema: Option<SchemaId>,
+    #[b(3)] attrs: Attributes<'a>,
+}
+
+impl<'a> Sign<'a> {
+    pub fn new(
+        subject: IdentityIdentifier,
+        schema: Option<SchemaId>,
+        attrs: Attributes<'a>,
+    ) -> Self {
+        Sign {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            subject,
+            schema,
+            attrs,
+        }
+    }
+
+    pub fn subject(&self) -> &IdentityIdentifier {
+        &self.subject
+    }
+
+    pub fn schema(&self) -> Option<SchemaId> {
+        self.schema
+    }
+
+    pub fn attributes(&self) -> &Attributes<'a> {
+        &self.attrs
+    }
+}
@@ -0,0 +1,228 @@
+use minicbor::{Decode, Encode};
+use serde::Serialize;
+
+use ockam_core::CowStr;
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+#[derive(Encode, Decode, Serialize, Debug)]
+#[cfg_attr(test, derive(Clone))]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Addon<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] pub tag: TypeTag<1530077>,
+    #[b(1)] pub id: CowStr<'a>,
+    #[b(2)] pub description: CowStr<'a>,
+    #[n(3)] pub enabled: bool,
+}
+
+#[derive(Encode, Decode, Debug)]
+#[cfg_attr(test, derive(Clone))]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ConfluentConfig<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] pub tag: TypeTag<1697996>,
+    #[b(1)] pub bootstrap_server: CowStr<'a>,
+}
+
+impl<'a> ConfluentConfig<'a> {
+    pub fn new<S: Into<CowStr<'a>>>(bootstrap_server: S) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            bootstrap_server: bootstrap_server.into(),
+        }
+    }
+}
+
+#[derive(Encode, Decode, Debug)]
+#[cfg_attr(test, derive(Clone))]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct InfluxDBConfig<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] pub tag: TypeTag<2736452>,
+    #[b(1)] pub endpoint: CowStr<'a>,
+    #[b(2)] pub token: CowStr<'a>,
+    #[b(3)] pub org_id: CowStr<'a>,
+    #[b(4)] pub permissions: CowStr<'a>,
+    #[n(5)] pub max_ttl_secs: u64,
+}
+
+impl<'a> InfluxDBConfig<'a> {
+    pub fn new<S: Into<CowStr<'a>>>(
+        endpoint: S,
+        token: S,
+        org_id: S,
+        permissions: S,
+        max_ttl_secs: u64,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            endpoint: endpoint.into(),
+            token: token.into(),
+            org_id: org_id.into(),
+            permissions: permissions.into(),
+            max_ttl_secs,
+        }
+    }
+}
+
+mod node {
+    use minicbor::{Decoder, Encode};
+    use tracing::trace;
+
+    use ockam_core::api::Request;
+    use ockam_core::{self, Result};
+    use ockam_node::Context;
+
+    use crate::cloud::{BareCloudRequestWrapper, CloudRequestWrapper};
+    use crate::nodes::NodeManagerWorker;
+
+    use super::*;
+
+    const TARGET: &str = "ockam_api::cloud::addon";
+
+    impl NodeManagerWorker {
+        pub(crate) async fn list_addons(
+            &mut self,
+            ctx: &mut Context,
+            dec: &mut Decoder<'_>,
+            project_id: &str,
+        ) -> Result<Vec<u8>> {
+            let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
+            let cloud_route = req_wrapper.controller_route()?;
+
+            let label = "list_addons";
+            trace!(target: TARGET, %project_id, "listing addons");
+
+            let req_builder = Request::get(format!("/v0/{project_id}/addons"));
+            self.request_controller(ctx, label, None, cloud_route, "projects", req_builder)
+                .await
+        }
+
+        pub(crate) async fn configure_confluent_addon(
+            &mut self,
+            ctx: &mut Context,
+            dec: &mut Decoder<'_>,
+            project_id: &str,
+        ) -> Result<Vec<u8>> {
+            let req_wrapper: CloudRequestWrapper<ConfluentConfig> = dec.decode()?;
+            self.configure_addon(ctx, req_wrapper, project_id, "confluent")
+                .await
+        }
+
+        pub(crate) async fn configure_influxdb_addon(
+            &mut self,
+            ctx: &mut Context,
+            dec: &mut Decoder<'_>,
+            project_id: &str,
+        ) -> Result<Vec<u8>> {
+            let req_wrapper: CloudRequestWrapper<InfluxDBConfig> = dec.decode()?;
+            self.configure_addon(ctx, req_wrapper, project_id, "influxdb_token_lease_manager")
+                .await
+        }
+
+        async fn configure_addon<T>(
+            &mut self,
+            ctx: &mut Context,
+            req_wrapper: CloudRequestWrapper<'_, T>,
+            project_id: &str,
+            addon_id: &str,
+        ) -> Result<Vec<u8>>
+        where
+            T: Encode<()>,
+        {
+            let cloud_route = req_wrapper.controller_route()?;
+            let req_body = req_wrapper.req;
+
+            let label = "configure_addon";
+            trace!(target: TARGET, %project_id, %addon_id, "configuring addon");
+
+            let req_builder =
+                Request::put(format!("/v0/{project_id}/configure_addon/{addon_id}")).body(req_body);
+            self.request_controller(ctx, label, None, cloud_route, "projects", req_builder)
+                .await
+        }
+
+        pub(crate) async fn disable_addon(
+            &mut self,
+            ctx: &mut Context,
+            dec: &mut Decoder<'_>,
+            project_id: &str,
+            addon_id: &str,
+        ) -> Result<Vec<u8>> {
+            let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
+            let cloud_route = req_wrapper.controller_route()?;
+
+            let label = "disable_addon";
+            trace!(target: TARGET, %project_id, %addon_id, "disabling addon");
+
+            let req_builder = Request::delete(format!("/v0/{project_id}/addons/{addon_id}"));
+            self.request_controller(ctx, label, None, cloud_route, "projects", req_builder)
+                .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickcheck::{Arbitrary, Gen};
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct CC(ConfluentConfig<'static>);
+
+    impl Arbitrary for CC {
+        fn arbitrary(g: &mut Gen) -> Self {
+            CC(ConfluentConfig::new(String::arbitrary(g)))
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct IC(InfluxDBConfig<'static>);
+
+    impl Arbitrary for IC {
+        fn arbitrary(g: &mut Gen) -> Self {
+            IC(InfluxDBConfig::new(
+                String::arbitrary(g),
+                String::arbitrary(g),
+                String::arbitrary(g),
+                String::arbitrary(g),
+                u64::arbitrary(g),
+            ))
+        }
+    }
+
+    mod schema {
+        use cddl_cat::validate_cbor_bytes;
+        use quickcheck::{quickcheck, TestResult};
+
+        use ockam_core::api::SCHEMA;
+
+        use super::*;
+
+        quickcheck! {
+            fn confluent_config(o: CC) -> TestResult {
+                let cbor = minicbor::to_vec(&o.0).unwrap();
+                if let Err(e) = validate_cbor_bytes("confluent_config", SCHEMA, &cbor) {
+                    return TestResult::error(e.to_string())
+                }
+                TestResult::passed()
+            }
+
+            fn influxdb_config(o: IC) -> TestResult {
+                let cbor = minicbor::to_vec(&o.0).unwrap();
+                if let Err(e) = validate_cbor_bytes("influxdb_config", SCHEMA, &cbor) {
+                    return TestResult::error(e.to_string())
+                }
+                TestResult::passed()
+            }
+        }
+    }
+}
@@ -29,14 +29,14 @@ mod node {
     use tracing::trace;
 
     use ockam_core::api::Request;
-    use ockam_core::{self, Result, Route};
+    use ockam_core::{self, Result};
     use ockam_node::Context;
 
     use crate::cloud::enroll::auth0::AuthenticateAuth0Token;
     use crate::cloud::enroll::enrollment_token::{
         AuthenticateEnrollmentToken, EnrollmentToken, RequestEnrollmentToken,
     };
-    use crate::cloud::CloudRequestWrapper;
+    use crate::cloud::{CloudRequestWrapper, ControllerRoute};
     use crate::nodes::NodeManagerWorker;
     use ockam_identity::credential::Attributes;
 
@@ -52,7 +52,7 @@ mod node {
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
             let req_wrapper: CloudRequestWrapper<AuthenticateAuth0Token> = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
             let req_body: AuthenticateAuth0Token = req_wrapper.req;
             let req_body = AuthenticateToken::Auth0(req_body);
 
@@ -67,7 +67,7 @@ mod node {
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
             let req_wrapper: CloudRequestWrapper<Attributes> = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
             let req_body: Attributes = req_wrapper.req;
             let req_body = RequestEnrollmentToken::new(req_body);
 
@@ -93,7 +93,7 @@ mod node {
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
             let req_wrapper: CloudRequestWrapper<EnrollmentToken> = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
             let req_body: EnrollmentToken = req_wrapper.req;
             let req_body =
                 AuthenticateToken::EnrollmentToken(AuthenticateEnrollmentToken::new(req_body));
@@ -105,7 +105,7 @@ mod node {
         async fn authenticate_token(
             &mut self,
             ctx: &mut Context,
-            cloud_route: Route,
+            cloud_route: ControllerRoute,
             body: AuthenticateToken<'_>,
         ) -> Result<Vec<u8>> {
             // TODO: add AuthenticateAuth0Token to schema.cddl and use it here
@@ -1,7 +1,10 @@
+use std::collections::BTreeMap;
 use std::str::FromStr;
+use std::time::Duration;
 
-use minicbor::{Decode, Encode};
+use minicbor::{Decode, Decoder, Encode};
 
+use ockam_core::api::{self, Id, Response, Status};
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
 use ockam_core::{CowStr, Result, Route};
@@ -9,6 +12,7 @@ use ockam_multiaddr::MultiAddr;
 
 use crate::error::ApiError;
 
+pub mod addon;
 pub mod enroll;
 pub mod project;
 pub mod space;
@@ -21,6 +25,42 @@ pub mod subscription;
 /// add the env variable. `OCKAM_CONTROLLER_IDENTITY_ID={identity.id-contents} ockam ...`
 pub(crate) const OCKAM_CONTROLLER_IDENTITY_ID: &str = "OCKAM_CONTROLLER_IDENTITY_ID";
 
+/// Percent-encode a value used as a single segment of a request path.
+///
+/// Only unreserved characters are kept, so that values such as email
+/// addresses can't contain a `/`, `?` or `#` changing the path.
+pub fn path_segment(value: &str) -> String {
+    let mut segment = String::with_capacity(value.len());
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                segment.push(b as char)
+            }
+            _ => segment.push_str(&format!("%{b:02X}")),
+        }
+    }
+    segment
+}
+
+/// Decode a request path segment encoded with [`path_segment`].
+pub fn decode_path_segment(segment: &str) -> Result<String> {
+    let invalid = || ApiError::generic(&format!("Invalid path segment: {segment}"));
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = tail.get(..2).ok_or_else(invalid)?;
+            let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
 /// A wrapper around a cloud request with extra fields.
 #[derive(Encode, Decode, Debug)]
 #[cfg_attr(test, derive(Clone))]
@@ -31,6 +71,7 @@ pub struct CloudRequestWrapper<'a, T> {
     #[n(0)] pub tag: TypeTag<8956240>,
     #[b(1)] pub req: T,
     #[b(2)] route: CowStr<'a>,
+    #[n(3)] retry: Option<RetryPolicy>,
 }
 
 impl<'a, T> CloudRequestWrapper<'a, T> {
@@ -40,15 +81,30 @@ impl<'a, T> CloudRequestWrapper<'a, T> {
             tag: TypeTag,
             req,
             route: route.to_string().into(),
+            retry: None,
         }
     }
 
+    /// Override the node's retry policy for this request.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
     pub fn route(&self) -> Result<Route> {
         let maddr = MultiAddr::from_str(self.route.as_ref())
             .map_err(|_err| ApiError::generic(&format!("Invalid route: {}", self.route)))?;
         crate::multiaddr_to_route(&maddr)
             .ok_or_else(|| ApiError::generic(&format!("Invalid MultiAddr: {}", maddr)))
     }
+
+    /// The route to the controller, along with the retry policy override if any.
+    pub fn controller_route(&self) -> Result<ControllerRoute> {
+        Ok(ControllerRoute {
+            route: self.route()?,
+            retry: self.retry,
+        })
+    }
 }
 
 /// A CloudRequestWrapper without an internal request.
@@ -60,23 +116,184 @@ impl<'a> BareCloudRequestWrapper<'a> {
     }
 }
 
+/// How requests to the controller are retried when they fail with a transient error.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RetryPolicy {
+    #[cfg(feature = "tag")]
+    #[n(0)] pub tag: TypeTag<3322475>,
+    /// Number of retries after the first attempt.
+    #[n(1)] pub retries: u32,
+    /// Delay before the first retry, doubled after each retry.
+    #[n(2)] pub backoff_millis: u64,
+    /// Timeout of a single attempt.
+    #[n(3)] pub timeout_secs: u64,
+    /// Time after which no more attempts are made.
+    #[n(4)] pub deadline_secs: u64,
+}
+
+impl RetryPolicy {
+    pub fn new(retries: u32, backoff: Duration, timeout: Duration, deadline: Duration) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            retries,
+            backoff_millis: backoff.as_millis() as u64,
+            timeout_secs: timeout.as_secs(),
+            deadline_secs: deadline.as_secs(),
+        }
+    }
+
+    /// A policy making a single attempt.
+    pub fn no_retry(timeout: Duration) -> Self {
+        Self::new(0, Duration::ZERO, timeout, timeout)
+    }
+
+    pub fn backoff(&self) -> Duration {
+        Duration::from_millis(self.backoff_millis)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    pub fn deadline(&self) -> Duration {
+        Duration::from_secs(self.deadline_secs)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(
+            3,
+            Duration::from_millis(500),
+            Duration::from_secs(ockam_node::DEFAULT_TIMEOUT),
+            Duration::from_secs(90),
+        )
+    }
+}
+
+/// A route to the controller and the retry policy to use on it.
+#[derive(Debug, Clone)]
+pub struct ControllerRoute {
+    route: Route,
+    retry: Option<RetryPolicy>,
+}
+
+impl From<Route> for ControllerRoute {
+    fn from(route: Route) -> Self {
+        Self { route, retry: None }
+    }
+}
+
+/// Largest number of controller responses kept in a [`ControllerCache`].
+const MAX_CACHED_RESPONSES: usize = 128;
+
+/// A response of the controller, along with its revision and when it was
+/// last used.
+#[derive(Debug)]
+struct CachedResponse {
+    revision: String,
+    data: Vec<u8>,
+    used: u64,
+}
+
+/// Responses of the controller to `GET` requests, along with their revision.
+///
+/// A cached response is sent again to the controller as a revision token, and
+/// is served as is when the controller answers that it was not modified.
+///
+/// At most [`MAX_CACHED_RESPONSES`] are kept, the least recently used ones
+/// being evicted first.
+#[derive(Debug, Default)]
+pub(crate) struct ControllerCache {
+    responses: BTreeMap<String, CachedResponse>,
+    /// Incremented on every use of the cache, to order the responses.
+    clock: u64,
+}
+
+impl ControllerCache {
+    /// The revision of the response cached under `key`.
+    pub(crate) fn revision(&self, key: &str) -> Option<&str> {
+        self.responses.get(key).map(|c| c.revision.as_str())
+    }
+
+    /// Cache the response `data` for `key`, evicting the least recently
+    /// used response if the cache is full.
+    fn insert(&mut self, key: String, revision: String, data: Vec<u8>) {
+        self.clock += 1;
+        if !self.responses.contains_key(&key) && self.responses.len() >= MAX_CACHED_RESPONSES {
+            let oldest = self
+                .responses
+                .iter()
+                .min_by_key(|(_, c)| c.used)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                self.responses.remove(&oldest);
+            }
+        }
+        let used = self.clock;
+        self.responses.insert(
+            key,
+            CachedResponse {
+                revision,
+                data,
+                used,
+            },
+        );
+    }
+
+    /// Update the cache with the response `res` to the `GET` request `re`
+    /// for `key` and return the up to date response.
+    ///
+    /// A cached response is addressed to `re` before being returned.
+    pub(crate) fn revalidate(&mut self, key: String, re: Id, res: Vec<u8>) -> Result<Vec<u8>> {
+        let header: Response = Decoder::new(&res).decode()?;
+        match (header.status(), header.revision()) {
+            (Some(Status::NotModified), _) => match self.responses.get_mut(&key) {
+                Some(cached) => {
+                    trace!(%key, "serving cached controller response");
+                    self.clock += 1;
+                    cached.used = self.clock;
+                    api::readdress(&cached.data, re)
+                }
+                None => Err(ApiError::generic(&format!("No cached response for {key}"))),
+            },
+            (Some(Status::Ok), Some(rev)) => {
+                self.insert(key, rev.to_string(), res.clone());
+                Ok(res)
+            }
+            _ => {
+                self.responses.remove(&key);
+                Ok(res)
+            }
+        }
+    }
+}
+
 mod node {
     use std::env;
     use std::str::FromStr;
+    use std::time::Instant;
 
     use minicbor::Encode;
     use rust_embed::EmbeddedFile;
 
-    use ockam_core::api::RequestBuilder;
-    use ockam_core::{self, route, Address, Result, Route};
-    use ockam_identity::{IdentityIdentifier, TrustIdentifierPolicy};
-    use ockam_node::api::request;
-    use ockam_node::Context;
+    use ockam_core::api::{assert_request_match, Method, RequestBuilder};
+    use ockam_core::errcode::{Kind, Origin};
+    use ockam_core::{self, route, Address, AsyncTryClone, Result, Route};
+    use ockam_identity::{Identity, IdentityIdentifier, TrustIdentifierPolicy};
+    use ockam_node::{tokio, Context};
+    use ockam_vault::Vault;
+    use tracing::Instrument;
 
-    use crate::cloud::OCKAM_CONTROLLER_IDENTITY_ID;
+    use crate::cloud::{ControllerRoute, OCKAM_CONTROLLER_IDENTITY_ID};
     use crate::error::ApiError;
+    use crate::lmdb::LmdbStorage;
+    use crate::nodes::service::checkpoint;
     use crate::nodes::{NodeManager, NodeManagerWorker};
-    use crate::StaticFiles;
+    use crate::{telemetry, StaticFiles};
 
     const TARGET: &str = "ockam_api::nodemanager::service";
 
@@ -111,45 +328,271 @@ mod node {
         pub(crate) fn controller_identity_id(&self) -> IdentityIdentifier {
             self.controller_identity_id.clone()
         }
+    }
 
-        /// Returns a secure channel between the node and the controller.
-        async fn controller_secure_channel(&mut self, route: impl Into<Route>) -> Result<Address> {
-            let identity = self.identity()?;
-            let route = route.into();
-            // Create secure channel for the given route using the orchestrator identity.
-            trace!(target: TARGET, %route, "Creating orchestrator secure channel");
-            let addr = identity
-                .create_secure_channel(
-                    route,
-                    TrustIdentifierPolicy::new(self.controller_identity_id()),
-                    &self.authenticated_storage,
-                )
-                .await?;
-            debug!(target: TARGET, %addr, "Orchestrator secure channel created");
-            Ok(addr)
-        }
+    /// Returns a secure channel between the node and the controller.
+    async fn controller_secure_channel(
+        identity: &Identity<Vault>,
+        storage: &LmdbStorage,
+        controller: IdentityIdentifier,
+        route: Route,
+    ) -> Result<Address> {
+        // Create secure channel for the given route using the orchestrator identity.
+        trace!(target: TARGET, %route, "Creating orchestrator secure channel");
+        let addr = identity
+            .create_secure_channel(route, TrustIdentifierPolicy::new(controller), storage)
+            .await?;
+        debug!(target: TARGET, %addr, "Orchestrator secure channel created");
+        Ok(addr)
     }
 
     impl NodeManagerWorker {
+        /// Send a request to a controller service.
+        ///
+        /// Attempts failing with a transient error are retried according to the
+        /// retry policy of the route, or else of the node. Requests which may
+        /// have reached the controller are only retried if they are idempotent,
+        /// i.e. `GET` and `DELETE` requests.
+        ///
+        /// Responses to `GET` requests which carry a revision are cached. The
+        /// revision is sent along with later requests for the same resource,
+        /// and the cached response is returned if the controller answers that
+        /// it was not modified.
         pub(super) async fn request_controller<T>(
             &mut self,
             ctx: &mut Context,
             label: &str,
             schema: impl Into<Option<&str>>,
-            cloud_route: impl Into<Route>,
+            cloud_route: impl Into<ControllerRoute>,
             api_service: &str,
             req: RequestBuilder<'_, T>,
         ) -> Result<Vec<u8>>
         where
             T: Encode<()>,
         {
-            let mut node_manger = self.get().write().await;
-            let cloud_route = cloud_route.into();
-            let sc = node_manger.controller_secure_channel(cloud_route).await?;
-            let route = route![&sc.to_string(), api_service];
-            let res = request(ctx, label, schema, route, req).await;
-            ctx.stop_worker(sc).await?;
+            let ControllerRoute { route, retry } = cloud_route.into();
+            // The node manager is not locked during the request, which may
+            // take as long as the retry policy allows.
+            let (policy, identity, storage, controller) = {
+                let node_manager = self.get().read().await;
+                (
+                    retry.unwrap_or(node_manager.controller_retry_policy),
+                    node_manager.identity()?.async_try_clone().await?,
+                    node_manager.authenticated_storage.async_try_clone().await?,
+                    node_manager.controller_identity_id(),
+                )
+            };
+
+            let idempotent = matches!(
+                req.header().method(),
+                Some(Method::Get) | Some(Method::Delete)
+            );
+            let cache_key = match req.header().method() {
+                Some(Method::Get) => Some(format!("{route}/{api_service}{}", req.header().path())),
+                _ => None,
+            };
+            let mut req = req;
+            if let Some(key) = &cache_key {
+                let node_manager = self.get().read().await;
+                if let Some(rev) = node_manager.controller_cache.revision(key) {
+                    req = req.revision(rev.to_string());
+                }
+            }
+
+            let req = telemetry::inject_trace_context(req);
+            let id = req.header().id();
+            let mut buf = Vec::new();
+            req.encode(&mut buf)?;
+            assert_request_match(schema, &buf);
+            trace! {
+                target:  TARGET,
+                id     = %req.header().id(),
+                method = ?req.header().method(),
+                path   = %req.header().path(),
+                body   = %req.header().has_body(),
+                "-> {label}"
+            };
+
+            let deadline = Instant::now() + policy.deadline();
+            let mut backoff = policy.backoff();
+            let mut attempt = 0;
+            loop {
+                // Stop before opening another channel to the controller
+                checkpoint()?;
+                // Whether the request may have reached the controller
+                let mut sent = false;
+                let res: Result<Vec<u8>> = async {
+                    let sc = controller_secure_channel(
+                        &identity,
+                        &storage,
+                        controller.clone(),
+                        route.clone(),
+                    )
+                    .await?;
+                    sent = true;
+                    let res = ctx
+                        .send_and_receive_with_timeout(
+                            route![&sc.to_string(), api_service],
+                            buf.clone(),
+                            policy.timeout_secs,
+                        )
+                        .await;
+                    ctx.stop_worker(sc).await?;
+                    res
+                }
+                .instrument(info_span!(target: TARGET, "controller_request", %label, %attempt))
+                .await;
+                match res {
+                    Err(err)
+                        if attempt < policy.retries
+                            && is_retryable(&err, sent, idempotent)
+                            && Instant::now() + backoff < deadline =>
+                    {
+                        attempt += 1;
+                        warn!(target: TARGET, %err, %attempt, ?backoff, "{label} failed, retrying");
+                        tokio::time::sleep(backoff).await;
+                        backoff *= 2;
+                    }
+                    Ok(res) => match cache_key {
+                        Some(key) => {
+                            let mut node_manager = self.get().write().await;
+                            return node_manager.controller_cache.revalidate(key, id, res);
+                        }
+                        None => return Ok(res),
+                    },
+                    res => return res,
+                }
+            }
+        }
+    }
+
+    /// Whether a failed controller request may succeed if retried.
+    fn is_transient(err: &ockam_core::Error) -> bool {
+        let code = err.code();
+        matches!(code.kind, Kind::Timeout | Kind::Io) || code.origin == Origin::Transport
+    }
+
+    /// Whether a failed controller request can be retried without risking
+    /// to apply it twice.
+    fn is_retryable(err: &ockam_core::Error, sent: bool, idempotent: bool) -> bool {
+        is_transient(err) && (idempotent || !sent)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn only_idempotent_requests_are_retried_once_sent() {
+            let timeout = ockam_core::Error::new(Origin::Node, Kind::Timeout, "timeout");
+            let invalid = ockam_core::Error::new(Origin::Application, Kind::Invalid, "invalid");
+
+            // No secure channel to the controller, nothing was sent
+            assert!(is_retryable(&timeout, false, false));
+            assert!(is_retryable(&timeout, false, true));
+            // The controller may have received the request
+            assert!(!is_retryable(&timeout, true, false));
+            assert!(is_retryable(&timeout, true, true));
+            // Errors which won't go away are never retried
+            assert!(!is_retryable(&invalid, false, true));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cddl_cat::validate_cbor_bytes;
+    use ockam_core::api::SCHEMA;
+
+    #[test]
+    fn path_segments_are_encoded() {
+        for email in [
+            "alice@example.com",
+            "a+b/../c?d#e%f@x.io",
+            "ünïcode@example.com",
+        ] {
+            let segment = path_segment(email);
+            assert!(!segment.contains(['/', '?', '#', '+']));
+            assert_eq!(decode_path_segment(&segment).unwrap(), email);
+        }
+        assert_eq!(path_segment("a+b@x.io"), "a%2Bb%40x.io");
+        assert!(decode_path_segment("a%2").is_err());
+        assert!(decode_path_segment("a%zz").is_err());
+        assert!(decode_path_segment("%FF").is_err());
+    }
+
+    #[test]
+    fn cached_responses_are_addressed_to_the_current_request() {
+        let mut cache = ControllerCache::default();
+        let key = "/project/1234".to_string();
+        let first = Id::fresh();
+        let res = Response::ok(first)
+            .revision("1")
+            .body("cached")
+            .to_vec()
+            .unwrap();
+        assert_eq!(
+            cache.revalidate(key.clone(), first, res.clone()).unwrap(),
             res
+        );
+        assert_eq!(cache.revision(&key), Some("1"));
+
+        let second = Id::fresh();
+        let not_modified = Response::not_modified(second).to_vec().unwrap();
+        let res = cache.revalidate(key.clone(), second, not_modified).unwrap();
+        let mut dec = Decoder::new(&res);
+        let header: Response = dec.decode().unwrap();
+        assert_eq!(header.re(), second);
+        assert_eq!(header.status(), Some(Status::Ok));
+        assert_eq!(dec.decode::<&str>().unwrap(), "cached");
+
+        // Responses without a revision are not cached
+        let third = Id::fresh();
+        let res = Response::ok(third).body("fresh").to_vec().unwrap();
+        cache.revalidate(key.clone(), third, res).unwrap();
+        assert_eq!(cache.revision(&key), None);
+    }
+
+    #[test]
+    fn least_recently_used_responses_are_evicted() {
+        let mut cache = ControllerCache::default();
+        let cache_response = |cache: &mut ControllerCache, i: usize| {
+            let id = Id::fresh();
+            let res = Response::ok(id).revision("1").body(i).to_vec().unwrap();
+            cache.revalidate(format!("/project/{i}"), id, res).unwrap();
+        };
+        for i in 0..MAX_CACHED_RESPONSES {
+            cache_response(&mut cache, i);
         }
+
+        // Serving the first response makes the second one the least recently used
+        let id = Id::fresh();
+        let not_modified = Response::not_modified(id).to_vec().unwrap();
+        cache
+            .revalidate("/project/0".to_string(), id, not_modified)
+            .unwrap();
+        cache_response(&mut cache, MAX_CACHED_RESPONSES);
+
+        assert_eq!(cache.responses.len(), MAX_CACHED_RESPONSES);
+        assert_eq!(cache.revision("/project/0"), Some("1"));
+        assert_eq!(cache.revision("/project/1"), None);
+        let last = format!("/project/{MAX_CACHED_RESPONSES}");
+        assert_eq!(cache.revision(&last), Some("1"));
+    }
+
+    #[test]
+    fn cloud_request_wrapper_schema() {
+        let route = MultiAddr::from_str("/dnsaddr/localhost/tcp/4000/service/api").unwrap();
+        let wrapper = BareCloudRequestWrapper::bare(&route);
+        let cbor = minicbor::to_vec(&wrapper).unwrap();
+        validate_cbor_bytes("cloud_request_wrapper", SCHEMA, &cbor).unwrap();
+
+        let policy = RetryPolicy::no_retry(Duration::from_secs(10));
+        let wrapper = BareCloudRequestWrapper::bare(&route).with_retry_policy(policy);
+        let cbor = minicbor::to_vec(&wrapper).unwrap();
+        validate_cbor_bytes("cloud_request_wrapper", SCHEMA, &cbor).unwrap();
+        let decoded: BareCloudRequestWrapper = minicbor::decode(&cbor).unwrap();
+        assert_eq!(decoded.retry, Some(policy));
     }
 }
@@ -194,7 +194,9 @@ mod node {
     use ockam_core::{self, Result};
     use ockam_node::Context;
 
-    use crate::cloud::{BareCloudRequestWrapper, CloudRequestWrapper};
+    use crate::cloud::{
+        decode_path_segment, path_segment, BareCloudRequestWrapper, CloudRequestWrapper,
+    };
     use crate::nodes::NodeManagerWorker;
 
     use super::*;
@@ -209,7 +211,7 @@ mod node {
             space_id: &str,
         ) -> Result<Vec<u8>> {
             let req_wrapper: CloudRequestWrapper<CreateProject> = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
             let req_body = req_wrapper.req;
 
             let label = "create_project";
@@ -233,7 +235,7 @@ mod node {
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
             let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
 
             let label = "list_projects";
             trace!(target: TARGET, "listing projects");
@@ -250,7 +252,7 @@ mod node {
             project_id: &str,
         ) -> Result<Vec<u8>> {
             let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
 
             let label = "get_project";
             trace!(target: TARGET, %project_id, "getting project");
@@ -268,7 +270,7 @@ mod node {
             project_id: &str,
         ) -> Result<Vec<u8>> {
             let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
 
             let label = "delete_project";
             trace!(target: TARGET, %space_id, %project_id, "deleting project");
@@ -285,7 +287,7 @@ mod node {
             project_id: &str,
         ) -> Result<Vec<u8>> {
             let req_wrapper: CloudRequestWrapper<AddEnroller> = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
             let req_body = req_wrapper.req;
 
             let label = "add_enroller";
@@ -303,7 +305,7 @@ mod node {
             project_id: &str,
         ) -> Result<Vec<u8>> {
             let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
 
             let label = "list_enrollers";
             trace!(target: TARGET, %project_id, "listing enrollers");
@@ -321,7 +323,7 @@ mod node {
             enroller_identity_id: &str,
         ) -> Result<Vec<u8>> {
             let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
 
             let label = "delete_enroller";
             trace!(target: TARGET, %project_id, %enroller_identity_id, "deleting enroller");
@@ -331,6 +333,46 @@ mod node {
             self.request_controller(ctx, label, None, cloud_route, "projects", req_builder)
                 .await
         }
+
+        pub(crate) async fn add_project_member(
+            &mut self,
+            ctx: &mut Context,
+            dec: &mut Decoder<'_>,
+            project_id: &str,
+            email: &str,
+        ) -> Result<Vec<u8>> {
+            let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
+            let cloud_route = req_wrapper.controller_route()?;
+
+            let email = decode_path_segment(email)?;
+            let label = "add_project_member";
+            trace!(target: TARGET, %project_id, %email, "adding project member");
+
+            let req_builder =
+                Request::put(format!("/v0/{project_id}/users/{}", path_segment(&email)));
+            self.request_controller(ctx, label, None, cloud_route, "projects", req_builder)
+                .await
+        }
+
+        pub(crate) async fn remove_project_member(
+            &mut self,
+            ctx: &mut Context,
+            dec: &mut Decoder<'_>,
+            project_id: &str,
+            email: &str,
+        ) -> Result<Vec<u8>> {
+            let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
+            let cloud_route = req_wrapper.controller_route()?;
+
+            let email = decode_path_segment(email)?;
+            let label = "remove_project_member";
+            trace!(target: TARGET, %project_id, %email, "removing project member");
+
+            let req_builder =
+                Request::delete(format!("/v0/{project_id}/users/{}", path_segment(&email)));
+            self.request_controller(ctx, label, None, cloud_route, "projects", req_builder)
+                .await
+        }
     }
 }
 
@@ -66,7 +66,9 @@ mod node {
     use ockam_node::Context;
 
     use crate::cloud::space::CreateSpace;
-    use crate::cloud::{BareCloudRequestWrapper, CloudRequestWrapper};
+    use crate::cloud::{
+        decode_path_segment, path_segment, BareCloudRequestWrapper, CloudRequestWrapper,
+    };
     use crate::nodes::NodeManagerWorker;
 
     const TARGET: &str = "ockam_api::cloud::space";
@@ -78,7 +80,7 @@ mod node {
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
             let req_wrapper: CloudRequestWrapper<CreateSpace> = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
             let req_body = req_wrapper.req;
 
             let label = "create_space";
@@ -102,7 +104,7 @@ mod node {
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
             let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
 
             let label = "list_spaces";
             trace!(target: TARGET, "listing spaces");
@@ -119,7 +121,7 @@ mod node {
             id: &str,
         ) -> Result<Vec<u8>> {
             let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
 
             let label = "get_space";
             trace!(target: TARGET, space = %id, space = %id, "getting space");
@@ -136,7 +138,7 @@ mod node {
             id: &str,
         ) -> Result<Vec<u8>> {
             let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
 
             let label = "delete_space";
             trace!(target: TARGET, space = %id, "deleting space");
@@ -145,6 +147,44 @@ mod node {
             self.request_controller(ctx, label, None, cloud_route, "spaces", req_builder)
                 .await
         }
+
+        pub(crate) async fn add_space_member(
+            &mut self,
+            ctx: &mut Context,
+            dec: &mut Decoder<'_>,
+            id: &str,
+            email: &str,
+        ) -> Result<Vec<u8>> {
+            let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
+            let cloud_route = req_wrapper.controller_route()?;
+
+            let email = decode_path_segment(email)?;
+            let label = "add_space_member";
+            trace!(target: TARGET, space = %id, %email, "adding space member");
+
+            let req_builder = Request::put(format!("/v0/{id}/users/{}", path_segment(&email)));
+            self.request_controller(ctx, label, None, cloud_route, "spaces", req_builder)
+                .await
+        }
+
+        pub(crate) async fn remove_space_member(
+            &mut self,
+            ctx: &mut Context,
+            dec: &mut Decoder<'_>,
+            id: &str,
+            email: &str,
+        ) -> Result<Vec<u8>> {
+            let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
+            let cloud_route = req_wrapper.controller_route()?;
+
+            let email = decode_path_segment(email)?;
+            let label = "remove_space_member";
+            trace!(target: TARGET, space = %id, %email, "removing space member");
+
+            let req_builder = Request::delete(format!("/v0/{id}/users/{}", path_segment(&email)));
+            self.request_controller(ctx, label, None, cloud_route, "spaces", req_builder)
+                .await
+        }
     }
 }
 
@@ -109,7 +109,7 @@ mod node {
             id: &str,
         ) -> Result<Vec<u8>> {
             let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
 
             let label = "unsubscribe";
             trace!(target: TARGET, subscription = %id, "unsubscribing");
@@ -126,7 +126,7 @@ mod node {
             id: &str,
         ) -> Result<Vec<u8>> {
             let req_wrapper: CloudRequestWrapper<String> = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
             let req_body = req_wrapper.req;
 
             let label = "list_sbuscriptions";
@@ -143,7 +143,7 @@ mod node {
             id: &str,
         ) -> Result<Vec<u8>> {
             let req_wrapper: CloudRequestWrapper<String> = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
             let req_body = req_wrapper.req;
 
             let label = "update_subscription_contact_info";
@@ -159,7 +159,7 @@ mod node {
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
             let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
 
             let label = "list_subscriptions";
             trace!(target: TARGET, "listing subscriptions");
@@ -175,7 +175,7 @@ mod node {
             id: &str,
         ) -> Result<Vec<u8>> {
             let req_wrapper: BareCloudRequestWrapper = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
 
             let label = "get_subscription";
             trace!(target: TARGET, subscription = %id, "getting subscription");
@@ -190,7 +190,7 @@ mod node {
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
             let req_wrapper: CloudRequestWrapper<ActivateSubscription> = dec.decode()?;
-            let cloud_route = req_wrapper.route()?;
+            let cloud_route = req_wrapper.controller_route()?;
             let req_body = req_wrapper.req;
 
             let label = "activate_subscription";
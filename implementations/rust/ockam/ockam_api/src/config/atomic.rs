@@ -70,8 +70,19 @@ impl<V: ConfigValues> AtomicUpdater<V> {
         let json: String = serde_json::to_string_pretty(&*inner)?;
         new_f.write_all(json.as_bytes())?;
 
-        // Then rename it over the existing config
-        fs::rename(&tmp_path, &self.config_path)?;
+        // Close the file before renaming it, Windows refuses to move open files
+        drop(new_f);
+
+        // Then rename it over the existing config.  On Windows the rename
+        // fails while another process has the config file open, so retry.
+        let mut attempts = 0;
+        while let Err(e) = fs::rename(&tmp_path, &self.config_path) {
+            attempts += 1;
+            if !cfg!(windows) || attempts >= 100 {
+                return Err(e.into());
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
 
         Ok(())
     }
@@ -11,6 +11,7 @@ use ockam_identity::{IdentityIdentifier, IdentityVault, PublicIdentity};
 use ockam_multiaddr::MultiAddr;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{
     env,
     path::{Path, PathBuf},
@@ -39,6 +40,10 @@ pub struct OckamConfig {
     #[serde(default = "default_lookup")]
     pub lookup: ConfigLookup,
 
+    /// Last successful listings fetched from the controller
+    #[serde(default)]
+    pub cache: ListingsCache,
+
     pub default_identity: Option<Vec<u8>>,
     pub default_vault_path: Option<PathBuf>,
     /// Default node
@@ -59,6 +64,7 @@ impl ConfigValues for OckamConfig {
             directories: Some(Self::directories()),
             nodes: BTreeMap::new(),
             lookup: default_lookup(),
+            cache: ListingsCache::default(),
             default_identity: None,
             default_vault_path: None,
             default: None,
@@ -159,6 +165,12 @@ impl NodeConfigOld {
         self.port
     }
 
+    /// Set the address of the API listener, once the node bound it
+    pub fn set_addr(&mut self, addr: InternetAddress) {
+        self.port = addr.port();
+        self.addr = addr;
+    }
+
     pub fn verbose(&self) -> u8 {
         self.verbose
     }
@@ -172,6 +184,42 @@ impl NodeConfigOld {
     }
 }
 
+/// Controller listings kept to be used when the controller is unreachable
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ListingsCache {
+    pub spaces: Option<CachedListing>,
+    pub projects: Option<CachedListing>,
+}
+
+/// A CBOR-encoded listing along with the time it was fetched
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CachedListing {
+    /// Seconds since the unix epoch.
+    pub fetched_at: u64,
+    pub data: HexByteVec,
+}
+
+impl CachedListing {
+    pub fn new(data: Vec<u8>) -> Self {
+        let fetched_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self {
+            fetched_at,
+            data: data.into(),
+        }
+    }
+
+    /// Seconds elapsed since the listing was fetched.
+    pub fn age(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs().saturating_sub(self.fetched_at))
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct AuthoritiesConfig {
     authorities: BTreeMap<IdentityIdentifier, Authority>,
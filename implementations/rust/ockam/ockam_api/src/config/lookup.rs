@@ -125,6 +125,17 @@ impl ConfigLookup {
         Some(m)
     }
 
+    pub fn spaces(&self) -> impl Iterator<Item = (String, SpaceLookup)> + '_ {
+        self.map.iter().filter_map(|(k, v)| {
+            if let LookupValue::Space(s) = v {
+                let name = k.strip_prefix("/space/").unwrap_or(k).to_string();
+                Some((name, s.clone()))
+            } else {
+                None
+            }
+        })
+    }
+
     pub fn projects(&self) -> impl Iterator<Item = (String, ProjectLookup)> + '_ {
         self.map.iter().filter_map(|(k, v)| {
             if let LookupValue::Project(p) = v {
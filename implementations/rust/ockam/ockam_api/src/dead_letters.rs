@@ -0,0 +1,119 @@
+//! Messages sent to local addresses which don't exist.
+//!
+//! The node hands them over to the worker at [`DEAD_LETTER_ADDRESS`], if
+//! there is one, instead of only failing to deliver them. The dead letter
+//! service keeps the most recent ones, which can be inspected via
+//! `GET /node/dead_letters`.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ockam::{Any, Context, Result, Routed, Worker};
+pub use ockam_node::DEAD_LETTER_ADDRESS;
+
+use crate::nodes::models::dead_letters::{DeadLetter, DeadLetterList};
+
+/// Number of dead letters retained by default.
+pub const DEFAULT_CAPACITY: usize = 100;
+
+#[derive(Default)]
+struct Retained {
+    letters: VecDeque<DeadLetter<'static>>,
+    dropped: u64,
+}
+
+/// The most recent dead letters, shared by the service and the node manager
+#[derive(Clone)]
+pub struct DeadLetters {
+    retained: Arc<Mutex<Retained>>,
+    capacity: usize,
+}
+
+impl DeadLetters {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            retained: Arc::new(Mutex::new(Retained::default())),
+            capacity,
+        }
+    }
+
+    /// Retain a dead letter, dropping the oldest one when full.
+    pub fn push(&self, letter: DeadLetter<'static>) {
+        let mut retained = self.retained.lock().unwrap_or_else(|e| e.into_inner());
+        if self.capacity == 0 {
+            retained.dropped += 1;
+            return;
+        }
+        if retained.letters.len() == self.capacity {
+            retained.letters.pop_front();
+            retained.dropped += 1;
+        }
+        retained.letters.push_back(letter);
+    }
+
+    pub fn list(&self) -> DeadLetterList<'static> {
+        let retained = self.retained.lock().unwrap_or_else(|e| e.into_inner());
+        DeadLetterList::new(retained.letters.iter().cloned().collect(), retained.dropped)
+    }
+}
+
+/// Records the messages the node could not deliver.
+pub struct DeadLetterService {
+    letters: DeadLetters,
+}
+
+impl DeadLetterService {
+    pub fn new(letters: DeadLetters) -> Self {
+        Self { letters }
+    }
+}
+
+#[ockam::worker]
+impl Worker for DeadLetterService {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(&mut self, _ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let letter = DeadLetter::new(
+            timestamp,
+            msg.onward_route().to_string(),
+            msg.return_route().to_string(),
+            msg.payload().len() as u64,
+        );
+        debug!(to = %letter.onward_route, from = %letter.return_route, "dead letter");
+        self.letters.push(letter);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::route;
+
+    #[ockam_macros::test]
+    async fn undeliverable_messages_are_retained(ctx: &mut Context) -> Result<()> {
+        let letters = DeadLetters::new(2);
+        ctx.start_worker(DEAD_LETTER_ADDRESS, DeadLetterService::new(letters.clone()))
+            .await?;
+
+        for to in ["missing_1", "missing_2", "missing_3"] {
+            let res = ctx.send(route![to, "next"], "hello".to_string()).await;
+            assert!(res.is_err());
+        }
+        ctx.sleep(std::time::Duration::from_millis(100)).await;
+
+        let list = letters.list();
+        assert_eq!(list.dropped, 1);
+        assert_eq!(list.letters.len(), 2);
+        assert_eq!(list.letters[0].onward_route, "0#missing_2 => 0#next");
+        assert_eq!(list.letters[1].return_route, ctx.address().to_string());
+
+        ctx.stop().await
+    }
+}
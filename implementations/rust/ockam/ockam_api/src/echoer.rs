@@ -1,8 +1,95 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
 use ockam::{Any, Context, Result, Routed, Worker};
 use ockam_core::NeutralMessage;
+use ockam_identity::IdentitySecureChannelLocalInfo;
 use tracing as log;
 
-pub struct Echoer;
+/// Length of the window the rate limits apply to
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Number of messages echoed back per second across all senders, when the
+/// rate of senders is limited without a total limit
+pub const DEFAULT_TOTAL_RATE_LIMIT: u32 = 1000;
+
+/// Echoes messages back to their sender.
+///
+/// By default anyone can have any payload echoed back. Since the echo
+/// service of a public node amplifies the traffic sent to it, the payload
+/// size and the rate of messages per sender can be limited, and senders can
+/// be required to use an authenticated secure channel.
+///
+/// Unauthenticated senders are told apart by their return route, which they
+/// choose freely, so the rate of all senders together is limited as well.
+#[derive(Default)]
+pub struct Echoer {
+    max_payload: Option<usize>,
+    rate_limit: Option<u32>,
+    total_rate_limit: Option<u32>,
+    authenticated: bool,
+    senders: HashMap<String, (Instant, u32)>,
+    total: Option<(Instant, u32)>,
+}
+
+impl Echoer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop messages whose payload is larger than `max` bytes.
+    pub fn with_max_payload(mut self, max: usize) -> Self {
+        self.max_payload = Some(max);
+        self
+    }
+
+    /// Drop the messages of a sender beyond `max` per second.
+    pub fn with_rate_limit(mut self, max: u32) -> Self {
+        self.rate_limit = Some(max);
+        self
+    }
+
+    /// Drop the messages of all senders beyond `max` per second.
+    pub fn with_total_rate_limit(mut self, max: u32) -> Self {
+        self.total_rate_limit = Some(max);
+        self
+    }
+
+    /// Drop messages which didn't arrive over an authenticated secure channel.
+    pub fn with_authenticated(mut self, authenticated: bool) -> Self {
+        self.authenticated = authenticated;
+        self
+    }
+
+    /// Count a message of `sender` and whether it is within the rate limit
+    fn admit(&mut self, sender: String) -> bool {
+        let total_max = match (self.rate_limit, self.total_rate_limit) {
+            (_, Some(max)) => max,
+            (Some(_), None) => DEFAULT_TOTAL_RATE_LIMIT,
+            (None, None) => return true,
+        };
+        let now = Instant::now();
+        let (start, total) = self.total.get_or_insert((now, 0));
+        if now.duration_since(*start) >= RATE_WINDOW {
+            *start = now;
+            *total = 0;
+        }
+        if *total >= total_max {
+            return false;
+        }
+        *total += 1;
+        let max = match self.rate_limit {
+            Some(max) => max,
+            None => return true,
+        };
+        // Forget the senders whose window is over
+        self.senders
+            .retain(|_, (start, _)| now.duration_since(*start) < RATE_WINDOW);
+        let (_, count) = self.senders.entry(sender).or_insert((now, 0));
+        *count += 1;
+        *count <= max
+    }
+}
 
 #[ockam::worker]
 impl Worker for Echoer {
@@ -10,8 +97,94 @@ impl Worker for Echoer {
     type Message = Any;
 
     async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let identity = IdentitySecureChannelLocalInfo::find_info(msg.local_message())
+            .ok()
+            .map(|info| info.their_identity_id().to_string());
+        if self.authenticated && identity.is_none() {
+            log::debug!(from = %msg.sender(), "not echoing an unauthenticated message");
+            return Ok(());
+        }
+        if let Some(max) = self.max_payload {
+            if msg.payload().len() > max {
+                log::debug!(
+                    from = %msg.sender(),
+                    size = msg.payload().len(),
+                    "not echoing a message over the size limit"
+                );
+                return Ok(());
+            }
+        }
+        let sender = identity.unwrap_or_else(|| msg.return_route().to_string());
+        if !self.admit(sender) {
+            log::debug!(from = %msg.sender(), "not echoing a message over the rate limit");
+            return Ok(());
+        }
         log::debug!(to = %msg.sender(), "echoing back");
         ctx.send(msg.return_route(), NeutralMessage::from(msg.take_payload()))
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::route;
+    use ockam_core::Address;
+
+    #[ockam_macros::test]
+    async fn limits_payload_size_and_rate(ctx: &mut Context) -> Result<()> {
+        let echoer = Echoer::new().with_max_payload(4).with_rate_limit(2);
+        ctx.start_worker("echo", echoer).await?;
+        let mut child = ctx.new_detached(Address::random_local()).await?;
+        let timeout = Duration::from_millis(200);
+
+        child.send(route!["echo"], b"too large".to_vec()).await?;
+        assert!(child
+            .receive_duration_timeout::<Vec<u8>>(timeout)
+            .await
+            .is_err());
+
+        for _ in 0..2 {
+            child.send(route!["echo"], b"ok".to_vec()).await?;
+            let reply = child.receive_duration_timeout::<Vec<u8>>(timeout).await?;
+            assert_eq!(reply.take().body(), b"ok".to_vec());
+        }
+        child.send(route!["echo"], b"ok".to_vec()).await?;
+        assert!(child
+            .receive_duration_timeout::<Vec<u8>>(timeout)
+            .await
+            .is_err());
+
+        ctx.stop().await
+    }
+
+    #[test]
+    fn limits_the_rate_of_all_senders() {
+        let mut echoer = Echoer::new().with_rate_limit(2).with_total_rate_limit(3);
+        assert!(echoer.admit("a".into()));
+        assert!(echoer.admit("b".into()));
+        assert!(echoer.admit("c".into()));
+        assert!(!echoer.admit("d".into()));
+
+        let mut echoer = Echoer::new().with_rate_limit(1);
+        let admitted = (0..2 * DEFAULT_TOTAL_RATE_LIMIT)
+            .filter(|i| echoer.admit(i.to_string()))
+            .count();
+        assert_eq!(admitted, DEFAULT_TOTAL_RATE_LIMIT as usize);
+    }
+
+    #[ockam_macros::test]
+    async fn requires_authenticated_sender(ctx: &mut Context) -> Result<()> {
+        ctx.start_worker("echo", Echoer::new().with_authenticated(true))
+            .await?;
+        let mut child = ctx.new_detached(Address::random_local()).await?;
+
+        child.send(route!["echo"], b"hello".to_vec()).await?;
+        let reply = child
+            .receive_duration_timeout::<Vec<u8>>(Duration::from_millis(200))
+            .await;
+        assert!(reply.is_err());
+
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,564 @@
+//! Transfer of files between nodes.
+//!
+//! A [`Client`] offers a file to a [`Server`] with its size and SHA-256
+//! digest, then sends it in chunks. The server writes the chunks to a
+//! partial file in its directory and only moves it in place once its
+//! digest has been checked. Partial files are kept when a transfer is
+//! interrupted, and offering the same file again resumes the transfer
+//! where it stopped.
+//!
+//! Files should be sent over a secure channel, e.g. to the route
+//! `/node/n2/service/api/service/file_transfer`.
+
+pub mod types;
+
+use core::fmt;
+use minicbor::{Decoder, Encode};
+use ockam_core::api::{self, assert_request_match, assert_response_match};
+use ockam_core::api::{Error, ErrorCode, Method, Request, RequestBuilder, Response, Status};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{self, Address, Result, Route, Routed, Worker};
+use ockam_identity::{IdentityIdentifier, IdentitySecureChannelLocalInfo};
+use ockam_node::tokio::task::{self, JoinError};
+use ockam_node::Context;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tracing::{trace, warn};
+
+use self::types::{Accepted, Chunk, Complete, Offer};
+
+/// Size of the chunks sent by a [`Client`].
+pub const CHUNK_SIZE: usize = 32 * 1024;
+
+/// Largest file a [`Server`] accepts unless configured otherwise, in bytes.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// A file being received.
+struct Transfer {
+    size: u64,
+    digest: Vec<u8>,
+    part: PathBuf,
+}
+
+/// Receives files into a directory.
+///
+/// Files are only accepted over a secure channel from one of the identities
+/// the server was created with, unless its worker is guarded by an access
+/// control (see [`Server::without_identity_check`]).
+pub struct Server {
+    dir: PathBuf,
+    transfers: HashMap<String, Transfer>,
+    /// `None` if the senders are checked by the access control of the worker.
+    authorized: Option<Vec<IdentityIdentifier>>,
+    overwrite: bool,
+    max_size: u64,
+}
+
+#[ockam_core::worker]
+impl Worker for Server {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        let mut dec = Decoder::new(m.as_body());
+        let req: Request = dec.decode()?;
+        if let Some(authorized) = &self.authorized {
+            let res = match IdentitySecureChannelLocalInfo::find_info(m.local_message()) {
+                Ok(i) if authorized.contains(i.their_identity_id()) => None,
+                Ok(i) => {
+                    warn! {
+                        target: "ockam_api::file_transfer::server",
+                        from = %i.their_identity_id(),
+                        "unauthorised sender"
+                    }
+                    Some(api::forbidden(&req, "unauthorized sender").to_vec()?)
+                }
+                Err(_) => Some(
+                    api::forbidden(&req, "secure channel required")
+                        .with_code(ErrorCode::SecureChannelRequired)
+                        .to_vec()?,
+                ),
+            };
+            if let Some(res) = res {
+                return c.send(m.return_route(), res).await;
+            }
+        }
+        let res = match self.on_request(&req, &mut dec).await {
+            Ok(res) => res,
+            Err(err) => {
+                warn!(target: "ockam_api::file_transfer::server", %err, "failed to handle request");
+                api::internal_error(&req, &err.to_string()).to_vec()?
+            }
+        };
+        c.send(m.return_route(), res).await
+    }
+}
+
+impl Server {
+    /// Receive files into `dir`.
+    ///
+    /// No file is accepted until senders are authorized with
+    /// [`Server::with_authorized`].
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Server {
+            dir: dir.into(),
+            transfers: HashMap::new(),
+            authorized: Some(Vec::new()),
+            overwrite: false,
+            max_size: DEFAULT_MAX_FILE_SIZE,
+        }
+    }
+
+    /// Accept files sent over secure channels by these identities.
+    pub fn with_authorized(mut self, authorized: Vec<IdentityIdentifier>) -> Self {
+        self.authorized = Some(authorized);
+        self
+    }
+
+    /// Accept files from any sender.
+    ///
+    /// Only for servers whose worker is guarded by an access control.
+    pub fn without_identity_check(mut self) -> Self {
+        self.authorized = None;
+        self
+    }
+
+    /// Replace existing files of the directory.
+    pub fn with_overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Refuse files larger than `max_size` bytes.
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    async fn on_request(&mut self, req: &Request<'_>, dec: &mut Decoder<'_>) -> Result<Vec<u8>> {
+        trace! {
+            target: "ockam_api::file_transfer::server",
+            id     = %req.id(),
+            method = ?req.method(),
+            path   = %req.path(),
+            body   = %req.has_body(),
+            "request"
+        }
+
+        let res = match req.method() {
+            Some(Method::Post) => match req.path_segments::<2>().as_slice() {
+                ["offer"] => {
+                    if let Err(e) = api::validate_body("file_offer", dec) {
+                        return Ok(api::invalid_body(req, &e).to_vec()?);
+                    }
+                    let offer: Offer = dec.decode()?;
+                    if !is_valid_name(offer.name()) {
+                        return Ok(api::bad_request(req, "invalid file name").to_vec()?);
+                    }
+                    if offer.digest().len() != 32 {
+                        return Ok(api::bad_request(req, "invalid digest").to_vec()?);
+                    }
+                    if offer.size() > self.max_size {
+                        return Ok(api::bad_request(req, "file is too large").to_vec()?);
+                    }
+                    let target = self.dir.join(offer.name());
+                    let part = self.dir.join(format!(
+                        ".{}.{}.part",
+                        offer.name(),
+                        hex::encode(&offer.digest()[..8])
+                    ));
+                    let (overwrite, size) = (self.overwrite, offer.size());
+                    let offset = blocking(move || {
+                        if !overwrite && target.exists() {
+                            return Ok(None);
+                        }
+                        match fs::metadata(&part) {
+                            Ok(m) if m.len() > size => {
+                                fs::remove_file(&part).map_err(map_io_err)?;
+                                Ok(Some((part, 0)))
+                            }
+                            Ok(m) => Ok(Some((part, m.len()))),
+                            Err(_) => Ok(Some((part, 0))),
+                        }
+                    })
+                    .await?;
+                    let (part, offset) = match offset {
+                        Some(o) => o,
+                        None => return Ok(api::bad_request(req, "file exists").to_vec()?),
+                    };
+                    self.transfers.insert(
+                        offer.name().to_string(),
+                        Transfer {
+                            size: offer.size(),
+                            digest: offer.digest().to_vec(),
+                            part,
+                        },
+                    );
+                    Response::ok(req.id())
+                        .body(Accepted::new(offset))
+                        .to_vec()?
+                }
+                ["chunk"] => {
+                    if let Err(e) = api::validate_body("file_chunk", dec) {
+                        return Ok(api::invalid_body(req, &e).to_vec()?);
+                    }
+                    let chunk: Chunk = dec.decode()?;
+                    let t = match self.transfers.get(chunk.name()) {
+                        Some(t) => t,
+                        None => return Ok(api::bad_request(req, "unknown transfer").to_vec()?),
+                    };
+                    let (part, size) = (t.part.clone(), t.size);
+                    let (offset, data) = (chunk.offset(), chunk.data().to_vec());
+                    let written = blocking(move || {
+                        let mut file = OpenOptions::new()
+                            .create(true)
+                            .append(true)
+                            .open(&part)
+                            .map_err(map_io_err)?;
+                        let len = file.metadata().map_err(map_io_err)?.len();
+                        if offset != len {
+                            return Ok(Err("unexpected chunk offset"));
+                        }
+                        if len + data.len() as u64 > size {
+                            return Ok(Err("chunk exceeds file size"));
+                        }
+                        file.write_all(&data).map_err(map_io_err)?;
+                        Ok(Ok(len + data.len() as u64))
+                    })
+                    .await?;
+                    match written {
+                        Ok(offset) => Response::ok(req.id())
+                            .body(Accepted::new(offset))
+                            .to_vec()?,
+                        Err(msg) => api::bad_request(req, msg).to_vec()?,
+                    }
+                }
+                ["complete"] => {
+                    if let Err(e) = api::validate_body("file_complete", dec) {
+                        return Ok(api::invalid_body(req, &e).to_vec()?);
+                    }
+                    let complete: Complete = dec.decode()?;
+                    let t = match self.transfers.remove(complete.name()) {
+                        Some(t) => t,
+                        None => return Ok(api::bad_request(req, "unknown transfer").to_vec()?),
+                    };
+                    let target = self.dir.join(complete.name());
+                    let overwrite = self.overwrite;
+                    let completed = blocking(move || {
+                        if file_digest(&t.part)?[..] != t.digest[..] {
+                            fs::remove_file(&t.part).map_err(map_io_err)?;
+                            return Ok(Err("integrity check failed"));
+                        }
+                        // The file may have been created since the offer.
+                        if !overwrite && target.exists() {
+                            return Ok(Err("file exists"));
+                        }
+                        fs::rename(&t.part, &target).map_err(map_io_err)?;
+                        Ok(Ok(()))
+                    })
+                    .await?;
+                    match completed {
+                        Ok(()) => Response::ok(req.id()).to_vec()?,
+                        Err(msg) => {
+                            warn! {
+                                target: "ockam_api::file_transfer::server",
+                                name = %complete.name(),
+                                "{msg}"
+                            }
+                            api::bad_request(req, msg).to_vec()?
+                        }
+                    }
+                }
+                _ => api::unknown_path(req).to_vec()?,
+            },
+            _ => api::invalid_method(req).to_vec()?,
+        };
+
+        Ok(res)
+    }
+}
+
+/// A file name must not point outside of the directory of the server.
+fn is_valid_name(name: &str) -> bool {
+    !name.starts_with('.') && Path::new(name).file_name() == Some(name.as_ref())
+}
+
+/// Compute the SHA-256 digest of a file.
+fn file_digest(path: &Path) -> Result<[u8; 32]> {
+    let mut file = File::open(path).map_err(map_io_err)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut buf).map_err(map_io_err)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n])
+    }
+    Ok(hasher.finalize().into())
+}
+
+/// Run file system operations off the async runtime.
+async fn blocking<T, F>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    task::spawn_blocking(f).await.map_err(map_join_err)?
+}
+
+fn map_io_err(err: std::io::Error) -> ockam_core::Error {
+    ockam_core::Error::new(Origin::Application, Kind::Io, err)
+}
+
+fn map_join_err(err: JoinError) -> ockam_core::Error {
+    ockam_core::Error::new(Origin::Application, Kind::Io, err)
+}
+
+pub struct Client {
+    ctx: Context,
+    route: Route,
+    buf: Vec<u8>,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("route", &self.route)
+            .finish()
+    }
+}
+
+impl Client {
+    pub async fn new(r: Route, ctx: &Context) -> Result<Self> {
+        let ctx = ctx.new_detached(Address::random_local()).await?;
+        Ok(Client {
+            ctx,
+            route: r,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Send the file at `path` under the given name.
+    ///
+    /// Returns the offset the transfer was resumed from, which is 0 unless
+    /// a previous transfer of the same file was interrupted.
+    pub async fn send_file(&mut self, path: &Path, name: &str) -> Result<u64> {
+        let path = path.to_path_buf();
+        let (mut file, size, digest) = blocking(move || {
+            let file = File::open(&path).map_err(map_io_err)?;
+            let size = file.metadata().map_err(map_io_err)?.len();
+            Ok((file, size, file_digest(&path)?))
+        })
+        .await?;
+
+        let req = Request::post("/offer").body(Offer::new(name, size, &digest[..]));
+        let resumed = self.accepted("offer", "file_offer", &req).await?;
+
+        let mut offset = resumed;
+        let mut data = vec![0; CHUNK_SIZE];
+        while offset < size {
+            let (f, d, n) = blocking(move || {
+                file.seek(SeekFrom::Start(offset)).map_err(map_io_err)?;
+                let n = file.read(&mut data).map_err(map_io_err)?;
+                Ok((file, data, n))
+            })
+            .await?;
+            file = f;
+            data = d;
+            if n == 0 {
+                break;
+            }
+            let req = Request::post("/chunk").body(Chunk::new(name, offset, &data[..n]));
+            offset = self.accepted("chunk", "file_chunk", &req).await?;
+        }
+
+        let req = Request::post("/complete").body(Complete::new(name));
+        self.buf = self.request("complete", "file_complete", &req).await?;
+        assert_response_match(None, &self.buf);
+        let mut d = Decoder::new(&self.buf);
+        let res = response("complete", &mut d)?;
+        if res.status() == Some(Status::Ok) {
+            Ok(resumed)
+        } else {
+            Err(error("complete", &res, &mut d))
+        }
+    }
+
+    /// Send a request answered with the offset of the next chunk.
+    async fn accepted<T>(
+        &mut self,
+        label: &str,
+        schema: &str,
+        req: &RequestBuilder<'_, T>,
+    ) -> Result<u64>
+    where
+        T: Encode<()>,
+    {
+        self.buf = self.request(label, schema, req).await?;
+        assert_response_match("file_accepted", &self.buf);
+        let mut d = Decoder::new(&self.buf);
+        let res = response(label, &mut d)?;
+        if res.status() == Some(Status::Ok) {
+            let a: Accepted = d.decode()?;
+            Ok(a.offset())
+        } else {
+            Err(error(label, &res, &mut d))
+        }
+    }
+
+    /// Encode request header and body (if any) and send the package to the server.
+    async fn request<T>(
+        &mut self,
+        label: &str,
+        schema: impl Into<Option<&str>>,
+        req: &RequestBuilder<'_, T>,
+    ) -> Result<Vec<u8>>
+    where
+        T: Encode<()>,
+    {
+        let mut buf = Vec::new();
+        req.encode(&mut buf)?;
+        assert_request_match(schema, &buf);
+        trace! {
+            target: "ockam_api::file_transfer::client",
+            id     = %req.header().id(),
+            method = ?req.header().method(),
+            path   = %req.header().path(),
+            body   = %req.header().has_body(),
+            "-> {label}"
+        };
+        let vec: Vec<u8> = self.ctx.send_and_receive(self.route.clone(), buf).await?;
+        Ok(vec)
+    }
+}
+
+/// Decode and log response header.
+fn response(label: &str, dec: &mut Decoder<'_>) -> Result<Response> {
+    let res: Response = dec.decode()?;
+    trace! {
+        target: "ockam_api::file_transfer::client",
+        re     = %res.re(),
+        id     = %res.id(),
+        status = ?res.status(),
+        body   = %res.has_body(),
+        "<- {label}"
+    }
+    Ok(res)
+}
+
+/// Decode, log and map response error to ockam_core error.
+fn error(label: &str, res: &Response, dec: &mut Decoder<'_>) -> ockam_core::Error {
+    if res.has_body() {
+        let err = match dec.decode::<Error>() {
+            Ok(e) => e,
+            Err(e) => return e.into(),
+        };
+        warn! {
+            target: "ockam_api::file_transfer::client",
+            id     = %res.id(),
+            re     = %res.re(),
+            status = ?res.status(),
+            error  = ?err.message(),
+            "<- {label}"
+        }
+        let msg = err.message().unwrap_or(label);
+        ockam_core::Error::new(Origin::Application, Kind::Protocol, msg)
+    } else {
+        ockam_core::Error::new(Origin::Application, Kind::Protocol, label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::route;
+    use ockam_identity::authenticated_storage::mem::InMemoryStorage;
+    use ockam_identity::{Identity, TrustEveryonePolicy};
+    use ockam_vault::Vault;
+
+    /// Start a server accepting files from a new identity and return a
+    /// client sending them as that identity.
+    async fn start(ctx: &Context, server: Server) -> Result<Client> {
+        let storage = InMemoryStorage::new();
+        let listener = Identity::create(ctx, &Vault::create()).await?;
+        listener
+            .create_secure_channel_listener("listener", TrustEveryonePolicy, &storage)
+            .await?;
+        let sender = Identity::create(ctx, &Vault::create()).await?;
+        let channel = sender
+            .create_secure_channel("listener", TrustEveryonePolicy, &storage)
+            .await?;
+        let server = server.with_authorized(vec![sender.identifier().clone()]);
+        ctx.start_worker("file_transfer", server).await?;
+        Client::new(route![channel, "file_transfer"], ctx).await
+    }
+
+    #[ockam_macros::test]
+    async fn transfers_are_resumed(ctx: &mut Context) -> Result<()> {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..3 * CHUNK_SIZE + 100).map(|i| i as u8).collect();
+        let path = src.path().join("data.bin");
+        fs::write(&path, &data).unwrap();
+
+        let mut client = start(ctx, Server::new(dst.path())).await?;
+
+        // Names pointing outside of the directory are rejected
+        assert!(client.send_file(&path, "../data.bin").await.is_err());
+
+        // An interrupted transfer leaves a partial file behind
+        let digest = file_digest(&path)?;
+        let part = dst
+            .path()
+            .join(format!(".data.bin.{}.part", hex::encode(&digest[..8])));
+        fs::write(&part, &data[..CHUNK_SIZE + 7]).unwrap();
+
+        let resumed = client.send_file(&path, "data.bin").await?;
+        assert_eq!(resumed, CHUNK_SIZE as u64 + 7);
+        assert_eq!(fs::read(dst.path().join("data.bin")).unwrap(), data);
+        assert!(!part.exists());
+
+        // A corrupted partial file fails the integrity check
+        let part = dst
+            .path()
+            .join(format!(".copy.bin.{}.part", hex::encode(&digest[..8])));
+        fs::write(&part, b"corrupted").unwrap();
+        assert!(client.send_file(&path, "copy.bin").await.is_err());
+        assert!(!part.exists());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn files_are_checked_before_being_accepted(ctx: &mut Context) -> Result<()> {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        let path = src.path().join("data.bin");
+        fs::write(&path, vec![1; 100]).unwrap();
+        let existing = dst.path().join("existing.bin");
+        fs::write(&existing, b"keep").unwrap();
+
+        let server = Server::new(dst.path()).with_max_size(99);
+        let mut client = start(ctx, server).await?;
+
+        // Files above the maximum size are refused
+        assert!(client.send_file(&path, "data.bin").await.is_err());
+        fs::write(&path, vec![1; 99]).unwrap();
+        client.send_file(&path, "data.bin").await?;
+
+        // Existing files are kept
+        assert!(client.send_file(&path, "existing.bin").await.is_err());
+        assert_eq!(fs::read(&existing).unwrap(), b"keep");
+
+        // Only authorized identities may send files, over a secure channel
+        let mut local = Client::new(route!["file_transfer"], ctx).await?;
+        assert!(local.send_file(&path, "other.bin").await.is_err());
+        assert!(!dst.path().join("other.bin").exists());
+
+        ctx.stop().await
+    }
+}
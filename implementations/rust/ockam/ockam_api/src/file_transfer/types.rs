@@ -0,0 +1,126 @@
+use minicbor::{Decode, Encode};
+use ockam_core::{CowBytes, CowStr};
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Offer to send a file.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Offer<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7310562>,
+    #[b(1)] name: CowStr<'a>,
+    #[n(2)] size: u64,
+    #[b(3)] digest: CowBytes<'a>,
+}
+
+impl<'a> Offer<'a> {
+    pub fn new(name: impl Into<CowStr<'a>>, size: u64, digest: impl Into<CowBytes<'a>>) -> Self {
+        Offer {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            name: name.into(),
+            size,
+            digest: digest.into(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// SHA-256 digest of the file content.
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+}
+
+/// Position from which the receiver expects the next chunk.
+#[derive(Debug, Clone, Copy, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Accepted {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4083917>,
+    #[n(1)] offset: u64,
+}
+
+impl Accepted {
+    pub fn new(offset: u64) -> Self {
+        Accepted {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            offset,
+        }
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+/// A part of an offered file.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Chunk<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2659048>,
+    #[b(1)] name: CowStr<'a>,
+    #[n(2)] offset: u64,
+    #[b(3)] data: CowBytes<'a>,
+}
+
+impl<'a> Chunk<'a> {
+    pub fn new(name: impl Into<CowStr<'a>>, offset: u64, data: impl Into<CowBytes<'a>>) -> Self {
+        Chunk {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            name: name.into(),
+            offset,
+            data: data.into(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Notice that all chunks of an offered file have been sent.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Complete<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<8812734>,
+    #[b(1)] name: CowStr<'a>,
+}
+
+impl<'a> Complete<'a> {
+    pub fn new(name: impl Into<CowStr<'a>>) -> Self {
+        Complete {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            name: name.into(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
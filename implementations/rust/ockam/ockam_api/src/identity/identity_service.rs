@@ -3,7 +3,7 @@ use core::convert::Infallible;
 use crate::identity::models::*;
 use minicbor::encode::Write;
 use minicbor::{Decoder, Encode};
-use ockam_core::api::{Error, Id, Method, Request, Response, Status};
+use ockam_core::api::{self, Error, Id, Method, Request, Response, SchemaMismatch, Status};
 use ockam_core::vault::Signature;
 use ockam_core::{Address, Result, Routed, Worker};
 use ockam_identity::change_history::IdentityHistoryComparison;
@@ -45,6 +45,15 @@ impl<V: IdentityVault> IdentityService<V> {
         Ok(())
     }
 
+    fn response_for_invalid_body<W>(req: &Request, e: &SchemaMismatch, enc: W) -> Result<()>
+    where
+        W: Write<Error = Infallible>,
+    {
+        api::invalid_body(req, e).encode(enc)?;
+
+        Ok(())
+    }
+
     fn ok_response<W, B>(req: &Request, body: Option<B>, enc: W) -> Result<()>
     where
         W: Write<Error = Infallible>,
@@ -117,6 +126,11 @@ impl<V: IdentityVault> IdentityService<V> {
                         return Self::response_for_bad_request(req, "empty body", enc);
                     }
 
+                    if let Err(e) =
+                        api::validate_body("validate_identity_change_history_request", dec)
+                    {
+                        return Self::response_for_invalid_body(req, &e, enc);
+                    }
                     let args = dec.decode::<ValidateIdentityChangeHistoryRequest>()?;
                     let identity =
                         Identity::import(&self.ctx, args.identity(), &self.vault).await?;
@@ -132,6 +146,9 @@ impl<V: IdentityVault> IdentityService<V> {
                         return Self::response_for_bad_request(req, "empty body", enc);
                     }
 
+                    if let Err(e) = api::validate_body("create_signature_request", dec) {
+                        return Self::response_for_invalid_body(req, &e, enc);
+                    }
                     let args = dec.decode::<CreateSignatureRequest>()?;
                     let identity =
                         Identity::import(&self.ctx, args.identity(), &self.vault).await?;
@@ -147,6 +164,9 @@ impl<V: IdentityVault> IdentityService<V> {
                         return Self::response_for_bad_request(req, "empty body", enc);
                     }
 
+                    if let Err(e) = api::validate_body("verify_signature_request", dec) {
+                        return Self::response_for_invalid_body(req, &e, enc);
+                    }
                     let args = dec.decode::<VerifySignatureRequest>()?;
                     let peer_identity =
                         PublicIdentity::import(args.signer_identity(), &self.vault).await?;
@@ -169,6 +189,11 @@ impl<V: IdentityVault> IdentityService<V> {
                         return Self::response_for_bad_request(req, "empty body", enc);
                     }
 
+                    if let Err(e) =
+                        api::validate_body("compare_identity_change_history_request", dec)
+                    {
+                        return Self::response_for_invalid_body(req, &e, enc);
+                    }
                     let args = dec.decode::<CompareIdentityChangeHistoryRequest>()?;
 
                     let current_identity =
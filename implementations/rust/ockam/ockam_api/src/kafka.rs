@@ -0,0 +1,24 @@
+//! Kafka-aware portals
+//!
+//! A Kafka client bootstraps from a single broker and then connects directly
+//! to the brokers advertised by the cluster, which plain TCP portals can not
+//! reach. The Kafka portal services sit in the middle of portals and rewrite
+//! the advertised brokers:
+//!
+//! - the outlet service, next to the cluster, creates an outlet to every
+//!   advertised broker;
+//! - the inlet service, next to the clients, creates an inlet for every
+//!   advertised broker and advertises it instead.
+
+mod interceptor;
+mod protocol;
+
+pub(crate) use interceptor::{KafkaPortalInterceptor, Mode};
+
+/// Address of the outlet to the bootstrap server, next to the Kafka outlet service.
+pub const KAFKA_BOOTSTRAP_ADDRESS: &str = "kafka_bootstrap";
+
+/// Address of the outlet to a broker, next to the Kafka outlet service.
+pub fn broker_outlet_address(node_id: i32) -> String {
+    format!("kafka_broker_{node_id}")
+}
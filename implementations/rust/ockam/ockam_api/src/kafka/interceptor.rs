@@ -0,0 +1,316 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use ockam::tcp::PortalMessage;
+use ockam::TcpTransport;
+use ockam_core::{
+    async_trait, Address, AllowAll, Any, AsyncTryClone, Decodable, Encodable, LocalInfo,
+    LocalMessage, Mailbox, Mailboxes, Result, Route, Routed, TransportMessage, Worker,
+};
+use ockam_node::{Context, WorkerBuilder};
+
+use super::protocol::{self, Broker, FrameBuffer, RequestHeader};
+use super::{broker_outlet_address, KAFKA_BOOTSTRAP_ADDRESS};
+
+/// Largest payload forwarded in a single portal message.
+const MAX_PAYLOAD_SIZE: usize = 48 * 1024;
+
+/// What to do with the brokers advertised by the Kafka cluster.
+pub(crate) enum Mode {
+    /// Advertise every broker as a local inlet.
+    Inlet {
+        /// Host advertised to Kafka clients.
+        host: String,
+        /// Route to the remote Kafka outlet service.
+        outlet_route: Route,
+        /// Ports of the inlets created for each broker.
+        inlets: BTreeMap<i32, (Address, u16)>,
+    },
+    /// Create an outlet to every broker.
+    Outlet {
+        /// Addresses of the outlets created for each broker.
+        outlets: BTreeMap<i32, String>,
+    },
+}
+
+impl Mode {
+    pub(crate) fn inlet(bind_addr: SocketAddr, outlet_route: Route) -> Self {
+        let host = if bind_addr.ip().is_unspecified() {
+            "127.0.0.1".to_string()
+        } else {
+            bind_addr.ip().to_string()
+        };
+        Mode::Inlet {
+            host,
+            outlet_route,
+            inlets: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn outlet() -> Self {
+        Mode::Outlet {
+            outlets: BTreeMap::new(),
+        }
+    }
+}
+
+/// Kafka protocol state of a portal connection.
+#[derive(Default)]
+struct Connection {
+    requests: FrameBuffer,
+    responses: FrameBuffer,
+    pending: BTreeMap<i32, RequestHeader>,
+}
+
+/// A worker sitting in the middle of Kafka portals.
+///
+/// Messages from inlets are sent to its main address and forwarded with its
+/// second address prepended to their return route, so that the replies of
+/// the outlets go through it as well. Responses advertising brokers are
+/// rewritten according to the [`Mode`].
+pub(crate) struct KafkaPortalInterceptor {
+    inbound: Address,
+    outbound: Address,
+    tcp: TcpTransport,
+    mode: Mode,
+    /// Connections indexed by their route back to the inlet.
+    connections: BTreeMap<String, Connection>,
+}
+
+impl KafkaPortalInterceptor {
+    pub(crate) async fn start(
+        ctx: &Context,
+        address: Address,
+        tcp: &TcpTransport,
+        mode: Mode,
+    ) -> Result<()> {
+        let outbound = Address::random_local();
+        let worker = Self {
+            inbound: address.clone(),
+            outbound: outbound.clone(),
+            tcp: tcp.async_try_clone().await?,
+            mode,
+            connections: BTreeMap::new(),
+        };
+        let mailboxes = Mailboxes::new(
+            Mailbox::new(address, Arc::new(AllowAll)),
+            vec![Mailbox::new(outbound, Arc::new(AllowAll))],
+        );
+        WorkerBuilder::with_mailboxes(mailboxes, worker)
+            .start(ctx)
+            .await
+    }
+
+    /// Forward a client request towards the Kafka cluster.
+    async fn handle_request(
+        &mut self,
+        ctx: &Context,
+        msg: TransportMessage,
+        local_info: Vec<LocalInfo>,
+    ) -> Result<()> {
+        let mut onward_route = msg.onward_route;
+        onward_route.step()?;
+        let key = msg.return_route.to_string();
+        let return_route = prepend(self.outbound.clone(), msg.return_route);
+
+        let payload = match PortalMessage::decode(&msg.payload)? {
+            PortalMessage::Payload(payload) => payload,
+            PortalMessage::Disconnect => {
+                self.connections.remove(&key);
+                return forward(ctx, onward_route, return_route, msg.payload, local_info).await;
+            }
+            // The frames have to stay readable, so no compression is
+            // negotiated through the interceptor
+            PortalMessage::CompressedPing(_) => {
+                let ping = PortalMessage::Ping.encode()?;
+                return forward(ctx, onward_route, return_route, ping, local_info).await;
+            }
+            _ => return forward(ctx, onward_route, return_route, msg.payload, local_info).await,
+        };
+
+        let conn = self.connections.entry(key).or_default();
+        conn.requests.push(&payload);
+        let mut out = Vec::new();
+        while let Some(frame) = conn.requests.next_frame()? {
+            if let Some(header) = RequestHeader::parse(&frame) {
+                if header.has_brokers() {
+                    conn.pending.insert(header.correlation_id, header);
+                }
+            }
+            out.extend(protocol::encode_frame(&frame));
+        }
+        send_payload(ctx, onward_route, return_route, &out, local_info).await
+    }
+
+    /// Forward a broker response back to the client, rewriting advertised brokers.
+    async fn handle_response(
+        &mut self,
+        ctx: &Context,
+        msg: TransportMessage,
+        local_info: Vec<LocalInfo>,
+    ) -> Result<()> {
+        let mut onward_route = msg.onward_route;
+        onward_route.step()?;
+        let key = onward_route.to_string();
+        let return_route = prepend(self.inbound.clone(), msg.return_route);
+
+        let payload = match PortalMessage::decode(&msg.payload)? {
+            PortalMessage::Payload(payload) => payload,
+            PortalMessage::Disconnect => {
+                self.connections.remove(&key);
+                return forward(ctx, onward_route, return_route, msg.payload, local_info).await;
+            }
+            _ => return forward(ctx, onward_route, return_route, msg.payload, local_info).await,
+        };
+
+        let mut frames = Vec::new();
+        {
+            let conn = self.connections.entry(key.clone()).or_default();
+            conn.responses.push(&payload);
+            while let Some(frame) = conn.responses.next_frame()? {
+                let header =
+                    protocol::correlation_id(&frame).and_then(|id| conn.pending.remove(&id));
+                frames.push((frame, header));
+            }
+        }
+
+        let mut out = Vec::new();
+        for (frame, header) in frames {
+            match header {
+                Some(header) => {
+                    let frame = self.rewrite(&frame, &header).await?;
+                    out.extend(protocol::encode_frame(&frame))
+                }
+                None => out.extend(protocol::encode_frame(&frame)),
+            }
+        }
+        send_payload(ctx, onward_route, return_route, &out, local_info).await
+    }
+
+    async fn rewrite(&mut self, frame: &[u8], header: &RequestHeader) -> Result<Vec<u8>> {
+        let mut brokers = Vec::new();
+        protocol::rewrite_response(frame, header, |b| {
+            brokers.push(b.clone());
+            None
+        })?;
+        let advertised = self.advertise(&brokers).await?;
+        protocol::rewrite_response(frame, header, |b| advertised.get(&b.node_id).cloned())
+    }
+
+    /// Make the given brokers reachable and return the addresses to advertise.
+    async fn advertise(&mut self, brokers: &[Broker]) -> Result<BTreeMap<i32, (String, i32)>> {
+        let mut advertised = BTreeMap::new();
+        match &mut self.mode {
+            Mode::Inlet {
+                host,
+                outlet_route,
+                inlets,
+            } => {
+                for b in brokers {
+                    if !inlets.contains_key(&b.node_id) {
+                        let route: Route = prepend(self.inbound.clone(), outlet_route.clone())
+                            .modify()
+                            .append(broker_outlet_address(b.node_id))
+                            .into();
+                        let (addr, bind) =
+                            self.tcp.create_inlet(format!("{host}:0"), route).await?;
+                        debug!(node_id = %b.node_id, %bind, "created kafka broker inlet");
+                        inlets.insert(b.node_id, (addr, bind.port()));
+                    }
+                    let port = inlets[&b.node_id].1;
+                    advertised.insert(b.node_id, (host.clone(), i32::from(port)));
+                }
+            }
+            Mode::Outlet { outlets } => {
+                for b in brokers {
+                    let peer = format!("{}:{}", b.host, b.port);
+                    match outlets.get(&b.node_id) {
+                        Some(p) if *p == peer => continue,
+                        Some(_) => {
+                            self.tcp
+                                .stop_outlet(broker_outlet_address(b.node_id))
+                                .await?
+                        }
+                        None => {}
+                    }
+                    self.tcp
+                        .create_outlet(broker_outlet_address(b.node_id), peer.clone())
+                        .await?;
+                    debug!(node_id = %b.node_id, %peer, "created kafka broker outlet");
+                    outlets.insert(b.node_id, peer);
+                }
+            }
+        }
+        Ok(advertised)
+    }
+}
+
+#[async_trait]
+impl Worker for KafkaPortalInterceptor {
+    type Context = Context;
+    type Message = Any;
+
+    async fn shutdown(&mut self, _ctx: &mut Context) -> Result<()> {
+        match &self.mode {
+            Mode::Inlet { inlets, .. } => {
+                for (addr, _) in inlets.values() {
+                    let _ = self.tcp.stop_inlet(addr.clone()).await;
+                }
+            }
+            Mode::Outlet { outlets } => {
+                let _ = self.tcp.stop_outlet(KAFKA_BOOTSTRAP_ADDRESS).await;
+                for node_id in outlets.keys() {
+                    let _ = self.tcp.stop_outlet(broker_outlet_address(*node_id)).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let recipient = msg.msg_addr();
+        let (msg, local_info) = msg.into_local_message().dissolve();
+        if recipient == self.outbound {
+            self.handle_response(ctx, msg, local_info).await
+        } else {
+            self.handle_request(ctx, msg, local_info).await
+        }
+    }
+}
+
+fn prepend(addr: Address, mut route: Route) -> Route {
+    route.modify().prepend(addr).into()
+}
+
+async fn forward(
+    ctx: &Context,
+    onward_route: Route,
+    return_route: Route,
+    payload: Vec<u8>,
+    local_info: Vec<LocalInfo>,
+) -> Result<()> {
+    let msg = TransportMessage::v1(onward_route, return_route, payload);
+    ctx.forward(LocalMessage::new(msg, local_info)).await
+}
+
+async fn send_payload(
+    ctx: &Context,
+    onward_route: Route,
+    return_route: Route,
+    payload: &[u8],
+    local_info: Vec<LocalInfo>,
+) -> Result<()> {
+    for chunk in payload.chunks(MAX_PAYLOAD_SIZE) {
+        let msg = PortalMessage::Payload(chunk.to_vec()).encode()?;
+        forward(
+            ctx,
+            onward_route.clone(),
+            return_route.clone(),
+            msg,
+            local_info.clone(),
+        )
+        .await?;
+    }
+    Ok(())
+}
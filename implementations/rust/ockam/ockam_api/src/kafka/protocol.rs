@@ -0,0 +1,472 @@
+//! Just enough of the Kafka wire protocol to rewrite broker addresses.
+//!
+//! Kafka clients bootstrap from one broker and then connect to the brokers
+//! advertised in `Metadata` and `FindCoordinator` responses. Those addresses
+//! must be replaced by addresses reachable through a portal.
+
+use ockam_core::Result;
+
+use crate::error::ApiError;
+
+/// `Metadata` request API key.
+pub(crate) const METADATA: i16 = 3;
+
+/// `FindCoordinator` request API key.
+pub(crate) const FIND_COORDINATOR: i16 = 10;
+
+/// Largest frame accepted, Kafka brokers default to 100 MiB.
+const MAX_FRAME_SIZE: usize = 100 * 1024 * 1024;
+
+/// The part of a request header needed to interpret the response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RequestHeader {
+    pub(crate) api_key: i16,
+    pub(crate) api_version: i16,
+    pub(crate) correlation_id: i32,
+}
+
+impl RequestHeader {
+    /// Parse the header of a request frame (without its size prefix).
+    pub(crate) fn parse(frame: &[u8]) -> Option<Self> {
+        if frame.len() < 8 {
+            return None;
+        }
+        Some(Self {
+            api_key: i16::from_be_bytes([frame[0], frame[1]]),
+            api_version: i16::from_be_bytes([frame[2], frame[3]]),
+            correlation_id: i32::from_be_bytes([frame[4], frame[5], frame[6], frame[7]]),
+        })
+    }
+
+    /// Whether the response to this request advertises broker addresses.
+    pub(crate) fn has_brokers(&self) -> bool {
+        matches!(self.api_key, METADATA | FIND_COORDINATOR)
+    }
+}
+
+/// The correlation id of a response frame (without its size prefix).
+pub(crate) fn correlation_id(frame: &[u8]) -> Option<i32> {
+    frame
+        .get(..4)
+        .map(|b| i32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// A broker address advertised in a response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Broker {
+    pub(crate) node_id: i32,
+    pub(crate) host: String,
+    pub(crate) port: i32,
+}
+
+/// Splits a byte stream into size-prefixed frames.
+#[derive(Debug, Default)]
+pub(crate) struct FrameBuffer {
+    buf: Vec<u8>,
+}
+
+impl FrameBuffer {
+    pub(crate) fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes)
+    }
+
+    /// Take the next complete frame, without its size prefix.
+    pub(crate) fn next_frame(&mut self) -> Result<Option<Vec<u8>>> {
+        if self.buf.len() < 4 {
+            return Ok(None);
+        }
+        let size = i32::from_be_bytes([self.buf[0], self.buf[1], self.buf[2], self.buf[3]]);
+        let size = usize::try_from(size)
+            .ok()
+            .filter(|s| *s <= MAX_FRAME_SIZE)
+            .ok_or_else(|| ApiError::message(format!("invalid kafka frame size: {size}")))?;
+        if self.buf.len() < 4 + size {
+            return Ok(None);
+        }
+        let frame = self.buf[4..4 + size].to_vec();
+        self.buf.drain(..4 + size);
+        Ok(Some(frame))
+    }
+}
+
+/// Prefix a frame with its size.
+pub(crate) fn encode_frame(frame: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(4 + frame.len());
+    out.extend_from_slice(&(frame.len() as i32).to_be_bytes());
+    out.extend_from_slice(frame);
+    out
+}
+
+/// Rewrite the brokers advertised in a response frame (without its size prefix).
+///
+/// `f` is called for every advertised broker and returns the address to
+/// advertise instead, if any. Responses to other requests are returned as is.
+pub(crate) fn rewrite_response<F>(frame: &[u8], req: &RequestHeader, f: F) -> Result<Vec<u8>>
+where
+    F: FnMut(&Broker) -> Option<(String, i32)>,
+{
+    let mut rw = Rewriter::new(frame, f);
+    match req.api_key {
+        METADATA => rw.metadata(req.api_version)?,
+        FIND_COORDINATOR => rw.find_coordinator(req.api_version)?,
+        _ => return Ok(frame.to_vec()),
+    }
+    Ok(rw.finish())
+}
+
+/// Copies a response while replacing broker hosts and ports.
+struct Rewriter<'a, F> {
+    input: &'a [u8],
+    pos: usize,
+    output: Vec<u8>,
+    f: F,
+}
+
+impl<'a, F> Rewriter<'a, F>
+where
+    F: FnMut(&Broker) -> Option<(String, i32)>,
+{
+    fn new(input: &'a [u8], f: F) -> Self {
+        Self {
+            input,
+            pos: 0,
+            output: Vec::with_capacity(input.len()),
+            f,
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.output.extend_from_slice(&self.input[self.pos..]);
+        self.output
+    }
+
+    /// Metadata response, versions 0 to 12.
+    fn metadata(&mut self, version: i16) -> Result<()> {
+        let flexible = version >= 9;
+        self.header(flexible)?;
+        if version >= 3 {
+            self.copy(4)?; // throttle_time_ms
+        }
+        let brokers = self.copy_array_len(flexible)?;
+        for _ in 0..brokers {
+            let node_id = self.copy_i32()?;
+            self.broker_address(node_id, flexible)?;
+            if version >= 1 {
+                self.copy_string(flexible)?; // rack
+            }
+            if flexible {
+                self.copy_tagged_fields()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// FindCoordinator response, versions 0 to 4.
+    fn find_coordinator(&mut self, version: i16) -> Result<()> {
+        let flexible = version >= 3;
+        self.header(flexible)?;
+        if version >= 1 {
+            self.copy(4)?; // throttle_time_ms
+        }
+        if version >= 4 {
+            let coordinators = self.copy_array_len(true)?;
+            for _ in 0..coordinators {
+                self.copy_string(true)?; // key
+                let node_id = self.copy_i32()?;
+                self.broker_address(node_id, true)?;
+                self.copy(2)?; // error_code
+                self.copy_string(true)?; // error_message
+                self.copy_tagged_fields()?;
+            }
+            return Ok(());
+        }
+        self.copy(2)?; // error_code
+        if version >= 1 {
+            self.copy_string(flexible)?; // error_message
+        }
+        let node_id = self.copy_i32()?;
+        self.broker_address(node_id, flexible)
+    }
+
+    fn header(&mut self, flexible: bool) -> Result<()> {
+        self.copy(4)?; // correlation_id
+        if flexible {
+            self.copy_tagged_fields()?;
+        }
+        Ok(())
+    }
+
+    /// Read a broker host and port and write their replacement.
+    fn broker_address(&mut self, node_id: i32, flexible: bool) -> Result<()> {
+        let host = self.read_string(flexible)?.unwrap_or_default();
+        let port = self.read_i32()?;
+        let broker = Broker {
+            node_id,
+            host,
+            port,
+        };
+        let (host, port) = (self.f)(&broker).unwrap_or((broker.host, broker.port));
+        self.write_string(&host, flexible);
+        self.output.extend_from_slice(&port.to_be_bytes());
+        Ok(())
+    }
+
+    fn read(&mut self, n: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .input
+            .get(self.pos..self.pos + n)
+            .ok_or_else(|| ApiError::generic("truncated kafka response"))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn copy(&mut self, n: usize) -> Result<()> {
+        let bytes = self.read(n)?;
+        self.output.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn read_i16(&mut self) -> Result<i16> {
+        let b = self.read(2)?;
+        Ok(i16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn read_i32(&mut self) -> Result<i32> {
+        let b = self.read(4)?;
+        Ok(i32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn copy_i32(&mut self) -> Result<i32> {
+        let n = self.read_i32()?;
+        self.output.extend_from_slice(&n.to_be_bytes());
+        Ok(n)
+    }
+
+    fn read_uvarint(&mut self) -> Result<u32> {
+        let mut n: u32 = 0;
+        for shift in (0..35).step_by(7) {
+            let b = self.read(1)?[0];
+            n |= u32::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(ApiError::generic("invalid kafka varint"))
+    }
+
+    fn copy_uvarint(&mut self) -> Result<u32> {
+        let start = self.pos;
+        let n = self.read_uvarint()?;
+        self.output.extend_from_slice(&self.input[start..self.pos]);
+        Ok(n)
+    }
+
+    fn write_uvarint(&mut self, mut n: u32) {
+        while n >= 0x80 {
+            self.output.push((n as u8) | 0x80);
+            n >>= 7;
+        }
+        self.output.push(n as u8)
+    }
+
+    /// Copy an array length, a null array counts as empty.
+    fn copy_array_len(&mut self, compact: bool) -> Result<usize> {
+        if compact {
+            Ok(self.copy_uvarint()?.saturating_sub(1) as usize)
+        } else {
+            Ok(self.copy_i32()?.max(0) as usize)
+        }
+    }
+
+    /// Read a (nullable) string.
+    fn read_string(&mut self, compact: bool) -> Result<Option<String>> {
+        let len = if compact {
+            match self.read_uvarint()? {
+                0 => return Ok(None),
+                n => (n - 1) as usize,
+            }
+        } else {
+            match self.read_i16()? {
+                n if n < 0 => return Ok(None),
+                n => n as usize,
+            }
+        };
+        let bytes = self.read(len)?;
+        String::from_utf8(bytes.to_vec())
+            .map(Some)
+            .map_err(|_| ApiError::generic("invalid kafka string"))
+    }
+
+    fn copy_string(&mut self, compact: bool) -> Result<()> {
+        let start = self.pos;
+        self.read_string(compact)?;
+        self.output.extend_from_slice(&self.input[start..self.pos]);
+        Ok(())
+    }
+
+    fn write_string(&mut self, s: &str, compact: bool) {
+        if compact {
+            self.write_uvarint(s.len() as u32 + 1)
+        } else {
+            self.output
+                .extend_from_slice(&(s.len() as i16).to_be_bytes())
+        }
+        self.output.extend_from_slice(s.as_bytes())
+    }
+
+    fn copy_tagged_fields(&mut self) -> Result<()> {
+        let n = self.copy_uvarint()?;
+        for _ in 0..n {
+            self.copy_uvarint()?; // tag
+            let size = self.copy_uvarint()?;
+            self.copy(size as usize)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> Vec<u8> {
+        let mut v = (s.len() as i16).to_be_bytes().to_vec();
+        v.extend_from_slice(s.as_bytes());
+        v
+    }
+
+    fn compact_string(s: &str) -> Vec<u8> {
+        let mut v = vec![s.len() as u8 + 1];
+        v.extend_from_slice(s.as_bytes());
+        v
+    }
+
+    fn rewrite(b: &Broker) -> Option<(String, i32)> {
+        Some(("127.0.0.1".to_string(), 10000 + b.node_id))
+    }
+
+    #[test]
+    fn metadata_v1() {
+        let req = RequestHeader {
+            api_key: METADATA,
+            api_version: 1,
+            correlation_id: 7,
+        };
+        let mut frame = 7i32.to_be_bytes().to_vec();
+        frame.extend(1i32.to_be_bytes()); // one broker
+        frame.extend(2i32.to_be_bytes());
+        frame.extend(string("broker-2.example.com"));
+        frame.extend(9092i32.to_be_bytes());
+        frame.extend((-1i16).to_be_bytes()); // no rack
+        frame.extend(2i32.to_be_bytes()); // controller id
+        frame.extend(0i32.to_be_bytes()); // no topics
+
+        let mut seen = Vec::new();
+        let out = rewrite_response(&frame, &req, |b| {
+            seen.push(b.clone());
+            rewrite(b)
+        })
+        .unwrap();
+        assert_eq!(
+            seen,
+            vec![Broker {
+                node_id: 2,
+                host: "broker-2.example.com".to_string(),
+                port: 9092
+            }]
+        );
+
+        let mut expected = 7i32.to_be_bytes().to_vec();
+        expected.extend(1i32.to_be_bytes());
+        expected.extend(2i32.to_be_bytes());
+        expected.extend(string("127.0.0.1"));
+        expected.extend(10002i32.to_be_bytes());
+        expected.extend((-1i16).to_be_bytes());
+        expected.extend(2i32.to_be_bytes());
+        expected.extend(0i32.to_be_bytes());
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn metadata_v9_flexible() {
+        let req = RequestHeader {
+            api_key: METADATA,
+            api_version: 9,
+            correlation_id: 1,
+        };
+        let mut frame = 1i32.to_be_bytes().to_vec();
+        frame.push(0); // header tagged fields
+        frame.extend(0i32.to_be_bytes()); // throttle
+        frame.push(2); // one broker
+        frame.extend(0i32.to_be_bytes());
+        frame.extend(compact_string("kafka"));
+        frame.extend(9093i32.to_be_bytes());
+        frame.push(0); // null rack
+        frame.push(0); // broker tagged fields
+        frame.extend([0xde, 0xad]); // remainder, copied as is
+
+        let out = rewrite_response(&frame, &req, rewrite).unwrap();
+
+        let mut expected = 1i32.to_be_bytes().to_vec();
+        expected.push(0);
+        expected.extend(0i32.to_be_bytes());
+        expected.push(2);
+        expected.extend(0i32.to_be_bytes());
+        expected.extend(compact_string("127.0.0.1"));
+        expected.extend(10000i32.to_be_bytes());
+        expected.push(0);
+        expected.push(0);
+        expected.extend([0xde, 0xad]);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn find_coordinator_v0() {
+        let req = RequestHeader {
+            api_key: FIND_COORDINATOR,
+            api_version: 0,
+            correlation_id: 3,
+        };
+        let mut frame = 3i32.to_be_bytes().to_vec();
+        frame.extend(0i16.to_be_bytes());
+        frame.extend(5i32.to_be_bytes());
+        frame.extend(string("coordinator"));
+        frame.extend(9092i32.to_be_bytes());
+
+        let out = rewrite_response(&frame, &req, rewrite).unwrap();
+
+        let mut expected = 3i32.to_be_bytes().to_vec();
+        expected.extend(0i16.to_be_bytes());
+        expected.extend(5i32.to_be_bytes());
+        expected.extend(string("127.0.0.1"));
+        expected.extend(10005i32.to_be_bytes());
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn truncated_response() {
+        let req = RequestHeader {
+            api_key: METADATA,
+            api_version: 0,
+            correlation_id: 0,
+        };
+        let mut frame = 0i32.to_be_bytes().to_vec();
+        frame.extend(1i32.to_be_bytes());
+        assert!(rewrite_response(&frame, &req, rewrite).is_err())
+    }
+
+    #[test]
+    fn frames_split_across_chunks() {
+        let mut buf = FrameBuffer::default();
+        let a = encode_frame(b"hello");
+        let b = encode_frame(b"kafka");
+        buf.push(&a[..3]);
+        assert_eq!(buf.next_frame().unwrap(), None);
+        buf.push(&a[3..]);
+        buf.push(&b[..6]);
+        assert_eq!(buf.next_frame().unwrap(), Some(b"hello".to_vec()));
+        assert_eq!(buf.next_frame().unwrap(), None);
+        buf.push(&b[6..]);
+        assert_eq!(buf.next_frame().unwrap(), Some(b"kafka".to_vec()));
+    }
+}
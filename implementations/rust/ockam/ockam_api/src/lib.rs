@@ -1,15 +1,26 @@
+pub mod ack;
+pub mod audit;
 pub mod auth;
 pub mod authenticator;
 pub mod cloud;
 pub mod config;
+pub mod dead_letters;
 pub mod echoer;
 pub mod error;
+pub mod file_transfer;
 pub mod identity;
+pub mod kafka;
 pub mod nodes;
+pub mod perf;
+pub mod pubsub;
+pub mod revocation;
+pub mod telemetry;
 pub mod uppercase;
 pub mod vault;
 pub mod verifier;
+pub mod x509;
 
+mod proxy;
 mod session;
 mod util;
 pub use util::*;
@@ -17,6 +28,9 @@ pub use util::*;
 #[cfg(feature = "lmdb")]
 pub mod lmdb;
 
+#[cfg(feature = "redis")]
+pub mod redis;
+
 #[macro_use]
 extern crate tracing;
 
@@ -28,10 +42,20 @@ impl DefaultAddress {
     pub const AUTHENTICATED_SERVICE: &'static str = "authenticated";
     pub const UPPERCASE_SERVICE: &'static str = "uppercase";
     pub const ECHO_SERVICE: &'static str = "echo";
+    pub const ACK_SERVICE: &'static str = "ack";
+    pub const PERF_SERVICE: &'static str = "perf";
     pub const CREDENTIAL_SERVICE: &'static str = "credentials";
     pub const SECURE_CHANNEL_LISTENER: &'static str = "api";
     pub const AUTHENTICATOR: &'static str = "authenticator";
     pub const VERIFIER: &'static str = "verifier";
+    pub const REVOCATION: &'static str = "revocation";
+    pub const SIGNER: &'static str = "signer";
+    pub const FILE_TRANSFER: &'static str = "file_transfer";
+    pub const PUBSUB: &'static str = "pubsub";
+    pub const KAFKA_OUTLET: &'static str = "kafka_outlet";
+    pub const KAFKA_INLET: &'static str = "kafka_inlet";
+    pub const PROXY_SERVICE: &'static str = "proxy";
+    pub const FORWARDING_SERVICE: &'static str = "forwarding_service";
 }
 
 use core::fmt;
@@ -83,6 +83,25 @@ impl AuthenticatedStorage for LmdbStorage {
         };
         task::spawn_blocking(t).await.map_err(map_join_err)?
     }
+
+    async fn take(&self, id: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        let d = self.clone();
+        let k = format!("{id}:{key}");
+        let t = move || {
+            // Write transactions are serialized, so the value is only
+            // returned to one of concurrent callers.
+            let mut w = d.env.begin_rw_txn().map_err(map_lmdb_err)?;
+            let val = match w.get(d.map, &k) {
+                Ok(value) => Vec::from(value),
+                Err(lmdb::Error::NotFound) => return Ok(None),
+                Err(e) => return Err(map_lmdb_err(e)),
+            };
+            w.del(d.map, &k, None).map_err(map_lmdb_err)?;
+            w.commit().map_err(map_lmdb_err)?;
+            Ok(Some(val))
+        };
+        task::spawn_blocking(t).await.map_err(map_join_err)?
+    }
 }
 
 fn map_join_err(err: JoinError) -> Error {
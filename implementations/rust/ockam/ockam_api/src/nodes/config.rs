@@ -1,4 +1,5 @@
 use crate::config::{Config, ConfigValues};
+use crate::nodes::models::config::NodeSetup;
 pub use commands::*;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
@@ -7,13 +8,19 @@ use std::path::{Path, PathBuf};
 pub struct NodeConfig {
     state: Config<NodeStateConfig>,
     commands: Config<Commands>,
+    setup: Config<NodeSetup>,
 }
 
 impl NodeConfig {
     pub fn new(config_dir: &Path) -> anyhow::Result<Self> {
         let state = Config::load(config_dir, "state")?;
         let commands = Config::load(config_dir, "commands")?;
-        Ok(Self { state, commands })
+        let setup = Config::load(config_dir, "setup")?;
+        Ok(Self {
+            state,
+            commands,
+            setup,
+        })
     }
 
     pub fn state(&self) -> &Config<NodeStateConfig> {
@@ -23,6 +30,11 @@ impl NodeConfig {
     pub fn commands(&self) -> &Config<Commands> {
         &self.commands
     }
+
+    /// What the node sets up when it starts
+    pub fn setup(&self) -> &Config<NodeSetup> {
+        &self.setup
+    }
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -35,7 +47,28 @@ pub struct NodeStateConfig {
     pub identity: Option<Vec<u8>>,
     /// Identity was overridden
     pub identity_was_overridden: bool,
+    /// DER encoded X.509 certificate binding the identity
+    #[serde(default)]
+    pub identity_certificate: Option<Vec<u8>>,
     pub commands: Commands,
+    /// How the node serves its API
+    #[serde(default)]
+    pub api: NodeApiConfig,
+    /// How many times the supervisor restarts the node, kept to restart
+    /// the node the same way. Unset for nodes created without it.
+    #[serde(default)]
+    pub max_restarts: Option<u32>,
+}
+
+/// How a node serves its API, kept to restart the node the same way
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct NodeApiConfig {
+    /// Serve the API on a Unix domain socket in the node directory
+    #[serde(default)]
+    pub socket: bool,
+    /// Don't serve the API to local clients over TCP, only on the socket
+    #[serde(default)]
+    pub no_tcp: bool,
 }
 
 impl ConfigValues for NodeStateConfig {
@@ -44,6 +77,19 @@ impl ConfigValues for NodeStateConfig {
     }
 }
 
+impl ConfigValues for NodeSetup {
+    fn default_values(_config_dir: &Path) -> Self {
+        Self::default()
+    }
+}
+
+impl Config<NodeSetup> {
+    pub fn set(&self, setup: NodeSetup) -> anyhow::Result<()> {
+        *self.write() = setup;
+        self.persist_config_updates()
+    }
+}
+
 mod commands {
     use super::*;
 
@@ -1,4 +1,5 @@
 pub mod config;
+pub mod policies;
 pub mod registry;
 
 pub mod service;
@@ -0,0 +1,146 @@
+//! Audit log request/response types
+
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Kind of a security-relevant event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditKind {
+    /// An identity opened a secure channel to one of our listeners.
+    #[n(0)] SecureChannelAccepted,
+    /// An identity was refused a secure channel by a trust policy.
+    #[n(1)] SecureChannelRejected,
+    /// We opened a secure channel to another node.
+    #[n(2)] SecureChannelCreated,
+    /// We presented our credential over a secure channel.
+    #[n(3)] CredentialPresented,
+    /// An enroller added a member.
+    #[n(4)] MemberAdded,
+    /// An enroller created a one-time code.
+    #[n(5)] TokenCreated,
+    /// An identity became a member by redeeming a one-time code.
+    #[n(6)] TokenRedeemed,
+    /// A credential was issued to a member.
+    #[n(7)] CredentialIssued,
+    /// A message was denied by an ABAC policy.
+    #[n(8)] PolicyDenied,
+    /// A member renewed its credential.
+    #[n(9)] CredentialRenewed,
+}
+
+/// A record of the audit log
+#[derive(Debug, Clone, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AuditRecord<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<4160259>,
+    /// Seconds since the unix epoch.
+    #[n(1)] pub timestamp: u64,
+    #[n(2)] pub kind: AuditKind,
+    /// The identity the event is about.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[b(3)] pub identity: Option<CowStr<'a>>,
+    /// The address of the secure channel, listener or worker involved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[b(4)] pub address: Option<CowStr<'a>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[b(5)] pub detail: Option<CowStr<'a>>,
+}
+
+impl<'a> AuditRecord<'a> {
+    /// Create a record of an event which just happened.
+    pub fn new(kind: AuditKind) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            timestamp,
+            kind,
+            identity: None,
+            address: None,
+            detail: None,
+        }
+    }
+
+    pub fn with_identity(mut self, identity: impl ToString) -> Self {
+        self.identity = Some(identity.to_string().into());
+        self
+    }
+
+    pub fn with_address(mut self, address: impl ToString) -> Self {
+        self.address = Some(address.to_string().into());
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<CowStr<'a>>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// Request body to query the audit log
+///
+/// All the bounds are optional, timestamps are in seconds since the unix epoch.
+#[derive(Debug, Clone, Default, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AuditQuery {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7385121>,
+    /// Only records at or after this time.
+    #[n(1)] pub since: Option<u64>,
+    /// Only records before this time.
+    #[n(2)] pub until: Option<u64>,
+    /// Only the most recent records, up to this number.
+    #[n(3)] pub limit: Option<u32>,
+}
+
+impl AuditQuery {
+    pub fn new(since: Option<u64>, until: Option<u64>, limit: Option<u32>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            since,
+            until,
+            limit,
+        }
+    }
+
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        self.since.map_or(true, |t| record.timestamp >= t)
+            && self.until.map_or(true, |t| record.timestamp < t)
+    }
+}
+
+/// Response body listing audit records, oldest first
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AuditRecordList<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2208743>,
+    #[b(1)] pub records: Vec<AuditRecord<'a>>,
+}
+
+impl<'a> AuditRecordList<'a> {
+    pub fn new(records: Vec<AuditRecord<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            records,
+        }
+    }
+}
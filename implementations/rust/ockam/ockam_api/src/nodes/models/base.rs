@@ -4,6 +4,11 @@
 
 use minicbor::{Decode, Encode};
 use ockam_core::compat::borrow::Cow;
+use ockam_identity::IdentityIdentifier;
+
+use super::portal::{InletStatus, OutletStatus};
+use super::services::ServiceStatus;
+use super::transport::TransportStatus;
 
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
@@ -45,3 +50,192 @@ impl<'a> NodeStatus<'a> {
         }
     }
 }
+
+/// Response body for a node health check
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct NodeHealth {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2591063>,
+    /// Seconds since the node manager started
+    #[n(1)] pub uptime: u64,
+    #[n(2)] pub workers: u32,
+    /// Number of health checks answered so far, including this one
+    #[n(3)] pub heartbeat: u64,
+}
+
+impl NodeHealth {
+    pub fn new(uptime: u64, workers: u32, heartbeat: u64) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            uptime,
+            workers,
+            heartbeat,
+        }
+    }
+}
+
+/// Response body for a detailed view of a node
+///
+/// Gathers everything running on a node in a single response.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct NodeDetails<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3815463>,
+    #[b(1)] pub status: NodeStatus<'a>,
+    #[n(2)] pub identity: Option<IdentityIdentifier>,
+    /// Seconds since the node manager started
+    #[n(3)] pub uptime: u64,
+    #[b(4)] pub transports: Vec<TransportStatus<'a>>,
+    #[n(5)] pub secure_channel_listeners: Vec<String>,
+    #[n(6)] pub secure_channels: Vec<String>,
+    #[b(7)] pub services: Vec<ServiceStatus<'a>>,
+    #[b(8)] pub inlets: Vec<InletStatus<'a>>,
+    #[b(9)] pub outlets: Vec<OutletStatus<'a>>,
+    #[b(10)] pub sessions: Vec<SessionStatus<'a>>,
+}
+
+impl<'a> NodeDetails<'a> {
+    pub fn new(status: NodeStatus<'a>, identity: Option<IdentityIdentifier>, uptime: u64) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            status,
+            identity,
+            uptime,
+            transports: Vec::new(),
+            secure_channel_listeners: Vec::new(),
+            secure_channels: Vec::new(),
+            services: Vec::new(),
+            inlets: Vec::new(),
+            outlets: Vec::new(),
+            sessions: Vec::new(),
+        }
+    }
+}
+
+/// Health of a session supervised by the node
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SessionStatus<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7248157>,
+    #[b(1)] pub addr: Cow<'a, str>,
+    #[b(2)] pub status: Cow<'a, str>,
+    /// Number of pings which have not been answered yet
+    #[n(3)] pub pending_pings: u32,
+}
+
+impl<'a> SessionStatus<'a> {
+    pub fn new(
+        addr: impl Into<Cow<'a, str>>,
+        status: impl Into<Cow<'a, str>>,
+        pending_pings: u32,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: addr.into(),
+            status: status.into(),
+            pending_pings,
+        }
+    }
+}
+
+/// A session supervised by the node, as a vertex of the session graph
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SessionNode<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<3958172>,
+    #[b(1)] pub key: Cow<'a, str>,
+    #[b(2)] pub addr: Cow<'a, str>,
+    #[b(3)] pub status: Cow<'a, str>,
+}
+
+impl<'a> SessionNode<'a> {
+    pub fn new(
+        key: impl Into<Cow<'a, str>>,
+        addr: impl Into<Cow<'a, str>>,
+        status: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            key: key.into(),
+            addr: addr.into(),
+            status: status.into(),
+        }
+    }
+}
+
+/// A dependency of a session on another one
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SessionEdge<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<5127640>,
+    /// Key of the dependent session
+    #[b(1)] pub from: Cow<'a, str>,
+    /// Key of the session it depends on
+    #[b(2)] pub to: Cow<'a, str>,
+}
+
+impl<'a> SessionEdge<'a> {
+    pub fn new(from: impl Into<Cow<'a, str>>, to: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+}
+
+/// Response body for the dependency graph of the sessions of a node
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SessionGraph<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<8391024>,
+    #[b(1)] pub nodes: Vec<SessionNode<'a>>,
+    #[b(2)] pub edges: Vec<SessionEdge<'a>>,
+}
+
+impl<'a> SessionGraph<'a> {
+    pub fn new(nodes: Vec<SessionNode<'a>>, edges: Vec<SessionEdge<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            nodes,
+            edges,
+        }
+    }
+
+    /// Render the graph in the DOT language of Graphviz.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph sessions {\n");
+        for n in &self.nodes {
+            dot.push_str(&format!(
+                "    \"{}\" [label=\"{}\\n{}\"];\n",
+                n.key, n.addr, n.status
+            ));
+        }
+        for e in &self.edges {
+            dot.push_str(&format!("    \"{}\" -> \"{}\";\n", e.from, e.to));
+        }
+        dot.push('}');
+        dot
+    }
+}
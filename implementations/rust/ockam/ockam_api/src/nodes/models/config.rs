@@ -0,0 +1,427 @@
+//! Declarative node configuration types
+//!
+//! A [`NodeSetup`] is read from the file given to `ockam node create
+//! --setup`, applied by the node manager when the node starts and returned
+//! by `GET /node/config`. Running nodes are converged to another setup with
+//! `POST /node/config`.
+
+use minicbor::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+use crate::nodes::models::transport::TransportMode;
+
+/// What a node sets up when it starts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+#[serde(default, deny_unknown_fields)]
+pub struct NodeSetup {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<4719302>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[n(1)] pub transports: Vec<TransportSetup>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[n(2)] pub listeners: Vec<ListenerSetup>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[n(3)] pub services: Vec<ServiceSetup>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[n(4)] pub forwarders: Vec<ForwarderSetup>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[n(5)] pub inlets: Vec<InletSetup>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[n(6)] pub outlets: Vec<OutletSetup>,
+    /// Applied before anything else, so that services and portals can
+    /// refer to them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[n(7)] pub policies: Vec<PolicySetup>,
+    /// Declared attribute types, which policies are checked against.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[n(8)] pub attributes: Vec<AttributeSetup>,
+    /// Secure channels opened ahead of their first use.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[n(9)] pub channels: Vec<ChannelSetup>,
+}
+
+impl NodeSetup {
+    pub fn is_empty(&self) -> bool {
+        self.transports.is_empty()
+            && self.listeners.is_empty()
+            && self.services.is_empty()
+            && self.forwarders.is_empty()
+            && self.inlets.is_empty()
+            && self.outlets.is_empty()
+            && self.policies.is_empty()
+            && self.attributes.is_empty()
+            && self.channels.is_empty()
+    }
+
+    /// Whether applying the setup sets policies, either directly or to
+    /// guard its portals.
+    pub fn writes_policies(&self) -> bool {
+        !self.policies.is_empty()
+            || self.inlets.iter().any(|i| i.policy.is_some())
+            || self.outlets.iter().any(|o| o.policy.is_some())
+    }
+}
+
+/// A TCP transport to listen at or connect to
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+#[serde(deny_unknown_fields)]
+pub struct TransportSetup {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<8325716>,
+    #[n(1)] pub mode: TransportMode,
+    #[n(2)] pub address: String,
+}
+
+impl TransportSetup {
+    pub fn new(mode: TransportMode, address: impl Into<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            mode,
+            address: address.into(),
+        }
+    }
+}
+
+/// A secure channel listener
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+#[serde(deny_unknown_fields)]
+pub struct ListenerSetup {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<2093847>,
+    #[n(1)] pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(2)] pub authorized_identifiers: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(3)] pub max_channels: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(4)] pub max_channels_per_identity: Option<u32>,
+    /// Only accept the project members, fetched from the project authority.
+    #[serde(default)]
+    #[n(5)] pub sync_members: bool,
+    /// Seconds between two fetches of the project members.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(6)] pub sync_interval: Option<u64>,
+}
+
+impl ListenerSetup {
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            address: address.into(),
+            authorized_identifiers: None,
+            max_channels: None,
+            max_channels_per_identity: None,
+            sync_members: false,
+            sync_interval: None,
+        }
+    }
+}
+
+/// A service of the node's service catalog
+///
+/// Only the settings of its kind are used, e.g. `dir` for `file_transfer`.
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceSetup {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<6610584>,
+    #[n(1)] pub kind: String,
+    #[n(2)] pub address: String,
+    /// The ABAC resource guarding the service.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(3)] pub resource: Option<String>,
+    /// Restart the service when its worker stops.
+    #[serde(default)]
+    #[n(4)] pub restart: bool,
+    /// The directory files are stored in, for `file_transfer`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(5)] pub dir: Option<String>,
+    /// Only present credentials, for `credentials`.
+    #[serde(default)]
+    #[n(6)] pub oneway: bool,
+    /// How many events are retained per topic, for `pubsub`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(7)] pub retain: Option<u64>,
+    /// Persist retained events, for `pubsub`.
+    #[serde(default)]
+    #[n(8)] pub persist: bool,
+    /// The identities allowed to request signatures, for `signer`, or to
+    /// send files, for `file_transfer`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[n(9)] pub authorized: Vec<String>,
+    /// Largest payload echoed back, in bytes, for `echo`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(10)] pub max_payload: Option<u64>,
+    /// Messages echoed back per second and sender, for `echo`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(11)] pub rate_limit: Option<u32>,
+    /// Only echo back messages over authenticated secure channels, for `echo`.
+    #[serde(default)]
+    #[n(12)] pub authenticated: bool,
+    /// Give up restarting the service after this many restarts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(13)] pub max_restarts: Option<u32>,
+    /// The JSON file of enroller identifiers, for `authenticator`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(14)] pub enrollers: Option<String>,
+    /// The project members are enrolled into, for `authenticator`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(15)] pub project: Option<String>,
+    /// Store members in this Redis server, for `authenticator`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(16)] pub redis: Option<String>,
+    /// Route to the signer service issuing credentials, for `authenticator`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(17)] pub signer: Option<String>,
+    /// Keep one-time codes in the member storage, for `authenticator`.
+    #[serde(default)]
+    #[n(18)] pub stateless: bool,
+    /// Replace existing files, for `file_transfer`.
+    #[serde(default)]
+    #[n(19)] pub overwrite: bool,
+    /// Largest file accepted, in bytes, for `file_transfer`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(20)] pub max_size: Option<u64>,
+    /// Messages echoed back per second across all senders, for `echo`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(21)] pub total_rate_limit: Option<u32>,
+}
+
+impl ServiceSetup {
+    pub fn new(kind: impl Into<String>, address: impl Into<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            kind: kind.into(),
+            address: address.into(),
+            resource: None,
+            restart: false,
+            dir: None,
+            oneway: false,
+            retain: None,
+            persist: false,
+            authorized: Vec::new(),
+            max_payload: None,
+            rate_limit: None,
+            authenticated: false,
+            max_restarts: None,
+            enrollers: None,
+            project: None,
+            redis: None,
+            signer: None,
+            stateless: false,
+            overwrite: false,
+            max_size: None,
+            total_rate_limit: None,
+        }
+    }
+}
+
+/// A forwarder at a project or another node
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+#[serde(deny_unknown_fields)]
+pub struct ForwarderSetup {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<3958201>,
+    /// The multiaddr of the node to create the forwarder at.
+    #[n(1)] pub at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(2)] pub alias: Option<String>,
+    /// An authorised identity for secure channels to non-project nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(3)] pub authorized: Option<String>,
+}
+
+/// A secure channel kept open to a known destination, e.g. the project
+/// node, so that connecting to it does not wait for a handshake
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+#[serde(deny_unknown_fields)]
+pub struct ChannelSetup {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<5304817>,
+    /// The multiaddr of the destination, e.g. `/project/default`.
+    #[n(1)] pub to: String,
+    /// An authorised identity for secure channels to non-project nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(2)] pub authorized: Option<String>,
+}
+
+impl ChannelSetup {
+    pub fn new(to: impl Into<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            to: to.into(),
+            authorized: None,
+        }
+    }
+}
+
+/// A TCP inlet
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+#[serde(deny_unknown_fields)]
+pub struct InletSetup {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<7742019>,
+    /// The socket address to listen at.
+    #[n(1)] pub from: String,
+    /// The multiaddr of the outlet.
+    #[n(2)] pub to: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(3)] pub alias: Option<String>,
+    #[serde(default)]
+    #[n(4)] pub check_credential: bool,
+    /// A JSON encoded ABAC condition the outlet identity has to satisfy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(5)] pub policy: Option<String>,
+}
+
+/// A TCP outlet
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+#[serde(deny_unknown_fields)]
+pub struct OutletSetup {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<1586630>,
+    /// The address of the outlet worker.
+    #[n(1)] pub from: String,
+    /// The TCP address to connect to.
+    #[n(2)] pub to: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(3)] pub alias: Option<String>,
+    #[serde(default)]
+    #[n(4)] pub check_credential: bool,
+    /// A JSON encoded ABAC condition the inlet identities have to satisfy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(5)] pub policy: Option<String>,
+}
+
+/// An ABAC policy
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+#[serde(deny_unknown_fields)]
+pub struct PolicySetup {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<9217453>,
+    #[n(1)] pub resource: String,
+    /// Defaults to `handle_message`. An action group like `@admin` sets
+    /// the policy for all of its actions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[n(2)] pub action: Option<String>,
+    /// A JSON encoded `ockam_abac::Conditional`.
+    #[n(3)] pub condition: String,
+}
+
+/// The declared type of a subject attribute
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode, Serialize, Deserialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+#[serde(deny_unknown_fields)]
+pub struct AttributeSetup {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<6392751>,
+    #[n(1)] pub name: String,
+    /// `string`, `int` or `bool`.
+    #[serde(rename = "type")]
+    #[n(2)] pub kind: String,
+}
+
+impl AttributeSetup {
+    pub fn new(name: impl Into<String>, kind: impl Into<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            name: name.into(),
+            kind: kind.into(),
+        }
+    }
+}
+
+/// Request body to converge a running node to a setup
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+#[serde(deny_unknown_fields)]
+pub struct ApplySetup {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5172934>,
+    #[n(1)] setup: NodeSetup,
+    /// Only report the changes, without making them.
+    #[n(2)] dry_run: bool,
+}
+
+impl ApplySetup {
+    pub fn new(setup: NodeSetup, dry_run: bool) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            setup,
+            dry_run,
+        }
+    }
+
+    pub fn setup(&self) -> &NodeSetup {
+        &self.setup
+    }
+
+    pub fn into_setup(self) -> NodeSetup {
+        self.setup
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+}
+
+/// The changes made to converge a node to a setup
+///
+/// Entries of the setup which differ in any setting are deleted and created
+/// again.
+#[derive(Debug, Clone, Default, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SetupChanges {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<8840261>,
+    #[n(1)] pub created: Vec<String>,
+    #[n(2)] pub deleted: Vec<String>,
+    /// The changes which could not be made, with the reason why.
+    #[n(3)] pub failed: Vec<String>,
+}
+
+impl SetupChanges {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.deleted.is_empty() && self.failed.is_empty()
+    }
+}
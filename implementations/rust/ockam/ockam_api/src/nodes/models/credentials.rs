@@ -1,7 +1,7 @@
 //! Credentials request/response types
 
 use minicbor::{Decode, Encode};
-use ockam_core::compat::borrow::Cow;
+use ockam_core::CowStr;
 
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
@@ -32,7 +32,7 @@ impl GetCredentialRequest {
 pub struct PresentCredentialRequest<'a> {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<3698687>,
-    #[b(1)] pub route: Cow<'a, str>,
+    #[b(1)] pub route: CowStr<'a>,
     #[n(2)] pub oneway: bool,
 }
 
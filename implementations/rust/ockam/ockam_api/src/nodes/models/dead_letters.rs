@@ -0,0 +1,67 @@
+//! Dead letter request/response types
+
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+use serde::Serialize;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// A message sent to a local address which doesn't exist
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct DeadLetter<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<1873402>,
+    /// Seconds since the unix epoch.
+    #[n(1)] pub timestamp: u64,
+    /// The onward route of the message, starting with the missing address.
+    #[b(2)] pub onward_route: CowStr<'a>,
+    #[b(3)] pub return_route: CowStr<'a>,
+    /// Size of the payload, in bytes.
+    #[n(4)] pub size: u64,
+}
+
+impl<'a> DeadLetter<'a> {
+    pub fn new(
+        timestamp: u64,
+        onward_route: impl Into<CowStr<'a>>,
+        return_route: impl Into<CowStr<'a>>,
+        size: u64,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            timestamp,
+            onward_route: onward_route.into(),
+            return_route: return_route.into(),
+            size,
+        }
+    }
+}
+
+/// Response body listing the retained dead letters, oldest first
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct DeadLetterList<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<5590617>,
+    #[b(1)] pub letters: Vec<DeadLetter<'a>>,
+    /// Number of older dead letters which were not retained.
+    #[n(2)] pub dropped: u64,
+}
+
+impl<'a> DeadLetterList<'a> {
+    pub fn new(letters: Vec<DeadLetter<'a>>, dropped: u64) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            letters,
+            dropped,
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request body to send a file from a node
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SendFile<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6627085>,
+    #[b(1)] path: CowStr<'a>,
+    #[b(2)] to: CowStr<'a>,
+    #[b(3)] name: Option<CowStr<'a>>,
+}
+
+impl<'a> SendFile<'a> {
+    pub fn new(path: impl Into<CowStr<'a>>, to: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            path: path.into(),
+            to: to.into(),
+            name: None,
+        }
+    }
+
+    /// Name of the file on the receiving node, instead of the name of the
+    /// sent file.
+    pub fn with_name(mut self, name: impl Into<CowStr<'a>>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Route to the file transfer service of the receiving node.
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+/// Response body when a file has been sent
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct FileSent {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3905471>,
+    #[n(1)] size: u64,
+    #[n(2)] resumed_from: u64,
+}
+
+impl FileSent {
+    pub fn new(size: u64, resumed_from: u64) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            size,
+            resumed_from,
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Number of bytes the receiving node already had from an interrupted
+    /// transfer.
+    pub fn resumed_from(&self) -> u64 {
+        self.resumed_from
+    }
+}
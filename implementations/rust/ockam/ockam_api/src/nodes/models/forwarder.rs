@@ -1,6 +1,9 @@
+use std::time::Duration;
+
 use minicbor::{Decode, Encode};
 
 use ockam::remote::RemoteForwarderInfo;
+use ockam::TakeoverPolicy;
 use ockam_core::CowStr;
 use ockam_identity::IdentityIdentifier;
 use ockam_multiaddr::MultiAddr;
@@ -24,7 +27,19 @@ pub struct CreateForwarder<'a> {
     /// An authorised identity for secure channels.
     /// Only set for non-project addresses as for projects the project's
     /// authorised identity will be used.
-    #[n(4)] authorized: Option<IdentityIdentifier>
+    #[n(4)] authorized: Option<IdentityIdentifier>,
+    /// What happens when the alias is taken. Only supported by the
+    /// forwarding service of rust nodes.
+    #[n(5)] takeover: Option<TakeoverPolicy>,
+    /// Seconds between refreshes of the registration. Only supported by
+    /// the forwarding service of rust nodes.
+    #[n(6)] heartbeat_interval: Option<u64>,
+    /// Seconds after which the registration expires unless refreshed. Only
+    /// supported by the forwarding service of rust nodes.
+    #[n(7)] ttl: Option<u64>,
+    /// Service the node pings to check that the secure channel of the
+    /// forwarder is alive, instead of the echo service.
+    #[b(8)] probe_service: Option<CowStr<'a>>,
 }
 
 impl<'a> CreateForwarder<'a> {
@@ -36,6 +51,10 @@ impl<'a> CreateForwarder<'a> {
             alias: alias.map(|s| s.into()),
             at_rust_node: false,
             authorized: None,
+            takeover: None,
+            heartbeat_interval: None,
+            ttl: None,
+            probe_service: None,
         }
     }
 
@@ -52,9 +71,33 @@ impl<'a> CreateForwarder<'a> {
             alias: alias.map(|s| s.into()),
             at_rust_node,
             authorized: auth,
+            takeover: None,
+            heartbeat_interval: None,
+            ttl: None,
+            probe_service: None,
         }
     }
 
+    pub fn with_takeover(mut self, policy: TakeoverPolicy) -> Self {
+        self.takeover = Some(policy);
+        self
+    }
+
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval.as_secs());
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl.as_secs());
+        self
+    }
+
+    pub fn with_probe_service(mut self, service: impl Into<CowStr<'a>>) -> Self {
+        self.probe_service = Some(service.into());
+        self
+    }
+
     pub fn address(&self) -> &MultiAddr {
         &self.address
     }
@@ -70,9 +113,25 @@ impl<'a> CreateForwarder<'a> {
     pub fn authorized(&self) -> Option<IdentityIdentifier> {
         self.authorized.clone()
     }
+
+    pub fn takeover(&self) -> Option<TakeoverPolicy> {
+        self.takeover
+    }
+
+    pub fn heartbeat_interval(&self) -> Option<Duration> {
+        self.heartbeat_interval.map(Duration::from_secs)
+    }
+
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl.map(Duration::from_secs)
+    }
+
+    pub fn probe_service(&self) -> Option<&str> {
+        self.probe_service.as_deref()
+    }
 }
 
-/// Response body when creating a forwarder
+/// Response body when creating or showing a forwarder
 #[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
 #[rustfmt::skip]
 #[cbor(map)]
@@ -83,6 +142,11 @@ pub struct ForwarderInfo<'a> {
     #[b(1)] forwarding_route: CowStr<'a>,
     #[b(2)] remote_address: CowStr<'a>,
     #[b(3)] worker_address: CowStr<'a>,
+    /// The alias is taken and the forwarder waits for it to be released.
+    #[n(4)] queued: bool,
+    /// Seconds until the registration expires unless refreshed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[n(5)] remaining_ttl: Option<u64>,
 }
 
 impl<'a> ForwarderInfo<'a> {
@@ -93,6 +157,23 @@ impl<'a> ForwarderInfo<'a> {
     pub fn remote_address(&'a self) -> &'a str {
         &self.remote_address
     }
+
+    pub fn worker_address(&'a self) -> &'a str {
+        &self.worker_address
+    }
+
+    pub fn is_queued(&self) -> bool {
+        self.queued
+    }
+
+    pub fn with_remaining_ttl(mut self, ttl: Duration) -> Self {
+        self.remaining_ttl = Some(ttl.as_secs());
+        self
+    }
+
+    pub fn remaining_ttl(&self) -> Option<Duration> {
+        self.remaining_ttl.map(Duration::from_secs)
+    }
 }
 
 impl<'a> From<RemoteForwarderInfo> for ForwarderInfo<'a> {
@@ -103,6 +184,8 @@ impl<'a> From<RemoteForwarderInfo> for ForwarderInfo<'a> {
             forwarding_route: inner.forwarding_route().to_string().into(),
             remote_address: inner.remote_address().to_string().into(),
             worker_address: inner.worker_address().to_string().into(),
+            queued: inner.is_queued(),
+            remaining_ttl: None,
         }
     }
 }
@@ -6,6 +6,39 @@ use ockam_core::CowBytes;
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
 
+/// Where the root key of a new identity is generated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+pub enum IdentityKeyBackend {
+    /// In the node's vault
+    #[n(0)] Software,
+    /// In AWS KMS, the vault only keeps a reference to the key
+    #[n(1)] AwsKms,
+    /// In a PKCS#11 token, the vault only keeps a reference to the key
+    #[n(2)] Pkcs11,
+}
+
+/// Request body when instructing a node to create an Identity
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateIdentityRequest {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3360195>,
+    #[n(1)] pub key_backend: IdentityKeyBackend,
+}
+
+impl CreateIdentityRequest {
+    pub fn new(key_backend: IdentityKeyBackend) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            key_backend,
+        }
+    }
+}
+
 /// Response body when instructing a node to create a Secure Channel
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
@@ -63,3 +96,72 @@ impl<'a> ShortIdentityResponse<'a> {
         }
     }
 }
+
+/// Response body with a certificate signing request for the node's identity
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct IdentityCsrResponse<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4021598>,
+    /// DER encoded PKCS#10 request
+    #[b(1)] pub csr: CowBytes<'a>,
+}
+
+impl<'a> IdentityCsrResponse<'a> {
+    pub fn new(csr: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            csr: CowBytes(csr.into()),
+        }
+    }
+}
+
+/// Request body to import a certificate binding the node's identity
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ImportIdentityCertificateRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7133910>,
+    /// DER encoded certificate
+    #[b(1)] pub certificate: CowBytes<'a>,
+    /// DER encoded certificate of the issuing CA
+    #[b(2)] pub ca_certificate: CowBytes<'a>,
+}
+
+impl<'a> ImportIdentityCertificateRequest<'a> {
+    pub fn new(
+        certificate: impl Into<Cow<'a, [u8]>>,
+        ca_certificate: impl Into<Cow<'a, [u8]>>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            certificate: CowBytes(certificate.into()),
+            ca_certificate: CowBytes(ca_certificate.into()),
+        }
+    }
+}
+
+/// Response body with the certificate binding the node's identity
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct IdentityCertificateResponse<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2581947>,
+    /// DER encoded certificate
+    #[b(1)] pub certificate: CowBytes<'a>,
+}
+
+impl<'a> IdentityCertificateResponse<'a> {
+    pub fn new(certificate: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            certificate: CowBytes(certificate.into()),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request and response body for the tracing filter of a node
+///
+/// The filter uses the syntax of `OCKAM_LOG`, e.g.
+/// `info,ockam_api::session=trace`.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct LogFilterBody<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6152874>,
+    #[b(1)] filter: CowStr<'a>,
+}
+
+impl<'a> LogFilterBody<'a> {
+    pub fn new(filter: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            filter: filter.into(),
+        }
+    }
+
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+}
@@ -2,12 +2,21 @@
 ///
 /// This module is only a type facade and should not have any logic of
 /// its own
+pub mod audit;
 pub mod base;
+pub mod config;
 pub mod credentials;
+pub mod dead_letters;
+pub mod file_transfer;
 pub mod forwarder;
 pub mod identity;
+pub mod logging;
+pub mod perf;
+pub mod policy;
 pub mod portal;
+pub mod route;
 pub mod secure_channel;
 pub mod services;
+pub mod trace;
 pub mod transport;
 pub mod vault;
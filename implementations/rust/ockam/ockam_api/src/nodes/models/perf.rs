@@ -0,0 +1,143 @@
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+use ockam_core::{Result, Route};
+use ockam_multiaddr::MultiAddr;
+use std::str::FromStr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+use crate::error::ApiError;
+
+/// Request body when instructing a node to measure a route
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PerfRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5213690>,
+    /// Route to a perf service
+    #[b(1)] to: CowStr<'a>,
+    /// Size of the payloads, in bytes
+    #[n(2)] size: u32,
+    /// How long to send data for, in milliseconds
+    #[n(3)] duration_ms: u64,
+    /// Number of pings measuring the latency
+    #[n(4)] pings: u32,
+}
+
+impl<'a> PerfRequest<'a> {
+    pub fn new(to: impl Into<CowStr<'a>>, size: u32, duration_ms: u64, pings: u32) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            to: to.into(),
+            size,
+            duration_ms,
+            pings,
+        }
+    }
+
+    pub fn to(&self) -> Result<Route> {
+        let maddr = MultiAddr::from_str(self.to.as_ref())
+            .map_err(|_err| ApiError::generic(&format!("Invalid route: {}", self.to)))?;
+        crate::multiaddr_to_route(&maddr)
+            .ok_or_else(|| ApiError::generic(&format!("Invalid MultiAddr: {}", maddr)))
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.duration_ms
+    }
+
+    pub fn pings(&self) -> u32 {
+        self.pings
+    }
+}
+
+/// Response body of a route measurement, durations are in microseconds
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PerfReport {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<4078126>,
+    #[n(1)] messages: u64,
+    #[n(2)] bytes: u64,
+    #[n(3)] elapsed_micros: u64,
+    #[n(4)] pings: u64,
+    #[n(5)] p50_micros: u64,
+    #[n(6)] p90_micros: u64,
+    #[n(7)] p99_micros: u64,
+    #[n(8)] max_micros: u64,
+}
+
+impl PerfReport {
+    pub fn new(messages: u64, bytes: u64, elapsed_micros: u64) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            messages,
+            bytes,
+            elapsed_micros,
+            pings: 0,
+            p50_micros: 0,
+            p90_micros: 0,
+            p99_micros: 0,
+            max_micros: 0,
+        }
+    }
+
+    pub fn with_latencies(mut self, pings: u64, p50: u64, p90: u64, p99: u64, max: u64) -> Self {
+        self.pings = pings;
+        self.p50_micros = p50;
+        self.p90_micros = p90;
+        self.p99_micros = p99;
+        self.max_micros = max;
+        self
+    }
+
+    pub fn messages(&self) -> u64 {
+        self.messages
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    pub fn elapsed_micros(&self) -> u64 {
+        self.elapsed_micros
+    }
+
+    pub fn pings(&self) -> u64 {
+        self.pings
+    }
+
+    pub fn p50_micros(&self) -> u64 {
+        self.p50_micros
+    }
+
+    pub fn p90_micros(&self) -> u64 {
+        self.p90_micros
+    }
+
+    pub fn p99_micros(&self) -> u64 {
+        self.p99_micros
+    }
+
+    pub fn max_micros(&self) -> u64 {
+        self.max_micros
+    }
+
+    /// Bits per second received by the perf service
+    pub fn throughput_bps(&self) -> f64 {
+        if self.elapsed_micros == 0 {
+            return 0.0;
+        }
+        self.bytes as f64 * 8.0 * 1_000_000.0 / self.elapsed_micros as f64
+    }
+}
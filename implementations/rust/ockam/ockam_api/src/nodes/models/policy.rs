@@ -0,0 +1,145 @@
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+use super::portal::PortalPolicy;
+
+/// The resource and action a policy is set for
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyTarget<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7136421>,
+    #[b(1)] resource: CowStr<'a>,
+    /// Defaults to [`PortalPolicy::DEFAULT_ACTION`].
+    #[b(2)] action: Option<CowStr<'a>>,
+}
+
+impl<'a> PolicyTarget<'a> {
+    pub fn new(resource: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            resource: resource.into(),
+            action: None,
+        }
+    }
+
+    pub fn with_action(mut self, a: impl Into<CowStr<'a>>) -> Self {
+        self.action = Some(a.into());
+        self
+    }
+
+    pub fn resource(&self) -> &str {
+        &self.resource
+    }
+
+    pub fn action(&self) -> &str {
+        self.action
+            .as_deref()
+            .unwrap_or(PortalPolicy::DEFAULT_ACTION)
+    }
+}
+
+/// Request body to set the policy of a resource and action
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SetPolicy<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2894017>,
+    #[b(1)] target: PolicyTarget<'a>,
+    /// A JSON encoded `ockam_abac::Conditional`.
+    #[b(2)] condition: CowStr<'a>,
+    /// Actions and `ockam_abac::ACTION_GROUPS` to set the policy for,
+    /// instead of the action of the target.
+    #[b(3)] actions: Option<Vec<CowStr<'a>>>,
+}
+
+impl<'a> SetPolicy<'a> {
+    pub fn new(target: PolicyTarget<'a>, condition: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            target,
+            condition: condition.into(),
+            actions: None,
+        }
+    }
+
+    pub fn with_actions<A: Into<CowStr<'a>>>(
+        mut self,
+        actions: impl IntoIterator<Item = A>,
+    ) -> Self {
+        self.actions = Some(actions.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// The actions and action groups the policy is set for.
+    pub fn actions(&self) -> Vec<&str> {
+        match &self.actions {
+            Some(a) if !a.is_empty() => a.iter().map(|a| a.as_ref()).collect(),
+            _ => vec![self.target.action()],
+        }
+    }
+
+    pub fn target(&self) -> &PolicyTarget<'a> {
+        &self.target
+    }
+
+    pub fn condition(&self) -> &str {
+        &self.condition
+    }
+}
+
+/// A policy condition
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Policy<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5619032>,
+    /// A JSON encoded `ockam_abac::Conditional`.
+    #[b(1)] condition: CowStr<'a>,
+}
+
+impl<'a> Policy<'a> {
+    pub fn new(condition: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            condition: condition.into(),
+        }
+    }
+
+    pub fn condition(&self) -> &str {
+        &self.condition
+    }
+}
+
+/// The previous versions of a policy, oldest first
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyHistory<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3948215>,
+    #[b(1)] versions: Vec<Policy<'a>>,
+}
+
+impl<'a> PolicyHistory<'a> {
+    pub fn new(versions: Vec<Policy<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            versions,
+        }
+    }
+
+    pub fn versions(&self) -> &[Policy<'a>] {
+        &self.versions
+    }
+}
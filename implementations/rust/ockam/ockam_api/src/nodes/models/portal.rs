@@ -1,10 +1,12 @@
 //! Inlets and outlet request/response types
 
 use std::net::SocketAddr;
+use std::path::Path;
 
 use minicbor::{Decode, Encode};
 use ockam_core::compat::borrow::Cow;
 
+use ockam::tcp::{InletTls, OutletTls, PortalCompression, PortalTraffic, RateLimit};
 use ockam_core::CowStr;
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
@@ -31,7 +33,15 @@ pub struct CreateInlet<'a> {
     /// An authorised identity for secure channels.
     /// Only set for non-project addresses as for projects the project's
     /// authorised identity will be used.
-    #[n(5)] authorized: Option<IdentityIdentifier>
+    #[n(5)] authorized: Option<IdentityIdentifier>,
+    /// An ABAC policy the outlet identity has to satisfy.
+    #[b(6)] policy: Option<PortalPolicy<'a>>,
+    /// A bandwidth cap shared by all the inlet connections.
+    #[n(7)] rate_limit: Option<PortalRateLimit>,
+    /// TLS terminated by the inlet on the accepted connections.
+    #[b(8)] tls: Option<InletTlsConfig<'a>>,
+    /// Compression algorithms offered to the outlet, in order of preference.
+    #[n(9)] compression: Option<Vec<Compression>>
 }
 
 impl<'a> CreateInlet<'a> {
@@ -44,6 +54,10 @@ impl<'a> CreateInlet<'a> {
             alias: None,
             check_credential,
             authorized: None,
+            policy: None,
+            rate_limit: None,
+            tls: None,
+            compression: None,
         }
     }
 
@@ -61,6 +75,10 @@ impl<'a> CreateInlet<'a> {
             alias: None,
             check_credential,
             authorized: auth,
+            policy: None,
+            rate_limit: None,
+            tls: None,
+            compression: None,
         }
     }
 
@@ -68,6 +86,22 @@ impl<'a> CreateInlet<'a> {
         self.alias = Some(CowStr(a.into()))
     }
 
+    pub fn set_policy(&mut self, p: PortalPolicy<'a>) {
+        self.policy = Some(p)
+    }
+
+    pub fn set_rate_limit(&mut self, r: PortalRateLimit) {
+        self.rate_limit = Some(r)
+    }
+
+    pub fn set_tls(&mut self, t: InletTlsConfig<'a>) {
+        self.tls = Some(t)
+    }
+
+    pub fn set_compression(&mut self, c: Vec<Compression>) {
+        self.compression = Some(c)
+    }
+
     pub fn listen_addr(&self) -> SocketAddr {
         self.listen_addr
     }
@@ -87,6 +121,22 @@ impl<'a> CreateInlet<'a> {
     pub fn is_check_credential(&self) -> bool {
         self.check_credential
     }
+
+    pub fn policy(&self) -> Option<&PortalPolicy<'a>> {
+        self.policy.as_ref()
+    }
+
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limit.map(RateLimit::from)
+    }
+
+    pub fn tls(&self) -> Option<&InletTlsConfig<'a>> {
+        self.tls.as_ref()
+    }
+
+    pub fn compression(&self) -> Vec<PortalCompression> {
+        Compression::to_portal(self.compression.as_deref().unwrap_or_default())
+    }
 }
 
 /// Request body to create an inlet or outlet
@@ -97,13 +147,21 @@ pub struct CreateOutlet<'a> {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<5351558>,
     /// The address the portal should connect or bind to
-    #[b(1)] pub tcp_addr: Cow<'a, str>,
+    #[b(1)] pub tcp_addr: CowStr<'a>,
     /// The address the portal should connect or bind to
-    #[b(2)] pub worker_addr: Cow<'a, str>,
+    #[b(2)] pub worker_addr: CowStr<'a>,
     /// A human-friendly alias for this portal endpoint
     #[b(3)] pub alias: Option<CowStr<'a>>,
     /// Enable credentials authorization
     #[n(4)] pub check_credential: bool,
+    /// An ABAC policy the inlet identities have to satisfy.
+    #[b(5)] pub policy: Option<PortalPolicy<'a>>,
+    /// A bandwidth cap shared by all the outlet connections.
+    #[n(6)] pub rate_limit: Option<PortalRateLimit>,
+    /// TLS originated by the outlet on the connections to the target.
+    #[b(7)] pub tls: Option<OutletTlsConfig<'a>>,
+    /// Compression algorithms accepted from inlets, defaults to all of them.
+    #[n(8)] pub compression: Option<Vec<Compression>>,
 }
 
 impl<'a> CreateOutlet<'a> {
@@ -116,12 +174,291 @@ impl<'a> CreateOutlet<'a> {
         Self {
             #[cfg(feature = "tag")]
             tag: TypeTag,
-            tcp_addr: tcp_addr.into(),
-            worker_addr: worker_addr.into(),
+            tcp_addr: CowStr(tcp_addr.into()),
+            worker_addr: CowStr(worker_addr.into()),
             alias: alias.into(),
             check_credential,
+            policy: None,
+            rate_limit: None,
+            tls: None,
+            compression: None,
         }
     }
+
+    pub fn set_policy(&mut self, p: PortalPolicy<'a>) {
+        self.policy = Some(p)
+    }
+
+    pub fn set_rate_limit(&mut self, r: PortalRateLimit) {
+        self.rate_limit = Some(r)
+    }
+
+    pub fn set_tls(&mut self, t: OutletTlsConfig<'a>) {
+        self.tls = Some(t)
+    }
+
+    pub fn set_compression(&mut self, c: Vec<Compression>) {
+        self.compression = Some(c)
+    }
+}
+
+/// Compression of the payloads going through a portal
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+pub enum Compression {
+    #[n(0)] Lz4,
+    #[n(1)] Zstd,
+}
+
+impl Compression {
+    pub fn to_portal(c: &[Compression]) -> Vec<PortalCompression> {
+        c.iter().copied().map(PortalCompression::from).collect()
+    }
+}
+
+impl From<Compression> for PortalCompression {
+    fn from(c: Compression) -> Self {
+        match c {
+            Compression::Lz4 => PortalCompression::Lz4,
+            Compression::Zstd => PortalCompression::Zstd,
+        }
+    }
+}
+
+/// TLS terminated by an inlet
+///
+/// The paths are PEM files on the node host.
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct InletTlsConfig<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3994217>,
+    #[b(1)] pub certificate_chain: CowStr<'a>,
+    #[b(2)] pub private_key: CowStr<'a>,
+    /// Require client certificates issued by this CA (mutual TLS).
+    #[b(3)] pub client_ca: Option<CowStr<'a>>,
+}
+
+impl<'a> InletTlsConfig<'a> {
+    pub fn new(
+        certificate_chain: impl Into<CowStr<'a>>,
+        private_key: impl Into<CowStr<'a>>,
+        client_ca: Option<CowStr<'a>>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            certificate_chain: certificate_chain.into(),
+            private_key: private_key.into(),
+            client_ca,
+        }
+    }
+
+    pub fn to_tls(&self) -> ockam_core::Result<InletTls> {
+        InletTls::new(
+            &*self.certificate_chain,
+            &*self.private_key,
+            self.client_ca.as_deref().map(Path::new),
+        )
+    }
+}
+
+/// TLS originated by an outlet
+///
+/// The paths are PEM files on the node host.
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OutletTlsConfig<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<8460153>,
+    /// Defaults to the host of the outlet address.
+    #[b(1)] pub server_name: Option<CowStr<'a>>,
+    /// Defaults to the webpki roots.
+    #[b(2)] pub ca: Option<CowStr<'a>>,
+    /// Client certificate presented to the target (mutual TLS).
+    #[b(3)] pub client_certificate_chain: Option<CowStr<'a>>,
+    #[b(4)] pub client_private_key: Option<CowStr<'a>>,
+}
+
+impl<'a> OutletTlsConfig<'a> {
+    pub fn new(server_name: Option<CowStr<'a>>, ca: Option<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            server_name,
+            ca,
+            client_certificate_chain: None,
+            client_private_key: None,
+        }
+    }
+
+    pub fn with_client_certificate(
+        mut self,
+        certificate_chain: impl Into<CowStr<'a>>,
+        private_key: impl Into<CowStr<'a>>,
+    ) -> Self {
+        self.client_certificate_chain = Some(certificate_chain.into());
+        self.client_private_key = Some(private_key.into());
+        self
+    }
+
+    /// Create the outlet TLS for a target at `tcp_addr`
+    pub fn to_tls(&self, tcp_addr: &str) -> ockam_core::Result<OutletTls> {
+        let server_name = match self.server_name.as_deref() {
+            Some(name) => name,
+            None => tcp_addr
+                .rsplit_once(':')
+                .map(|(host, _)| host)
+                .unwrap_or(tcp_addr),
+        };
+        let client_certificate = match (
+            self.client_certificate_chain.as_deref(),
+            self.client_private_key.as_deref(),
+        ) {
+            (Some(chain), Some(key)) => Some((Path::new(chain), Path::new(key))),
+            _ => None,
+        };
+        OutletTls::new(
+            server_name,
+            self.ca.as_deref().map(Path::new),
+            client_certificate,
+        )
+    }
+}
+
+/// A bandwidth cap on an inlet or outlet
+#[derive(Clone, Copy, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PortalRateLimit {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5804317>,
+    #[n(1)] bytes_per_sec: u64,
+    #[n(2)] burst: u64,
+}
+
+impl PortalRateLimit {
+    /// The burst defaults to `bytes_per_sec` when not set.
+    pub fn new(bytes_per_sec: u64, burst: Option<u64>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            bytes_per_sec,
+            burst: burst.unwrap_or(bytes_per_sec),
+        }
+    }
+}
+
+impl From<PortalRateLimit> for RateLimit {
+    fn from(r: PortalRateLimit) -> Self {
+        RateLimit::new(r.bytes_per_sec, r.burst)
+    }
+}
+
+/// Traffic counters of an inlet or outlet
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TrafficStatus {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2950783>,
+    /// Bytes received from tcp clients
+    #[n(1)] pub bytes_in: u64,
+    /// Bytes sent to tcp clients
+    #[n(2)] pub bytes_out: u64,
+    /// Number of times a connection waited for the rate limit
+    #[n(3)] pub throttled: u64,
+    #[n(4)] pub bytes_per_sec: Option<u64>,
+    #[n(5)] pub burst: Option<u64>,
+    /// Payload bytes sent through the portal, after compression
+    #[n(6)] pub portal_bytes_sent: Option<u64>,
+    /// Payload bytes received through the portal, before decompression
+    #[n(7)] pub portal_bytes_received: Option<u64>,
+}
+
+impl TrafficStatus {
+    /// See [`PortalTraffic::compression_ratio`]
+    pub fn compression_ratio(&self) -> Option<f64> {
+        let portal = self.portal_bytes_sent? + self.portal_bytes_received?;
+        if portal == 0 {
+            return None;
+        }
+        Some((self.bytes_in + self.bytes_out) as f64 / portal as f64)
+    }
+}
+
+impl From<&PortalTraffic> for TrafficStatus {
+    fn from(t: &PortalTraffic) -> Self {
+        let limit = t.rate_limit();
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            bytes_in: t.bytes_in(),
+            bytes_out: t.bytes_out(),
+            throttled: t.throttled(),
+            bytes_per_sec: limit.map(|l| l.bytes_per_sec()),
+            burst: limit.map(|l| l.burst()),
+            portal_bytes_sent: Some(t.portal_bytes_sent()),
+            portal_bytes_received: Some(t.portal_bytes_received()),
+        }
+    }
+}
+
+/// An ABAC policy guarding an inlet or outlet
+///
+/// The policy is evaluated against the credential attributes of the
+/// identity at the other end of the portal, before any data is forwarded.
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PortalPolicy<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6230178>,
+    /// Defaults to `/inlet/<alias>` or `/outlet/<alias>`.
+    #[b(1)] resource: Option<CowStr<'a>>,
+    /// Defaults to [`PortalPolicy::DEFAULT_ACTION`].
+    #[b(2)] action: Option<CowStr<'a>>,
+    /// A JSON encoded `ockam_abac::Conditional`.
+    #[b(3)] condition: CowStr<'a>,
+}
+
+impl<'a> PortalPolicy<'a> {
+    pub const DEFAULT_ACTION: &'static str = "handle_message";
+
+    pub fn new(condition: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            resource: None,
+            action: None,
+            condition: condition.into(),
+        }
+    }
+
+    pub fn with_resource(mut self, r: impl Into<CowStr<'a>>) -> Self {
+        self.resource = Some(r.into());
+        self
+    }
+
+    pub fn with_action(mut self, a: impl Into<CowStr<'a>>) -> Self {
+        self.action = Some(a.into());
+        self
+    }
+
+    pub fn resource(&self) -> Option<&str> {
+        self.resource.as_deref()
+    }
+
+    pub fn action(&self) -> &str {
+        self.action.as_deref().unwrap_or(Self::DEFAULT_ACTION)
+    }
+
+    pub fn condition(&self) -> &str {
+        &self.condition
+    }
 }
 
 /// Response body when interacting with a portal endpoint
@@ -137,6 +474,7 @@ pub struct InletStatus<'a> {
     /// An optional status payload
     #[b(4)] pub payload: Option<Cow<'a, str>>,
     #[b(5)] pub outlet_route: Cow<'a, str>,
+    #[n(6)] pub traffic: Option<TrafficStatus>,
 }
 
 impl<'a> InletStatus<'a> {
@@ -149,6 +487,7 @@ impl<'a> InletStatus<'a> {
             alias: "".into(),
             payload: Some(reason.into()),
             outlet_route: "".into(),
+            traffic: None,
         }
     }
 
@@ -167,8 +506,14 @@ impl<'a> InletStatus<'a> {
             alias: alias.into(),
             payload: payload.into(),
             outlet_route: outlet_route.into(),
+            traffic: None,
         }
     }
+
+    pub fn with_traffic(mut self, t: &PortalTraffic) -> Self {
+        self.traffic = Some(t.into());
+        self
+    }
 }
 
 /// Response body when interacting with a portal endpoint
@@ -183,6 +528,7 @@ pub struct OutletStatus<'a> {
     #[b(3)] pub alias: Cow<'a, str>,
     /// An optional status payload
     #[b(4)] pub payload: Option<Cow<'a, str>>,
+    #[n(5)] pub traffic: Option<TrafficStatus>,
 }
 
 impl<'a> OutletStatus<'a> {
@@ -194,6 +540,7 @@ impl<'a> OutletStatus<'a> {
             worker_addr: "".into(),
             alias: "".into(),
             payload: Some(reason.into()),
+            traffic: None,
         }
     }
 
@@ -210,8 +557,14 @@ impl<'a> OutletStatus<'a> {
             worker_addr: worker_addr.into(),
             alias: alias.into(),
             payload: payload.into(),
+            traffic: None,
         }
     }
+
+    pub fn with_traffic(mut self, t: &PortalTraffic) -> Self {
+        self.traffic = Some(t.into());
+        self
+    }
 }
 
 /// Response body when returning a list of Inlets
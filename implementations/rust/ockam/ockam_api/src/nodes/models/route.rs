@@ -0,0 +1,82 @@
+use minicbor::{Decode, Encode};
+
+use ockam_core::CowStr;
+use ockam_identity::IdentityIdentifier;
+use ockam_multiaddr::MultiAddr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request body when instructing a node to resolve an address
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ResolveRoute {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6205817>,
+    /// Address to resolve, which may start with a project and contain
+    /// secure channel hops.
+    #[n(1)] address: MultiAddr,
+    /// An authorised identity for the last secure channel hop.
+    #[n(2)] authorized: Option<IdentityIdentifier>,
+}
+
+impl ResolveRoute {
+    pub fn new(address: MultiAddr, authorized: Option<IdentityIdentifier>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            address,
+            authorized,
+        }
+    }
+
+    pub fn address(&self) -> &MultiAddr {
+        &self.address
+    }
+
+    pub fn authorized(&self) -> Option<IdentityIdentifier> {
+        self.authorized.clone()
+    }
+}
+
+/// Response body when resolving an address
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ResolvedRoute<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<2470619>,
+    /// The address, with its project and secure channel hops replaced by
+    /// the secure channels created for them.
+    #[b(1)] address: CowStr<'a>,
+    /// The route the node sends messages along.
+    #[b(2)] route: CowStr<'a>,
+    /// The secure channels created or reused on the way, in order.
+    #[b(3)] secure_channels: Vec<CowStr<'a>>,
+}
+
+impl<'a> ResolvedRoute<'a> {
+    pub fn new(address: &MultiAddr, route: String, secure_channels: Vec<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            address: address.to_string().into(),
+            route: route.into(),
+            secure_channels: secure_channels.into_iter().map(CowStr::from).collect(),
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn route(&self) -> &str {
+        &self.route
+    }
+
+    pub fn secure_channels(&self) -> &[CowStr<'a>] {
+        &self.secure_channels
+    }
+}
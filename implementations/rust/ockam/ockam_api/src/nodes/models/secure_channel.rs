@@ -7,7 +7,7 @@ use ockam_core::compat::borrow::Cow;
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
 use ockam_core::{route, Address, CowStr, Result};
-use ockam_identity::IdentityIdentifier;
+use ockam_identity::{IdentityIdentifier, KeyExchangeMode, SecureChannelListenerLimits};
 use ockam_multiaddr::MultiAddr;
 use serde::Serialize;
 
@@ -23,6 +23,24 @@ pub enum CredentialExchangeMode {
     #[n(2)] Mutual,
 }
 
+/// Key exchange of the secure channel handshake, see [`KeyExchangeMode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+pub enum KeyExchange {
+    #[n(0)] Classic,
+    #[n(1)] Hybrid,
+}
+
+impl From<KeyExchange> for KeyExchangeMode {
+    fn from(k: KeyExchange) -> Self {
+        match k {
+            KeyExchange::Classic => KeyExchangeMode::Classic,
+            KeyExchange::Hybrid => KeyExchangeMode::Hybrid,
+        }
+    }
+}
+
 /// Request body when instructing a node to create a Secure Channel
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
@@ -33,7 +51,13 @@ pub struct CreateSecureChannelRequest<'a> {
     #[b(1)] pub addr: CowStr<'a>,
     #[b(2)] pub authorized_identifiers: Option<Vec<CowStr<'a>>>,
     #[n(3)] pub credential_exchange_mode: CredentialExchangeMode,
-    #[n(4)] pub timeout: Option<Duration>
+    #[n(4)] pub timeout: Option<Duration>,
+    /// Further candidate addresses of the same peer, tried concurrently with `addr`
+    #[b(5)] pub alternatives: Option<Vec<CowStr<'a>>>,
+    /// Delay between starting the attempts of consecutive candidates
+    #[n(6)] pub attempt_delay: Option<Duration>,
+    /// Key exchange offered to the listener, classic if absent
+    #[n(7)] pub key_exchange: Option<KeyExchange>,
 }
 
 impl<'a> CreateSecureChannelRequest<'a> {
@@ -50,7 +74,31 @@ impl<'a> CreateSecureChannelRequest<'a> {
                 .map(|x| x.into_iter().map(|y| y.to_string().into()).collect()),
             credential_exchange_mode,
             timeout: None,
+            alternatives: None,
+            attempt_delay: None,
+            key_exchange: None,
+        }
+    }
+
+    /// Also try to reach the peer at the given addresses. The first candidate
+    /// to complete the handshake is kept.
+    pub fn with_alternatives(mut self, alternatives: &[MultiAddr]) -> Self {
+        if !alternatives.is_empty() {
+            self.alternatives = Some(alternatives.iter().map(|a| a.to_string().into()).collect());
         }
+        self
+    }
+
+    /// Wait the given delay before each further candidate is attempted.
+    pub fn with_attempt_delay(mut self, delay: Duration) -> Self {
+        self.attempt_delay = Some(delay);
+        self
+    }
+
+    /// Offer the given key exchange. The listener may fall back to the classic one.
+    pub fn with_key_exchange(mut self, key_exchange: KeyExchange) -> Self {
+        self.key_exchange = Some(key_exchange);
+        self
     }
 }
 
@@ -94,8 +142,42 @@ impl<'a> CreateSecureChannelResponse<'a> {
 pub struct CreateSecureChannelListenerRequest<'a> {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<8112242>,
-    #[b(1)] pub addr: Cow<'a, str>,
+    #[b(1)] pub addr: CowStr<'a>,
     #[b(2)] pub authorized_identifiers: Option<Vec<CowStr<'a>>>,
+    #[n(3)] pub max_channels: Option<u32>,
+    #[n(4)] pub max_channels_per_identity: Option<u32>,
+    #[b(5)] pub trusted_attributes: Option<Vec<TrustedAttribute<'a>>>,
+    /// Key exchange accepted from initiators, classic if absent
+    #[n(6)] pub key_exchange: Option<KeyExchange>,
+    /// Only accept the members of the project, as periodically fetched from
+    /// its authority, instead of a static list of identifiers
+    #[n(7)] pub sync_members: Option<bool>,
+    /// Seconds between two fetches of the members
+    #[n(8)] pub sync_interval: Option<u64>,
+}
+
+/// Credential attribute required from secure channel initiators
+///
+/// The attribute must be equal to one of the values.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TrustedAttribute<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3355290>,
+    #[b(1)] pub name: CowStr<'a>,
+    #[b(2)] pub values: Vec<CowStr<'a>>,
+}
+
+impl<'a> TrustedAttribute<'a> {
+    pub fn new(name: impl Into<CowStr<'a>>, values: Vec<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            name: name.into(),
+            values,
+        }
+    }
 }
 
 impl<'a> CreateSecureChannelListenerRequest<'a> {
@@ -106,8 +188,64 @@ impl<'a> CreateSecureChannelListenerRequest<'a> {
             addr: addr.to_string().into(),
             authorized_identifiers: authorized_identifiers
                 .map(|x| x.into_iter().map(|y| y.to_string().into()).collect()),
+            max_channels: None,
+            max_channels_per_identity: None,
+            trusted_attributes: None,
+            key_exchange: None,
+            sync_members: None,
+            sync_interval: None,
         }
     }
+
+    /// Only accept the members of the project, fetched from its authority
+    /// every `interval` seconds.
+    pub fn with_synced_members(mut self, interval: Option<u64>) -> Self {
+        self.sync_members = Some(true);
+        self.sync_interval = interval;
+        self
+    }
+
+    pub fn syncs_members(&self) -> bool {
+        self.sync_members.unwrap_or(false)
+    }
+
+    /// Accept the given key exchange. Initiators may fall back to the classic one.
+    pub fn with_key_exchange(mut self, key_exchange: KeyExchange) -> Self {
+        self.key_exchange = Some(key_exchange);
+        self
+    }
+
+    /// Only accept initiators whose credential attributes match all the given ones.
+    pub fn with_trusted_attributes(mut self, attributes: Vec<TrustedAttribute<'a>>) -> Self {
+        self.trusted_attributes = Some(attributes);
+        self
+    }
+
+    /// Limit the concurrent channels of the listener, in total and per initiator identity.
+    pub fn with_limits(
+        mut self,
+        max_channels: Option<u32>,
+        max_channels_per_identity: Option<u32>,
+    ) -> Self {
+        self.max_channels = max_channels;
+        self.max_channels_per_identity = max_channels_per_identity;
+        self
+    }
+
+    pub fn limits(&self) -> SecureChannelListenerLimits {
+        let mut limits = SecureChannelListenerLimits::new();
+        if let Some(n) = self.max_channels {
+            limits = limits.with_max_channels(n as usize)
+        }
+        if let Some(n) = self.max_channels_per_identity {
+            limits = limits.with_max_channels_per_identity(n as usize)
+        }
+        limits
+    }
+
+    pub fn key_exchange(&self) -> KeyExchangeMode {
+        self.key_exchange.map(Into::into).unwrap_or_default()
+    }
 }
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
@@ -115,7 +253,7 @@ impl<'a> CreateSecureChannelListenerRequest<'a> {
 pub struct DeleteSecureChannelRequest<'a> {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<8472592>,
-    #[b(1)] pub channel: Cow<'a, str>,
+    #[b(1)] pub channel: CowStr<'a>,
 }
 
 impl<'a> DeleteSecureChannelRequest<'a> {
@@ -152,7 +290,7 @@ impl<'a> DeleteSecureChannelResponse<'a> {
 pub struct ShowSecureChannelRequest<'a> {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<3277982>,
-    #[b(1)] pub channel: Cow<'a, str>,
+    #[b(1)] pub channel: CowStr<'a>,
 }
 
 impl<'a> ShowSecureChannelRequest<'a> {
@@ -175,6 +313,10 @@ pub struct ShowSecureChannelResponse<'a> {
     #[b(1)] pub channel: Option<Cow<'a, str>>,
     #[b(2)] pub route: Option<Cow<'a, str>>,
     #[b(4)] pub authorized_identifiers: Option<Vec<CowStr<'a>>>,
+    #[n(5)] pub decrypt_failures: Option<u64>,
+    #[n(6)] pub out_of_window: Option<u64>,
+    #[n(7)] pub duplicates: Option<u64>,
+    #[b(8)] pub candidates: Option<Vec<CowStr<'a>>>,
 }
 
 impl<'a> ShowSecureChannelResponse<'a> {
@@ -190,6 +332,15 @@ impl<'a> ShowSecureChannelResponse<'a> {
                         .map(|ids| ids.iter().map(|iid| iid.to_string().into()).collect())
                 })
                 .unwrap_or(None),
+            decrypt_failures: info.map(|info| info.counters().decrypt_failures() as u64),
+            out_of_window: info.map(|info| info.counters().out_of_window() as u64),
+            duplicates: info.map(|info| info.counters().duplicates() as u64),
+            candidates: info.filter(|info| info.candidates().len() > 1).map(|info| {
+                info.candidates()
+                    .iter()
+                    .map(|r| r.to_string().into())
+                    .collect()
+            }),
         }
     }
 }
@@ -2,10 +2,75 @@ use std::path::Path;
 
 use minicbor::{bytes::ByteSlice, Decode, Encode};
 use ockam_core::compat::borrow::Cow;
+use ockam_core::CowStr;
+use ockam_identity::IdentityIdentifier;
 
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
 
+/// Options common to the services of the service catalog
+///
+/// They are found at index 10 of every start request of a catalog service.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ServiceOptions<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3804523>,
+    #[b(1)] resource: Option<CowStr<'a>>,
+    #[n(2)] restart: bool,
+    #[n(3)] max_restarts: Option<u32>,
+}
+
+impl<'a> ServiceOptions<'a> {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            resource: None,
+            restart: false,
+            max_restarts: None,
+        }
+    }
+
+    /// Guard the messages sent to the service with the ABAC policy of
+    /// this resource.
+    pub fn with_resource(mut self, r: impl Into<CowStr<'a>>) -> Self {
+        self.resource = Some(r.into());
+        self
+    }
+
+    /// Restart the service when its worker stops unexpectedly.
+    pub fn with_restart(mut self, restart: bool) -> Self {
+        self.restart = restart;
+        self
+    }
+
+    /// Give up restarting the service after this many restarts.
+    pub fn with_max_restarts(mut self, max: u32) -> Self {
+        self.max_restarts = Some(max);
+        self
+    }
+
+    pub fn resource(&self) -> Option<&str> {
+        self.resource.as_deref()
+    }
+
+    pub fn restart(&self) -> bool {
+        self.restart
+    }
+
+    pub fn max_restarts(&self) -> Option<u32> {
+        self.max_restarts
+    }
+}
+
+impl Default for ServiceOptions<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Request body when instructing a node to start a Vault service
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
@@ -13,7 +78,7 @@ use ockam_core::TypeTag;
 pub struct StartVaultServiceRequest<'a> {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<9798850>,
-    #[b(1)] pub addr: Cow<'a, str>,
+    #[b(1)] pub addr: CowStr<'a>,
 }
 
 impl<'a> StartVaultServiceRequest<'a> {
@@ -21,7 +86,7 @@ impl<'a> StartVaultServiceRequest<'a> {
         Self {
             #[cfg(feature = "tag")]
             tag: TypeTag,
-            addr: addr.into(),
+            addr: CowStr(addr.into()),
         }
     }
 }
@@ -33,7 +98,7 @@ impl<'a> StartVaultServiceRequest<'a> {
 pub struct StartIdentityServiceRequest<'a> {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<6129106>,
-    #[b(1)] pub addr: Cow<'a, str>,
+    #[b(1)] pub addr: CowStr<'a>,
 }
 
 impl<'a> StartIdentityServiceRequest<'a> {
@@ -41,7 +106,7 @@ impl<'a> StartIdentityServiceRequest<'a> {
         Self {
             #[cfg(feature = "tag")]
             tag: TypeTag,
-            addr: addr.into(),
+            addr: CowStr(addr.into()),
         }
     }
 }
@@ -53,7 +118,7 @@ impl<'a> StartIdentityServiceRequest<'a> {
 pub struct StartAuthenticatedServiceRequest<'a> {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<5179596>,
-    #[b(1)] pub addr: Cow<'a, str>,
+    #[b(1)] pub addr: CowStr<'a>,
 }
 
 impl<'a> StartAuthenticatedServiceRequest<'a> {
@@ -61,7 +126,7 @@ impl<'a> StartAuthenticatedServiceRequest<'a> {
         Self {
             #[cfg(feature = "tag")]
             tag: TypeTag,
-            addr: addr.into(),
+            addr: CowStr(addr.into()),
         }
     }
 }
@@ -73,7 +138,8 @@ impl<'a> StartAuthenticatedServiceRequest<'a> {
 pub struct StartUppercaseServiceRequest<'a> {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<8177400>,
-    #[b(1)] pub addr: Cow<'a, str>,
+    #[b(1)] pub addr: CowStr<'a>,
+    #[b(10)] options: Option<ServiceOptions<'a>>,
 }
 
 impl<'a> StartUppercaseServiceRequest<'a> {
@@ -81,9 +147,15 @@ impl<'a> StartUppercaseServiceRequest<'a> {
         Self {
             #[cfg(feature = "tag")]
             tag: TypeTag,
-            addr: addr.into(),
+            addr: CowStr(addr.into()),
+            options: None,
         }
     }
+
+    pub fn with_options(mut self, options: ServiceOptions<'a>) -> Self {
+        self.options = Some(options);
+        self
+    }
 }
 
 /// Request body when instructing a node to start an Echoer service
@@ -93,7 +165,12 @@ impl<'a> StartUppercaseServiceRequest<'a> {
 pub struct StartEchoerServiceRequest<'a> {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<7636656>,
-    #[b(1)] pub addr: Cow<'a, str>,
+    #[b(1)] pub addr: CowStr<'a>,
+    #[n(2)] max_payload: Option<u64>,
+    #[n(3)] rate_limit: Option<u32>,
+    #[n(4)] authenticated: Option<bool>,
+    #[n(5)] total_rate_limit: Option<u32>,
+    #[b(10)] options: Option<ServiceOptions<'a>>,
 }
 
 impl<'a> StartEchoerServiceRequest<'a> {
@@ -101,9 +178,158 @@ impl<'a> StartEchoerServiceRequest<'a> {
         Self {
             #[cfg(feature = "tag")]
             tag: TypeTag,
-            addr: addr.into(),
+            addr: CowStr(addr.into()),
+            max_payload: None,
+            rate_limit: None,
+            authenticated: None,
+            total_rate_limit: None,
+            options: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: ServiceOptions<'a>) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    pub fn with_max_payload(mut self, max: u64) -> Self {
+        self.max_payload = Some(max);
+        self
+    }
+
+    pub fn with_rate_limit(mut self, max: u32) -> Self {
+        self.rate_limit = Some(max);
+        self
+    }
+
+    pub fn with_total_rate_limit(mut self, max: u32) -> Self {
+        self.total_rate_limit = Some(max);
+        self
+    }
+
+    pub fn with_authenticated(mut self, authenticated: bool) -> Self {
+        self.authenticated = Some(authenticated);
+        self
+    }
+
+    /// Largest payload echoed back, in bytes.
+    pub fn max_payload(&self) -> Option<u64> {
+        self.max_payload
+    }
+
+    /// Number of messages echoed back per second and sender.
+    pub fn rate_limit(&self) -> Option<u32> {
+        self.rate_limit
+    }
+
+    /// Number of messages echoed back per second across all senders.
+    pub fn total_rate_limit(&self) -> Option<u32> {
+        self.total_rate_limit
+    }
+
+    /// Whether only messages over authenticated secure channels are echoed back.
+    pub fn is_authenticated(&self) -> bool {
+        self.authenticated.unwrap_or(false)
+    }
+}
+
+/// Request body when instructing a node to start a perf service
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartPerfServiceRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2960381>,
+    #[b(1)] pub addr: CowStr<'a>,
+    #[b(10)] options: Option<ServiceOptions<'a>>,
+}
+
+impl<'a> StartPerfServiceRequest<'a> {
+    pub fn new(addr: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: CowStr(addr.into()),
+            options: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: ServiceOptions<'a>) -> Self {
+        self.options = Some(options);
+        self
+    }
+}
+
+/// Request body when instructing a node to start an ack service
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartAckServiceRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6940235>,
+    #[b(1)] pub addr: CowStr<'a>,
+    #[b(10)] options: Option<ServiceOptions<'a>>,
+}
+
+impl<'a> StartAckServiceRequest<'a> {
+    pub fn new(addr: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: CowStr(addr.into()),
+            options: None,
         }
     }
+
+    pub fn with_options(mut self, options: ServiceOptions<'a>) -> Self {
+        self.options = Some(options);
+        self
+    }
+}
+
+/// Request body when instructing a node to start its dead letter service
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartDeadLetterService<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<8023964>,
+    #[b(1)] addr: &'a str,
+    /// Number of dead letters retained
+    #[n(2)] capacity: Option<u64>,
+    #[b(10)] options: Option<ServiceOptions<'a>>,
+}
+
+impl<'a> StartDeadLetterService<'a> {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: ockam_node::DEAD_LETTER_ADDRESS,
+            capacity: None,
+            options: None,
+        }
+    }
+
+    pub fn with_capacity(mut self, capacity: u64) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    pub fn with_options(mut self, options: ServiceOptions<'a>) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    pub fn capacity(&self) -> Option<u64> {
+        self.capacity
+    }
+}
+
+impl Default for StartDeadLetterService<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Debug, Clone, Decode, Encode)]
@@ -114,7 +340,11 @@ pub struct StartAuthenticatorRequest<'a> {
     #[n(0)] tag: TypeTag<2749734>,
     #[b(1)] addr: &'a str,
     #[b(2)] path: &'a Path,
-    #[b(3)] proj: &'a ByteSlice
+    #[b(3)] proj: &'a ByteSlice,
+    #[b(4)] redis: Option<&'a str>,
+    #[b(5)] signer: Option<&'a str>,
+    #[n(6)] stateless: bool,
+    #[b(10)] options: Option<ServiceOptions<'a>>,
 }
 
 impl<'a> StartAuthenticatorRequest<'a> {
@@ -125,9 +355,39 @@ impl<'a> StartAuthenticatorRequest<'a> {
             addr,
             path,
             proj: proj.into(),
+            redis: None,
+            signer: None,
+            stateless: false,
+            options: None,
         }
     }
 
+    pub fn with_options(mut self, options: ServiceOptions<'a>) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    /// Store members in the Redis server at the given URL, instead of the
+    /// node storage, to share them with other replicas of the authenticator.
+    pub fn with_redis(mut self, url: &'a str) -> Self {
+        self.redis = Some(url);
+        self
+    }
+
+    /// Have credentials signed by the signer service at the given route,
+    /// instead of the identity of the node.
+    pub fn with_signer(mut self, route: &'a str) -> Self {
+        self.signer = Some(route);
+        self
+    }
+
+    /// Keep pending one-time codes in the member storage, so that they can
+    /// be redeemed at any authenticator sharing it.
+    pub fn with_stateless(mut self, stateless: bool) -> Self {
+        self.stateless = stateless;
+        self
+    }
+
     pub fn address(&self) -> &'a str {
         self.addr
     }
@@ -139,6 +399,18 @@ impl<'a> StartAuthenticatorRequest<'a> {
     pub fn project(&self) -> &'a [u8] {
         self.proj
     }
+
+    pub fn redis(&self) -> Option<&'a str> {
+        self.redis
+    }
+
+    pub fn signer(&self) -> Option<&'a str> {
+        self.signer
+    }
+
+    pub fn is_stateless(&self) -> bool {
+        self.stateless
+    }
 }
 
 #[derive(Debug, Clone, Decode, Encode)]
@@ -148,6 +420,7 @@ pub struct StartVerifierService<'a> {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<9580740>,
     #[b(1)] addr: &'a str,
+    #[b(10)] options: Option<ServiceOptions<'a>>,
 }
 
 impl<'a> StartVerifierService<'a> {
@@ -156,12 +429,247 @@ impl<'a> StartVerifierService<'a> {
             #[cfg(feature = "tag")]
             tag: TypeTag,
             addr,
+            options: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: ServiceOptions<'a>) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    pub fn address(&self) -> &'a str {
+        self.addr
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartRevocationService<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5517702>,
+    #[b(1)] addr: &'a str,
+    #[b(10)] options: Option<ServiceOptions<'a>>,
+}
+
+impl<'a> StartRevocationService<'a> {
+    pub fn new(addr: &'a str) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr,
+            options: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: ServiceOptions<'a>) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    pub fn address(&self) -> &'a str {
+        self.addr
+    }
+}
+
+/// Request body when instructing a node to start a forwarding service,
+/// at which other nodes register their forwarders
+///
+/// The ABAC policy of the resource in the options decides which
+/// identities may register aliases.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartForwardingService<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4815093>,
+    #[b(1)] addr: CowStr<'a>,
+    #[b(10)] options: Option<ServiceOptions<'a>>,
+}
+
+impl<'a> StartForwardingService<'a> {
+    pub fn new(addr: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: CowStr(addr.into()),
+            options: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: ServiceOptions<'a>) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    pub fn address(&self) -> &str {
+        &self.addr
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartFileTransferService<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5340196>,
+    #[b(1)] addr: &'a str,
+    #[b(2)] dir: &'a Path,
+    #[n(3)] authorized: Vec<IdentityIdentifier>,
+    #[n(4)] overwrite: bool,
+    #[n(5)] max_size: Option<u64>,
+    #[b(10)] options: Option<ServiceOptions<'a>>,
+}
+
+impl<'a> StartFileTransferService<'a> {
+    pub fn new(addr: &'a str, dir: &'a Path) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr,
+            dir,
+            authorized: Vec::new(),
+            overwrite: false,
+            max_size: None,
+            options: None,
+        }
+    }
+
+    pub fn with_authorized(mut self, authorized: Vec<IdentityIdentifier>) -> Self {
+        self.authorized = authorized;
+        self
+    }
+
+    pub fn with_overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    pub fn with_options(mut self, options: ServiceOptions<'a>) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    pub fn address(&self) -> &'a str {
+        self.addr
+    }
+
+    /// Directory the received files are written to.
+    pub fn dir(&self) -> &'a Path {
+        self.dir
+    }
+
+    /// Identities allowed to send files.
+    pub fn authorized(&self) -> &[IdentityIdentifier] {
+        &self.authorized
+    }
+
+    /// Whether existing files are replaced.
+    pub fn overwrite(&self) -> bool {
+        self.overwrite
+    }
+
+    /// Largest file accepted, in bytes.
+    pub fn max_size(&self) -> Option<u64> {
+        self.max_size
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartPubSubService<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3627491>,
+    #[b(1)] addr: &'a str,
+    #[n(2)] retain: Option<u64>,
+    #[n(3)] persist: bool,
+    #[b(10)] options: Option<ServiceOptions<'a>>,
+}
+
+impl<'a> StartPubSubService<'a> {
+    pub fn new(addr: &'a str) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr,
+            retain: None,
+            persist: false,
+            options: None,
         }
     }
 
+    pub fn with_options(mut self, options: ServiceOptions<'a>) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    pub fn with_retain(mut self, retain: u64) -> Self {
+        self.retain = Some(retain);
+        self
+    }
+
+    pub fn with_persist(mut self, persist: bool) -> Self {
+        self.persist = persist;
+        self
+    }
+
+    pub fn address(&self) -> &'a str {
+        self.addr
+    }
+
+    /// Number of events retained per topic.
+    pub fn retain(&self) -> Option<u64> {
+        self.retain
+    }
+
+    /// Whether retained events are kept in the node storage.
+    pub fn is_persistent(&self) -> bool {
+        self.persist
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartSignerService<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6392485>,
+    #[b(1)] addr: &'a str,
+    #[n(2)] authorized: Vec<IdentityIdentifier>,
+    #[b(10)] options: Option<ServiceOptions<'a>>,
+}
+
+impl<'a> StartSignerService<'a> {
+    pub fn new(addr: &'a str, authorized: Vec<IdentityIdentifier>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr,
+            authorized,
+            options: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: ServiceOptions<'a>) -> Self {
+        self.options = Some(options);
+        self
+    }
+
     pub fn address(&self) -> &'a str {
         self.addr
     }
+
+    /// Identities of the authenticators allowed to request signatures.
+    pub fn authorized(&self) -> &[IdentityIdentifier] {
+        &self.authorized
+    }
 }
 
 #[derive(Debug, Clone, Decode, Encode)]
@@ -172,6 +680,7 @@ pub struct StartCredentialsService<'a> {
     #[n(0)] tag: TypeTag<6467937>,
     #[b(1)] addr: &'a str,
     #[n(2)] oneway: bool,
+    #[b(10)] options: Option<ServiceOptions<'a>>,
 }
 
 impl<'a> StartCredentialsService<'a> {
@@ -181,9 +690,15 @@ impl<'a> StartCredentialsService<'a> {
             tag: TypeTag,
             addr,
             oneway,
+            options: None,
         }
     }
 
+    pub fn with_options(mut self, options: ServiceOptions<'a>) -> Self {
+        self.options = Some(options);
+        self
+    }
+
     pub fn address(&self) -> &'a str {
         self.addr
     }
@@ -193,6 +708,150 @@ impl<'a> StartCredentialsService<'a> {
     }
 }
 
+/// Request body when instructing a node to start a Kafka outlet service
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartKafkaOutletRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4582911>,
+    #[b(1)] addr: CowStr<'a>,
+    #[b(2)] bootstrap_server: CowStr<'a>,
+}
+
+impl<'a> StartKafkaOutletRequest<'a> {
+    pub fn new(addr: impl Into<CowStr<'a>>, bootstrap_server: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: addr.into(),
+            bootstrap_server: bootstrap_server.into(),
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.addr
+    }
+
+    pub fn bootstrap_server(&self) -> &str {
+        &self.bootstrap_server
+    }
+}
+
+/// Request body when instructing a node to start a Kafka inlet service
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartKafkaInletRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7370581>,
+    #[b(1)] addr: CowStr<'a>,
+    #[b(2)] bind_addr: CowStr<'a>,
+    /// Address of the Kafka outlet service.
+    #[b(3)] to: CowStr<'a>,
+}
+
+impl<'a> StartKafkaInletRequest<'a> {
+    pub fn new(
+        addr: impl Into<CowStr<'a>>,
+        bind_addr: impl Into<CowStr<'a>>,
+        to: impl Into<CowStr<'a>>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: addr.into(),
+            bind_addr: bind_addr.into(),
+            to: to.into(),
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.addr
+    }
+
+    pub fn bind_addr(&self) -> &str {
+        &self.bind_addr
+    }
+
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+}
+
+/// Request body when instructing a node to start a SOCKS5 / HTTP CONNECT proxy service
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StartProxyServiceRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2816354>,
+    #[b(1)] addr: CowStr<'a>,
+    #[b(2)] bind_addr: CowStr<'a>,
+    /// Address of the node hosting the outlets.
+    #[b(3)] to: CowStr<'a>,
+    /// Addresses of the outlets clients may connect to.
+    #[b(4)] outlets: Vec<CowStr<'a>>,
+}
+
+impl<'a> StartProxyServiceRequest<'a> {
+    pub fn new(
+        addr: impl Into<CowStr<'a>>,
+        bind_addr: impl Into<CowStr<'a>>,
+        to: impl Into<CowStr<'a>>,
+        outlets: Vec<CowStr<'a>>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: addr.into(),
+            bind_addr: bind_addr.into(),
+            to: to.into(),
+            outlets,
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.addr
+    }
+
+    pub fn bind_addr(&self) -> &str {
+        &self.bind_addr
+    }
+
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    pub fn outlets(&self) -> &[CowStr<'a>] {
+        &self.outlets
+    }
+}
+
+/// Request body when instructing a node to stop a service
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct StopServiceRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7093715>,
+    #[b(1)] addr: CowStr<'a>,
+}
+
+impl<'a> StopServiceRequest<'a> {
+    pub fn new(addr: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: addr.into(),
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.addr
+    }
+}
+
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
 #[cbor(map)]
@@ -201,6 +860,8 @@ pub struct ServiceStatus<'a> {
     #[n(0)] tag: TypeTag<8542064>,
     #[n(2)] pub addr: Cow<'a, str>,
     #[n(3)] pub service_type: Cow<'a, str>,
+    /// Number of times the service was restarted after its worker stopped.
+    #[n(4)] pub restarts: u32,
 }
 
 impl<'a> ServiceStatus<'a> {
@@ -210,8 +871,14 @@ impl<'a> ServiceStatus<'a> {
             tag: TypeTag,
             addr: addr.into(),
             service_type: service_type.into(),
+            restarts: 0,
         }
     }
+
+    pub fn with_restarts(mut self, restarts: u32) -> Self {
+        self.restarts = restarts;
+        self
+    }
 }
 
 /// Response body for listing services
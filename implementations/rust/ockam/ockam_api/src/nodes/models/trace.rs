@@ -0,0 +1,104 @@
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+use ockam_core::{Result, Route};
+use ockam_multiaddr::MultiAddr;
+use std::str::FromStr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+use crate::error::ApiError;
+
+/// Request body when instructing a node to trace a route
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TraceRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7319046>,
+    /// Route to trace, the probe is echoed back by the echo service at
+    /// its end
+    #[b(1)] to: CowStr<'a>,
+    /// How long to wait for the echoed probe, in milliseconds
+    #[n(2)] timeout_ms: u64,
+}
+
+impl<'a> TraceRequest<'a> {
+    pub fn new(to: impl Into<CowStr<'a>>, timeout_ms: u64) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            to: to.into(),
+            timeout_ms,
+        }
+    }
+
+    pub fn to(&self) -> Result<Route> {
+        let maddr = MultiAddr::from_str(self.to.as_ref())
+            .map_err(|_err| ApiError::generic(&format!("Invalid route: {}", self.to)))?;
+        crate::multiaddr_to_route(&maddr)
+            .ok_or_else(|| ApiError::generic(&format!("Invalid MultiAddr: {}", maddr)))
+    }
+
+    pub fn timeout_ms(&self) -> u64 {
+        self.timeout_ms
+    }
+}
+
+/// A hop of a traced route
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TracedHop<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<4627518>,
+    /// Address of the worker which forwarded the probe
+    #[b(1)] pub address: CowStr<'a>,
+    /// Address the probe was forwarded to
+    #[b(2)] pub next: CowStr<'a>,
+    /// Time since the probe was sent, as given by the clock of the
+    /// forwarding node
+    #[n(3)] pub elapsed_micros: i64,
+}
+
+impl<'a> TracedHop<'a> {
+    pub fn new(
+        address: impl Into<CowStr<'a>>,
+        next: impl Into<CowStr<'a>>,
+        elapsed_micros: i64,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            address: address.into(),
+            next: next.into(),
+            elapsed_micros,
+        }
+    }
+}
+
+/// Response body of a route trace
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TraceReport<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<2291873>,
+    /// Hops of the probe on its way to the echo service and back
+    #[b(1)] pub hops: Vec<TracedHop<'a>>,
+    /// Time until the echoed probe came back
+    #[n(2)] pub round_trip_micros: u64,
+}
+
+impl<'a> TraceReport<'a> {
+    pub fn new(hops: Vec<TracedHop<'a>>, round_trip_micros: u64) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            hops,
+            round_trip_micros,
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use minicbor::{Decode, Encode};
+use ockam::tcp::{TcpConnectionInfo, TcpConnectionMode, TcpListenerInfo};
 use ockam_core::compat::borrow::Cow;
+use ockam_core::CowStr;
+use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display};
 
 #[cfg(feature = "tag")]
@@ -20,7 +23,7 @@ pub struct CreateTransport<'a> {
     /// The mode the transport should operate in
     #[n(2)] pub tm: TransportMode,
     /// The address payload for the transport
-    #[n(3)] pub addr: Cow<'a, str>,
+    #[b(3)] pub addr: CowStr<'a>,
 }
 
 impl<'a> CreateTransport<'a> {
@@ -30,7 +33,7 @@ impl<'a> CreateTransport<'a> {
             tag: TypeTag,
             tt,
             tm,
-            addr: addr.into(),
+            addr: CowStr(addr.into()),
         }
     }
 }
@@ -44,7 +47,7 @@ pub struct DeleteTransport<'a> {
     #[n(0)]
     tag: TypeTag<4739996>,
     /// The transport ID to delete
-    #[n(1)] pub tid: Cow<'a, str>,
+    #[b(1)] pub tid: CowStr<'a>,
     /// The user has indicated that deleting the API transport is A-OK
     #[n(2)] pub force: bool,
 }
@@ -54,7 +57,7 @@ impl<'a> DeleteTransport<'a> {
         Self {
             #[cfg(feature = "tag")]
             tag: TypeTag,
-            tid: tid.into(),
+            tid: CowStr(tid.into()),
             force,
         }
     }
@@ -86,8 +89,9 @@ impl Display for TransportType {
 }
 
 /// Encode which type of transport is being requested
-#[derive(Copy, Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, Decode, Encode, PartialEq, Eq, Serialize, Deserialize)]
 #[rustfmt::skip]
+#[serde(rename_all = "snake_case")]
 pub enum TransportMode {
     /// Listen on a set address
     #[n(0)] Listen,
@@ -163,3 +167,108 @@ impl<'a> TransportList<'a> {
         }
     }
 }
+
+/// An open connection of the tcp transport, as seen by the transport
+/// itself rather than the node manager
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TcpConnectionStatus<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7482913>,
+    /// Address of the worker sending over the connection, used to close it
+    #[b(1)] pub address: CowStr<'a>,
+    /// Address of the remote peer
+    #[b(2)] pub peer: CowStr<'a>,
+    /// Whether the connection was accepted by a listener of the node
+    #[n(3)] pub incoming: bool,
+    /// Seconds since the connection was established
+    #[n(4)] pub age: u64,
+    /// Bytes written to the connection
+    #[n(5)] pub bytes_sent: u64,
+    /// Bytes read from the connection
+    #[n(6)] pub bytes_received: u64,
+}
+
+impl<'a> TcpConnectionStatus<'a> {
+    pub fn new(info: &TcpConnectionInfo) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            address: CowStr::from(info.address().to_string()),
+            peer: CowStr::from(info.peer().to_string()),
+            incoming: info.mode() == TcpConnectionMode::Incoming,
+            age: info.age().as_secs(),
+            bytes_sent: info.bytes_sent(),
+            bytes_received: info.bytes_received(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TcpConnectionList<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3094856>,
+    #[n(1)] pub list: Vec<TcpConnectionStatus<'a>>
+}
+
+impl<'a> TcpConnectionList<'a> {
+    pub fn new(list: Vec<TcpConnectionStatus<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
+        }
+    }
+}
+
+/// A listener of the tcp transport
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TcpListenerStatus<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5861230>,
+    /// Address of the processor accepting connections
+    #[b(1)] pub address: CowStr<'a>,
+    /// Socket address the listener is bound to
+    #[b(2)] pub bind: CowStr<'a>,
+    /// Seconds since the listener was bound
+    #[n(3)] pub age: u64,
+    /// Number of connections accepted so far
+    #[n(4)] pub accepted: u64,
+}
+
+impl<'a> TcpListenerStatus<'a> {
+    pub fn new(info: &TcpListenerInfo) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            address: CowStr::from(info.address().to_string()),
+            bind: CowStr::from(info.bind().to_string()),
+            age: info.age().as_secs(),
+            accepted: info.accepted(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TcpListenerList<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2730418>,
+    #[n(1)] pub list: Vec<TcpListenerStatus<'a>>
+}
+
+impl<'a> TcpListenerList<'a> {
+    pub fn new(list: Vec<TcpListenerStatus<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
+        }
+    }
+}
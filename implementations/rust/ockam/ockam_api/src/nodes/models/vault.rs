@@ -1,8 +1,10 @@
 use minicbor::{Decode, Encode};
+use serde::Serialize;
 
-use ockam_core::CowStr;
+use ockam_core::vault::SecretType;
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
+use ockam_core::{CowBytes, CowStr};
 
 /// Request body when instructing a node to create a Vault
 #[derive(Debug, Clone, Decode, Encode)]
@@ -23,3 +25,133 @@ impl<'a> CreateVaultRequest<'a> {
         }
     }
 }
+
+/// A key stored in a node's Vault
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct KeyInfo<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<7372019>,
+    #[b(1)] pub key_id: CowStr<'a>,
+    #[n(2)] pub stype: SecretType,
+    /// Hex-encoded public key, for asymmetric keys only
+    #[b(3)] pub public_key: Option<CowStr<'a>>,
+}
+
+impl<'a> KeyInfo<'a> {
+    pub fn new(
+        key_id: impl Into<CowStr<'a>>,
+        stype: SecretType,
+        public_key: Option<impl Into<CowStr<'a>>>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            key_id: key_id.into(),
+            stype,
+            public_key: public_key.map(|p| p.into()),
+        }
+    }
+}
+
+/// Response body listing the keys of a node's Vault
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct KeyList<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<4036201>,
+    #[b(1)] pub keys: Vec<KeyInfo<'a>>,
+}
+
+impl<'a> KeyList<'a> {
+    pub fn new(keys: Vec<KeyInfo<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            keys,
+        }
+    }
+}
+
+/// Request body when instructing a node to export its Vault
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ExportVaultRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2283541>,
+    /// Password the backup is encrypted with
+    #[b(1)] pub password: CowStr<'a>,
+}
+
+impl<'a> ExportVaultRequest<'a> {
+    pub fn new(password: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            password: password.into(),
+        }
+    }
+}
+
+/// An encrypted backup of a Vault
+///
+/// The keys are encrypted with AES-256-GCM, using a key derived from a
+/// password and the salt with PBKDF2-HMAC-SHA256 and the given number of
+/// iterations.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct VaultBackup<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6659852>,
+    #[b(1)] pub salt: CowBytes<'a>,
+    #[b(2)] pub nonce: CowBytes<'a>,
+    #[b(3)] pub ciphertext: CowBytes<'a>,
+    #[n(4)] pub iterations: u32,
+}
+
+impl<'a> VaultBackup<'a> {
+    pub fn new(
+        iterations: u32,
+        salt: impl Into<CowBytes<'a>>,
+        nonce: impl Into<CowBytes<'a>>,
+        ciphertext: impl Into<CowBytes<'a>>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            salt: salt.into(),
+            nonce: nonce.into(),
+            ciphertext: ciphertext.into(),
+            iterations,
+        }
+    }
+}
+
+/// Request body when instructing a node to import a Vault backup
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ImportVaultRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<9112638>,
+    /// Password the backup was encrypted with
+    #[b(1)] pub password: CowStr<'a>,
+    #[b(2)] pub backup: VaultBackup<'a>,
+}
+
+impl<'a> ImportVaultRequest<'a> {
+    pub fn new(password: impl Into<CowStr<'a>>, backup: VaultBackup<'a>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            password: password.into(),
+            backup,
+        }
+    }
+}
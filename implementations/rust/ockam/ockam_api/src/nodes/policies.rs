@@ -0,0 +1,211 @@
+//! Persistent storage of the ABAC policies of a node.
+
+use ockam::abac::mem::{Memory, PolicySnapshot};
+use ockam::abac::{AbacPolicyStorage, Action, CompiledPolicy, Conditional, Resource};
+use ockam::compat::asynchronous::Mutex;
+use ockam_core::{async_trait, Result};
+use ockam_identity::authenticated_storage::AuthenticatedStorage;
+use std::sync::Arc;
+
+use crate::error::ApiError;
+
+/// Storage identifier and key of the policies.
+const POLICIES_ID: &str = "policies";
+const POLICIES_KEY: &str = "snapshot";
+
+/// ABAC policies kept in memory and saved in an [`AuthenticatedStorage`]
+/// after every change, so that they survive a node restart.
+pub struct PersistentPolicies<S> {
+    memory: Memory,
+    storage: S,
+    /// Serialises changes, so that saved snapshots are in change order.
+    lock: Mutex<()>,
+}
+
+impl<S: AuthenticatedStorage> PersistentPolicies<S> {
+    /// Load the policies saved in the given storage, if any.
+    pub async fn load(storage: S) -> Result<Self> {
+        let memory = Memory::new();
+        if let Some(data) = storage.get(POLICIES_ID, POLICIES_KEY).await? {
+            let snapshot: PolicySnapshot = serde_json::from_slice(&data)
+                .map_err(|e| ApiError::generic(&format!("Invalid saved policies: {e}")))?;
+            memory.restore_policies(snapshot)?;
+        }
+        Ok(Self {
+            memory,
+            storage,
+            lock: Mutex::new(()),
+        })
+    }
+
+    /// Save the policies, restoring the previous ones if they can't be saved.
+    async fn save(&self, previous: PolicySnapshot) -> Result<()> {
+        let data = serde_json::to_vec(&self.memory.policy_snapshot()?)
+            .map_err(|e| ApiError::generic(&format!("Failed to encode policies: {e}")));
+        let saved = match data {
+            Ok(data) => {
+                self.storage
+                    .set(POLICIES_ID, POLICIES_KEY.to_string(), data)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        if saved.is_err() {
+            self.memory.restore_policies(previous)?
+        }
+        saved
+    }
+}
+
+#[async_trait]
+impl<S: AuthenticatedStorage> AbacPolicyStorage for PersistentPolicies<S> {
+    async fn del_policy(&self, r: &Resource) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        let previous = self.memory.policy_snapshot()?;
+        self.memory.del_policy(r).await?;
+        self.save(previous).await
+    }
+
+    async fn del_policy_action(&self, r: &Resource, a: &Action) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        let previous = self.memory.policy_snapshot()?;
+        self.memory.del_policy_action(r, a).await?;
+        self.save(previous).await
+    }
+
+    async fn get_policy(&self, r: &Resource, a: &Action) -> Result<Option<Conditional>> {
+        self.memory.get_policy(r, a).await
+    }
+
+    async fn set_policy(&self, r: Resource, a: Action, c: &Conditional) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        let previous = self.memory.policy_snapshot()?;
+        self.memory.set_policy(r, a, c).await?;
+        self.save(previous).await
+    }
+
+    async fn set_policies(&self, r: Resource, a: &[Action], c: &Conditional) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        let previous = self.memory.policy_snapshot()?;
+        self.memory.set_policies(r, a, c).await?;
+        self.save(previous).await
+    }
+
+    async fn get_policy_history(&self, r: &Resource, a: &Action) -> Result<Vec<Conditional>> {
+        self.memory.get_policy_history(r, a).await
+    }
+
+    async fn rollback_policy(&self, r: &Resource, a: &Action) -> Result<Option<Conditional>> {
+        let _guard = self.lock.lock().await;
+        let previous = self.memory.policy_snapshot()?;
+        let restored = self.memory.rollback_policy(r, a).await?;
+        self.save(previous).await?;
+        Ok(restored)
+    }
+
+    async fn get_named_policy(&self, name: &str) -> Result<Option<Conditional>> {
+        self.memory.get_named_policy(name).await
+    }
+
+    async fn set_named_policy(&self, name: &str, c: &Conditional) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        let previous = self.memory.policy_snapshot()?;
+        self.memory.set_named_policy(name, c).await?;
+        self.save(previous).await
+    }
+
+    async fn del_named_policy(&self, name: &str) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        let previous = self.memory.policy_snapshot()?;
+        self.memory.del_named_policy(name).await?;
+        self.save(previous).await
+    }
+
+    async fn get_compiled_policy(
+        &self,
+        r: &Resource,
+        a: &Action,
+    ) -> Result<Option<Arc<CompiledPolicy>>> {
+        self.memory.get_compiled_policy(r, a).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_identity::authenticated_storage::mem::InMemoryStorage;
+
+    #[ockam_macros::test]
+    async fn policies_are_reloaded(ctx: &mut ockam::Context) -> Result<()> {
+        let storage = InMemoryStorage::new();
+        let resource = Resource::from("/outlet/db");
+        let action = Action::from("handle_message");
+
+        let policies = PersistentPolicies::load(storage.clone()).await?;
+        policies.set_named_policy("a", &Conditional::True).await?;
+        for c in [Conditional::False, Conditional::Policy("a".into())] {
+            policies
+                .set_policy(resource.clone(), action.clone(), &c)
+                .await?;
+        }
+        policies.rollback_policy(&resource, &action).await?;
+
+        let reloaded = PersistentPolicies::load(storage).await?;
+        let current = reloaded.get_policy(&resource, &action).await?;
+        assert!(matches!(current, Some(Conditional::False)));
+        let named = reloaded.get_named_policy("a").await?;
+        assert!(matches!(named, Some(Conditional::True)));
+
+        ctx.stop().await
+    }
+
+    /// A storage which can't save anything.
+    #[derive(Clone)]
+    struct ReadOnlyStorage;
+
+    #[async_trait]
+    impl AuthenticatedStorage for ReadOnlyStorage {
+        async fn get(&self, _: &str, _: &str) -> Result<Option<Vec<u8>>> {
+            Ok(None)
+        }
+
+        async fn set(&self, _: &str, _: String, _: Vec<u8>) -> Result<()> {
+            Err(ApiError::generic("read-only storage"))
+        }
+
+        async fn del(&self, _: &str, _: &str) -> Result<()> {
+            Err(ApiError::generic("read-only storage"))
+        }
+
+        async fn take(&self, _: &str, _: &str) -> Result<Option<Vec<u8>>> {
+            Err(ApiError::generic("read-only storage"))
+        }
+    }
+
+    #[ockam_macros::test]
+    async fn policies_of_several_actions_are_set_together(ctx: &mut ockam::Context) -> Result<()> {
+        let resource = Resource::from("/outlet/db");
+        let actions = [Action::from("handle_message"), Action::from("delete")];
+
+        let policies = PersistentPolicies::load(InMemoryStorage::new()).await?;
+        policies
+            .set_policies(resource.clone(), &actions, &Conditional::False)
+            .await?;
+        for a in &actions {
+            let current = policies.get_policy(&resource, a).await?;
+            assert!(matches!(current, Some(Conditional::False)));
+        }
+
+        // When the policies can't be saved, no action keeps the new policy
+        let policies = PersistentPolicies::load(ReadOnlyStorage).await?;
+        assert!(policies
+            .set_policies(resource.clone(), &actions, &Conditional::True)
+            .await
+            .is_err());
+        for a in &actions {
+            assert!(policies.get_policy(&resource, a).await?.is_none());
+        }
+
+        ctx.stop().await
+    }
+}
@@ -1,38 +1,92 @@
 use crate::nodes::service::Alias;
-use ockam_core::compat::collections::BTreeMap;
+use ockam::remote::{RegistrationExpiry, RemoteForwarderInfo};
+use ockam::tcp::PortalTraffic;
+use ockam_core::compat::collections::{BTreeMap, HashMap};
+use ockam_core::compat::sync::{Arc, RwLock};
 use ockam_core::{Address, Route};
-use ockam_identity::IdentityIdentifier;
+use ockam_identity::{IdentityIdentifier, SecureChannelCounters};
+use ockam_node::tokio::task::JoinHandle;
+use std::time::Duration;
 
-#[derive(Default)]
+/// The secure channels created by the node, indexed by their address and
+/// by the routes they were created for.
+///
+/// Clones share the same channels, so that tasks can look channels up
+/// without holding on to the node manager.
+#[derive(Default, Clone)]
 pub(crate) struct SecureChannelRegistry {
-    channels: Vec<SecureChannelInfo>,
+    channels: Arc<RwLock<SecureChannels>>,
+}
+
+#[derive(Default)]
+struct SecureChannels {
+    by_addr: BTreeMap<Address, SecureChannelInfo>,
+    /// Addresses of the channels by their route and candidate routes, in
+    /// the order the channels were created
+    by_route: HashMap<Route, Vec<Address>>,
 }
 
 impl SecureChannelRegistry {
-    pub fn get_by_route(&self, route: &Route) -> Option<&SecureChannelInfo> {
-        self.channels.iter().find(|&x| x.route() == route)
+    pub fn get_by_route(&self, route: &Route) -> Option<SecureChannelInfo> {
+        let channels = self.channels.read().unwrap();
+        let addr = channels.by_route.get(route)?.first()?;
+        channels.by_addr.get(addr).cloned()
     }
 
-    pub fn get_by_addr(&self, addr: &Address) -> Option<&SecureChannelInfo> {
-        self.channels.iter().find(|&x| x.addr() == addr)
+    pub fn get_by_addr(&self, addr: &Address) -> Option<SecureChannelInfo> {
+        self.channels.read().unwrap().by_addr.get(addr).cloned()
     }
 
     pub fn insert(
-        &mut self,
+        &self,
         addr: Address,
         route: Route,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        counters: Arc<SecureChannelCounters>,
+        candidates: Vec<Route>,
     ) {
-        self.channels
-            .push(SecureChannelInfo::new(route, addr, authorized_identifiers))
+        let info = SecureChannelInfo::new(route, addr.clone(), authorized_identifiers, counters)
+            .with_candidates(candidates);
+        let mut channels = self.channels.write().unwrap();
+        channels.remove(&addr);
+        for r in info.routes() {
+            channels
+                .by_route
+                .entry(r.clone())
+                .or_default()
+                .push(addr.clone())
+        }
+        channels.by_addr.insert(addr, info);
+    }
+
+    pub fn remove_by_addr(&self, addr: &Address) {
+        self.channels.write().unwrap().remove(addr)
     }
 
-    pub fn remove_by_addr(&mut self, addr: &Address) {
-        self.channels.retain(|x| x.addr() != addr)
+    /// The channels, ordered by address.
+    pub fn list(&self) -> Vec<SecureChannelInfo> {
+        self.channels
+            .read()
+            .unwrap()
+            .by_addr
+            .values()
+            .cloned()
+            .collect()
     }
+}
 
-    pub fn list(&self) -> &[SecureChannelInfo] {
-        &self.channels
+impl SecureChannels {
+    fn remove(&mut self, addr: &Address) {
+        if let Some(info) = self.by_addr.remove(addr) {
+            for r in info.routes() {
+                if let Some(addrs) = self.by_route.get_mut(r) {
+                    addrs.retain(|a| a != addr);
+                    if addrs.is_empty() {
+                        self.by_route.remove(r);
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -43,6 +97,10 @@ pub struct SecureChannelInfo {
     // Local address of the created channel
     addr: Address,
     authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+    // Messages rejected by the channel
+    counters: Arc<SecureChannelCounters>,
+    // All the routes which were attempted, `route` being the one which won
+    candidates: Vec<Route>,
 }
 
 impl SecureChannelInfo {
@@ -50,14 +108,22 @@ impl SecureChannelInfo {
         route: Route,
         addr: Address,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        counters: Arc<SecureChannelCounters>,
     ) -> Self {
         Self {
             addr,
             route,
             authorized_identifiers,
+            counters,
+            candidates: Vec::new(),
         }
     }
 
+    pub fn with_candidates(mut self, candidates: Vec<Route>) -> Self {
+        self.candidates = candidates;
+        self
+    }
+
     pub fn route(&self) -> &Route {
         &self.route
     }
@@ -69,10 +135,34 @@ impl SecureChannelInfo {
     pub fn authorized_identifiers(&self) -> Option<&Vec<IdentityIdentifier>> {
         self.authorized_identifiers.as_ref()
     }
+
+    pub fn counters(&self) -> &SecureChannelCounters {
+        &self.counters
+    }
+
+    pub fn candidates(&self) -> &[Route] {
+        &self.candidates
+    }
+
+    /// The route of the channel and its candidate routes.
+    fn routes(&self) -> impl Iterator<Item = &Route> {
+        core::iter::once(&self.route).chain(&self.candidates)
+    }
 }
 
 #[derive(Default)]
-pub(crate) struct SecureChannelListenerInfo {}
+pub(crate) struct SecureChannelListenerInfo {
+    /// Fetches the members accepted by the listener, if it syncs them
+    pub(crate) member_sync: Option<JoinHandle<()>>,
+}
+
+impl Drop for SecureChannelListenerInfo {
+    fn drop(&mut self) {
+        if let Some(sync) = &self.member_sync {
+            sync.abort()
+        }
+    }
+}
 
 #[derive(Default)]
 pub(crate) struct VaultServiceInfo {}
@@ -83,25 +173,73 @@ pub(crate) struct IdentityServiceInfo {}
 #[derive(Default)]
 pub(crate) struct AuthenticatedServiceInfo {}
 
-#[derive(Default)]
-pub(crate) struct UppercaseServiceInfo {}
+/// A running service of a catalog kind.
+pub(crate) struct ServiceInfo {
+    kind: &'static str,
+    /// The start request of services restarted when their worker stops.
+    restart: Option<Vec<u8>>,
+    /// Number of restarts after which the service is no longer restarted.
+    max_restarts: Option<u32>,
+    /// Number of times the service was restarted.
+    restarts: u32,
+}
 
-#[derive(Default)]
-pub(crate) struct EchoerServiceInfo {}
+impl ServiceInfo {
+    pub(crate) fn new(kind: &'static str) -> Self {
+        Self {
+            kind,
+            restart: None,
+            max_restarts: None,
+            restarts: 0,
+        }
+    }
 
-#[derive(Default)]
-pub(crate) struct VerifierServiceInfo {}
+    pub(crate) fn with_restart(mut self, req: Vec<u8>, max_restarts: Option<u32>) -> Self {
+        self.restart = Some(req);
+        self.max_restarts = max_restarts;
+        self
+    }
 
-#[derive(Default)]
-pub(crate) struct CredentialsServiceInfo {}
+    pub(crate) fn with_restarts(mut self, restarts: u32) -> Self {
+        self.restarts = restarts;
+        self
+    }
+
+    pub(crate) fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    pub(crate) fn restart(&self) -> Option<&[u8]> {
+        self.restart.as_deref()
+    }
+
+    pub(crate) fn max_restarts(&self) -> Option<u32> {
+        self.max_restarts
+    }
+
+    pub(crate) fn restarts(&self) -> u32 {
+        self.restarts
+    }
+
+    /// Stop restarting the service.
+    pub(crate) fn give_up(&mut self) {
+        self.restart = None
+    }
+}
+
+pub(crate) struct KafkaServiceInfo {
+    /// Either `kafka_inlet` or `kafka_outlet`.
+    pub(crate) kind: &'static str,
+}
 
 #[derive(Default)]
-pub(crate) struct AuthenticatorServiceInfo {}
+pub(crate) struct ProxyServiceInfo {}
 
 pub(crate) struct InletInfo {
     pub(crate) bind_addr: String,
     pub(crate) worker_addr: Address,
     pub(crate) outlet_route: Route,
+    pub(crate) traffic: Arc<PortalTraffic>,
 }
 
 impl InletInfo {
@@ -109,6 +247,7 @@ impl InletInfo {
         bind_addr: &str,
         worker_addr: Option<&Address>,
         outlet_route: &Route,
+        traffic: Arc<PortalTraffic>,
     ) -> Self {
         let worker_addr = match worker_addr {
             Some(addr) => addr.clone(),
@@ -118,6 +257,7 @@ impl InletInfo {
             bind_addr: bind_addr.to_owned(),
             worker_addr,
             outlet_route: outlet_route.to_owned(),
+            traffic,
         }
     }
 }
@@ -125,10 +265,15 @@ impl InletInfo {
 pub(crate) struct OutletInfo {
     pub(crate) tcp_addr: String,
     pub(crate) worker_addr: Address,
+    pub(crate) traffic: Arc<PortalTraffic>,
 }
 
 impl OutletInfo {
-    pub(crate) fn new(tcp_addr: &str, worker_addr: Option<&Address>) -> Self {
+    pub(crate) fn new(
+        tcp_addr: &str,
+        worker_addr: Option<&Address>,
+        traffic: Arc<PortalTraffic>,
+    ) -> Self {
         let worker_addr = match worker_addr {
             Some(addr) => addr.clone(),
             None => Address::from_string(""),
@@ -136,10 +281,28 @@ impl OutletInfo {
         Self {
             tcp_addr: tcp_addr.to_owned(),
             worker_addr,
+            traffic,
         }
     }
 }
 
+pub(crate) struct ForwarderRegistryInfo {
+    pub(crate) info: RemoteForwarderInfo,
+    /// Updated by the forwarder whenever its registration is confirmed
+    pub(crate) expiry: Option<RegistrationExpiry>,
+}
+
+impl ForwarderRegistryInfo {
+    pub(crate) fn new(info: RemoteForwarderInfo, expiry: Option<RegistrationExpiry>) -> Self {
+        Self { info, expiry }
+    }
+
+    /// The time until the registration expires unless refreshed.
+    pub(crate) fn remaining_ttl(&self) -> Option<Duration> {
+        self.expiry.as_ref()?.remaining()
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct Registry {
     pub(crate) secure_channels: SecureChannelRegistry,
@@ -147,14 +310,50 @@ pub(crate) struct Registry {
     pub(crate) vault_services: BTreeMap<Address, VaultServiceInfo>,
     pub(crate) identity_services: BTreeMap<Address, IdentityServiceInfo>,
     pub(crate) authenticated_services: BTreeMap<Address, AuthenticatedServiceInfo>,
-    pub(crate) uppercase_services: BTreeMap<Address, UppercaseServiceInfo>,
-    pub(crate) echoer_services: BTreeMap<Address, EchoerServiceInfo>,
-    pub(crate) verifier_services: BTreeMap<Address, VerifierServiceInfo>,
-    pub(crate) credentials_services: BTreeMap<Address, CredentialsServiceInfo>,
-    #[cfg(feature = "direct-authenticator")]
-    pub(crate) authenticator_service: BTreeMap<Address, AuthenticatorServiceInfo>,
+    pub(crate) kafka_services: BTreeMap<Address, KafkaServiceInfo>,
+    pub(crate) proxy_services: BTreeMap<Address, ProxyServiceInfo>,
+    /// Services started through the service catalog
+    pub(crate) services: BTreeMap<Address, ServiceInfo>,
 
     // FIXME: wow this is a terrible way to store data
     pub(crate) inlets: BTreeMap<Alias, InletInfo>,
     pub(crate) outlets: BTreeMap<Alias, OutletInfo>,
+    /// Forwarders by their remote address
+    pub(crate) forwarders: BTreeMap<String, ForwarderRegistryInfo>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::route;
+
+    #[test]
+    fn secure_channels_are_indexed_by_address_and_routes() {
+        let registry = SecureChannelRegistry::default();
+        let counters = Arc::new(SecureChannelCounters::default());
+        registry.insert(
+            "sc1".into(),
+            route!["tcp", "api"],
+            None,
+            counters.clone(),
+            vec![route!["relay", "api"]],
+        );
+        registry.insert("sc2".into(), route!["tcp", "api"], None, counters, vec![]);
+        assert_eq!(registry.list().len(), 2);
+
+        let sc1 = Address::from("sc1");
+        let by_candidate = registry.get_by_route(&route!["relay", "api"]).unwrap();
+        assert_eq!(by_candidate.addr(), &sc1);
+        assert_eq!(
+            registry.get_by_route(&route!["tcp", "api"]).unwrap().addr(),
+            &sc1
+        );
+
+        registry.remove_by_addr(&sc1);
+        assert!(registry.get_by_addr(&sc1).is_none());
+        assert!(registry.get_by_route(&route!["relay", "api"]).is_none());
+        let remaining = registry.get_by_route(&route!["tcp", "api"]).unwrap();
+        assert_eq!(remaining.addr(), &Address::from("sc2"));
+        assert_eq!(registry.list().len(), 1);
+    }
 }
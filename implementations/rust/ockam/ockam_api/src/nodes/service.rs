@@ -2,9 +2,10 @@
 
 use minicbor::Decoder;
 
+use ockam::abac::{AbacPolicyStorage, AttributeSchema};
 use ockam::compat::asynchronous::RwLock;
-use ockam::{Address, Context, ForwardingService, Result, Routed, TcpTransport, Worker};
-use ockam_core::api::{Error, Method, Request, Response, Status};
+use ockam::{Address, Context, LocalMessage, Result, Route, Routed, TcpTransport, Worker};
+use ockam_core::api::{self, Error, ErrorCode, Method, Request, Response, Status};
 use ockam_core::compat::{
     boxed::Box,
     string::String,
@@ -12,7 +13,9 @@ use ockam_core::compat::{
 };
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::AsyncTryClone;
-use ockam_identity::{Identity, IdentityIdentifier, PublicIdentity};
+use ockam_identity::{
+    Identity, IdentityIdentifier, KeyExchangeMode, PublicIdentity, SecureChannelListenerLimits,
+};
 use ockam_multiaddr::proto::{Project, Secure};
 use ockam_multiaddr::{MultiAddr, Protocol};
 use ockam_node::tokio;
@@ -22,29 +25,67 @@ use ockam_vault::Vault;
 use std::collections::BTreeMap;
 use std::error::Error as _;
 use std::path::PathBuf;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::Instrument;
 
 use super::models::secure_channel::CredentialExchangeMode;
 use super::registry::Registry;
+use crate::audit::{AuditLog, AUDIT_LOG_FILE};
+use crate::cloud::{ControllerCache, RetryPolicy};
 use crate::config::cli::AuthoritiesConfig;
 use crate::config::lookup::ProjectLookup;
+use crate::dead_letters::DeadLetters;
 use crate::error::ApiError;
 use crate::lmdb::LmdbStorage;
 use crate::nodes::config::NodeConfig;
-use crate::nodes::models::base::NodeStatus;
-use crate::nodes::models::transport::{TransportMode, TransportType};
-use crate::session::util::starts_with_host_tcp_secure;
-use crate::session::{Medic, Sessions};
+use crate::nodes::models::base::{
+    NodeDetails, NodeHealth, NodeStatus, SessionEdge, SessionGraph, SessionNode, SessionStatus,
+};
+use crate::nodes::models::identity::IdentityKeyBackend;
+use crate::nodes::models::services::{
+    StartAckServiceRequest, StartCredentialsService, StartEchoerServiceRequest,
+    StartForwardingService, StartUppercaseServiceRequest,
+};
+use crate::nodes::models::transport::{TransportMode, TransportStatus, TransportType};
+use crate::nodes::policies::PersistentPolicies;
+use crate::revocation::Revocations;
+use crate::session::util::{resolve_hosts, starts_with_host_tcp_secure};
+use crate::session::{Medic, Sessions, Status as SessionHealth};
+use crate::telemetry;
 use crate::{multiaddr_to_route, try_address_to_multiaddr, DefaultAddress};
+pub use authorization::LocalApiInfo;
+pub(crate) use cancellation::checkpoint;
+use cancellation::PendingRequests;
+use catalog::ServiceCatalog;
+use config::AppliedSetup;
+use fragments::PendingFragments;
+use idempotency::{Idempotency, IdempotentResponses};
+pub use logging::LogFilter;
 
 pub mod message;
 
+mod audit;
+mod authorization;
+mod cancellation;
+mod catalog;
+mod config;
 mod credentials;
+mod dead_letters;
+mod file_transfer;
 mod forwarder;
+mod fragments;
+mod idempotency;
 mod identity;
+mod logging;
+mod perf;
+mod policy;
 mod portals;
+mod revocation;
+mod route;
 mod secure_channel;
 mod services;
+mod trace;
 mod transport;
 mod vault;
 
@@ -100,31 +141,65 @@ pub struct NodeManager {
     node_name: String,
     node_dir: PathBuf,
     config: NodeConfig,
+    /// The part of the configured setup which was applied
+    setup: AppliedSetup,
+    /// Held while a setup is applied
+    setup_lock: Arc<tokio::sync::Mutex<()>>,
     api_transport_id: Alias,
     transports: BTreeMap<Alias, (TransportType, TransportMode, String)>,
     tcp_transport: TcpTransport,
     pub(crate) controller_identity_id: IdentityIdentifier,
+    pub(crate) controller_retry_policy: RetryPolicy,
+    pub(crate) controller_cache: ControllerCache,
+    idempotent_responses: IdempotentResponses,
     skip_defaults: bool,
     enable_credential_checks: bool,
     vault: Option<Vault>,
+    vault_storage: Option<Arc<FileStorage>>,
     identity: Option<Identity<Vault>>,
     project_id: Option<Vec<u8>>,
     projects: Arc<BTreeMap<String, ProjectLookup>>,
     authorities: Option<Authorities>,
     pub(crate) authenticated_storage: LmdbStorage,
+    pub(crate) policies: Arc<dyn AbacPolicyStorage>,
+    /// Declared attribute types, which policies are checked against
+    /// Shared with the access controls evaluating policies, which parse
+    /// credential attributes into their declared types.
+    pub(crate) attribute_schema: Arc<std::sync::RwLock<AttributeSchema>>,
+    pub(crate) audit: AuditLog,
+    dead_letters: Option<DeadLetters>,
+    pub(crate) revocations: Revocations,
+    revocation_sync: Option<JoinHandle<()>>,
     pub(crate) registry: Registry,
+    catalog: ServiceCatalog,
+    service_supervisor: Option<JoinHandle<()>>,
     sessions: Arc<Mutex<Sessions>>,
     medic: JoinHandle<Result<(), ockam_core::Error>>,
+    started: Instant,
+    heartbeat: AtomicU64,
+    log_filter: Option<Arc<dyn LogFilter>>,
+    tcp_api: bool,
 }
 
+/// Serves the API of a [`NodeManager`]
+///
+/// Every request is handled in its own task, so that a slow request, e.g. one
+/// waiting on the controller, does not hold up unrelated ones. Handlers share
+/// the node manager behind a lock, which they should not keep across remote
+/// calls.
+#[derive(Clone)]
 pub struct NodeManagerWorker {
     node_manager: Arc<RwLock<NodeManager>>,
+    pending: PendingRequests,
+    fragments: PendingFragments,
 }
 
 impl NodeManagerWorker {
     pub fn new(node_manager: NodeManager) -> Self {
         NodeManagerWorker {
             node_manager: Arc::new(RwLock::new(node_manager)),
+            pending: PendingRequests::default(),
+            fragments: PendingFragments::default(),
         }
     }
 
@@ -172,6 +247,9 @@ pub struct NodeManagerGeneralOptions {
     enable_credential_checks: bool,
     // Should be passed only when creating fresh node and we want it to get default root Identity
     identity_override: Option<IdentityOverride>,
+    controller_retry_policy: RetryPolicy,
+    log_filter: Option<Arc<dyn LogFilter>>,
+    tcp_api: bool,
 }
 
 impl NodeManagerGeneralOptions {
@@ -188,8 +266,31 @@ impl NodeManagerGeneralOptions {
             skip_defaults,
             enable_credential_checks,
             identity_override,
+            controller_retry_policy: RetryPolicy::default(),
+            log_filter: None,
+            tcp_api: true,
         }
     }
+
+    /// Set how requests to the controller are retried, unless overridden per request.
+    pub fn with_controller_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.controller_retry_policy = policy;
+        self
+    }
+
+    /// Let the tracing filter of the process be changed through the API.
+    pub fn with_log_filter(mut self, filter: Arc<dyn LogFilter>) -> Self {
+        self.log_filter = Some(filter);
+        self
+    }
+
+    /// Only accept API requests marked with [`LocalApiInfo`] or received over
+    /// a secure channel, e.g. when the node serves its API on a socket
+    /// instead of its TCP listener.
+    pub fn without_tcp_api(mut self) -> Self {
+        self.tcp_api = false;
+        self
+    }
 }
 
 pub struct NodeManagerProjectsOptions<'a> {
@@ -280,14 +381,14 @@ impl NodeManager {
 
         // Check if we had existing Vault
         let vault_path = state.read().vault_path.clone();
-        let vault = match vault_path {
+        let (vault, vault_storage) = match vault_path {
             Some(vault_path) => {
-                let vault_storage = FileStorage::create(vault_path).await?;
-                let vault = Vault::new(Some(Arc::new(vault_storage)));
+                let vault_storage = Arc::new(FileStorage::create(vault_path).await?);
+                let vault = Vault::new(Some(vault_storage.clone()));
 
-                Some(vault)
+                (Some(vault), Some(vault_storage))
             }
-            None => None,
+            None => (None, None),
         };
 
         // Check if we had existing Identity
@@ -314,29 +415,50 @@ impl NodeManager {
 
         let medic = Medic::new();
         let sessions = medic.sessions();
+        let audit = AuditLog::new(general_options.node_dir.join(AUDIT_LOG_FILE));
+        let revocations = Revocations::load(&authenticated_storage).await?;
+        let policies = PersistentPolicies::load(authenticated_storage.clone()).await?;
 
         let mut s = Self {
             node_name: general_options.node_name,
             node_dir: general_options.node_dir,
             config,
+            setup: Default::default(),
+            setup_lock: Default::default(),
             api_transport_id,
             transports,
             tcp_transport: transport_options.tcp_transport,
             controller_identity_id: Self::load_controller_identity_id()?,
+            controller_retry_policy: general_options.controller_retry_policy,
+            controller_cache: Default::default(),
+            idempotent_responses: Default::default(),
             skip_defaults: general_options.skip_defaults,
             enable_credential_checks: general_options.enable_credential_checks,
             vault,
+            vault_storage,
             identity,
             projects: Arc::new(projects_options.projects),
             project_id: projects_options.project_id,
             authorities: None,
             authenticated_storage,
+            policies: Arc::new(policies),
+            attribute_schema: Default::default(),
+            audit,
+            dead_letters: None,
+            revocations,
+            revocation_sync: None,
             registry: Default::default(),
+            catalog: Default::default(),
+            service_supervisor: None,
             medic: {
                 let ctx = ctx.async_try_clone().await?;
                 tokio::spawn(medic.start(ctx))
             },
             sessions,
+            started: Instant::now(),
+            heartbeat: AtomicU64::new(0),
+            log_filter: general_options.log_filter,
+            tcp_api: general_options.tcp_api,
         };
 
         if !general_options.skip_defaults {
@@ -347,8 +469,14 @@ impl NodeManager {
             }
         }
 
-        s.start_echoer_service_impl(ctx, DefaultAddress::ECHO_SERVICE.into())
-            .await?;
+        let req = StartEchoerServiceRequest::new(DefaultAddress::ECHO_SERVICE);
+        s.start_service_with(ctx, "echo", req).await?;
+        let req = StartAckServiceRequest::new(DefaultAddress::ACK_SERVICE);
+        s.start_service_with(ctx, "ack", req).await?;
+
+        if let Err(err) = s.restore_revocation_sync(ctx).await {
+            warn!(%err, "failed to restore the revocation sync")
+        }
 
         Ok(s)
     }
@@ -373,7 +501,8 @@ impl NodeManager {
     async fn create_defaults(&mut self, ctx: &Context) -> Result<()> {
         // Create default vault and identity, if they don't exists already
         self.create_vault_impl(None, true).await?;
-        self.create_identity_impl(ctx, true).await?;
+        self.create_identity_impl(ctx, true, IdentityKeyBackend::Software)
+            .await?;
 
         Ok(())
     }
@@ -386,21 +515,26 @@ impl NodeManager {
             .await?;
         self.start_authenticated_service_impl(ctx, DefaultAddress::AUTHENTICATED_SERVICE.into())
             .await?;
-        self.start_uppercase_service_impl(ctx, DefaultAddress::UPPERCASE_SERVICE.into())
-            .await?;
+        let req = StartUppercaseServiceRequest::new(DefaultAddress::UPPERCASE_SERVICE);
+        self.start_service_with(ctx, "uppercase", req).await?;
 
-        ForwardingService::create(ctx).await?;
+        let req = StartForwardingService::new(DefaultAddress::FORWARDING_SERVICE);
+        self.start_service_with(ctx, "forwarding", req).await?;
 
         self.create_secure_channel_listener_impl(
             DefaultAddress::SECURE_CHANNEL_LISTENER.into(),
             None, // Not checking identifiers here in favor of credentials check
+            None,
+            None,
+            SecureChannelListenerLimits::default(),
+            KeyExchangeMode::Classic,
         )
         .await?;
 
         // If we've been configured with authorities, we can start Credentials Exchange service
         if self.authorities().is_ok() {
-            self.start_credentials_service_impl(DefaultAddress::CREDENTIAL_SERVICE.into(), false)
-                .await?;
+            let req = StartCredentialsService::new(DefaultAddress::CREDENTIAL_SERVICE, false);
+            self.start_service_with(ctx, "credentials", req).await?;
         }
 
         Ok(())
@@ -416,44 +550,112 @@ impl NodeManager {
         auth: Option<IdentityIdentifier>,
         timeout: Option<Duration>,
     ) -> Result<(MultiAddr, MultiAddr)> {
+        self.connect_impl(addr, auth, timeout, false).await
+    }
+
+    /// Like `connect`, to replace a connection which failed.
+    ///
+    /// Host names, including the one of a project, are resolved again, as
+    /// the host may have moved to another IP address since.
+    async fn reconnect(
+        &mut self,
+        addr: &MultiAddr,
+        auth: Option<IdentityIdentifier>,
+        timeout: Option<Duration>,
+    ) -> Result<(MultiAddr, MultiAddr)> {
+        self.connect_impl(addr, auth, timeout, true).await
+    }
+
+    async fn connect_impl(
+        &mut self,
+        addr: &MultiAddr,
+        auth: Option<IdentityIdentifier>,
+        timeout: Option<Duration>,
+        resolve_hosts: bool,
+    ) -> Result<(MultiAddr, MultiAddr)> {
+        let plan = self.connect_plan(addr, auth, resolve_hosts)?;
+        match plan.channel {
+            Some(r) => {
+                let (i, m) = (plan.auth, plan.mode);
+                checkpoint()?;
+                let w = self.create_secure_channel_impl(r, i, m, timeout).await?;
+                Ok((try_address_to_multiaddr(&w)?, plan.rest))
+            }
+            None => Ok((MultiAddr::default(), plan.rest)),
+        }
+    }
+
+    /// The secure channel `connect` creates for an address, if any.
+    fn connect_plan(
+        &self,
+        addr: &MultiAddr,
+        auth: Option<IdentityIdentifier>,
+        resolve_hosts: bool,
+    ) -> Result<ConnectPlan> {
         if let Some(p) = addr.first() {
             if p.code() == Project::CODE {
                 let p = p
                     .cast::<Project>()
                     .ok_or_else(|| ApiError::message("invalid project protocol in multiaddr"))?;
                 let (a, i) = self.resolve_project(&p)?;
+                let a = if resolve_hosts {
+                    self.resolve_hosts(&a)?
+                } else {
+                    a
+                };
                 debug!(addr = %a, "creating secure channel");
                 let r =
                     multiaddr_to_route(&a).ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
-                let i = Some(vec![i]);
-                let m = CredentialExchangeMode::Oneway;
-                let w = self.create_secure_channel_impl(r, i, m, timeout).await?;
-                let a = MultiAddr::default().try_with(addr.iter().skip(1))?;
-                return Ok((try_address_to_multiaddr(&w)?, a));
+                return Ok(ConnectPlan {
+                    channel: Some(r),
+                    auth: Some(vec![i]),
+                    mode: CredentialExchangeMode::Oneway,
+                    rest: MultiAddr::default().try_with(addr.iter().skip(1))?,
+                });
             }
         }
 
+        let addr = &if resolve_hosts {
+            self.resolve_hosts(addr)?
+        } else {
+            addr.clone()
+        };
+
         if let Some(pos) = starts_with_host_tcp_secure(addr) {
             debug!(%addr, "creating secure channel");
             let (a, b) = addr.split(pos);
             let r = multiaddr_to_route(&a).ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
-            let i = auth.clone().map(|i| vec![i]);
-            let m = CredentialExchangeMode::Mutual;
-            let w = self.create_secure_channel_impl(r, i, m, timeout).await?;
-            return Ok((try_address_to_multiaddr(&w)?, b));
+            return Ok(ConnectPlan {
+                channel: Some(r),
+                auth: auth.map(|i| vec![i]),
+                mode: CredentialExchangeMode::Mutual,
+                rest: b,
+            });
         }
 
         if Some(Secure::CODE) == addr.last().map(|p| p.code()) {
             debug!(%addr, "creating secure channel");
             let r =
                 multiaddr_to_route(addr).ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
-            let i = auth.clone().map(|i| vec![i]);
-            let m = CredentialExchangeMode::Mutual;
-            let w = self.create_secure_channel_impl(r, i, m, timeout).await?;
-            return Ok((try_address_to_multiaddr(&w)?, MultiAddr::default()));
+            return Ok(ConnectPlan {
+                channel: Some(r),
+                auth: auth.map(|i| vec![i]),
+                mode: CredentialExchangeMode::Mutual,
+                rest: MultiAddr::default(),
+            });
         }
 
-        Ok((MultiAddr::default(), addr.clone()))
+        Ok(ConnectPlan {
+            channel: None,
+            auth: None,
+            mode: CredentialExchangeMode::None,
+            rest: addr.clone(),
+        })
+    }
+
+    /// Replace the host names of an address with their current IP addresses.
+    fn resolve_hosts(&self, addr: &MultiAddr) -> Result<MultiAddr> {
+        resolve_hosts(addr, |peer| self.tcp_transport.resolve(peer))
     }
 
     fn resolve_project(&self, name: &str) -> Result<(MultiAddr, IdentityIdentifier)> {
@@ -475,7 +677,107 @@ impl NodeManager {
     }
 }
 
+/// The secure channel to create to connect to an address, see
+/// [`NodeManager::connect`], and the remainder of the address.
+struct ConnectPlan {
+    channel: Option<Route>,
+    auth: Option<Vec<IdentityIdentifier>>,
+    mode: CredentialExchangeMode,
+    rest: MultiAddr,
+}
+
 impl NodeManagerWorker {
+    /// Collect everything running on this node into a single response.
+    async fn node_details(&self, ctx: &Context, req: &Request<'_>) -> Result<Vec<u8>> {
+        let node_manager = self.node_manager.read().await;
+        let status = NodeStatus::new(
+            node_manager.node_name.as_str(),
+            "Running",
+            ctx.list_workers().await?.len() as u32,
+            std::process::id() as i32,
+            node_manager.transports.len() as u32,
+        );
+        let identity = node_manager
+            .identity
+            .as_ref()
+            .map(|i| i.identifier().clone());
+        let uptime = node_manager.started.elapsed().as_secs();
+
+        let registry = &node_manager.registry;
+        let mut details = NodeDetails::new(status, identity, uptime);
+        details.transports = node_manager
+            .transports
+            .iter()
+            .map(|(tid, (tt, tm, addr))| TransportStatus::new(*tt, *tm, addr, tid))
+            .collect();
+        details.secure_channel_listeners = self
+            .list_secure_channel_listener(req, registry)
+            .into_parts()
+            .1
+            .unwrap_or_default();
+        details.secure_channels = self
+            .list_secure_channels(req, registry)
+            .into_parts()
+            .1
+            .unwrap_or_default();
+        details.services = self
+            .list_services(req, registry)
+            .into_parts()
+            .1
+            .map(|l| l.list)
+            .unwrap_or_default();
+        details.inlets = self
+            .get_inlets(req, registry)
+            .into_parts()
+            .1
+            .map(|l| l.list)
+            .unwrap_or_default();
+        details.outlets = self
+            .get_outlets(req, registry)
+            .into_parts()
+            .1
+            .map(|l| l.list)
+            .unwrap_or_default();
+        details.sessions = node_manager
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, s)| {
+                let status = match s.status() {
+                    SessionHealth::Up => "up",
+                    SessionHealth::Down => "down",
+                };
+                SessionStatus::new(s.ping_address().to_string(), status, s.pings().len() as u32)
+            })
+            .collect();
+
+        Ok(Response::ok(req.id()).body(details).to_vec()?)
+    }
+
+    /// The sessions supervised by this node and their dependencies.
+    async fn session_graph(&self, req: &Request<'_>) -> Result<Vec<u8>> {
+        let node_manager = self.node_manager.read().await;
+        let sessions = node_manager.sessions.lock().unwrap();
+        let nodes = sessions
+            .iter()
+            .map(|(k, s)| {
+                let status = match s.status() {
+                    SessionHealth::Up => "up",
+                    SessionHealth::Down => "down",
+                };
+                SessionNode::new(k.to_string(), s.ping_address().to_string(), status)
+            })
+            .collect();
+        let edges = sessions
+            .dependencies()
+            .into_iter()
+            .map(|(from, to)| SessionEdge::new(from.to_string(), to.to_string()))
+            .collect();
+        let graph = SessionGraph::new(nodes, edges);
+        Ok(Response::ok(req.id()).body(graph).to_vec()?)
+    }
+
     //////// Request matching and response handling ////////
 
     async fn handle_request(
@@ -516,6 +818,24 @@ impl NodeManagerWorker {
                     ))
                     .to_vec()?
             }
+            (Get, ["node", "details"]) => self.node_details(ctx, req).await?,
+            (Get, ["node", "sessions", "graph"]) => self.session_graph(req).await?,
+            (Get, ["node", "health"]) => {
+                let node_manager = self.node_manager.read().await;
+                Response::ok(req.id())
+                    .body(NodeHealth::new(
+                        node_manager.started.elapsed().as_secs(),
+                        ctx.list_workers().await?.len() as u32,
+                        node_manager.heartbeat.fetch_add(1, Ordering::Relaxed) + 1,
+                    ))
+                    .to_vec()?
+            }
+            (Get, ["node", "audit"]) => self.query_audit_log(req, dec).await?.to_vec()?,
+            (Get, ["node", "dead_letters"]) => self.list_dead_letters(req).await?,
+            (Get, ["node", "config"]) => self.get_node_config(req).await?.to_vec()?,
+            (Get, ["node", "logging"]) => self.get_log_filter(req).await?,
+            (Put, ["node", "logging"]) => self.set_log_filter(req, dec).await?,
+            (Post, ["node", "config"]) => self.apply_node_config(ctx, req, dec).await?.to_vec()?,
 
             // ==*== Tcp Connection ==*==
             // TODO: Get all tcp connections
@@ -531,6 +851,16 @@ impl NodeManagerWorker {
                 self.delete_transport(req, dec).await?.to_vec()?
             }
 
+            (Get, ["node", "tcp", "connections"]) => {
+                self.list_tcp_transport_connections(req).await.to_vec()?
+            }
+            (Delete, ["node", "tcp", "connections", address]) => {
+                self.close_tcp_transport_connection(req, address).await?
+            }
+            (Get, ["node", "tcp", "listeners"]) => {
+                self.list_tcp_transport_listeners(req).await.to_vec()?
+            }
+
             // ==*== Tcp Listeners ==*==
             (Get, ["node", "tcp", "listener"]) => {
                 let node_manager = self.node_manager.read().await;
@@ -548,15 +878,26 @@ impl NodeManagerWorker {
 
             // ==*== Vault ==*==
             (Post, ["node", "vault"]) => self.create_vault(req, dec).await?.to_vec()?,
+            (Get, ["node", "vault", "keys"]) => self.list_vault_keys(req).await?.to_vec()?,
+            (Get, ["node", "vault", "keys", key_id]) => {
+                self.show_vault_key(req, key_id).await?.to_vec()?
+            }
+            (Post, ["node", "vault", "export"]) => self.export_vault(req, dec).await?.to_vec()?,
+            (Post, ["node", "vault", "import"]) => self.import_vault(req, dec).await?.to_vec()?,
 
             // ==*== Identity ==*==
-            (Post, ["node", "identity"]) => self.create_identity(ctx, req).await?.to_vec()?,
+            (Post, ["node", "identity"]) => self.create_identity(ctx, req, dec).await?.to_vec()?,
             (Post, ["node", "identity", "actions", "show", "short"]) => {
                 self.short_identity(req).await?.to_vec()?
             }
             (Post, ["node", "identity", "actions", "show", "long"]) => {
                 self.long_identity(req).await?.to_vec()?
             }
+            (Get, ["node", "identity", "csr"]) => self.identity_csr(req).await?.to_vec()?,
+            (Get, ["node", "identity", "certificate"]) => self.identity_certificate(req).await?,
+            (Post, ["node", "identity", "certificate"]) => {
+                self.import_identity_certificate(req, dec).await?
+            }
 
             // ==*== Credentials ==*==
             (Post, ["node", "credentials", "actions", "get"]) => {
@@ -565,6 +906,9 @@ impl NodeManagerWorker {
             (Post, ["node", "credentials", "actions", "present"]) => {
                 self.present_credential(req, dec).await?.to_vec()?
             }
+            (Post, ["node", "credentials", "actions", "verify"]) => {
+                self.verify_credential(req, dec).await?
+            }
 
             // ==*== Secure channels ==*==
             // TODO: Change to RequestBuilder format
@@ -592,6 +936,21 @@ impl NodeManagerWorker {
                 .await?
                 .to_vec()?,
 
+            // ==*== Revocations ==*==
+            (Get, ["node", "revocations"]) => self.list_revocations(req).await.to_vec()?,
+            (Post, ["node", "revocations"]) => {
+                self.revoke_identity(ctx, req, dec).await?.to_vec()?
+            }
+            (Post, ["node", "revocations", "sync"]) => {
+                self.sync_revocations(ctx, req, dec).await?.to_vec()?
+            }
+
+            // ==*== Policies ==*==
+            (Post, ["node", "policy"]) => self.set_policy(req, dec).await?,
+            (Get, ["node", "policy"]) => self.get_policy(req, dec).await?,
+            (Get, ["node", "policy", "history"]) => self.get_policy_history(req, dec).await?,
+            (Post, ["node", "policy", "rollback"]) => self.rollback_policy(req, dec).await?,
+
             // ==*== Services ==*==
             (Post, ["node", "services", "vault"]) => {
                 self.start_vault_service(ctx, req, dec).await?.to_vec()?
@@ -603,31 +962,37 @@ impl NodeManagerWorker {
                 .start_authenticated_service(ctx, req, dec)
                 .await?
                 .to_vec()?,
-            (Post, ["node", "services", "uppercase"]) => self
-                .start_uppercase_service(ctx, req, dec)
+            (Post, ["node", "services", "kafka_outlet"]) => self
+                .start_kafka_outlet_service(ctx, req, dec)
                 .await?
                 .to_vec()?,
-            (Post, ["node", "services", "echo"]) => {
-                self.start_echoer_service(ctx, req, dec).await?.to_vec()?
-            }
-            (Post, ["node", "services", "authenticator"]) => self
-                .start_authenticator_service(ctx, req, dec)
+            (Post, ["node", "services", "kafka_inlet"]) => self
+                .start_kafka_inlet_service(ctx, req, dec)
                 .await?
                 .to_vec()?,
-            (Post, ["node", "services", "verifier"]) => {
-                self.start_verifier_service(ctx, req, dec).await?.to_vec()?
+            (Post, ["node", "services", "proxy"]) => {
+                self.start_proxy_service(ctx, req, dec).await?.to_vec()?
             }
-            (Post, ["node", "services", "credentials"]) => self
-                .start_credentials_service(ctx, req, dec)
-                .await?
-                .to_vec()?,
             (Get, ["node", "services"]) => {
                 let node_manager = self.node_manager.read().await;
                 self.list_services(req, &node_manager.registry).to_vec()?
             }
+            (Get, ["node", "services", kind]) => self.list_services_of_kind(req, kind).await?,
+            (Post, ["node", "services", kind]) => {
+                self.start_service(ctx, req, kind, dec).await?.to_vec()?
+            }
+            (Delete, ["node", "services", kind]) => {
+                self.stop_service(ctx, req, kind, dec).await?.to_vec()?
+            }
+
+            // ==*== Routes ==*==
+            (Post, ["node", "route", "resolve"]) => self.resolve_route(req, dec).await?,
 
             // ==*== Forwarder commands ==*==
             (Post, ["node", "forwarder"]) => self.create_forwarder(ctx, req.id(), dec).await?,
+            (Get, ["node", "forwarder", remote_address]) => {
+                self.show_forwarder(ctx, req, remote_address).await?
+            }
 
             // ==*== Inlets & Outlets ==*==
             (Get, ["node", "inlet"]) => {
@@ -638,7 +1003,7 @@ impl NodeManagerWorker {
                 let node_manager = self.node_manager.read().await;
                 self.get_outlets(req, &node_manager.registry).to_vec()?
             }
-            (Post, ["node", "inlet"]) => self.create_inlet(req, dec).await?.to_vec()?,
+            (Post, ["node", "inlet"]) => self.create_inlet(ctx, req, dec).await?.to_vec()?,
             (Post, ["node", "outlet"]) => self.create_outlet(req, dec).await?.to_vec()?,
             (Delete, ["node", "portal"]) => todo!(),
 
@@ -648,6 +1013,14 @@ impl NodeManagerWorker {
             (Get, ["v0", "spaces", id]) => self.get_space(ctx, dec, id).await?,
             (Delete, ["v0", "spaces", id]) => self.delete_space(ctx, dec, id).await?,
 
+            // ==*== Space' members ==*==
+            (Put, ["v0", "space-members", id, email]) => {
+                self.add_space_member(ctx, dec, id, email).await?
+            }
+            (Delete, ["v0", "space-members", id, email]) => {
+                self.remove_space_member(ctx, dec, id, email).await?
+            }
+
             // ==*== Project' enrollers ==*==
             (Post, ["v0", "project-enrollers", project_id]) => {
                 self.add_project_enroller(ctx, dec, project_id).await?
@@ -660,6 +1033,29 @@ impl NodeManagerWorker {
                     .await?
             }
 
+            // ==*== Project' members ==*==
+            (Put, ["v0", "project-members", project_id, email]) => {
+                self.add_project_member(ctx, dec, project_id, email).await?
+            }
+            (Delete, ["v0", "project-members", project_id, email]) => {
+                self.remove_project_member(ctx, dec, project_id, email)
+                    .await?
+            }
+
+            // ==*== Project' addons ==*==
+            (Get, ["v0", "project-addons", project_id]) => {
+                self.list_addons(ctx, dec, project_id).await?
+            }
+            (Put, ["v0", "project-addons", project_id, "confluent"]) => {
+                self.configure_confluent_addon(ctx, dec, project_id).await?
+            }
+            (Put, ["v0", "project-addons", project_id, "influxdb"]) => {
+                self.configure_influxdb_addon(ctx, dec, project_id).await?
+            }
+            (Delete, ["v0", "project-addons", project_id, addon_id]) => {
+                self.disable_addon(ctx, dec, project_id, addon_id).await?
+            }
+
             // ==*== Projects ==*==
             (Post, ["v0", "projects", space_id]) => self.create_project(ctx, dec, space_id).await?,
             (Get, ["v0", "projects"]) => self.list_projects(ctx, dec).await?,
@@ -687,15 +1083,18 @@ impl NodeManagerWorker {
             }
             (Put, ["subscription", id, "unsubscribe"]) => self.unsubscribe(ctx, dec, id).await?,
 
+            // ==*== Files ==*==
+            (Post, ["node", "files"]) => self.send_file(ctx, req, dec).await?.to_vec()?,
+
             // ==*== Messages ==*==
             (Post, ["v0", "message"]) => self.send_message(ctx, req, dec).await?,
+            (Post, ["v0", "perf"]) => self.run_perf(ctx, req, dec).await?,
+            (Post, ["v0", "trace"]) => self.trace_route(ctx, req, dec).await?,
 
             // ==*== Catch-all for Unimplemented APIs ==*==
             _ => {
                 warn!(%method, %path, "Called invalid endpoint");
-                Response::bad_request(req.id())
-                    .body(format!("Invalid endpoint: {}", path))
-                    .to_vec()?
+                api::unknown_path(req).to_vec()?
             }
         };
         Ok(r)
@@ -712,6 +1111,17 @@ impl Worker for NodeManagerWorker {
         if !node_manger.skip_defaults {
             node_manger.initialize_defaults(ctx).await?;
         }
+        drop(node_manger);
+
+        // Forwarders and inlets may take a while to connect, so the setup
+        // is applied without holding up the start of the node.
+        let mut ctx = ctx.new_detached(Address::random_local()).await?;
+        let mut worker = self.clone();
+        tokio::spawn(async move {
+            if let Err(err) = worker.apply_setup(&mut ctx).await {
+                error!(target: TARGET, %err, "failed to apply the node setup")
+            }
+        });
 
         Ok(())
     }
@@ -719,11 +1129,47 @@ impl Worker for NodeManagerWorker {
     async fn shutdown(&mut self, _: &mut Self::Context) -> Result<()> {
         let node_manager = self.node_manager.read().await;
         node_manager.medic.abort();
+        if let Some(sync) = &node_manager.revocation_sync {
+            sync.abort();
+        }
         Ok(())
     }
 
     async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Vec<u8>>) -> Result<()> {
-        let mut dec = Decoder::new(msg.as_body());
+        let return_route = msg.return_route();
+        let local_msg = msg.local_message().clone();
+        let mut data = msg.body();
+        // Requests too large for the transport arrive in fragments
+        if api::is_fragment(&data) {
+            match self.fragments.push(&return_route, &data) {
+                Some(req) => data = req,
+                None => return Ok(()),
+            }
+        }
+        let mut ctx = ctx.new_detached(Address::random_local()).await?;
+        let mut worker = self.clone();
+        tokio::spawn(async move {
+            if let Err(err) = worker
+                .respond(&mut ctx, return_route, &local_msg, &data)
+                .await
+            {
+                error!(target: TARGET, %err, "failed to respond to request")
+            }
+        });
+        Ok(())
+    }
+}
+
+impl NodeManagerWorker {
+    /// Handle a request and send the response back to `return_route`.
+    async fn respond(
+        &mut self,
+        ctx: &mut Context,
+        return_route: Route,
+        local_msg: &LocalMessage,
+        data: &[u8],
+    ) -> Result<()> {
+        let mut dec = Decoder::new(data);
         let req: Request = match dec.decode() {
             Ok(r) => r,
             Err(e) => {
@@ -732,8 +1178,82 @@ impl Worker for NodeManagerWorker {
             }
         };
 
-        let r = match self.handle_request(ctx, &req, &mut dec).await {
+        let span = info_span! {
+            target: TARGET,
+            "node_api_request",
+            method = ?req.method(),
+            path   = %req.path(),
+        };
+        telemetry::set_parent_context(&span, &req);
+
+        if !self.authorize(local_msg, &req, dec.clone()).await? {
+            warn! {
+                target: TARGET,
+                re     = %req.id(),
+                method = ?req.method(),
+                path   = %req.path(),
+                "request denied"
+            }
+            let r = api::forbidden(&req, "not authorized to use this endpoint").to_vec()?;
+            return ctx.send(return_route, r).await;
+        }
+
+        let mut reservation = None;
+        if let Some(key) = IdempotentResponses::key(&req) {
+            loop {
+                let next = {
+                    let mut node_manager = self.node_manager.write().await;
+                    node_manager.idempotent_responses.begin(&key)
+                };
+                let replay = match next {
+                    Idempotency::Replay(r) => r,
+                    Idempotency::Wait(mut rx) => {
+                        let r = rx.wait_for(Option::is_some).await.map(|r| r.clone());
+                        match r {
+                            Ok(r) => r.unwrap_or_default(),
+                            // The original request was dropped, take it over
+                            Err(_) => continue,
+                        }
+                    }
+                    Idempotency::Handle(tx) => {
+                        reservation = Some((key, tx));
+                        break;
+                    }
+                };
+                debug! {
+                    target: TARGET,
+                    re     = %req.id(),
+                    path   = %req.path(),
+                    "replaying response to retried request"
+                }
+                let r = api::readdress(&replay, req.id())?;
+                return self.send_response(ctx, return_route, &req, r).await;
+            }
+        }
+
+        let handled = match (req.method(), req.path_segments::<5>().as_slice()) {
+            (Some(Method::Post), ["node", "cancel", id]) => {
+                self.cancel_request(&req, &return_route, id)
+            }
+            _ => {
+                let flag = self.pending.insert(req.id(), &return_route);
+                let handling = self.handle_request(ctx, &req, &mut dec).instrument(span);
+                let r = cancellation::scope(flag, handling).await;
+                self.pending.remove(req.id());
+                r
+            }
+        };
+        let r = match handled {
             Ok(r) => r,
+            Err(err) if cancellation::is_cancelled(&err) => {
+                debug! {
+                    target: TARGET,
+                    re     = %req.id(),
+                    path   = %req.path(),
+                    "request cancelled"
+                }
+                cancellation::cancelled(&req)?
+            }
             Err(err) => {
                 error! {
                     target: TARGET,
@@ -744,13 +1264,18 @@ impl Worker for NodeManagerWorker {
                     cause  = ?err.source(),
                     "failed to handle request"
                 }
-                let err =
-                    Error::new(req.path()).with_message(format!("failed to handle request: {err}"));
+                let err = Error::new(req.path())
+                    .with_message(format!("failed to handle request: {err}"))
+                    .with_code(ErrorCode::Internal);
                 Response::builder(req.id(), Status::InternalServerError)
                     .body(err)
                     .to_vec()?
             }
         };
+        if let Some((key, tx)) = reservation {
+            let mut node_manager = self.node_manager.write().await;
+            node_manager.idempotent_responses.finish(key, tx, &r);
+        }
         debug! {
             target: TARGET,
             re     = %req.id(),
@@ -758,32 +1283,109 @@ impl Worker for NodeManagerWorker {
             path   = %req.path(),
             "responding"
         }
-        ctx.send(msg.return_route(), r).await
+        self.send_response(ctx, return_route, &req, r).await
+    }
+
+    /// Send a response, split into fragments if it is larger than what both
+    /// the caller and the TCP transport of this node accept.
+    async fn send_response(
+        &self,
+        ctx: &Context,
+        return_route: Route,
+        req: &Request<'_>,
+        r: Vec<u8>,
+    ) -> Result<()> {
+        let limit = match req.max_message_size() {
+            Some(n) => {
+                let node_manager = self.node_manager.read().await;
+                (n as usize).min(node_manager.tcp_transport.max_message_size())
+            }
+            None => return ctx.send(return_route, r).await,
+        };
+        let messages = match api::fragment(req.id(), &r, limit) {
+            Ok(m) => m,
+            Err(err) => {
+                warn!(target: TARGET, re = %req.id(), %err, size = r.len(), "response is too large");
+                let r = api::internal_error(req, "response is too large").to_vec()?;
+                return ctx.send(return_route, r).await;
+            }
+        };
+        if messages.len() > 1 {
+            debug! {
+                target: TARGET,
+                re        = %req.id(),
+                size      = r.len(),
+                limit     = limit,
+                fragments = messages.len(),
+                "splitting response"
+            }
+        }
+        for m in messages {
+            ctx.send(return_route.clone(), m).await?
+        }
+        Ok(())
     }
 }
 
 #[cfg(test)]
 pub(crate) mod tests {
+    use crate::cloud::BareCloudRequestWrapper;
     use crate::nodes::NodeManager;
+    use crate::route_to_multiaddr;
     use ockam::{route, Route};
+    use ockam_core::Any;
 
     use super::*;
 
     impl NodeManager {
         pub(crate) async fn test_create(ctx: &Context) -> Result<Route> {
             let node_dir = tempfile::tempdir().unwrap();
+            Self::test_create_in(ctx, node_dir.into_path()).await
+        }
+
+        /// Create a node manager with its configuration in `node_dir`.
+        pub(crate) async fn test_create_in(ctx: &Context, node_dir: PathBuf) -> Result<Route> {
+            let options =
+                NodeManagerGeneralOptions::new("node".to_string(), node_dir, true, false, None);
+            Self::test_create_with(ctx, options).await
+        }
+
+        /// Create a node manager with the given options.
+        pub(crate) async fn test_create_with(
+            ctx: &Context,
+            options: NodeManagerGeneralOptions,
+        ) -> Result<Route> {
             let node_manager = "manager";
+            let node_man = Self::test_manager_with(ctx, options).await?;
+            let node_manager_worker = NodeManagerWorker::new(node_man);
+
+            // Initialize node_man worker and return its route
+            ctx.start_worker(node_manager, node_manager_worker).await?;
+            Ok(route![node_manager])
+        }
+
+        /// Create a node manager without serving its API.
+        pub(crate) async fn test_manager(ctx: &Context) -> Result<NodeManager> {
+            let node_dir = tempfile::tempdir().unwrap();
+            let options = NodeManagerGeneralOptions::new(
+                "node".to_string(),
+                node_dir.into_path(),
+                true,
+                false,
+                None,
+            );
+            Self::test_manager_with(ctx, options).await
+        }
+
+        async fn test_manager_with(
+            ctx: &Context,
+            options: NodeManagerGeneralOptions,
+        ) -> Result<NodeManager> {
             let transport = TcpTransport::create(ctx).await?;
             let node_address = transport.listen("127.0.0.1:0").await?;
             let mut node_man = NodeManager::create(
                 ctx,
-                NodeManagerGeneralOptions::new(
-                    "node".to_string(),
-                    node_dir.into_path(),
-                    true,
-                    false,
-                    None,
-                ),
+                options,
                 NodeManagerProjectsOptions::new(None, None, Default::default()),
                 NodeManagerTransportOptions::new(
                     (
@@ -798,13 +1400,90 @@ pub(crate) mod tests {
 
             // Initialize identity
             node_man.create_vault_impl(None, false).await?;
-            node_man.create_identity_impl(ctx, false).await?;
+            node_man
+                .create_identity_impl(ctx, false, IdentityKeyBackend::Software)
+                .await?;
+            Ok(node_man)
+        }
+    }
 
-            let node_manager_worker = NodeManagerWorker::new(node_man);
+    /// A controller which never answers.
+    struct Unresponsive;
 
-            // Initialize node_man worker and return its route
-            ctx.start_worker(node_manager, node_manager_worker).await?;
-            Ok(route![node_manager])
+    #[ockam::worker]
+    impl Worker for Unresponsive {
+        type Message = Any;
+        type Context = Context;
+
+        async fn handle_message(&mut self, _: &mut Context, _: Routed<Any>) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[ockam_macros::test]
+    async fn requests_are_handled_concurrently(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+        ctx.start_worker("controller", Unresponsive).await?;
+
+        // A request waiting on the controller...
+        let controller = route_to_multiaddr(&route!["controller"]).unwrap();
+        let mut buf = Vec::new();
+        Request::get("/v0/projects")
+            .body(BareCloudRequestWrapper::bare(&controller))
+            .encode(&mut buf)?;
+        let slow = ctx.new_detached(Address::random_local()).await?;
+        slow.send(node_manager.clone(), buf).await?;
+
+        // ...does not hold up unrelated ones.
+        let mut buf = Vec::new();
+        Request::get("/node/secure_channel").encode(&mut buf)?;
+        let res: Vec<u8> = ctx
+            .send_and_receive_with_timeout(node_manager, buf, 5)
+            .await?;
+        let mut dec = Decoder::new(&res);
+        assert_eq!(dec.decode::<Response>()?.status(), Some(Status::Ok));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn node_details_are_served_apart_from_the_status(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+
+        for (path, details) in [("/node/details", true), ("/node", false)] {
+            let mut buf = Vec::new();
+            Request::get(path).encode(&mut buf)?;
+            let res: Vec<u8> = ctx
+                .send_and_receive_with_timeout(node_manager.clone(), buf, 5)
+                .await?;
+            let mut dec = Decoder::new(&res);
+            assert_eq!(dec.decode::<Response>()?.status(), Some(Status::Ok));
+            let name = if details {
+                dec.decode::<NodeDetails>()?.status.node_name.to_string()
+            } else {
+                dec.decode::<NodeStatus>()?.node_name.to_string()
+            };
+            assert_eq!(name, "node");
         }
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn fragmented_requests_are_reassembled(ctx: &mut Context) -> Result<()> {
+        let node_manager = NodeManager::test_create(ctx).await?;
+
+        let req = Request::get("/node").body("x".repeat(300)).to_vec()?;
+        let fragments = api::fragment(api::Id::fresh(), &req, 100)?;
+        assert!(fragments.len() > 1);
+        let mut requester = ctx.new_detached(Address::random_local()).await?;
+        for f in fragments {
+            requester.send(node_manager.clone(), f).await?;
+        }
+        let res = requester.receive_timeout::<Vec<u8>>(5).await?.take().body();
+        let mut dec = Decoder::new(&res);
+        assert_eq!(dec.decode::<Response>()?.status(), Some(Status::Ok));
+
+        ctx.stop().await
     }
 }
@@ -0,0 +1,22 @@
+use super::NodeManagerWorker;
+use crate::nodes::models::audit::{AuditQuery, AuditRecordList};
+use minicbor::Decoder;
+use ockam::Result;
+use ockam_core::api::{Request, Response, ResponseBuilder};
+
+impl NodeManagerWorker {
+    pub(super) async fn query_audit_log(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<AuditRecordList<'static>>> {
+        let query: AuditQuery = if req.has_body() {
+            dec.decode()?
+        } else {
+            AuditQuery::default()
+        };
+        let audit = self.node_manager.read().await.audit.clone();
+        let records = audit.query(&query).await?;
+        Ok(Response::ok(req.id()).body(AuditRecordList::new(records)))
+    }
+}
@@ -0,0 +1,347 @@
+use minicbor::Decoder;
+use ockam::abac::{Action, PolicyAccessControl, PolicyLookup, Resource};
+use ockam::Result;
+use ockam_core::api::{Method, Request};
+use ockam_core::{AccessControl, LocalInfo, LocalMessage};
+use ockam_identity::IdentitySecureChannelLocalInfo;
+
+use super::NodeManagerWorker;
+use crate::audit::AuditAccessControl;
+use crate::nodes::models::config::ApplySetup;
+use crate::nodes::models::portal::{CreateInlet, CreateOutlet};
+
+/// Resource of the policies guarding the whole node API.
+pub(crate) const API_RESOURCE: &str = "/api";
+
+/// Resource of the endpoints managing the policies of the node.
+const POLICY_RESOURCE: &str = "/api/node/policy";
+
+/// [`LocalInfo`] type identifier of [`LocalApiInfo`].
+pub const LOCAL_API_IDENTIFIER: &str = "LOCAL_API_IDENTIFIER";
+
+/// Marks a request to the node API as coming from the user owning the node,
+/// e.g. over its API socket or from a command running the node in-process.
+///
+/// Local info never crosses a transport or a secure channel, so only the
+/// workers of the node itself can attach it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalApiInfo;
+
+impl LocalApiInfo {
+    /// Convert to LocalInfo
+    pub fn to_local_info(&self) -> LocalInfo {
+        LocalInfo::new(LOCAL_API_IDENTIFIER.into(), Vec::new())
+    }
+
+    /// Find the marker in a LocalMessage
+    pub fn find_info(local_msg: &LocalMessage) -> Option<Self> {
+        local_msg
+            .local_info()
+            .iter()
+            .any(|x| x.type_identifier() == LOCAL_API_IDENTIFIER)
+            .then_some(LocalApiInfo)
+    }
+}
+
+/// The resource guarding the endpoint of a request path, made of its first
+/// two segments, e.g. `/api/node/services` for `/node/services/echo`.
+fn endpoint_resource(path: &str) -> Resource {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).take(2).collect();
+    Resource::from(format!("{API_RESOURCE}/{}", segments.join("/")).as_str())
+}
+
+fn method_action(method: Method) -> Action {
+    Action::from(method.to_string().to_lowercase().as_str())
+}
+
+impl NodeManagerWorker {
+    /// Whether the sender of a request may use its endpoint.
+    ///
+    /// Endpoints are guarded by the policy applying to their resource (see
+    /// [`endpoint_resource`]) and the request method as action, including
+    /// the policies of wildcard patterns like `/api/v0/*`, or else by the
+    /// policy set for [`API_RESOURCE`]. Requests to a guarded endpoint
+    /// have to arrive over a secure channel, from an identity satisfying
+    /// the policy.
+    ///
+    /// Requests marked with [`LocalApiInfo`] and not received over a secure
+    /// channel are always allowed. Other unmarked requests which were not
+    /// received over a secure channel are refused if the node doesn't serve
+    /// its API over TCP. Other endpoints without any policy stay open, so
+    /// that a fresh node can be set up before policies are set, except that
+    /// requests over a secure channel can't write policies (see
+    /// [`writes_policies`]) until a policy of [`POLICY_RESOURCE`] lets them.
+    pub(super) async fn authorize(
+        &self,
+        local_msg: &LocalMessage,
+        req: &Request<'_>,
+        body: Decoder<'_>,
+    ) -> Result<bool> {
+        let remote = IdentitySecureChannelLocalInfo::find_info(local_msg).is_ok();
+        if !remote && LocalApiInfo::find_info(local_msg).is_some() {
+            return Ok(true);
+        }
+        let action = match req.method() {
+            Some(m) => method_action(m),
+            None => return Ok(false),
+        };
+        if !remote && !self.node_manager.read().await.tcp_api {
+            return Ok(false);
+        }
+        let resource = endpoint_resource(req.path());
+        let policy_endpoint = resource == Resource::from(POLICY_RESOURCE);
+        if !self
+            .is_authorized(local_msg, resource, &action, !(remote && policy_endpoint))
+            .await?
+        {
+            return Ok(false);
+        }
+        if policy_endpoint || !self.writes_policies(req, body).await {
+            return Ok(true);
+        }
+        let resource = Resource::from(POLICY_RESOURCE);
+        self.is_authorized(local_msg, resource, &Action::from("post"), !remote)
+            .await
+    }
+
+    /// Whether a request sets policies besides its own endpoint, i.e. applies
+    /// a setup adding or removing policies, or creates a portal guarded by
+    /// a policy.
+    ///
+    /// Such requests are also guarded by the policies of [`POLICY_RESOURCE`].
+    /// Bodies which can't be decoded are left to the handler to refuse.
+    async fn writes_policies(&self, req: &Request<'_>, mut body: Decoder<'_>) -> bool {
+        if req.method() != Some(Method::Post) {
+            return false;
+        }
+        let path_segments = req.path_segments::<3>();
+        match path_segments.as_slice() {
+            ["node", "config"] => match body.decode::<ApplySetup>() {
+                Ok(b) if !b.is_dry_run() => {
+                    let node_manager = self.node_manager.read().await;
+                    let applied = node_manager.config.setup().read().writes_policies();
+                    applied || b.setup().writes_policies()
+                }
+                _ => false,
+            },
+            ["node", "inlet"] => body
+                .decode::<CreateInlet>()
+                .map(|b| b.policy().is_some())
+                .unwrap_or(false),
+            ["node", "outlet"] => body
+                .decode::<CreateOutlet>()
+                .map(|b| b.policy.is_some())
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// Evaluate the policy applying to a resource and action, falling back
+    /// to the policy of [`API_RESOURCE`], or else to `default`.
+    async fn is_authorized(
+        &self,
+        local_msg: &LocalMessage,
+        resource: Resource,
+        action: &Action,
+        default: bool,
+    ) -> Result<bool> {
+        let node_manager = self.node_manager.read().await;
+        let lookup = PolicyLookup::new(node_manager.policies.clone());
+        for resource in [resource, Resource::from(API_RESOURCE)] {
+            let action = action.clone();
+            if lookup.find(&resource, &action).await?.is_none() {
+                continue;
+            }
+            let detail = format!("{resource} {action}");
+            let policy = PolicyAccessControl::new(
+                resource,
+                action,
+                node_manager.policies.clone(),
+                node_manager.authenticated_storage.clone(),
+            )
+            .with_schema(node_manager.attribute_schema.clone());
+            let policy = AuditAccessControl::new(policy, node_manager.audit.clone(), detail);
+            return policy.is_authorized(local_msg).await;
+        }
+        Ok(default)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use minicbor::{Decoder, Encode};
+    use ockam::identity::authenticated_storage::mem::InMemoryStorage;
+    use ockam::identity::{Identity, TrustEveryonePolicy};
+    use ockam::vault::Vault;
+    use ockam::{route, Context};
+    use ockam_core::api::{RequestBuilder, Response, Status};
+    use ockam_core::{Address, Encodable, Route, TransportMessage};
+
+    use super::*;
+    use crate::nodes::models::policy::{PolicyTarget, SetPolicy};
+    use crate::nodes::models::portal::PortalPolicy;
+    use crate::nodes::service::NodeManagerGeneralOptions;
+    use crate::nodes::NodeManager;
+
+    /// Send a request to a node as its owner, and return the reply.
+    pub(crate) async fn call_local<T: Encode<()>>(
+        ctx: &Context,
+        node: &Route,
+        req: RequestBuilder<'_, T>,
+    ) -> Result<Vec<u8>> {
+        let mut child = ctx.new_detached(Address::random_local()).await?;
+        let payload = req.to_vec()?.encode()?;
+        let msg = TransportMessage::v1(node.clone(), route![child.address()], payload);
+        let local_info = vec![LocalApiInfo.to_local_info()];
+        child.forward(LocalMessage::new(msg, local_info)).await?;
+        Ok(child.receive::<Vec<u8>>().await?.take().body())
+    }
+
+    async fn call<T: Encode<()>>(
+        ctx: &Context,
+        node: &Route,
+        req: RequestBuilder<'_, T>,
+    ) -> Result<Option<Status>> {
+        let res: Vec<u8> = ctx.send_and_receive(node.clone(), req.to_vec()?).await?;
+        Ok(Decoder::new(&res).decode::<Response>()?.status())
+    }
+
+    #[ockam_macros::test]
+    async fn remote_identities_are_subject_to_policies(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+        let storage = InMemoryStorage::new();
+        let listener = Identity::create(ctx, &Vault::create()).await?;
+        listener
+            .create_secure_channel_listener("api", TrustEveryonePolicy, &storage)
+            .await?;
+        let remote = Identity::create(ctx, &Vault::create()).await?;
+        let channel = remote
+            .create_secure_channel("api", TrustEveryonePolicy, &storage)
+            .await?;
+        let over_channel = route![channel, node.recipient()];
+
+        // Without policies, endpoints stay open but policies can't be managed
+        assert_eq!(
+            call(ctx, &over_channel, Request::get("/node")).await?,
+            Some(Status::Ok)
+        );
+        let set = SetPolicy::new(PolicyTarget::new("/api/node"), r#""True""#);
+        let req = Request::post("/node/policy").body(set);
+        assert_eq!(
+            call(ctx, &over_channel, req).await?,
+            Some(Status::Forbidden)
+        );
+
+        let set =
+            SetPolicy::new(PolicyTarget::new("/api/node"), r#""False""#).with_actions(["get"]);
+        let res = call_local(ctx, &node, Request::post("/node/policy").body(set)).await?;
+        let status = Decoder::new(&res).decode::<Response>()?.status();
+        assert_eq!(status, Some(Status::Ok));
+
+        assert_eq!(
+            call(ctx, &over_channel, Request::get("/node")).await?,
+            Some(Status::Forbidden)
+        );
+        // Unmarked local requests are subject to policies as well
+        assert_eq!(
+            call(ctx, &node, Request::get("/node")).await?,
+            Some(Status::Forbidden)
+        );
+        let res = call_local(ctx, &node, Request::get("/node")).await?;
+        let status = Decoder::new(&res).decode::<Response>()?.status();
+        assert_eq!(status, Some(Status::Ok));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn policy_writes_over_channels_are_guarded(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+        let storage = InMemoryStorage::new();
+        let listener = Identity::create(ctx, &Vault::create()).await?;
+        listener
+            .create_secure_channel_listener("api", TrustEveryonePolicy, &storage)
+            .await?;
+        let remote = Identity::create(ctx, &Vault::create()).await?;
+        let channel = remote
+            .create_secure_channel("api", TrustEveryonePolicy, &storage)
+            .await?;
+        let over_channel = route![channel, node.recipient()];
+
+        // Portals setting a policy write policies as well
+        let mut outlet = CreateOutlet::new("127.0.0.1:5000", "db", None, false);
+        outlet.set_policy(PortalPolicy::new(r#""True""#));
+        let req = Request::post("/node/outlet").body(outlet.clone());
+        assert_eq!(
+            call(ctx, &over_channel, req).await?,
+            Some(Status::Forbidden)
+        );
+
+        // Commands on the same host manage policies by default
+        let set = SetPolicy::new(PolicyTarget::new("/api/node/policy"), r#""True""#)
+            .with_actions(["post"]);
+        let req = Request::post("/node/policy").body(set);
+        assert_eq!(call(ctx, &node, req).await?, Some(Status::Ok));
+
+        let req = Request::post("/node/outlet").body(outlet);
+        assert_ne!(
+            call(ctx, &over_channel, req).await?,
+            Some(Status::Forbidden)
+        );
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn nodes_without_tcp_api_only_answer_marked_requests(ctx: &mut Context) -> Result<()> {
+        let node_dir = tempfile::tempdir().unwrap().into_path();
+        let options =
+            NodeManagerGeneralOptions::new("node".to_string(), node_dir, true, false, None)
+                .without_tcp_api();
+        let node = NodeManager::test_create_with(ctx, options).await?;
+
+        assert_eq!(
+            call(ctx, &node, Request::get("/node")).await?,
+            Some(Status::Forbidden)
+        );
+        let res = call_local(ctx, &node, Request::get("/node")).await?;
+        let status = Decoder::new(&res).decode::<Response>()?.status();
+        assert_eq!(status, Some(Status::Ok));
+
+        // Remote identities are still subject to policies only
+        let storage = InMemoryStorage::new();
+        let listener = Identity::create(ctx, &Vault::create()).await?;
+        listener
+            .create_secure_channel_listener("api", TrustEveryonePolicy, &storage)
+            .await?;
+        let remote = Identity::create(ctx, &Vault::create()).await?;
+        let channel = remote
+            .create_secure_channel("api", TrustEveryonePolicy, &storage)
+            .await?;
+        assert_eq!(
+            call(
+                ctx,
+                &route![channel, node.recipient()],
+                Request::get("/node")
+            )
+            .await?,
+            Some(Status::Ok)
+        );
+
+        ctx.stop().await
+    }
+
+    #[test]
+    fn endpoints_are_guarded_by_their_first_segments() {
+        assert_eq!(
+            endpoint_resource("/node/services/echo"),
+            Resource::from("/api/node/services")
+        );
+        assert_eq!(endpoint_resource("node"), Resource::from("/api/node"));
+        assert_eq!(
+            endpoint_resource("/v0/projects/1234"),
+            Resource::from("/api/v0/projects")
+        );
+        assert_eq!(method_action(Method::Post), Action::from("post"));
+    }
+}
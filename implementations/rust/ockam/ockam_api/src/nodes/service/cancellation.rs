@@ -0,0 +1,166 @@
+use ockam::Result;
+use ockam_core::api::{self, ErrorCode, Request, Response, Status};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Address, Route};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::NodeManagerWorker;
+
+ockam_node::tokio::task_local! {
+    /// Whether the request handled by the current task has been cancelled.
+    static CANCELLED: Arc<AtomicBool>;
+}
+
+/// Requests being handled by the node manager, by request id.
+///
+/// Cancelling a request only flags it: the operation stops at its next
+/// [checkpoint], between steps which would otherwise leave half-created
+/// resources behind, e.g. before connecting a secure channel or before
+/// retrying a controller request.
+#[derive(Clone, Default)]
+pub(super) struct PendingRequests {
+    requests: Arc<Mutex<BTreeMap<api::Id, (Vec<Address>, Arc<AtomicBool>)>>>,
+}
+
+impl PendingRequests {
+    /// Track a request sent along `return_route`, until it is
+    /// [removed](Self::remove).
+    pub(super) fn insert(&self, id: api::Id, return_route: &Route) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        let requester = requester(return_route);
+        self.requests
+            .lock()
+            .unwrap()
+            .insert(id, (requester, flag.clone()));
+        flag
+    }
+
+    pub(super) fn remove(&self, id: api::Id) {
+        self.requests.lock().unwrap().remove(&id);
+    }
+
+    /// Cancel a request, returning whether it was still pending and sent
+    /// by the same requester.
+    fn cancel(&self, id: api::Id, return_route: &Route) -> bool {
+        let mut requests = self.requests.lock().unwrap();
+        match requests.get(&id) {
+            Some((r, _)) if *r == requester(return_route) => {}
+            _ => return false,
+        }
+        match requests.remove(&id) {
+            Some((_, flag)) => !flag.swap(true, Ordering::SeqCst),
+            None => false,
+        }
+    }
+}
+
+/// The route to the requester of a message, without the address that
+/// receives this particular response, which is fresh for every request.
+fn requester(return_route: &Route) -> Vec<Address> {
+    let mut hops: Vec<Address> = return_route.iter().cloned().collect();
+    hops.pop();
+    hops
+}
+
+/// Run the handling of a request, which stops at its next [checkpoint]
+/// once `flag` is set.
+pub(super) async fn scope<F: Future>(flag: Arc<AtomicBool>, f: F) -> F::Output {
+    CANCELLED.scope(flag, f).await
+}
+
+/// Fail with a [`Kind::Cancelled`] error if the request handled by the
+/// current task has been cancelled.
+///
+/// Call this only where stopping leaves nothing behind.
+pub(crate) fn checkpoint() -> Result<()> {
+    let cancelled = CANCELLED
+        .try_with(|c| c.load(Ordering::SeqCst))
+        .unwrap_or(false);
+    if cancelled {
+        return Err(ockam_core::Error::new(
+            Origin::Node,
+            Kind::Cancelled,
+            "request cancelled",
+        ));
+    }
+    Ok(())
+}
+
+/// Whether an error comes from a cancelled request.
+pub(super) fn is_cancelled(err: &ockam_core::Error) -> bool {
+    err.code().kind == Kind::Cancelled
+}
+
+/// The response to a cancelled request.
+pub(super) fn cancelled(req: &Request<'_>) -> Result<Vec<u8>> {
+    let e = api::Error::new(req.path())
+        .with_message("request cancelled")
+        .with_code(ErrorCode::Cancelled);
+    Ok(Response::builder(req.id(), Status::Conflict)
+        .body(e)
+        .to_vec()?)
+}
+
+impl NodeManagerWorker {
+    /// Cancel a request of the same requester as `req`.
+    pub(super) fn cancel_request(
+        &self,
+        req: &Request<'_>,
+        return_route: &Route,
+        id: &str,
+    ) -> Result<Vec<u8>> {
+        let id = match u32::from_str_radix(id, 16) {
+            Ok(id) => api::Id::from(id),
+            Err(_) => return Ok(api::bad_request(req, "invalid request id").to_vec()?),
+        };
+        if self.pending.cancel(id, return_route) {
+            Ok(Response::ok(req.id()).to_vec()?)
+        } else {
+            Ok(Response::not_found(req.id()).to_vec()?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::route;
+
+    #[test]
+    fn cancels_pending_requests_once() {
+        let pending = PendingRequests::default();
+        let id = api::Id::fresh();
+        let flag = pending.insert(id, &route!["tcp", "app.1"]);
+        assert!(!flag.load(Ordering::SeqCst));
+        assert!(pending.cancel(id, &route!["tcp", "app.2"]));
+        assert!(flag.load(Ordering::SeqCst));
+        assert!(!pending.cancel(id, &route!["tcp", "app.2"]));
+    }
+
+    #[test]
+    fn only_the_requester_cancels_a_request() {
+        let pending = PendingRequests::default();
+        let id = api::Id::fresh();
+        let flag = pending.insert(id, &route!["tcp.1", "app.1"]);
+        assert!(!pending.cancel(id, &route!["tcp.2", "app.2"]));
+        assert!(!flag.load(Ordering::SeqCst));
+        assert!(pending.cancel(id, &route!["tcp.1", "app.2"]));
+    }
+
+    #[ockam_macros::test]
+    async fn checkpoints_fail_once_cancelled(ctx: &mut ockam::Context) -> Result<()> {
+        let flag = Arc::new(AtomicBool::new(false));
+        let r = scope(flag.clone(), async {
+            checkpoint()?;
+            flag.store(true, Ordering::SeqCst);
+            checkpoint()
+        })
+        .await;
+        assert!(r.map_err(|e| is_cancelled(&e)).unwrap_err());
+        assert!(checkpoint().is_ok());
+        ctx.stop().await
+    }
+}
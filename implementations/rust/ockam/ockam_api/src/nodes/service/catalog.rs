@@ -0,0 +1,776 @@
+//! The catalog of services a node can start through its API.
+//!
+//! Every [`ServiceKind`] registered in the [`ServiceCatalog`] of a node
+//! manager is started with `POST /node/services/{kind}`, stopped with
+//! `DELETE /node/services/{kind}` and listed with `GET /node/services/{kind}`.
+//! Adding a service only requires implementing [`ServiceKind`] and
+//! registering it in [`ServiceCatalog::default`].
+//!
+//! Start requests may carry [`ServiceOptions`], to guard a service with an
+//! ABAC policy or to restart it when its worker stops, for instance after
+//! a panic. How many times a service was restarted is part of its listing.
+
+use super::{NodeManager, NodeManagerWorker};
+use crate::ack::Acker;
+use crate::authenticator::signer::Server as SignerServer;
+use crate::dead_letters::{DeadLetterService, DeadLetters, DEAD_LETTER_ADDRESS, DEFAULT_CAPACITY};
+use crate::echoer::Echoer;
+use crate::error::ApiError;
+use crate::file_transfer::DEFAULT_MAX_FILE_SIZE;
+use crate::nodes::models::services::{
+    ServiceList, ServiceOptions, ServiceStatus, StartCredentialsService, StartDeadLetterService,
+    StartEchoerServiceRequest, StartFileTransferService, StartPubSubService, StartSignerService,
+    StopServiceRequest,
+};
+use crate::nodes::registry::ServiceInfo;
+use crate::perf::PerfResponder;
+use crate::uppercase::Uppercase;
+use minicbor::{Decode, Decoder, Encode};
+use ockam::abac::{Action, Resource};
+use ockam::compat::asynchronous::RwLock;
+use ockam::{
+    Address, AsyncTryClone, Context, ForwardingService, Mailboxes, Message, Result, Worker,
+    WorkerBuilder,
+};
+use ockam_core::api::{Request, Response, ResponseBuilder};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, AccessControl, AllowAll, CowStr};
+use ockam_node::tokio;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tracing::warn;
+
+/// A kind of service nodes can start.
+#[async_trait]
+pub(crate) trait ServiceKind: Send + Sync + 'static {
+    /// Start a service of this kind at `addr`.
+    ///
+    /// Its worker must be guarded by the access control, if any. The
+    /// decoder is positioned at the body of the start request, from which
+    /// the service options are read.
+    async fn start(
+        &self,
+        ctx: &Context,
+        node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<()>;
+
+    /// Stop the service of this kind running at `addr`.
+    async fn stop(&self, ctx: &Context, addr: &Address) -> Result<()> {
+        ctx.stop_worker(addr.clone()).await
+    }
+}
+
+/// The service kinds a node can start, by name.
+pub(crate) struct ServiceCatalog {
+    kinds: BTreeMap<&'static str, Arc<dyn ServiceKind>>,
+}
+
+impl ServiceCatalog {
+    pub(crate) fn empty() -> Self {
+        ServiceCatalog {
+            kinds: BTreeMap::new(),
+        }
+    }
+
+    /// Register a service kind under the given name.
+    pub(crate) fn register(&mut self, name: &'static str, kind: impl ServiceKind) {
+        self.kinds.insert(name, Arc::new(kind));
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<(&'static str, Arc<dyn ServiceKind>)> {
+        self.kinds.get_key_value(name).map(|(n, k)| (*n, k.clone()))
+    }
+}
+
+impl Default for ServiceCatalog {
+    fn default() -> Self {
+        let mut c = ServiceCatalog::empty();
+        c.register("echo", EchoerKind);
+        c.register("perf", PerfKind);
+        c.register("ack", AckerKind);
+        c.register("dead_letters", DeadLettersKind);
+        c.register("uppercase", UppercaseKind);
+        c.register("verifier", VerifierKind);
+        c.register("credentials", CredentialsKind);
+        c.register("revocation", RevocationKind);
+        c.register("signer", SignerKind);
+        c.register("file_transfer", FileTransferKind);
+        c.register("pubsub", PubSubKind);
+        c.register("forwarding", ForwardingKind);
+        #[cfg(feature = "direct-authenticator")]
+        c.register("authenticator", AuthenticatorKind);
+        c
+    }
+}
+
+/// What every start request of a catalog service carries.
+#[derive(Decode)]
+#[cbor(map)]
+struct ServiceRequest<'a> {
+    #[b(1)]
+    addr: CowStr<'a>,
+    #[b(10)]
+    options: Option<ServiceOptions<'a>>,
+}
+
+/// The ABAC action of the messages sent to a service.
+const SERVICE_ACTION: &str = "handle_message";
+
+/// How often the workers of restartable services are checked.
+const SUPERVISION_INTERVAL: Duration = Duration::from_secs(5);
+
+impl NodeManager {
+    /// Start a service of a catalog kind and record it in the registry.
+    ///
+    /// Returns whether the service is restarted when its worker stops.
+    pub(super) async fn start_service_impl(
+        &mut self,
+        ctx: &Context,
+        kind: &str,
+        dec: &mut Decoder<'_>,
+    ) -> Result<bool> {
+        let (name, service) = self
+            .catalog
+            .get(kind)
+            .ok_or_else(|| ApiError::message(format!("Unknown service kind {kind}")))?;
+
+        let body = &dec.input()[dec.position()..];
+        let req: ServiceRequest = dec.clone().decode()?;
+        let addr: Address = req.addr.to_string().into();
+
+        if self.registry.services.contains_key(&addr) {
+            return Err(ApiError::message(format!(
+                "A service exists at address {addr}"
+            )));
+        }
+
+        let options = req.options.unwrap_or_default();
+        let access_control = match options.resource() {
+            Some(r) => {
+                let policy = (Resource::from(r), Action::from(SERVICE_ACTION));
+                Some(self.access_control(None, Some(policy))?)
+            }
+            None => None,
+        };
+
+        service.start(ctx, self, &addr, access_control, dec).await?;
+
+        let mut info = ServiceInfo::new(name);
+        if options.restart() {
+            info = info.with_restart(body.to_vec(), options.max_restarts())
+        }
+        self.registry.services.insert(addr, info);
+
+        Ok(options.restart())
+    }
+
+    /// Start a service of a catalog kind with the given start request body.
+    pub(super) async fn start_service_with<T: Encode<()>>(
+        &mut self,
+        ctx: &Context,
+        kind: &str,
+        body: T,
+    ) -> Result<()> {
+        let buf = minicbor::to_vec(body)?;
+        self.start_service_impl(ctx, kind, &mut Decoder::new(&buf))
+            .await?;
+        Ok(())
+    }
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn start_service(
+        &mut self,
+        ctx: &Context,
+        req: &Request<'_>,
+        kind: &str,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder> {
+        let mut node_manager = self.node_manager.write().await;
+        let restart = node_manager.start_service_impl(ctx, kind, dec).await?;
+        if restart && node_manager.service_supervisor.is_none() {
+            let ctx = ctx.new_detached(Address::random_local()).await?;
+            let supervisor = supervise(self.node_manager.clone(), ctx);
+            node_manager.service_supervisor = Some(tokio::spawn(supervisor));
+        }
+        Ok(Response::ok(req.id()))
+    }
+
+    pub(super) async fn stop_service(
+        &mut self,
+        ctx: &Context,
+        req: &Request<'_>,
+        kind: &str,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder> {
+        let mut node_manager = self.node_manager.write().await;
+        let body: StopServiceRequest = dec.decode()?;
+        let addr: Address = body.address().into();
+
+        match node_manager.registry.services.get(&addr) {
+            Some(info) if info.kind() == kind => {}
+            _ => return Ok(Response::not_found(req.id())),
+        }
+        if let Some((_, service)) = node_manager.catalog.get(kind) {
+            service.stop(ctx, &addr).await?
+        }
+        node_manager.registry.services.remove(&addr);
+
+        Ok(Response::ok(req.id()))
+    }
+
+    pub(super) async fn list_services_of_kind(
+        &self,
+        req: &Request<'_>,
+        kind: &str,
+    ) -> Result<Vec<u8>> {
+        let node_manager = self.node_manager.read().await;
+        if node_manager.catalog.get(kind).is_none() {
+            return Ok(Response::not_found(req.id()).to_vec()?);
+        }
+        let list = node_manager
+            .registry
+            .services
+            .iter()
+            .filter(|(_, info)| info.kind() == kind)
+            .map(|(addr, info)| {
+                ServiceStatus::new(addr.address(), info.kind()).with_restarts(info.restarts())
+            })
+            .collect();
+        Ok(Response::ok(req.id())
+            .body(ServiceList::new(list))
+            .to_vec()?)
+    }
+}
+
+/// Restart the restartable services whose worker is gone.
+///
+/// A service with a maximum number of restarts is left stopped once it
+/// has been restarted that many times.
+async fn supervise(node_manager: Arc<RwLock<NodeManager>>, ctx: Context) {
+    loop {
+        tokio::time::sleep(SUPERVISION_INTERVAL).await;
+        let workers = match ctx.list_workers().await {
+            Ok(workers) => workers,
+            Err(_) => break,
+        };
+        let mut node_manager = node_manager.write().await;
+        let stopped: Vec<Address> = node_manager
+            .registry
+            .services
+            .iter()
+            .filter(|(addr, info)| !workers.contains(addr) && info.restart().is_some())
+            .map(|(addr, _)| addr.clone())
+            .collect();
+        for addr in stopped {
+            let info = match node_manager.registry.services.get_mut(&addr) {
+                Some(info) => info,
+                None => continue,
+            };
+            let (kind, restarts) = (info.kind(), info.restarts());
+            if matches!(info.max_restarts(), Some(max) if restarts >= max) {
+                warn!(%addr, %kind, %restarts, "giving up restarting stopped service");
+                info.give_up();
+                continue;
+            }
+            let max_restarts = info.max_restarts();
+            let body = info.restart().map(|b| b.to_vec()).unwrap_or_default();
+            warn!(%addr, %kind, %restarts, "restarting stopped service");
+            node_manager.registry.services.remove(&addr);
+            let mut dec = Decoder::new(&body);
+            match node_manager.start_service_impl(&ctx, kind, &mut dec).await {
+                Ok(_) => {
+                    if let Some(info) = node_manager.registry.services.remove(&addr) {
+                        let info = info.with_restarts(restarts + 1);
+                        node_manager.registry.services.insert(addr, info);
+                    }
+                }
+                Err(err) => {
+                    warn!(%addr, %err, "failed to restart service");
+                    // Try again at the next check
+                    let info = ServiceInfo::new(kind)
+                        .with_restart(body, max_restarts)
+                        .with_restarts(restarts);
+                    node_manager.registry.services.insert(addr, info);
+                }
+            }
+        }
+    }
+}
+
+/// Start the worker of a service, guarded by the given access control.
+async fn start_worker<W, M>(
+    ctx: &Context,
+    addr: &Address,
+    access_control: Option<Arc<dyn AccessControl>>,
+    worker: W,
+) -> Result<()>
+where
+    M: Message + Send + 'static,
+    W: Worker<Context = Context, Message = M>,
+{
+    match access_control {
+        Some(ac) => {
+            let mailboxes = Mailboxes::main(addr.clone(), ac);
+            WorkerBuilder::with_mailboxes(mailboxes, worker)
+                .start(ctx)
+                .await?;
+            Ok(())
+        }
+        None => ctx.start_worker(addr.clone(), worker).await,
+    }
+}
+
+struct EchoerKind;
+
+#[async_trait]
+impl ServiceKind for EchoerKind {
+    async fn start(
+        &self,
+        ctx: &Context,
+        _node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        let body: StartEchoerServiceRequest = dec.decode()?;
+        let mut echoer = Echoer::new().with_authenticated(body.is_authenticated());
+        if let Some(max) = body.max_payload() {
+            echoer = echoer.with_max_payload(max as usize)
+        }
+        if let Some(max) = body.rate_limit() {
+            echoer = echoer.with_rate_limit(max)
+        }
+        if let Some(max) = body.total_rate_limit() {
+            echoer = echoer.with_total_rate_limit(max)
+        }
+        start_worker(ctx, addr, access_control, echoer).await
+    }
+}
+
+struct PerfKind;
+
+#[async_trait]
+impl ServiceKind for PerfKind {
+    async fn start(
+        &self,
+        ctx: &Context,
+        _node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        _dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        start_worker(ctx, addr, access_control, PerfResponder::default()).await
+    }
+}
+
+struct AckerKind;
+
+#[async_trait]
+impl ServiceKind for AckerKind {
+    async fn start(
+        &self,
+        ctx: &Context,
+        _node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        _dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        start_worker(ctx, addr, access_control, Acker).await
+    }
+}
+
+struct DeadLettersKind;
+
+#[async_trait]
+impl ServiceKind for DeadLettersKind {
+    async fn start(
+        &self,
+        ctx: &Context,
+        node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        // The node only hands undeliverable messages over to this address
+        if addr.address() != DEAD_LETTER_ADDRESS {
+            return Err(ApiError::message(format!(
+                "The dead letter service must be started at {DEAD_LETTER_ADDRESS}"
+            )));
+        }
+        let body: StartDeadLetterService = dec.decode()?;
+        let capacity = body.capacity().map_or(DEFAULT_CAPACITY, |c| c as usize);
+        let letters = DeadLetters::new(capacity);
+        let service = DeadLetterService::new(letters.clone());
+        start_worker(ctx, addr, access_control, service).await?;
+        node.dead_letters = Some(letters);
+        Ok(())
+    }
+}
+
+struct UppercaseKind;
+
+#[async_trait]
+impl ServiceKind for UppercaseKind {
+    async fn start(
+        &self,
+        ctx: &Context,
+        _node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        _dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        start_worker(ctx, addr, access_control, Uppercase).await
+    }
+}
+
+struct VerifierKind;
+
+#[async_trait]
+impl ServiceKind for VerifierKind {
+    async fn start(
+        &self,
+        ctx: &Context,
+        node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        _dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        let vault = if let Some(v) = &node.vault {
+            v.async_try_clone().await?
+        } else {
+            return Err(ApiError::generic("Vault not found"));
+        };
+        let vs = crate::verifier::Verifier::new(vault).with_revocations(node.revocations.clone());
+        start_worker(ctx, addr, access_control, vs).await
+    }
+}
+
+struct CredentialsKind;
+
+#[async_trait]
+impl ServiceKind for CredentialsKind {
+    async fn start(
+        &self,
+        _ctx: &Context,
+        node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        if access_control.is_some() {
+            return Err(ApiError::generic(
+                "The credentials service does not support access control",
+            ));
+        }
+        let body: StartCredentialsService = dec.decode()?;
+        let identity = node.identity()?;
+        let authorities = node.authorities()?;
+        identity
+            .start_credentials_exchange_worker(
+                authorities.public_identities(),
+                addr.clone(),
+                !body.oneway(),
+                node.authenticated_storage.async_try_clone().await?,
+            )
+            .await
+    }
+}
+
+struct RevocationKind;
+
+#[async_trait]
+impl ServiceKind for RevocationKind {
+    async fn start(
+        &self,
+        ctx: &Context,
+        node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        _dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        let id = node.identity()?.async_try_clone().await?;
+        let server = crate::revocation::Server::new(node.revocations.clone(), id);
+        start_worker(ctx, addr, access_control, server).await
+    }
+}
+
+struct SignerKind;
+
+#[async_trait]
+impl ServiceKind for SignerKind {
+    async fn start(
+        &self,
+        ctx: &Context,
+        node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        let body: StartSignerService = dec.decode()?;
+        let id = node.identity()?.async_try_clone().await?;
+        let server = SignerServer::new(id, body.authorized().to_vec());
+        start_worker(ctx, addr, access_control, server).await
+    }
+}
+
+struct FileTransferKind;
+
+#[async_trait]
+impl ServiceKind for FileTransferKind {
+    async fn start(
+        &self,
+        ctx: &Context,
+        _node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        let body: StartFileTransferService = dec.decode()?;
+        std::fs::create_dir_all(body.dir())
+            .map_err(|_| ApiError::generic("Invalid file transfer directory"))?;
+        let mut server = crate::file_transfer::Server::new(body.dir())
+            .with_overwrite(body.overwrite())
+            .with_max_size(body.max_size().unwrap_or(DEFAULT_MAX_FILE_SIZE));
+        server = if !body.authorized().is_empty() {
+            server.with_authorized(body.authorized().to_vec())
+        } else if access_control.is_some() {
+            server.without_identity_check()
+        } else {
+            return Err(ApiError::generic(
+                "file_transfer requires authorized identities or a resource",
+            ));
+        };
+        start_worker(ctx, addr, access_control, server).await
+    }
+}
+
+struct PubSubKind;
+
+#[async_trait]
+impl ServiceKind for PubSubKind {
+    async fn start(
+        &self,
+        ctx: &Context,
+        node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        let body: StartPubSubService = dec.decode()?;
+        let retain = body.retain().unwrap_or(0) as usize;
+        let mut server = crate::pubsub::Server::new(retain);
+        if body.is_persistent() {
+            let storage = node.authenticated_storage.async_try_clone().await?;
+            server = server.with_storage(storage)
+        }
+        start_worker(ctx, addr, access_control, server).await
+    }
+}
+
+struct ForwardingKind;
+
+#[async_trait]
+impl ServiceKind for ForwardingKind {
+    async fn start(
+        &self,
+        ctx: &Context,
+        _node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        _dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        // The access control only applies to registrations, the forwarders
+        // themselves forward messages from anyone
+        let access_control = access_control.unwrap_or_else(|| Arc::new(AllowAll));
+        ForwardingService::create_with_access_control(ctx, addr.clone(), access_control).await
+    }
+}
+
+#[cfg(feature = "direct-authenticator")]
+struct AuthenticatorKind;
+
+#[cfg(feature = "direct-authenticator")]
+#[async_trait]
+impl ServiceKind for AuthenticatorKind {
+    async fn start(
+        &self,
+        ctx: &Context,
+        node: &mut NodeManager,
+        addr: &Address,
+        access_control: Option<Arc<dyn AccessControl>>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<()> {
+        use crate::authenticator::direct::Server;
+        use crate::multiaddr_to_route;
+        use crate::nodes::models::services::StartAuthenticatorRequest;
+        use ockam_multiaddr::MultiAddr;
+        use std::str::FromStr;
+
+        let body: StartAuthenticatorRequest = dec.decode()?;
+        let (path, proj) = (body.path(), body.project());
+        let id = node.identity()?.async_try_clone().await?;
+        let signer = match body.signer() {
+            Some(signer) => {
+                let addr = MultiAddr::from_str(signer)
+                    .map_err(|_| ApiError::generic("Invalid signer route"))?;
+                let route = multiaddr_to_route(&addr)
+                    .ok_or_else(|| ApiError::generic("Invalid signer route"))?;
+                Some(crate::authenticator::signer::Client::new(route, ctx).await?)
+            }
+            None => None,
+        };
+        match body.redis() {
+            #[cfg(feature = "redis")]
+            Some(url) => {
+                let prefix = format!("ockam:{}", String::from_utf8_lossy(proj));
+                let db = crate::redis::RedisStorage::new(url, prefix).await?;
+                let mut au = Server::new(proj.to_vec(), db, path, id)
+                    .with_audit(node.audit.clone())
+                    .with_revocations(node.revocations.clone());
+                if let Some(signer) = signer {
+                    au = au.with_signer(signer)
+                }
+                if body.is_stateless() {
+                    au = au.with_shared_tokens()
+                }
+                start_worker(ctx, addr, access_control, au).await
+            }
+            #[cfg(not(feature = "redis"))]
+            Some(_) => Err(ApiError::generic("Redis storage not available")),
+            None => {
+                let db = node.authenticated_storage.async_try_clone().await?;
+                let mut au = Server::new(proj.to_vec(), db, path, id)
+                    .with_audit(node.audit.clone())
+                    .with_revocations(node.revocations.clone());
+                if let Some(signer) = signer {
+                    au = au.with_signer(signer)
+                }
+                if body.is_stateless() {
+                    au = au.with_shared_tokens()
+                }
+                start_worker(ctx, addr, access_control, au).await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::models::services::StartUppercaseServiceRequest;
+    use ockam::route;
+    use ockam_core::api::Status;
+
+    async fn call<T: Encode<()>>(
+        ctx: &Context,
+        node: &ockam::Route,
+        req: ockam_core::api::RequestBuilder<'_, T>,
+    ) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        req.encode(&mut buf)?;
+        ctx.send_and_receive(node.clone(), buf).await
+    }
+
+    fn status(res: &[u8]) -> Result<Option<Status>> {
+        Ok(Decoder::new(res).decode::<Response>()?.status())
+    }
+
+    #[ockam_macros::test]
+    async fn services_are_started_listed_and_stopped(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+
+        let start = || {
+            Request::post("/node/services/uppercase").body(StartUppercaseServiceRequest::new("up"))
+        };
+        let res = call(ctx, &node, start()).await?;
+        assert_eq!(status(&res)?, Some(Status::Ok));
+        let msg: String = ctx
+            .send_and_receive(route!["up"], "hello".to_string())
+            .await?;
+        assert_eq!(msg, "HELLO");
+
+        // The address is taken
+        let res = call(ctx, &node, start()).await?;
+        assert_ne!(status(&res)?, Some(Status::Ok));
+
+        let res = call(ctx, &node, Request::get("/node/services/uppercase")).await?;
+        let mut dec = Decoder::new(&res);
+        dec.decode::<Response>()?;
+        let list: ServiceList = dec.decode()?;
+        assert_eq!(list.list.len(), 1);
+        assert_eq!(list.list[0].addr, "up");
+
+        // Services are stopped by kind
+        let stop = |kind: &str| {
+            Request::delete(format!("/node/services/{kind}")).body(StopServiceRequest::new("up"))
+        };
+        let res = call(ctx, &node, stop("echo")).await?;
+        assert_eq!(status(&res)?, Some(Status::NotFound));
+        let res = call(ctx, &node, stop("uppercase")).await?;
+        assert_eq!(status(&res)?, Some(Status::Ok));
+        let res = call(ctx, &node, stop("uppercase")).await?;
+        assert_eq!(status(&res)?, Some(Status::NotFound));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn restartable_services_are_restarted(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+
+        let options = ServiceOptions::new().with_restart(true);
+        let req = Request::post("/node/services/uppercase")
+            .body(StartUppercaseServiceRequest::new("up").with_options(options));
+        let res = call(ctx, &node, req).await?;
+        assert_eq!(status(&res)?, Some(Status::Ok));
+
+        // The worker stops without the service being stopped
+        ctx.stop_worker("up").await?;
+        tokio::time::sleep(SUPERVISION_INTERVAL + Duration::from_secs(1)).await;
+
+        let msg: String = ctx
+            .send_and_receive(route!["up"], "hello".to_string())
+            .await?;
+        assert_eq!(msg, "HELLO");
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn restarts_are_counted_and_limited(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+
+        let options = ServiceOptions::new()
+            .with_restart(true)
+            .with_max_restarts(1);
+        let req = Request::post("/node/services/uppercase")
+            .body(StartUppercaseServiceRequest::new("up").with_options(options));
+        let res = call(ctx, &node, req).await?;
+        assert_eq!(status(&res)?, Some(Status::Ok));
+
+        let restarts = |res: Vec<u8>| -> Result<u32> {
+            let mut dec = Decoder::new(&res);
+            dec.decode::<Response>()?;
+            let list: ServiceList = dec.decode()?;
+            Ok(list.list[0].restarts)
+        };
+        let list = || Request::get("/node/services/uppercase");
+        assert_eq!(restarts(call(ctx, &node, list()).await?)?, 0);
+
+        ctx.stop_worker("up").await?;
+        tokio::time::sleep(SUPERVISION_INTERVAL + Duration::from_secs(1)).await;
+        assert_eq!(restarts(call(ctx, &node, list()).await?)?, 1);
+
+        // The service is not restarted a second time
+        ctx.stop_worker("up").await?;
+        tokio::time::sleep(SUPERVISION_INTERVAL + Duration::from_secs(1)).await;
+        assert!(!ctx.list_workers().await?.contains(&"up".into()));
+        assert_eq!(restarts(call(ctx, &node, list()).await?)?, 1);
+
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,941 @@
+//! The declarative setup of a node.
+//!
+//! The [`NodeSetup`] stored in the node configuration is applied when the
+//! node manager starts, and running nodes are converged to another setup
+//! with `POST /node/config`. Every entry is created through the node API,
+//! as if a client had requested it, so it behaves exactly like its
+//! imperative counterpart. Entries which can not be created are reported
+//! and left out of the effective configuration returned by `GET
+//! /node/config`.
+
+use super::policy::check_policy;
+use super::{map_anyhow_err, Alias, NodeManagerWorker};
+use crate::error::ApiError;
+use crate::nodes::models::config::{
+    ApplySetup, AttributeSetup, ChannelSetup, ForwarderSetup, InletSetup, ListenerSetup, NodeSetup,
+    OutletSetup, PolicySetup, ServiceSetup, SetupChanges, TransportSetup,
+};
+use crate::nodes::models::forwarder::{CreateForwarder, ForwarderInfo};
+use crate::nodes::models::portal::{
+    CreateInlet, CreateOutlet, InletStatus, OutletStatus, PortalPolicy,
+};
+use crate::nodes::models::secure_channel::CreateSecureChannelListenerRequest;
+use crate::nodes::models::services::{
+    ServiceOptions, StartAckServiceRequest, StartCredentialsService, StartEchoerServiceRequest,
+    StartFileTransferService, StartPerfServiceRequest, StartPubSubService, StartRevocationService,
+    StartSignerService, StartUppercaseServiceRequest, StartVerifierService, StopServiceRequest,
+};
+use crate::nodes::models::transport::{
+    CreateTransport, DeleteTransport, TransportMode, TransportStatus, TransportType,
+};
+use crate::session::Key as SessionKey;
+use minicbor::{Decode, Decoder, Encode};
+use ockam::abac::{expand_actions, AttributeType, Conditional, Key, Resource};
+use ockam::{Address, Context, Result};
+use ockam_core::api::{self, Request, RequestBuilder, Response, ResponseBuilder};
+use ockam_core::CowStr;
+use ockam_identity::IdentityIdentifier;
+use ockam_multiaddr::MultiAddr;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The setup a node applied, with what is needed to undo it.
+#[derive(Clone, Default)]
+pub(super) struct AppliedSetup {
+    attributes: Vec<(AttributeSetup, ())>,
+    policies: Vec<(PolicySetup, ())>,
+    /// With the transport ID.
+    transports: Vec<(TransportSetup, Alias)>,
+    listeners: Vec<(ListenerSetup, ())>,
+    /// With the key of the session supervising the channel.
+    channels: Vec<(ChannelSetup, SessionKey)>,
+    services: Vec<(ServiceSetup, ())>,
+    /// With the outlet alias.
+    outlets: Vec<(OutletSetup, Alias)>,
+    /// With the inlet alias.
+    inlets: Vec<(InletSetup, Alias)>,
+    /// With the forwarder worker address.
+    forwarders: Vec<(ForwarderSetup, Address)>,
+}
+
+impl AppliedSetup {
+    fn setup(&self) -> NodeSetup {
+        fn entries<T: Clone, H>(v: &[(T, H)]) -> Vec<T> {
+            v.iter().map(|(e, _)| e.clone()).collect()
+        }
+        let mut setup = NodeSetup::default();
+        setup.attributes = entries(&self.attributes);
+        setup.policies = entries(&self.policies);
+        setup.transports = entries(&self.transports);
+        setup.listeners = entries(&self.listeners);
+        setup.channels = entries(&self.channels);
+        setup.services = entries(&self.services);
+        setup.outlets = entries(&self.outlets);
+        setup.inlets = entries(&self.inlets);
+        setup.forwarders = entries(&self.forwarders);
+        setup
+    }
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn get_node_config(
+        &self,
+        req: &Request<'_>,
+    ) -> Result<ResponseBuilder<NodeSetup>> {
+        let node_manager = self.node_manager.read().await;
+        Ok(Response::ok(req.id()).body(node_manager.setup.setup()))
+    }
+
+    /// Converge the node to the setup of the request, and make it the one
+    /// applied when the node starts.
+    pub(super) async fn apply_node_config(
+        &mut self,
+        ctx: &mut Context,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<SetupChanges>> {
+        let body: ApplySetup = dec.decode()?;
+        let dry_run = body.is_dry_run();
+        let desired = body.into_setup();
+        let lock = self.node_manager.read().await.setup_lock.clone();
+        let _applying = lock.lock().await;
+        let changes = self.reconcile(ctx, &desired, dry_run).await?;
+        if !dry_run {
+            let node_manager = self.node_manager.read().await;
+            node_manager
+                .config
+                .setup()
+                .set(desired)
+                .map_err(map_anyhow_err)?;
+        }
+        Ok(Response::ok(req.id()).body(changes))
+    }
+
+    /// Apply the setup of the node configuration.
+    pub(super) async fn apply_setup(&mut self, ctx: &mut Context) -> Result<()> {
+        let setup = {
+            let node_manager = self.node_manager.read().await;
+            node_manager.config.setup().read().clone()
+        };
+        if setup.is_empty() {
+            return Ok(());
+        }
+        info!("Applying the node setup");
+        let lock = self.node_manager.read().await.setup_lock.clone();
+        let _applying = lock.lock().await;
+        let changes = self.reconcile(ctx, &setup, false).await?;
+        for f in changes.failed {
+            warn!("Failed to {f}")
+        }
+        Ok(())
+    }
+
+    /// Delete the applied entries which are not desired, then create the
+    /// desired entries which are not applied.
+    ///
+    /// Entries are deleted in the reverse order they are created in, so
+    /// that nothing refers to a deleted policy or transport. With `dry_run`
+    /// the changes are only reported.
+    ///
+    /// Callers hold the setup lock, so that setups are applied one at a
+    /// time while the applied setup stays visible to other requests.
+    async fn reconcile(
+        &mut self,
+        ctx: &mut Context,
+        desired: &NodeSetup,
+        dry_run: bool,
+    ) -> Result<SetupChanges> {
+        let mut applied = self.node_manager.read().await.setup.clone();
+        let mut changes = SetupChanges::default();
+
+        for (f, addr) in stale(&mut applied.forwarders, &desired.forwarders) {
+            let res = if dry_run {
+                Ok(())
+            } else {
+                ctx.stop_worker(addr.clone()).await
+            };
+            if !deleted(&mut changes, &f, res, dry_run) {
+                applied.forwarders.push((f, addr))
+            }
+        }
+        for (i, alias) in stale(&mut applied.inlets, &desired.inlets) {
+            let res = if dry_run {
+                Ok(())
+            } else {
+                self.delete_inlet(&alias).await
+            };
+            if !deleted(&mut changes, &i, res, dry_run) {
+                applied.inlets.push((i, alias))
+            }
+        }
+        for (o, alias) in stale(&mut applied.outlets, &desired.outlets) {
+            let res = if dry_run {
+                Ok(())
+            } else {
+                self.delete_outlet(&alias).await
+            };
+            if !deleted(&mut changes, &o, res, dry_run) {
+                applied.outlets.push((o, alias))
+            }
+        }
+        for (s, ()) in stale(&mut applied.services, &desired.services) {
+            let res = if dry_run {
+                Ok(())
+            } else {
+                let path = format!("/node/services/{}", s.kind);
+                let req = Request::delete(path).body(StopServiceRequest::new(s.address.as_str()));
+                self.apply(ctx, req).await.map(|_| ())
+            };
+            if !deleted(&mut changes, &s, res, dry_run) {
+                applied.services.push((s, ()))
+            }
+        }
+        for (c, k) in stale(&mut applied.channels, &desired.channels) {
+            let res = if dry_run {
+                Ok(())
+            } else {
+                self.cool_secure_channel(&k).await
+            };
+            if !deleted(&mut changes, &c, res, dry_run) {
+                applied.channels.push((c, k))
+            }
+        }
+        for (l, ()) in stale(&mut applied.listeners, &desired.listeners) {
+            let res = if dry_run {
+                Ok(())
+            } else {
+                self.delete_listener(ctx, &l).await
+            };
+            if !deleted(&mut changes, &l, res, dry_run) {
+                applied.listeners.push((l, ()))
+            }
+        }
+        for (t, tid) in stale(&mut applied.transports, &desired.transports) {
+            let res = match t.mode {
+                TransportMode::Listen => Err(ApiError::generic("TCP listeners can not be stopped")),
+                TransportMode::Connect if dry_run => Ok(()),
+                TransportMode::Connect => {
+                    let req = Request::delete("/node/tcp/connection")
+                        .body(DeleteTransport::new(tid.as_str(), false));
+                    self.apply(ctx, req).await.map(|_| ())
+                }
+            };
+            if !deleted(&mut changes, &t, res, dry_run) {
+                applied.transports.push((t, tid))
+            }
+        }
+        for (p, ()) in stale(&mut applied.policies, &desired.policies) {
+            let res = if dry_run {
+                Ok(())
+            } else {
+                self.delete_policy(&p).await
+            };
+            if !deleted(&mut changes, &p, res, dry_run) {
+                applied.policies.push((p, ()))
+            }
+        }
+        for (a, ()) in stale(&mut applied.attributes, &desired.attributes) {
+            if !dry_run {
+                let node_manager = self.node_manager.read().await;
+                let mut schema = node_manager.attribute_schema.write().unwrap();
+                schema.remove(&Key::from(a.name.as_str()));
+            }
+            if !deleted(&mut changes, &a, Ok(()), dry_run) {
+                applied.attributes.push((a, ()))
+            }
+        }
+
+        for a in missing(&desired.attributes, &applied.attributes) {
+            let res = match AttributeType::from_str(&a.kind) {
+                Ok(_) if dry_run => Ok(()),
+                Ok(t) => {
+                    let node_manager = self.node_manager.read().await;
+                    let mut schema = node_manager.attribute_schema.write().unwrap();
+                    schema.declare(a.name.as_str(), t);
+                    Ok(())
+                }
+                Err(e) => Err(e.into()),
+            };
+            if let Some(()) = created(&mut changes, a, res, dry_run) {
+                applied.attributes.push((a.clone(), ()))
+            }
+        }
+
+        for p in missing(&desired.policies, &applied.policies) {
+            let res = if dry_run {
+                Ok(())
+            } else {
+                self.create_policy(p).await
+            };
+            if let Some(()) = created(&mut changes, p, res, dry_run) {
+                applied.policies.push((p.clone(), ()))
+            }
+        }
+        for t in missing(&desired.transports, &applied.transports) {
+            let res = if dry_run {
+                Ok(Alias::new())
+            } else {
+                self.create_transport(ctx, t).await
+            };
+            if let Some(tid) = created(&mut changes, t, res, dry_run) {
+                applied.transports.push((t.clone(), tid))
+            }
+        }
+        for l in missing(&desired.listeners, &applied.listeners) {
+            let res = match listener(l) {
+                Ok(_) if dry_run => Ok(()),
+                Ok(req) => self.apply(ctx, req).await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+            if let Some(()) = created(&mut changes, l, res, dry_run) {
+                applied.listeners.push((l.clone(), ()))
+            }
+        }
+        for c in missing(&desired.channels, &applied.channels) {
+            let res = match channel(c) {
+                Ok(_) if dry_run => Ok(None),
+                Ok((to, auth)) => self.warm_secure_channel(&to, auth).await.map(Some),
+                Err(e) => Err(e),
+            };
+            if let Some(Some(k)) = created(&mut changes, c, res, dry_run) {
+                applied.channels.push((c.clone(), k))
+            }
+        }
+        for s in missing(&desired.services, &applied.services) {
+            let res = if dry_run {
+                Ok(())
+            } else {
+                self.create_service(ctx, s).await
+            };
+            if let Some(()) = created(&mut changes, s, res, dry_run) {
+                applied.services.push((s.clone(), ()))
+            }
+        }
+        for o in missing(&desired.outlets, &applied.outlets) {
+            let res = if dry_run {
+                Ok(Alias::new())
+            } else {
+                self.create_outlet_from(ctx, o).await
+            };
+            if let Some(alias) = created(&mut changes, o, res, dry_run) {
+                applied.outlets.push((o.clone(), alias))
+            }
+        }
+        for i in missing(&desired.inlets, &applied.inlets) {
+            let res = match inlet(i) {
+                Ok(_) if dry_run => Ok(Alias::new()),
+                Ok(req) => match self.apply(ctx, req).await {
+                    Ok(res) => body::<InletStatus>(&res).map(|s| s.alias.to_string()),
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            };
+            if let Some(alias) = created(&mut changes, i, res, dry_run) {
+                applied.inlets.push((i.clone(), alias))
+            }
+        }
+        for f in missing(&desired.forwarders, &applied.forwarders) {
+            let res = match forwarder(f) {
+                Ok(_) if dry_run => Ok(Address::random_local()),
+                Ok(req) => match self.apply(ctx, req).await {
+                    Ok(res) => body::<ForwarderInfo>(&res).map(|i| i.worker_address().into()),
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            };
+            if let Some(addr) = created(&mut changes, f, res, dry_run) {
+                applied.forwarders.push((f.clone(), addr))
+            }
+        }
+
+        self.node_manager.write().await.setup = applied;
+        Ok(changes)
+    }
+
+    /// Handle a request of the setup like any other API request.
+    ///
+    /// Returns the response, if its status is ok.
+    async fn apply<T: Encode<()>>(
+        &mut self,
+        ctx: &mut Context,
+        req: RequestBuilder<'_, T>,
+    ) -> Result<Vec<u8>> {
+        let buf = req.to_vec()?;
+        let mut dec = Decoder::new(&buf);
+        let req: Request = dec.decode()?;
+        let res = self.handle_request(ctx, &req, &mut dec).await?;
+        api::is_ok(req.path(), &res)?;
+        Ok(res)
+    }
+
+    async fn create_policy(&self, p: &PolicySetup) -> Result<()> {
+        let condition: Conditional = serde_json::from_str(&p.condition)
+            .map_err(|e| ApiError::generic(&format!("Invalid policy condition: {e}")))?;
+        let action = p.action.as_deref().unwrap_or(PortalPolicy::DEFAULT_ACTION);
+        let resource = Resource::from(p.resource.as_str());
+        let node_manager = self.node_manager.read().await;
+        check_policy(&node_manager.attribute_schema.read().unwrap(), &condition)?;
+        for action in expand_actions([action])? {
+            node_manager
+                .policies
+                .set_policy(resource.clone(), action, &condition)
+                .await?
+        }
+        Ok(())
+    }
+
+    /// Delete the entries of the actions of a policy, leaving the other
+    /// actions of its resource alone.
+    async fn delete_policy(&self, p: &PolicySetup) -> Result<()> {
+        let action = p.action.as_deref().unwrap_or(PortalPolicy::DEFAULT_ACTION);
+        let resource = Resource::from(p.resource.as_str());
+        let node_manager = self.node_manager.read().await;
+        for action in expand_actions([action])? {
+            node_manager
+                .policies
+                .del_policy_action(&resource, &action)
+                .await?
+        }
+        Ok(())
+    }
+
+    async fn create_transport(&mut self, ctx: &mut Context, t: &TransportSetup) -> Result<Alias> {
+        let res = self.apply(ctx, transport(t)).await?;
+        Ok(body::<TransportStatus>(&res)?.tid.to_string())
+    }
+
+    async fn create_outlet_from(&mut self, ctx: &mut Context, o: &OutletSetup) -> Result<Alias> {
+        let res = self.apply(ctx, outlet(o)).await?;
+        Ok(body::<OutletStatus>(&res)?.alias.to_string())
+    }
+
+    async fn create_service(&mut self, ctx: &mut Context, s: &ServiceSetup) -> Result<()> {
+        let path = format!("/node/services/{}", s.kind);
+        let addr = s.address.as_str();
+        let mut options = ServiceOptions::new().with_restart(s.restart);
+        if let Some(r) = &s.resource {
+            options = options.with_resource(r.as_str())
+        }
+        if let Some(n) = s.max_restarts {
+            options = options.with_max_restarts(n)
+        }
+        let req = Request::post(path);
+        let res = match s.kind.as_str() {
+            "echo" => {
+                let mut b = StartEchoerServiceRequest::new(addr)
+                    .with_authenticated(s.authenticated)
+                    .with_options(options);
+                if let Some(n) = s.max_payload {
+                    b = b.with_max_payload(n)
+                }
+                if let Some(n) = s.rate_limit {
+                    b = b.with_rate_limit(n)
+                }
+                if let Some(n) = s.total_rate_limit {
+                    b = b.with_total_rate_limit(n)
+                }
+                self.apply(ctx, req.body(b)).await
+            }
+            "ack" => {
+                let b = StartAckServiceRequest::new(addr).with_options(options);
+                self.apply(ctx, req.body(b)).await
+            }
+            "perf" => {
+                let b = StartPerfServiceRequest::new(addr).with_options(options);
+                self.apply(ctx, req.body(b)).await
+            }
+            "uppercase" => {
+                let b = StartUppercaseServiceRequest::new(addr).with_options(options);
+                self.apply(ctx, req.body(b)).await
+            }
+            "verifier" => {
+                let b = StartVerifierService::new(addr).with_options(options);
+                self.apply(ctx, req.body(b)).await
+            }
+            "revocation" => {
+                let b = StartRevocationService::new(addr).with_options(options);
+                self.apply(ctx, req.body(b)).await
+            }
+            "credentials" => {
+                let b = StartCredentialsService::new(addr, s.oneway).with_options(options);
+                self.apply(ctx, req.body(b)).await
+            }
+            "signer" => {
+                let authorized = s
+                    .authorized
+                    .iter()
+                    .map(|i| IdentityIdentifier::try_from(i.as_str()))
+                    .collect::<Result<Vec<_>>>()?;
+                let b = StartSignerService::new(addr, authorized).with_options(options);
+                self.apply(ctx, req.body(b)).await
+            }
+            "file_transfer" => {
+                let dir = s
+                    .dir
+                    .as_deref()
+                    .ok_or_else(|| ApiError::generic("file_transfer requires a dir"))?;
+                let authorized = s
+                    .authorized
+                    .iter()
+                    .map(|i| IdentityIdentifier::try_from(i.as_str()))
+                    .collect::<Result<Vec<_>>>()?;
+                let mut b = StartFileTransferService::new(addr, Path::new(dir))
+                    .with_authorized(authorized)
+                    .with_overwrite(s.overwrite)
+                    .with_options(options);
+                if let Some(n) = s.max_size {
+                    b = b.with_max_size(n)
+                }
+                self.apply(ctx, req.body(b)).await
+            }
+            "pubsub" => {
+                let mut b = StartPubSubService::new(addr)
+                    .with_persist(s.persist)
+                    .with_options(options);
+                if let Some(n) = s.retain {
+                    b = b.with_retain(n)
+                }
+                self.apply(ctx, req.body(b)).await
+            }
+            #[cfg(feature = "direct-authenticator")]
+            "authenticator" => {
+                use crate::nodes::models::services::StartAuthenticatorRequest;
+                let (path, project) = match (&s.enrollers, &s.project) {
+                    (Some(path), Some(project)) => (Path::new(path), project),
+                    _ => {
+                        return Err(ApiError::generic(
+                            "authenticator requires enrollers and a project",
+                        ))
+                    }
+                };
+                let mut b = StartAuthenticatorRequest::new(addr, path, project.as_bytes())
+                    .with_stateless(s.stateless)
+                    .with_options(options);
+                if let Some(url) = &s.redis {
+                    b = b.with_redis(url)
+                }
+                if let Some(route) = &s.signer {
+                    b = b.with_signer(route)
+                }
+                self.apply(ctx, req.body(b)).await
+            }
+            kind => Err(ApiError::message(format!(
+                "Service kind {kind} can not be set up declaratively"
+            ))),
+        };
+        res.map(|_| ())
+    }
+
+    async fn delete_listener(&mut self, ctx: &Context, l: &ListenerSetup) -> Result<()> {
+        let addr = Address::from(l.address.as_str());
+        let mut node_manager = self.node_manager.write().await;
+        ctx.stop_worker(addr.clone()).await?;
+        node_manager.registry.secure_channel_listeners.remove(&addr);
+        Ok(())
+    }
+
+    async fn delete_inlet(&mut self, alias: &str) -> Result<()> {
+        let mut node_manager = self.node_manager.write().await;
+        if let Some(info) = node_manager.registry.inlets.remove(alias) {
+            node_manager
+                .tcp_transport
+                .stop_inlet(info.worker_addr)
+                .await?
+        }
+        Ok(())
+    }
+
+    async fn delete_outlet(&mut self, alias: &str) -> Result<()> {
+        let mut node_manager = self.node_manager.write().await;
+        if let Some(info) = node_manager.registry.outlets.remove(alias) {
+            node_manager
+                .tcp_transport
+                .stop_outlet(info.worker_addr)
+                .await?
+        }
+        Ok(())
+    }
+}
+
+/// A human readable description of a setup entry, to report changes.
+trait Describe {
+    fn describe(&self) -> String;
+}
+
+impl Describe for AttributeSetup {
+    fn describe(&self) -> String {
+        format!("{} attribute {}", self.kind, self.name)
+    }
+}
+
+impl Describe for PolicySetup {
+    fn describe(&self) -> String {
+        let action = self
+            .action
+            .as_deref()
+            .unwrap_or(PortalPolicy::DEFAULT_ACTION);
+        format!("policy of {} on {}", action, self.resource)
+    }
+}
+
+impl Describe for TransportSetup {
+    fn describe(&self) -> String {
+        match self.mode {
+            TransportMode::Listen => format!("tcp listener at {}", self.address),
+            TransportMode::Connect => format!("tcp connection to {}", self.address),
+        }
+    }
+}
+
+impl Describe for ChannelSetup {
+    fn describe(&self) -> String {
+        format!("secure channel to {}", self.to)
+    }
+}
+
+impl Describe for ListenerSetup {
+    fn describe(&self) -> String {
+        format!("secure channel listener at {}", self.address)
+    }
+}
+
+impl Describe for ServiceSetup {
+    fn describe(&self) -> String {
+        format!("{} service at {}", self.kind, self.address)
+    }
+}
+
+impl Describe for OutletSetup {
+    fn describe(&self) -> String {
+        format!("outlet from {} to {}", self.from, self.to)
+    }
+}
+
+impl Describe for InletSetup {
+    fn describe(&self) -> String {
+        format!("inlet from {} to {}", self.from, self.to)
+    }
+}
+
+impl Describe for ForwarderSetup {
+    fn describe(&self) -> String {
+        match &self.alias {
+            Some(a) => format!("forwarder {} at {}", a, self.at),
+            None => format!("forwarder at {}", self.at),
+        }
+    }
+}
+
+/// Remove the applied entries which are not desired and return them.
+fn stale<T: PartialEq, H>(applied: &mut Vec<(T, H)>, desired: &[T]) -> Vec<(T, H)> {
+    let (keep, stale): (Vec<_>, Vec<_>) = std::mem::take(applied)
+        .into_iter()
+        .partition(|(e, _)| desired.contains(e));
+    *applied = keep;
+    stale
+}
+
+/// The desired entries which are not applied.
+fn missing<'a, T: PartialEq, H>(desired: &'a [T], applied: &[(T, H)]) -> Vec<&'a T> {
+    desired
+        .iter()
+        .filter(|d| !applied.iter().any(|(e, _)| e == *d))
+        .collect()
+}
+
+/// Record the deletion of an entry and return whether it was deleted.
+///
+/// Nothing is deleted in a dry run.
+fn deleted<T: Describe>(changes: &mut SetupChanges, e: &T, res: Result<()>, dry_run: bool) -> bool {
+    match res {
+        Ok(()) => {
+            changes.deleted.push(e.describe());
+            !dry_run
+        }
+        Err(err) => {
+            changes
+                .failed
+                .push(format!("delete {}: {err}", e.describe()));
+            false
+        }
+    }
+}
+
+/// Record the creation of an entry and return what is needed to delete it.
+///
+/// Nothing is created in a dry run.
+fn created<T: Describe, H>(
+    changes: &mut SetupChanges,
+    e: &T,
+    res: Result<H>,
+    dry_run: bool,
+) -> Option<H> {
+    match res {
+        Ok(h) => {
+            changes.created.push(e.describe());
+            if dry_run {
+                None
+            } else {
+                Some(h)
+            }
+        }
+        Err(err) => {
+            changes
+                .failed
+                .push(format!("create {}: {err}", e.describe()));
+            None
+        }
+    }
+}
+
+/// Decode the body of an ok response.
+fn body<'a, T: Decode<'a, ()>>(res: &'a [u8]) -> Result<T> {
+    let mut dec = Decoder::new(res);
+    dec.decode::<Response>()?;
+    Ok(dec.decode()?)
+}
+
+fn transport(t: &TransportSetup) -> RequestBuilder<'_, CreateTransport<'_>> {
+    let path = match t.mode {
+        TransportMode::Listen => "/node/tcp/listener",
+        TransportMode::Connect => "/node/tcp/connection",
+    };
+    Request::post(path).body(CreateTransport::new(
+        TransportType::Tcp,
+        t.mode,
+        t.address.as_str(),
+    ))
+}
+
+fn channel(c: &ChannelSetup) -> Result<(MultiAddr, Option<IdentityIdentifier>)> {
+    let to = MultiAddr::from_str(&c.to)
+        .map_err(|e| ApiError::message(format!("Invalid channel address {}: {e}", c.to)))?;
+    let auth = match &c.authorized {
+        Some(i) => Some(IdentityIdentifier::try_from(i.as_str())?),
+        None => None,
+    };
+    Ok((to, auth))
+}
+
+fn listener(
+    l: &ListenerSetup,
+) -> Result<RequestBuilder<'_, CreateSecureChannelListenerRequest<'_>>> {
+    let ids = match &l.authorized_identifiers {
+        Some(ids) => Some(
+            ids.iter()
+                .map(|i| IdentityIdentifier::try_from(i.as_str()))
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        None => None,
+    };
+    let addr = Address::from(l.address.as_str());
+    let mut body = CreateSecureChannelListenerRequest::new(&addr, ids)
+        .with_limits(l.max_channels, l.max_channels_per_identity);
+    if l.sync_members {
+        body = body.with_synced_members(l.sync_interval)
+    }
+    Ok(Request::post("/node/secure_channel_listener").body(body))
+}
+
+fn outlet(o: &OutletSetup) -> RequestBuilder<'_, CreateOutlet<'_>> {
+    let alias: Option<CowStr> = o.alias.as_deref().map(CowStr::from);
+    let mut body = CreateOutlet::new(o.to.as_str(), o.from.as_str(), alias, o.check_credential);
+    if let Some(c) = &o.policy {
+        body.set_policy(PortalPolicy::new(c.as_str()))
+    }
+    Request::post("/node/outlet").body(body)
+}
+
+fn inlet(i: &InletSetup) -> Result<RequestBuilder<'_, CreateInlet<'_>>> {
+    let from = SocketAddr::from_str(&i.from)
+        .map_err(|e| ApiError::message(format!("Invalid inlet address {}: {e}", i.from)))?;
+    let to = MultiAddr::from_str(&i.to)
+        .map_err(|e| ApiError::message(format!("Invalid outlet address {}: {e}", i.to)))?;
+    let mut body = CreateInlet::to_node(from, to, i.check_credential, None);
+    if let Some(a) = &i.alias {
+        body.set_alias(a.as_str())
+    }
+    if let Some(c) = &i.policy {
+        body.set_policy(PortalPolicy::new(c.as_str()))
+    }
+    Ok(Request::post("/node/inlet").body(body))
+}
+
+fn forwarder(f: &ForwarderSetup) -> Result<RequestBuilder<'static, CreateForwarder<'static>>> {
+    let at = MultiAddr::from_str(&f.at)
+        .map_err(|e| ApiError::message(format!("Invalid forwarder address {}: {e}", f.at)))?;
+    let auth = match &f.authorized {
+        Some(i) => Some(IdentityIdentifier::try_from(i.as_str())?),
+        None => None,
+    };
+    let body = CreateForwarder::at_node(at, f.alias.clone(), false, auth);
+    Ok(Request::post("/node/forwarder").body(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::models::secure_channel::{
+        CreateSecureChannelRequest, CreateSecureChannelResponse, CredentialExchangeMode,
+    };
+    use crate::nodes::NodeManager;
+    use ockam::route;
+    use ockam_core::api::Status;
+    use ockam_node::tokio;
+    use std::time::Duration;
+
+    #[ockam_macros::test]
+    async fn setup_is_applied_at_startup(ctx: &mut Context) -> Result<()> {
+        let node_dir = tempfile::tempdir().unwrap();
+
+        let mut setup = NodeSetup::default();
+        setup.services.push(ServiceSetup::new("uppercase", "up"));
+        setup.services.push(ServiceSetup::new("unknown", "nope"));
+        setup.listeners.push(ListenerSetup::new("setup_listener"));
+        let config = crate::nodes::config::NodeConfig::new(node_dir.path()).unwrap();
+        config.setup().set(setup).unwrap();
+
+        let route = NodeManager::test_create_in(ctx, node_dir.into_path()).await?;
+
+        // The setup is applied in the background.
+        let mut applied = None;
+        for _ in 0..50 {
+            let req = Request::get("/node/config").to_vec()?;
+            let res: Vec<u8> = ctx.send_and_receive(route.clone(), req).await?;
+            let mut dec = Decoder::new(&res);
+            let header: api::Response = dec.decode()?;
+            assert_eq!(header.status(), Some(Status::Ok));
+            let setup: NodeSetup = dec.decode()?;
+            if !setup.is_empty() {
+                applied = Some(setup);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let applied = applied.expect("the setup is applied");
+
+        // The unknown service is left out of the effective configuration.
+        assert_eq!(applied.services.len(), 1);
+        assert_eq!(applied.services[0].address, "up");
+        assert_eq!(applied.listeners.len(), 1);
+
+        let reply: String = ctx
+            .send_and_receive(route!["up"], "hello".to_string())
+            .await?;
+        assert_eq!(reply, "HELLO");
+
+        ctx.stop().await
+    }
+
+    async fn apply_setup(
+        ctx: &Context,
+        node: &ockam::Route,
+        setup: NodeSetup,
+        dry_run: bool,
+    ) -> Result<SetupChanges> {
+        let req = Request::post("/node/config")
+            .body(ApplySetup::new(setup, dry_run))
+            .to_vec()?;
+        let res: Vec<u8> = ctx.send_and_receive(node.clone(), req).await?;
+        body(&res)
+    }
+
+    async fn secure_channels(ctx: &Context, node: &ockam::Route) -> Result<Vec<String>> {
+        let req = Request::get("/node/secure_channel").to_vec()?;
+        let res: Vec<u8> = ctx.send_and_receive(node.clone(), req).await?;
+        body(&res)
+    }
+
+    #[ockam_macros::test]
+    async fn channels_are_opened_ahead_of_use(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+
+        let mut setup = NodeSetup::default();
+        setup.listeners.push(ListenerSetup::new("warm_listener"));
+        setup
+            .channels
+            .push(ChannelSetup::new("/secure/warm_listener"));
+        let changes = apply_setup(ctx, &node, setup, false).await?;
+        assert_eq!(changes.created.len(), 2);
+        assert!(changes.failed.is_empty());
+        let warm = secure_channels(ctx, &node).await?;
+        assert_eq!(warm.len(), 1);
+
+        // Connecting to the destination reuses the channel
+        let to: MultiAddr = "/secure/warm_listener".parse().unwrap();
+        let req = Request::post("/node/secure_channel")
+            .body(CreateSecureChannelRequest::new(
+                &to,
+                None,
+                CredentialExchangeMode::None,
+            ))
+            .to_vec()?;
+        let res: Vec<u8> = ctx.send_and_receive(node.clone(), req).await?;
+        let created: CreateSecureChannelResponse = body(&res)?;
+        assert_eq!(created.addr.to_string(), warm[0]);
+
+        let changes = apply_setup(ctx, &node, NodeSetup::default(), false).await?;
+        assert_eq!(changes.deleted.len(), 2);
+        assert!(secure_channels(ctx, &node).await?.is_empty());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn running_nodes_converge_to_a_setup(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+
+        let mut first = NodeSetup::default();
+        first.services.push(ServiceSetup::new("uppercase", "up"));
+        first.services.push(ServiceSetup::new("echo", "echo_kept"));
+        let changes = apply_setup(ctx, &node, first.clone(), false).await?;
+        assert_eq!(changes.created.len(), 2);
+        assert!(changes.deleted.is_empty() && changes.failed.is_empty());
+
+        // Applying the same setup again changes nothing
+        let changes = apply_setup(ctx, &node, first.clone(), false).await?;
+        assert!(changes.is_empty());
+
+        let mut second = NodeSetup::default();
+        second.services.push(ServiceSetup::new("uppercase", "up2"));
+        second.services.push(ServiceSetup::new("echo", "echo_kept"));
+
+        // A dry run only reports the changes
+        let changes = apply_setup(ctx, &node, second.clone(), true).await?;
+        assert_eq!(changes.created, vec!["uppercase service at up2"]);
+        assert_eq!(changes.deleted, vec!["uppercase service at up"]);
+        let req = Request::get("/node/config").to_vec()?;
+        let res: Vec<u8> = ctx.send_and_receive(node.clone(), req).await?;
+        assert_eq!(body::<NodeSetup>(&res)?, first);
+
+        let changes = apply_setup(ctx, &node, second.clone(), false).await?;
+        assert_eq!(changes.created, vec!["uppercase service at up2"]);
+        assert_eq!(changes.deleted, vec!["uppercase service at up"]);
+        let req = Request::get("/node/config").to_vec()?;
+        let res: Vec<u8> = ctx.send_and_receive(node.clone(), req).await?;
+        let mut applied = body::<NodeSetup>(&res)?;
+        applied.services.sort_by(|a, b| a.address.cmp(&b.address));
+        assert_eq!(
+            applied.services,
+            vec![second.services[1].clone(), second.services[0].clone()]
+        );
+
+        let reply: String = ctx
+            .send_and_receive(route!["up2"], "hello".to_string())
+            .await?;
+        assert_eq!(reply, "HELLO");
+
+        ctx.stop().await
+    }
+
+    #[test]
+    fn unknown_setup_fields_are_rejected() {
+        let setup = r#"{"services": [{"kind": "echo", "address": "echo"}]}"#;
+        assert!(serde_json::from_str::<NodeSetup>(setup).is_ok());
+        let typo = r#"{"services": [{"kind": "echo", "adress": "echo"}]}"#;
+        assert!(serde_json::from_str::<NodeSetup>(typo).is_err());
+        let typo = r#"{"service": [{"kind": "echo", "address": "echo"}]}"#;
+        assert!(serde_json::from_str::<NodeSetup>(typo).is_err());
+    }
+}
@@ -4,8 +4,13 @@ use crate::multiaddr_to_route;
 use crate::nodes::models::credentials::{GetCredentialRequest, PresentCredentialRequest};
 use crate::nodes::service::map_multiaddr_err;
 use crate::nodes::NodeManager;
+use crate::verifier::types::{VerifyRequest, VerifyResponse};
+use crate::verifier::verify_credential;
 use crate::DefaultAddress;
+use either::Either;
 use minicbor::Decoder;
+use ockam::identity::credential::Credential;
+use ockam::vault::Vault;
 use ockam::Result;
 use ockam_core::api::{Request, Response, ResponseBuilder};
 use ockam_core::{route, AsyncTryClone};
@@ -115,4 +120,29 @@ impl NodeManagerWorker {
         let response = Response::ok(req.id());
         Ok(response)
     }
+
+    /// Verify a credential against the authority identities given in the request,
+    /// without contacting the authority.
+    pub(super) async fn verify_credential(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let request: VerifyRequest = dec.decode()?;
+        let credential: Credential = minicbor::decode(request.credential())?;
+
+        // Only public keys are needed, so the node's vault is not involved
+        let vault = Vault::create();
+        let res =
+            match verify_credential(&vault, req.id(), req.path(), &request, &credential).await? {
+                Either::Left(err) => err.to_vec()?,
+                Either::Right(data) => {
+                    let exp = data.expires_at();
+                    Response::ok(req.id())
+                        .body(VerifyResponse::new(data.into_attributes(), exp))
+                        .to_vec()?
+                }
+            };
+        Ok(res)
+    }
 }
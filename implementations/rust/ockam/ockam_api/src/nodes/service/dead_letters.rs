@@ -0,0 +1,16 @@
+use super::NodeManagerWorker;
+use ockam::Result;
+use ockam_core::api::{Request, Response};
+
+impl NodeManagerWorker {
+    pub(super) async fn list_dead_letters(&self, req: &Request<'_>) -> Result<Vec<u8>> {
+        let node_manager = self.node_manager.read().await;
+        let res = match &node_manager.dead_letters {
+            Some(letters) => Response::ok(req.id()).body(letters.list()).to_vec()?,
+            None => Response::not_found(req.id())
+                .body("The dead letter service is not started")
+                .to_vec()?,
+        };
+        Ok(res)
+    }
+}
@@ -0,0 +1,45 @@
+use super::NodeManagerWorker;
+use crate::error::ApiError;
+use crate::file_transfer::Client;
+use crate::multiaddr_to_route;
+use crate::nodes::models::file_transfer::{FileSent, SendFile};
+use minicbor::Decoder;
+use ockam::{Context, Result};
+use ockam_core::api::{Request, Response, ResponseBuilder};
+use ockam_multiaddr::MultiAddr;
+use std::path::Path;
+use std::str::FromStr;
+
+impl NodeManagerWorker {
+    /// Send a file to the file transfer service of another node.
+    ///
+    /// The node manager is not locked during the transfer.
+    pub(super) async fn send_file(
+        &mut self,
+        ctx: &Context,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<FileSent>> {
+        let body: SendFile = dec.decode()?;
+        let addr =
+            MultiAddr::from_str(body.to()).map_err(|_| ApiError::generic("Invalid route"))?;
+        let route = multiaddr_to_route(&addr).ok_or_else(|| ApiError::generic("Invalid route"))?;
+        let path = Path::new(body.path());
+        let name = match body.name() {
+            Some(name) => name,
+            None => path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .ok_or_else(|| ApiError::generic("Invalid file path"))?,
+        };
+        let size = std::fs::metadata(path)
+            .map_err(|_| ApiError::generic("Invalid file path"))?
+            .len();
+
+        let mut client = Client::new(route, ctx).await?;
+        let resumed_from = client.send_file(path, name).await?;
+        info!(path = %body.path(), to = %body.to(), %size, %resumed_from, "file sent");
+
+        Ok(Response::ok(req.id()).body(FileSent::new(size, resumed_from)))
+    }
+}
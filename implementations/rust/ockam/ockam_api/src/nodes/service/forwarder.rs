@@ -3,9 +3,10 @@ use std::sync::Arc;
 use minicbor::Decoder;
 
 use ockam::compat::asynchronous::RwLock;
-use ockam::remote::RemoteForwarder;
+use ockam::remote::{RegistrationExpiry, RemoteForwarder, RemoteForwarderOptions};
 use ockam::Result;
-use ockam_core::api::{Id, Response, Status};
+use ockam_core::api::{Id, Request, Response, Status};
+use ockam_core::errcode::Kind;
 use ockam_core::AsyncTryClone;
 use ockam_identity::IdentityIdentifier;
 use ockam_multiaddr::MultiAddr;
@@ -14,8 +15,9 @@ use ockam_node::Context;
 
 use crate::error::ApiError;
 use crate::nodes::models::forwarder::{CreateForwarder, ForwarderInfo};
+use crate::nodes::registry::ForwarderRegistryInfo;
 use crate::session::util;
-use crate::session::{Replacer, Session};
+use crate::session::{Probe, Replacer, Session};
 use crate::{multiaddr_to_route, try_multiaddr_to_addr};
 
 use super::{NodeManager, NodeManagerWorker};
@@ -33,6 +35,19 @@ impl NodeManagerWorker {
 
         debug!(addr = %req.address(), alias = ?req.alias(), "Handling CreateForwarder request");
 
+        let options =
+            req.takeover().is_some() || req.heartbeat_interval().is_some() || req.ttl().is_some();
+        if options && !(req.at_rust_node() && req.alias().is_some()) {
+            let msg = "takeover, heartbeats and TTLs are only supported for aliases at rust nodes";
+            return Ok(Response::bad_request(rid).body(msg).to_vec()?);
+        }
+        if let Some(interval) = req.heartbeat_interval() {
+            if interval.is_zero() || req.ttl().map_or(false, |ttl| interval >= ttl) {
+                let msg = "the heartbeat interval must be positive and shorter than the TTL";
+                return Ok(Response::bad_request(rid).body(msg).to_vec()?);
+            }
+        }
+
         let (sec_chan, suffix) = node_manager
             .connect(req.address(), req.authorized(), None)
             .await?;
@@ -41,9 +56,20 @@ impl NodeManagerWorker {
         let route = multiaddr_to_route(&full)
             .ok_or_else(|| ApiError::message("invalid address: {addr}"))?;
 
+        let mut expiry = None;
         let forwarder = if req.at_rust_node() {
             if let Some(alias) = req.alias() {
-                RemoteForwarder::create_static_without_heartbeats(ctx, route, alias).await
+                let mut options =
+                    RemoteForwarderOptions::new().with_takeover(req.takeover().unwrap_or_default());
+                if let Some(interval) = req.heartbeat_interval() {
+                    options = options.with_heartbeat_interval(interval)
+                }
+                if let Some(ttl) = req.ttl() {
+                    let e = RegistrationExpiry::new();
+                    options = options.with_ttl(ttl).with_expiry(e.clone());
+                    expiry = Some(e)
+                }
+                RemoteForwarder::create_static_with_options(ctx, route, alias, options).await
             } else {
                 RemoteForwarder::create(ctx, route).await
             }
@@ -64,6 +90,9 @@ impl NodeManagerWorker {
                 );
                 let mut s = Session::new(sec_chan);
                 s.set_replacer(repl);
+                if let Some(service) = req.probe_service() {
+                    s.set_probe(Probe::Service(service.to_string()))
+                }
                 node_manager.sessions.lock().unwrap().add(s);
             }
             f
@@ -71,7 +100,15 @@ impl NodeManagerWorker {
 
         match forwarder {
             Ok(info) => {
-                let b = ForwarderInfo::from(info);
+                let registered = ForwarderRegistryInfo::new(info.clone(), expiry);
+                let mut b = ForwarderInfo::from(info);
+                if let Some(ttl) = registered.remaining_ttl() {
+                    b = b.with_remaining_ttl(ttl)
+                }
+                node_manager
+                    .registry
+                    .forwarders
+                    .insert(b.remote_address().to_string(), registered);
                 debug!(
                     forwarding_route = %b.forwarding_route(),
                     remote_address = %b.remote_address(),
@@ -79,6 +116,15 @@ impl NodeManagerWorker {
                 );
                 Ok(Response::ok(rid).body(b).to_vec()?)
             }
+            Err(err) if err.code().kind == Kind::AlreadyExists => {
+                warn!(alias = ?req.alias(), "Forwarder alias is taken");
+                Ok(Response::builder(rid, Status::Conflict)
+                    .body(format!(
+                        "the alias {} is taken",
+                        req.alias().unwrap_or_default()
+                    ))
+                    .to_vec()?)
+            }
             Err(err) => {
                 error!(?err, "Failed to create forwarder");
                 Ok(Response::builder(rid, Status::InternalServerError)
@@ -89,6 +135,34 @@ impl NodeManagerWorker {
     }
 }
 
+impl NodeManagerWorker {
+    pub(super) async fn show_forwarder(
+        &mut self,
+        ctx: &Context,
+        req: &Request<'_>,
+        remote_address: &str,
+    ) -> Result<Vec<u8>> {
+        let mut node_manager = self.node_manager.write().await;
+        let running = match node_manager.registry.forwarders.get(remote_address) {
+            Some(f) => ctx.list_workers().await?.contains(f.info.worker_address()),
+            None => false,
+        };
+        if !running {
+            // Forwarders stop when their alias is taken over.
+            node_manager.registry.forwarders.remove(remote_address);
+            return Ok(Response::not_found(req.id())
+                .body(format!("no forwarder for {remote_address}"))
+                .to_vec()?);
+        }
+        let registered = &node_manager.registry.forwarders[remote_address];
+        let mut b = ForwarderInfo::from(registered.info.clone());
+        if let Some(ttl) = registered.remaining_ttl() {
+            b = b.with_remaining_ttl(ttl)
+        }
+        Ok(Response::ok(req.id()).body(b).to_vec()?)
+    }
+}
+
 /// Create a session replacer.
 ///
 /// This returns a function that accepts the previous ping address (e.g.
@@ -114,15 +188,18 @@ fn replacer(
                 let mut this = manager.write().await;
                 let _ = this.delete_secure_channel(&prev).await;
                 let timeout = Some(util::MAX_CONNECT_TIME);
-                let (sec, rest) = this.connect(&addr, auth, timeout).await?;
+                let (sec, rest) = this.reconnect(&addr, auth, timeout).await?;
                 let a = sec.clone().try_with(&rest)?;
                 let r = multiaddr_to_route(&a)
                     .ok_or_else(|| ApiError::message(format!("invalid multiaddr: {a}")))?;
-                if let Some(alias) = &alias {
-                    RemoteForwarder::create_static(&ctx, r, alias).await?;
+                let info = if let Some(alias) = &alias {
+                    RemoteForwarder::create_static(&ctx, r, alias).await?
                 } else {
-                    RemoteForwarder::create(&ctx, r).await?;
-                }
+                    RemoteForwarder::create(&ctx, r).await?
+                };
+                let remote_address = info.remote_address().to_string();
+                let registered = ForwarderRegistryInfo::new(info, None);
+                this.registry.forwarders.insert(remote_address, registered);
                 Ok(sec)
             };
             match timeout(util::MAX_RECOVERY_TIME, f).await {
@@ -0,0 +1,95 @@
+use ockam_core::api::Reassembly;
+use ockam_core::Route;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::TARGET;
+
+/// Largest number of requests being reassembled at the same time.
+const MAX_REASSEMBLIES: usize = 64;
+
+/// How long the fragments of a request have to arrive.
+const MAX_REASSEMBLY_TIME: Duration = Duration::from_secs(60);
+
+/// Requests received in fragments, by the route of their requester.
+///
+/// Requesters send the fragments of one request at a time, from the address
+/// the response goes to.
+#[derive(Clone, Default)]
+pub(super) struct PendingFragments {
+    requests: Arc<Mutex<BTreeMap<Route, (Instant, Reassembly)>>>,
+}
+
+impl PendingFragments {
+    /// Add a fragment of a request sent along `return_route`, returning the
+    /// request once all its fragments arrived.
+    ///
+    /// Invalid fragments, and fragments of requests which take too long to
+    /// arrive, are dropped.
+    pub(super) fn push(&self, return_route: &Route, data: &[u8]) -> Option<Vec<u8>> {
+        let now = Instant::now();
+        let mut requests = self.requests.lock().unwrap();
+        requests.retain(|_, (started, _)| now.duration_since(*started) < MAX_REASSEMBLY_TIME);
+        if !requests.contains_key(return_route) && requests.len() >= MAX_REASSEMBLIES {
+            warn!(target: TARGET, %return_route, "too many fragmented requests");
+            return None;
+        }
+        let (_, reassembly) = requests
+            .entry(return_route.clone())
+            .or_insert_with(|| (now, Reassembly::new()));
+        match reassembly.push(data) {
+            Ok(None) => None,
+            Ok(Some(req)) => {
+                requests.remove(return_route);
+                Some(req)
+            }
+            Err(err) => {
+                warn!(target: TARGET, %return_route, %err, "invalid request fragment");
+                requests.remove(return_route);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::api::{self, Id};
+    use ockam_core::route;
+
+    #[test]
+    fn requests_are_reassembled_per_requester() {
+        let pending = PendingFragments::default();
+        let data: Vec<u8> = (0..3000).map(|i| i as u8).collect();
+        let other: Vec<u8> = (0..3000).map(|i| (i * 7) as u8).collect();
+        let size = api::FRAGMENT_OVERHEAD + 1000;
+        let fragments = api::fragment(Id::fresh(), &data, size).unwrap();
+        let others = api::fragment(Id::fresh(), &other, size).unwrap();
+
+        let (last, rest) = fragments.split_last().unwrap();
+        let (other_last, other_rest) = others.split_last().unwrap();
+        for (f, o) in rest.iter().zip(other_rest) {
+            assert!(pending.push(&route!["app.1"], f).is_none());
+            assert!(pending.push(&route!["app.2"], o).is_none());
+        }
+        assert_eq!(pending.push(&route!["app.1"], last), Some(data));
+        assert_eq!(pending.push(&route!["app.2"], other_last), Some(other));
+    }
+
+    #[test]
+    fn invalid_fragments_are_dropped() {
+        let pending = PendingFragments::default();
+        let data = [0; 3000];
+        let size = api::FRAGMENT_OVERHEAD + 1000;
+        let fragments = api::fragment(Id::fresh(), &data, size).unwrap();
+        let others = api::fragment(Id::fresh(), &data, size).unwrap();
+
+        assert!(pending.push(&route!["app"], &fragments[0]).is_none());
+        // A fragment of another request discards the first one
+        assert!(pending.push(&route!["app"], &others[1]).is_none());
+        assert!(pending.push(&route!["app"], &fragments[1]).is_none());
+        assert!(pending.push(&route!["app"], &fragments[2]).is_none());
+    }
+}
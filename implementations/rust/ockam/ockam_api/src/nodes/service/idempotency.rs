@@ -0,0 +1,164 @@
+use minicbor::Decoder;
+use ockam_core::api::{Method, Request, Response, Status};
+use ockam_node::tokio::sync::watch;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How long responses are kept to be replayed.
+const RETENTION: Duration = Duration::from_secs(10 * 60);
+
+/// How many responses are kept to be replayed.
+const CAPACITY: usize = 1024;
+
+/// Endpoints creating resources, whose requests may carry an idempotency key.
+const MUTATIONS: &[&str] = &[
+    "/node/secure_channel",
+    "/node/forwarder",
+    "/node/inlet",
+    "/node/outlet",
+];
+
+/// Successful responses to mutations, by request path and idempotency key.
+///
+/// A retried request with the same key gets the original response again
+/// instead of creating another resource. Keys are reserved while the
+/// original request is handled, so that a retry arriving in the meantime
+/// waits for its response instead of being handled as well.
+#[derive(Debug, Default)]
+pub(crate) struct IdempotentResponses {
+    responses: HashMap<(String, String), (Instant, Vec<u8>)>,
+    pending: HashMap<(String, String), watch::Receiver<Option<Vec<u8>>>>,
+}
+
+/// What to do with a request carrying an idempotency key.
+pub(crate) enum Idempotency {
+    /// Send this response to a previous request with the same key.
+    Replay(Vec<u8>),
+    /// Wait for the response to a request with the same key, being handled.
+    Wait(watch::Receiver<Option<Vec<u8>>>),
+    /// Handle the request, then [`IdempotentResponses::finish`] it.
+    Handle(watch::Sender<Option<Vec<u8>>>),
+}
+
+impl IdempotentResponses {
+    /// The key under which the response to `req` is kept, if any.
+    pub(crate) fn key(req: &Request<'_>) -> Option<(String, String)> {
+        let key = req.idempotency_key()?;
+        if req.method() == Some(Method::Post) && MUTATIONS.contains(&req.path()) {
+            Some((req.path().to_string(), key.to_string()))
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn get(&mut self, key: &(String, String)) -> Option<&[u8]> {
+        self.responses.retain(|_, (t, _)| t.elapsed() < RETENTION);
+        self.responses.get(key).map(|(_, r)| r.as_slice())
+    }
+
+    /// Replay the response kept under `key`, wait for the request being
+    /// handled with that key, or else reserve the key.
+    ///
+    /// A reservation whose request was dropped without a response is taken
+    /// over.
+    pub(crate) fn begin(&mut self, key: &(String, String)) -> Idempotency {
+        if let Some(r) = self.get(key) {
+            return Idempotency::Replay(r.to_vec());
+        }
+        if let Some(rx) = self.pending.get(key) {
+            if rx.has_changed().is_ok() {
+                return Idempotency::Wait(rx.clone());
+            }
+        }
+        let (tx, rx) = watch::channel(None);
+        self.pending.insert(key.clone(), rx);
+        Idempotency::Handle(tx)
+    }
+
+    /// Release the reservation of `key`, keeping `response` if it is
+    /// successful, and pass it on to the requests waiting for it.
+    pub(crate) fn finish(
+        &mut self,
+        key: (String, String),
+        tx: watch::Sender<Option<Vec<u8>>>,
+        response: &[u8],
+    ) {
+        self.pending.remove(&key);
+        self.insert(key, response);
+        let _ = tx.send(Some(response.to_vec()));
+    }
+
+    /// Keep `response` if it is successful.
+    pub(crate) fn insert(&mut self, key: (String, String), response: &[u8]) {
+        let ok = Decoder::new(response)
+            .decode::<Response>()
+            .map(|r| r.status() == Some(Status::Ok))
+            .unwrap_or(false);
+        if !ok {
+            return;
+        }
+        if self.responses.len() >= CAPACITY {
+            let oldest = self
+                .responses
+                .iter()
+                .min_by_key(|(_, (t, _))| *t)
+                .map(|(k, _)| k.clone());
+            if let Some(k) = oldest {
+                self.responses.remove(&k);
+            }
+        }
+        self.responses
+            .insert(key, (Instant::now(), response.to_vec()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_successful_mutations() {
+        let req = Request::post("/node/inlet").idempotency_key("k1");
+        let key = IdempotentResponses::key(req.header()).unwrap();
+        let mut responses = IdempotentResponses::default();
+
+        let failed = Response::bad_request(req.header().id()).to_vec().unwrap();
+        responses.insert(key.clone(), &failed);
+        assert!(responses.get(&key).is_none());
+
+        let ok = Response::ok(req.header().id()).to_vec().unwrap();
+        responses.insert(key.clone(), &ok);
+        assert_eq!(Some(ok.as_slice()), responses.get(&key));
+
+        let req = Request::get("/node/inlet").idempotency_key("k1");
+        assert!(IdempotentResponses::key(req.header()).is_none());
+    }
+
+    #[test]
+    fn retries_wait_for_the_original_request() {
+        let req = Request::post("/node/outlet").idempotency_key("k2");
+        let key = IdempotentResponses::key(req.header()).unwrap();
+        let mut responses = IdempotentResponses::default();
+
+        let tx = match responses.begin(&key) {
+            Idempotency::Handle(tx) => tx,
+            _ => panic!("the first request should be handled"),
+        };
+        let rx = match responses.begin(&key) {
+            Idempotency::Wait(rx) => rx,
+            _ => panic!("a retry should wait for the first request"),
+        };
+
+        let failed = Response::bad_request(req.header().id()).to_vec().unwrap();
+        responses.finish(key.clone(), tx, &failed);
+        assert_eq!(rx.borrow().as_deref(), Some(failed.as_slice()));
+
+        // Failed requests can be retried, and dropped reservations are taken over
+        let tx = match responses.begin(&key) {
+            Idempotency::Handle(tx) => tx,
+            _ => panic!("a failed request should be handled again"),
+        };
+        drop(tx);
+        assert!(matches!(responses.begin(&key), Idempotency::Handle(_)));
+    }
+}
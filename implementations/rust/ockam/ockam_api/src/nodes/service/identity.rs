@@ -1,18 +1,24 @@
 use super::{map_anyhow_err, NodeManagerWorker};
 use crate::nodes::models::identity::{
-    CreateIdentityResponse, LongIdentityResponse, ShortIdentityResponse,
+    CreateIdentityRequest, CreateIdentityResponse, IdentityCertificateResponse,
+    IdentityCsrResponse, IdentityKeyBackend, ImportIdentityCertificateRequest,
+    LongIdentityResponse, ShortIdentityResponse,
 };
 use crate::nodes::NodeManager;
+use crate::x509;
+use minicbor::Decoder;
 use ockam::identity::{Identity, IdentityIdentifier};
 use ockam::{Context, Result};
 use ockam_core::api::{Request, Response, ResponseBuilder};
 use ockam_core::errcode::{Kind, Origin};
+use ockam_vault::{KeyId, Vault};
 
 impl NodeManager {
     pub(super) async fn create_identity_impl(
         &mut self,
         ctx: &Context,
         reuse_if_exists: bool,
+        key_backend: IdentityKeyBackend,
     ) -> Result<IdentityIdentifier> {
         if let Some(identity) = &self.identity {
             return if reuse_if_exists {
@@ -29,7 +35,13 @@ impl NodeManager {
 
         let vault = self.vault()?;
 
-        let identity = Identity::create(ctx, vault).await?;
+        let identity = match key_backend {
+            IdentityKeyBackend::Software => Identity::create(ctx, vault).await?,
+            _ => {
+                let root_key = key_store_root_key(vault, key_backend).await?;
+                Identity::create_with_root_key(ctx, vault, &root_key).await?
+            }
+        };
         let identifier = identity.identifier().clone();
         let exported_identity = identity.export().await?;
 
@@ -43,14 +55,59 @@ impl NodeManager {
     }
 }
 
+/// Generate a root key in the key store of the given backend
+#[cfg_attr(
+    not(any(feature = "aws-kms", feature = "pkcs11")),
+    allow(unused_variables)
+)]
+async fn key_store_root_key(vault: &Vault, key_backend: IdentityKeyBackend) -> Result<KeyId> {
+    #[cfg(any(feature = "aws-kms", feature = "pkcs11"))]
+    use ockam_core::vault::{
+        SecretAttributes, SecretPersistence, SecretType, NIST_P256_SECRET_LENGTH_U32,
+    };
+    #[cfg(any(feature = "aws-kms", feature = "pkcs11"))]
+    let attributes = SecretAttributes::new(
+        SecretType::NistP256,
+        SecretPersistence::Persistent,
+        NIST_P256_SECRET_LENGTH_U32,
+    );
+    match key_backend {
+        #[cfg(feature = "aws-kms")]
+        IdentityKeyBackend::AwsKms => {
+            vault
+                .key_store_secret_generate(ockam_vault::AwsKms::SCHEME, attributes)
+                .await
+        }
+        #[cfg(feature = "pkcs11")]
+        IdentityKeyBackend::Pkcs11 => {
+            vault
+                .key_store_secret_generate(ockam_vault::Pkcs11::SCHEME, attributes)
+                .await
+        }
+        _ => Err(ockam_core::Error::new(
+            Origin::Application,
+            Kind::Unsupported,
+            format!("Node was built without the {:?} key backend", key_backend),
+        )),
+    }
+}
+
 impl NodeManagerWorker {
     pub(super) async fn create_identity(
         &mut self,
         ctx: &Context,
         req: &Request<'_>,
+        dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder<CreateIdentityResponse<'_>>> {
+        let key_backend = if req.has_body() {
+            dec.decode::<CreateIdentityRequest>()?.key_backend
+        } else {
+            IdentityKeyBackend::Software
+        };
         let mut node_manager = self.node_manager.write().await;
-        let identifier = node_manager.create_identity_impl(ctx, false).await?;
+        let identifier = node_manager
+            .create_identity_impl(ctx, false, key_backend)
+            .await?;
 
         let response =
             Response::ok(req.id()).body(CreateIdentityResponse::new(identifier.to_string()));
@@ -81,4 +138,61 @@ impl NodeManagerWorker {
             Response::ok(req.id()).body(ShortIdentityResponse::new(identifier.to_string()));
         Ok(response)
     }
+
+    pub(super) async fn identity_csr(
+        &mut self,
+        req: &Request<'_>,
+    ) -> Result<ResponseBuilder<IdentityCsrResponse<'_>>> {
+        let node_manager = self.node_manager.read().await;
+        let csr = x509::create_csr(node_manager.identity()?).await?;
+
+        let response = Response::ok(req.id()).body(IdentityCsrResponse::new(csr));
+        Ok(response)
+    }
+
+    /// Store a certificate binding the node's identity, once verified
+    /// against the issuing CA certificate
+    pub(super) async fn import_identity_certificate(
+        &mut self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: ImportIdentityCertificateRequest = dec.decode()?;
+        let node_manager = self.node_manager.read().await;
+        let identity = node_manager.identity()?;
+        if let Err(e) = x509::verify_certificate(
+            identity.vault(),
+            &body.certificate,
+            &body.ca_certificate,
+            &identity.to_public().await?,
+        )
+        .await
+        {
+            return Ok(Response::bad_request(req.id())
+                .body(format!("invalid certificate: {e}"))
+                .to_vec()?);
+        }
+
+        let state = node_manager.config.state();
+        state.write().identity_certificate = Some(body.certificate.to_vec());
+        state.persist_config_updates().map_err(map_anyhow_err)?;
+
+        Ok(Response::ok(req.id()).to_vec()?)
+    }
+
+    pub(super) async fn identity_certificate(&mut self, req: &Request<'_>) -> Result<Vec<u8>> {
+        let node_manager = self.node_manager.read().await;
+        let certificate = node_manager
+            .config
+            .state()
+            .read()
+            .identity_certificate
+            .clone();
+        match certificate {
+            Some(c) => Ok(Response::ok(req.id())
+                .body(IdentityCertificateResponse::new(c))
+                .to_vec()?),
+            None => Ok(Response::not_found(req.id()).to_vec()?),
+        }
+    }
 }
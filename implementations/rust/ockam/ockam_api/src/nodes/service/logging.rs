@@ -0,0 +1,57 @@
+use minicbor::Decoder;
+use ockam_core::api::{self, Request, Response, ResponseBuilder};
+use ockam_core::Result;
+use tracing::info;
+
+use super::NodeManagerWorker;
+use crate::nodes::models::logging::LogFilterBody;
+
+/// The tracing filter of the process running a node.
+///
+/// The node manager does not install any tracing subscriber itself, so the
+/// process which does hands it over with
+/// [`NodeManagerGeneralOptions::with_log_filter`](super::NodeManagerGeneralOptions::with_log_filter).
+pub trait LogFilter: Send + Sync + 'static {
+    /// The current filter, in the syntax of `OCKAM_LOG`.
+    fn get(&self) -> String;
+
+    /// Replace the filter, e.g. with `info,ockam_api::session=trace`.
+    fn set(&self, filter: &str) -> Result<()>;
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn get_log_filter(&self, req: &Request<'_>) -> Result<Vec<u8>> {
+        let node_manager = self.node_manager.read().await;
+        match &node_manager.log_filter {
+            Some(f) => Ok(Response::ok(req.id())
+                .body(LogFilterBody::new(f.get()))
+                .to_vec()?),
+            None => Ok(logging_disabled(req).to_vec()?),
+        }
+    }
+
+    pub(super) async fn set_log_filter(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: LogFilterBody = dec.decode()?;
+        let node_manager = self.node_manager.read().await;
+        let log_filter = match &node_manager.log_filter {
+            Some(f) => f,
+            None => return Ok(logging_disabled(req).to_vec()?),
+        };
+        if let Err(e) = log_filter.set(body.filter()) {
+            let msg = format!("Invalid log filter: {e}");
+            return Ok(api::bad_request(req, &msg).to_vec()?);
+        }
+        info!(filter = %body.filter(), "log filter changed");
+        Ok(Response::ok(req.id()).to_vec()?)
+    }
+}
+
+fn logging_disabled<'a>(req: &'a Request) -> ResponseBuilder<api::Error<'a>> {
+    let error = api::Error::new(req.path())
+        .with_message("Logging is disabled on this node, start it with --verbose or OCKAM_LOG");
+    Response::not_implemented(req.id()).body(error)
+}
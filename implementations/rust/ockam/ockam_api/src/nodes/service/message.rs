@@ -1,4 +1,5 @@
 use std::str::FromStr;
+use std::time::Duration;
 
 use minicbor::{Decode, Encode};
 
@@ -19,6 +20,11 @@ pub struct SendMessage<'a> {
     #[n(0)] pub tag: TypeTag<8400702>,
     #[b(1)] pub route: CowStr<'a>,
     #[b(2)] pub message: CowBytes<'a>,
+    /// How long to wait for the reply, or the acknowledgement
+    #[n(3)] pub timeout_ms: Option<u64>,
+    /// Wait for an acknowledgement of the ack service before the last hop,
+    /// rather than for a reply
+    #[n(4)] pub ack: Option<bool>,
 }
 
 impl<'a> SendMessage<'a> {
@@ -28,9 +34,31 @@ impl<'a> SendMessage<'a> {
             tag: TypeTag,
             route: route.to_string().into(),
             message: message.into(),
+            timeout_ms: None,
+            ack: None,
         }
     }
 
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    pub fn with_ack(mut self, ack: bool) -> Self {
+        self.ack = Some(ack);
+        self
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or_else(|| Duration::from_secs(ockam_node::DEFAULT_TIMEOUT))
+    }
+
+    pub fn ack(&self) -> bool {
+        self.ack.unwrap_or(false)
+    }
+
     pub fn route(&self) -> Result<Route> {
         let maddr = MultiAddr::from_str(self.route.as_ref())
             .map_err(|_err| ApiError::generic(&format!("Invalid route: {}", self.route)))?;
@@ -39,15 +67,59 @@ impl<'a> SendMessage<'a> {
     }
 }
 
+/// Why a message could not be sent
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+#[serde(rename_all = "snake_case")]
+pub enum SendMessageFailure {
+    /// The route is invalid or leads nowhere
+    #[n(0)] Route,
+    /// No reply, or acknowledgement, arrived in time
+    #[n(1)] Timeout,
+    /// The ack service could not hand the message over to the last hop
+    #[n(2)] NotDelivered,
+    /// Any other failure
+    #[n(3)] Other,
+}
+
+/// Response body when a message could not be sent
+#[derive(Debug, Clone, Decode, Encode, serde::Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SendMessageError<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<3310570>,
+    #[n(1)] pub failure: SendMessageFailure,
+    #[b(2)] pub message: CowStr<'a>,
+}
+
+impl<'a> SendMessageError<'a> {
+    pub fn new(failure: SendMessageFailure, message: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            failure,
+            message: message.into(),
+        }
+    }
+}
+
 mod node {
     use minicbor::Decoder;
+    use std::time::Instant;
     use tracing::trace;
 
     use ockam_core::api::{Request, Response, Status};
-    use ockam_core::{self, Result};
+    use ockam_core::errcode::Kind;
+    use ockam_core::{self, Address, Any, CowBytes, Decodable, Result, Route};
     use ockam_node::Context;
 
+    use super::{SendMessage, SendMessageError, SendMessageFailure};
+    use crate::ack::Acknowledgement;
     use crate::nodes::NodeManagerWorker;
+    use crate::DefaultAddress;
 
     const TARGET: &str = "ockam_api::message";
 
@@ -58,23 +130,111 @@ mod node {
             req: &Request<'_>,
             dec: &mut Decoder<'_>,
         ) -> Result<Vec<u8>> {
-            let req_body: super::SendMessage = dec.decode()?;
-            let route = req_body.route()?;
+            let req_body: SendMessage = dec.decode()?;
+            let route = match req_body.route() {
+                Ok(route) => route,
+                Err(err) => {
+                    let body = SendMessageError::new(SendMessageFailure::Route, err.to_string());
+                    return Ok(Response::bad_request(req.id()).body(body).to_vec()?);
+                }
+            };
             let msg = req_body.message.to_vec();
             let msg_length = msg.len();
 
-            trace!(target: TARGET, route = %req_body.route, msg_l = %msg_length, "sending message");
+            trace!(target: TARGET, route = %req_body.route, msg_l = %msg_length, ack = req_body.ack(), "sending message");
 
-            let res: Result<Vec<u8>> = ctx.send_and_receive(route, msg).await;
+            let res = if req_body.ack() {
+                send_with_ack(ctx, route, msg, &req_body).await
+            } else {
+                send_and_receive(ctx, route, msg, &req_body).await
+            };
             match res {
-                Ok(r) => Ok(Response::builder(req.id(), Status::Ok).body(r).to_vec()?),
+                // The reply is sent as a CBOR byte string. Older nodes sent
+                // an array of integers, `ockam message send` accepts both.
+                Ok(r) => Ok(Response::builder(req.id(), Status::Ok)
+                    .body(CowBytes::from(r))
+                    .to_vec()?),
                 Err(err) => {
                     error!(target: TARGET, ?err, "Failed to send message");
                     Ok(Response::builder(req.id(), Status::InternalServerError)
-                        .body(err.to_string())
+                        .body(err)
                         .to_vec()?)
                 }
             }
         }
     }
+
+    async fn send_and_receive(
+        ctx: &Context,
+        route: Route,
+        msg: Vec<u8>,
+        req: &SendMessage<'_>,
+    ) -> Result<Vec<u8>, SendMessageError<'static>> {
+        let mut child = ctx
+            .new_detached(Address::random_local())
+            .await
+            .map_err(failure)?;
+        child.send(route, msg).await.map_err(failure)?;
+        let reply = child
+            .receive_duration_timeout::<Vec<u8>>(req.timeout())
+            .await
+            .map_err(failure)?;
+        Ok(reply.take().body())
+    }
+
+    /// Send the message through the ack service of the node of the last
+    /// hop, and wait for its acknowledgement rather than for a reply
+    async fn send_with_ack(
+        ctx: &Context,
+        route: Route,
+        msg: Vec<u8>,
+        req: &SendMessage<'_>,
+    ) -> Result<Vec<u8>, SendMessageError<'static>> {
+        let ack_addr = Address::from_string(DefaultAddress::ACK_SERVICE);
+        let mut hops: Vec<Address> = route.iter().cloned().collect();
+        let last = hops.pop().ok_or_else(|| {
+            SendMessageError::new(SendMessageFailure::Route, "The route is empty")
+        })?;
+        hops.push(ack_addr.clone());
+        hops.push(last);
+
+        let mut child = ctx
+            .new_detached(Address::random_local())
+            .await
+            .map_err(failure)?;
+        child
+            .send(Route::create(hops), msg)
+            .await
+            .map_err(failure)?;
+        let deadline = Instant::now() + req.timeout();
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let reply = child
+                .receive_duration_timeout::<Any>(timeout)
+                .await
+                .map_err(failure)?
+                .take();
+            // Replies of the last hop may arrive before the acknowledgement
+            if reply.return_route().iter().last() != Some(&ack_addr) {
+                continue;
+            }
+            let ack = Acknowledgement::decode(reply.payload()).map_err(failure)?;
+            return match ack {
+                Acknowledgement::Delivered => Ok(Vec::new()),
+                Acknowledgement::NotDelivered => Err(SendMessageError::new(
+                    SendMessageFailure::NotDelivered,
+                    "The message could not be delivered to the last hop",
+                )),
+            };
+        }
+    }
+
+    fn failure(err: ockam_core::Error) -> SendMessageError<'static> {
+        let failure = match err.code().kind {
+            Kind::Timeout => SendMessageFailure::Timeout,
+            Kind::NotFound => SendMessageFailure::Route,
+            _ => SendMessageFailure::Other,
+        };
+        SendMessageError::new(failure, err.to_string())
+    }
 }
@@ -0,0 +1,37 @@
+use minicbor::Decoder;
+use tracing::trace;
+
+use ockam_core::api::{Request, Response, Status};
+use ockam_core::Result;
+use ockam_node::Context;
+
+use crate::nodes::models::perf::PerfRequest;
+use crate::nodes::NodeManagerWorker;
+
+impl NodeManagerWorker {
+    pub(super) async fn run_perf(
+        &mut self,
+        ctx: &mut Context,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: PerfRequest = dec.decode()?;
+        let to = body.to()?;
+        trace!(%to, size = body.size(), duration_ms = body.duration_ms(), "measuring route");
+        if let Err(err) = crate::perf::validate(&body) {
+            return Ok(Response::bad_request(req.id())
+                .body(err.to_string())
+                .to_vec()?);
+        }
+
+        match crate::perf::run(ctx, to, &body).await {
+            Ok(report) => Ok(Response::ok(req.id()).body(report).to_vec()?),
+            Err(err) => {
+                error!(?err, "Failed to measure route");
+                Ok(Response::builder(req.id(), Status::InternalServerError)
+                    .body(err.to_string())
+                    .to_vec()?)
+            }
+        }
+    }
+}
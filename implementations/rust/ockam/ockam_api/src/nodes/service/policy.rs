@@ -0,0 +1,286 @@
+use minicbor::Decoder;
+use ockam::abac::{expand_actions, Action, AttributeSchema, Conditional, Resource};
+use ockam::Result;
+use ockam_core::api::{Request, Response};
+
+use super::NodeManagerWorker;
+use crate::error::ApiError;
+use crate::nodes::models::policy::{Policy, PolicyHistory, PolicyTarget, SetPolicy};
+
+fn to_json(c: &Conditional) -> Result<String> {
+    serde_json::to_string(c).map_err(|e| ApiError::generic(&e.to_string()))
+}
+
+fn target(t: &PolicyTarget) -> (Resource, Action) {
+    (Resource::from(t.resource()), Action::from(t.action()))
+}
+
+/// Fail with all the conditions of a policy which do not fit the declared
+/// attribute types.
+pub(super) fn check_policy(schema: &AttributeSchema, c: &Conditional) -> Result<()> {
+    let violations = schema.check(c);
+    if violations.is_empty() {
+        return Ok(());
+    }
+    let violations: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+    Err(ApiError::message(format!(
+        "policy does not fit the declared attributes: {}",
+        violations.join(", ")
+    )))
+}
+
+impl NodeManagerWorker {
+    /// Set the policy of a resource and one or more actions, keeping the
+    /// replaced ones as previous versions.
+    ///
+    /// Action groups like `@admin` are expanded to their actions.
+    pub(super) async fn set_policy(
+        &mut self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: SetPolicy = dec.decode()?;
+        let condition: Conditional = match serde_json::from_str(body.condition()) {
+            Ok(c) => c,
+            Err(e) => {
+                return Ok(Response::bad_request(req.id())
+                    .body(format!("invalid policy condition: {e}"))
+                    .to_vec()?)
+            }
+        };
+        let actions = match expand_actions(body.actions()) {
+            Ok(a) => a,
+            Err(e) => {
+                return Ok(Response::bad_request(req.id())
+                    .body(format!("invalid policy actions: {e}"))
+                    .to_vec()?)
+            }
+        };
+        let resource = Resource::from(body.target().resource());
+        let node_manager = self.node_manager.read().await;
+        let checked = check_policy(&node_manager.attribute_schema.read().unwrap(), &condition);
+        if let Err(e) = checked {
+            return Ok(Response::bad_request(req.id())
+                .body(e.to_string())
+                .to_vec()?);
+        }
+        node_manager
+            .policies
+            .set_policies(resource, &actions, &condition)
+            .await?;
+        Ok(Response::ok(req.id()).to_vec()?)
+    }
+
+    pub(super) async fn get_policy(
+        &mut self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: PolicyTarget = dec.decode()?;
+        let (resource, action) = target(&body);
+        let node_manager = self.node_manager.read().await;
+        match node_manager.policies.get_policy(&resource, &action).await? {
+            Some(c) => Ok(Response::ok(req.id())
+                .body(Policy::new(to_json(&c)?))
+                .to_vec()?),
+            None => Ok(Response::not_found(req.id()).to_vec()?),
+        }
+    }
+
+    pub(super) async fn get_policy_history(
+        &mut self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: PolicyTarget = dec.decode()?;
+        let (resource, action) = target(&body);
+        let node_manager = self.node_manager.read().await;
+        let versions = node_manager
+            .policies
+            .get_policy_history(&resource, &action)
+            .await?
+            .iter()
+            .map(|c| to_json(c).map(Policy::new))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Response::ok(req.id())
+            .body(PolicyHistory::new(versions))
+            .to_vec()?)
+    }
+
+    /// Restore the previous version of a policy.
+    pub(super) async fn rollback_policy(
+        &mut self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: PolicyTarget = dec.decode()?;
+        let (resource, action) = target(&body);
+        let node_manager = self.node_manager.read().await;
+        match node_manager
+            .policies
+            .rollback_policy(&resource, &action)
+            .await?
+        {
+            Some(c) => {
+                info!(%resource, %action, "policy rolled back");
+                Ok(Response::ok(req.id())
+                    .body(Policy::new(to_json(&c)?))
+                    .to_vec()?)
+            }
+            None => Ok(Response::not_found(req.id())
+                .body(format!("no previous policy for {resource} and {action}"))
+                .to_vec()?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minicbor::Decode;
+
+    use ockam::Context;
+    use ockam_core::api::Status;
+
+    use super::*;
+    use crate::nodes::models::config::{ApplySetup, AttributeSetup, NodeSetup};
+    use crate::nodes::service::authorization::tests::call_local as call;
+    use crate::nodes::NodeManager;
+
+    fn body<'a, T: Decode<'a, ()>>(res: &'a [u8]) -> Result<(Option<Status>, Option<T>)> {
+        let mut dec = Decoder::new(res);
+        let status = dec.decode::<Response>()?.status();
+        Ok((status, dec.decode().ok()))
+    }
+
+    #[ockam_macros::test]
+    async fn policies_can_be_rolled_back(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+        let target = || PolicyTarget::new("/inlet/db");
+
+        let res = call(
+            ctx,
+            &node,
+            Request::post("/node/policy/rollback").body(target()),
+        )
+        .await?;
+        assert_eq!(body::<Policy>(&res)?.0, Some(Status::NotFound));
+
+        for condition in [r#""True""#, r#""False""#] {
+            let set = SetPolicy::new(target(), condition);
+            let res = call(ctx, &node, Request::post("/node/policy").body(set)).await?;
+            assert_eq!(body::<()>(&res)?.0, Some(Status::Ok));
+        }
+
+        // Invalid conditions are rejected and keep the current policy
+        let set = SetPolicy::new(target(), "{not json");
+        let res = call(ctx, &node, Request::post("/node/policy").body(set)).await?;
+        assert_eq!(body::<()>(&res)?.0, Some(Status::BadRequest));
+
+        let res = call(
+            ctx,
+            &node,
+            Request::get("/node/policy/history").body(target()),
+        )
+        .await?;
+        let (status, history) = body::<PolicyHistory>(&res)?;
+        assert_eq!(status, Some(Status::Ok));
+        let history = history.unwrap();
+        assert_eq!(history.versions().len(), 1);
+        assert_eq!(history.versions()[0].condition(), r#""True""#);
+
+        let res = call(
+            ctx,
+            &node,
+            Request::post("/node/policy/rollback").body(target()),
+        )
+        .await?;
+        let (status, restored) = body::<Policy>(&res)?;
+        assert_eq!(status, Some(Status::Ok));
+        assert_eq!(restored.unwrap().condition(), r#""True""#);
+
+        let res = call(ctx, &node, Request::get("/node/policy").body(target())).await?;
+        let (status, current) = body::<Policy>(&res)?;
+        assert_eq!(status, Some(Status::Ok));
+        assert_eq!(current.unwrap().condition(), r#""True""#);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn policies_can_be_set_for_several_actions(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+        let target = || PolicyTarget::new("/api/node");
+
+        let set = SetPolicy::new(target(), r#""True""#).with_actions(["@read-only", "post"]);
+        let res = call(ctx, &node, Request::post("/node/policy").body(set)).await?;
+        assert_eq!(body::<()>(&res)?.0, Some(Status::Ok));
+        for action in ["get", "post"] {
+            let get = Request::get("/node/policy").body(target().with_action(action));
+            let (status, _) = body::<Policy>(&call(ctx, &node, get).await?)?;
+            assert_eq!(status, Some(Status::Ok));
+        }
+        let get = Request::get("/node/policy").body(target().with_action("delete"));
+        let (status, _) = body::<Policy>(&call(ctx, &node, get).await?)?;
+        assert_eq!(status, Some(Status::NotFound));
+
+        let set = SetPolicy::new(target(), r#""True""#).with_actions(["@nope"]);
+        let res = call(ctx, &node, Request::post("/node/policy").body(set)).await?;
+        assert_eq!(body::<()>(&res)?.0, Some(Status::BadRequest));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn policies_are_checked_against_declared_attributes(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+        let condition = r#"{"Eq":["role",{"I":1}]}"#;
+
+        let set = SetPolicy::new(PolicyTarget::new("/inlet/db"), condition);
+        let res = call(ctx, &node, Request::post("/node/policy").body(set)).await?;
+        assert_eq!(body::<()>(&res)?.0, Some(Status::Ok));
+
+        let mut setup = NodeSetup::default();
+        setup.attributes.push(AttributeSetup::new("role", "string"));
+        let apply = ApplySetup::new(setup, false);
+        let res = call(ctx, &node, Request::post("/node/config").body(apply)).await?;
+        assert_eq!(body::<()>(&res)?.0, Some(Status::Ok));
+
+        let set = SetPolicy::new(PolicyTarget::new("/inlet/db"), condition);
+        let res = call(ctx, &node, Request::post("/node/policy").body(set)).await?;
+        assert_eq!(body::<()>(&res)?.0, Some(Status::BadRequest));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn removing_a_setup_policy_keeps_other_actions(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+        let target = |action| PolicyTarget::new("/inlet/db").with_action(action);
+        let apply = |setup: &str| {
+            let setup: NodeSetup = serde_json::from_str(setup).unwrap();
+            Request::post("/node/config").body(ApplySetup::new(setup, false))
+        };
+
+        let both = r#"{"policies": [
+            {"resource": "/inlet/db", "action": "get", "condition": "\"True\""},
+            {"resource": "/inlet/db", "action": "post", "condition": "\"True\""}
+        ]}"#;
+        let res = call(ctx, &node, apply(both)).await?;
+        assert_eq!(body::<()>(&res)?.0, Some(Status::Ok));
+
+        let one = r#"{"policies": [
+            {"resource": "/inlet/db", "action": "get", "condition": "\"True\""}
+        ]}"#;
+        let res = call(ctx, &node, apply(one)).await?;
+        assert_eq!(body::<()>(&res)?.0, Some(Status::Ok));
+
+        let get = Request::get("/node/policy").body(target("get"));
+        let (status, _) = body::<Policy>(&call(ctx, &node, get).await?)?;
+        assert_eq!(status, Some(Status::Ok));
+        let get = Request::get("/node/policy").body(target("post"));
+        let (status, _) = body::<Policy>(&call(ctx, &node, get).await?)?;
+        assert_eq!(status, Some(Status::NotFound));
+
+        ctx.stop().await
+    }
+}
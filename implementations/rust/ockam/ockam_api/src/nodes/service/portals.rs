@@ -1,43 +1,102 @@
+use crate::audit::AuditAccessControl;
 use crate::authenticator::direct::{PROJECT_ID, ROLE};
 use crate::error::ApiError;
 use crate::nodes::models::portal::{
-    CreateInlet, CreateOutlet, InletList, InletStatus, OutletList, OutletStatus,
+    Compression, CreateInlet, CreateOutlet, InletList, InletStatus, InletTlsConfig, OutletList,
+    OutletStatus, PortalPolicy,
 };
 use crate::nodes::registry::{InletInfo, OutletInfo, Registry};
 use crate::nodes::service::random_alias;
 use crate::session::{util, Data, Replacer, Session};
-use crate::{multiaddr_to_route, try_multiaddr_to_addr};
+use crate::{multiaddr_to_route, try_multiaddr_to_addr, DefaultAddress};
 use minicbor::Decoder;
+use ockam::abac::{Action, Conditional, PolicyAccessControl, Resource};
 use ockam::compat::asynchronous::RwLock;
 use ockam::compat::tokio::time::timeout;
-use ockam::tcp::{InletOptions, OutletOptions};
-use ockam::{Address, Result};
+use ockam::tcp::{InletOptions, InletTls, OutletOptions, PortalCompression, PortalTraffic};
+use ockam::{Address, Context, Result};
 use ockam_core::api::{Request, Response, ResponseBuilder};
-use ockam_core::{AccessControl, AllowAll};
+use ockam_core::{AccessControl, AllAccessControl, AllowAll};
 use ockam_identity::credential::access_control::CredentialAccessControl;
 use ockam_identity::IdentityIdentifier;
 use ockam_multiaddr::proto::{Project, Secure, Service};
 use ockam_multiaddr::{MultiAddr, Protocol};
 use std::sync::Arc;
 
+use super::secure_channel::{connect_unlocked, reconnect_unlocked};
 use super::{NodeManager, NodeManagerWorker};
 
 const INLET_WORKER: &str = "inlet-worker";
 const OUTER_CHAN: &str = "outer-chan";
+const INNER_ROUTE: &str = "inner-route";
 
 impl NodeManager {
-    fn access_control(&self, project_id: Option<Vec<u8>>) -> Result<Arc<dyn AccessControl>> {
-        if let Some(pid) = project_id {
+    pub(super) fn access_control(
+        &self,
+        project_id: Option<Vec<u8>>,
+        policy: Option<(Resource, Action)>,
+    ) -> Result<Arc<dyn AccessControl>> {
+        let credential = project_id.map(|pid| {
             let required_attributes = vec![
                 (PROJECT_ID.to_string(), pid),
                 (ROLE.to_string(), b"member".to_vec()),
             ];
-            Ok(Arc::new(CredentialAccessControl::new(
-                &required_attributes,
+            CredentialAccessControl::new(&required_attributes, self.authenticated_storage.clone())
+        });
+        let policy = policy.map(|(resource, action)| {
+            let detail = format!("{resource} {action}");
+            let policy = PolicyAccessControl::new(
+                resource,
+                action,
+                self.policies.clone(),
                 self.authenticated_storage.clone(),
-            )))
-        } else {
-            Ok(Arc::new(AllowAll))
+            )
+            .with_schema(self.attribute_schema.clone());
+            AuditAccessControl::new(policy, self.audit.clone(), detail)
+        });
+        Ok(match (credential, policy) {
+            (Some(c), Some(p)) => Arc::new(AllAccessControl::new(c, p)),
+            (Some(c), None) => Arc::new(c),
+            (None, Some(p)) => Arc::new(p),
+            (None, None) => Arc::new(AllowAll),
+        })
+    }
+
+    /// Parse the ABAC policy of a portal, with the resource and action it guards.
+    ///
+    /// The policy is only stored with [`NodeManager::set_portal_policy`] once
+    /// the portal exists, so that failed requests leave no policy behind.
+    fn portal_policy(
+        kind: &str,
+        alias: &str,
+        policy: Option<&PortalPolicy<'_>>,
+    ) -> Result<Option<(Resource, Action, Conditional)>> {
+        let policy = match policy {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+        let condition: Conditional = serde_json::from_str(policy.condition())
+            .map_err(|e| ApiError::generic(&format!("Invalid policy condition: {e}")))?;
+        let resource = match policy.resource() {
+            Some(r) => Resource::from(r),
+            None => Resource::from(format!("/{kind}/{alias}").as_str()),
+        };
+        let action = Action::from(policy.action());
+        Ok(Some((resource, action, condition)))
+    }
+
+    /// Store the ABAC policy of a portal.
+    async fn set_portal_policy(
+        &self,
+        policy: &Option<(Resource, Action, Conditional)>,
+    ) -> Result<()> {
+        match policy {
+            Some((resource, action, condition)) => {
+                self.policies
+                    .set_policy(resource.clone(), action.clone(), condition)
+                    .await
+            }
+            None => Ok(()),
         }
     }
 }
@@ -60,6 +119,7 @@ impl NodeManagerWorker {
                         None,
                         info.outlet_route.to_string(),
                     )
+                    .with_traffic(&info.traffic)
                 })
                 .collect(),
         ))
@@ -76,6 +136,7 @@ impl NodeManagerWorker {
                 .iter()
                 .map(|(alias, info)| {
                     OutletStatus::new(&info.tcp_addr, info.worker_addr.to_string(), alias, None)
+                        .with_traffic(&info.traffic)
                 })
                 .collect(),
         ))
@@ -83,6 +144,7 @@ impl NodeManagerWorker {
 
     pub(super) async fn create_inlet<'a>(
         &mut self,
+        ctx: &Context,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder<InletStatus<'a>>> {
@@ -106,21 +168,30 @@ impl NodeManagerWorker {
             "Creating inlet portal"
         }
 
+        let tls = match req.tls().map(InletTlsConfig::to_tls).transpose() {
+            Ok(tls) => tls,
+            Err(_) => {
+                return Ok(Response::bad_request(rid)
+                    .body(InletStatus::bad_request("invalid TLS configuration")))
+            }
+        };
+
         // The addressing scheme is very flexible. Typically the node connects to
         // the cloud via secure channel and the with another secure channel via
         // forwarder to the actual outlet on the target node. However it is also
         // possible that there is just a single secure channel used to go directly
         // to another node.
-        let (outer, rest) = {
+        let (outer, inner, rest) = {
             let (sec1, rest) = node_manager
                 .connect(req.outlet_addr(), req.authorized(), None)
                 .await?;
             if !sec1.is_empty() && rest.matches(0, &[Service::CODE.into(), Secure::CODE.into()]) {
                 let addr = sec1.clone().try_with(rest.iter().take(2))?;
                 let (sec2, _) = node_manager.connect(&addr, None, None).await?;
-                (sec1, sec2.try_with(rest.iter().skip(2))?)
+                let full = sec2.try_with(rest.iter().skip(2))?;
+                (sec1, rest, full)
             } else {
-                (MultiAddr::default(), sec1.try_with(&rest)?)
+                (MultiAddr::default(), rest.clone(), sec1.try_with(&rest)?)
             }
         };
 
@@ -132,7 +203,8 @@ impl NodeManagerWorker {
             }
         };
 
-        let access_control = node_manager.access_control(if req.is_check_credential() {
+        let policy = NodeManager::portal_policy("inlet", &alias, req.policy())?;
+        let project_id = if req.is_check_credential() {
             let pid = req
                 .outlet_addr()
                 .first()
@@ -153,13 +225,21 @@ impl NodeManagerWorker {
             pid
         } else {
             None
-        })?;
+        };
+        let guarded = policy.as_ref().map(|(r, a, _)| (r.clone(), a.clone()));
+        let access_control = node_manager.access_control(project_id, guarded)?;
 
-        let options = InletOptions::new(
+        let traffic = Arc::new(PortalTraffic::new(req.rate_limit()));
+        let mut options = InletOptions::new(
             listen_addr.clone(),
             outlet_route.clone(),
             access_control.clone(),
-        );
+        )
+        .with_traffic(traffic.clone())
+        .with_compression(req.compression());
+        if let Some(tls) = tls.clone() {
+            options = options.with_tls(tls);
+        }
 
         let res = node_manager
             .tcp_transport
@@ -168,22 +248,37 @@ impl NodeManagerWorker {
 
         Ok(match res {
             Ok((worker_addr, _)) => {
+                if let Err(e) = node_manager.set_portal_policy(&policy).await {
+                    node_manager.tcp_transport.stop_inlet(worker_addr).await?;
+                    return Err(e);
+                }
                 // TODO: Use better way to store inlets?
                 node_manager.registry.inlets.insert(
                     alias.clone(),
-                    InletInfo::new(&listen_addr, Some(&worker_addr), &outlet_route),
+                    InletInfo::new(
+                        &listen_addr,
+                        Some(&worker_addr),
+                        &outlet_route,
+                        traffic.clone(),
+                    ),
                 );
                 if !outer.is_empty() {
                     let mut s = Session::new(without_outlet_address(rest));
                     s.data().put(INLET_WORKER, worker_addr.clone());
                     s.data().put(OUTER_CHAN, outer);
+                    s.data().put(INNER_ROUTE, inner);
+                    let probe_ctx = ctx.new_detached(Address::random_local()).await?;
                     let repl = replacer(
                         manager,
+                        Arc::new(probe_ctx),
                         s.data(),
                         listen_addr.clone(),
                         req.outlet_addr().clone(),
                         req.authorized(),
                         access_control.clone(),
+                        traffic.clone(),
+                        tls,
+                        req.compression(),
                     );
                     s.set_replacer(repl);
                     node_manager.sessions.lock().unwrap().add(s);
@@ -202,7 +297,7 @@ impl NodeManagerWorker {
                 // TODO: Use better way to store inlets?
                 node_manager.registry.inlets.insert(
                     alias.clone(),
-                    InletInfo::new(&listen_addr, None, &outlet_route),
+                    InletInfo::new(&listen_addr, None, &outlet_route, traffic),
                 );
 
                 Response::bad_request(rid).body(InletStatus::new(
@@ -227,21 +322,44 @@ impl NodeManagerWorker {
             worker_addr,
             alias,
             check_credential,
+            policy,
+            rate_limit,
+            tls,
+            compression,
             ..
         } = dec.decode()?;
         let tcp_addr = tcp_addr.to_string();
 
+        let tls = match tls.map(|t| t.to_tls(&tcp_addr)).transpose() {
+            Ok(tls) => tls,
+            Err(_) => {
+                return Ok(Response::bad_request(req.id())
+                    .body(OutletStatus::bad_request("invalid TLS configuration")))
+            }
+        };
+
         let alias = alias.map(|a| a.0.into()).unwrap_or_else(random_alias);
 
         info!("Handling request to create outlet portal");
         let worker_addr = Address::from(worker_addr.as_ref());
 
-        let access_control = node_manager.access_control(if check_credential {
+        let policy = NodeManager::portal_policy("outlet", &alias, policy.as_ref())?;
+        let project_id = if check_credential {
             Some(node_manager.project_id()?.clone())
         } else {
             None
-        })?;
-        let options = OutletOptions::new(worker_addr.clone(), tcp_addr.clone(), access_control);
+        };
+        let guarded = policy.as_ref().map(|(r, a, _)| (r.clone(), a.clone()));
+        let access_control = node_manager.access_control(project_id, guarded)?;
+        let traffic = Arc::new(PortalTraffic::new(rate_limit.map(Into::into)));
+        let mut options = OutletOptions::new(worker_addr.clone(), tcp_addr.clone(), access_control)
+            .with_traffic(traffic.clone());
+        if let Some(tls) = tls {
+            options = options.with_tls(tls);
+        }
+        if let Some(compression) = compression {
+            options = options.with_compression(Compression::to_portal(&compression));
+        }
 
         let res = node_manager
             .tcp_transport
@@ -250,10 +368,14 @@ impl NodeManagerWorker {
 
         Ok(match res {
             Ok(_) => {
+                if let Err(e) = node_manager.set_portal_policy(&policy).await {
+                    node_manager.tcp_transport.stop_outlet(worker_addr).await?;
+                    return Err(e);
+                }
                 // TODO: Use better way to store outlets?
                 node_manager.registry.outlets.insert(
                     alias.clone(),
-                    OutletInfo::new(&tcp_addr, Some(&worker_addr)),
+                    OutletInfo::new(&tcp_addr, Some(&worker_addr), traffic),
                 );
 
                 Response::ok(req.id()).body(OutletStatus::new(
@@ -268,7 +390,7 @@ impl NodeManagerWorker {
                 node_manager
                     .registry
                     .outlets
-                    .insert(alias.clone(), OutletInfo::new(&tcp_addr, None));
+                    .insert(alias.clone(), OutletInfo::new(&tcp_addr, None, traffic));
 
                 Response::bad_request(req.id()).body(OutletStatus::new(
                     tcp_addr,
@@ -286,68 +408,111 @@ impl NodeManagerWorker {
 /// This returns a function that accepts the previous ping address (e.g.
 /// the secure channel worker address) and constructs the whole route
 /// again.
+#[allow(clippy::too_many_arguments)]
 fn replacer(
     manager: Arc<RwLock<NodeManager>>,
+    ctx: Arc<Context>,
     data: Data,
     bind: String,
     addr: MultiAddr,
     auth: Option<IdentityIdentifier>,
     access: Arc<dyn AccessControl>,
+    traffic: Arc<PortalTraffic>,
+    tls: Option<InletTls>,
+    compression: Vec<PortalCompression>,
 ) -> Replacer {
     Box::new(move |prev| {
         let addr = addr.clone();
         let auth = auth.clone();
         let bind = bind.clone();
         let manager = manager.clone();
+        let ctx = ctx.clone();
         let access = access.clone();
+        let traffic = traffic.clone();
+        let tls = tls.clone();
+        let compression = compression.clone();
         let data = data.clone();
         Box::pin(async move {
             debug!(%prev, %addr, "creating new tcp inlet");
             // The future that recreates the inlet:
             let f = async {
                 let prev = try_multiaddr_to_addr(&prev)?;
-                let mut this = manager.write().await;
                 let timeout = Some(util::MAX_CONNECT_TIME);
 
-                // First the previous secure channel is deleted, and -- if secure
-                // channels were nested -- the outer one as well:
-
-                let _ = this.delete_secure_channel(&prev).await;
-                if let Some(a) = data.get::<MultiAddr>(OUTER_CHAN) {
-                    let a = try_multiaddr_to_addr(&a)?;
-                    let _ = this.delete_secure_channel(&a).await;
-                }
+                // First the previous secure channel is deleted:
+
+                let _ = manager.write().await.delete_secure_channel(&prev).await;
+
+                // If secure channels were nested and the outer one still
+                // answers, the route is repaired by creating the inner one
+                // again through it, which keeps the outer channel and its
+                // connection:
+
+                let repaired = match (
+                    data.get::<MultiAddr>(OUTER_CHAN),
+                    data.get::<MultiAddr>(INNER_ROUTE),
+                ) {
+                    (Some(outer), Some(inner)) => {
+                        match repair(&manager, &ctx, &outer, &inner).await {
+                            Ok(rest) => Some(rest),
+                            Err(e) => {
+                                debug!(%outer, err = %e, "repairing inner secure channel failed");
+                                None
+                            }
+                        }
+                    }
+                    _ => None,
+                };
 
-                // Now a connection attempt is made:
-
-                let rest = {
-                    let (sec1, rest) = this.connect(&addr, auth, timeout).await?;
-                    if !sec1.is_empty()
-                        && rest.matches(0, &[Service::CODE.into(), Secure::CODE.into()])
-                    {
-                        // Another secure channel needs to be created. The first one
-                        // needs to be remembered so it can be cleaned up if this recovery
-                        // executes multiple times:
-                        data.put(OUTER_CHAN, sec1.clone());
-
-                        let addr = sec1.clone().try_with(rest.iter().take(2))?;
-                        let (sec2, _) = this.connect(&addr, None, timeout).await?;
-                        sec2.try_with(rest.iter().skip(2))?
-                    } else {
-                        sec1.try_with(&rest)?
+                let rest = match repaired {
+                    Some(rest) => rest,
+                    None => {
+                        // Otherwise the outer secure channel is deleted as
+                        // well and a new connection attempt is made:
+
+                        if let Some(a) = data.get::<MultiAddr>(OUTER_CHAN) {
+                            let a = try_multiaddr_to_addr(&a)?;
+                            let _ = manager.write().await.delete_secure_channel(&a).await;
+                        }
+
+                        let (sec1, rest) =
+                            reconnect_unlocked(&manager, &addr, auth, timeout).await?;
+                        if !sec1.is_empty()
+                            && rest.matches(0, &[Service::CODE.into(), Secure::CODE.into()])
+                        {
+                            // Another secure channel needs to be created. The first one
+                            // needs to be remembered so it can be cleaned up if this recovery
+                            // executes multiple times, or reused for a repair:
+                            data.put(OUTER_CHAN, sec1.clone());
+                            data.put(INNER_ROUTE, rest.clone());
+
+                            let addr = sec1.clone().try_with(rest.iter().take(2))?;
+                            let (sec2, _) =
+                                connect_unlocked(&manager, &addr, None, timeout).await?;
+                            sec2.try_with(rest.iter().skip(2))?
+                        } else {
+                            sec1.try_with(&rest)?
+                        }
                     }
                 };
 
                 let r = multiaddr_to_route(&rest)
                     .ok_or_else(|| ApiError::message(format!("invalid multiaddr: {rest}")))?;
 
+                let this = manager.read().await;
+
                 // The previous inlet worker needs to be stopped:
                 if let Some(wa) = data.get::<Address>(INLET_WORKER) {
                     let _ = this.tcp_transport.stop_inlet(wa).await;
                 }
 
                 // Finally attempt to create a new inlet using the new route:
-                let opts = InletOptions::new(bind, r, access);
+                let mut opts = InletOptions::new(bind, r, access)
+                    .with_traffic(traffic)
+                    .with_compression(compression);
+                if let Some(tls) = tls {
+                    opts = opts.with_tls(tls);
+                }
                 let wa = this.tcp_transport.create_inlet_extended(opts).await?.0;
                 data.put(INLET_WORKER, wa);
 
@@ -370,6 +535,55 @@ fn replacer(
     })
 }
 
+/// Whether the secure channel at `addr` is still registered.
+fn has_secure_channel(node_manager: &NodeManager, addr: &MultiAddr) -> bool {
+    try_multiaddr_to_addr(addr)
+        .map(|a| {
+            node_manager
+                .registry
+                .secure_channels
+                .get_by_addr(&a)
+                .is_some()
+        })
+        .unwrap_or(false)
+}
+
+/// Check that the secure channel at `addr` is alive, by having the echo
+/// service at its other end answer a ping within [`util::MAX_PROBE_TIME`].
+async fn probe(ctx: &Context, addr: &MultiAddr) -> Result<()> {
+    let mut r = multiaddr_to_route(addr)
+        .ok_or_else(|| ApiError::message(format!("invalid multiaddr: {addr}")))?;
+    r.modify().append(DefaultAddress::ECHO_SERVICE);
+    let secs = util::MAX_PROBE_TIME.as_secs();
+    let _: String = ctx
+        .send_and_receive_with_timeout(r, String::from("ping"), secs)
+        .await?;
+    Ok(())
+}
+
+/// Create the inner secure channel of a nested route again, through the
+/// outer secure channel if it is still up.
+///
+/// The node manager is not locked while waiting on the network.
+///
+/// Returns the full route through the new inner secure channel.
+async fn repair(
+    manager: &RwLock<NodeManager>,
+    ctx: &Context,
+    outer: &MultiAddr,
+    inner: &MultiAddr,
+) -> Result<MultiAddr> {
+    if !has_secure_channel(&manager.read().await, outer) {
+        return Err(ApiError::generic("outer secure channel is gone"));
+    }
+    probe(ctx, outer).await?;
+    debug!(%outer, %inner, "repairing inner secure channel");
+    let addr = outer.clone().try_with(inner.iter().take(2))?;
+    let timeout = Some(util::MAX_CONNECT_TIME);
+    let (sec, _) = connect_unlocked(manager, &addr, None, timeout).await?;
+    Ok(sec.try_with(inner.iter().skip(2))?)
+}
+
 fn without_outlet_address(mut addr: MultiAddr) -> MultiAddr {
     if let Some(p) = addr.last() {
         if let Some(a) = p.cast::<Service>() {
@@ -380,3 +594,104 @@ fn without_outlet_address(mut addr: MultiAddr) -> MultiAddr {
     }
     addr
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::compat::tokio;
+    use ockam::{Any, Routed, Worker};
+    use ockam_identity::{KeyExchangeMode, SecureChannelListenerLimits};
+    use std::time::{Duration, Instant};
+
+    /// Forwards messages to the next hop, standing for a relay between two
+    /// nodes.
+    struct Hop;
+
+    #[ockam::worker]
+    impl Worker for Hop {
+        type Message = Any;
+        type Context = Context;
+
+        async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+            let mut msg = msg.into_local_message();
+            let t = msg.transport_mut();
+            t.onward_route.step()?;
+            t.return_route.modify().prepend(ctx.address());
+            ctx.forward(msg).await
+        }
+    }
+
+    /// A node manager with a secure channel listener at "listener" and a
+    /// relay to it at "hop".
+    async fn manager(ctx: &Context) -> Result<Arc<RwLock<NodeManager>>> {
+        let mut manager = NodeManager::test_manager(ctx).await?;
+        manager
+            .create_secure_channel_listener_impl(
+                "listener".into(),
+                None,
+                None,
+                None,
+                SecureChannelListenerLimits::new(),
+                KeyExchangeMode::Classic,
+            )
+            .await?;
+        ctx.start_worker("hop", Hop).await?;
+        Ok(Arc::new(RwLock::new(manager)))
+    }
+
+    fn inner() -> MultiAddr {
+        "/service/hop/secure/listener/service/outlet"
+            .parse()
+            .unwrap()
+    }
+
+    #[ockam_macros::test]
+    async fn repair_creates_the_inner_channel_again(ctx: &mut Context) -> Result<()> {
+        let manager = manager(ctx).await?;
+        let outer: MultiAddr = "/secure/listener".parse().unwrap();
+        let (outer, _) = connect_unlocked(&manager, &outer, None, None).await?;
+
+        let rest = repair(&manager, ctx, &outer, &inner()).await?;
+        let sec = MultiAddr::default().try_with(rest.iter().take(1))?;
+        assert_ne!(sec, outer);
+        assert!(has_secure_channel(&manager.read().await, &sec));
+        assert_eq!(without_outlet_address(rest.clone()), sec);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn repair_gives_up_on_a_dead_outer_channel(ctx: &mut Context) -> Result<()> {
+        let manager = manager(ctx).await?;
+        let outer: MultiAddr = "/service/hop/secure/listener".parse().unwrap();
+        let (outer, _) = connect_unlocked(&manager, &outer, None, None).await?;
+
+        // The channel is still registered, but its peer is gone
+        ctx.stop_worker("hop").await?;
+        assert!(has_secure_channel(&manager.read().await, &outer));
+
+        let probe_ctx = ctx.new_detached(Address::random_local()).await?;
+        let m = manager.clone();
+        let start = Instant::now();
+        let repairing = tokio::spawn(async move { repair(&m, &probe_ctx, &outer, &inner()).await });
+
+        // The node manager is not locked while the channel is probed
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let locked = tokio::time::timeout(Duration::from_secs(1), manager.write()).await;
+        assert!(locked.is_ok());
+        drop(locked);
+
+        assert!(repairing.await.unwrap().is_err());
+        assert!(start.elapsed() < util::MAX_CONNECT_TIME);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn repair_needs_a_registered_outer_channel(ctx: &mut Context) -> Result<()> {
+        let manager = manager(ctx).await?;
+        let outer: MultiAddr = "/service/missing".parse().unwrap();
+        assert!(repair(&manager, ctx, &outer, &inner()).await.is_err());
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,134 @@
+use super::{NodeManager, NodeManagerWorker};
+use crate::error::ApiError;
+use crate::multiaddr_to_route;
+use crate::revocation::types::{RevocationList, Revoke, SyncRevocations};
+use crate::revocation::{self, Client, DEFAULT_MAX_STALENESS_INTERVALS, DEFAULT_SYNC_INTERVAL};
+use crate::revocation::{REVOCATIONS_ID, SYNC_KEY};
+use minicbor::Decoder;
+use ockam::{AsyncTryClone, Context, Result};
+use ockam_core::api::{Request, Response, ResponseBuilder};
+use ockam_identity::authenticated_storage::AuthenticatedStorage;
+use ockam_multiaddr::MultiAddr;
+use ockam_node::tokio;
+use std::str::FromStr;
+use std::time::Duration;
+
+impl NodeManagerWorker {
+    pub(super) async fn list_revocations(
+        &self,
+        req: &Request<'_>,
+    ) -> ResponseBuilder<RevocationList> {
+        let node_manager = self.node_manager.read().await;
+        Response::ok(req.id()).body(node_manager.revocations.list())
+    }
+
+    /// Revoke an identity in the list published by this node.
+    ///
+    /// Also closes the channels the identity has open on this node.
+    pub(super) async fn revoke_identity(
+        &mut self,
+        ctx: &Context,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder> {
+        let node_manager = self.node_manager.read().await;
+        let body: Revoke = dec.decode()?;
+        if node_manager.revocations.revoke(body.identity().clone()) {
+            info!(identity = %body.identity(), "identity revoked");
+            node_manager
+                .revocations
+                .save(&node_manager.authenticated_storage)
+                .await?;
+            node_manager.revocations.close_revoked_channels(ctx).await;
+        }
+        Ok(Response::ok(req.id()))
+    }
+
+    /// Periodically fetch the revocation list of an authority.
+    ///
+    /// Only lists signed by one of the authorities of the node are accepted.
+    /// The request has to name the authority if the node has several ones.
+    /// Replaces the previous sync, if any, and is restored when the node
+    /// restarts.
+    pub(super) async fn sync_revocations(
+        &mut self,
+        ctx: &Context,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder> {
+        let mut node_manager = self.node_manager.write().await;
+        let body: SyncRevocations = dec.decode()?;
+        node_manager.start_revocation_sync(ctx, &body).await?;
+        let data = minicbor::to_vec(&body)?;
+        node_manager
+            .authenticated_storage
+            .set(REVOCATIONS_ID, SYNC_KEY.to_string(), data)
+            .await?;
+        Ok(Response::ok(req.id()))
+    }
+}
+
+impl NodeManager {
+    /// Restart the revocation sync requested before the node restarted, if any.
+    pub(super) async fn restore_revocation_sync(&mut self, ctx: &Context) -> Result<()> {
+        let data = self
+            .authenticated_storage
+            .get(REVOCATIONS_ID, SYNC_KEY)
+            .await?;
+        if let Some(data) = data {
+            let body: SyncRevocations = minicbor::decode(&data)?;
+            self.start_revocation_sync(ctx, &body).await?;
+            debug!(route = %body.route(), "revocation sync restored");
+        }
+        Ok(())
+    }
+
+    async fn start_revocation_sync(
+        &mut self,
+        ctx: &Context,
+        body: &SyncRevocations<'_>,
+    ) -> Result<()> {
+        let addr =
+            MultiAddr::from_str(body.route()).map_err(|_| ApiError::generic("Invalid route"))?;
+        let route = multiaddr_to_route(&addr).ok_or_else(|| ApiError::generic("Invalid route"))?;
+        let interval = body
+            .interval()
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_SYNC_INTERVAL);
+        let max_staleness = body
+            .max_staleness()
+            .map(Duration::from_secs)
+            .unwrap_or(interval * DEFAULT_MAX_STALENESS_INTERVALS);
+
+        let authorities = self
+            .authorities()?
+            .public_identities()
+            .into_iter()
+            .filter(|a| body.authority().map_or(true, |id| a.identifier() == id))
+            .collect::<Vec<_>>();
+        let authority = match authorities.as_slice() {
+            [authority] => authority.clone(),
+            [] => return Err(ApiError::generic("Unknown authority")),
+            _ => return Err(ApiError::generic("Several authorities, pick one")),
+        };
+
+        let client = Client::new(route, ctx).await?;
+        let vault = self.vault()?.async_try_clone().await?;
+        let revocations = self.revocations.clone();
+        revocations.set_max_staleness(Some(max_staleness));
+        let storage = self.authenticated_storage.async_try_clone().await?;
+        let sync = tokio::spawn(revocation::sync(
+            client,
+            authority,
+            vault,
+            revocations,
+            storage,
+            interval,
+        ));
+        if let Some(previous) = self.revocation_sync.replace(sync) {
+            previous.abort()
+        }
+
+        Ok(())
+    }
+}
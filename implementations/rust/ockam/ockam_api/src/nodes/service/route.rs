@@ -0,0 +1,258 @@
+use std::time::Duration;
+
+use minicbor::Decoder;
+
+use ockam::{Address, Result, Route};
+use ockam_core::api::{Request, Response};
+use ockam_identity::IdentityIdentifier;
+use ockam_multiaddr::proto::{Project, Secure};
+use ockam_multiaddr::{MultiAddr, Protocol};
+
+use super::{NodeManager, NodeManagerWorker};
+use crate::error::ApiError;
+use crate::nodes::models::route::{ResolveRoute, ResolvedRoute};
+use crate::nodes::models::secure_channel::CredentialExchangeMode;
+use crate::{multiaddr_to_route, try_address_to_multiaddr};
+
+impl NodeManager {
+    /// Create a secure channel to a project, returning its address.
+    ///
+    /// A channel created for it is added to `created`.
+    pub(super) async fn connect_project(
+        &mut self,
+        name: &str,
+        timeout: Option<Duration>,
+        resolve_hosts: bool,
+        created: &mut Vec<Address>,
+    ) -> Result<MultiAddr> {
+        let (a, i) = self.resolve_project(name)?;
+        let a = if resolve_hosts {
+            self.resolve_hosts(&a)?
+        } else {
+            a
+        };
+        debug!(addr = %a, "creating secure channel");
+        let r = multiaddr_to_route(&a).ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
+        let i = Some(vec![i]);
+        let m = CredentialExchangeMode::Oneway;
+        self.open_channel(r, i, m, timeout, created).await
+    }
+
+    /// Create a secure channel along a route, or reuse the one already
+    /// there, returning its address.
+    ///
+    /// A channel created here is added to `created`, even when a step after
+    /// the handshake, e.g. presenting a credential, fails.
+    async fn open_channel(
+        &mut self,
+        r: Route,
+        i: Option<Vec<IdentityIdentifier>>,
+        m: CredentialExchangeMode,
+        timeout: Option<Duration>,
+        created: &mut Vec<Address>,
+    ) -> Result<MultiAddr> {
+        let reused = self.registry.secure_channels.get_by_route(&r).is_some();
+        let res = self
+            .create_secure_channel_impl(r.clone(), i, m, timeout)
+            .await;
+        if !reused {
+            if let Some(c) = self.registry.secure_channels.get_by_route(&r) {
+                created.push(c.addr().clone())
+            }
+        }
+        try_address_to_multiaddr(&res?)
+    }
+
+    /// Close secure channels, the last one first.
+    async fn close_channels(&mut self, channels: &[Address]) {
+        for c in channels.iter().rev() {
+            let _ = self.delete_secure_channel(c).await;
+        }
+    }
+
+    /// Expand an address into one whose route this node can send along.
+    ///
+    /// A leading `/project/<name>` is replaced by a secure channel to the
+    /// project, and every `/secure/<addr>` hop by a secure channel along
+    /// the route resolved up to it, e.g. through a forwarder at a relay
+    /// node. The authorised identity only applies to the last secure
+    /// channel hop. Returns the resolved address, its route and the secure
+    /// channels of the hops.
+    ///
+    /// If resolution fails, the secure channels it created are closed again.
+    pub(super) async fn resolve_route(
+        &mut self,
+        addr: &MultiAddr,
+        auth: Option<IdentityIdentifier>,
+        timeout: Option<Duration>,
+    ) -> Result<(MultiAddr, Route, Vec<MultiAddr>)> {
+        let mut created = Vec::new();
+        match self.resolve_hops(addr, auth, timeout, &mut created).await {
+            Ok(resolved) => Ok(resolved),
+            Err(e) => {
+                self.close_channels(&created).await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn resolve_hops(
+        &mut self,
+        addr: &MultiAddr,
+        auth: Option<IdentityIdentifier>,
+        timeout: Option<Duration>,
+        created: &mut Vec<Address>,
+    ) -> Result<(MultiAddr, Route, Vec<MultiAddr>)> {
+        let protos: Vec<_> = addr.iter().collect();
+        let last_secure = protos.iter().rposition(|p| p.code() == Secure::CODE);
+        let mut resolved = MultiAddr::default();
+        let mut channels = Vec::new();
+        for (n, p) in protos.iter().enumerate() {
+            match p.code() {
+                Project::CODE if n == 0 => {
+                    let name = p.cast::<Project>().ok_or_else(|| {
+                        ApiError::message("invalid project protocol in multiaddr")
+                    })?;
+                    resolved = self.connect_project(&name, timeout, false, created).await?;
+                    channels.push(resolved.clone());
+                }
+                Project::CODE => {
+                    return Err(ApiError::message("projects can only start an address"));
+                }
+                Secure::CODE => {
+                    resolved.push_back_value(p)?;
+                    debug!(addr = %resolved, "creating secure channel");
+                    let r = multiaddr_to_route(&resolved)
+                        .ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
+                    let i = match last_secure {
+                        Some(last) if last == n => auth.clone().map(|i| vec![i]),
+                        _ => None,
+                    };
+                    let m = CredentialExchangeMode::Mutual;
+                    resolved = self.open_channel(r, i, m, timeout, created).await?;
+                    channels.push(resolved.clone());
+                }
+                _ => resolved.push_back_value(p)?,
+            }
+        }
+        let route = multiaddr_to_route(&resolved)
+            .ok_or_else(|| ApiError::message(format!("invalid address: {resolved}")))?;
+        Ok((resolved, route, channels))
+    }
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn resolve_route(
+        &mut self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let mut node_manager = self.node_manager.write().await;
+        let body: ResolveRoute = dec.decode()?;
+        let (resolved, route, channels) = node_manager
+            .resolve_route(body.address(), body.authorized(), None)
+            .await?;
+        let channels = channels.iter().map(|c| c.to_string()).collect();
+        let body = ResolvedRoute::new(&resolved, route.to_string(), channels);
+        Ok(Response::ok(req.id()).body(body).to_vec()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minicbor::Decode;
+
+    use ockam::Context;
+    use ockam_core::api::Status;
+    use ockam_core::route;
+    use ockam_identity::{KeyExchangeMode, SecureChannelListenerLimits};
+
+    use super::*;
+    use crate::nodes::models::secure_channel::CreateSecureChannelListenerRequest;
+
+    async fn resolve(ctx: &Context, node: &Route, address: &str) -> Result<Vec<u8>> {
+        let req = Request::post("/node/route/resolve")
+            .body(ResolveRoute::new(address.parse().unwrap(), None))
+            .to_vec()?;
+        ctx.send_and_receive(node.clone(), req).await
+    }
+
+    fn body<'a, T: Decode<'a, ()>>(res: &'a [u8]) -> Result<(Option<Status>, Option<T>)> {
+        let mut dec = Decoder::new(res);
+        let status = dec.decode::<Response>()?.status();
+        Ok((status, dec.decode().ok()))
+    }
+
+    #[ockam_macros::test]
+    async fn secure_channel_hops_are_resolved(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+        let req = Request::post("/node/secure_channel_listener")
+            .body(CreateSecureChannelListenerRequest::new(
+                &Address::from_string("listener"),
+                None,
+            ))
+            .to_vec()?;
+        let _: Vec<u8> = ctx.send_and_receive(node.clone(), req).await?;
+
+        // Addresses without hops are unchanged
+        let res = resolve(ctx, &node, "/service/a/service/b").await?;
+        let (status, resolved) = body::<ResolvedRoute>(&res)?;
+        assert_eq!(status, Some(Status::Ok));
+        let resolved = resolved.unwrap();
+        assert_eq!(resolved.address(), "/service/a/service/b");
+        assert!(resolved.secure_channels().is_empty());
+
+        // A secure channel replaces the route up to its hop
+        let res = resolve(ctx, &node, "/secure/listener/service/echo").await?;
+        let (status, resolved) = body::<ResolvedRoute>(&res)?;
+        assert_eq!(status, Some(Status::Ok));
+        let resolved = resolved.unwrap();
+        let channel = &resolved.secure_channels()[0];
+        assert!(channel.starts_with("/service/"));
+        assert_eq!(resolved.address(), format!("{channel}/service/echo"));
+
+        // Projects unknown to the node are not resolved
+        let res = resolve(ctx, &node, "/project/missing/service/echo").await?;
+        let (status, _) = body::<ResolvedRoute>(&res)?;
+        assert_ne!(status, Some(Status::Ok));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn channels_created_by_a_failed_resolution_are_closed(ctx: &mut Context) -> Result<()> {
+        let mut manager = NodeManager::test_manager(ctx).await?;
+        manager
+            .create_secure_channel_listener_impl(
+                "listener".into(),
+                None,
+                None,
+                None,
+                SecureChannelListenerLimits::new(),
+                KeyExchangeMode::Classic,
+            )
+            .await?;
+        let channel = |m: &NodeManager| {
+            m.registry
+                .secure_channels
+                .get_by_route(&route!["listener"])
+                .map(|c| c.addr().clone())
+        };
+
+        // The channel is closed when a later hop fails
+        let addr = "/secure/listener/project/missing".parse().unwrap();
+        assert!(manager.resolve_route(&addr, None, None).await.is_err());
+        assert!(channel(&manager).is_none());
+
+        // But a channel which was already there is kept
+        let addr = "/secure/listener/service/echo".parse().unwrap();
+        manager.resolve_route(&addr, None, None).await?;
+        let before = channel(&manager);
+        assert!(before.is_some());
+        let addr = "/secure/listener/project/missing".parse().unwrap();
+        assert!(manager.resolve_route(&addr, None, None).await.is_err());
+        assert_eq!(channel(&manager), before);
+
+        ctx.stop().await
+    }
+}
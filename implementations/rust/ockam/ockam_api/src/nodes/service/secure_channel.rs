@@ -1,7 +1,13 @@
 use std::time::Duration;
 
-use super::{map_multiaddr_err, NodeManagerWorker};
+use super::{checkpoint, map_multiaddr_err, NodeManagerWorker};
+use crate::audit::{AuditLog, AuditTrustPolicy};
+use crate::authenticator::direct::types::MemberList;
+use crate::authenticator::direct::{Client, Members};
 use crate::error::ApiError;
+use crate::lmdb::LmdbStorage;
+use crate::multiaddr_to_route;
+use crate::nodes::models::audit::{AuditKind, AuditRecord};
 use crate::nodes::models::secure_channel::{
     CreateSecureChannelListenerRequest, CreateSecureChannelRequest, CreateSecureChannelResponse,
     CredentialExchangeMode, DeleteSecureChannelRequest, DeleteSecureChannelResponse,
@@ -9,15 +15,31 @@ use crate::nodes::models::secure_channel::{
 };
 use crate::nodes::registry::Registry;
 use crate::nodes::NodeManager;
-use crate::DefaultAddress;
+use crate::session::{util, Key, Replacer, Session};
+use crate::{try_address_to_multiaddr, try_multiaddr_to_addr, DefaultAddress};
 use minicbor::Decoder;
-use ockam::identity::TrustEveryonePolicy;
+use ockam::compat::asynchronous::RwLock;
+use ockam::identity::{TrustEveryonePolicy, TrustIdentifierPolicy, TrustPolicy};
 use ockam::{Address, Result, Route};
 use ockam_core::api::{Request, Response, ResponseBuilder};
+use ockam_core::compat::sync::Arc;
 use ockam_core::{route, AsyncTryClone};
-use ockam_identity::{Identity, IdentityIdentifier, TrustMultiIdentifiersPolicy};
+use ockam_identity::{
+    Identity, IdentityIdentifier, KeyExchangeMode, PublicIdentity, SecureChannelCounters,
+    SecureChannelListenerLimits, TrustAttributePolicy, TrustMultiIdentifiersPolicy,
+};
 use ockam_multiaddr::MultiAddr;
+use ockam_node::tokio;
+use ockam_node::tokio::task::JoinHandle;
 use ockam_vault::Vault;
+use tracing::Instrument;
+
+/// Delay between the attempts of consecutive candidate routes, see
+/// [`NodeManager::create_secure_channel_to_any`]
+const DEFAULT_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Default time between two fetches of the members of a project
+const DEFAULT_SYNC_INTERVAL: Duration = Duration::from_secs(60);
 
 impl NodeManager {
     async fn get_credential_if_needed(&mut self) -> Result<()> {
@@ -41,47 +63,109 @@ impl NodeManager {
         sc_route: Route,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
         timeout: Option<Duration>,
+    ) -> Result<Address> {
+        self.create_secure_channel_to_any(
+            identity,
+            vec![sc_route],
+            authorized_identifiers,
+            timeout,
+            DEFAULT_ATTEMPT_DELAY,
+            KeyExchangeMode::Classic,
+        )
+        .await
+    }
+
+    /// Create a secure channel to the first candidate route which completes the
+    /// handshake. Candidates are attempted concurrently, each one starting
+    /// `attempt_delay` after the previous one.
+    pub(crate) async fn create_secure_channel_to_any(
+        &mut self,
+        identity: &Identity<Vault>,
+        sc_routes: Vec<Route>,
+        authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        timeout: Option<Duration>,
+        attempt_delay: Duration,
+        key_exchange: KeyExchangeMode,
     ) -> Result<Address> {
         // If channel was already created, do nothing.
-        if let Some(channel) = self.registry.secure_channels.get_by_route(&sc_route) {
-            let addr = channel.addr();
-            debug!(%addr, "Using cached secure channel");
-            return Ok(addr.clone());
+        if let Some(addr) = self.cached_secure_channel(&sc_routes) {
+            return Ok(addr);
         }
         // Else, create it.
+        let (sc_route, sc_addr, counters) = handshake(
+            identity,
+            &sc_routes,
+            authorized_identifiers.clone(),
+            &self.authenticated_storage,
+            timeout,
+            attempt_delay,
+            key_exchange,
+        )
+        .await?;
+        self.register_secure_channel(
+            sc_routes,
+            sc_route,
+            sc_addr.clone(),
+            authorized_identifiers,
+            counters,
+        );
+        Ok(sc_addr)
+    }
 
-        debug!(%sc_route, "Creating secure channel");
-        let timeout = timeout.unwrap_or(Duration::from_secs(120));
-        let sc_addr = match authorized_identifiers.clone() {
-            Some(ids) => {
-                identity
-                    .create_secure_channel_extended(
-                        sc_route.clone(),
-                        TrustMultiIdentifiersPolicy::new(ids),
-                        &self.authenticated_storage,
-                        timeout,
-                    )
-                    .await
-            }
-            None => {
-                identity
-                    .create_secure_channel_extended(
-                        sc_route.clone(),
-                        TrustEveryonePolicy,
-                        &self.authenticated_storage,
-                        timeout,
-                    )
-                    .await
-            }
-        }?;
+    /// The channel already created along one of the routes, if any.
+    fn cached_secure_channel(&self, sc_routes: &[Route]) -> Option<Address> {
+        let channel = sc_routes
+            .iter()
+            .find_map(|r| self.registry.secure_channels.get_by_route(r))?;
+        let addr = channel.addr();
+        debug!(%addr, "Using cached secure channel");
+        Some(addr.clone())
+    }
 
+    fn register_secure_channel(
+        &mut self,
+        sc_routes: Vec<Route>,
+        sc_route: Route,
+        sc_addr: Address,
+        authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        counters: Arc<SecureChannelCounters>,
+    ) {
         debug!(%sc_route, %sc_addr, "Created secure channel");
+        self.audit.record(
+            AuditRecord::new(AuditKind::SecureChannelCreated)
+                .with_address(&sc_addr)
+                .with_detail(sc_route.to_string()),
+        );
 
-        self.registry
-            .secure_channels
-            .insert(sc_addr.clone(), sc_route, authorized_identifiers);
+        let candidates = if sc_routes.len() > 1 {
+            sc_routes
+        } else {
+            Vec::new()
+        };
+        self.registry.secure_channels.insert(
+            sc_addr,
+            sc_route,
+            authorized_identifiers,
+            counters,
+            candidates,
+        );
+    }
 
-        Ok(sc_addr)
+    /// The credential exchange to run on new channels, and the authorities
+    /// whose credentials are accepted in a mutual exchange.
+    fn credential_exchange(
+        &self,
+        mode: CredentialExchangeMode,
+    ) -> Result<(CredentialExchangeMode, Vec<PublicIdentity>)> {
+        match (self.enable_credential_checks, mode) {
+            (false, _) | (_, CredentialExchangeMode::None) => {
+                Ok((CredentialExchangeMode::None, Vec::new()))
+            }
+            (true, CredentialExchangeMode::Oneway) => Ok((mode, Vec::new())),
+            (true, CredentialExchangeMode::Mutual) => {
+                Ok((mode, self.authorities()?.public_identities()))
+            }
+        }
     }
 
     pub(super) async fn create_secure_channel_impl(
@@ -90,45 +174,53 @@ impl NodeManager {
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
         credential_exchange_mode: CredentialExchangeMode,
         timeout: Option<Duration>,
+    ) -> Result<Address> {
+        self.create_secure_channel_to_any_impl(
+            vec![sc_route],
+            authorized_identifiers,
+            credential_exchange_mode,
+            timeout,
+            DEFAULT_ATTEMPT_DELAY,
+            KeyExchangeMode::Classic,
+        )
+        .await
+    }
+
+    pub(super) async fn create_secure_channel_to_any_impl(
+        &mut self,
+        sc_routes: Vec<Route>,
+        authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        credential_exchange_mode: CredentialExchangeMode,
+        timeout: Option<Duration>,
+        attempt_delay: Duration,
+        key_exchange: KeyExchangeMode,
     ) -> Result<Address> {
         let identity = self.identity()?.async_try_clone().await?;
 
         let sc_addr = self
-            .create_secure_channel_internal(&identity, sc_route, authorized_identifiers, timeout)
+            .create_secure_channel_to_any(
+                &identity,
+                sc_routes,
+                authorized_identifiers,
+                timeout,
+                attempt_delay,
+                key_exchange,
+            )
             .await?;
 
-        let actual_exchange_mode = if self.enable_credential_checks {
-            credential_exchange_mode
-        } else {
-            CredentialExchangeMode::None
-        };
-
-        match actual_exchange_mode {
-            CredentialExchangeMode::None => {
-                debug!(%sc_addr, "No credential presentation");
-            }
-            CredentialExchangeMode::Oneway => {
-                debug!(%sc_addr, "One-way credential presentation");
-                self.get_credential_if_needed().await?;
-                identity
-                    .present_credential(route![sc_addr.clone(), DefaultAddress::CREDENTIAL_SERVICE])
-                    .await?;
-                debug!(%sc_addr, "One-way credential presentation success");
-            }
-            CredentialExchangeMode::Mutual => {
-                debug!(%sc_addr, "Mutual credential presentation");
-                self.get_credential_if_needed().await?;
-                let authorities = self.authorities()?;
-                identity
-                    .present_credential_mutual(
-                        route![sc_addr.clone(), DefaultAddress::CREDENTIAL_SERVICE],
-                        &authorities.public_identities(),
-                        &self.authenticated_storage,
-                    )
-                    .await?;
-                debug!(%sc_addr, "Mutual credential presentation success");
-            }
+        let (mode, authorities) = self.credential_exchange(credential_exchange_mode)?;
+        if !matches!(mode, CredentialExchangeMode::None) {
+            self.get_credential_if_needed().await?;
         }
+        present_credential(
+            &identity,
+            &sc_addr,
+            mode,
+            &authorities,
+            &self.authenticated_storage,
+            &self.audit,
+        )
+        .await?;
 
         // Return secure channel address
         Ok(sc_addr)
@@ -138,6 +230,10 @@ impl NodeManager {
         &mut self,
         addr: Address,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        members: Option<Members>,
+        trusted_attributes: Option<TrustAttributePolicy<LmdbStorage>>,
+        limits: SecureChannelListenerLimits,
+        key_exchange: KeyExchangeMode,
     ) -> Result<()> {
         info!(
             "Handling request to create a new secure channel listener: {}",
@@ -146,26 +242,27 @@ impl NodeManager {
 
         let identity = self.identity()?;
 
-        match authorized_identifiers {
-            Some(ids) => {
-                identity
-                    .create_secure_channel_listener(
-                        addr.clone(),
-                        TrustMultiIdentifiersPolicy::new(ids),
-                        &self.authenticated_storage,
-                    )
-                    .await
-            }
-            None => {
-                identity
-                    .create_secure_channel_listener(
-                        addr.clone(),
-                        TrustEveryonePolicy,
-                        &self.authenticated_storage,
-                    )
-                    .await
-            }
-        }?;
+        let trust_policy: Box<dyn TrustPolicy> = match (authorized_identifiers, members) {
+            (Some(ids), _) => Box::new(TrustMultiIdentifiersPolicy::new(ids)),
+            (None, Some(members)) => Box::new(members),
+            (None, None) => Box::new(TrustEveryonePolicy),
+        };
+        let trust_policy: Box<dyn TrustPolicy> = match trusted_attributes {
+            Some(attributes) => Box::new(trust_policy.and(attributes)),
+            None => trust_policy,
+        };
+        let trust_policy = trust_policy.and(self.revocations.clone());
+
+        let channels = identity
+            .create_secure_channel_listener_with_key_exchange(
+                addr.clone(),
+                AuditTrustPolicy::new(trust_policy, self.audit.clone(), &addr),
+                &self.authenticated_storage,
+                limits,
+                key_exchange,
+            )
+            .await?;
+        self.revocations.watch(channels);
 
         self.registry
             .secure_channel_listeners
@@ -174,6 +271,26 @@ impl NodeManager {
         Ok(())
     }
 
+    /// Where to fetch the members of the project from.
+    async fn member_source(&self) -> Result<MemberSource> {
+        let identity = self.identity()?.async_try_clone().await?;
+        let storage = self.authenticated_storage.async_try_clone().await?;
+        let authority = self
+            .authorities()?
+            .as_ref()
+            .first()
+            .ok_or_else(|| ApiError::generic("No known Authority"))?;
+        let route = multiaddr_to_route(&authority.addr)
+            .ok_or_else(|| ApiError::generic("invalid authority route"))?;
+        let authority = authority.identity.identifier().clone();
+        Ok(MemberSource {
+            identity,
+            storage,
+            route,
+            authority,
+        })
+    }
+
     pub(super) async fn delete_secure_channel(&mut self, addr: &Address) -> Result<()> {
         debug!(%addr, "deleting secure channel");
         let identity = self.identity()?;
@@ -183,6 +300,399 @@ impl NodeManager {
     }
 }
 
+/// The authority of a project, to fetch its members from
+struct MemberSource {
+    identity: Identity<Vault>,
+    storage: LmdbStorage,
+    route: Route,
+    authority: IdentityIdentifier,
+}
+
+impl MemberSource {
+    async fn fetch(&self) -> Result<MemberList> {
+        fetch_members(
+            &self.identity,
+            &self.storage,
+            self.route.clone(),
+            &self.authority,
+        )
+        .await
+    }
+
+    /// Keep fetching the members every `interval` in the background.
+    fn sync(self, members: Members, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                match self.fetch().await {
+                    Ok(list) => {
+                        if members.update(&list) {
+                            debug!(members = list.members().len(), "project members updated")
+                        }
+                    }
+                    Err(err) => warn!(%err, "failed to sync the project members"),
+                }
+            }
+        })
+    }
+}
+
+/// Replace a secure channel opened ahead of its first use by a new one to
+/// the same destination.
+fn channel_replacer(
+    manager: Arc<RwLock<NodeManager>>,
+    to: MultiAddr,
+    auth: Option<IdentityIdentifier>,
+) -> Replacer {
+    Box::new(move |prev| {
+        let manager = manager.clone();
+        let to = to.clone();
+        let auth = auth.clone();
+        Box::pin(async move {
+            debug!(%prev, %to, "replacing secure channel");
+            let prev = try_multiaddr_to_addr(&prev)?;
+            let _ = manager.write().await.delete_secure_channel(&prev).await;
+            let timeout = Some(util::MAX_CONNECT_TIME);
+            let (sc, _) = connect_unlocked(&manager, &to, auth, timeout).await?;
+            Ok(sc)
+        })
+    })
+}
+
+/// Create a secure channel like [`NodeManager::create_secure_channel_to_any_impl`],
+/// but only hold the node manager lock to read and update its state, not
+/// for the handshakes.
+///
+/// Fetching the credential of the node, if it doesn't have one yet, still
+/// holds the lock.
+pub(crate) async fn create_secure_channel_unlocked(
+    manager: &RwLock<NodeManager>,
+    sc_routes: Vec<Route>,
+    authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+    credential_exchange_mode: CredentialExchangeMode,
+    timeout: Option<Duration>,
+    attempt_delay: Duration,
+    key_exchange: KeyExchangeMode,
+) -> Result<Address> {
+    let (identity, storage, audit, cached, (mode, authorities)) = {
+        let node_manager = manager.read().await;
+        (
+            node_manager.identity()?.async_try_clone().await?,
+            node_manager.authenticated_storage.clone(),
+            node_manager.audit.clone(),
+            node_manager.cached_secure_channel(&sc_routes),
+            node_manager.credential_exchange(credential_exchange_mode)?,
+        )
+    };
+    let sc_addr = match cached {
+        Some(sc_addr) => sc_addr,
+        None => {
+            let (sc_route, sc_addr, counters) = handshake(
+                &identity,
+                &sc_routes,
+                authorized_identifiers.clone(),
+                &storage,
+                timeout,
+                attempt_delay,
+                key_exchange,
+            )
+            .await?;
+            manager.write().await.register_secure_channel(
+                sc_routes,
+                sc_route,
+                sc_addr.clone(),
+                authorized_identifiers,
+                counters,
+            );
+            sc_addr
+        }
+    };
+    if !matches!(mode, CredentialExchangeMode::None) && identity.credential().await.is_none() {
+        manager.write().await.get_credential_if_needed().await?;
+    }
+    present_credential(&identity, &sc_addr, mode, &authorities, &storage, &audit).await?;
+    Ok(sc_addr)
+}
+
+/// Connect to an address like [`NodeManager::connect`], without holding
+/// the node manager lock for the handshakes, see
+/// [`create_secure_channel_unlocked`].
+pub(crate) async fn connect_unlocked(
+    manager: &RwLock<NodeManager>,
+    addr: &MultiAddr,
+    auth: Option<IdentityIdentifier>,
+    timeout: Option<Duration>,
+) -> Result<(MultiAddr, MultiAddr)> {
+    connect_unlocked_impl(manager, addr, auth, timeout, false).await
+}
+
+/// Like [`connect_unlocked`], to replace a connection which failed, see
+/// [`NodeManager::reconnect`].
+pub(crate) async fn reconnect_unlocked(
+    manager: &RwLock<NodeManager>,
+    addr: &MultiAddr,
+    auth: Option<IdentityIdentifier>,
+    timeout: Option<Duration>,
+) -> Result<(MultiAddr, MultiAddr)> {
+    connect_unlocked_impl(manager, addr, auth, timeout, true).await
+}
+
+async fn connect_unlocked_impl(
+    manager: &RwLock<NodeManager>,
+    addr: &MultiAddr,
+    auth: Option<IdentityIdentifier>,
+    timeout: Option<Duration>,
+    resolve_hosts: bool,
+) -> Result<(MultiAddr, MultiAddr)> {
+    let plan = manager
+        .read()
+        .await
+        .connect_plan(addr, auth, resolve_hosts)?;
+    match plan.channel {
+        Some(r) => {
+            checkpoint()?;
+            let w = create_secure_channel_unlocked(
+                manager,
+                vec![r],
+                plan.auth,
+                plan.mode,
+                timeout,
+                DEFAULT_ATTEMPT_DELAY,
+                KeyExchangeMode::Classic,
+            )
+            .await?;
+            Ok((try_address_to_multiaddr(&w)?, plan.rest))
+        }
+        None => Ok((MultiAddr::default(), plan.rest)),
+    }
+}
+
+/// Complete the handshake along the only route, or the first of several
+/// candidate routes to complete it, see [`race_secure_channels`].
+async fn handshake(
+    identity: &Identity<Vault>,
+    sc_routes: &[Route],
+    authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+    storage: &LmdbStorage,
+    timeout: Option<Duration>,
+    attempt_delay: Duration,
+    key_exchange: KeyExchangeMode,
+) -> Result<(Route, Address, Arc<SecureChannelCounters>)> {
+    let timeout = timeout.unwrap_or(Duration::from_secs(120));
+    match sc_routes {
+        [] => Err(ApiError::generic("No route to create a secure channel to")),
+        [sc_route] => {
+            let counters = Arc::new(SecureChannelCounters::default());
+            let sc_addr = initiate_secure_channel(
+                identity,
+                sc_route.clone(),
+                authorized_identifiers,
+                storage,
+                timeout,
+                counters.clone(),
+                key_exchange,
+            )
+            .await?;
+            Ok((sc_route.clone(), sc_addr, counters))
+        }
+        candidates => {
+            race_secure_channels(
+                identity,
+                candidates,
+                authorized_identifiers,
+                storage,
+                timeout,
+                attempt_delay,
+                key_exchange,
+            )
+            .await
+        }
+    }
+}
+
+/// Present the credential of the node over a new channel, as required by
+/// the credential exchange `mode`.
+async fn present_credential(
+    identity: &Identity<Vault>,
+    sc_addr: &Address,
+    mode: CredentialExchangeMode,
+    authorities: &[PublicIdentity],
+    storage: &LmdbStorage,
+    audit: &AuditLog,
+) -> Result<()> {
+    match mode {
+        CredentialExchangeMode::None => {
+            debug!(%sc_addr, "No credential presentation");
+        }
+        CredentialExchangeMode::Oneway => {
+            debug!(%sc_addr, "One-way credential presentation");
+            identity
+                .present_credential(route![sc_addr.clone(), DefaultAddress::CREDENTIAL_SERVICE])
+                .await?;
+            debug!(%sc_addr, "One-way credential presentation success");
+            audit.record(
+                AuditRecord::new(AuditKind::CredentialPresented)
+                    .with_address(sc_addr)
+                    .with_detail("oneway"),
+            );
+        }
+        CredentialExchangeMode::Mutual => {
+            debug!(%sc_addr, "Mutual credential presentation");
+            identity
+                .present_credential_mutual(
+                    route![sc_addr.clone(), DefaultAddress::CREDENTIAL_SERVICE],
+                    authorities,
+                    storage,
+                )
+                .await?;
+            debug!(%sc_addr, "Mutual credential presentation success");
+            audit.record(
+                AuditRecord::new(AuditKind::CredentialPresented)
+                    .with_address(sc_addr)
+                    .with_detail("mutual"),
+            );
+        }
+    }
+    Ok(())
+}
+
+async fn initiate_secure_channel(
+    identity: &Identity<Vault>,
+    sc_route: Route,
+    authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+    storage: &LmdbStorage,
+    timeout: Duration,
+    counters: Arc<SecureChannelCounters>,
+    key_exchange: KeyExchangeMode,
+) -> Result<Address> {
+    debug!(%sc_route, "Creating secure channel");
+    let span = info_span!("create_secure_channel", %sc_route);
+    match authorized_identifiers {
+        Some(ids) => {
+            identity
+                .create_secure_channel_with_key_exchange(
+                    sc_route,
+                    TrustMultiIdentifiersPolicy::new(ids),
+                    storage,
+                    timeout,
+                    counters,
+                    key_exchange,
+                )
+                .instrument(span)
+                .await
+        }
+        None => {
+            identity
+                .create_secure_channel_with_key_exchange(
+                    sc_route,
+                    TrustEveryonePolicy,
+                    storage,
+                    timeout,
+                    counters,
+                    key_exchange,
+                )
+                .instrument(span)
+                .await
+        }
+    }
+}
+
+/// Get the members of the project from the authenticator of its authority,
+/// over a secure channel only used for that
+async fn fetch_members(
+    identity: &Identity<Vault>,
+    storage: &LmdbStorage,
+    route: Route,
+    authority: &IdentityIdentifier,
+) -> Result<MemberList> {
+    let sc = identity
+        .create_secure_channel(
+            route,
+            TrustIdentifierPolicy::new(authority.clone()),
+            storage,
+        )
+        .await?;
+    let route = route![sc.clone(), DefaultAddress::AUTHENTICATOR];
+    let list = match Client::new(route, identity.ctx()).await {
+        Ok(mut client) => client.members().await,
+        Err(err) => Err(err),
+    };
+    if let Err(err) = identity.stop_secure_channel(&sc).await {
+        debug!(%err, "failed to stop the secure channel to the authority")
+    }
+    list
+}
+
+/// Attempt a secure channel to every candidate, starting them `attempt_delay`
+/// apart, and return the first one to succeed. The other candidates are
+/// aborted then, whether they are still waiting for their turn or in the
+/// middle of their handshake.
+async fn race_secure_channels(
+    identity: &Identity<Vault>,
+    candidates: &[Route],
+    authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+    storage: &LmdbStorage,
+    timeout: Duration,
+    attempt_delay: Duration,
+    key_exchange: KeyExchangeMode,
+) -> Result<(Route, Address, Arc<SecureChannelCounters>)> {
+    let (tx, mut rx) = tokio::sync::mpsc::channel(candidates.len());
+    let mut attempts = Vec::with_capacity(candidates.len());
+    for (i, sc_route) in candidates.iter().cloned().enumerate() {
+        let identity = identity.async_try_clone().await?;
+        let storage = storage.clone();
+        let authorized_identifiers = authorized_identifiers.clone();
+        let tx = tx.clone();
+        let delay = attempt_delay * i as u32;
+        let attempt = tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let counters = Arc::new(SecureChannelCounters::default());
+            let res = initiate_secure_channel(
+                &identity,
+                sc_route.clone(),
+                authorized_identifiers,
+                &storage,
+                timeout,
+                counters.clone(),
+                key_exchange,
+            )
+            .await;
+            // Without a yield point, so that a completed channel is not lost
+            // to an abort
+            let _ = tx.try_send((sc_route, res.map(|addr| (addr, counters))));
+        });
+        attempts.push(attempt);
+    }
+    drop(tx);
+
+    let mut last_error = None;
+    while let Some((sc_route, res)) = rx.recv().await {
+        match res {
+            Ok((sc_addr, counters)) => {
+                // A channel whose handshake is aborted stops itself once it
+                // completes, as nobody is waiting for it any more
+                for attempt in &attempts {
+                    attempt.abort()
+                }
+                // Channels completed before their attempt was aborted
+                rx.close();
+                while let Ok((_, res)) = rx.try_recv() {
+                    if let Ok((addr, _)) = res {
+                        debug!(%addr, "Stopping redundant secure channel");
+                        let _ = identity.stop_secure_channel(&addr).await;
+                    }
+                }
+                return Ok((sc_route, sc_addr, counters));
+            }
+            Err(e) => {
+                debug!(%sc_route, %e, "Secure channel candidate failed");
+                last_error = Some(e)
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| ApiError::generic("No secure channel candidate succeeded")))
+}
+
 impl NodeManagerWorker {
     pub(super) fn list_secure_channels(
         &self,
@@ -218,12 +728,14 @@ impl NodeManagerWorker {
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder<CreateSecureChannelResponse<'a>>> {
-        let mut node_manager = self.node_manager.write().await;
         let CreateSecureChannelRequest {
             addr,
             authorized_identifiers,
             credential_exchange_mode,
             timeout,
+            alternatives,
+            attempt_delay,
+            key_exchange,
             ..
         } = dec.decode()?;
 
@@ -242,24 +754,72 @@ impl NodeManagerWorker {
         };
 
         // TODO: Improve error handling + move logic into CreateSecureChannelRequest
-        let addr = MultiAddr::try_from(addr.as_ref()).map_err(map_multiaddr_err)?;
-        let route = crate::multiaddr_to_route(&addr)
-            .ok_or_else(|| ApiError::generic("Invalid Multiaddr"))?;
+        let routes = std::iter::once(addr)
+            .chain(alternatives.into_iter().flatten())
+            .map(|addr| {
+                let addr = MultiAddr::try_from(addr.as_ref()).map_err(map_multiaddr_err)?;
+                crate::multiaddr_to_route(&addr)
+                    .ok_or_else(|| ApiError::generic("Invalid Multiaddr"))
+            })
+            .collect::<Result<Vec<Route>>>()?;
 
-        let channel = node_manager
-            .create_secure_channel_impl(
-                route,
-                authorized_identifiers,
-                credential_exchange_mode,
-                timeout,
-            )
-            .await?;
+        let channel = create_secure_channel_unlocked(
+            &self.node_manager,
+            routes,
+            authorized_identifiers,
+            credential_exchange_mode,
+            timeout,
+            attempt_delay.unwrap_or(DEFAULT_ATTEMPT_DELAY),
+            key_exchange.map(Into::into).unwrap_or_default(),
+        )
+        .await?;
 
         let response = Response::ok(req.id()).body(CreateSecureChannelResponse::new(&channel));
 
         Ok(response)
     }
 
+    /// Open a secure channel to a destination ahead of its first use, and
+    /// let the medic check it and replace it when it fails.
+    ///
+    /// Connections to the same destination reuse the channel, see
+    /// [`NodeManager::create_secure_channel_to_any`]. Returns the key of
+    /// the session supervising it.
+    pub(super) async fn warm_secure_channel(
+        &self,
+        to: &MultiAddr,
+        auth: Option<IdentityIdentifier>,
+    ) -> Result<Key> {
+        let timeout = Some(util::MAX_CONNECT_TIME);
+        let (sc, _) = connect_unlocked(&self.node_manager, to, auth.clone(), timeout).await?;
+        if sc.is_empty() {
+            return Err(ApiError::message(format!(
+                "no secure channel to open to {to}"
+            )));
+        }
+        let mut s = Session::new(sc);
+        s.set_replacer(channel_replacer(
+            self.node_manager.clone(),
+            to.clone(),
+            auth,
+        ));
+        let node_manager = self.node_manager.read().await;
+        let key = node_manager.sessions.lock().unwrap().add(s);
+        Ok(key)
+    }
+
+    /// Stop supervising a secure channel opened with
+    /// [`NodeManagerWorker::warm_secure_channel`], and delete it.
+    pub(super) async fn cool_secure_channel(&self, k: &Key) -> Result<()> {
+        let mut node_manager = self.node_manager.write().await;
+        let session = node_manager.sessions.lock().unwrap().remove(k);
+        if let Some(s) = session {
+            let addr = try_multiaddr_to_addr(s.ping_address())?;
+            node_manager.delete_secure_channel(&addr).await?;
+        }
+        Ok(())
+    }
+
     pub(super) async fn delete_secure_channel<'a>(
         &mut self,
         req: &Request<'_>,
@@ -299,7 +859,7 @@ impl NodeManagerWorker {
             .secure_channels
             .get_by_addr(&sc_address);
 
-        Ok(Response::ok(req.id()).body(ShowSecureChannelResponse::new(info)))
+        Ok(Response::ok(req.id()).body(ShowSecureChannelResponse::new(info.as_ref())))
     }
 
     pub(super) async fn create_secure_channel_listener(
@@ -307,12 +867,23 @@ impl NodeManagerWorker {
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder<()>> {
-        let mut node_manager = self.node_manager.write().await;
+        let req_body: CreateSecureChannelListenerRequest = dec.decode()?;
+        let limits = req_body.limits();
+        let key_exchange = req_body.key_exchange();
+        let sync_members = req_body.syncs_members();
+        let sync_interval = req_body
+            .sync_interval
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_SYNC_INTERVAL);
+        if !key_exchange.is_supported() {
+            return Ok(Response::bad_request(req.id()));
+        }
         let CreateSecureChannelListenerRequest {
             addr,
             authorized_identifiers,
+            trusted_attributes,
             ..
-        } = dec.decode()?;
+        } = req_body;
 
         let authorized_identifiers = match authorized_identifiers {
             Some(ids) => {
@@ -327,16 +898,175 @@ impl NodeManagerWorker {
         };
 
         let addr = Address::from(addr.as_ref());
-        if !addr.is_local() {
+        if !addr.is_local() || (sync_members && authorized_identifiers.is_some()) {
             return Ok(Response::bad_request(req.id()));
         }
 
+        // The members are fetched over a secure channel to the authority,
+        // without holding up the other requests to the node
+        let members = if sync_members {
+            let source = self.node_manager.read().await.member_source().await?;
+            let members = Members::default();
+            members.update(&source.fetch().await?);
+            Some((members, source))
+        } else {
+            None
+        };
+
+        let mut node_manager = self.node_manager.write().await;
+        let trusted_attributes = trusted_attributes.map(|attributes| {
+            attributes.into_iter().fold(
+                TrustAttributePolicy::new(node_manager.authenticated_storage.clone()),
+                |policy, attribute| {
+                    policy.with_attribute_in(
+                        attribute.name.to_string(),
+                        attribute.values.iter().map(|v| v.as_bytes()),
+                    )
+                },
+            )
+        });
+
         node_manager
-            .create_secure_channel_listener_impl(addr, authorized_identifiers)
+            .create_secure_channel_listener_impl(
+                addr.clone(),
+                authorized_identifiers,
+                members.as_ref().map(|(m, _)| m.clone()),
+                trusted_attributes,
+                limits,
+                key_exchange,
+            )
             .await?;
 
+        // Only synced once the listener exists, so that the task can't leak
+        if let Some((members, source)) = members {
+            if let Some(info) = node_manager
+                .registry
+                .secure_channel_listeners
+                .get_mut(&addr)
+            {
+                info.member_sync = Some(source.sync(members, sync_interval))
+            }
+        }
+
         let response = Response::ok(req.id());
 
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use minicbor::Decode;
+
+    use ockam::Context;
+    use ockam_core::api::Status;
+
+    use super::*;
+
+    fn body<'a, T: Decode<'a, ()>>(res: &'a [u8]) -> Result<(Option<Status>, Option<T>)> {
+        let mut dec = Decoder::new(res);
+        let status = dec.decode::<Response>()?.status();
+        Ok((status, dec.decode().ok()))
+    }
+
+    #[ockam_macros::test]
+    async fn first_reachable_candidate_is_kept(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+        let req = Request::post("/node/secure_channel_listener")
+            .body(CreateSecureChannelListenerRequest::new(
+                &Address::from_string("listener"),
+                None,
+            ))
+            .to_vec()?;
+        let _: Vec<u8> = ctx.send_and_receive(node.clone(), req).await?;
+
+        let missing: MultiAddr = "/service/missing".parse().unwrap();
+        let listener: MultiAddr = "/service/listener".parse().unwrap();
+        let mut create =
+            CreateSecureChannelRequest::new(&missing, None, CredentialExchangeMode::None)
+                .with_alternatives(&[listener])
+                .with_attempt_delay(Duration::from_millis(10));
+        create.timeout = Some(Duration::from_secs(5));
+        let req = Request::post("/node/secure_channel")
+            .body(create)
+            .to_vec()?;
+        let res: Vec<u8> = ctx.send_and_receive(node.clone(), req).await?;
+        let (status, created) = body::<CreateSecureChannelResponse>(&res)?;
+        assert_eq!(status, Some(Status::Ok));
+        let channel = Address::from(created.unwrap().addr.as_ref());
+
+        let req = Request::get("/node/show_secure_channel")
+            .body(ShowSecureChannelRequest::new(&channel))
+            .to_vec()?;
+        let res: Vec<u8> = ctx.send_and_receive(node.clone(), req).await?;
+        let (status, shown) = body::<ShowSecureChannelResponse>(&res)?;
+        assert_eq!(status, Some(Status::Ok));
+        let shown = shown.unwrap();
+        assert_eq!(shown.route.as_deref(), Some("0#listener"));
+        assert_eq!(shown.candidates.map(|c| c.len()), Some(2));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn requests_are_answered_during_handshakes(ctx: &mut Context) -> Result<()> {
+        let node = NodeManager::test_create(ctx).await?;
+
+        // A handshake with a peer which never answers...
+        let missing: MultiAddr = "/service/missing".parse().unwrap();
+        let mut create =
+            CreateSecureChannelRequest::new(&missing, None, CredentialExchangeMode::None);
+        create.timeout = Some(Duration::from_secs(30));
+        let req = Request::post("/node/secure_channel")
+            .body(create)
+            .to_vec()?;
+        let pending = ctx.new_detached(Address::random_local()).await?;
+        pending.send(node.clone(), req).await?;
+
+        // ...does not hold up the requests reading the node state
+        let req = Request::get("/node/secure_channel").to_vec()?;
+        let res: Vec<u8> = ctx
+            .send_and_receive_with_timeout(node.clone(), req, 5)
+            .await?;
+        let (status, _) = body::<Vec<String>>(&res)?;
+        assert_eq!(status, Some(Status::Ok));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn losing_candidates_are_aborted(ctx: &mut Context) -> Result<()> {
+        let mut manager = NodeManager::test_manager(ctx).await?;
+        manager
+            .create_secure_channel_listener_impl(
+                "listener".into(),
+                None,
+                None,
+                None,
+                SecureChannelListenerLimits::new(),
+                KeyExchangeMode::Classic,
+            )
+            .await?;
+
+        let candidates = [route!["listener"], route!["listener"]];
+        let delay = Duration::from_millis(200);
+        let (_, addr, _) = race_secure_channels(
+            manager.identity()?,
+            &candidates,
+            None,
+            &manager.authenticated_storage,
+            Duration::from_secs(5),
+            delay,
+            KeyExchangeMode::Classic,
+        )
+        .await?;
+        let workers = ctx.list_workers().await?;
+        assert!(workers.contains(&addr));
+
+        // The second candidate never starts its handshake
+        tokio::time::sleep(delay * 2).await;
+        assert_eq!(ctx.list_workers().await?.len(), workers.len());
+
+        ctx.stop().await
+    }
+}
@@ -1,19 +1,23 @@
 use crate::auth::Server;
-use crate::echoer::Echoer;
 use crate::error::ApiError;
 use crate::identity::IdentityService;
+use crate::kafka::{KafkaPortalInterceptor, Mode, KAFKA_BOOTSTRAP_ADDRESS};
+use crate::multiaddr_to_route;
 use crate::nodes::models::services::{
-    ServiceList, ServiceStatus, StartAuthenticatedServiceRequest, StartAuthenticatorRequest,
-    StartCredentialsService, StartEchoerServiceRequest, StartIdentityServiceRequest,
-    StartUppercaseServiceRequest, StartVaultServiceRequest, StartVerifierService,
+    ServiceList, ServiceStatus, StartAuthenticatedServiceRequest, StartIdentityServiceRequest,
+    StartKafkaInletRequest, StartKafkaOutletRequest, StartProxyServiceRequest,
+    StartVaultServiceRequest,
 };
-use crate::nodes::registry::{CredentialsServiceInfo, Registry, VerifierServiceInfo};
+use crate::nodes::registry::{KafkaServiceInfo, ProxyServiceInfo, Registry};
 use crate::nodes::NodeManager;
-use crate::uppercase::Uppercase;
+use crate::proxy::ProxyListenProcessor;
 use crate::vault::VaultService;
 use minicbor::Decoder;
-use ockam::{Address, AsyncTryClone, Context, Result};
+use ockam::{Address, AsyncTryClone, Context, Result, Route};
 use ockam_core::api::{Request, Response, ResponseBuilder};
+use ockam_multiaddr::MultiAddr;
+use std::net::SocketAddr;
+use std::str::FromStr;
 
 use super::NodeManagerWorker;
 
@@ -58,37 +62,6 @@ impl NodeManager {
         Ok(())
     }
 
-    pub(super) async fn start_credentials_service_impl<'a>(
-        &mut self,
-        addr: Address,
-        oneway: bool,
-    ) -> Result<()> {
-        if self.registry.credentials_services.contains_key(&addr) {
-            return Err(ApiError::generic(
-                "Credentials service exists at this address",
-            ));
-        }
-
-        let identity = self.identity()?;
-
-        let authorities = self.authorities()?;
-
-        identity
-            .start_credentials_exchange_worker(
-                authorities.public_identities(),
-                addr.clone(),
-                !oneway,
-                self.authenticated_storage.async_try_clone().await?,
-            )
-            .await?;
-
-        self.registry
-            .credentials_services
-            .insert(addr, CredentialsServiceInfo::default());
-
-        Ok(())
-    }
-
     pub(super) async fn start_authenticated_service_impl(
         &mut self,
         ctx: &Context,
@@ -110,66 +83,6 @@ impl NodeManager {
 
         Ok(())
     }
-
-    pub(super) async fn start_uppercase_service_impl(
-        &mut self,
-        ctx: &Context,
-        addr: Address,
-    ) -> Result<()> {
-        if self.registry.uppercase_services.contains_key(&addr) {
-            return Err(ApiError::generic(
-                "Uppercase service exists at this address",
-            ));
-        }
-
-        ctx.start_worker(addr.clone(), Uppercase).await?;
-
-        self.registry
-            .uppercase_services
-            .insert(addr, Default::default());
-
-        Ok(())
-    }
-
-    pub(super) async fn start_echoer_service_impl(
-        &mut self,
-        ctx: &Context,
-        addr: Address,
-    ) -> Result<()> {
-        if self.registry.echoer_services.contains_key(&addr) {
-            return Err(ApiError::generic("Echoer service exists at this address"));
-        }
-
-        ctx.start_worker(addr.clone(), Echoer).await?;
-
-        self.registry
-            .echoer_services
-            .insert(addr, Default::default());
-
-        Ok(())
-    }
-
-    #[cfg(feature = "direct-authenticator")]
-    pub(super) async fn start_direct_authenticator_service_impl(
-        &mut self,
-        ctx: &Context,
-        addr: Address,
-        path: &std::path::Path,
-        proj: &[u8],
-    ) -> Result<()> {
-        use crate::nodes::registry::AuthenticatorServiceInfo;
-        if self.registry.authenticator_service.contains_key(&addr) {
-            return Err(ApiError::generic("Authenticator service already started"));
-        }
-        let db = self.authenticated_storage.async_try_clone().await?;
-        let id = self.identity()?.async_try_clone().await?;
-        let au = crate::authenticator::direct::Server::new(proj.to_vec(), db, path, id);
-        ctx.start_worker(addr.clone(), au).await?;
-        self.registry
-            .authenticator_service
-            .insert(addr, AuthenticatorServiceInfo::default());
-        Ok(())
-    }
 }
 
 impl NodeManagerWorker {
@@ -214,100 +127,145 @@ impl NodeManagerWorker {
         Ok(Response::ok(req.id()))
     }
 
-    pub(super) async fn start_uppercase_service(
-        &mut self,
-        ctx: &Context,
-        req: &Request<'_>,
-        dec: &mut Decoder<'_>,
-    ) -> Result<ResponseBuilder> {
-        let mut node_manager = self.node_manager.write().await;
-        let req_body: StartUppercaseServiceRequest = dec.decode()?;
-        let addr = req_body.addr.to_string().into();
-        node_manager.start_uppercase_service_impl(ctx, addr).await?;
-        Ok(Response::ok(req.id()))
-    }
-
-    pub(super) async fn start_echoer_service(
-        &mut self,
-        ctx: &Context,
-        req: &Request<'_>,
-        dec: &mut Decoder<'_>,
-    ) -> Result<ResponseBuilder> {
-        let mut node_manager = self.node_manager.write().await;
-        let req_body: StartEchoerServiceRequest = dec.decode()?;
-        let addr = req_body.addr.to_string().into();
-        node_manager.start_echoer_service_impl(ctx, addr).await?;
-        Ok(Response::ok(req.id()))
-    }
-
-    pub(super) async fn start_authenticator_service<'a>(
+    pub(super) async fn start_kafka_outlet_service<'a>(
         &mut self,
         ctx: &Context,
         req: &'a Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder> {
         let mut node_manager = self.node_manager.write().await;
-        #[cfg(not(feature = "direct-authenticator"))]
-        return Err(ApiError::generic("Direct authenticator not available"));
+        let body: StartKafkaOutletRequest = dec.decode()?;
+        let addr: Address = body.address().into();
 
-        #[cfg(feature = "direct-authenticator")]
+        // The bootstrap server outlet has a well-known address.
+        if node_manager
+            .registry
+            .kafka_services
+            .values()
+            .any(|s| s.kind == "kafka_outlet")
         {
-            let body: StartAuthenticatorRequest = dec.decode()?;
-            let addr: Address = body.address().into();
-
-            node_manager
-                .start_direct_authenticator_service_impl(ctx, addr, body.path(), body.project())
-                .await?;
+            return Err(ApiError::generic("Kafka outlet service already started"));
+        }
+        if node_manager.registry.kafka_services.contains_key(&addr) {
+            return Err(ApiError::generic("Kafka service exists at this address"));
         }
 
+        node_manager
+            .tcp_transport
+            .create_outlet(KAFKA_BOOTSTRAP_ADDRESS, body.bootstrap_server())
+            .await?;
+        KafkaPortalInterceptor::start(
+            ctx,
+            addr.clone(),
+            &node_manager.tcp_transport,
+            Mode::outlet(),
+        )
+        .await?;
+
+        node_manager.registry.kafka_services.insert(
+            addr,
+            KafkaServiceInfo {
+                kind: "kafka_outlet",
+            },
+        );
+
         Ok(Response::ok(req.id()))
     }
 
-    pub(super) async fn start_verifier_service<'a>(
+    pub(super) async fn start_kafka_inlet_service<'a>(
         &mut self,
         ctx: &Context,
         req: &'a Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder> {
         let mut node_manager = self.node_manager.write().await;
-        let body: StartVerifierService = dec.decode()?;
+        let body: StartKafkaInletRequest = dec.decode()?;
         let addr: Address = body.address().into();
 
-        if node_manager.registry.verifier_services.contains_key(&addr) {
-            return Err(ApiError::generic("Verifier service exists at this address"));
+        if node_manager.registry.kafka_services.contains_key(&addr) {
+            return Err(ApiError::generic("Kafka service exists at this address"));
         }
 
-        let vault = if let Some(v) = &node_manager.vault {
-            v.async_try_clone().await?
-        } else {
-            return Err(ApiError::generic("Vault not found"));
-        };
-
-        let vs = crate::verifier::Verifier::new(vault);
-        ctx.start_worker(addr.clone(), vs).await?;
+        let bind_addr = SocketAddr::from_str(body.bind_addr())
+            .map_err(|_| ApiError::generic("Invalid bind address"))?;
+        let to = MultiAddr::from_str(body.to())
+            .map_err(|_| ApiError::generic("Invalid Kafka outlet service address"))?;
+        let (sc, rest) = node_manager.connect(&to, None, None).await?;
+        let outlet_route = multiaddr_to_route(&sc.try_with(&rest)?)
+            .ok_or_else(|| ApiError::generic("Invalid Kafka outlet service address"))?;
+
+        KafkaPortalInterceptor::start(
+            ctx,
+            addr.clone(),
+            &node_manager.tcp_transport,
+            Mode::inlet(bind_addr, outlet_route.clone()),
+        )
+        .await?;
+
+        // Connections to the bootstrap server go through the interceptor as well.
+        let mut route = outlet_route;
+        let route: Route = route
+            .modify()
+            .prepend(addr.clone())
+            .append(KAFKA_BOOTSTRAP_ADDRESS)
+            .into();
+        if let Err(e) = node_manager
+            .tcp_transport
+            .create_inlet(body.bind_addr(), route)
+            .await
+        {
+            ctx.stop_worker(addr).await?;
+            return Err(e);
+        }
 
-        node_manager
-            .registry
-            .verifier_services
-            .insert(addr, VerifierServiceInfo::default());
+        node_manager.registry.kafka_services.insert(
+            addr,
+            KafkaServiceInfo {
+                kind: "kafka_inlet",
+            },
+        );
 
         Ok(Response::ok(req.id()))
     }
 
-    pub(super) async fn start_credentials_service<'a>(
+    pub(super) async fn start_proxy_service<'a>(
         &mut self,
-        _ctx: &Context,
+        ctx: &Context,
         req: &'a Request<'_>,
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder> {
         let mut node_manager = self.node_manager.write().await;
-        let body: StartCredentialsService = dec.decode()?;
+        let body: StartProxyServiceRequest = dec.decode()?;
         let addr: Address = body.address().into();
-        let oneway = body.oneway();
+
+        if node_manager.registry.proxy_services.contains_key(&addr) {
+            return Err(ApiError::generic("Proxy service exists at this address"));
+        }
+
+        let bind_addr = SocketAddr::from_str(body.bind_addr())
+            .map_err(|_| ApiError::generic("Invalid bind address"))?;
+        let to = MultiAddr::from_str(body.to())
+            .map_err(|_| ApiError::generic("Invalid outlet node address"))?;
+        let (sc, rest) = node_manager.connect(&to, None, None).await?;
+        let route = multiaddr_to_route(&sc.try_with(&rest)?)
+            .ok_or_else(|| ApiError::generic("Invalid outlet node address"))?;
+        let outlets = body.outlets().iter().map(|o| o.to_string()).collect();
+
+        let bound = ProxyListenProcessor::start(
+            ctx,
+            addr.clone(),
+            bind_addr,
+            &node_manager.tcp_transport,
+            route,
+            outlets,
+        )
+        .await?;
+        info!(%addr, %bound, "proxy service started");
 
         node_manager
-            .start_credentials_service_impl(addr, oneway)
-            .await?;
+            .registry
+            .proxy_services
+            .insert(addr, ProxyServiceInfo::default());
 
         Ok(Response::ok(req.id()))
     }
@@ -331,27 +289,17 @@ impl NodeManagerWorker {
             .keys()
             .for_each(|addr| list.push(ServiceStatus::new(addr.address(), "authenticated")));
         registry
-            .uppercase_services
-            .keys()
-            .for_each(|addr| list.push(ServiceStatus::new(addr.address(), "uppercase")));
-        registry
-            .echoer_services
-            .keys()
-            .for_each(|addr| list.push(ServiceStatus::new(addr.address(), "echoer")));
-        registry
-            .verifier_services
-            .keys()
-            .for_each(|addr| list.push(ServiceStatus::new(addr.address(), "verifier")));
-        registry
-            .credentials_services
-            .keys()
-            .for_each(|addr| list.push(ServiceStatus::new(addr.address(), "credentials")));
-
-        #[cfg(feature = "direct-authenticator")]
+            .kafka_services
+            .iter()
+            .for_each(|(addr, info)| list.push(ServiceStatus::new(addr.address(), info.kind)));
         registry
-            .authenticator_service
+            .proxy_services
             .keys()
-            .for_each(|addr| list.push(ServiceStatus::new(addr.address(), "authenticator")));
+            .for_each(|addr| list.push(ServiceStatus::new(addr.address(), "proxy")));
+        registry.services.iter().for_each(|(addr, info)| {
+            let status = ServiceStatus::new(addr.address(), info.kind());
+            list.push(status.with_restarts(info.restarts()))
+        });
 
         Response::ok(req.id()).body(ServiceList::new(list))
     }
@@ -0,0 +1,80 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use minicbor::Decoder;
+use tracing::trace;
+
+use ockam_core::api::{Request, Response, Status};
+use ockam_core::{
+    route, Address, LocalMessage, NeutralMessage, Result, Route, TracedPayload, TransportMessage,
+};
+use ockam_node::Context;
+
+use crate::nodes::models::trace::{TraceReport, TraceRequest, TracedHop};
+use crate::nodes::NodeManagerWorker;
+use crate::DefaultAddress;
+
+impl NodeManagerWorker {
+    pub(super) async fn trace_route(
+        &mut self,
+        ctx: &mut Context,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let body: TraceRequest = dec.decode()?;
+        let to = body.to()?;
+        trace!(%to, "tracing route");
+
+        match probe(ctx, to, &body).await {
+            Ok(report) => Ok(Response::ok(req.id()).body(report).to_vec()?),
+            Err(err) => {
+                error!(?err, "Failed to trace route");
+                Ok(Response::builder(req.id(), Status::InternalServerError)
+                    .body(err.to_string())
+                    .to_vec()?)
+            }
+        }
+    }
+}
+
+/// Send a traced probe to the echo service at the end of the route, and
+/// collect the hops it recorded on its way there and back
+async fn probe(
+    ctx: &Context,
+    mut to: Route,
+    req: &TraceRequest<'_>,
+) -> Result<TraceReport<'static>> {
+    to.modify().append(DefaultAddress::ECHO_SERVICE);
+    let mut child = ctx.new_detached(Address::random_local()).await?;
+    let msg = TransportMessage::v1_traced(to, route![child.address()], Vec::new())?;
+
+    let sent_at = now_micros();
+    let start = Instant::now();
+    child.forward(LocalMessage::new(msg, Vec::new())).await?;
+    let reply = child
+        .receive_duration_timeout::<NeutralMessage>(Duration::from_millis(req.timeout_ms()))
+        .await?
+        .take()
+        .body();
+    let round_trip_micros = start.elapsed().as_micros() as u64;
+
+    let traced = TracedPayload::from_payload(&Vec::<u8>::from(reply))?;
+    let hops = traced
+        .hops
+        .into_iter()
+        .map(|hop| {
+            TracedHop::new(
+                hop.address.to_string(),
+                hop.next.to_string(),
+                hop.timestamp_micros as i64 - sent_at as i64,
+            )
+        })
+        .collect();
+    Ok(TraceReport::new(hops, round_trip_micros))
+}
+
+fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or_default()
+}
@@ -1,12 +1,13 @@
 use std::collections::BTreeMap;
 
 use crate::nodes::models::transport::{
-    CreateTransport, DeleteTransport, TransportList, TransportMode, TransportStatus, TransportType,
+    CreateTransport, DeleteTransport, TcpConnectionList, TcpConnectionStatus, TcpListenerList,
+    TcpListenerStatus, TransportList, TransportMode, TransportStatus, TransportType,
 };
 use crate::nodes::service::{random_alias, Alias};
 use minicbor::Decoder;
-use ockam::Result;
-use ockam_core::api::{Request, Response, ResponseBuilder};
+use ockam::{Address, Result};
+use ockam_core::api::{self, Request, Response, ResponseBuilder};
 
 use super::NodeManagerWorker;
 
@@ -26,6 +27,71 @@ impl NodeManagerWorker {
         ))
     }
 
+    /// List every open connection of the tcp transport, including the
+    /// incoming ones and those not created through this API.
+    pub(super) async fn list_tcp_transport_connections(
+        &self,
+        req: &Request<'_>,
+    ) -> ResponseBuilder<TcpConnectionList<'static>> {
+        let node_manager = self.node_manager.read().await;
+        let connections = node_manager.tcp_transport.connections();
+        Response::ok(req.id()).body(TcpConnectionList::new(
+            connections.iter().map(TcpConnectionStatus::new).collect(),
+        ))
+    }
+
+    pub(super) async fn list_tcp_transport_listeners(
+        &self,
+        req: &Request<'_>,
+    ) -> ResponseBuilder<TcpListenerList<'static>> {
+        let node_manager = self.node_manager.read().await;
+        let listeners = node_manager.tcp_transport.listeners();
+        Response::ok(req.id()).body(TcpListenerList::new(
+            listeners.iter().map(TcpListenerStatus::new).collect(),
+        ))
+    }
+
+    /// Force-close an open connection of the tcp transport, given the
+    /// address of its sending worker.
+    pub(super) async fn close_tcp_transport_connection(
+        &self,
+        req: &Request<'_>,
+        address: &str,
+    ) -> Result<Vec<u8>> {
+        let address: Address = match address.parse() {
+            Ok(a) => a,
+            Err(e) => {
+                let msg = format!("Invalid connection address: {e}");
+                return Ok(api::bad_request(req, &msg).to_vec()?);
+            }
+        };
+        let mut node_manager = self.node_manager.write().await;
+        let tcp = &node_manager.tcp_transport;
+        let peer = match tcp.connections().iter().find(|c| c.address() == &address) {
+            Some(c) => c.peer(),
+            None => return Ok(Response::not_found(req.id()).to_vec()?),
+        };
+        tcp.close_connection(&address).await?;
+        info!(%address, %peer, "Closed tcp connection");
+
+        // Forget the connection if it was created through this API
+        let api_transport_id = node_manager.api_transport_id.clone();
+        let closed: Vec<Alias> = node_manager
+            .transports
+            .iter()
+            .filter(|(tid, (_, tm, addr))| {
+                *tid != &api_transport_id
+                    && *tm == TransportMode::Connect
+                    && tcp.resolve(addr).ok() == Some(peer)
+            })
+            .map(|(tid, _)| tid.clone())
+            .collect();
+        for tid in closed {
+            node_manager.transports.remove(&tid);
+        }
+        Ok(Response::ok(req.id()).to_vec()?)
+    }
+
     pub(super) async fn add_transport<'a>(
         &self,
         req: &Request<'_>,
@@ -84,7 +150,7 @@ impl NodeManagerWorker {
         let body: DeleteTransport = dec.decode()?;
         info!("Handling request to delete transport: {}", body.tid);
 
-        let tid: Alias = body.tid.into();
+        let tid: Alias = body.tid.into_owned();
 
         if node_manager.api_transport_id == tid && !body.force {
             warn!("User requested to delete the API transport without providing force OP flag...");
@@ -1,15 +1,36 @@
 use super::{map_anyhow_err, NodeManagerWorker};
-use crate::nodes::models::vault::CreateVaultRequest;
+use crate::error::ApiError;
+use crate::nodes::models::vault::{
+    CreateVaultRequest, ExportVaultRequest, ImportVaultRequest, KeyInfo, KeyList, VaultBackup,
+};
 use crate::nodes::NodeManager;
+use hmac::Hmac;
 use minicbor::Decoder;
 use ockam::vault::storage::FileStorage;
 use ockam::vault::Vault;
 use ockam::Result;
 use ockam_core::api::{Request, Response, ResponseBuilder};
+use ockam_core::compat::rand::random;
 use ockam_core::errcode::{Kind, Origin};
+use ockam_core::vault::{
+    KeyId, SecretAttributes, SecretPersistence, SecretType, SecretVault, SymmetricVault,
+    AES256_SECRET_LENGTH_U32,
+};
+use ockam_node::tokio::task;
+use sha2::Sha256;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+const BACKUP_SALT_LENGTH: usize = 32;
+const BACKUP_NONCE_LENGTH: usize = 12;
+/// PBKDF2-HMAC-SHA256 iterations for new backups
+const BACKUP_KDF_ITERATIONS: u32 = 600_000;
+/// Lowest iteration count accepted when importing a backup
+const BACKUP_KDF_MIN_ITERATIONS: u32 = 100_000;
+/// Highest iteration count accepted when importing a backup, so that a
+/// crafted backup can't keep the node busy deriving its key
+const BACKUP_KDF_MAX_ITERATIONS: u32 = 10_000_000;
+
 impl NodeManager {
     pub fn default_vault_path(node_dir: &Path) -> PathBuf {
         node_dir.join("vault.json")
@@ -35,17 +56,67 @@ impl NodeManager {
 
         let path = path.unwrap_or_else(|| Self::default_vault_path(&self.node_dir));
 
-        let vault_storage = FileStorage::create(path.clone()).await?;
-        let vault = Vault::new(Some(Arc::new(vault_storage)));
+        let vault_storage = Arc::new(FileStorage::create(path.clone()).await?);
+        let vault = Vault::new(Some(vault_storage.clone()));
 
         let state = self.config.state();
         state.write().vault_path = Some(path);
         state.persist_config_updates().map_err(map_anyhow_err)?;
 
         self.vault = Some(vault);
+        self.vault_storage = Some(vault_storage);
 
         Ok(())
     }
+
+    fn vault_storage(&self) -> Result<&Arc<FileStorage>> {
+        self.vault_storage
+            .as_ref()
+            .ok_or_else(|| ApiError::generic("Vault doesn't exist"))
+    }
+
+    async fn key_info(&self, key_id: &KeyId) -> Result<KeyInfo<'static>> {
+        let vault = self.vault()?;
+        let stype = vault.secret_attributes_get(key_id).await?.stype();
+        let public_key = match stype {
+            SecretType::X25519 | SecretType::Ed25519 | SecretType::NistP256 => {
+                let public_key = vault.secret_public_key_get(key_id).await?;
+                Some(hex::encode(public_key.data()))
+            }
+            _ => None,
+        };
+        Ok(KeyInfo::new(key_id.to_string(), stype, public_key))
+    }
+}
+
+/// Derive the key encrypting a Vault backup from a password
+///
+/// The key is stretched with PBKDF2-HMAC-SHA256 on a blocking thread. A
+/// throwaway Vault is used so the derived key never ends up in the node's Vault.
+async fn backup_key(password: &str, salt: &[u8], iterations: u32) -> Result<(Vault, KeyId)> {
+    if password.is_empty() {
+        return Err(ApiError::generic("The backup password must not be empty"));
+    }
+    let password = password.as_bytes().to_vec();
+    let salt = salt.to_vec();
+    let derived = task::spawn_blocking(move || pbkdf2_sha256(&password, &salt, iterations))
+        .await
+        .map_err(|e| ockam_core::Error::new(Origin::Application, Kind::Internal, e))?;
+    let aes = SecretAttributes::new(
+        SecretType::Aes,
+        SecretPersistence::Ephemeral,
+        AES256_SECRET_LENGTH_U32,
+    );
+    let vault = Vault::create();
+    let key = vault.secret_import(&derived, aes).await?;
+    Ok((vault, key))
+}
+
+/// PBKDF2-HMAC-SHA256 with a 32 bytes output
+fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut derived = [0u8; 32];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(password, salt, iterations, &mut derived);
+    derived
 }
 
 impl NodeManagerWorker {
@@ -65,4 +136,136 @@ impl NodeManagerWorker {
 
         Ok(response)
     }
+
+    pub(super) async fn list_vault_keys(
+        &mut self,
+        req: &Request<'_>,
+    ) -> Result<ResponseBuilder<KeyList<'_>>> {
+        let node_manager = self.node_manager.read().await;
+        let mut keys = Vec::new();
+        for key_id in node_manager.vault_storage()?.key_ids().await {
+            keys.push(node_manager.key_info(&key_id).await?);
+        }
+
+        let response = Response::ok(req.id()).body(KeyList::new(keys));
+        Ok(response)
+    }
+
+    pub(super) async fn show_vault_key(
+        &mut self,
+        req: &Request<'_>,
+        key_id: &str,
+    ) -> Result<ResponseBuilder<KeyInfo<'_>>> {
+        let node_manager = self.node_manager.read().await;
+        let key_id = KeyId::from(key_id);
+        if !node_manager
+            .vault_storage()?
+            .key_ids()
+            .await
+            .contains(&key_id)
+        {
+            return Err(ockam_core::Error::new(
+                Origin::Application,
+                Kind::NotFound,
+                "Key not found",
+            ));
+        }
+        let key = node_manager.key_info(&key_id).await?;
+
+        let response = Response::ok(req.id()).body(key);
+        Ok(response)
+    }
+
+    pub(super) async fn export_vault(
+        &mut self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<VaultBackup<'_>>> {
+        let node_manager = self.node_manager.read().await;
+        let req_body: ExportVaultRequest = dec.decode()?;
+        let plaintext = node_manager.vault_storage()?.export().await?;
+
+        let salt: [u8; BACKUP_SALT_LENGTH] = random();
+        let nonce: [u8; BACKUP_NONCE_LENGTH] = random();
+        let (vault, key) = backup_key(&req_body.password, &salt, BACKUP_KDF_ITERATIONS).await?;
+        let ciphertext = vault
+            .aead_aes_gcm_encrypt(&key, &plaintext, &nonce, &[])
+            .await?;
+
+        let backup = VaultBackup::new(
+            BACKUP_KDF_ITERATIONS,
+            salt.to_vec(),
+            nonce.to_vec(),
+            ciphertext,
+        );
+        let response = Response::ok(req.id()).body(backup);
+        Ok(response)
+    }
+
+    pub(super) async fn import_vault(
+        &mut self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<KeyList<'_>>> {
+        let node_manager = self.node_manager.read().await;
+        let req_body: ImportVaultRequest = dec.decode()?;
+        let backup = req_body.backup;
+        if backup.nonce.len() != BACKUP_NONCE_LENGTH || backup.salt.len() != BACKUP_SALT_LENGTH {
+            return Err(ApiError::generic("Invalid Vault backup"));
+        }
+        if backup.iterations < BACKUP_KDF_MIN_ITERATIONS {
+            return Err(ApiError::generic(
+                "The Vault backup uses too few key derivation iterations",
+            ));
+        }
+        if backup.iterations > BACKUP_KDF_MAX_ITERATIONS {
+            return Err(ApiError::generic(
+                "The Vault backup uses too many key derivation iterations",
+            ));
+        }
+
+        let (vault, key) = backup_key(&req_body.password, &backup.salt, backup.iterations).await?;
+        let plaintext = vault
+            .aead_aes_gcm_decrypt(&key, &backup.ciphertext, &backup.nonce, &[])
+            .await
+            .map_err(|_| ApiError::generic("Invalid Vault backup or password"))?;
+
+        let key_ids = node_manager.vault_storage()?.import(&plaintext).await?;
+        // Keys replaced by the backup may still be cached by the Vault
+        node_manager.vault()?.evict(&key_ids).await;
+        let mut keys = Vec::new();
+        for key_id in key_ids {
+            keys.push(node_manager.key_info(&key_id).await?);
+        }
+
+        let response = Response::ok(req.id()).body(KeyList::new(keys));
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pbkdf2_sha256_test_vectors() {
+        let vectors: [(u32, &str); 3] = [
+            (
+                1,
+                "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b",
+            ),
+            (
+                2,
+                "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43",
+            ),
+            (
+                4096,
+                "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a",
+            ),
+        ];
+        for (iterations, expected) in vectors {
+            let derived = pbkdf2_sha256(b"password", b"salt", iterations);
+            assert_eq!(hex::encode(derived), expected);
+        }
+    }
 }
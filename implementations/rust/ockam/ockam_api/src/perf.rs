@@ -0,0 +1,259 @@
+//! Throughput and latency measurements over a route.
+//!
+//! A [`PerfResponder`] echoes the pings it receives and counts the data
+//! messages sent to it, like an iperf server. [`run`] measures the round
+//! trip time of pings to a responder, then how much data it can push to it
+//! in a given duration. Every [`WINDOW`] data messages the sender waits for
+//! a ping to come back, which keeps it from flooding slow routes.
+//!
+//! Measurements are bounded by [`MAX_PAYLOAD`], [`MAX_DURATION`] and
+//! [`MAX_PINGS`], and a responder tracks at most [`MAX_RUNS`] runs at once.
+use ockam_core::{Address, Message, Result, Route, Routed, Worker};
+use ockam_node::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+use crate::error::ApiError;
+use crate::nodes::models::perf::{PerfReport, PerfRequest};
+
+/// Number of data messages sent before waiting for the responder.
+pub const WINDOW: u64 = 16;
+
+/// How long to wait for a reply of the responder.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest payload of a measurement, in bytes.
+pub const MAX_PAYLOAD: u32 = 1024 * 1024;
+
+/// Longest duration of a measurement.
+pub const MAX_DURATION: Duration = Duration::from_secs(300);
+
+/// Largest number of pings of a measurement.
+pub const MAX_PINGS: u32 = 10_000;
+
+/// Number of runs a responder tracks at once. When a new run starts beyond
+/// it, the oldest run is forgotten and its summary comes back empty.
+pub const MAX_RUNS: usize = 64;
+
+#[derive(Serialize, Deserialize, Message, Debug)]
+pub enum PerfMessage {
+    /// Echoed back by the responder
+    Ping(Vec<u8>),
+    /// Counted by the responder
+    Data(Vec<u8>),
+    /// Ends a run, the responder replies with a summary
+    Finish,
+    /// What the responder received during a run
+    Summary { messages: u64, bytes: u64 },
+}
+
+#[derive(Default)]
+struct Received {
+    /// Order in which the runs started
+    seq: u64,
+    messages: u64,
+    bytes: u64,
+}
+
+/// Replies to the messages of [`run`], for every sender at once.
+#[derive(Default)]
+pub struct PerfResponder {
+    runs: BTreeMap<String, Received>,
+    next_seq: u64,
+}
+
+impl PerfResponder {
+    fn received(&mut self, sender: String) -> &mut Received {
+        if !self.runs.contains_key(&sender) {
+            if self.runs.len() >= MAX_RUNS {
+                let oldest = self
+                    .runs
+                    .iter()
+                    .min_by_key(|(_, r)| r.seq)
+                    .map(|(k, _)| k.clone());
+                if let Some(k) = oldest {
+                    debug!(sender = %k, "forgetting the oldest perf run");
+                    self.runs.remove(&k);
+                }
+            }
+            self.next_seq += 1;
+            let run = Received {
+                seq: self.next_seq,
+                ..Default::default()
+            };
+            self.runs.insert(sender.clone(), run);
+        }
+        self.runs.entry(sender).or_default()
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for PerfResponder {
+    type Context = Context;
+    type Message = PerfMessage;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<PerfMessage>) -> Result<()> {
+        let return_route = msg.return_route();
+        match msg.body() {
+            PerfMessage::Ping(payload) => ctx.send(return_route, PerfMessage::Ping(payload)).await,
+            PerfMessage::Data(payload) => {
+                let received = self.received(return_route.to_string());
+                received.messages += 1;
+                received.bytes += payload.len() as u64;
+                Ok(())
+            }
+            PerfMessage::Finish => {
+                let received = self
+                    .runs
+                    .remove(&return_route.to_string())
+                    .unwrap_or_default();
+                debug!(to = %return_route, messages = received.messages, "perf run finished");
+                let summary = PerfMessage::Summary {
+                    messages: received.messages,
+                    bytes: received.bytes,
+                };
+                ctx.send(return_route, summary).await
+            }
+            PerfMessage::Summary { .. } => Ok(()),
+        }
+    }
+}
+
+/// Check that a measurement stays within the limits of this module.
+pub fn validate(req: &PerfRequest<'_>) -> Result<()> {
+    if req.size() > MAX_PAYLOAD {
+        return Err(ApiError::generic(&format!(
+            "payloads are limited to {MAX_PAYLOAD} bytes"
+        )));
+    }
+    if Duration::from_millis(req.duration_ms()) > MAX_DURATION {
+        return Err(ApiError::generic(&format!(
+            "measurements are limited to {} seconds",
+            MAX_DURATION.as_secs()
+        )));
+    }
+    if req.pings() > MAX_PINGS {
+        return Err(ApiError::generic(&format!(
+            "measurements are limited to {MAX_PINGS} pings"
+        )));
+    }
+    Ok(())
+}
+
+/// Measure the latency and throughput to the responder at the end of the route.
+pub async fn run(ctx: &Context, to: Route, req: &PerfRequest<'_>) -> Result<PerfReport> {
+    validate(req)?;
+    let mut ctx = ctx.new_detached(Address::random_local()).await?;
+    let payload = vec![0u8; req.size() as usize];
+
+    let mut latencies = Vec::with_capacity(req.pings() as usize);
+    for _ in 0..req.pings() {
+        let start = Instant::now();
+        ping(&mut ctx, &to, payload.clone()).await?;
+        latencies.push(start.elapsed().as_micros() as u64);
+    }
+    latencies.sort_unstable();
+
+    let duration = Duration::from_millis(req.duration_ms());
+    let start = Instant::now();
+    let mut sent = 0;
+    while start.elapsed() < duration {
+        ctx.send(to.clone(), PerfMessage::Data(payload.clone()))
+            .await?;
+        sent += 1;
+        if sent % WINDOW == 0 {
+            ping(&mut ctx, &to, Vec::new()).await?;
+        }
+    }
+    ctx.send(to, PerfMessage::Finish).await?;
+    let (messages, bytes) = match ctx
+        .receive_duration_timeout::<PerfMessage>(REPLY_TIMEOUT)
+        .await?
+        .take()
+        .body()
+    {
+        PerfMessage::Summary { messages, bytes } => (messages, bytes),
+        _ => return Err(ApiError::generic("unexpected reply of the perf service")),
+    };
+    let elapsed = start.elapsed().as_micros() as u64;
+    debug!(sent, messages, "perf run done");
+
+    Ok(PerfReport::new(messages, bytes, elapsed).with_latencies(
+        latencies.len() as u64,
+        percentile(&latencies, 50),
+        percentile(&latencies, 90),
+        percentile(&latencies, 99),
+        latencies.last().copied().unwrap_or_default(),
+    ))
+}
+
+async fn ping(ctx: &mut Context, to: &Route, payload: Vec<u8>) -> Result<()> {
+    ctx.send(to.clone(), PerfMessage::Ping(payload)).await?;
+    match ctx
+        .receive_duration_timeout::<PerfMessage>(REPLY_TIMEOUT)
+        .await?
+        .take()
+        .body()
+    {
+        PerfMessage::Ping(_) => Ok(()),
+        _ => Err(ApiError::generic("unexpected reply of the perf service")),
+    }
+}
+
+/// Nearest-rank percentile of sorted values.
+fn percentile(sorted: &[u64], p: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p * sorted.len() + 99) / 100;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::route;
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        let values: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&values, 50), 50);
+        assert_eq!(percentile(&values, 99), 99);
+        assert_eq!(percentile(&[7], 90), 7);
+        assert_eq!(percentile(&[1, 2, 3], 50), 2);
+        assert_eq!(percentile(&[], 50), 0);
+    }
+
+    #[test]
+    fn measurements_are_bounded() {
+        assert!(validate(&PerfRequest::new("/service/perf", MAX_PAYLOAD, 1000, 1)).is_ok());
+        assert!(validate(&PerfRequest::new("/service/perf", MAX_PAYLOAD + 1, 1000, 1)).is_err());
+        assert!(validate(&PerfRequest::new("/service/perf", 1, u64::MAX, 1)).is_err());
+        assert!(validate(&PerfRequest::new("/service/perf", 1, 1000, MAX_PINGS + 1)).is_err());
+    }
+
+    #[test]
+    fn responder_forgets_the_oldest_runs() {
+        let mut responder = PerfResponder::default();
+        for i in 0..=MAX_RUNS {
+            responder.received(format!("sender{i}")).messages += 1;
+        }
+        assert_eq!(responder.runs.len(), MAX_RUNS);
+        assert!(!responder.runs.contains_key("sender0"));
+        assert!(responder.runs.contains_key(&format!("sender{MAX_RUNS}")));
+    }
+
+    #[ockam_macros::test]
+    async fn responder_counts_the_data(ctx: &mut Context) -> Result<()> {
+        ctx.start_worker("perf", PerfResponder::default()).await?;
+        let req = PerfRequest::new("/service/perf", 100, 50, 5);
+        let report = run(ctx, route!["perf"], &req).await?;
+        assert_eq!(report.pings(), 5);
+        assert!(report.messages() > 0);
+        assert_eq!(report.bytes(), report.messages() * 100);
+        assert!(report.p50_micros() <= report.max_micros());
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,414 @@
+//! SOCKS5 and HTTP CONNECT front-end for outlets
+//!
+//! The proxy service listens on a local address and accepts both SOCKS5
+//! (without authentication) and HTTP CONNECT requests. The requested host
+//! names an outlet on a remote node and the requested port is ignored, e.g.
+//!
+//! ```text
+//! curl --proxy socks5h://127.0.0.1:1080 http://my_outlet/
+//! curl --proxy http://127.0.0.1:1080 https://my_outlet/
+//! ```
+//!
+//! Each proxied connection is then forwarded to that outlet through its own
+//! portal, so unmodified applications can use ockam tunnels without creating
+//! an inlet per outlet.
+//!
+//! Only the outlets the proxy was started with can be reached, requests for
+//! any other name are refused before anything is sent to the remote node.
+
+use std::collections::BTreeSet;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ockam::compat::tokio;
+use ockam::TcpTransport;
+use ockam_core::{async_trait, Address, AsyncTryClone, Processor, Result, Route, LOCAL};
+use ockam_node::Context;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+
+use crate::error::ApiError;
+
+/// How long a client may take to send its proxy request.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest HTTP CONNECT request head accepted.
+const MAX_HTTP_HEAD_SIZE: usize = 8 * 1024;
+
+const SOCKS_VERSION: u8 = 5;
+const NO_AUTHENTICATION: u8 = 0;
+const NO_ACCEPTABLE_METHODS: u8 = 0xff;
+const CONNECT: u8 = 1;
+const ADDRESS_IPV4: u8 = 1;
+const ADDRESS_DOMAIN: u8 = 3;
+const ADDRESS_IPV6: u8 = 4;
+const SUCCEEDED: u8 = 0;
+const GENERAL_FAILURE: u8 = 1;
+const NOT_ALLOWED: u8 = 2;
+const COMMAND_NOT_SUPPORTED: u8 = 7;
+const ADDRESS_TYPE_NOT_SUPPORTED: u8 = 8;
+
+/// Accepts proxy connections and hands them over to portals.
+pub(crate) struct ProxyListenProcessor {
+    listener: TcpListener,
+    tcp: Arc<TcpTransport>,
+    /// Route to the node hosting the outlets.
+    route: Route,
+    /// Addresses of the outlets exposed through the proxy.
+    outlets: Arc<BTreeSet<String>>,
+}
+
+impl ProxyListenProcessor {
+    /// Start a proxy listening on `bind_addr` and return the address it is bound to.
+    pub(crate) async fn start(
+        ctx: &Context,
+        address: Address,
+        bind_addr: SocketAddr,
+        tcp: &TcpTransport,
+        route: Route,
+        outlets: BTreeSet<String>,
+    ) -> Result<SocketAddr> {
+        if outlets.is_empty() {
+            return Err(ApiError::generic(
+                "A proxy has to expose at least one outlet",
+            ));
+        }
+        if let Some(name) = outlets.iter().find(|o| !is_valid_outlet(o)) {
+            return Err(ApiError::generic(&format!("Invalid outlet name: {name}")));
+        }
+        let listener = TcpListener::bind(bind_addr)
+            .await
+            .map_err(|e| ApiError::generic(&format!("Failed to bind {bind_addr}: {e}")))?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| ApiError::generic(&e.to_string()))?;
+        let processor = Self {
+            listener,
+            tcp: Arc::new(tcp.async_try_clone().await?),
+            route,
+            outlets: Arc::new(outlets),
+        };
+        ctx.start_processor(address, processor).await?;
+        Ok(local_addr)
+    }
+}
+
+#[async_trait]
+impl Processor for ProxyListenProcessor {
+    type Context = Context;
+
+    async fn process(&mut self, _ctx: &mut Context) -> Result<bool> {
+        let (stream, peer) = match self.listener.accept().await {
+            Ok(s) => s,
+            Err(e) => {
+                warn!(%e, "failed to accept proxy connection");
+                return Ok(true);
+            }
+        };
+        // The handshake is done off the processor so that a slow client does
+        // not hold up the others.
+        let tcp = self.tcp.clone();
+        let route = self.route.clone();
+        let outlets = self.outlets.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve(stream, &tcp, route, &outlets).await {
+                debug!(%peer, %e, "proxy connection failed")
+            }
+        });
+        Ok(true)
+    }
+}
+
+async fn serve(
+    mut stream: TcpStream,
+    tcp: &TcpTransport,
+    mut route: Route,
+    outlets: &BTreeSet<String>,
+) -> Result<()> {
+    let outlet = timeout(HANDSHAKE_TIMEOUT, handshake(&mut stream, outlets))
+        .await
+        .map_err(|_| ApiError::generic("Proxy handshake timed out"))?
+        .map_err(|e| ApiError::generic(&format!("Invalid proxy request: {e}")))?;
+    debug!(%outlet, "proxying connection");
+    let route: Route = route.modify().append(Address::new(LOCAL, outlet)).into();
+    tcp.create_inlet_for_stream(stream, route).await?;
+    Ok(())
+}
+
+/// Read a SOCKS5 or HTTP CONNECT request from the client, accept it if it
+/// is for one of `outlets` and return the name of the requested outlet.
+///
+/// Nothing past the request is read, the rest of the stream belongs to the
+/// proxied connection.
+pub(crate) async fn handshake<S>(stream: &mut S, outlets: &BTreeSet<String>) -> io::Result<String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let first = stream.read_u8().await?;
+    if first == SOCKS_VERSION {
+        socks5(stream, outlets).await
+    } else {
+        http_connect(stream, first, outlets).await
+    }
+}
+
+async fn socks5<S>(stream: &mut S, outlets: &BTreeSet<String>) -> io::Result<String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let n = stream.read_u8().await?;
+    let mut methods = vec![0; usize::from(n)];
+    stream.read_exact(&mut methods).await?;
+    if !methods.contains(&NO_AUTHENTICATION) {
+        stream
+            .write_all(&[SOCKS_VERSION, NO_ACCEPTABLE_METHODS])
+            .await?;
+        return Err(invalid("no supported SOCKS5 authentication method"));
+    }
+    stream
+        .write_all(&[SOCKS_VERSION, NO_AUTHENTICATION])
+        .await?;
+
+    let mut header = [0; 4];
+    stream.read_exact(&mut header).await?;
+    let [version, command, _, address_type] = header;
+    if version != SOCKS_VERSION {
+        socks5_reply(stream, GENERAL_FAILURE).await?;
+        return Err(invalid("unexpected SOCKS version"));
+    }
+    let len = match address_type {
+        ADDRESS_IPV4 => 4,
+        ADDRESS_IPV6 => 16,
+        ADDRESS_DOMAIN => usize::from(stream.read_u8().await?),
+        _ => {
+            socks5_reply(stream, ADDRESS_TYPE_NOT_SUPPORTED).await?;
+            return Err(invalid("unknown SOCKS5 address type"));
+        }
+    };
+    let mut host = vec![0; len];
+    stream.read_exact(&mut host).await?;
+    let _port = stream.read_u16().await?;
+
+    if command != CONNECT {
+        socks5_reply(stream, COMMAND_NOT_SUPPORTED).await?;
+        return Err(invalid("only the SOCKS5 CONNECT command is supported"));
+    }
+    // Outlets are named, an IP address can not designate one.
+    if address_type != ADDRESS_DOMAIN {
+        socks5_reply(stream, ADDRESS_TYPE_NOT_SUPPORTED).await?;
+        return Err(invalid("SOCKS5 requests must use a domain name"));
+    }
+    let outlet = match String::from_utf8(host) {
+        Ok(h) if is_valid_outlet(&h) => h,
+        _ => {
+            socks5_reply(stream, GENERAL_FAILURE).await?;
+            return Err(invalid("invalid outlet name"));
+        }
+    };
+    if !outlets.contains(&outlet) {
+        socks5_reply(stream, NOT_ALLOWED).await?;
+        return Err(invalid("outlet is not exposed by this proxy"));
+    }
+    socks5_reply(stream, SUCCEEDED).await?;
+    Ok(outlet)
+}
+
+async fn socks5_reply<S>(stream: &mut S, status: u8) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    // The bound address is meaningless here, an empty IPv4 address is sent.
+    let reply = [SOCKS_VERSION, status, 0, ADDRESS_IPV4, 0, 0, 0, 0, 0, 0];
+    stream.write_all(&reply).await
+}
+
+async fn http_connect<S>(
+    stream: &mut S,
+    first: u8,
+    outlets: &BTreeSet<String>,
+) -> io::Result<String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    // The head is read byte by byte to not consume any tunneled data.
+    let mut head = vec![first];
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() >= MAX_HTTP_HEAD_SIZE {
+            http_reply(stream, "431 Request Header Fields Too Large").await?;
+            return Err(invalid("HTTP request head is too large"));
+        }
+        head.push(stream.read_u8().await?);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    match (request_line.next(), request_line.next()) {
+        (Some("CONNECT"), Some(authority)) => {
+            let host = match authority.rsplit_once(':') {
+                Some((host, _port)) => host,
+                None => authority,
+            };
+            if !is_valid_outlet(host) {
+                http_reply(stream, "400 Bad Request").await?;
+                return Err(invalid("invalid outlet name"));
+            }
+            if !outlets.contains(host) {
+                http_reply(stream, "403 Forbidden").await?;
+                return Err(invalid("outlet is not exposed by this proxy"));
+            }
+            http_reply(stream, "200 Connection Established").await?;
+            Ok(host.to_string())
+        }
+        (Some(_), Some(_)) => {
+            http_reply(stream, "405 Method Not Allowed").await?;
+            Err(invalid("only the HTTP CONNECT method is supported"))
+        }
+        _ => {
+            http_reply(stream, "400 Bad Request").await?;
+            Err(invalid("malformed HTTP request"))
+        }
+    }
+}
+
+async fn http_reply<S>(stream: &mut S, status: &str) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    let reply = format!("HTTP/1.1 {status}\r\n\r\n");
+    stream.write_all(reply.as_bytes()).await
+}
+
+/// Outlet names are local worker addresses.
+fn is_valid_outlet(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_.".contains(&b))
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::duplex;
+
+    fn outlets() -> BTreeSet<String> {
+        BTreeSet::from(["outlet".to_string()])
+    }
+
+    #[ockam_macros::test]
+    async fn socks5_domain(ctx: &mut Context) -> Result<()> {
+        let (mut client, mut server) = duplex(1024);
+        let handshake = tokio::spawn(async move { handshake(&mut server, &outlets()).await });
+
+        client.write_all(&[5, 1, 0]).await.unwrap();
+        let mut method = [0; 2];
+        client.read_exact(&mut method).await.unwrap();
+        assert_eq!(method, [5, 0]);
+
+        client.write_all(&[5, 1, 0, 3, 6]).await.unwrap();
+        client.write_all(b"outlet").await.unwrap();
+        client.write_all(&80u16.to_be_bytes()).await.unwrap();
+        let mut reply = [0; 10];
+        client.read_exact(&mut reply).await.unwrap();
+        assert_eq!(reply[1], SUCCEEDED);
+
+        assert_eq!(handshake.await.unwrap().unwrap(), "outlet");
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn socks5_ip_address(ctx: &mut Context) -> Result<()> {
+        let (mut client, mut server) = duplex(1024);
+        let handshake = tokio::spawn(async move { handshake(&mut server, &outlets()).await });
+
+        client.write_all(&[5, 1, 0]).await.unwrap();
+        let mut method = [0; 2];
+        client.read_exact(&mut method).await.unwrap();
+
+        client
+            .write_all(&[5, 1, 0, 1, 127, 0, 0, 1, 0, 80])
+            .await
+            .unwrap();
+        let mut reply = [0; 10];
+        client.read_exact(&mut reply).await.unwrap();
+        assert_eq!(reply[1], ADDRESS_TYPE_NOT_SUPPORTED);
+
+        assert!(handshake.await.unwrap().is_err());
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn http_connect_keeps_tunneled_data(ctx: &mut Context) -> Result<()> {
+        let (mut client, mut server) = duplex(1024);
+        client
+            .write_all(b"CONNECT outlet:443 HTTP/1.1\r\nHost: outlet:443\r\n\r\nhello")
+            .await
+            .unwrap();
+
+        assert_eq!(handshake(&mut server, &outlets()).await.unwrap(), "outlet");
+        let mut rest = [0; 5];
+        server.read_exact(&mut rest).await.unwrap();
+        assert_eq!(&rest, b"hello");
+
+        let mut reply = [0; 39];
+        client.read_exact(&mut reply).await.unwrap();
+        assert_eq!(&reply, b"HTTP/1.1 200 Connection Established\r\n\r\n");
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn http_get_is_rejected(ctx: &mut Context) -> Result<()> {
+        let (mut client, mut server) = duplex(1024);
+        client
+            .write_all(b"GET http://outlet/ HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+
+        assert!(handshake(&mut server, &outlets()).await.is_err());
+        let mut reply = [0; 35];
+        client.read_exact(&mut reply).await.unwrap();
+        assert_eq!(&reply, b"HTTP/1.1 405 Method Not Allowed\r\n\r\n");
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn unexposed_outlets_are_refused(ctx: &mut Context) -> Result<()> {
+        let (mut client, mut server) = duplex(1024);
+        let handshake = tokio::spawn(async move { handshake(&mut server, &outlets()).await });
+
+        client.write_all(&[5, 1, 0]).await.unwrap();
+        let mut method = [0; 2];
+        client.read_exact(&mut method).await.unwrap();
+
+        // Any local worker of the remote node, e.g. its API, is not an outlet
+        let name = b"_internal.nodemanager";
+        client
+            .write_all(&[5, 1, 0, 3, name.len() as u8])
+            .await
+            .unwrap();
+        client.write_all(name).await.unwrap();
+        client.write_all(&80u16.to_be_bytes()).await.unwrap();
+        let mut reply = [0; 10];
+        client.read_exact(&mut reply).await.unwrap();
+        assert_eq!(reply[1], NOT_ALLOWED);
+        assert!(handshake.await.unwrap().is_err());
+
+        let (mut client, mut server) = duplex(1024);
+        client
+            .write_all(b"CONNECT api:443 HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+        assert!(handshake(&mut server, &outlets()).await.is_err());
+        let mut reply = [0; 26];
+        client.read_exact(&mut reply).await.unwrap();
+        assert_eq!(&reply, b"HTTP/1.1 403 Forbidden\r\n\r\n");
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,439 @@
+//! Topic based publish/subscribe.
+//!
+//! A [`Server`] delivers the events published to a topic to the routes of
+//! its subscribers. It can retain the last events of every topic, to replay
+//! them to new subscribers, and persist them in an [`AuthenticatedStorage`]
+//! to keep them across restarts.
+//!
+//! Subscribers receive every [`Event`] as an encoded `Vec<u8>` message.
+
+pub mod types;
+
+use core::fmt;
+use minicbor::{Decoder, Encode};
+use ockam_core::api::{self, assert_request_match, assert_response_match};
+use ockam_core::api::{Error, Method, Request, RequestBuilder, Response, Status};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{self, Address, Result, Route, Routed, Worker};
+use ockam_identity::authenticated_storage::AuthenticatedStorage;
+use ockam_multiaddr::MultiAddr;
+use ockam_node::Context;
+use std::collections::{BTreeMap, VecDeque};
+use std::str::FromStr;
+use tracing::{trace, warn};
+
+use self::types::{Event, Publish, Published, Subscribe, Subscribed, TopicInfo, TopicList};
+use crate::multiaddr_to_route;
+
+/// Storage identifier of the retained events, keyed by topic.
+const PUBSUB_ID: &str = "pubsub";
+
+struct Subscriber {
+    id: u64,
+    route: Route,
+}
+
+#[derive(Default)]
+struct Topic {
+    seq: u64,
+    subscribers: Vec<Subscriber>,
+    retained: VecDeque<(u64, Vec<u8>)>,
+}
+
+/// Delivers published events to the subscribers of their topic.
+pub struct Server<S> {
+    topics: BTreeMap<String, Topic>,
+    retain: usize,
+    storage: Option<S>,
+    next_id: u64,
+}
+
+#[ockam_core::worker]
+impl<S: AuthenticatedStorage> Worker for Server<S> {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        let mut dec = Decoder::new(m.as_body());
+        let req: Request = dec.decode()?;
+        let res = match self.on_request(c, m.return_route(), &req, &mut dec).await {
+            Ok(res) => res,
+            Err(err) => {
+                warn!(target: "ockam_api::pubsub::server", %err, "failed to handle request");
+                api::internal_error(&req, &err.to_string()).to_vec()?
+            }
+        };
+        c.send(m.return_route(), res).await
+    }
+}
+
+impl<S: AuthenticatedStorage> Server<S> {
+    /// Create a server retaining the last `retain` events of every topic.
+    pub fn new(retain: usize) -> Self {
+        Server {
+            topics: BTreeMap::new(),
+            retain,
+            storage: None,
+            next_id: 0,
+        }
+    }
+
+    /// Persist the retained events in the given storage.
+    pub fn with_storage(mut self, storage: S) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    async fn on_request(
+        &mut self,
+        ctx: &Context,
+        return_route: Route,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        trace! {
+            target: "ockam_api::pubsub::server",
+            id     = %req.id(),
+            method = ?req.method(),
+            path   = %req.path(),
+            body   = %req.has_body(),
+            "request"
+        }
+
+        let res = match (req.method(), req.path_segments::<3>().as_slice()) {
+            (Some(Method::Post), ["subscriptions"]) => {
+                if let Err(e) = api::validate_body("subscribe", dec) {
+                    return Ok(api::invalid_body(req, &e).to_vec()?);
+                }
+                let sub: Subscribe = dec.decode()?;
+                let route = match sub.route() {
+                    Some(r) => match MultiAddr::from_str(r)
+                        .ok()
+                        .and_then(|a| multiaddr_to_route(&a))
+                    {
+                        Some(route) => route,
+                        None => return Ok(api::bad_request(req, "invalid route").to_vec()?),
+                    },
+                    None => return_route,
+                };
+                let id = self.next_id;
+                self.next_id += 1;
+                let topic = self.topic(sub.topic()).await?;
+                if sub.replay() {
+                    for (seq, data) in &topic.retained {
+                        let event = Event::new(sub.topic(), *seq, data.as_slice());
+                        ctx.send(route.clone(), minicbor::to_vec(&event)?).await?
+                    }
+                }
+                topic.subscribers.push(Subscriber { id, route });
+                Response::ok(req.id()).body(Subscribed::new(id)).to_vec()?
+            }
+            (Some(Method::Delete), ["subscriptions", id]) => {
+                let id: u64 = match id.parse() {
+                    Ok(id) => id,
+                    Err(_) => return Ok(api::bad_request(req, "invalid subscription").to_vec()?),
+                };
+                let mut found = false;
+                for topic in self.topics.values_mut() {
+                    let len = topic.subscribers.len();
+                    topic.subscribers.retain(|s| s.id != id);
+                    found |= topic.subscribers.len() != len
+                }
+                if found {
+                    Response::ok(req.id()).to_vec()?
+                } else {
+                    Response::not_found(req.id()).to_vec()?
+                }
+            }
+            (Some(Method::Post), ["topics", name]) => {
+                if let Err(e) = api::validate_body("publish", dec) {
+                    return Ok(api::invalid_body(req, &e).to_vec()?);
+                }
+                let publish: Publish = dec.decode()?;
+                let retain = self.retain;
+                let topic = self.topic(name).await?;
+                topic.seq += 1;
+                let seq = topic.seq;
+                let event = minicbor::to_vec(Event::new(*name, seq, publish.data()))?;
+                let mut delivered = 0;
+                let mut failed = Vec::new();
+                for s in &topic.subscribers {
+                    match ctx.send(s.route.clone(), event.clone()).await {
+                        Ok(()) => delivered += 1,
+                        Err(err) => {
+                            warn! {
+                                target: "ockam_api::pubsub::server",
+                                %err,
+                                route = %s.route,
+                                "dropping subscriber"
+                            }
+                            failed.push(s.id)
+                        }
+                    }
+                }
+                topic.subscribers.retain(|s| !failed.contains(&s.id));
+                if retain > 0 {
+                    topic.retained.push_back((seq, publish.data().to_vec()));
+                    while topic.retained.len() > retain {
+                        topic.retained.pop_front();
+                    }
+                    self.save(name).await?
+                }
+                Response::ok(req.id())
+                    .body(Published::new(seq, delivered))
+                    .to_vec()?
+            }
+            (Some(Method::Get), ["topics"]) => {
+                let topics = self
+                    .topics
+                    .iter()
+                    .map(|(name, t)| {
+                        TopicInfo::new(
+                            name.as_str(),
+                            t.subscribers.len() as u64,
+                            t.retained.len() as u64,
+                        )
+                    })
+                    .collect();
+                Response::ok(req.id())
+                    .body(TopicList::new(topics))
+                    .to_vec()?
+            }
+            (Some(_), _) => api::unknown_path(req).to_vec()?,
+            (None, _) => api::invalid_method(req).to_vec()?,
+        };
+
+        Ok(res)
+    }
+
+    /// Get a topic, loading its retained events from the storage if needed.
+    async fn topic(&mut self, name: &str) -> Result<&mut Topic> {
+        if !self.topics.contains_key(name) {
+            let mut topic = Topic::default();
+            if let Some(storage) = &self.storage {
+                if let Some(data) = storage.get(PUBSUB_ID, name).await? {
+                    let events: Vec<Event> = minicbor::decode(&data)?;
+                    for e in events.iter().rev().take(self.retain).rev() {
+                        topic.retained.push_back((e.seq(), e.data().to_vec()))
+                    }
+                    topic.seq = events.last().map(|e| e.seq()).unwrap_or(0);
+                }
+            }
+            self.topics.insert(name.to_string(), topic);
+        }
+        Ok(self.topics.get_mut(name).expect("topic exists"))
+    }
+
+    /// Persist the retained events of a topic.
+    async fn save(&self, name: &str) -> Result<()> {
+        if let (Some(storage), Some(topic)) = (&self.storage, self.topics.get(name)) {
+            let events: Vec<Event> = topic
+                .retained
+                .iter()
+                .map(|(seq, data)| Event::new(name, *seq, data.as_slice()))
+                .collect();
+            storage
+                .set(PUBSUB_ID, name.to_string(), minicbor::to_vec(&events)?)
+                .await?
+        }
+        Ok(())
+    }
+}
+
+pub struct Client {
+    ctx: Context,
+    route: Route,
+    buf: Vec<u8>,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("route", &self.route)
+            .finish()
+    }
+}
+
+impl Client {
+    pub async fn new(r: Route, ctx: &Context) -> Result<Self> {
+        let ctx = ctx.new_detached(Address::random_local()).await?;
+        Ok(Client {
+            ctx,
+            route: r,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Subscribe to a topic, returning the subscription identifier.
+    ///
+    /// The subscription should name the route to deliver events to, as the
+    /// return route of this client only receives responses.
+    pub async fn subscribe(&mut self, sub: Subscribe<'_>) -> Result<u64> {
+        let req = Request::post("/subscriptions").body(sub);
+        self.buf = self.request("subscribe", "subscribe", &req).await?;
+        assert_response_match("subscribed", &self.buf);
+        let mut d = Decoder::new(&self.buf);
+        let res = response("subscribe", &mut d)?;
+        if res.status() == Some(Status::Ok) {
+            let s: Subscribed = d.decode()?;
+            Ok(s.id())
+        } else {
+            Err(error("subscribe", &res, &mut d))
+        }
+    }
+
+    pub async fn unsubscribe(&mut self, id: u64) -> Result<()> {
+        let req = Request::delete(format!("/subscriptions/{id}"));
+        self.buf = self.request("unsubscribe", None, &req).await?;
+        assert_response_match(None, &self.buf);
+        let mut d = Decoder::new(&self.buf);
+        let res = response("unsubscribe", &mut d)?;
+        if res.status() == Some(Status::Ok) {
+            Ok(())
+        } else {
+            Err(error("unsubscribe", &res, &mut d))
+        }
+    }
+
+    pub async fn publish(&mut self, topic: &str, data: &[u8]) -> Result<Published> {
+        let req = Request::post(format!("/topics/{topic}")).body(Publish::new(data));
+        self.buf = self.request("publish", "publish", &req).await?;
+        assert_response_match("published", &self.buf);
+        let mut d = Decoder::new(&self.buf);
+        let res = response("publish", &mut d)?;
+        if res.status() == Some(Status::Ok) {
+            Ok(d.decode()?)
+        } else {
+            Err(error("publish", &res, &mut d))
+        }
+    }
+
+    pub async fn topics(&mut self) -> Result<TopicList<'_>> {
+        let req = Request::get("/topics");
+        self.buf = self.request("topics", None, &req).await?;
+        assert_response_match("topic_list", &self.buf);
+        let mut d = Decoder::new(&self.buf);
+        let res = response("topics", &mut d)?;
+        if res.status() == Some(Status::Ok) {
+            Ok(d.decode()?)
+        } else {
+            Err(error("topics", &res, &mut d))
+        }
+    }
+
+    /// Encode request header and body (if any) and send the package to the server.
+    async fn request<T>(
+        &mut self,
+        label: &str,
+        schema: impl Into<Option<&str>>,
+        req: &RequestBuilder<'_, T>,
+    ) -> Result<Vec<u8>>
+    where
+        T: Encode<()>,
+    {
+        let mut buf = Vec::new();
+        req.encode(&mut buf)?;
+        assert_request_match(schema, &buf);
+        trace! {
+            target: "ockam_api::pubsub::client",
+            id     = %req.header().id(),
+            method = ?req.header().method(),
+            path   = %req.header().path(),
+            body   = %req.header().has_body(),
+            "-> {label}"
+        };
+        let vec: Vec<u8> = self.ctx.send_and_receive(self.route.clone(), buf).await?;
+        Ok(vec)
+    }
+}
+
+/// Decode and log response header.
+fn response(label: &str, dec: &mut Decoder<'_>) -> Result<Response> {
+    let res: Response = dec.decode()?;
+    trace! {
+        target: "ockam_api::pubsub::client",
+        re     = %res.re(),
+        id     = %res.id(),
+        status = ?res.status(),
+        body   = %res.has_body(),
+        "<- {label}"
+    }
+    Ok(res)
+}
+
+/// Decode, log and map response error to ockam_core error.
+fn error(label: &str, res: &Response, dec: &mut Decoder<'_>) -> ockam_core::Error {
+    if res.has_body() {
+        let err = match dec.decode::<Error>() {
+            Ok(e) => e,
+            Err(e) => return e.into(),
+        };
+        warn! {
+            target: "ockam_api::pubsub::client",
+            id     = %res.id(),
+            re     = %res.re(),
+            status = ?res.status(),
+            error  = ?err.message(),
+            "<- {label}"
+        }
+        let msg = err.message().unwrap_or(label);
+        ockam_core::Error::new(Origin::Application, Kind::Protocol, msg)
+    } else {
+        ockam_core::Error::new(Origin::Application, Kind::Protocol, label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route_to_multiaddr;
+    use ockam_core::route;
+    use ockam_identity::authenticated_storage::mem::InMemoryStorage;
+
+    async fn next_event(ctx: &mut Context) -> Result<(u64, Vec<u8>)> {
+        let msg = ctx.receive::<Vec<u8>>().await?.take().body();
+        let event: Event = minicbor::decode(&msg)?;
+        assert_eq!(event.topic(), "news");
+        Ok((event.seq(), event.data().to_vec()))
+    }
+
+    #[ockam_macros::test]
+    async fn events_are_delivered_and_retained(ctx: &mut Context) -> Result<()> {
+        let storage = InMemoryStorage::new();
+        let server = Server::new(2).with_storage(storage.clone());
+        ctx.start_worker("pubsub", server).await?;
+        let mut client = Client::new(route!["pubsub"], ctx).await?;
+
+        let mut sub = ctx.new_detached(Address::random_local()).await?;
+        let to = route_to_multiaddr(&route![sub.address()]).unwrap();
+        let id = client
+            .subscribe(Subscribe::new("news").with_route(to.to_string()))
+            .await?;
+
+        for data in [&b"a"[..], b"b", b"c"] {
+            assert_eq!(client.publish("news", data).await?.delivered(), 1);
+        }
+        assert_eq!(next_event(&mut sub).await?, (1, b"a".to_vec()));
+        assert_eq!(next_event(&mut sub).await?, (2, b"b".to_vec()));
+        assert_eq!(next_event(&mut sub).await?, (3, b"c".to_vec()));
+
+        client.unsubscribe(id).await?;
+        assert_eq!(client.publish("news", b"d").await?.delivered(), 0);
+        assert!(client.unsubscribe(id).await.is_err());
+
+        // A new server replays the retained events from the storage
+        let server = Server::new(2).with_storage(storage);
+        ctx.start_worker("pubsub2", server).await?;
+        let mut client = Client::new(route!["pubsub2"], ctx).await?;
+        let req = Subscribe::new("news")
+            .with_route(to.to_string())
+            .with_replay(true);
+        client.subscribe(req).await?;
+        assert_eq!(next_event(&mut sub).await?, (3, b"c".to_vec()));
+        assert_eq!(next_event(&mut sub).await?, (4, b"d".to_vec()));
+        assert_eq!(client.publish("news", b"e").await?.seq(), 5);
+        assert_eq!(next_event(&mut sub).await?, (5, b"e".to_vec()));
+
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,226 @@
+use minicbor::{Decode, Encode};
+use ockam_core::{CowBytes, CowStr};
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request to receive the events published to a topic.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Subscribe<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<1948263>,
+    #[b(1)] topic: CowStr<'a>,
+    #[b(2)] route: Option<CowStr<'a>>,
+    #[n(3)] replay: bool,
+}
+
+impl<'a> Subscribe<'a> {
+    pub fn new(topic: impl Into<CowStr<'a>>) -> Self {
+        Subscribe {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            topic: topic.into(),
+            route: None,
+            replay: false,
+        }
+    }
+
+    /// Deliver events to the given route instead of the return route of
+    /// the subscription request.
+    pub fn with_route(mut self, route: impl Into<CowStr<'a>>) -> Self {
+        self.route = Some(route.into());
+        self
+    }
+
+    /// Deliver the events retained by the topic before new ones.
+    pub fn with_replay(mut self, replay: bool) -> Self {
+        self.replay = replay;
+        self
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    pub fn route(&self) -> Option<&str> {
+        self.route.as_deref()
+    }
+
+    pub fn replay(&self) -> bool {
+        self.replay
+    }
+}
+
+#[derive(Debug, Clone, Copy, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Subscribed {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7725106>,
+    #[n(1)] id: u64,
+}
+
+impl Subscribed {
+    pub fn new(id: u64) -> Self {
+        Subscribed {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            id,
+        }
+    }
+
+    /// Identifier to cancel the subscription with.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Publish<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6081349>,
+    #[b(1)] data: CowBytes<'a>,
+}
+
+impl<'a> Publish<'a> {
+    pub fn new(data: impl Into<CowBytes<'a>>) -> Self {
+        Publish {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            data: data.into(),
+        }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[derive(Debug, Clone, Copy, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Published {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2318570>,
+    #[n(1)] seq: u64,
+    #[n(2)] delivered: u64,
+}
+
+impl Published {
+    pub fn new(seq: u64, delivered: u64) -> Self {
+        Published {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            seq,
+            delivered,
+        }
+    }
+
+    /// Sequence number of the event in its topic.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Number of subscribers the event was delivered to.
+    pub fn delivered(&self) -> u64 {
+        self.delivered
+    }
+}
+
+/// An event delivered to subscribers.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Event<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5593814>,
+    #[b(1)] topic: CowStr<'a>,
+    #[n(2)] seq: u64,
+    #[b(3)] data: CowBytes<'a>,
+}
+
+impl<'a> Event<'a> {
+    pub fn new(topic: impl Into<CowStr<'a>>, seq: u64, data: impl Into<CowBytes<'a>>) -> Self {
+        Event {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            topic: topic.into(),
+            seq,
+            data: data.into(),
+        }
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TopicInfo<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<8046627>,
+    #[b(1)] name: CowStr<'a>,
+    #[n(2)] subscribers: u64,
+    #[n(3)] retained: u64,
+}
+
+impl<'a> TopicInfo<'a> {
+    pub fn new(name: impl Into<CowStr<'a>>, subscribers: u64, retained: u64) -> Self {
+        TopicInfo {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            name: name.into(),
+            subscribers,
+            retained,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn subscribers(&self) -> u64 {
+        self.subscribers
+    }
+
+    pub fn retained(&self) -> u64 {
+        self.retained
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TopicList<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4170938>,
+    #[b(1)] topics: Vec<TopicInfo<'a>>,
+}
+
+impl<'a> TopicList<'a> {
+    pub fn new(topics: Vec<TopicInfo<'a>>) -> Self {
+        TopicList {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            topics,
+        }
+    }
+
+    pub fn topics(&self) -> &[TopicInfo<'a>] {
+        &self.topics
+    }
+}
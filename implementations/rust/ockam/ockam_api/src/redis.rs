@@ -0,0 +1,82 @@
+use ::redis::aio::ConnectionManager;
+use ::redis::AsyncCommands;
+use ockam_core::async_trait;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use ockam_identity::authenticated_storage::AuthenticatedStorage;
+use std::fmt;
+
+/// Redis AuthenticatedStorage implementation
+///
+/// The entries of an identity are stored in a hash, named after the
+/// identity and a prefix shared by the nodes using the same state, e.g.
+/// the replicas of an authority.
+#[derive(Clone)]
+pub struct RedisStorage {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+impl fmt::Debug for RedisStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisStorage")
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+impl RedisStorage {
+    /// Connect to the Redis server at `url`, e.g. `redis://127.0.0.1:6379`.
+    pub async fn new(url: &str, prefix: impl Into<String>) -> Result<Self> {
+        let client = ::redis::Client::open(url).map_err(map_redis_err)?;
+        let conn = ConnectionManager::new(client)
+            .await
+            .map_err(map_redis_err)?;
+        Ok(RedisStorage {
+            conn,
+            prefix: prefix.into(),
+        })
+    }
+
+    fn hash(&self, id: &str) -> String {
+        format!("{}:{id}", self.prefix)
+    }
+}
+
+#[async_trait]
+impl AuthenticatedStorage for RedisStorage {
+    async fn get(&self, id: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        let mut conn = self.conn.clone();
+        conn.hget(self.hash(id), key).await.map_err(map_redis_err)
+    }
+
+    async fn set(&self, id: &str, key: String, val: Vec<u8>) -> Result<()> {
+        let mut conn = self.conn.clone();
+        conn.hset(self.hash(id), key, val)
+            .await
+            .map_err(map_redis_err)
+    }
+
+    async fn del(&self, id: &str, key: &str) -> Result<()> {
+        let mut conn = self.conn.clone();
+        conn.hdel(self.hash(id), key).await.map_err(map_redis_err)
+    }
+
+    async fn take(&self, id: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        let mut conn = self.conn.clone();
+        let hash = self.hash(id);
+        // MULTI/EXEC, so that no other client can read the value in between
+        let (val, _): (Option<Vec<u8>>, u64) = ::redis::pipe()
+            .atomic()
+            .hget(&hash, key)
+            .hdel(&hash, key)
+            .query_async(&mut conn)
+            .await
+            .map_err(map_redis_err)?;
+        Ok(val)
+    }
+}
+
+fn map_redis_err(err: ::redis::RedisError) -> Error {
+    Error::new(Origin::Application, Kind::Io, err)
+}
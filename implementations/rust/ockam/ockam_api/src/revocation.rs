@@ -0,0 +1,530 @@
+//! Distribution of the identities revoked by an authority.
+//!
+//! An authority keeps a versioned list of revoked identities and serves it,
+//! signed with its identity, with a [`Server`]. Other nodes periodically
+//! fetch it with [`sync`], only accept lists signed by the authority, and
+//! reject revoked identities in their secure channel listeners and
+//! credential verifiers. A revocation takes effect on a node at most one
+//! sync interval after the authority published it, and closes the channels
+//! the revoked identity had opened to the node's listeners.
+//!
+//! A node whose list is older than the maximum staleness of its sync, for
+//! example because the authority is unreachable, fails closed and rejects
+//! every identity until the next successful sync.
+
+pub mod types;
+
+use core::fmt;
+use minicbor::{Decoder, Encode};
+use ockam_core::api::{self, assert_request_match, assert_response_match};
+use ockam_core::api::{Error, Method, Request, RequestBuilder, Response, Status};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::vault::Signature;
+use ockam_core::{self, async_trait, Address, Result, Route, Routed, Worker};
+use ockam_identity::authenticated_storage::AuthenticatedStorage;
+use ockam_identity::{
+    Identity, IdentityIdentifier, IdentityVault, ListenerChannels, PublicIdentity,
+    SecureChannelTrustInfo, TrustPolicy,
+};
+use ockam_node::tokio;
+use ockam_node::Context;
+use std::collections::BTreeSet;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{trace, warn};
+
+use self::types::{RevocationList, SignedRevocationList};
+
+/// Default time between two syncs of the revocation list.
+pub const DEFAULT_SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Maximum staleness of a synced list, in sync intervals, unless the sync
+/// request sets one.
+pub const DEFAULT_MAX_STALENESS_INTERVALS: u32 = 10;
+
+/// Storage identifier and keys of the revocation list, the time of its last
+/// sync and the sync request of the node.
+pub(crate) const REVOCATIONS_ID: &str = "revocations";
+const REVOCATIONS_KEY: &str = "list";
+const SYNCED_AT_KEY: &str = "synced_at";
+pub(crate) const SYNC_KEY: &str = "sync";
+
+#[derive(Debug, Default)]
+struct State {
+    version: u64,
+    revoked: BTreeSet<IdentityIdentifier>,
+    /// Seconds since the UNIX epoch of the last successful sync.
+    synced_at: Option<u64>,
+    max_staleness: Option<Duration>,
+}
+
+/// The set of revoked identities known to a node
+///
+/// As a [`TrustPolicy`] it only trusts identities which are not revoked,
+/// and none at all while the list is stale.
+#[derive(Clone, Default)]
+pub struct Revocations {
+    state: Arc<RwLock<State>>,
+    listeners: Arc<RwLock<Vec<ListenerChannels>>>,
+}
+
+impl fmt::Debug for Revocations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Revocations")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl Revocations {
+    /// Load the revocation list saved in the given storage, if any.
+    pub async fn load<S: AuthenticatedStorage>(storage: &S) -> Result<Self> {
+        let revocations = Self::default();
+        if let Some(data) = storage.get(REVOCATIONS_ID, REVOCATIONS_KEY).await? {
+            let list: RevocationList = minicbor::decode(&data)?;
+            revocations.update(&list);
+        }
+        if let Some(data) = storage.get(REVOCATIONS_ID, SYNCED_AT_KEY).await? {
+            let mut state = revocations.state.write().unwrap_or_else(|e| e.into_inner());
+            state.synced_at = Some(minicbor::decode(&data)?);
+        }
+        Ok(revocations)
+    }
+
+    /// Save the revocation list, and the time of its last sync, in the given
+    /// storage.
+    pub async fn save<S: AuthenticatedStorage>(&self, storage: &S) -> Result<()> {
+        let data = minicbor::to_vec(self.list())?;
+        storage
+            .set(REVOCATIONS_ID, REVOCATIONS_KEY.to_string(), data)
+            .await?;
+        let synced_at = self
+            .state
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .synced_at;
+        if let Some(t) = synced_at {
+            let data = minicbor::to_vec(t)?;
+            storage
+                .set(REVOCATIONS_ID, SYNCED_AT_KEY.to_string(), data)
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub fn is_revoked(&self, identity: &IdentityIdentifier) -> bool {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state.revoked.contains(identity)
+    }
+
+    /// Fail closed once the list was not synced for longer than the
+    /// maximum staleness. Lists which are not synced are never stale.
+    pub fn set_max_staleness(&self, max: Option<Duration>) {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state.max_staleness = max;
+    }
+
+    /// Record a successful sync of the list.
+    pub fn mark_synced(&self) {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state.synced_at = Some(now());
+    }
+
+    /// Whether the list was not synced within its maximum staleness.
+    pub fn is_stale(&self) -> bool {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        match (state.max_staleness, state.synced_at) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(max), Some(t)) => now().saturating_sub(t) > max.as_secs(),
+        }
+    }
+
+    /// Whether `identity` has to be rejected, either because it is revoked
+    /// or because the list is stale.
+    pub fn rejects(&self, identity: &IdentityIdentifier) -> bool {
+        self.is_stale() || self.is_revoked(identity)
+    }
+
+    /// Track the channels of a listener, to close the ones of identities
+    /// which get revoked.
+    pub fn watch(&self, channels: ListenerChannels) {
+        let mut listeners = self.listeners.write().unwrap_or_else(|e| e.into_inner());
+        listeners.push(channels)
+    }
+
+    /// Stop the channels revoked identities still have open on the
+    /// watched listeners.
+    pub async fn close_revoked_channels(&self, ctx: &Context) {
+        let addresses = {
+            let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+            let listeners = self.listeners.read().unwrap_or_else(|e| e.into_inner());
+            let mut addresses = Vec::new();
+            for id in &state.revoked {
+                for channels in listeners.iter() {
+                    let found = channels.addresses_of(id);
+                    if !found.is_empty() {
+                        info!(identity = %id, "closing secure channel of revoked identity");
+                    }
+                    addresses.extend(found)
+                }
+            }
+            addresses
+        };
+        for addr in addresses {
+            if let Err(err) = ctx.stop_worker(addr.clone()).await {
+                debug!(%addr, %err, "failed to stop secure channel worker")
+            }
+        }
+    }
+
+    pub fn list(&self) -> RevocationList {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        RevocationList::new(state.version, state.revoked.iter().cloned().collect())
+    }
+
+    /// Revoke an identity, returning `false` if it was already revoked.
+    ///
+    /// Only used by the authority publishing the list.
+    pub fn revoke(&self, identity: IdentityIdentifier) -> bool {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        if state.revoked.insert(identity) {
+            state.version += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Replace the revoked identities if the list is newer than the current one.
+    pub fn update(&self, list: &RevocationList) -> bool {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        if list.version() <= state.version {
+            return false;
+        }
+        state.version = list.version();
+        state.revoked = list.revoked().iter().cloned().collect();
+        true
+    }
+}
+
+#[async_trait]
+impl TrustPolicy for Revocations {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        if self.is_stale() {
+            let identity = trust_info.their_identity_id();
+            warn!(%identity, "revocation list is stale, rejecting identity");
+            return Ok(false);
+        }
+        let revoked = self.is_revoked(trust_info.their_identity_id());
+        if revoked {
+            warn!(identity = %trust_info.their_identity_id(), "rejecting revoked identity")
+        }
+        Ok(!revoked)
+    }
+}
+
+/// Serves the revocation list of an authority, signed with its identity.
+pub struct Server<V: IdentityVault> {
+    revocations: Revocations,
+    identity: Identity<V>,
+}
+
+#[ockam_core::worker]
+impl<V> Worker for Server<V>
+where
+    V: IdentityVault,
+{
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        let r = self.on_request(m.as_body()).await?;
+        c.send(m.return_route(), r).await
+    }
+}
+
+impl<V> Server<V>
+where
+    V: IdentityVault,
+{
+    /// Serve `revocations`, signed with the authority `identity`.
+    pub fn new(revocations: Revocations, identity: Identity<V>) -> Self {
+        Server {
+            revocations,
+            identity,
+        }
+    }
+
+    async fn on_request(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut dec = Decoder::new(data);
+        let req: Request = dec.decode()?;
+
+        trace! {
+            target: "ockam_api::revocation::server",
+            id     = %req.id(),
+            method = ?req.method(),
+            path   = %req.path(),
+            body   = %req.has_body(),
+            "request"
+        }
+
+        let res = match req.method() {
+            Some(Method::Get) => match req.path_segments::<2>().as_slice() {
+                ["revoked"] => {
+                    let data = minicbor::to_vec(self.revocations.list())?;
+                    let signature = self.identity.create_signature(&data, None).await?;
+                    Response::ok(req.id())
+                        .body(SignedRevocationList::new(data, signature.as_ref().to_vec()))
+                        .to_vec()?
+                }
+                _ => api::unknown_path(&req).to_vec()?,
+            },
+            _ => api::invalid_method(&req).to_vec()?,
+        };
+
+        Ok(res)
+    }
+}
+
+pub struct Client {
+    ctx: Context,
+    route: Route,
+    buf: Vec<u8>,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("route", &self.route)
+            .finish()
+    }
+}
+
+impl Client {
+    pub async fn new(r: Route, ctx: &Context) -> Result<Self> {
+        let ctx = ctx.new_detached(Address::random_local()).await?;
+        Ok(Client {
+            ctx,
+            route: r,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Fetch the revocation list, failing unless it is signed by `authority`.
+    pub async fn revoked(
+        &mut self,
+        authority: &PublicIdentity,
+        vault: &impl IdentityVault,
+    ) -> Result<RevocationList> {
+        let req = Request::get("/revoked");
+        self.buf = self.request("revoked", None, &req).await?;
+        assert_response_match("signed_revocation_list", &self.buf);
+        let mut d = Decoder::new(&self.buf);
+        let res: Response = d.decode()?;
+        if res.status() == Some(Status::Ok) {
+            let signed: SignedRevocationList = d.decode()?;
+            let signature = Signature::new(signed.signature().to_vec());
+            let data = signed.unverified_data();
+            if !authority
+                .verify_signature(&signature, data, None, vault)
+                .await?
+            {
+                return Err(ockam_core::Error::new(
+                    Origin::Application,
+                    Kind::Invalid,
+                    "revocation list not signed by the authority",
+                ));
+            }
+            Ok(minicbor::decode(data)?)
+        } else {
+            let msg = if res.has_body() {
+                d.decode::<Error>()?.message().map(|m| m.to_string())
+            } else {
+                None
+            };
+            Err(ockam_core::Error::new(
+                Origin::Application,
+                Kind::Protocol,
+                msg.unwrap_or_else(|| "failed to get the revocation list".to_string()),
+            ))
+        }
+    }
+
+    /// Encode request header and body (if any) and send the package to the server.
+    async fn request<T>(
+        &mut self,
+        label: &str,
+        schema: impl Into<Option<&str>>,
+        req: &RequestBuilder<'_, T>,
+    ) -> Result<Vec<u8>>
+    where
+        T: Encode<()>,
+    {
+        let mut buf = Vec::new();
+        req.encode(&mut buf)?;
+        assert_request_match(schema, &buf);
+        trace! {
+            target: "ockam_api::revocation::client",
+            id     = %req.header().id(),
+            method = ?req.header().method(),
+            path   = %req.header().path(),
+            body   = %req.header().has_body(),
+            "-> {label}"
+        };
+        let vec: Vec<u8> = self.ctx.send_and_receive(self.route.clone(), buf).await?;
+        Ok(vec)
+    }
+}
+
+/// Fetch the revocation list of `authority` every `interval` and update
+/// `revocations`.
+///
+/// Lists not signed by the authority are rejected. Updated lists are saved
+/// in `storage`, and the channels of newly revoked identities are closed.
+/// This function never returns.
+pub async fn sync<S: AuthenticatedStorage, V: IdentityVault>(
+    mut client: Client,
+    authority: PublicIdentity,
+    vault: V,
+    revocations: Revocations,
+    storage: S,
+    interval: Duration,
+) {
+    loop {
+        match client.revoked(&authority, &vault).await {
+            Ok(list) => {
+                revocations.mark_synced();
+                if revocations.update(&list) {
+                    debug!(version = %list.version(), "revocation list updated");
+                    revocations.close_revoked_channels(&client.ctx).await
+                }
+                if let Err(err) = revocations.save(&storage).await {
+                    warn!(%err, "failed to save the revocation list")
+                }
+            }
+            Err(err) => warn!(%err, "failed to sync the revocation list"),
+        }
+        tokio::time::sleep(interval).await
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::{route, AsyncTryClone};
+    use ockam_identity::authenticated_storage::mem::InMemoryStorage;
+    use ockam_identity::{KeyExchangeMode, TrustEveryonePolicy};
+    use ockam_vault::Vault;
+
+    #[ockam_macros::test]
+    async fn revocations_are_synced(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let authority = Identity::create(ctx, &vault).await?;
+        let published = Revocations::default();
+        let alice = IdentityIdentifier::from_key_id("alice");
+        assert!(published.revoke(alice.clone()));
+        assert!(!published.revoke(alice.clone()));
+        let server = Server::new(published.clone(), authority.async_try_clone().await?);
+        ctx.start_worker("revocation", server).await?;
+
+        let storage = InMemoryStorage::new();
+        let synced = Revocations::load(&storage).await?;
+        assert!(!synced.is_revoked(&alice));
+
+        let public = authority.to_public().await?;
+        let mut client = Client::new(route!["revocation"], ctx).await?;
+        assert!(synced.update(&client.revoked(&public, &vault).await?));
+        assert!(synced.is_revoked(&alice));
+        synced.save(&storage).await?;
+        assert!(Revocations::load(&storage).await?.is_revoked(&alice));
+
+        // An older list is ignored
+        assert!(!synced.update(&RevocationList::new(0, Vec::new())));
+        assert!(!synced.check(&SecureChannelTrustInfo::new(alice)).await?);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn forged_lists_are_rejected(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let authority = Identity::create(ctx, &vault).await?;
+        let forger = Identity::create(ctx, &vault).await?;
+
+        // A list with a higher version, served by someone else
+        let forged = Revocations::default();
+        for id in ["alice", "bob"] {
+            forged.revoke(IdentityIdentifier::from_key_id(id));
+        }
+        ctx.start_worker("forged", Server::new(forged, forger))
+            .await?;
+
+        let public = authority.to_public().await?;
+        let mut client = Client::new(route!["forged"], ctx).await?;
+        assert!(client.revoked(&public, &vault).await.is_err());
+
+        ctx.stop().await
+    }
+
+    #[test]
+    fn stale_lists_reject_everyone() {
+        let revocations = Revocations::default();
+        let bob = IdentityIdentifier::from_key_id("bob");
+        assert!(!revocations.rejects(&bob));
+
+        // A synced list which was never fetched is stale
+        revocations.set_max_staleness(Some(Duration::from_secs(60)));
+        assert!(revocations.rejects(&bob));
+        revocations.mark_synced();
+        assert!(!revocations.rejects(&bob));
+
+        // Sync long ago
+        revocations.state.write().unwrap().synced_at = Some(now() - 61);
+        assert!(revocations.is_stale());
+        assert!(revocations.rejects(&bob));
+    }
+
+    #[ockam_macros::test]
+    async fn revoked_channels_are_closed(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+        let storage = InMemoryStorage::new();
+
+        let revocations = Revocations::default();
+        let channels = bob
+            .create_secure_channel_listener_with_key_exchange(
+                "listener",
+                revocations.clone(),
+                &storage,
+                Default::default(),
+                KeyExchangeMode::Classic,
+            )
+            .await?;
+        revocations.watch(channels.clone());
+
+        alice
+            .create_secure_channel(route!["listener"], TrustEveryonePolicy, &storage)
+            .await?;
+        ctx.sleep(Duration::from_millis(100)).await;
+        let addresses = channels.addresses_of(alice.identifier());
+        assert_eq!(addresses.len(), 2);
+
+        let mut list = revocations.list().revoked().to_vec();
+        list.push(alice.identifier().clone());
+        assert!(revocations.update(&RevocationList::new(1, list)));
+        revocations.close_revoked_channels(ctx).await;
+        ctx.sleep(Duration::from_millis(100)).await;
+        assert!(channels.addresses_of(alice.identifier()).is_empty());
+
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,156 @@
+use minicbor::{Decode, Encode};
+use ockam_core::{CowBytes, CowStr};
+use ockam_identity::IdentityIdentifier;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request body to revoke an identity on an authority node.
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Revoke {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4472209>,
+    #[n(1)] identity: IdentityIdentifier,
+}
+
+impl Revoke {
+    pub fn new(identity: IdentityIdentifier) -> Self {
+        Revoke {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            identity,
+        }
+    }
+
+    pub fn identity(&self) -> &IdentityIdentifier {
+        &self.identity
+    }
+}
+
+/// The identities revoked by an authority.
+///
+/// The version is increased by the authority on every revocation, so that
+/// an older list never replaces a newer one.
+#[derive(Debug, Clone, Default, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RevocationList {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6170351>,
+    #[n(1)] version: u64,
+    #[n(2)] revoked: Vec<IdentityIdentifier>,
+}
+
+impl RevocationList {
+    pub fn new(version: u64, revoked: Vec<IdentityIdentifier>) -> Self {
+        RevocationList {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            version,
+            revoked,
+        }
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn revoked(&self) -> &[IdentityIdentifier] {
+        &self.revoked
+    }
+}
+
+/// A [`RevocationList`] signed by the authority publishing it.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SignedRevocationList<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3528917>,
+    /// CBOR-encoded [`RevocationList`].
+    #[b(1)] data: CowBytes<'a>,
+    /// Signature of the data by the root key of the authority.
+    #[b(2)] signature: CowBytes<'a>,
+}
+
+impl<'a> SignedRevocationList<'a> {
+    pub fn new(data: impl Into<CowBytes<'a>>, signature: impl Into<CowBytes<'a>>) -> Self {
+        SignedRevocationList {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            data: data.into(),
+            signature: signature.into(),
+        }
+    }
+
+    /// The encoded list, which may only be decoded once the signature is
+    /// verified.
+    pub fn unverified_data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+}
+
+/// Request body to periodically sync the revocation list of an authority.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SyncRevocations<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<9025583>,
+    #[b(1)] route: CowStr<'a>,
+    #[n(2)] interval: Option<u64>,
+    #[n(3)] authority: Option<IdentityIdentifier>,
+    #[n(4)] max_staleness: Option<u64>,
+}
+
+impl<'a> SyncRevocations<'a> {
+    pub fn new(route: impl Into<CowStr<'a>>, interval: Option<u64>) -> Self {
+        SyncRevocations {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            route: route.into(),
+            interval,
+            authority: None,
+            max_staleness: None,
+        }
+    }
+
+    /// Only accept lists signed by the given authority of the node.
+    pub fn with_authority(mut self, authority: IdentityIdentifier) -> Self {
+        self.authority = Some(authority);
+        self
+    }
+
+    /// Reject every identity once the list was not synced for this many
+    /// seconds.
+    pub fn with_max_staleness(mut self, secs: u64) -> Self {
+        self.max_staleness = Some(secs);
+        self
+    }
+
+    /// Multiaddr of the revocation service of the authority.
+    pub fn route(&self) -> &str {
+        &self.route
+    }
+
+    /// Seconds between two syncs.
+    pub fn interval(&self) -> Option<u64> {
+        self.interval
+    }
+
+    /// Authority signing the list, needed if the node has several ones.
+    pub fn authority(&self) -> Option<&IdentityIdentifier> {
+        self.authority.as_ref()
+    }
+
+    /// Seconds after which an unsynced list is stale.
+    pub fn max_staleness(&self) -> Option<u64> {
+        self.max_staleness
+    }
+}
@@ -2,27 +2,35 @@ mod sessions;
 pub(crate) mod util;
 
 use crate::{multiaddr_to_route, DefaultAddress};
+use core::fmt;
 use minicbor::{Decode, Encode};
 use ockam::{LocalMessage, Route, TransportMessage, Worker};
 use ockam_core::compat::sync::{Arc, Mutex};
-use ockam_core::{Address, Decodable, Encodable, Error, Routed, LOCAL};
+use ockam_core::{async_trait, Address, Decodable, Encodable, Error, Routed, LOCAL};
 use ockam_multiaddr::MultiAddr;
 use ockam_node::tokio;
 use ockam_node::tokio::sync::mpsc;
 use ockam_node::tokio::task::JoinSet;
 use ockam_node::tokio::time::{timeout, Duration};
 use ockam_node::Context;
-use sessions::{Key, Ping, Status};
+use sessions::Ping;
+use std::time::Instant;
 use tracing as log;
+use tracing::Instrument;
 
-pub use sessions::{Data, Replacer, Session, Sessions};
+pub use sessions::{Data, Key, Probe, Replacer, Session, Sessions, Status};
 
 const MAX_FAILURES: usize = 3;
 const DELAY: Duration = Duration::from_secs(3);
 
+/// Upper bound of the delay between replacement attempts of a session,
+/// which doubles from [`DELAY`] with every failed attempt.
+const MAX_REPLACEMENT_DELAY: Duration = Duration::from_secs(60);
+
 #[derive(Debug)]
 pub struct Medic {
     delay: Duration,
+    clock: Arc<dyn Clock>,
     sessions: Arc<Mutex<Sessions>>,
     pings: JoinSet<(Key, Result<(), Error>)>,
     replacements: JoinSet<(Key, Result<MultiAddr, Error>)>,
@@ -35,10 +43,50 @@ pub struct Message {
     #[n(1)] ping: Ping,
 }
 
+/// The time source of the [`Medic`], checked against heartbeat probes.
+pub trait Clock: fmt::Debug + Send + Sync + 'static {
+    fn now(&self) -> Instant;
+}
+
+/// The system time.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Sends the pings of the [`Medic`].
+///
+/// Pongs are expected back as [`Message`]s at the collector address.
+#[async_trait]
+pub trait Pinger: Send + Sync + 'static {
+    async fn ping(&self, route: Route, m: Message) -> Result<(), Error>;
+}
+
+/// Sends pings from a node context.
+struct ContextPinger(Context);
+
+#[async_trait]
+impl Pinger for ContextPinger {
+    async fn ping(&self, route: Route, m: Message) -> Result<(), Error> {
+        let v = Encodable::encode(&m)?;
+        let t = TransportMessage::v1(route, Collector::address(), v);
+        self.0.forward(LocalMessage::new(t, Vec::new())).await
+    }
+}
+
 impl Medic {
     pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
         Self {
             delay: DELAY,
+            clock,
             sessions: Arc::new(Mutex::new(Sessions::new())),
             pings: JoinSet::new(),
             replacements: JoinSet::new(),
@@ -54,69 +102,77 @@ impl Medic {
         let (tx, rx) = mpsc::channel(32);
         ctx.start_worker(Collector::address(), Collector(tx))
             .await?;
-        self.go(ctx, rx).await
+        self.go(Arc::new(ContextPinger(ctx)), rx).await
     }
 
     /// Continuously check all sessions.
     ///
-    /// This method never returns. It will ping all healthy sessions and
+    /// This method never returns. It will probe all healthy sessions and
     /// trigger replacements for the unhealthy ones.
-    async fn go(mut self, ctx: Context, mut rx: mpsc::Receiver<Message>) -> ! {
-        let ctx = Arc::new(ctx);
+    async fn go(mut self, pinger: Arc<dyn Pinger>, mut rx: mpsc::Receiver<Message>) -> ! {
         loop {
-            log::debug!("check sessions");
-            {
-                let mut sessions = self.sessions.lock().unwrap();
-                for (&key, session) in sessions.iter_mut() {
-                    if session.pings().len() < MAX_FAILURES {
-                        let m = Message::new(session.key());
-                        session.add_ping(m.ping);
-                        let l = {
-                            let v = Encodable::encode(&m).expect("message can be encoded");
-                            let r: Route =
-                                if let Some(r) = multiaddr_to_route(session.ping_address()) {
-                                    r.clone()
-                                        .modify()
-                                        .append(DefaultAddress::ECHO_SERVICE)
-                                        .into()
-                                } else {
-                                    log::error! {
-                                        key  = %key,
-                                        addr = %session.ping_address(),
-                                        "failed to convert address to route"
-                                    }
-                                    continue;
-                                };
-                            log::debug! {
-                                key  = %key,
-                                addr = %session.ping_address(),
-                                ping = %m.ping,
-                                "send ping"
-                            }
-                            let t = TransportMessage::v1(r, Collector::address(), v);
-                            LocalMessage::new(t, Vec::new())
-                        };
-                        let sender = ctx.clone();
-                        self.pings
-                            .spawn(async move { (key, sender.forward(l).await) });
-                    } else {
-                        match session.status() {
-                            Status::Up => {
-                                log::warn!(%key, "session unresponsive");
-                                let f = session.replacement(session.ping_address().clone());
-                                session.set_status(Status::Down);
-                                log::info!(%key, "replacing session");
-                                self.replacements.spawn(async move { (key, f.await) });
-                            }
-                            Status::Down => {
-                                log::warn!(%key, "session is down");
-                            }
-                        }
+            self.check_sessions(&pinger);
+            let _ = timeout(self.delay, self.get_results(&mut rx)).await;
+        }
+    }
+
+    /// Send pings to the responsive sessions and start replacing the
+    /// unresponsive ones.
+    fn check_sessions(&mut self, pinger: &Arc<dyn Pinger>) {
+        log::debug!("check sessions");
+        let now = self.clock.now();
+        let mut sessions = self.sessions.lock().unwrap();
+        for (&key, session) in sessions.iter_mut() {
+            if session.is_responsive(MAX_FAILURES, now) {
+                let service = match session.probe() {
+                    Probe::Echo => DefaultAddress::ECHO_SERVICE.to_string(),
+                    Probe::Service(s) => s.clone(),
+                    Probe::Heartbeat(_) => continue,
+                };
+                let r: Route = if let Some(r) = multiaddr_to_route(session.ping_address()) {
+                    r.modify().append(service).into()
+                } else {
+                    log::error! {
+                        key  = %key,
+                        addr = %session.ping_address(),
+                        "failed to convert address to route"
+                    }
+                    continue;
+                };
+                let m = Message::new(session.key());
+                session.add_ping(m.ping);
+                log::debug! {
+                    key  = %key,
+                    addr = %session.ping_address(),
+                    ping = %m.ping,
+                    "send ping"
+                }
+                let pinger = pinger.clone();
+                self.pings
+                    .spawn(async move { (key, pinger.ping(r, m).await) });
+            } else {
+                match session.status() {
+                    Status::Up => {
+                        log::warn!(%key, "session unresponsive");
+                        let f = session.replacement(session.ping_address().clone());
+                        session.set_status(Status::Down);
+                        log::info!(%key, "replacing session");
+                        let span = log::info_span!("replace_session", %key);
+                        self.replacements
+                            .spawn(async move { (key, f.await) }.instrument(span));
+                    }
+                    Status::Down if session.take_retry(now) => {
+                        let f = session.replacement(session.ping_address().clone());
+                        log::info!(%key, "replacing session again");
+                        let span = log::info_span!("replace_session", %key);
+                        self.replacements
+                            .spawn(async move { (key, f.await) }.instrument(span));
+                    }
+                    Status::Down => {
+                        log::warn!(%key, "session is down");
                     }
                 }
             }
-
-            let _ = timeout(self.delay, self.get_results(&mut rx)).await;
         }
     }
 
@@ -130,39 +186,49 @@ impl Medic {
                     Some(Ok((k, Ok(())))) => log::debug!(key = %k, "sent ping"),
                 },
                 r = self.replacements.join_next(), if !self.replacements.is_empty() => match r {
-                    None                  => log::debug!("no replacements"),
-                    Some(Err(e))          => log::error!("task failed: {e:?}"),
-                    Some(Ok((k, Err(e)))) => {
-                        let mut sessions = self.sessions.lock().unwrap();
-                        if let Some(s) = sessions.session_mut(&k) {
-                            log::warn!(key = %k, err = %e, "replacing session failed");
-                            let f = s.replacement(s.ping_address().clone());
-                            log::info!(key = %k, "replacing session");
-                            self.replacements.spawn(async move { (k, f.await) });
-                        }
-                    }
-                    Some(Ok((k, Ok(a)))) => {
-                        let mut sessions = self.sessions.lock().unwrap();
-                        if let Some(s) = sessions.session_mut(&k) {
-                            log::info!(key = %k, addr = %a, "replacement is up");
-                            s.set_status(Status::Up);
-                            s.set_ping_address(a);
-                            s.clear_pings();
-                        }
-                    }
-                },
-                Some(m) = rx.recv() => {
-                    if let Some(s) = self.sessions.lock().unwrap().session_mut(&m.key) {
-                        if s.pings().contains(&m.ping) {
-                            log::debug!(key = %m.key, ping = %m.ping, "recv pong");
-                            s.clear_pings()
-                        }
-                    }
+                    None              => log::debug!("no replacements"),
+                    Some(Err(e))      => log::error!("task failed: {e:?}"),
+                    Some(Ok((k, r)))  => self.on_replacement(k, r)
                 },
+                Some(m) = rx.recv() => self.on_pong(m),
                 else => break
             }
         }
     }
+
+    /// Mark a replaced session as up, or schedule another attempt to
+    /// replace it, backing off after every failed attempt.
+    fn on_replacement(&mut self, k: Key, r: Result<MultiAddr, Error>) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(s) = sessions.session_mut(&k) {
+            match r {
+                Err(e) => {
+                    let delay = DELAY
+                        .checked_mul(1 << s.failed_replacements().min(16))
+                        .map_or(MAX_REPLACEMENT_DELAY, |d| d.min(MAX_REPLACEMENT_DELAY));
+                    log::warn!(key = %k, err = %e, retry_in = ?delay, "replacing session failed");
+                    s.replacement_failed(self.clock.now() + delay);
+                }
+                Ok(a) => {
+                    log::info!(key = %k, addr = %a, "replacement is up");
+                    s.set_status(Status::Up);
+                    s.set_ping_address(a);
+                    s.clear_pings();
+                    s.heartbeat(self.clock.now());
+                    s.replacement_succeeded();
+                }
+            }
+        }
+    }
+
+    fn on_pong(&self, m: Message) {
+        if let Some(s) = self.sessions.lock().unwrap().session_mut(&m.key) {
+            if s.pings().contains(&m.ping) {
+                log::debug!(key = %m.key, ping = %m.ping, "recv pong");
+                s.clear_pings()
+            }
+        }
+    }
 }
 
 impl Message {
@@ -216,3 +282,206 @@ impl Worker for Collector {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ApiError;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A clock which only moves when told to.
+    #[derive(Debug)]
+    struct MockClock(Mutex<Instant>);
+
+    impl MockClock {
+        fn advance(&self, d: Duration) {
+            *self.0.lock().unwrap() += d
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    /// Records the pings instead of sending them.
+    #[derive(Default)]
+    struct FakePinger(Mutex<Vec<Message>>);
+
+    #[async_trait]
+    impl Pinger for FakePinger {
+        async fn ping(&self, _: Route, m: Message) -> Result<(), Error> {
+            self.0.lock().unwrap().push(m);
+            Ok(())
+        }
+    }
+
+    /// Drives a medic step by step, with simulated time and pings.
+    struct Harness {
+        medic: Medic,
+        clock: Arc<MockClock>,
+        pinger: Arc<FakePinger>,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            let clock = Arc::new(MockClock(Mutex::new(Instant::now())));
+            Self {
+                medic: Medic::with_clock(clock.clone()),
+                clock,
+                pinger: Arc::new(FakePinger::default()),
+            }
+        }
+
+        fn add(&self, s: Session) -> Key {
+            self.medic.sessions.lock().unwrap().add(s)
+        }
+
+        /// Check the sessions once, and wait for the pings and replacements
+        /// which were started. Retries of failed replacements are left for
+        /// the next check.
+        async fn check(&mut self) {
+            let pinger: Arc<dyn Pinger> = self.pinger.clone();
+            self.medic.check_sessions(&pinger);
+            while let Some(p) = self.medic.pings.join_next().await {
+                p.unwrap().1.unwrap()
+            }
+            let mut done = Vec::new();
+            while let Some(r) = self.medic.replacements.join_next().await {
+                done.push(r.unwrap())
+            }
+            for (k, r) in done {
+                self.medic.on_replacement(k, r)
+            }
+        }
+
+        /// Answer the pings sent so far.
+        fn pong(&self) {
+            let pings: Vec<Message> = self.pinger.0.lock().unwrap().drain(..).collect();
+            for m in pings {
+                self.medic.on_pong(m)
+            }
+        }
+
+        fn sent(&self) -> usize {
+            self.pinger.0.lock().unwrap().len()
+        }
+
+        fn status(&self, k: &Key) -> (Status, MultiAddr) {
+            let sessions = self.medic.sessions.lock().unwrap();
+            let s = sessions.session(k).unwrap();
+            (s.status(), s.ping_address().clone())
+        }
+    }
+
+    /// A session with a replacer which fails `fails` times before moving
+    /// the session to `/service/replaced`, counting its calls.
+    fn session(fails: usize) -> (Session, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut s = Session::new("/service/original".parse().unwrap());
+        let c = calls.clone();
+        s.set_replacer(Box::new(move |_| {
+            let n = c.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                if n < fails {
+                    Err(ApiError::generic("replacement failed"))
+                } else {
+                    Ok("/service/replaced".parse().unwrap())
+                }
+            })
+        }));
+        (s, calls)
+    }
+
+    #[ockam_macros::test]
+    async fn answered_sessions_are_kept(ctx: &mut Context) -> ockam_core::Result<()> {
+        let mut h = Harness::new();
+        let (s, calls) = session(0);
+        let k = h.add(s);
+        for _ in 0..2 * MAX_FAILURES {
+            h.check().await;
+            assert_eq!(h.sent(), 1);
+            h.pong()
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        assert_eq!(h.status(&k).0, Status::Up);
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn unanswered_sessions_are_replaced(ctx: &mut Context) -> ockam_core::Result<()> {
+        let mut h = Harness::new();
+        let (s, calls) = session(0);
+        let k = h.add(s);
+        for _ in 0..MAX_FAILURES {
+            h.check().await;
+        }
+        assert_eq!(h.sent(), MAX_FAILURES);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        h.check().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let (status, addr) = h.status(&k);
+        assert_eq!(status, Status::Up);
+        assert_eq!(addr.to_string(), "/service/replaced");
+
+        // The replacement starts with no unanswered pings.
+        h.check().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn failed_replacements_are_retried(ctx: &mut Context) -> ockam_core::Result<()> {
+        let mut h = Harness::new();
+        let (s, calls) = session(2);
+        let k = h.add(s);
+        for _ in 0..=MAX_FAILURES {
+            h.check().await;
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(h.status(&k).0, Status::Down);
+
+        // Failed attempts are retried after a delay, doubling every time.
+        h.check().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        h.clock.advance(DELAY);
+        h.check().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(h.status(&k).0, Status::Down);
+        h.clock.advance(DELAY);
+        h.check().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        h.clock.advance(DELAY);
+        h.check().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(h.status(&k).0, Status::Up);
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn late_heartbeats_replace_sessions(ctx: &mut Context) -> ockam_core::Result<()> {
+        let mut h = Harness::new();
+        let (mut s, calls) = session(0);
+        s.set_probe(Probe::Heartbeat(Duration::from_secs(10)));
+        s.heartbeat(h.clock.now());
+        let k = h.add(s);
+
+        h.clock.advance(Duration::from_secs(9));
+        h.check().await;
+        assert_eq!(h.sent(), 0);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        h.clock.advance(Duration::from_secs(2));
+        h.check().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(h.status(&k).0, Status::Up);
+
+        // The replacement counts as a heartbeat.
+        h.clock.advance(Duration::from_secs(9));
+        h.check().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        ctx.stop().await
+    }
+}
@@ -9,6 +9,7 @@ use ockam_core::compat::rand;
 use ockam_core::Error;
 use ockam_multiaddr::MultiAddr;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tracing as log;
 
 pub type Replacement = Pin<Box<dyn Future<Output = Result<MultiAddr, Error>> + Send>>;
@@ -26,11 +27,30 @@ pub struct Session {
     status: Status,
     replace: Replacer,
     pings: Vec<Ping>,
+    probe: Probe,
+    last_heartbeat: Instant,
+    /// Failed replacement attempts since the session was last up.
+    failed_replacements: u32,
+    /// When to attempt the next replacement after a failed one.
+    retry_at: Option<Instant>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Data(Arc<Mutex<HashMap<&'static str, Box<dyn Any + Send>>>>);
 
+/// How the medic checks that a session is alive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Probe {
+    /// Ping the echo service at the ping address.
+    Echo,
+    /// Ping the given service at the ping address, which sends pings back
+    /// like the echo service.
+    Service(String),
+    /// Send no pings, but expect heartbeats to be recorded with
+    /// [`Session::heartbeat`] at least this often.
+    Heartbeat(Duration),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Status {
     Down,
@@ -44,10 +64,17 @@ impl fmt::Debug for Session {
             .field("addr", &self.addr)
             .field("status", &self.status)
             .field("pings", &self.pings)
+            .field("probe", &self.probe)
             .finish()
     }
 }
 
+impl Default for Probe {
+    fn default() -> Self {
+        Probe::Echo
+    }
+}
+
 impl Sessions {
     pub fn new() -> Self {
         Self {
@@ -76,6 +103,10 @@ impl Sessions {
         self.map.get_mut(k)
     }
 
+    pub fn remove(&mut self, k: &Key) -> Option<Session> {
+        self.map.remove(k)
+    }
+
     #[allow(unused)]
     pub fn iter(&self) -> impl Iterator<Item = (&Key, &Session)> + '_ {
         self.map.iter()
@@ -84,6 +115,25 @@ impl Sessions {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (&Key, &mut Session)> + '_ {
         self.map.iter_mut()
     }
+
+    /// Pairs of sessions where the first one depends on the second one.
+    ///
+    /// A session depends on another one if its ping address goes through the
+    /// ping address of the other one, e.g. a route through a secure channel
+    /// which is itself supervised by a session.
+    pub fn dependencies(&self) -> Vec<(Key, Key)> {
+        let mut deps = Vec::new();
+        for (k, s) in &self.map {
+            let addr = s.ping_address().to_string();
+            for (other, o) in &self.map {
+                let prefix = o.ping_address().to_string();
+                if k != other && addr.starts_with(&format!("{prefix}/")) {
+                    deps.push((*k, *other))
+                }
+            }
+        }
+        deps
+    }
 }
 
 impl Session {
@@ -95,6 +145,10 @@ impl Session {
             status: Status::Up,
             replace: Box::new(move |r| Box::pin(async move { Ok(r) })),
             pings: Vec::new(),
+            probe: Probe::default(),
+            last_heartbeat: Instant::now(),
+            failed_replacements: 0,
+            retry_at: None,
         }
     }
 
@@ -141,6 +195,58 @@ impl Session {
     pub fn clear_pings(&mut self) {
         self.pings.clear()
     }
+
+    pub fn failed_replacements(&self) -> u32 {
+        self.failed_replacements
+    }
+
+    /// Count a failed replacement, to be attempted again at `retry_at`.
+    pub fn replacement_failed(&mut self, retry_at: Instant) {
+        self.failed_replacements += 1;
+        self.retry_at = Some(retry_at)
+    }
+
+    /// Whether a failed replacement is due to be attempted again, in which
+    /// case it is only due again after the next failure.
+    pub fn take_retry(&mut self, now: Instant) -> bool {
+        match self.retry_at {
+            Some(t) if t <= now => {
+                self.retry_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Forget the failed replacements, once a replacement is up.
+    pub fn replacement_succeeded(&mut self) {
+        self.failed_replacements = 0;
+        self.retry_at = None
+    }
+
+    pub fn probe(&self) -> &Probe {
+        &self.probe
+    }
+
+    pub fn set_probe(&mut self, p: Probe) {
+        self.probe = p
+    }
+
+    /// Record that the session was alive at the given time.
+    pub fn heartbeat(&mut self, t: Instant) {
+        self.last_heartbeat = t
+    }
+
+    /// Whether the session passes its probe at the given time, given how
+    /// many pings may be left unanswered.
+    pub fn is_responsive(&self, max_failures: usize, now: Instant) -> bool {
+        match &self.probe {
+            Probe::Heartbeat(period) => {
+                now.saturating_duration_since(self.last_heartbeat) < *period
+            }
+            Probe::Echo | Probe::Service(_) => self.pings.len() < max_failures,
+        }
+    }
 }
 
 impl Data {
@@ -189,3 +295,37 @@ impl fmt::Display for Ping {
         write!(f, "{:x}", self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_depend_on_their_route_prefixes() {
+        let mut sessions = Sessions::new();
+        let channel = sessions.add(Session::new("/node/n1/secure/api".parse().unwrap()));
+        let inlet = sessions.add(Session::new(
+            "/node/n1/secure/api/service/outlet".parse().unwrap(),
+        ));
+        let other = sessions.add(Session::new("/node/n2/service/echo".parse().unwrap()));
+
+        let deps = sessions.dependencies();
+        assert_eq!(deps, vec![(inlet, channel)]);
+        assert!(deps.iter().all(|(a, b)| *a != other && *b != other));
+    }
+
+    #[test]
+    fn sessions_are_probed_as_configured() {
+        let mut s = Session::new("/service/echo".parse().unwrap());
+        let now = Instant::now();
+        s.add_ping(Ping::new());
+        assert!(s.is_responsive(2, now));
+        s.add_ping(Ping::new());
+        assert!(!s.is_responsive(2, now));
+
+        s.set_probe(Probe::Heartbeat(Duration::from_secs(60)));
+        assert!(s.is_responsive(2, now));
+        s.set_probe(Probe::Heartbeat(Duration::ZERO));
+        assert!(!s.is_responsive(2, now));
+    }
+}
@@ -1,9 +1,15 @@
+use crate::error::ApiError;
+use ockam_core::Result;
 use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Secure, Tcp};
 use ockam_multiaddr::{Match, MultiAddr, Protocol};
+use std::net::{IpAddr, SocketAddr};
 use std::time::Duration;
 
 pub(crate) const MAX_RECOVERY_TIME: Duration = Duration::from_secs(10);
 pub(crate) const MAX_CONNECT_TIME: Duration = Duration::from_secs(5);
+/// How long a secure channel has to answer a ping before it is considered
+/// dead.
+pub(crate) const MAX_PROBE_TIME: Duration = Duration::from_secs(2);
 
 pub(crate) fn starts_with_host_tcp_secure(addr: &MultiAddr) -> Option<usize> {
     let host_match = Match::any([DnsAddr::CODE, Ip4::CODE, Ip6::CODE]);
@@ -13,3 +19,97 @@ pub(crate) fn starts_with_host_tcp_secure(addr: &MultiAddr) -> Option<usize> {
         None
     }
 }
+
+/// Replace the host names of an address with the IP addresses they
+/// resolve to now.
+///
+/// Connections are made to hosts by name, so that a route to a host which
+/// moved to another IP address keeps being routed over the connection to
+/// its previous address. Replacing the names makes a new connection.
+pub(crate) fn resolve_hosts<F>(addr: &MultiAddr, resolve: F) -> Result<MultiAddr>
+where
+    F: Fn(&str) -> Result<SocketAddr>,
+{
+    let mut resolved = MultiAddr::default();
+    let mut it = addr.iter().peekable();
+    while let Some(p) = it.next() {
+        if p.code() == DnsAddr::CODE {
+            if let Some(port) = it.peek().and_then(|p| p.cast::<Tcp>()) {
+                let host = p
+                    .cast::<DnsAddr>()
+                    .ok_or_else(|| ApiError::message("invalid dnsaddr protocol in multiaddr"))?;
+                let peer = resolve(&format!("{}:{}", &*host, *port))?;
+                debug!(host = %&*host, %peer, "resolved host");
+                match peer.ip() {
+                    IpAddr::V4(ip) => resolved.push_back(Ip4(ip))?,
+                    IpAddr::V6(ip) => resolved.push_back(Ip6(ip))?,
+                }
+                resolved.push_back(Tcp(peer.port()))?;
+                it.next();
+                continue;
+            }
+        }
+        resolved.push_back_value(&p)?;
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use ockam_core::errcode::{Kind, Origin};
+    use ockam_core::Error;
+
+    #[test]
+    fn hosts_are_resolved_again() {
+        // A host which moves to another address after the first lookup.
+        let lookups = Cell::new(0);
+        let resolve = |peer: &str| {
+            assert_eq!(peer, "relay.example.com:4000");
+            lookups.set(lookups.get() + 1);
+            let ip = if lookups.get() == 1 {
+                "10.0.0.1"
+            } else {
+                "10.0.0.2"
+            };
+            Ok(SocketAddr::new(ip.parse().unwrap(), 4000))
+        };
+        let addr: MultiAddr = "/dnsaddr/relay.example.com/tcp/4000/secure/api/service/echo"
+            .parse()
+            .unwrap();
+
+        let first = resolve_hosts(&addr, resolve).unwrap();
+        assert_eq!(
+            first.to_string(),
+            "/ip4/10.0.0.1/tcp/4000/secure/api/service/echo"
+        );
+        let second = resolve_hosts(&addr, resolve).unwrap();
+        assert_eq!(
+            second.to_string(),
+            "/ip4/10.0.0.2/tcp/4000/secure/api/service/echo"
+        );
+    }
+
+    #[test]
+    fn addresses_without_hosts_are_unchanged() {
+        let resolve = |_: &str| -> Result<SocketAddr> { panic!("nothing to resolve") };
+        for a in ["/ip4/127.0.0.1/tcp/4000/service/api", "/service/a/secure/b"] {
+            let addr: MultiAddr = a.parse().unwrap();
+            assert_eq!(resolve_hosts(&addr, resolve).unwrap(), addr);
+        }
+    }
+
+    #[test]
+    fn unresolvable_hosts_fail() {
+        let resolve = |_: &str| -> Result<SocketAddr> {
+            Err(Error::new(
+                Origin::Transport,
+                Kind::NotFound,
+                "unknown host",
+            ))
+        };
+        let addr: MultiAddr = "/dnsaddr/gone.example.com/tcp/4000".parse().unwrap();
+        assert!(resolve_hosts(&addr, resolve).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! Export of tracing spans to an OpenTelemetry collector.
+//!
+//! Exporting is only available with the `telemetry` feature, and enabled by
+//! setting the [`OCKAM_OTLP_ENDPOINT`] environment variable. Without it the
+//! functions propagating the trace context of node API requests are no-ops.
+
+use ockam_core::api::{Request, RequestBuilder};
+
+/// Environment variable holding the URL of the OTLP/HTTP traces endpoint
+/// of the collector, e.g. `http://localhost:4318/v1/traces`.
+pub const OCKAM_OTLP_ENDPOINT: &str = "OCKAM_OTLP_ENDPOINT";
+
+/// Key of the W3C trace context header.
+#[cfg(feature = "telemetry")]
+const TRACEPARENT: &str = "traceparent";
+
+/// Create a tracing layer exporting spans to the collector set in
+/// [`OCKAM_OTLP_ENDPOINT`], if any.
+#[cfg(feature = "telemetry")]
+pub fn layer<S>(service_name: &'static str) -> Option<impl tracing_subscriber::Layer<S>>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    use opentelemetry::sdk::propagation::TraceContextPropagator;
+    use opentelemetry::sdk::{trace, Resource};
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+
+    let endpoint = std::env::var(OCKAM_OTLP_ENDPOINT).ok()?;
+    if endpoint.is_empty() {
+        return None;
+    }
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    let exporter = opentelemetry_otlp::new_exporter()
+        .http()
+        .with_endpoint(&endpoint);
+    let config =
+        trace::config().with_resource(Resource::new([KeyValue::new("service.name", service_name)]));
+    // The simple exporter runs on its own thread, so that spans can be
+    // exported before and after the async runtime exists.
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .with_trace_config(config)
+        .install_simple();
+    match tracer {
+        Ok(tracer) => Some(tracing_opentelemetry::layer().with_tracer(tracer)),
+        Err(err) => {
+            eprintln!("Failed to export traces to {endpoint}: {err}");
+            None
+        }
+    }
+}
+
+/// Flush the spans not exported yet.
+#[cfg(feature = "telemetry")]
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider()
+}
+
+/// Set the trace context of a request to the one of the current span.
+#[cfg(feature = "telemetry")]
+pub fn inject_trace_context<T>(req: RequestBuilder<'_, T>) -> RequestBuilder<'_, T> {
+    use std::collections::HashMap;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    let cx = tracing::Span::current().context();
+    let mut carrier = HashMap::new();
+    opentelemetry::global::get_text_map_propagator(|p| p.inject_context(&cx, &mut carrier));
+    match carrier.remove(TRACEPARENT) {
+        Some(tp) => req.trace_context(tp),
+        None => req,
+    }
+}
+
+/// Set the trace context of a request to the one of the current span.
+#[cfg(not(feature = "telemetry"))]
+pub fn inject_trace_context<T>(req: RequestBuilder<'_, T>) -> RequestBuilder<'_, T> {
+    req
+}
+
+/// Make the caller's span, as given by the trace context of the request,
+/// the parent of `span`.
+#[cfg(feature = "telemetry")]
+pub fn set_parent_context(span: &tracing::Span, req: &Request<'_>) {
+    use std::collections::HashMap;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    if let Some(tp) = req.trace_context() {
+        let carrier = HashMap::from([(TRACEPARENT.to_string(), tp.to_string())]);
+        let cx = opentelemetry::global::get_text_map_propagator(|p| p.extract(&carrier));
+        span.set_parent(cx)
+    }
+}
+
+/// Make the caller's span, as given by the trace context of the request,
+/// the parent of `span`.
+#[cfg(not(feature = "telemetry"))]
+pub fn set_parent_context(_span: &tracing::Span, _req: &Request<'_>) {}
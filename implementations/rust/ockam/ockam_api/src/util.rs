@@ -251,3 +251,28 @@ fn clean_multiaddr_simple() {
     let new_route = multiaddr_to_route(&new_addr).unwrap();
     println!("{:#?}", new_route);
 }
+
+#[test]
+fn ip6_multiaddr_route_roundtrip() {
+    use std::net::{Ipv6Addr, SocketAddr, SocketAddrV6};
+
+    let addr: MultiAddr = "/ip6/::1/tcp/4000/service/echoer".parse().unwrap();
+    let route = multiaddr_to_route(&addr).unwrap();
+    let tcp = route.iter().next().unwrap();
+    assert_eq!(tcp.transport_type(), TCP);
+    assert_eq!(tcp.address(), "[::1]:4000");
+    assert_eq!(route_to_multiaddr(&route).unwrap(), addr);
+
+    let lookup = {
+        let mut map = ConfigLookup::new();
+        map.set_node(
+            "hub",
+            SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::LOCALHOST, 666, 0, 0)).into(),
+        );
+        map
+    };
+    let addr: MultiAddr = "/node/hub/service/echoer".parse().unwrap();
+    let (new_addr, _) = clean_multiaddr(&addr, &lookup).unwrap();
+    assert_eq!(new_addr.to_string(), "/ip6/::1/tcp/666/service/echoer");
+    assert!(is_local_node(&new_addr).unwrap());
+}
@@ -3,7 +3,7 @@ pub mod types;
 use either::Either;
 use minicbor::Decoder;
 use ockam_core::api::{self, Id, ResponseBuilder};
-use ockam_core::api::{Error, Method, Request, Response};
+use ockam_core::api::{Error, ErrorCode, Method, Request, Response};
 use ockam_core::{self, Result, Routed, Worker};
 use ockam_identity::credential::{Credential, CredentialData, Verified};
 use ockam_identity::{IdentityVault, PublicIdentity};
@@ -11,10 +11,12 @@ use ockam_node::Context;
 use tracing::trace;
 
 use self::types::{VerifyRequest, VerifyResponse};
+use crate::revocation::Revocations;
 
 #[derive(Debug)]
 pub struct Verifier<V> {
     vault: V,
+    revocations: Option<Revocations>,
 }
 
 #[ockam_core::worker]
@@ -36,7 +38,16 @@ where
     V: IdentityVault,
 {
     pub fn new(vault: V) -> Self {
-        Self { vault }
+        Self {
+            vault,
+            revocations: None,
+        }
+    }
+
+    /// Reject the credentials of revoked subjects.
+    pub fn with_revocations(mut self, revocations: Revocations) -> Self {
+        self.revocations = Some(revocations);
+        self
     }
 
     async fn on_request(&mut self, data: &[u8]) -> Result<Vec<u8>> {
@@ -62,9 +73,26 @@ where
         let res = match req.method() {
             Some(Method::Post) => match req.path_segments::<2>().as_slice() {
                 ["verify"] => {
+                    if let Err(e) = api::validate_body("verify_request", &dec) {
+                        return Ok(api::invalid_body(&req, &e).to_vec()?);
+                    }
                     let vr: VerifyRequest = dec.decode()?;
                     let cr: Credential = minicbor::decode(vr.credential())?;
-                    match self.verify(req.id(), &vr, &cr).await {
+                    if let Some(revocations) = &self.revocations {
+                        if revocations.rejects(vr.subject()) {
+                            // A stale list may miss the revocation of the subject
+                            let msg = if revocations.is_stale() {
+                                "stale revocation list"
+                            } else {
+                                "revoked subject"
+                            };
+                            let err = Error::new("/verify")
+                                .with_message(msg)
+                                .with_code(ErrorCode::RevokedSubject);
+                            return Ok(Response::forbidden(req.id()).body(err).to_vec()?);
+                        }
+                    }
+                    match verify_credential(&self.vault, req.id(), "/verify", &vr, &cr).await {
                         Ok(Either::Left(err)) => err.to_vec()?,
                         Ok(Either::Right(dat)) => {
                             let exp = dat.expires_at();
@@ -84,34 +112,49 @@ where
 
         Ok(res)
     }
+}
 
-    async fn verify<'a>(
-        &self,
-        id: Id,
-        req: &'a VerifyRequest<'a>,
-        cre: &'a Credential<'a>,
-    ) -> Result<Either<ResponseBuilder<Error<'_>>, CredentialData<'a, Verified>>> {
-        let data = CredentialData::try_from(cre)?;
-
-        let ident = if let Some(ident) = req.authority(data.unverfied_issuer()) {
-            PublicIdentity::import(ident, &self.vault).await?
-        } else {
-            let err = Error::new("/verify").with_message("unauthorised issuer");
-            return Ok(Either::Left(Response::unauthorized(id).body(err)));
-        };
+/// Verify a credential against the authorities and attributes of a request.
+///
+/// Only the given authority identities are used, so no channel to the
+/// authority is needed. Verification failures are returned as error
+/// responses for `path`.
+pub async fn verify_credential<'a, V: IdentityVault>(
+    vault: &V,
+    id: Id,
+    path: &'a str,
+    req: &'a VerifyRequest<'a>,
+    cre: &'a Credential<'a>,
+) -> Result<Either<ResponseBuilder<Error<'a>>, CredentialData<'a, Verified>>> {
+    let data = CredentialData::try_from(cre)?;
 
-        let data = match ident
-            .verify_credential(cre, req.subject(), &self.vault)
-            .await
-        {
-            Ok(data) => data,
-            Err(err) => {
-                let err = Error::new("/verify")
-                    .with_message(format!("error verifying a credential: {}", err));
-                return Ok(Either::Left(Response::forbidden(id).body(err)));
-            }
-        };
+    let ident = if let Some(ident) = req.authority(data.unverfied_issuer()) {
+        PublicIdentity::import(ident, vault).await?
+    } else {
+        let err = Error::new(path)
+            .with_message("unauthorised issuer")
+            .with_code(ErrorCode::UnknownIssuer);
+        return Ok(Either::Left(Response::unauthorized(id).body(err)));
+    };
 
-        Ok(Either::Right(data))
+    let data = match ident.verify_credential(cre, req.subject(), vault).await {
+        Ok(data) => data,
+        Err(err) => {
+            let err = Error::new(path)
+                .with_message(format!("error verifying a credential: {}", err))
+                .with_code(ErrorCode::InvalidCredential);
+            return Ok(Either::Left(Response::forbidden(id).body(err)));
+        }
+    };
+
+    for (key, expected) in req.attributes() {
+        if data.attributes().get(key) != Some(expected.as_bytes()) {
+            let err = Error::new(path)
+                .with_message(format!("credential attribute {key} does not match"))
+                .with_code(ErrorCode::AttributeMismatch);
+            return Ok(Either::Left(Response::forbidden(id).body(err)));
+        }
     }
+
+    Ok(Either::Right(data))
 }
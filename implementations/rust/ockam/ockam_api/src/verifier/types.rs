@@ -1,6 +1,6 @@
 use minicbor::{Decode, Encode};
 use ockam_core::compat::borrow::Cow;
-use ockam_core::CowBytes;
+use ockam_core::{CowBytes, CowStr};
 use ockam_identity::credential::{Attributes, Timestamp};
 use ockam_identity::IdentityIdentifier;
 use std::collections::BTreeMap;
@@ -16,7 +16,8 @@ pub struct VerifyRequest<'a> {
     #[n(0)] tag: TypeTag<4592146>,
     #[b(1)] cred: CowBytes<'a>,
     #[n(2)] subj: IdentityIdentifier,
-    #[b(3)] auth: BTreeMap<IdentityIdentifier, CowBytes<'a>>
+    #[b(3)] auth: BTreeMap<IdentityIdentifier, CowBytes<'a>>,
+    #[b(4)] attrs: Option<BTreeMap<CowStr<'a>, CowStr<'a>>>
 }
 
 #[derive(Debug, Decode, Encode)]
@@ -37,9 +38,22 @@ impl<'a> VerifyRequest<'a> {
             cred: CowBytes(cred.into()),
             subj,
             auth: BTreeMap::new(),
+            attrs: None,
         }
     }
 
+    /// Require the credential to contain the given attribute value.
+    pub fn with_attribute<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<CowStr<'a>>,
+        V: Into<CowStr<'a>>,
+    {
+        self.attrs
+            .get_or_insert_with(BTreeMap::new)
+            .insert(key.into(), value.into());
+        self
+    }
+
     pub fn with_authority<T>(mut self, id: IdentityIdentifier, identity: T) -> Self
     where
         T: Into<Cow<'a, [u8]>>,
@@ -63,6 +77,13 @@ impl<'a> VerifyRequest<'a> {
     pub fn authority(&self, id: &IdentityIdentifier) -> Option<&CowBytes<'a>> {
         self.auth.get(id)
     }
+
+    pub fn attributes(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.attrs
+            .iter()
+            .flatten()
+            .map(|(k, v)| (k.as_ref(), v.as_ref()))
+    }
 }
 
 impl<'a> VerifyResponse<'a> {
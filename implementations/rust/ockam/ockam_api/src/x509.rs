@@ -0,0 +1,257 @@
+//! Bridge between Ockam identities and X.509 certificates.
+//!
+//! The root key of an identity can be exported as a PKCS#10 certificate
+//! signing request, signed by the identity itself and whose common name is
+//! the identity identifier. A certificate issued by a CA for that request
+//! binds the identity to the PKI: environments which only trust PKI anchors
+//! can then cross-validate an identity with [`verify_certificate`].
+//!
+//! Ed25519 and NIST P-256 (ECDSA with SHA-256) keys are supported, for the
+//! identities as well as for the CAs.
+use crate::error::ApiError;
+use ockam::identity::{Identity, IdentityVault, PublicIdentity};
+use ockam_core::vault::{PublicKey, SecretType, Signature};
+use ockam_core::Result;
+use x509_parser::oid_registry::{
+    OID_EC_P256, OID_KEY_TYPE_EC_PUBLIC_KEY, OID_SIG_ECDSA_WITH_SHA256, OID_SIG_ED25519,
+};
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// `2.5.4.3`, common name
+const OID_COMMON_NAME: &[u8] = &[0x06, 0x03, 0x55, 0x04, 0x03];
+/// `1.3.101.112`, Ed25519 keys and signatures
+const OID_ED25519: &[u8] = &[0x06, 0x03, 0x2b, 0x65, 0x70];
+/// `1.2.840.10045.2.1`, elliptic curve public key
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+/// `1.2.840.10045.3.1.7`, NIST P-256 curve
+const OID_P256: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+/// `1.2.840.10045.4.3.2`, ECDSA with SHA-256 signatures
+const OID_ECDSA_SHA256: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+
+/// Create a DER encoded certificate signing request for the root key of the identity
+pub async fn create_csr<V: IdentityVault>(identity: &Identity<V>) -> Result<Vec<u8>> {
+    let public_key = identity.to_public().await?.get_root_public_key()?;
+    let (key_algorithm, signature_algorithm) = match public_key.stype() {
+        SecretType::Ed25519 => (sequence(&[OID_ED25519]), sequence(&[OID_ED25519])),
+        SecretType::NistP256 => (
+            sequence(&[OID_EC_PUBLIC_KEY, OID_P256]),
+            sequence(&[OID_ECDSA_SHA256]),
+        ),
+        stype => {
+            return Err(ApiError::message(format!(
+                "{:?} keys can't be used in certificates",
+                stype
+            )))
+        }
+    };
+
+    let common_name = der(0x0c, identity.identifier().to_string().as_bytes());
+    let subject = sequence(&[&der(0x31, &sequence(&[OID_COMMON_NAME, &common_name]))]);
+    let subject_public_key_info = sequence(&[&key_algorithm, &bit_string(public_key.data())]);
+    let info = sequence(&[
+        // Version 1
+        &[0x02, 0x01, 0x00],
+        &subject,
+        &subject_public_key_info,
+        // No attributes
+        &[0xa0, 0x00],
+    ]);
+
+    let signature = identity.create_signature(&info, None).await?;
+    let signature = match public_key.stype() {
+        SecretType::NistP256 => p256::ecdsa::Signature::try_from(signature.as_ref())
+            .map_err(|_| ApiError::generic("Invalid P-256 signature"))?
+            .to_der()
+            .as_bytes()
+            .to_vec(),
+        _ => signature.as_ref().to_vec(),
+    };
+
+    Ok(sequence(&[
+        &info,
+        &signature_algorithm,
+        &bit_string(&signature),
+    ]))
+}
+
+/// Return the public key bound by a DER encoded certificate
+pub fn certificate_public_key(certificate: &[u8]) -> Result<PublicKey> {
+    public_key(&parse(certificate)?)
+}
+
+/// Verify that a DER encoded certificate is currently valid, issued by the
+/// given CA certificate and binds the root key of the identity
+pub async fn verify_certificate(
+    vault: &impl IdentityVault,
+    certificate: &[u8],
+    ca_certificate: &[u8],
+    identity: &PublicIdentity,
+) -> Result<()> {
+    let certificate = parse(certificate)?;
+    let ca_certificate = parse(ca_certificate)?;
+
+    if !ca_certificate.is_ca() {
+        return Err(ApiError::generic("The issuer certificate is not a CA"));
+    }
+    if certificate.issuer().as_raw() != ca_certificate.subject().as_raw() {
+        return Err(ApiError::generic(
+            "The certificate was not issued by the CA",
+        ));
+    }
+    if !certificate.validity().is_valid() || !ca_certificate.validity().is_valid() {
+        return Err(ApiError::generic("The certificate has expired"));
+    }
+    if !verify_signature(vault, &certificate, &public_key(&ca_certificate)?).await? {
+        return Err(ApiError::generic("Invalid certificate signature"));
+    }
+    if public_key(&certificate)? != identity.get_root_public_key()? {
+        return Err(ApiError::generic(
+            "The certificate doesn't bind the identity root key",
+        ));
+    }
+    Ok(())
+}
+
+fn parse(certificate: &[u8]) -> Result<X509Certificate<'_>> {
+    X509Certificate::from_der(certificate)
+        .map(|(_, c)| c)
+        .map_err(|e| ApiError::message(format!("Invalid certificate: {}", e)))
+}
+
+fn public_key(certificate: &X509Certificate<'_>) -> Result<PublicKey> {
+    let spki = certificate.public_key();
+    let data = spki.subject_public_key.data.to_vec();
+    let algorithm = &spki.algorithm.algorithm;
+    if *algorithm == OID_SIG_ED25519 && data.len() == 32 {
+        Ok(PublicKey::new(data, SecretType::Ed25519))
+    } else if *algorithm == OID_KEY_TYPE_EC_PUBLIC_KEY && data.len() == 65 {
+        // EC keys name their curve in the algorithm parameters
+        match spki.algorithm.parameters.as_ref().map(|p| p.as_oid()) {
+            Some(Ok(curve)) if curve == OID_EC_P256 => {
+                Ok(PublicKey::new(data, SecretType::NistP256))
+            }
+            _ => Err(ApiError::generic("Unsupported certificate key curve")),
+        }
+    } else {
+        Err(ApiError::generic("Unsupported certificate key type"))
+    }
+}
+
+async fn verify_signature(
+    vault: &impl IdentityVault,
+    certificate: &X509Certificate<'_>,
+    issuer_key: &PublicKey,
+) -> Result<bool> {
+    let signature = certificate.signature_value.data.to_vec();
+    let algorithm = &certificate.signature_algorithm.algorithm;
+    let signature = match issuer_key.stype() {
+        SecretType::Ed25519 if *algorithm == OID_SIG_ED25519 => signature,
+        SecretType::NistP256 if *algorithm == OID_SIG_ECDSA_WITH_SHA256 => {
+            match p256::ecdsa::Signature::from_der(&signature) {
+                Ok(signature) => signature.as_ref().to_vec(),
+                Err(_) => return Ok(false),
+            }
+        }
+        _ => return Err(ApiError::generic("Unsupported certificate signature")),
+    };
+    vault
+        .verify(
+            &Signature::new(signature),
+            issuer_key,
+            certificate.tbs_certificate.as_ref(),
+        )
+        .await
+}
+
+/// Encode a DER value with the given tag
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut output = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        output.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let bytes = &bytes[bytes.iter().take_while(|b| **b == 0).count()..];
+        output.push(0x80 | bytes.len() as u8);
+        output.extend_from_slice(bytes);
+    }
+    output.extend_from_slice(content);
+    output
+}
+
+fn sequence(items: &[&[u8]]) -> Vec<u8> {
+    der(0x30, &items.concat())
+}
+
+fn bit_string(data: &[u8]) -> Vec<u8> {
+    // No unused bits
+    let mut content = vec![0x00];
+    content.extend_from_slice(data);
+    der(0x03, &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::Context;
+    use ockam_vault::Vault;
+    use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa};
+
+    fn ca() -> rcgen::Certificate {
+        let mut params = CertificateParams::new(vec![]);
+        params.alg = &rcgen::PKCS_ECDSA_P256_SHA256;
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "Ockam test CA");
+        rcgen::Certificate::from_params(params).unwrap()
+    }
+
+    #[ockam_macros::test]
+    async fn certificate_binds_identity(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let identity = Identity::create(ctx, &vault).await?;
+        let other = Identity::create(ctx, &vault).await?;
+
+        let csr = create_csr(&identity).await?;
+        // The CA checks the signature of the request before issuing the certificate
+        let csr = rcgen::CertificateSigningRequest::from_der(&csr).unwrap();
+        let ca = ca();
+        let certificate = csr.serialize_der_with_signer(&ca).unwrap();
+        let ca_certificate = ca.serialize_der().unwrap();
+
+        let public_identity = identity.to_public().await?;
+        assert_eq!(
+            certificate_public_key(&certificate)?,
+            public_identity.get_root_public_key()?
+        );
+        verify_certificate(&vault, &certificate, &ca_certificate, &public_identity).await?;
+
+        // Another identity isn't bound by the certificate
+        let other = other.to_public().await?;
+        assert!(
+            verify_certificate(&vault, &certificate, &ca_certificate, &other)
+                .await
+                .is_err()
+        );
+
+        // Keys on another curve than P-256 are refused, here prime239v3
+        let at = ca_certificate
+            .windows(OID_P256.len())
+            .position(|w| w == OID_P256)
+            .unwrap();
+        let mut other_curve = ca_certificate.clone();
+        other_curve[at + OID_P256.len() - 1] = 0x06;
+        assert!(certificate_public_key(&other_curve).is_err());
+
+        // The certificate isn't issued by another CA
+        let other_ca = ca().serialize_der().unwrap();
+        assert!(
+            verify_certificate(&vault, &certificate, &other_ca, &public_identity)
+                .await
+                .is_err()
+        );
+
+        ctx.stop().await
+    }
+}
@@ -1,14 +1,21 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use ockam::identity::authenticated_storage::mem::InMemoryStorage;
+use ockam::identity::credential::{Credential, MAX_CREDENTIAL_VALIDITY};
 use ockam::identity::Identity;
 use ockam::route;
 use ockam::vault::Vault;
 use ockam_api::authenticator::direct;
-use ockam_api::authenticator::direct::types::Enroller;
-use ockam_core::Result;
+use ockam_api::authenticator::direct::types::{
+    AddMember, CreateToken, EnrollMany, EnrollMember, Enroller,
+};
+use ockam_api::revocation::Revocations;
+use ockam_api::verifier::types::VerifyRequest;
+use ockam_api::verifier::verify_credential;
+use ockam_core::api::Id;
+use ockam_core::{AsyncTryClone, Result};
 use ockam_identity::{IdentityIdentifier, PublicIdentity, TrustEveryonePolicy};
-use ockam_node::Context;
+use ockam_node::{tokio, Context};
 use tempfile::NamedTempFile;
 
 #[ockam_macros::test]
@@ -73,3 +80,461 @@ async fn credential(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+#[ockam_macros::test]
+async fn credential_from_one_time_code(ctx: &mut Context) -> Result<()> {
+    // Create the authority:
+    let enroller = Identity::create(ctx, &Vault::create()).await?;
+    let mut tmpf = NamedTempFile::new().unwrap();
+    let enrollers = [(enroller.identifier().clone(), Enroller::default())];
+    serde_json::to_writer(&mut tmpf, &HashMap::from(enrollers)).unwrap();
+
+    let authority = {
+        let a = Identity::create(ctx, &Vault::create()).await?;
+        a.create_secure_channel_listener("api", TrustEveryonePolicy, &InMemoryStorage::new())
+            .await?;
+        let exported = a.export().await?;
+        let store = InMemoryStorage::new();
+        let auth = direct::Server::new(b"project42".to_vec(), store, tmpf.path(), a);
+        ctx.start_worker("auth", auth).await?;
+        exported
+    };
+
+    // Create a one-time code from the enroller:
+    let e2a = enroller
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![e2a, "auth"], ctx).await?;
+    let otc = c
+        .create_token(CreateToken::new().with_attribute("location", "Berlin"))
+        .await?;
+
+    // Redeem it from a member:
+    let member = Identity::create(ctx, &Vault::create()).await?;
+    let m2a = member
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![m2a, "auth"], ctx).await?;
+    c.redeem(otc).await?;
+
+    // The code can only be used once:
+    assert!(c.redeem(otc).await.is_err());
+
+    // The credential carries the attributes of the code:
+    let cred = c.credential().await?;
+    let pkey = PublicIdentity::import(&authority, &Vault::create())
+        .await
+        .unwrap();
+    let data = pkey
+        .verify_credential(&cred, member.identifier(), &Vault::create())
+        .await?;
+    assert_eq!(
+        Some(b"Berlin".as_slice()),
+        data.attributes().get("location")
+    );
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn shared_one_time_code_is_redeemed_once(ctx: &mut Context) -> Result<()> {
+    let enroller = Identity::create(ctx, &Vault::create()).await?;
+    let mut tmpf = NamedTempFile::new().unwrap();
+    let enrollers = [(enroller.identifier().clone(), Enroller::default())];
+    serde_json::to_writer(&mut tmpf, &HashMap::from(enrollers)).unwrap();
+
+    // Two stateless authenticators sharing the same storage:
+    let a = Identity::create(ctx, &Vault::create()).await?;
+    a.create_secure_channel_listener("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let store = InMemoryStorage::new();
+    for name in ["auth1", "auth2"] {
+        let auth = direct::Server::new(
+            b"project42".to_vec(),
+            store.clone(),
+            tmpf.path(),
+            a.async_try_clone().await?,
+        )
+        .with_shared_tokens();
+        ctx.start_worker(name, auth).await?;
+    }
+
+    // Create a one-time code at the first one:
+    let e2a = enroller
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![e2a, "auth1"], ctx).await?;
+    let otc = c.create_token(CreateToken::new()).await?;
+
+    // Two members redeem it at the same time, one at each authenticator:
+    let m1 = Identity::create(ctx, &Vault::create()).await?;
+    let m2 = Identity::create(ctx, &Vault::create()).await?;
+    let m12a = m1
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let m22a = m2
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c1 = direct::Client::new(route![m12a, "auth1"], ctx).await?;
+    let mut c2 = direct::Client::new(route![m22a, "auth2"], ctx).await?;
+    let (r1, r2) = tokio::join!(c1.redeem(otc), c2.redeem(otc));
+
+    // Only one of them becomes a member:
+    assert!(r1.is_ok() != r2.is_ok());
+    let mut c = if r1.is_ok() { c1 } else { c2 };
+    assert_eq!(c.members().await?.members().len(), 1);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn credentials_of_members_enrolled_at_once(ctx: &mut Context) -> Result<()> {
+    // Create the authority:
+    let enroller = Identity::create(ctx, &Vault::create()).await?;
+    let mut tmpf = NamedTempFile::new().unwrap();
+    let enrollers = [(enroller.identifier().clone(), Enroller::default())];
+    serde_json::to_writer(&mut tmpf, &HashMap::from(enrollers)).unwrap();
+
+    let authority = {
+        let a = Identity::create(ctx, &Vault::create()).await?;
+        a.create_secure_channel_listener("api", TrustEveryonePolicy, &InMemoryStorage::new())
+            .await?;
+        let exported = a.export().await?;
+        let store = InMemoryStorage::new();
+        let auth = direct::Server::new(b"project42".to_vec(), store, tmpf.path(), a);
+        ctx.start_worker("auth", auth).await?;
+        exported
+    };
+
+    // Enroll two members, one of them listed twice:
+    let m1 = Identity::create(ctx, &Vault::create()).await?;
+    let m2 = Identity::create(ctx, &Vault::create()).await?;
+    let e2a = enroller
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![e2a, "auth"], ctx).await?;
+    let batch = EnrollMany::new()
+        .with_member(
+            EnrollMember::new(m1.identifier().clone()).with_attribute("location", "Berlin"),
+        )
+        .with_member(
+            EnrollMember::new(m2.identifier().clone()).with_attribute("location", "Lisbon"),
+        )
+        .with_member(EnrollMember::new(m1.identifier().clone()));
+    let results = c.enroll_many(batch).await?;
+    let ok: Vec<bool> = results.results().iter().map(|r| r.is_ok()).collect();
+    assert_eq!(ok, [true, true, false]);
+
+    // Each member is credentialed with its own attributes:
+    let m2a = m2
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![m2a, "auth"], ctx).await?;
+    assert_eq!(c.members().await?.members().len(), 2);
+    let cred = c.credential().await?;
+    let pkey = PublicIdentity::import(&authority, &Vault::create())
+        .await
+        .unwrap();
+    let data = pkey
+        .verify_credential(&cred, m2.identifier(), &Vault::create())
+        .await?;
+    assert_eq!(
+        Some(b"Lisbon".as_slice()),
+        data.attributes().get("location")
+    );
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn offline_credential_verification(ctx: &mut Context) -> Result<()> {
+    let authority = Identity::create(ctx, &Vault::create()).await?;
+    let member = Identity::create(ctx, &Vault::create()).await?;
+
+    let builder =
+        Credential::builder(member.identifier().clone()).with_attribute("project_id", b"project42");
+    let cred = authority.issue_credential(builder).await?;
+    let cred = minicbor::to_vec(&cred).unwrap();
+    let exported = authority.export().await?;
+
+    let verify = |project_id: &'static str| {
+        VerifyRequest::new(cred.as_slice(), member.identifier().clone())
+            .with_authority(authority.identifier().clone(), exported.as_slice())
+            .with_attribute("project_id", project_id)
+    };
+
+    // Matching attributes -> ok
+    let req = verify("project42");
+    let c: Credential = minicbor::decode(req.credential()).unwrap();
+    let res = verify_credential(&Vault::create(), Id::fresh(), "/verify", &req, &c).await?;
+    assert!(res.is_right());
+
+    // Mismatching attributes -> fail
+    let req = verify("project43");
+    let c: Credential = minicbor::decode(req.credential()).unwrap();
+    let res = verify_credential(&Vault::create(), Id::fresh(), "/verify", &req, &c).await?;
+    assert!(res.is_left());
+
+    // Unknown authority -> fail
+    let req = VerifyRequest::new(cred.as_slice(), member.identifier().clone());
+    let c: Credential = minicbor::decode(req.credential()).unwrap();
+    let res = verify_credential(&Vault::create(), Id::fresh(), "/verify", &req, &c).await?;
+    assert!(res.is_left());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn attributes_assigned_at_enrollment(ctx: &mut Context) -> Result<()> {
+    // Create the authority with an enroller which may only assign some attributes:
+    let enroller = Identity::create(ctx, &Vault::create()).await?;
+    let mut tmpf = NamedTempFile::new().unwrap();
+    let allowed = BTreeMap::from([
+        ("segment".to_string(), vec!["blue".to_string()]),
+        ("site".to_string(), Vec::new()),
+    ]);
+    let e = Enroller {
+        attributes: Some(allowed),
+        ..Enroller::default()
+    };
+    let enrollers = [(enroller.identifier().clone(), e)];
+    serde_json::to_writer(&mut tmpf, &HashMap::from(enrollers)).unwrap();
+
+    let authority = {
+        let a = Identity::create(ctx, &Vault::create()).await?;
+        a.create_secure_channel_listener("api", TrustEveryonePolicy, &InMemoryStorage::new())
+            .await?;
+        let exported = a.export().await?;
+        let store = InMemoryStorage::new();
+        let auth = direct::Server::new(b"project42".to_vec(), store, tmpf.path(), a);
+        ctx.start_worker("auth", auth).await?;
+        exported
+    };
+
+    let member = Identity::create(ctx, &Vault::create()).await?;
+    let e2a = enroller
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![e2a, "auth"], ctx).await?;
+
+    // Neither an unlisted value nor an unlisted key may be assigned:
+    let add = AddMember::new(member.identifier().clone()).with_attribute("segment", "red");
+    assert!(c.add_member_with(add).await.is_err());
+    let add = AddMember::new(member.identifier().clone()).with_attribute("team", "x");
+    assert!(c.add_member_with(add).await.is_err());
+    assert!(c
+        .create_token(CreateToken::new().with_attribute("team", "x"))
+        .await
+        .is_err());
+
+    // Listed values and keys without a value list are fine:
+    let add = AddMember::new(member.identifier().clone())
+        .with_attribute("segment", "blue")
+        .with_attribute("site", "Lisbon");
+    c.add_member_with(add).await?;
+
+    // The credential carries the assigned attributes:
+    let m2a = member
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![m2a, "auth"], ctx).await?;
+    let cred = c.credential().await?;
+    let pkey = PublicIdentity::import(&authority, &Vault::create())
+        .await
+        .unwrap();
+    let data = pkey
+        .verify_credential(&cred, member.identifier(), &Vault::create())
+        .await?;
+    assert_eq!(Some(b"blue".as_slice()), data.attributes().get("segment"));
+    assert_eq!(Some(b"Lisbon".as_slice()), data.attributes().get("site"));
+    assert_eq!(Some(b"member".as_slice()), data.attributes().get("role"));
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn enroller_rights_are_bounded(ctx: &mut Context) -> Result<()> {
+    // One enroller may enroll two members, the rights of the other have expired:
+    let limited = Identity::create(ctx, &Vault::create()).await?;
+    let expired = Identity::create(ctx, &Vault::create()).await?;
+    let mut tmpf = NamedTempFile::new().unwrap();
+    let enrollers = [
+        (
+            limited.identifier().clone(),
+            Enroller {
+                max_members: Some(2),
+                ..Enroller::default()
+            },
+        ),
+        (
+            expired.identifier().clone(),
+            Enroller {
+                expires_at: Some(1),
+                ..Enroller::default()
+            },
+        ),
+    ];
+    serde_json::to_writer(&mut tmpf, &HashMap::from(enrollers)).unwrap();
+
+    let a = Identity::create(ctx, &Vault::create()).await?;
+    a.create_secure_channel_listener("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let store = InMemoryStorage::new();
+    let auth = direct::Server::new(b"project42".to_vec(), store, tmpf.path(), a);
+    ctx.start_worker("auth", auth).await?;
+
+    let m1 = Identity::create(ctx, &Vault::create()).await?;
+    let m2 = Identity::create(ctx, &Vault::create()).await?;
+
+    // The expired enroller can do nothing:
+    let x2a = expired
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![x2a, "auth"], ctx).await?;
+    assert!(c.add_member(m1.identifier().clone()).await.is_err());
+    assert!(c.create_token(CreateToken::new()).await.is_err());
+
+    // A one-time code counts against the limit like a member does:
+    let l2a = limited
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![l2a, "auth"], ctx).await?;
+    c.create_token(CreateToken::new()).await?;
+    c.add_member(m1.identifier().clone()).await?;
+    assert!(c.add_member(m2.identifier().clone()).await.is_err());
+    assert!(c.create_token(CreateToken::new()).await.is_err());
+
+    let batch = EnrollMany::new().with_member(EnrollMember::new(m2.identifier().clone()));
+    let res = c.enroll_many(batch).await?;
+    assert_eq!(
+        Some("enroller member limit reached"),
+        res.results()[0].error()
+    );
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn renew_credential(ctx: &mut Context) -> Result<()> {
+    let enroller = Identity::create(ctx, &Vault::create()).await?;
+    let mut tmpf = NamedTempFile::new().unwrap();
+    let enrollers = [(enroller.identifier().clone(), Enroller::default())];
+    serde_json::to_writer(&mut tmpf, &HashMap::from(enrollers)).unwrap();
+
+    let revocations = Revocations::default();
+    let authority = {
+        let a = Identity::create(ctx, &Vault::create()).await?;
+        a.create_secure_channel_listener("api", TrustEveryonePolicy, &InMemoryStorage::new())
+            .await?;
+        let exported = a.export().await?;
+        let store = InMemoryStorage::new();
+        // Credentials can be renewed as soon as they are issued:
+        let auth = direct::Server::new(b"project42".to_vec(), store, tmpf.path(), a)
+            .with_revocations(revocations.clone())
+            .with_renewal_window(MAX_CREDENTIAL_VALIDITY);
+        ctx.start_worker("auth", auth).await?;
+        exported
+    };
+
+    let member = Identity::create(ctx, &Vault::create()).await?;
+    let e2a = enroller
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![e2a, "auth"], ctx).await?;
+    let add = AddMember::new(member.identifier().clone()).with_attribute("site", "Lisbon");
+    c.add_member_with(add).await?;
+
+    // A member can renew its credential without the enroller, once it has
+    // one:
+    let m2a = member
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![m2a, "auth"], ctx).await?;
+    assert!(c.renew().await.is_err());
+    c.credential().await?;
+    let cred = c.renew().await?;
+    let pkey = PublicIdentity::import(&authority, &Vault::create())
+        .await
+        .unwrap();
+    let data = pkey
+        .verify_credential(&cred, member.identifier(), &Vault::create())
+        .await?;
+    assert_eq!(Some(b"Lisbon".as_slice()), data.attributes().get("site"));
+
+    // But not once it has been revoked:
+    revocations.revoke(member.identifier().clone());
+    assert!(c.renew().await.is_err());
+    assert!(c.credential().await.is_err());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn credentials_are_only_renewed_before_expiry(ctx: &mut Context) -> Result<()> {
+    let enroller = Identity::create(ctx, &Vault::create()).await?;
+    let mut tmpf = NamedTempFile::new().unwrap();
+    let enrollers = [(enroller.identifier().clone(), Enroller::default())];
+    serde_json::to_writer(&mut tmpf, &HashMap::from(enrollers)).unwrap();
+
+    let a = Identity::create(ctx, &Vault::create()).await?;
+    a.create_secure_channel_listener("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let store = InMemoryStorage::new();
+    let auth = direct::Server::new(b"project42".to_vec(), store, tmpf.path(), a);
+    ctx.start_worker("auth", auth).await?;
+
+    let member = Identity::create(ctx, &Vault::create()).await?;
+    let e2a = enroller
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![e2a, "auth"], ctx).await?;
+    c.add_member(member.identifier().clone()).await?;
+
+    // A fresh credential is not due for renewal yet:
+    let m2a = member
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![m2a, "auth"], ctx).await?;
+    c.credential().await?;
+    assert!(c.renew().await.is_err());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn members_are_listed(ctx: &mut Context) -> Result<()> {
+    let enroller = Identity::create(ctx, &Vault::create()).await?;
+    let mut tmpf = NamedTempFile::new().unwrap();
+    let enrollers = [(enroller.identifier().clone(), Enroller::default())];
+    serde_json::to_writer(&mut tmpf, &HashMap::from(enrollers)).unwrap();
+
+    // Create the authority:
+    let a = Identity::create(ctx, &Vault::create()).await?;
+    a.create_secure_channel_listener("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let auth = direct::Server::new(
+        b"project42".to_vec(),
+        InMemoryStorage::new(),
+        tmpf.path(),
+        a,
+    );
+    ctx.start_worker("auth", auth).await?;
+
+    // Add a member via the enroller's connection:
+    let member = Identity::create(ctx, &Vault::create()).await?;
+    let e2a = enroller
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![e2a, "auth"], ctx).await?;
+    c.add_member(member.identifier().clone()).await?;
+
+    // Members can list the members:
+    let m2a = member
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![m2a, "auth"], ctx).await?;
+    let members = c.members().await?;
+    assert_eq!(members.members(), &[member.identifier().clone()]);
+
+    ctx.stop().await
+}
@@ -0,0 +1,137 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of nonces below the highest received one that are still accepted,
+/// to tolerate reordering.
+///
+/// A message arriving after 64 or more newer messages of the same channel is
+/// dropped, as its nonce can no longer be checked for duplicates, and counted
+/// in [`SecureChannelCounters::out_of_window`]. Transports which reorder
+/// messages this much are not supported.
+pub const REPLAY_WINDOW_SIZE: u64 = 64;
+
+/// Counters of the messages rejected by a secure channel decryptor
+///
+/// They can be shared with the owner of the channel to detect tampering
+/// or severe reordering on the underlying links.
+#[derive(Debug, Default)]
+pub struct SecureChannelCounters {
+    decrypt_failures: AtomicUsize,
+    out_of_window: AtomicUsize,
+    duplicates: AtomicUsize,
+}
+
+impl SecureChannelCounters {
+    /// Messages which could not be decrypted.
+    pub fn decrypt_failures(&self) -> usize {
+        self.decrypt_failures.load(Ordering::Relaxed)
+    }
+
+    /// Messages whose nonce is too old to be checked for duplicates, see
+    /// [`REPLAY_WINDOW_SIZE`].
+    pub fn out_of_window(&self) -> usize {
+        self.out_of_window.load(Ordering::Relaxed)
+    }
+
+    /// Messages whose nonce was already received.
+    pub fn duplicates(&self) -> usize {
+        self.duplicates.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn on_decrypt_failure(&self) {
+        self.decrypt_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a rejected nonce and return how many were rejected for the same reason.
+    pub(crate) fn on_replay(&self, replay: Replay) -> usize {
+        let counter = match replay {
+            Replay::OutOfWindow => &self.out_of_window,
+            Replay::Duplicate => &self.duplicates,
+        };
+        counter.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
+/// Why a nonce was rejected by a [`ReplayWindow`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Replay {
+    OutOfWindow,
+    Duplicate,
+}
+
+/// Sliding window of the nonces received by a decryptor.
+#[derive(Debug, Default)]
+pub(crate) struct ReplayWindow {
+    /// Highest nonce received plus one, zero if none was received.
+    next: u64,
+    /// Bit `i` is set if nonce `next - 1 - i` was received.
+    seen: u64,
+}
+
+impl ReplayWindow {
+    /// Check that a nonce was not received yet, without marking it.
+    pub(crate) fn check(&self, nonce: u64) -> Result<(), Replay> {
+        if nonce >= self.next {
+            return Ok(());
+        }
+        let age = self.next - 1 - nonce;
+        if age >= REPLAY_WINDOW_SIZE {
+            Err(Replay::OutOfWindow)
+        } else if self.seen & (1 << age) != 0 {
+            Err(Replay::Duplicate)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Mark a nonce as received.
+    ///
+    /// Must only be called for authenticated messages, after [`Self::check`].
+    pub(crate) fn mark(&mut self, nonce: u64) {
+        if nonce >= self.next {
+            let shift = nonce - self.next + 1;
+            self.seen = if shift >= REPLAY_WINDOW_SIZE {
+                0
+            } else {
+                self.seen << shift
+            };
+            self.seen |= 1;
+            self.next = nonce.saturating_add(1);
+        } else {
+            let age = self.next - 1 - nonce;
+            self.seen |= 1 << age;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_window() {
+        let mut w = ReplayWindow::default();
+        for n in [0, 1, 3] {
+            assert_eq!(w.check(n), Ok(()));
+            w.mark(n);
+        }
+        assert_eq!(w.check(1), Err(Replay::Duplicate));
+        // Reordered, but in the window
+        assert_eq!(w.check(2), Ok(()));
+        w.mark(2);
+        assert_eq!(w.check(2), Err(Replay::Duplicate));
+
+        w.mark(100);
+        assert_eq!(w.check(3), Err(Replay::OutOfWindow));
+        assert_eq!(w.check(100 - REPLAY_WINDOW_SIZE + 1), Ok(()));
+        assert_eq!(w.check(100), Err(Replay::Duplicate));
+    }
+
+    #[test]
+    fn replays_are_counted_per_reason() {
+        let c = SecureChannelCounters::default();
+        assert_eq!(c.on_replay(Replay::Duplicate), 1);
+        assert_eq!(c.on_replay(Replay::Duplicate), 2);
+        assert_eq!(c.on_replay(Replay::OutOfWindow), 1);
+        assert_eq!((c.duplicates(), c.out_of_window()), (2, 1));
+    }
+}
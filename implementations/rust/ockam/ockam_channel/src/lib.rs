@@ -23,6 +23,7 @@ extern crate core;
 extern crate alloc;
 
 mod common;
+mod counters;
 mod error;
 mod local_info;
 mod secure_channel;
@@ -32,6 +33,7 @@ mod secure_channel_listener;
 mod traits;
 
 pub use common::*;
+pub use counters::*;
 pub use error::*;
 pub use local_info::*;
 pub use secure_channel::*;
@@ -67,6 +69,7 @@ mod tests {
             None,
             new_key_exchanger.initiator().await?,
             vault,
+            Default::default(),
         )
         .await?;
 
@@ -1,8 +1,8 @@
 use crate::{
-    KeyExchangeCompleted, SecureChannelDecryptor, SecureChannelKeyExchanger, SecureChannelListener,
-    SecureChannelNewKeyExchanger, SecureChannelVault,
+    KeyExchangeCompleted, SecureChannelCounters, SecureChannelDecryptor, SecureChannelKeyExchanger,
+    SecureChannelListener, SecureChannelNewKeyExchanger, SecureChannelVault,
 };
-use ockam_core::compat::{rand::random, vec::Vec};
+use ockam_core::compat::{rand::random, sync::Arc, vec::Vec};
 use ockam_core::{Address, Result, Route};
 use ockam_node::Context;
 use serde::{Deserialize, Serialize};
@@ -85,17 +85,21 @@ impl SecureChannel {
             None,
             new_key_exchanger.initiator().await?,
             vault.async_try_clone().await?,
+            Default::default(),
         )
         .await
     }
 
     /// Create initiator channel with given route to a remote channel listener.
+    ///
+    /// Messages rejected by the channel are counted in `counters`.
     pub async fn create_extended(
         ctx: &Context,
         route: impl Into<Route>,
         custom_payload: Option<Vec<u8>>,
         key_exchanger: impl SecureChannelKeyExchanger,
         vault: impl SecureChannelVault,
+        counters: Arc<SecureChannelCounters>,
     ) -> Result<SecureChannelInfo> {
         let address_remote: Address = random();
 
@@ -114,7 +118,8 @@ impl SecureChannel {
             custom_payload,
             vault.async_try_clone().await?,
         )
-        .await?;
+        .await?
+        .with_counters(counters);
 
         let mut child_ctx = ctx.new_detached(callback_address).await?;
         ctx.start_worker(address_remote.clone(), decryptor).await?;
@@ -1,21 +1,26 @@
 use crate::{
-    ChannelKeys, CreateResponderChannelMessage, KeyExchangeCompleted, Role, SecureChannelEncryptor,
-    SecureChannelError, SecureChannelKeyExchanger, SecureChannelLocalInfo, SecureChannelVault,
+    ChannelKeys, CreateResponderChannelMessage, KeyExchangeCompleted, ReplayWindow, Role,
+    SecureChannelCounters, SecureChannelEncryptor, SecureChannelError, SecureChannelKeyExchanger,
+    SecureChannelLocalInfo, SecureChannelVault,
 };
-use ockam_core::compat::{boxed::Box, string::String, vec::Vec};
+use ockam_core::compat::{boxed::Box, string::String, sync::Arc, vec::Vec};
 use ockam_core::{async_trait, route};
 use ockam_core::{
     Address, Any, Decodable, LocalMessage, Result, Route, Routed, TransportMessage, Worker,
 };
 use ockam_node::Context;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 struct DecryptorReadyState {
     keys: ChannelKeys,
     encryptor_address: Address,
+    window: ReplayWindow,
 }
 
 /// Secure Channel Decryptor
+///
+/// Messages whose nonce was already received, or is older than the
+/// [`REPLAY_WINDOW_SIZE`](crate::REPLAY_WINDOW_SIZE) last ones, are dropped.
 pub struct SecureChannelDecryptor<V: SecureChannelVault, K: SecureChannelKeyExchanger> {
     role: Role,
     key_exchanger: Option<K>,
@@ -26,6 +31,7 @@ pub struct SecureChannelDecryptor<V: SecureChannelVault, K: SecureChannelKeyExch
     custom_payload: Option<Vec<u8>>,
     vault: V,
     key_exchange_name: String,
+    counters: Arc<SecureChannelCounters>,
 }
 
 impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor<V, K> {
@@ -47,6 +53,7 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
             vault,
             key_exchange_name,
             state: None,
+            counters: Default::default(),
         })
     }
 
@@ -67,16 +74,26 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
             vault,
             key_exchange_name,
             state: None,
+            counters: Default::default(),
         })
     }
 
+    /// Count the messages rejected by this decryptor in the given counters.
+    pub fn with_counters(mut self, counters: Arc<SecureChannelCounters>) -> Self {
+        self.counters = counters;
+        self
+    }
+
     /// Restore 12-byte nonce needed for AES GCM from 8 byte that we use for noise
-    fn convert_nonce_from_small(b: &[u8]) -> Result<[u8; 12]> {
+    fn convert_nonce_from_small(b: &[u8]) -> Result<(u64, [u8; 12])> {
         let bytes: [u8; 8] = b.try_into().map_err(|_| SecureChannelError::InvalidNonce)?;
 
         let nonce = u64::from_be_bytes(bytes);
 
-        Ok(SecureChannelEncryptor::<V>::convert_nonce_from_u64(nonce).1)
+        Ok((
+            nonce,
+            SecureChannelEncryptor::<V>::convert_nonce_from_u64(nonce).1,
+        ))
     }
 
     async fn send_key_exchange_payload(
@@ -119,11 +136,33 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
                 return Err(SecureChannelError::InvalidNonce.into());
             }
 
-            let nonce = Self::convert_nonce_from_small(&payload.as_slice()[..8])?;
+            let (n, nonce) = Self::convert_nonce_from_small(&payload.as_slice()[..8])?;
+
+            if let Err(replay) = state.window.check(n) {
+                let rejected = self.counters.on_replay(replay);
+                // Only warn on a logarithmic scale, a flood of replayed
+                // messages must not flood the logs as well.
+                if rejected.is_power_of_two() {
+                    warn!(nonce = %n, ?replay, %rejected, "SecureChannel dropped replayed message");
+                } else {
+                    debug!(nonce = %n, ?replay, "SecureChannel dropped replayed message");
+                }
+                return Ok(());
+            }
 
-            self.vault
+            let plaintext = match self
+                .vault
                 .aead_aes_gcm_decrypt(&state.keys.key, &payload[8..], &nonce, &[])
-                .await?
+                .await
+            {
+                Ok(p) => p,
+                Err(err) => {
+                    self.counters.on_decrypt_failure();
+                    return Err(err);
+                }
+            };
+            state.window.mark(n);
+            plaintext
         };
 
         let mut transport_message = TransportMessage::decode(&payload)?;
@@ -213,6 +252,7 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
                 nonce: 0,
             },
             encryptor_address: address_local,
+            window: ReplayWindow::default(),
         });
 
         Ok(())
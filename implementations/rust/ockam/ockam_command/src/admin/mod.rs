@@ -24,7 +24,7 @@ pub enum AdminSubCommand {
 }
 
 impl AdminCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             AdminSubCommand::Subscription(c) => c.run(options),
         }
@@ -133,8 +133,8 @@ enum SubscriptionUpdateSubcommand {
 }
 
 impl SubscriptionCommand {
-    pub fn run(self, opts: CommandGlobalOpts) {
-        node_rpc(run_impl, (opts, self));
+    pub fn run(self, opts: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(run_impl, (opts, self))
     }
 }
 
@@ -47,10 +47,8 @@ pub enum AuthenticatedSubcommand {
 }
 
 impl AuthenticatedCommand {
-    pub fn run(self) {
-        if let Err(e) = embedded_node(run_impl, self.subcommand) {
-            eprintln!("Ockam node failed: {:?}", e,);
-        }
+    pub fn run(self) -> crate::Result<()> {
+        embedded_node(run_impl, self.subcommand)
     }
 }
 
@@ -0,0 +1,203 @@
+use anyhow::{anyhow, Context as _};
+use clap::Args;
+use ockam_api::nodes::models::config::{ListenerSetup, NodeSetup, PolicySetup, ServiceSetup};
+use ockam_api::DefaultAddress;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+use crate::authority::HELP_DETAIL;
+use crate::util::{exitcode, secret};
+use crate::{help, node, CommandGlobalOpts};
+
+/// Create an authority node from a configuration file
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(HELP_DETAIL))]
+pub struct CreateCommand {
+    /// Name of the node.
+    #[arg(default_value = "authority")]
+    node_name: String,
+
+    /// YAML file declaring the services of the authority
+    #[arg(long)]
+    config: PathBuf,
+
+    /// TCP listener address
+    #[arg(long, short, id = "SOCKET_ADDRESS", default_value = "127.0.0.1:0")]
+    tcp_listener_address: String,
+
+    /// Use the default identity instead of creating one for the node
+    #[arg(long)]
+    shared_identity: bool,
+
+    /// Only show the node setup derived from the configuration file
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl CreateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) -> crate::Result<()> {
+        run_impl(opts, self)
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: CreateCommand) -> crate::Result<()> {
+    let s = std::fs::read_to_string(&cmd.config)
+        .with_context(|| format!("Failed to read {}", cmd.config.display()))?;
+    let config: AuthorityConfig =
+        secret::from_yaml(&s).context("Failed to parse the authority configuration")?;
+    let setup = config.into_setup()?;
+
+    if cmd.dry_run {
+        let yaml = serde_yaml::to_string(&setup).context("Failed to serialize the node setup")?;
+        print!("{yaml}");
+        return Ok(());
+    }
+
+    node::CreateCommand {
+        node_name: cmd.node_name,
+        tcp_listener_address: cmd.tcp_listener_address,
+        no_shared_identity: !cmd.shared_identity,
+        setup: Some(setup),
+        ..Default::default()
+    }
+    .run(opts)
+}
+
+/// What an authority node runs, as declared in its configuration file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AuthorityConfig {
+    /// The project whose members are enrolled.
+    project: String,
+    /// JSON file of the identifiers allowed to enroll members.
+    enrollers: PathBuf,
+    #[serde(default)]
+    authenticator: AuthenticatorSection,
+    signer: Option<SignerSection>,
+    #[serde(default)]
+    verifier: ServiceSection,
+    #[serde(default)]
+    revocation: ServiceSection,
+    listener: Option<ListenerSetup>,
+    #[serde(default)]
+    policies: Vec<PolicySetup>,
+    /// Not supported, rejected with an explicit error.
+    oauth2: Option<serde_yaml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AuthenticatorSection {
+    address: Option<String>,
+    resource: Option<String>,
+    redis: Option<String>,
+    /// Route to a signer service issuing the credentials.
+    signer: Option<String>,
+    #[serde(default)]
+    stateless: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SignerSection {
+    address: Option<String>,
+    resource: Option<String>,
+    authorized: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ServiceSection {
+    address: Option<String>,
+    resource: Option<String>,
+    #[serde(default)]
+    disabled: bool,
+}
+
+impl AuthorityConfig {
+    fn into_setup(self) -> crate::Result<NodeSetup> {
+        if self.oauth2.is_some() {
+            return Err(crate::Error::new(
+                exitcode::CONFIG,
+                anyhow!("OAuth2 authenticators are not supported, remove the `oauth2` section"),
+            ));
+        }
+        // The node does not run in the current directory.
+        let enrollers = std::fs::canonicalize(&self.enrollers).map_err(|e| {
+            crate::Error::new(
+                exitcode::CONFIG,
+                anyhow!("Invalid enrollers file {}: {e}", self.enrollers.display()),
+            )
+        })?;
+
+        let mut setup = NodeSetup::default();
+
+        let a = self.authenticator;
+        let mut s = service("authenticator", a.address, DefaultAddress::AUTHENTICATOR);
+        s.resource = a.resource;
+        s.enrollers = Some(enrollers.display().to_string());
+        s.project = Some(self.project);
+        s.redis = a.redis;
+        s.signer = a.signer;
+        s.stateless = a.stateless;
+        setup.services.push(s);
+
+        if let Some(signer) = self.signer {
+            let mut s = service("signer", signer.address, DefaultAddress::SIGNER);
+            s.resource = signer.resource;
+            s.authorized = signer.authorized;
+            setup.services.push(s);
+        }
+        for (kind, section, default) in [
+            ("verifier", self.verifier, DefaultAddress::VERIFIER),
+            ("revocation", self.revocation, DefaultAddress::REVOCATION),
+        ] {
+            if !section.disabled {
+                let mut s = service(kind, section.address, default);
+                s.resource = section.resource;
+                setup.services.push(s);
+            }
+        }
+
+        setup.listeners.extend(self.listener);
+        setup.policies = self.policies;
+        Ok(setup)
+    }
+}
+
+/// A service restarted when its worker stops, like every authority service.
+fn service(kind: &str, address: Option<String>, default: &str) -> ServiceSetup {
+    let mut s = ServiceSetup::new(kind, address.unwrap_or_else(|| default.to_string()));
+    s.restart = true;
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(yaml: &str) -> AuthorityConfig {
+        secret::from_yaml(yaml).unwrap()
+    }
+
+    #[test]
+    fn config_declares_the_authority_services() {
+        let enrollers = tempfile::NamedTempFile::new().unwrap();
+        let yaml = format!(
+            "project: p1\nenrollers: {}\nsigner:\n  authorized: [ P123 ]\nrevocation:\n  disabled: true\n",
+            enrollers.path().display()
+        );
+        let setup = config(&yaml).into_setup().unwrap();
+        let kinds: Vec<_> = setup.services.iter().map(|s| s.kind.as_str()).collect();
+        assert_eq!(kinds, ["authenticator", "signer", "verifier"]);
+        assert_eq!(setup.services[0].project.as_deref(), Some("p1"));
+        assert_eq!(setup.services[1].authorized, ["P123"]);
+        assert!(setup.services.iter().all(|s| s.restart));
+    }
+
+    #[test]
+    fn oauth2_is_rejected() {
+        let yaml = "project: p1\nenrollers: e.json\noauth2:\n  issuer: https://example.com\n";
+        assert!(config(yaml).into_setup().is_err());
+    }
+}
@@ -0,0 +1,70 @@
+pub(crate) mod create;
+
+pub(crate) use create::CreateCommand;
+
+use crate::help;
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+
+const HELP_DETAIL: &str = "\
+About:
+    An authority node enrolls the members of a project and issues their
+    credentials. Its identity, services, secure channel listener and
+    policies are declared in a single YAML file:
+
+```yaml
+    # The project whose members are enrolled
+    project: 1b8d2a6c
+    # JSON file of the identifiers allowed to enroll members
+    enrollers: ./enrollers.json
+    # The direct authenticator, which issues the credentials
+    authenticator:
+      stateless: true
+    # Only let enrollers revoke credentials
+    revocation:
+      resource: authority.revocation
+    # A signer service for authenticators running on other nodes
+    signer:
+      authorized: [ P6c20e8a9a3e0e31e04d9f5b2a6f4c9e1d0b7e8f2c5a1d3b9e4f6a8c0d2e7b1f3 ]
+    # Only accept a limited number of secure channels
+    listener:
+      address: authority
+      max_channels_per_identity: 4
+    policies:
+      - resource: authority.revocation
+        condition: '{\"Eq\":[\"role\",{\"S\":\"enroller\"}]}'
+```
+
+```sh
+    # Create the authority node and print its identifier
+    $ ockam authority create authority --config authority.yaml
+
+    # Show the node setup derived from the file, without creating the node
+    $ ockam authority create authority --config authority.yaml --dry-run
+```
+";
+
+/// Create project authority nodes
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    after_long_help = help::template(HELP_DETAIL)
+)]
+pub struct AuthorityCommand {
+    #[command(subcommand)]
+    subcommand: AuthoritySubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum AuthoritySubcommand {
+    Create(CreateCommand),
+}
+
+impl AuthorityCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        match self.subcommand {
+            AuthoritySubcommand::Create(c) => c.run(options),
+        }
+    }
+}
@@ -36,12 +36,13 @@ pub struct CompletionCommand {
 }
 
 impl CompletionCommand {
-    pub fn run(self) {
+    pub fn run(self) -> crate::Result<()> {
         generate(
             self.shell,
             &mut OckamCommand::command(),
             "ockam",
             &mut io::stdout(),
-        )
+        );
+        Ok(())
     }
 }
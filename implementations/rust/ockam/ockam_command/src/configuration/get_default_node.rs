@@ -8,11 +8,8 @@ use crate::CommandGlobalOpts;
 pub struct GetDefaultNodeCommand {}
 
 impl GetDefaultNodeCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        if let Err(e) = run_impl(options) {
-            eprintln!("{}", e);
-            std::process::exit(e.code());
-        }
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        run_impl(options)
     }
 }
 
@@ -6,7 +6,7 @@ use ockam_api::config::lookup::LookupValue;
 pub struct ListCommand {}
 
 impl ListCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         let lookup = options.config.lookup();
 
         for (alias, value) in &lookup.map {
@@ -18,5 +18,6 @@ impl ListCommand {
                 println!("Node:    {}\nAddress: {}\n", alias, addr);
             }
         }
+        Ok(())
     }
 }
@@ -33,7 +33,7 @@ pub enum ConfigurationSubcommand {
 }
 
 impl ConfigurationCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             ConfigurationSubcommand::Get(c) => c.run(options),
             ConfigurationSubcommand::GetDefaultNode(c) => c.run(options),
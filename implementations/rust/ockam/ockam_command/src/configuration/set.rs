@@ -13,11 +13,8 @@ pub struct SetCommand {
 }
 
 impl SetCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        if let Err(e) = run_impl(options, self) {
-            eprintln!("{}", e);
-            std::process::exit(e.code());
-        }
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        run_impl(options, self)
     }
 }
 
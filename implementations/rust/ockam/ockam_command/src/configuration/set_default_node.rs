@@ -8,11 +8,8 @@ pub struct SetDefaultNodeCommand {
 }
 
 impl SetDefaultNodeCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        if let Err(e) = run_impl(&self.name, &options) {
-            eprintln!("{}", e);
-            std::process::exit(e.code());
-        }
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        run_impl(&self.name, &options)
     }
 }
 
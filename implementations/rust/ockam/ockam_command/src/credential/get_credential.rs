@@ -16,8 +16,8 @@ pub struct GetCredentialCommand {
 }
 
 impl GetCredentialCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        node_rpc(rpc, (options, self));
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
     }
 }
 
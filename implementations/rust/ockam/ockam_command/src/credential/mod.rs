@@ -1,8 +1,14 @@
 pub(crate) mod get_credential;
 pub(crate) mod present_credential;
+pub(crate) mod revoke;
+pub(crate) mod sync_revocations;
+pub(crate) mod verify_credential;
 
 pub(crate) use get_credential::GetCredentialCommand;
 pub(crate) use present_credential::PresentCredentialCommand;
+pub(crate) use revoke::RevokeCommand;
+pub(crate) use sync_revocations::SyncRevocationsCommand;
+pub(crate) use verify_credential::VerifyCredentialCommand;
 
 use crate::help;
 use crate::CommandGlobalOpts;
@@ -26,13 +32,19 @@ pub struct CredentialCommand {
 pub enum CredentialSubcommand {
     Get(GetCredentialCommand),
     Present(PresentCredentialCommand),
+    Verify(VerifyCredentialCommand),
+    Revoke(RevokeCommand),
+    SyncRevocations(SyncRevocationsCommand),
 }
 
 impl CredentialCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             CredentialSubcommand::Get(c) => c.run(options),
             CredentialSubcommand::Present(c) => c.run(options),
+            CredentialSubcommand::Verify(c) => c.run(options),
+            CredentialSubcommand::Revoke(c) => c.run(options),
+            CredentialSubcommand::SyncRevocations(c) => c.run(options),
         }
     }
 }
@@ -21,8 +21,8 @@ pub struct PresentCredentialCommand {
 }
 
 impl PresentCredentialCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        node_rpc(rpc, (options, self));
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
     }
 }
 
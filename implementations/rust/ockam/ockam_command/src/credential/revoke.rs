@@ -0,0 +1,46 @@
+use clap::Args;
+
+use ockam::identity::IdentityIdentifier;
+use ockam::Context;
+
+use crate::node::NodeOpts;
+use crate::util::api::{self};
+use crate::util::{node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+
+/// Revoke an identity in the revocation list published by an authority node
+#[derive(Clone, Debug, Args)]
+pub struct RevokeCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+
+    /// Identifier of the identity to revoke
+    #[arg(value_name = "IDENTIFIER")]
+    pub identity: IdentityIdentifier,
+}
+
+impl RevokeCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(
+    mut ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, RevokeCommand),
+) -> crate::Result<()> {
+    run_impl(&mut ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &mut Context,
+    opts: CommandGlobalOpts,
+    cmd: RevokeCommand,
+) -> crate::Result<()> {
+    let mut rpc = Rpc::background(ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request(api::credentials::revoke(cmd.identity.clone()))
+        .await?;
+    rpc.is_ok()?;
+    println!("Revoked {}", cmd.identity);
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use clap::Args;
+
+use ockam::identity::IdentityIdentifier;
+use ockam::Context;
+use ockam_multiaddr::MultiAddr;
+
+use crate::node::NodeOpts;
+use crate::util::api::{self};
+use crate::util::{node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+
+/// Periodically fetch the revocation list of an authority
+///
+/// Revoked identities are rejected by the secure channel listeners and
+/// the verifier of the node, and their open channels are closed. Only lists
+/// signed by an authority of the node are accepted. The sync is restored
+/// when the node restarts.
+#[derive(Clone, Debug, Args)]
+pub struct SyncRevocationsCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+
+    /// Route to the revocation service of the authority
+    #[arg(long, display_order = 900, id = "ROUTE")]
+    pub from: MultiAddr,
+
+    /// Seconds between two syncs
+    #[arg(long, value_name = "SECONDS")]
+    pub interval: Option<u64>,
+
+    /// Authority signing the list, if the node has several ones
+    #[arg(long, value_name = "IDENTIFIER")]
+    pub authority: Option<IdentityIdentifier>,
+
+    /// Reject every identity once the list was not synced for this many
+    /// seconds (defaults to ten intervals)
+    #[arg(long, value_name = "SECONDS")]
+    pub max_staleness: Option<u64>,
+}
+
+impl SyncRevocationsCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(
+    mut ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, SyncRevocationsCommand),
+) -> crate::Result<()> {
+    run_impl(&mut ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &mut Context,
+    opts: CommandGlobalOpts,
+    cmd: SyncRevocationsCommand,
+) -> crate::Result<()> {
+    let mut rpc = Rpc::background(ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request(api::credentials::sync_revocations(
+        &cmd.from,
+        cmd.interval,
+        cmd.authority,
+        cmd.max_staleness,
+    ))
+    .await?;
+    rpc.is_ok()?;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use anyhow::anyhow;
+use clap::Args;
+
+use ockam::identity::credential::Credential;
+use ockam::identity::IdentityIdentifier;
+use ockam::vault::Vault;
+use ockam::Context;
+use ockam_api::verifier::types::VerifyRequest;
+use ockam_api::verifier::verify_credential;
+use ockam_core::api::Id;
+
+use crate::util::secret::parse_secret;
+use crate::util::{node_rpc, parse_attribute};
+use crate::CommandGlobalOpts;
+
+/// Verify a credential locally, without contacting its authority
+#[derive(Clone, Debug, Args)]
+pub struct VerifyCredentialCommand {
+    /// Hex encoded credential, as printed with `--output json`.
+    /// Can be read from `@env:NAME` or `@file:PATH`.
+    #[arg(long, value_parser = parse_secret)]
+    pub credential: String,
+
+    /// Identifier of the identity the credential was issued to
+    #[arg(long)]
+    pub subject: IdentityIdentifier,
+
+    /// Hex encoded identity of the authority that issued the credential.
+    /// Can be read from `@env:NAME` or `@file:PATH`.
+    #[arg(long, value_name = "IDENTITY", value_parser = parse_secret)]
+    pub authority: String,
+
+    /// Attribute value the credential must contain, as `key=value`
+    #[arg(long = "attribute", value_name = "KEY=VALUE", value_parser = parse_attribute)]
+    pub attributes: Vec<(String, String)>,
+}
+
+impl VerifyCredentialCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(
+    _ctx: Context,
+    (_opts, cmd): (CommandGlobalOpts, VerifyCredentialCommand),
+) -> crate::Result<()> {
+    let credential = hex::decode(cmd.credential.trim().trim_matches('"'))
+        .map_err(|_| anyhow!("invalid credential encoding"))?;
+    let authority =
+        hex::decode(cmd.authority.trim()).map_err(|_| anyhow!("invalid authority encoding"))?;
+    let issuer = ockam::identity::PublicIdentity::import(&authority, &Vault::create()).await?;
+
+    let mut req = VerifyRequest::new(credential.as_slice(), cmd.subject)
+        .with_authority(issuer.identifier().clone(), authority.as_slice());
+    for (k, v) in &cmd.attributes {
+        req = req.with_attribute(k.as_str(), v.as_str())
+    }
+    let c: Credential =
+        minicbor::decode(req.credential()).map_err(|_| anyhow!("invalid credential"))?;
+
+    let res = verify_credential(&Vault::create(), Id::fresh(), "/verify", &req, &c).await?;
+    let data = res.map_left(|err| {
+        err.into_parts()
+            .1
+            .and_then(|e| e.message().map(|m| m.to_string()))
+            .unwrap_or_else(|| "invalid credential".to_string())
+    });
+    let data = data.either(
+        |msg| {
+            Err(crate::Error::new(
+                crate::util::exitcode::DATAERR,
+                anyhow!("Credential verification failed: {msg}"),
+            ))
+        },
+        Ok,
+    )?;
+
+    println!("Credential is valid");
+    println!("  Issuer: {}", data.issuer());
+    println!("  Subject: {}", data.subject());
+    println!("  Expires at: {}", u64::from(data.expires_at()));
+    println!("  Attributes:");
+    for (k, v) in data.attributes().iter() {
+        println!("    {k}: {}", String::from_utf8_lossy(v));
+    }
+    Ok(())
+}
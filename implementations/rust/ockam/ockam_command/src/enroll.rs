@@ -35,8 +35,8 @@ pub struct EnrollCommand {
 }
 
 impl EnrollCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        node_rpc(rpc, (options, self));
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
     }
 }
 
@@ -1,5 +1,7 @@
 use std::fmt::{Debug, Display, Formatter};
 
+use ockam_core::api::ErrorCode;
+
 use crate::util::ConfigError;
 use crate::{exitcode, ExitCode};
 
@@ -41,7 +43,14 @@ impl std::error::Error for Error {
 
 impl From<ConfigError> for Error {
     fn from(e: ConfigError) -> Self {
-        Error::new(exitcode::CONFIG, e.into())
+        Error::new(config_exit_code(&e), e.into())
+    }
+}
+
+fn config_exit_code(e: &ConfigError) -> ExitCode {
+    match e {
+        ConfigError::NotFound(_) => exitcode::NOT_FOUND,
+        _ => exitcode::CONFIG_ERROR,
     }
 }
 
@@ -53,7 +62,20 @@ impl From<ockam::Error> for Error {
 
 impl From<anyhow::Error> for Error {
     fn from(e: anyhow::Error) -> Self {
-        Error::new(exitcode::SOFTWARE, e)
+        let e = match e.downcast::<Error>() {
+            Ok(e) => return e,
+            Err(e) => e,
+        };
+        let code = if let Some(e) = e.downcast_ref::<ApiError>() {
+            e.exit_code()
+        } else if e.downcast_ref::<ConnectionError>().is_some() {
+            exitcode::CONNECTION_ERROR
+        } else if let Some(e) = e.downcast_ref::<ConfigError>() {
+            config_exit_code(e)
+        } else {
+            exitcode::SOFTWARE
+        };
+        Error::new(code, e)
     }
 }
 
@@ -80,3 +102,74 @@ impl From<std::net::AddrParseError> for Error {
         Error::new(exitcode::SOFTWARE, e.into())
     }
 }
+
+/// An error response to a request, with its API error code if it has one.
+#[derive(Debug)]
+pub struct ApiError {
+    code: Option<ErrorCode>,
+    message: String,
+}
+
+impl ApiError {
+    pub fn new(code: Option<ErrorCode>, message: String) -> Self {
+        Self { code, message }
+    }
+
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.code
+    }
+
+    /// The exit code of a command that failed with this error.
+    pub fn exit_code(&self) -> ExitCode {
+        match self.code {
+            Some(
+                ErrorCode::BadRequest
+                | ErrorCode::UnknownPath
+                | ErrorCode::InvalidMethod
+                | ErrorCode::InvalidBody,
+            ) => exitcode::DATAERR,
+            Some(ErrorCode::NotFound) => exitcode::NOT_FOUND,
+            Some(
+                ErrorCode::SecureChannelRequired
+                | ErrorCode::Unauthorized
+                | ErrorCode::UnknownOneTimeCode
+                | ErrorCode::ExpiredOneTimeCode
+                | ErrorCode::UnknownIssuer
+                | ErrorCode::InvalidCredential
+                | ErrorCode::AttributeMismatch
+                | ErrorCode::RevokedSubject,
+            ) => exitcode::FORBIDDEN,
+            _ => exitcode::API_ERROR,
+        }
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// A failure to reach a node or to get a response from it.
+#[derive(Debug)]
+pub struct ConnectionError {
+    message: String,
+}
+
+impl ConnectionError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ConnectionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ConnectionError {}
@@ -0,0 +1,56 @@
+pub(crate) mod receive;
+pub(crate) mod send;
+
+pub(crate) use receive::ReceiveCommand;
+pub(crate) use send::SendCommand;
+
+use crate::help;
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+
+const HELP_DETAIL: &str = "\
+About:
+    Files are sent in chunks to the file transfer service of another node,
+    which checks their integrity before writing them to its directory.
+    An interrupted transfer is resumed when the same file is sent again.
+
+```sh
+    # Create two nodes
+    $ ockam node create n1
+    $ ockam node create n2
+
+    # Receive files sent by n1 on n2
+    $ ockam file receive --node n2 --dir ./received \\
+        --authorized-identifier $(ockam identity show --node n1)
+
+    # Send a file from n1 to n2 over a secure channel
+    $ ockam file send ./report.pdf --node n1 --to /node/n2/service/api/service/file_transfer
+```
+";
+
+/// Send files between nodes
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    after_long_help = help::template(HELP_DETAIL)
+)]
+pub struct FileCommand {
+    #[command(subcommand)]
+    subcommand: FileSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum FileSubcommand {
+    Send(SendCommand),
+    Receive(ReceiveCommand),
+}
+
+impl FileCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        match self.subcommand {
+            FileSubcommand::Send(c) => c.run(options),
+            FileSubcommand::Receive(c) => c.run(options),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use clap::Args;
+use std::path::PathBuf;
+
+use ockam::identity::IdentityIdentifier;
+use ockam::Context;
+use ockam_api::nodes::models::services::StartFileTransferService;
+use ockam_api::DefaultAddress;
+
+use crate::node::NodeOpts;
+use crate::util::{api, node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+
+/// Start receiving files on a node
+#[derive(Clone, Debug, Args)]
+pub struct ReceiveCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+
+    /// Directory the received files are written to
+    #[arg(long, value_name = "DIRECTORY")]
+    pub dir: PathBuf,
+
+    /// Address of the file transfer service
+    #[arg(long, default_value_t = file_transfer_default_addr())]
+    pub addr: String,
+
+    /// Identifier of a node allowed to send files (can be repeated)
+    #[arg(
+        long = "authorized-identifier",
+        value_name = "IDENTIFIER",
+        required = true
+    )]
+    pub authorized: Vec<IdentityIdentifier>,
+
+    /// Replace existing files of the directory
+    #[arg(long)]
+    pub overwrite: bool,
+
+    /// Largest file accepted, in bytes
+    #[arg(long, value_name = "BYTES")]
+    pub max_size: Option<u64>,
+}
+
+fn file_transfer_default_addr() -> String {
+    DefaultAddress::FILE_TRANSFER.to_string()
+}
+
+impl ReceiveCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(
+    mut ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ReceiveCommand),
+) -> crate::Result<()> {
+    run_impl(&mut ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &mut Context,
+    opts: CommandGlobalOpts,
+    cmd: ReceiveCommand,
+) -> crate::Result<()> {
+    // The node may run in another directory
+    let dir = std::env::current_dir()?.join(&cmd.dir);
+    let mut rpc = Rpc::background(ctx, &opts, &cmd.node_opts.api_node)?;
+    let mut req = StartFileTransferService::new(&cmd.addr, &dir)
+        .with_authorized(cmd.authorized.clone())
+        .with_overwrite(cmd.overwrite);
+    if let Some(n) = cmd.max_size {
+        req = req.with_max_size(n)
+    }
+    rpc.request(api::start_file_transfer_service(req)).await?;
+    rpc.is_ok()?;
+    println!(
+        "Receiving files into {} at address: {}",
+        dir.display(),
+        cmd.addr
+    );
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use anyhow::{anyhow, Context as _};
+use clap::Args;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use ockam::Context;
+use ockam_api::clean_multiaddr;
+use ockam_api::nodes::models::file_transfer::FileSent;
+use ockam_multiaddr::MultiAddr;
+
+use crate::node::NodeOpts;
+use crate::util::{api, node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+
+/// Send a file to another node
+#[derive(Clone, Debug, Args)]
+pub struct SendCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+
+    /// Path of the file to send
+    pub path: PathBuf,
+
+    /// Route to the file transfer service of the receiving node
+    #[arg(long, value_name = "ROUTE")]
+    pub to: MultiAddr,
+
+    /// Name of the file on the receiving node, defaults to the name of the sent file
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Seconds to wait for the transfer to complete
+    #[arg(long, value_name = "SECONDS", default_value_t = 600)]
+    pub timeout: u64,
+}
+
+impl SendCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(mut ctx: Context, (opts, cmd): (CommandGlobalOpts, SendCommand)) -> crate::Result<()> {
+    run_impl(&mut ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &mut Context,
+    opts: CommandGlobalOpts,
+    cmd: SendCommand,
+) -> crate::Result<()> {
+    let (to, _) = clean_multiaddr(&cmd.to, &opts.config.lookup())
+        .ok_or_else(|| anyhow!("Argument '--to' is invalid"))?;
+    // The node may run in another directory
+    let path = std::env::current_dir()?.join(&cmd.path);
+    let path = path.to_str().context("Invalid file path")?;
+
+    let mut rpc = Rpc::background(ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request_with_timeout(
+        api::send_file(path, &to, cmd.name.as_deref()),
+        Duration::from_secs(cmd.timeout),
+    )
+    .await?;
+    let sent = rpc.parse_response::<FileSent>()?;
+    if sent.resumed_from() > 0 {
+        println!(
+            "Sent {} bytes, resumed after {} bytes",
+            sent.size(),
+            sent.resumed_from()
+        );
+    } else {
+        println!("Sent {} bytes", sent.size());
+    }
+    Ok(())
+}
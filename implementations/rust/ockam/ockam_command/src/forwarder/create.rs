@@ -1,16 +1,19 @@
+use std::time::Duration;
+
 use anyhow::{anyhow, Context as _};
 use clap::Args;
 use ockam::identity::IdentityIdentifier;
 use ockam_multiaddr::proto::Project;
 use rand::prelude::random;
 
-use ockam::{Context, TcpTransport};
+use ockam::{Context, TakeoverPolicy, TcpTransport};
 use ockam_api::is_local_node;
 use ockam_api::nodes::models::forwarder::{CreateForwarder, ForwarderInfo};
 use ockam_core::api::Request;
 use ockam_multiaddr::{proto::Node, MultiAddr, Protocol};
 
 use crate::forwarder::HELP_DETAIL;
+use crate::util::api::IdempotencyOpts;
 use crate::util::output::Output;
 use crate::util::{extract_address_value, node_rpc, RpcBuilder};
 use crate::Result;
@@ -38,11 +41,46 @@ pub struct CreateCommand {
     /// Authorized identity for secure channel connection (optional)
     #[arg(long, id = "AUTHORIZED", display_order = 900)]
     authorized: Option<IdentityIdentifier>,
+
+    /// What to do when the name is taken at a node: fail, replace the
+    /// current forwarder, or queue until it is released (optional)
+    #[arg(long, value_name = "POLICY", value_parser = parse_takeover, display_order = 900)]
+    takeover: Option<TakeoverPolicy>,
+
+    /// Seconds between refreshes of the forwarder registration at a node
+    /// (optional)
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..), display_order = 900)]
+    heartbeat_interval: Option<u64>,
+
+    /// Seconds after which a node releases the name, unless the forwarder
+    /// registration is refreshed (optional)
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..), display_order = 900)]
+    ttl: Option<u64>,
+
+    /// Service to ping to check that the connection to the node is alive,
+    /// for nodes which don't run an echo service (optional)
+    #[arg(long, value_name = "SERVICE", display_order = 900)]
+    probe_service: Option<String>,
+
+    #[command(flatten)]
+    idempotency_opts: IdempotencyOpts,
 }
 
 impl CreateCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        node_rpc(rpc, (options, self));
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+
+    /// The first given flag which is only supported at local nodes.
+    fn node_only_flag(&self) -> Option<&'static str> {
+        [
+            (self.takeover.is_some(), "--takeover"),
+            (self.heartbeat_interval.is_some(), "--heartbeat-interval"),
+            (self.ttl.is_some(), "--ttl"),
+        ]
+        .into_iter()
+        .find(|(given, _)| *given)
+        .map(|(_, flag)| flag)
     }
 }
 
@@ -80,20 +118,58 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> R
             if cmd.authorized.is_some() {
                 return Err(anyhow!("--authorized can not be used with project addresses").into());
             }
-            CreateForwarder::at_project(ma, Some(alias))
+            if let Some(flag) = cmd.node_only_flag() {
+                return Err(anyhow!("{flag} can not be used with project addresses").into());
+            }
+            let mut body = CreateForwarder::at_project(ma, Some(alias));
+            if let Some(service) = cmd.probe_service {
+                body = body.with_probe_service(service)
+            }
+            body
         } else {
-            CreateForwarder::at_node(ma, Some(alias), at_rust_node, cmd.authorized)
+            if let (Some(flag), false) = (cmd.node_only_flag(), at_rust_node) {
+                return Err(anyhow!("{flag} can only be used with local nodes").into());
+            }
+            if let (Some(interval), Some(ttl)) = (cmd.heartbeat_interval, cmd.ttl) {
+                if interval >= ttl {
+                    return Err(anyhow!("--heartbeat-interval must be shorter than --ttl").into());
+                }
+            }
+            let mut body = CreateForwarder::at_node(ma, Some(alias), at_rust_node, cmd.authorized);
+            if let Some(policy) = cmd.takeover {
+                body = body.with_takeover(policy)
+            }
+            if let Some(interval) = cmd.heartbeat_interval {
+                body = body.with_heartbeat_interval(Duration::from_secs(interval))
+            }
+            if let Some(ttl) = cmd.ttl {
+                body = body.with_ttl(Duration::from_secs(ttl))
+            }
+            if let Some(service) = cmd.probe_service {
+                body = body.with_probe_service(service)
+            }
+            body
         };
-        Request::post("/node/forwarder").body(body)
+        cmd.idempotency_opts
+            .apply(Request::post("/node/forwarder").body(body))
     };
 
     let mut rpc = RpcBuilder::new(&ctx, &opts, &api_node).tcp(&tcp)?.build();
     rpc.request(req).await?;
-    rpc.parse_and_print_response::<ForwarderInfo>()?;
+    let info = rpc.parse_and_print_response::<ForwarderInfo>()?;
+    if info.is_queued() {
+        eprintln!("The name is taken, messages are forwarded once it is released");
+    }
 
     Ok(())
 }
 
+fn parse_takeover(input: &str) -> anyhow::Result<TakeoverPolicy> {
+    input
+        .parse()
+        .map_err(|_| anyhow!("expected one of fail, replace or queue"))
+}
+
 impl Output for ForwarderInfo<'_> {
     fn output(&self) -> anyhow::Result<String> {
         Ok(format!("/service/{}", self.remote_address()))
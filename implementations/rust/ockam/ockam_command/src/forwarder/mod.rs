@@ -1,10 +1,12 @@
 use clap::{Args, Subcommand};
 
 pub(crate) use create::CreateCommand;
+pub(crate) use show::ShowCommand;
 
 use crate::{help, CommandGlobalOpts};
 
 mod create;
+mod show;
 
 const HELP_DETAIL: &str = "\
 About:
@@ -25,6 +27,14 @@ About:
     $ ockam message send hello --to /node/green/service/forward_to_blue/service/uppercase
 ```
 
+    Forwarders at local nodes can refresh their registration and let the node
+    release their name when they stop refreshing it
+
+```sh
+    $ ockam forwarder create blue --at /node/green --to /node/blue --heartbeat-interval 10 --ttl 30
+    $ ockam forwarder show forward_to_blue --node blue
+```
+
     This can be very useful in establishing communication between applications
     that cannot otherwise reach each other over the network.
 
@@ -62,12 +72,14 @@ pub struct ForwarderCommand {
 #[derive(Clone, Debug, Subcommand)]
 pub enum ForwarderSubCommand {
     Create(CreateCommand),
+    Show(ShowCommand),
 }
 
 impl ForwarderCommand {
-    pub fn run(self, opts: CommandGlobalOpts) {
+    pub fn run(self, opts: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             ForwarderSubCommand::Create(c) => c.run(opts),
+            ForwarderSubCommand::Show(c) => c.run(opts),
         }
     }
 }
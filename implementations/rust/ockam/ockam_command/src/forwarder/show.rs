@@ -0,0 +1,60 @@
+use clap::Args;
+use colorful::Colorful;
+
+use ockam::Context;
+use ockam_api::nodes::models::forwarder::ForwarderInfo;
+
+use crate::forwarder::HELP_DETAIL;
+use crate::node::NodeOpts;
+use crate::util::{api, node_rpc, Rpc};
+use crate::{help, CommandGlobalOpts, OutputFormat, Result};
+
+/// Show Forwarders
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, after_long_help = help::template(HELP_DETAIL))]
+pub struct ShowCommand {
+    /// Remote address of the forwarder, like forward_to_blue
+    remote_address: String,
+
+    #[command(flatten)]
+    node_opts: NodeOpts,
+}
+
+impl ShowCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, ShowCommand)) -> Result<()> {
+    let mut rpc = Rpc::background(&ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request(api::show_forwarder(&cmd.remote_address))
+        .await?;
+    let info = rpc.parse_response::<ForwarderInfo>()?;
+
+    match opts.global_args.output_format {
+        OutputFormat::Plain => {
+            let ttl = match info.remaining_ttl() {
+                Some(ttl) => format!("{}s", ttl.as_secs()),
+                None => "none".to_string(),
+            };
+            println!("\n  Forwarder:");
+            for (key, value) in [
+                (
+                    "Remote address",
+                    format!("/service/{}", info.remote_address()),
+                ),
+                ("Route", info.forwarding_route().to_string()),
+                ("Worker", info.worker_address().to_string()),
+                ("Queued", info.is_queued().to_string()),
+                ("TTL remaining", ttl),
+            ] {
+                println!("{} {}", format!("  • {key:>14}:").light_magenta(), value);
+            }
+        }
+        OutputFormat::Json => {
+            rpc.print_response(info)?;
+        }
+    }
+    Ok(())
+}
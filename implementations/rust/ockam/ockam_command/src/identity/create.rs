@@ -4,6 +4,7 @@ use crate::util::{node_rpc, Rpc};
 use crate::CommandGlobalOpts;
 use clap::Args;
 use ockam::Context;
+use ockam_api::nodes::models::identity::{CreateIdentityRequest, IdentityKeyBackend};
 use ockam_core::api::Request;
 
 #[derive(Clone, Debug, Args)]
@@ -11,10 +12,23 @@ use ockam_core::api::Request;
 pub struct CreateCommand {
     #[command(flatten)]
     node_opts: NodeOpts,
+
+    /// Generate the root key of the identity in AWS KMS, configured from the
+    /// environment, so it can't be exported. Requires a node built with the
+    /// `aws-kms` feature
+    #[arg(long)]
+    aws_kms: bool,
+
+    /// Generate the root key of the identity in a PKCS#11 token, e.g. a
+    /// YubiHSM, configured with the `OCKAM_PKCS11_MODULE`, `OCKAM_PKCS11_SLOT`
+    /// and `OCKAM_PKCS11_PIN_FILE` (or `OCKAM_PKCS11_PIN`) variables. Requires
+    /// a node built with the `pkcs11` feature
+    #[arg(long, conflicts_with = "aws_kms")]
+    pkcs11: bool,
 }
 
 impl CreateCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         node_rpc(run_impl, (options, self))
     }
 }
@@ -24,7 +38,14 @@ async fn run_impl(
     (options, cmd): (CommandGlobalOpts, CreateCommand),
 ) -> crate::Result<()> {
     let mut rpc = Rpc::background(&ctx, &options, &cmd.node_opts.api_node)?;
-    let request = Request::post("/node/identity");
+    let key_backend = if cmd.aws_kms {
+        IdentityKeyBackend::AwsKms
+    } else if cmd.pkcs11 {
+        IdentityKeyBackend::Pkcs11
+    } else {
+        IdentityKeyBackend::Software
+    };
+    let request = Request::post("/node/identity").body(CreateIdentityRequest::new(key_backend));
     rpc.request(request).await?;
     rpc.parse_response()?;
 
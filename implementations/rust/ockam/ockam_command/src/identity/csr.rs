@@ -0,0 +1,43 @@
+use crate::node::NodeOpts;
+use crate::util::{node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+use anyhow::Context as _;
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::identity::IdentityCsrResponse;
+use ockam_core::api::Request;
+use std::path::PathBuf;
+
+/// Export a certificate signing request for the identity of a node
+#[derive(Clone, Debug, Args)]
+pub struct CsrCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+
+    /// File where the DER encoded request is written
+    #[arg(long, short)]
+    output: PathBuf,
+}
+
+impl CsrCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (options, cmd): (CommandGlobalOpts, CsrCommand),
+) -> crate::Result<()> {
+    let mut rpc = Rpc::background(&ctx, &options, &cmd.node_opts.api_node)?;
+    rpc.request(Request::get("/node/identity/csr")).await?;
+    let response = rpc.parse_response::<IdentityCsrResponse>()?;
+
+    std::fs::write(&cmd.output, &*response.csr)
+        .with_context(|| format!("Failed to write {}", cmd.output.display()))?;
+    println!(
+        "Certificate signing request written to {}",
+        cmd.output.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use crate::node::NodeOpts;
+use crate::util::{node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+use anyhow::Context as _;
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::identity::ImportIdentityCertificateRequest;
+use ockam_core::api::Request;
+use std::path::PathBuf;
+
+/// Import an X.509 certificate binding the identity of a node, issued for
+/// the request exported with `ockam identity csr`
+#[derive(Clone, Debug, Args)]
+pub struct ImportCertificateCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+
+    /// DER encoded certificate
+    #[arg(long)]
+    certificate: PathBuf,
+
+    /// DER encoded certificate of the issuing CA, the certificate is
+    /// verified against it
+    #[arg(long)]
+    ca_certificate: PathBuf,
+}
+
+impl ImportCertificateCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (options, cmd): (CommandGlobalOpts, ImportCertificateCommand),
+) -> crate::Result<()> {
+    let read = |path: &PathBuf| {
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))
+    };
+    let certificate = read(&cmd.certificate)?;
+    let ca_certificate = read(&cmd.ca_certificate)?;
+
+    let mut rpc = Rpc::background(&ctx, &options, &cmd.node_opts.api_node)?;
+    let request = Request::post("/node/identity/certificate").body(
+        ImportIdentityCertificateRequest::new(certificate, ca_certificate),
+    );
+    rpc.request(request).await?;
+    rpc.is_ok()?;
+
+    println!("Certificate imported!");
+    Ok(())
+}
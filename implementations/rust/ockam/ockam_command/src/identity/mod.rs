@@ -1,7 +1,11 @@
 mod create;
+mod csr;
+mod import_certificate;
 mod show;
 
 pub(crate) use create::CreateCommand;
+pub(crate) use csr::CsrCommand;
+pub(crate) use import_certificate::ImportCertificateCommand;
 pub(crate) use show::ShowCommand;
 
 use crate::CommandGlobalOpts;
@@ -20,13 +24,19 @@ pub enum IdentitySubcommand {
     Create(CreateCommand),
     /// Print short existing identity, `--full` for long identity
     Show(ShowCommand),
+    /// Export a certificate signing request for the identity
+    Csr(CsrCommand),
+    /// Import a CA-issued certificate binding the identity
+    ImportCertificate(ImportCertificateCommand),
 }
 
 impl IdentityCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             IdentitySubcommand::Create(c) => c.run(options),
-            IdentitySubcommand::Show(c) => c.run(options).unwrap(),
+            IdentitySubcommand::Show(c) => c.run(options),
+            IdentitySubcommand::Csr(c) => c.run(options),
+            IdentitySubcommand::ImportCertificate(c) => c.run(options),
         }
     }
 }
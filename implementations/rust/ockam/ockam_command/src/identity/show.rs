@@ -1,6 +1,8 @@
+use crate::error::Error;
 use crate::util::{connect_to, exitcode, extract_address_value};
 use crate::CommandGlobalOpts;
 use crate::{node::NodeOpts, util::api};
+use anyhow::anyhow;
 use clap::Args;
 use ockam::{Context, Route};
 use ockam_api::nodes::NODEMANAGER_ADDR;
@@ -15,14 +17,12 @@ pub struct ShowCommand {
 }
 
 impl ShowCommand {
-    pub fn run(self, options: CommandGlobalOpts) -> anyhow::Result<()> {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         let cfg = options.config;
         let node = extract_address_value(&self.node_opts.api_node)?;
-        let port = cfg.get_node_port(&node).unwrap();
+        let port = cfg.get_node_port(&node)?;
 
-        connect_to(port, self, show_identity);
-
-        Ok(())
+        connect_to(port, self, show_identity)
     }
 }
 
@@ -46,8 +46,11 @@ pub async fn show_identity(
                 println!("{}", hex::encode(result.identity.0.as_ref()))
             }
             _ => {
-                eprintln!("An error occurred while exporting Identity",);
-                std::process::exit(exitcode::IOERR);
+                return Err(Error::new(
+                    exitcode::API_ERROR,
+                    anyhow!("An error occurred while exporting Identity"),
+                )
+                .into());
             }
         }
 
@@ -67,8 +70,11 @@ pub async fn show_identity(
                 println!("{}", result.identity_id)
             }
             _ => {
-                eprintln!("An error occurred while getting Identity",);
-                std::process::exit(exitcode::IOERR);
+                return Err(Error::new(
+                    exitcode::API_ERROR,
+                    anyhow!("An error occurred while getting Identity"),
+                )
+                .into());
             }
         }
 
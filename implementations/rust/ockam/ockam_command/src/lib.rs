@@ -3,24 +3,33 @@
 
 mod admin;
 mod authenticated;
+mod authority;
 mod completion;
 mod configuration;
 mod credential;
 mod enroll;
 mod error;
+mod file;
 mod forwarder;
 mod help;
 mod identity;
 mod message;
 mod node;
+mod perf;
+mod policy;
 mod project;
 mod reset;
+mod route;
 mod secure_channel;
 mod service;
+mod session;
+mod shell;
 mod space;
+mod status;
 mod subscription;
 mod tcp;
 mod terminal;
+mod trace;
 mod upgrade;
 mod util;
 mod vault;
@@ -28,25 +37,36 @@ mod version;
 
 use anyhow::Context;
 use authenticated::AuthenticatedCommand;
+use authority::AuthorityCommand;
 use completion::CompletionCommand;
 use configuration::ConfigurationCommand;
 use credential::CredentialCommand;
 use enroll::EnrollCommand;
 use error::{Error, Result};
+use file::FileCommand;
 use forwarder::ForwarderCommand;
 use identity::IdentityCommand;
 use message::MessageCommand;
 use node::NodeCommand;
+use ockam_api::nodes::service::LogFilter;
+use perf::PerfCommand;
+use policy::PolicyCommand;
 use project::ProjectCommand;
 use reset::ResetCommand;
+use route::RouteCommand;
 use secure_channel::{listener::SecureChannelListenerCommand, SecureChannelCommand};
 use service::ServiceCommand;
+use session::SessionCommand;
+use shell::ShellCommand;
 use space::SpaceCommand;
+use status::StatusCommand;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tcp::{
     connection::TcpConnectionCommand, inlet::TcpInletCommand, listener::TcpListenerCommand,
     outlet::TcpOutletCommand,
 };
+use trace::TraceCommand;
 use util::{exitcode, exitcode::ExitCode, setup_logging, OckamConfig};
 use vault::VaultCommand;
 use version::Version;
@@ -174,7 +194,8 @@ pub struct GlobalArgs {
     )]
     help: Option<bool>,
 
-    /// Do not print any trace messages
+    /// Do not print any trace messages, nor the human-readable output of commands.
+    /// Failures are reported by the exit code of the command
     #[arg(global = true, long, short, conflicts_with("verbose"))]
     quiet: bool,
 
@@ -243,6 +264,8 @@ impl ExportCommandArgs {
 pub struct CommandGlobalOpts {
     pub global_args: GlobalArgs,
     pub config: OckamConfig,
+    /// The tracing filter of this process, if logging is enabled.
+    pub log_filter: Option<Arc<dyn LogFilter>>,
 }
 
 impl CommandGlobalOpts {
@@ -250,6 +273,7 @@ impl CommandGlobalOpts {
         Self {
             global_args,
             config,
+            log_filter: None,
         }
     }
 }
@@ -264,6 +288,8 @@ pub enum OckamSubcommand {
     Project(ProjectCommand),
     #[command(display_order = 803)]
     Reset(ResetCommand),
+    #[command(display_order = 804)]
+    Status(StatusCommand),
 
     #[command(display_order = 811)]
     Node(NodeCommand),
@@ -285,9 +311,25 @@ pub enum OckamSubcommand {
     Forwarder(ForwarderCommand),
     #[command(display_order = 820)]
     Message(MessageCommand),
+    #[command(display_order = 821)]
+    File(FileCommand),
+    #[command(display_order = 822)]
+    Route(RouteCommand),
+    #[command(display_order = 823)]
+    Policy(PolicyCommand),
+    #[command(display_order = 824)]
+    Perf(PerfCommand),
+    #[command(display_order = 825)]
+    Trace(TraceCommand),
+    #[command(display_order = 826)]
+    Session(SessionCommand),
+    #[command(display_order = 827)]
+    Authority(AuthorityCommand),
 
     #[command(display_order = 900)]
     Completion(CompletionCommand),
+    #[command(display_order = 901)]
+    Shell(ShellCommand),
 
     Authenticated(AuthenticatedCommand),
     Configuration(ConfigurationCommand),
@@ -309,8 +351,9 @@ pub fn run() {
         check_if_an_upgrade_is_available();
     }
 
+    let mut log_filter = None;
     if !command.global_args.quiet {
-        setup_logging(command.global_args.verbose, command.global_args.no_color);
+        log_filter = setup_logging(command.global_args.verbose, command.global_args.no_color);
         tracing::debug!("{}", Version::short());
         tracing::debug!("Parsed {:?}", &command);
     }
@@ -324,19 +367,34 @@ pub fn run() {
         return;
     }
 
-    command.run();
+    let res = command.run_with_log_filter(log_filter);
+
+    #[cfg(feature = "telemetry")]
+    ockam_api::telemetry::shutdown();
+
+    // The only place where a failing command exits with its code
+    if let Err(e) = res {
+        tracing::error!(%e);
+        eprintln!("{e:?}");
+        std::process::exit(e.code());
+    }
 }
 
 impl OckamCommand {
-    pub fn run(self) {
+    pub fn run(self) -> crate::Result<()> {
+        self.run_with_log_filter(None)
+    }
+
+    fn run_with_log_filter(self, log_filter: Option<Arc<dyn LogFilter>>) -> crate::Result<()> {
         let config = OckamConfig::load().expect("Failed to load config");
-        let options = CommandGlobalOpts::new(self.global_args, config);
+        let mut options = CommandGlobalOpts::new(self.global_args, config);
+        options.log_filter = log_filter;
 
         // If test_argument_parser is true, command arguments are checked
         // but the command is not executed. This is useful to test arguments
         // without having to execute their logic.
         if options.global_args.test_argument_parser {
-            return;
+            return Ok(());
         }
 
         match self.subcommand {
@@ -345,6 +403,14 @@ impl OckamCommand {
             OckamSubcommand::Enroll(c) => c.run(options),
             OckamSubcommand::Forwarder(c) => c.run(options),
             OckamSubcommand::Message(c) => c.run(options),
+            OckamSubcommand::File(c) => c.run(options),
+            OckamSubcommand::Route(c) => c.run(options),
+            OckamSubcommand::Policy(c) => c.run(options),
+            OckamSubcommand::Perf(c) => c.run(options),
+            OckamSubcommand::Trace(c) => c.run(options),
+            OckamSubcommand::Session(c) => c.run(options),
+            OckamSubcommand::Authority(c) => c.run(options),
+            OckamSubcommand::Status(c) => c.run(options),
             OckamSubcommand::Node(c) => c.run(options),
             OckamSubcommand::Project(c) => c.run(options),
             OckamSubcommand::Space(c) => c.run(options),
@@ -358,6 +424,7 @@ impl OckamCommand {
             OckamSubcommand::SecureChannelListener(c) => c.run(options),
             OckamSubcommand::Service(c) => c.run(options),
             OckamSubcommand::Completion(c) => c.run(),
+            OckamSubcommand::Shell(c) => c.run(options),
             OckamSubcommand::Credential(c) => c.run(options),
             OckamSubcommand::Subscription(c) => c.run(options),
             OckamSubcommand::Reset(c) => c.run(options),
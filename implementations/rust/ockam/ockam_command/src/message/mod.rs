@@ -72,6 +72,10 @@ Examples:
     $ ockam message send hello --from /node/n1 --to /node/n2/service/uppercase
     HELLO
 
+    # Check that a relay delivers messages, without waiting for a reply,
+    # with a 5 seconds timeout and 2 retries
+    $ ockam message send hello --ack --timeout 5 --retry 2 --to /node/n2/service/forward_to_n3/service/sink
+
     # Create a secure channel from node n1 to the api service on node n2
     # The /service/api is a secure channel listener that is started on every node
     # Send a message through this encrypted channel to the uppercase service
@@ -100,7 +104,7 @@ pub enum MessageSubcommand {
 }
 
 impl MessageCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             MessageSubcommand::Send(c) => c.run(options),
         }
@@ -1,18 +1,22 @@
-use anyhow::Context as _;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context as _};
 use clap::Args;
 
+use minicbor::data::Type;
 use ockam::{Context, TcpTransport};
 use ockam_api::clean_multiaddr;
 use ockam_api::nodes::models::secure_channel::CredentialExchangeMode;
-use ockam_api::nodes::service::message::SendMessage;
-use ockam_core::api::{Request, RequestBuilder};
+use ockam_api::nodes::service::message::{SendMessage, SendMessageError, SendMessageFailure};
+use ockam_core::api::{Request, RequestBuilder, Status};
+use ockam_core::CowBytes;
 use ockam_multiaddr::MultiAddr;
 
 use crate::node::util::{delete_embedded_node, start_embedded_node};
 use crate::util::api::CloudOpts;
-use crate::util::{extract_address_value, node_rpc, RpcBuilder};
-use crate::Result;
-use crate::{help, message::HELP_DETAIL, CommandGlobalOpts};
+use crate::util::{exitcode, extract_address_value, node_rpc, RpcBuilder};
+use crate::{help, message::HELP_DETAIL, CommandGlobalOpts, OutputFormat};
+use crate::{Error, Result};
 
 /// Send messages
 #[derive(Clone, Debug, Args)]
@@ -26,9 +30,18 @@ pub struct SendCommand {
     #[arg(short, long, value_name = "ROUTE")]
     pub to: MultiAddr,
 
-    /// Override Default Timeout
-    #[arg(long, value_name = "TIMEOUT")]
-    pub timeout: Option<u64>,
+    /// How long to wait for the reply, or the acknowledgement, in seconds
+    #[arg(long, value_name = "TIMEOUT", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Wait for the ack service of the last node to hand the message over,
+    /// rather than for a reply
+    #[arg(long)]
+    pub ack: bool,
+
+    /// How many times to send the message again after a timeout
+    #[arg(long, value_name = "RETRIES", default_value_t = 0)]
+    pub retry: u32,
 
     pub message: String,
 
@@ -37,7 +50,7 @@ pub struct SendCommand {
 }
 
 impl SendCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         node_rpc(rpc, (options, self))
     }
 }
@@ -45,8 +58,16 @@ impl SendCommand {
 async fn rpc(mut ctx: Context, (opts, cmd): (CommandGlobalOpts, SendCommand)) -> Result<()> {
     async fn go(ctx: &mut Context, opts: &CommandGlobalOpts, cmd: SendCommand) -> Result<()> {
         // Process `--to` Multiaddr
-        let (to, meta) = clean_multiaddr(&cmd.to, &opts.config.lookup())
-            .context("Argument '--to' is invalid")?;
+        let (to, meta) = match clean_multiaddr(&cmd.to, &opts.config.lookup()) {
+            Some(to) => to,
+            None => {
+                let err = SendMessageError::new(
+                    SendMessageFailure::Route,
+                    format!("Argument '--to' is invalid: {}", cmd.to),
+                );
+                return Err(failure(opts, err, 0));
+            }
+        };
 
         // Setup environment depending on whether we are sending the message from an embedded node or a background node
         let (api_node, tcp) = if let Some(node) = &cmd.from {
@@ -68,19 +89,59 @@ async fn rpc(mut ctx: Context, (opts, cmd): (CommandGlobalOpts, SendCommand)) ->
             tcp.as_ref(),
             CredentialExchangeMode::None,
         )
-        .await?;
-        let to = crate::project::util::clean_projects_multiaddr(to, projects_sc)?;
+        .await
+        .and_then(|projects_sc| crate::project::util::clean_projects_multiaddr(to, projects_sc));
+        let to = match projects_sc {
+            Ok(to) => to,
+            Err(err) => {
+                let err = SendMessageError::new(SendMessageFailure::Route, err.to_string());
+                return Err(failure(opts, err, 0));
+            }
+        };
 
-        // Send request
+        // Send request, again after a timeout if retries are left
+        let timeout = Duration::from_secs(cmd.timeout);
         let mut rpc = RpcBuilder::new(ctx, opts, &api_node)
             .tcp(tcp.as_ref())?
             .build();
-        rpc.request(req(&to, &cmd.message)).await?;
-        let res = rpc.parse_response::<Vec<u8>>()?;
-        println!(
-            "{}",
-            String::from_utf8(res).context("Received content is not a valid utf8 string")?
-        );
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            // Leave the node the time to report the timeout
+            rpc.request_with_timeout(
+                req(&to, &cmd.message, timeout, cmd.ack),
+                timeout + Duration::from_secs(5),
+            )
+            .await?;
+            let (hdr, mut dec) = rpc.check_response()?;
+            if hdr.status() == Some(Status::Ok) {
+                // Older nodes reply with an array of integers, not a byte string
+                let res: Vec<u8> = match dec.datatype() {
+                    Ok(Type::Array | Type::ArrayIndef) => dec.decode(),
+                    _ => dec.decode::<CowBytes>().map(CowBytes::into_owned),
+                }
+                .context("Failed to decode response body")?;
+                if !cmd.ack {
+                    println!(
+                        "{}",
+                        std::str::from_utf8(&res)
+                            .context("Received content is not a valid utf8 string")?
+                    );
+                }
+                break;
+            }
+            let err = match dec.decode::<SendMessageError>() {
+                Ok(err) => SendMessageError::new(err.failure, err.message.to_string()),
+                Err(_) => {
+                    SendMessageError::new(SendMessageFailure::Other, rpc.parse_err_msg(hdr, dec))
+                }
+            };
+            if err.failure == SendMessageFailure::Timeout && attempts <= cmd.retry {
+                tracing::debug!(attempts, "no reply, sending the message again");
+                continue;
+            }
+            return Err(failure(opts, err, attempts));
+        }
 
         // only delete node in case 'from' is empty and embedded node was started before
         if cmd.from.is_none() {
@@ -92,6 +153,34 @@ async fn rpc(mut ctx: Context, (opts, cmd): (CommandGlobalOpts, SendCommand)) ->
     go(&mut ctx, &opts, cmd).await
 }
 
-pub(crate) fn req<'a>(to: &'a MultiAddr, message: &'a str) -> RequestBuilder<'a, SendMessage<'a>> {
-    Request::post("v0/message").body(SendMessage::new(to, message.as_bytes()))
+/// Report a failure, as JSON if requested, with an exit code telling
+/// failures to resolve the route from timeouts
+fn failure(opts: &CommandGlobalOpts, err: SendMessageError, attempts: u32) -> Error {
+    if opts.global_args.output_format == OutputFormat::Json {
+        let json = serde_json::json!({
+            "failure": err.failure,
+            "message": err.message,
+            "attempts": attempts,
+        });
+        println!("{json}");
+    }
+    let code = match err.failure {
+        SendMessageFailure::Route => exitcode::NOHOST,
+        SendMessageFailure::Timeout => exitcode::TEMPFAIL,
+        SendMessageFailure::NotDelivered => exitcode::UNAVAILABLE,
+        SendMessageFailure::Other => exitcode::SOFTWARE,
+    };
+    Error::new(code, anyhow!("{}", err.message))
+}
+
+pub(crate) fn req<'a>(
+    to: &'a MultiAddr,
+    message: &'a str,
+    timeout: Duration,
+    ack: bool,
+) -> RequestBuilder<'a, SendMessage<'a>> {
+    let body = SendMessage::new(to, message.as_bytes())
+        .with_timeout(timeout)
+        .with_ack(ack);
+    Request::post("v0/message").body(body)
 }
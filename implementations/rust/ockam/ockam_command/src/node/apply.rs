@@ -0,0 +1,77 @@
+use anyhow::{anyhow, Context as _};
+use clap::Args;
+use ockam::{Context, TcpTransport};
+use ockam_api::nodes::models::config::{NodeSetup, SetupChanges};
+use std::path::PathBuf;
+
+use crate::util::{api, exitcode, node_rpc, secret, RpcBuilder};
+use crate::{help, node::HELP_DETAIL, CommandGlobalOpts};
+
+/// Converge a running node to the setup of a configuration file
+///
+/// Whatever the node set up from its previous configuration and is not in
+/// the file anymore is deleted, and whatever is new in the file is created.
+/// The file is applied again when the node restarts.
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(HELP_DETAIL))]
+pub struct ApplyCommand {
+    /// Name of the node.
+    #[arg(default_value = "default")]
+    node_name: String,
+
+    /// YAML file declaring the setup of the node, as for `ockam node create`
+    #[arg(long)]
+    config: PathBuf,
+
+    /// Only show the changes, without making them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl ApplyCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, ApplyCommand)) -> crate::Result<()> {
+    let s = tokio::fs::read_to_string(&cmd.config)
+        .await
+        .with_context(|| format!("Failed to read {}", cmd.config.display()))?;
+    let setup: NodeSetup =
+        secret::from_yaml(&s).context("Failed to parse the node configuration")?;
+
+    let tcp = TcpTransport::create(&ctx).await?;
+    let mut rpc = RpcBuilder::new(&ctx, &opts, &cmd.node_name)
+        .tcp(&tcp)?
+        .build();
+    rpc.request(api::apply_node_config(setup, cmd.dry_run))
+        .await?;
+    let changes = rpc.parse_response::<SetupChanges>()?;
+    print_changes(&changes);
+
+    if changes.failed.is_empty() {
+        Ok(())
+    } else {
+        Err(crate::Error::new(
+            exitcode::SOFTWARE,
+            anyhow!("{} changes failed", changes.failed.len()),
+        ))
+    }
+}
+
+fn print_changes(changes: &SetupChanges) {
+    if changes.is_empty() {
+        println!("Nothing to change");
+        return;
+    }
+    for c in &changes.deleted {
+        println!("- {c}")
+    }
+    for c in &changes.created {
+        println!("+ {c}")
+    }
+    for c in &changes.failed {
+        println!("! {c}")
+    }
+}
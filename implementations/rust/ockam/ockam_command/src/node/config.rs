@@ -0,0 +1,35 @@
+use anyhow::Context as _;
+use clap::Args;
+use ockam::{Context, TcpTransport};
+use ockam_api::nodes::models::config::NodeSetup;
+
+use crate::util::{api, node_rpc, RpcBuilder};
+use crate::{help, node::HELP_DETAIL, CommandGlobalOpts};
+
+/// Show the setup a node applied from its configuration file
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(HELP_DETAIL))]
+pub struct ConfigCommand {
+    /// Name of the node.
+    #[arg(default_value = "default")]
+    node_name: String,
+}
+
+impl ConfigCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, ConfigCommand)) -> crate::Result<()> {
+    let tcp = TcpTransport::create(&ctx).await?;
+    let mut rpc = RpcBuilder::new(&ctx, &opts, &cmd.node_name)
+        .tcp(&tcp)?
+        .build();
+    rpc.request(api::node_config()).await?;
+    let setup = rpc.parse_response::<NodeSetup>()?;
+    // The output can be given back to `ockam node create --setup`.
+    let yaml = serde_yaml::to_string(&setup).context("Failed to serialize the node setup")?;
+    print!("{yaml}");
+    Ok(())
+}
@@ -3,9 +3,10 @@ use rand::prelude::random;
 
 use anyhow::{anyhow, Context as _, Result};
 use std::{
-    net::{IpAddr, SocketAddr},
+    net::SocketAddr,
     path::{Path, PathBuf},
     str::FromStr,
+    time::{Duration, Instant},
 };
 
 use crate::node::util::run::CommandsRunner;
@@ -16,18 +17,24 @@ use crate::project::ProjectInfo;
 use crate::secure_channel::listener::create as secure_channel_listener;
 use crate::service::config::Config;
 use crate::service::start;
-use crate::util::{bind_to_port_check, embedded_node_that_is_not_stopped, exitcode};
+use crate::util::api::CloudOpts;
+use crate::util::{
+    bind_to_port_check, embedded_node_that_is_not_stopped, exitcode, secret, OckamConfig,
+};
 use crate::{
     help,
     node::show::print_query_status,
     node::HELP_DETAIL,
     project,
-    util::{connect_to, embedded_node, find_available_port, startup},
+    util::{connect_to, embedded_node, startup},
     CommandGlobalOpts,
 };
 use ockam::{Address, AsyncTryClone, TCP};
 use ockam::{Context, TcpTransport};
 use ockam_api::{
+    nodes::config::{NodeApiConfig, NodeConfig},
+    nodes::models::config::NodeSetup,
+    nodes::models::services::ServiceOptions,
     nodes::models::transport::{TransportMode, TransportType},
     nodes::{
         service::{
@@ -51,6 +58,9 @@ pub struct CreateCommand {
     pub foreground: bool,
 
     /// TCP listener address
+    ///
+    /// With port 0, the default, the node listens on a port picked by the
+    /// system and records it in the configuration.
     #[arg(
         display_order = 900,
         long,
@@ -87,11 +97,51 @@ pub struct CreateCommand {
     #[arg(long, hide = true)]
     pub no_watchdog: bool,
 
+    /// Also serve the node API on a Unix domain socket in the node
+    /// directory, which only the current user can access.
+    ///
+    /// Commands run by that user then reach the node through the socket
+    /// instead of its TCP listener.
+    #[arg(long)]
+    pub api_socket: bool,
+
+    /// Only serve the node API to local clients on its socket
+    ///
+    /// Requests arriving over the TCP listener without a secure channel are
+    /// refused. Remote identities can still use the API over secure
+    /// channels, as allowed by the node's policies.
+    #[arg(long, requires = "api_socket")]
+    pub no_tcp_api: bool,
+
+    /// Number of times a crashed background node is restarted before giving up
+    #[arg(long, default_value_t = startup::DEFAULT_MAX_RESTARTS)]
+    pub max_restarts: u32,
+
     #[arg(long, hide = true)]
     pub project: Option<PathBuf>,
 
     #[arg(long, hide = true)]
     pub config: Option<PathBuf>,
+
+    /// YAML file declaring what the node sets up when it starts
+    ///
+    /// The file may declare `transports`, `listeners`, `services`,
+    /// `forwarders`, `inlets`, `outlets`, `policies` and the types of the
+    /// `attributes` policies refer to, which the node applies every time it
+    /// starts, in the background. Secure `channels` to known destinations,
+    /// e.g. `/project/default`, are opened ahead of their first use and kept
+    /// open. Use `ockam node config` to show what was applied.
+    ///
+    /// String values can be read from the environment with `@env:NAME`
+    /// or from a file with `@file:PATH`. The setup is stored in the node
+    /// directory and applied the same way by foreground and background
+    /// nodes.
+    #[arg(long = "setup", value_name = "YAML_FILE")]
+    pub setup_file: Option<PathBuf>,
+
+    /// Setup to store for the node, instead of the one read from `setup_file`
+    #[arg(skip)]
+    pub setup: Option<NodeSetup>,
 }
 
 impl Default for CreateCommand {
@@ -106,50 +156,43 @@ impl Default for CreateCommand {
             child_process: false,
             launch_config: None,
             no_watchdog: false,
+            api_socket: false,
+            no_tcp_api: false,
+            max_restarts: startup::DEFAULT_MAX_RESTARTS,
             project: None,
             config: None,
+            setup_file: None,
+            setup: None,
         }
     }
 }
 
 impl CreateCommand {
-    pub fn run(self, opts: CommandGlobalOpts) {
-        if let Err(e) = run_impl(opts, self) {
-            eprintln!("{}", e);
-            std::process::exit(e.code());
-        }
-    }
-
-    fn overwrite_addr(&self) -> Result<Self> {
-        let cmd = self.clone();
-        let addr: SocketAddr = if &cmd.tcp_listener_address == "127.0.0.1:0" {
-            let port = find_available_port().context("failed to acquire available port")?;
-            SocketAddr::new(IpAddr::from_str("127.0.0.1")?, port)
-        } else {
-            cmd.tcp_listener_address.parse()?
-        };
-        Ok(Self {
-            tcp_listener_address: addr.to_string(),
-            ..cmd
-        })
+    pub fn run(self, opts: CommandGlobalOpts) -> crate::Result<()> {
+        run_impl(opts, self)
     }
 }
 
+/// How long to wait for a background node to record the port it listens on
+const PORT_TIMEOUT: Duration = Duration::from_secs(30);
+
 fn run_impl(opts: CommandGlobalOpts, cmd: CreateCommand) -> crate::Result<()> {
     let verbose = opts.global_args.verbose;
     let cfg = &opts.config;
+    let addr = SocketAddr::from_str(&cmd.tcp_listener_address)?;
     if cmd.foreground {
-        let cmd = cmd.overwrite_addr()?;
-        let addr = SocketAddr::from_str(&cmd.tcp_listener_address)?;
         // HACK: try to get the current node dir.  If it doesn't
         // exist the user PROBABLY started a non-detached node.
         // Thus we need to create the node dir so that subsequent
         // calls to it don't fail
         if cfg.get_node_dir(&cmd.node_name).is_err() {
+            check_port_available(cfg, &addr)?;
             println!("Creating node directory...");
             cfg.create_node(&cmd.node_name, addr, verbose)?;
             cfg.persist_config_updates()?;
         }
+        store_node_setup(cfg, &cmd)?;
+        store_node_state(cfg, &cmd)?;
         embedded_node_that_is_not_stopped(run_foreground_node, (opts.clone(), cmd, addr))?;
     } else {
         if cmd.child_process {
@@ -159,14 +202,16 @@ fn run_impl(opts: CommandGlobalOpts, cmd: CreateCommand) -> crate::Result<()> {
             ));
         }
 
-        let cmd = cmd.overwrite_addr()?;
-        let addr = SocketAddr::from_str(&cmd.tcp_listener_address)?;
         embedded_node(spawn_background_node, (opts.clone(), cmd.clone(), addr))?;
+        let port = match addr.port() {
+            0 => wait_for_node_port(cfg, &cmd.node_name, addr)?,
+            port => port,
+        };
         connect_to(
-            addr.port(),
+            port,
             (cfg.clone(), cmd.node_name.clone(), true),
             print_query_status,
-        );
+        )?;
         if let Some(config_path) = &cmd.config {
             let node_config = cfg.node(&cmd.node_name)?;
             let commands = CommandsRunner::new(config_path.clone())?;
@@ -208,20 +253,38 @@ async fn run_foreground_node(
     };
 
     let tcp = TcpTransport::create(&ctx).await?;
-    let bind = cmd.tcp_listener_address;
-    tcp.listen(&bind).await?;
+    let bind = tcp
+        .listen(&cmd.tcp_listener_address)
+        .await
+        .with_context(|| format!("Failed to listen on {addr}, is the port already in use?"))?;
+    if addr.port() == 0 {
+        // Let clients find the port picked by the system
+        cfg.persist_node_addr(&cmd.node_name, bind)?;
+    }
+    let addr = bind;
+    let bind = bind.to_string();
 
     let node_dir = cfg.get_node_dir(&cmd.node_name)?;
     let projects = cfg.inner().lookup().projects().collect();
+    let mut general_options = NodeManagerGeneralOptions::new(
+        cmd.node_name.clone(),
+        node_dir,
+        cmd.skip_defaults || cmd.launch_config.is_some(),
+        cmd.enable_credential_checks,
+        identity_override,
+    );
+    if let Some(filter) = &opts.log_filter {
+        general_options = general_options.with_log_filter(filter.clone());
+    }
+    if let Some(policy) = CloudOpts::retry_policy()? {
+        general_options = general_options.with_controller_retry_policy(policy);
+    }
+    if cmd.no_tcp_api {
+        general_options = general_options.without_tcp_api();
+    }
     let node_man = NodeManager::create(
         &ctx,
-        NodeManagerGeneralOptions::new(
-            cmd.node_name.clone(),
-            node_dir,
-            cmd.skip_defaults || cmd.launch_config.is_some(),
-            cmd.enable_credential_checks,
-            identity_override,
-        ),
+        general_options,
         NodeManagerProjectsOptions::new(
             Some(&cfg.authorities(&cmd.node_name)?.snapshot()),
             project_id,
@@ -238,6 +301,10 @@ async fn run_foreground_node(
     ctx.start_worker(NODEMANAGER_ADDR, node_manager_worker)
         .await?;
 
+    if cmd.api_socket {
+        serve_api_socket(&ctx, &cfg.get_node_dir(&cmd.node_name)?).await?;
+    }
+
     if let Some(path) = cmd.launch_config {
         let node_opts = super::NodeOpts {
             api_node: cmd.node_name,
@@ -248,6 +315,16 @@ async fn run_foreground_node(
     Ok(())
 }
 
+#[cfg(unix)]
+async fn serve_api_socket(ctx: &Context, node_dir: &Path) -> Result<()> {
+    crate::util::api_socket::serve(ctx, node_dir).await
+}
+
+#[cfg(not(unix))]
+async fn serve_api_socket(_ctx: &Context, _node_dir: &Path) -> Result<()> {
+    Err(anyhow!("The API socket is only supported on Unix"))
+}
+
 async fn start_services(
     ctx: &Context,
     tcp: &TcpTransport,
@@ -286,37 +363,139 @@ async fn start_services(
         if !cfg.disabled {
             let adr = Address::from((LOCAL, cfg.address));
             let ids = cfg.authorized_identifiers;
+            let limits = secure_channel_listener::ListenerLimitsOpts {
+                max_channels: cfg.max_channels,
+                max_channels_per_identity: cfg.max_channels_per_identity,
+            };
+            let key_exchange = secure_channel_listener::key_exchange(cfg.hybrid_key_exchange);
+            let members = secure_channel_listener::MemberSyncOpts {
+                sync_members: cfg.sync_members,
+                sync_interval: cfg.sync_interval,
+            };
             let rte = addr.clone().into();
             println!("starting secure-channel listener ...");
-            secure_channel_listener::create_listener(ctx, adr, ids, rte).await?;
+            secure_channel_listener::create_listener(
+                ctx,
+                adr,
+                ids,
+                Vec::new(),
+                limits,
+                members,
+                key_exchange,
+                rte,
+            )
+            .await?;
         }
     }
     if let Some(cfg) = config.verifier {
         if !cfg.disabled {
             println!("starting verifier service ...");
-            start::start_verifier_service(ctx, opts, &node_opts.api_node, &cfg.address, Some(tcp))
-                .await?
+            let (node, addr) = (&node_opts.api_node, &cfg.address);
+            let options = ServiceOptions::default();
+            start::start_verifier_service(ctx, opts, node, addr, options, Some(tcp)).await?
         }
     }
     if let Some(cfg) = config.authenticator {
         if !cfg.disabled {
             println!("starting authenticator service ...");
-            start::start_authenticator_service(
-                ctx,
-                opts,
-                &node_opts.api_node,
-                &cfg.address,
-                &cfg.enrollers,
-                &cfg.project,
-                Some(tcp),
-            )
-            .await?
+            let node = &node_opts.api_node;
+            let options = ServiceOptions::default();
+            start::start_authenticator_service(ctx, opts, node, &cfg, options, Some(tcp)).await?
         }
     }
 
     Ok(())
 }
 
+/// Store the setup given to the command, or declared in its node
+/// configuration file, for the node to apply when it starts.
+fn store_node_setup(cfg: &OckamConfig, cmd: &CreateCommand) -> Result<()> {
+    let setup = match (&cmd.setup, &cmd.setup_file) {
+        (Some(setup), _) => setup.clone(),
+        (None, Some(path)) => read_node_setup(path)?,
+        (None, None) => return Ok(()),
+    };
+    let node_config = NodeConfig::new(&cfg.get_node_dir(&cmd.node_name)?)?;
+    node_config.setup().set(setup)
+}
+
+/// How the node serves its API, as chosen on the command line.
+fn node_api(cmd: &CreateCommand) -> NodeApiConfig {
+    NodeApiConfig {
+        socket: cmd.api_socket,
+        no_tcp: cmd.no_tcp_api,
+    }
+}
+
+/// Store how the node serves its API, so that it is restarted the same way.
+fn store_node_state(cfg: &OckamConfig, cmd: &CreateCommand) -> Result<()> {
+    let node_config = NodeConfig::new(&cfg.get_node_dir(&cmd.node_name)?)?;
+    {
+        let mut state = node_config.state().write();
+        state.api = node_api(cmd);
+        state.max_restarts = Some(cmd.max_restarts);
+    }
+    node_config.state().persist_config_updates()
+}
+
+fn read_node_setup(path: &Path) -> Result<NodeSetup> {
+    let s = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    secret::from_yaml(&s).context("Failed to parse the node configuration")
+}
+
+/// Fail with a clear error if the requested API port is already taken.
+///
+/// Port 0 is always available, the node binds a port picked by the system.
+fn check_port_available(cfg: &OckamConfig, addr: &SocketAddr) -> crate::Result<()> {
+    let port = addr.port();
+    if port == 0 {
+        return Ok(());
+    }
+    let reason = match cfg.get_node_by_port(port) {
+        Some(node) => format!("Port {port} is already used by node '{node}'"),
+        None if !bind_to_port_check(addr) => {
+            format!("Port {port} is already in use by another process")
+        }
+        None => return Ok(()),
+    };
+    Err(crate::Error::new(
+        exitcode::IOERR,
+        anyhow!("{reason}. Use port 0, the default, to let the node pick a free port"),
+    ))
+}
+
+/// Wait for a background node started with port 0 to record the port it
+/// bound, and return it.
+pub(super) fn wait_for_node_port(
+    cfg: &OckamConfig,
+    name: &str,
+    mut addr: SocketAddr,
+) -> crate::Result<u16> {
+    let deadline = Instant::now() + PORT_TIMEOUT;
+    loop {
+        let port = OckamConfig::load()
+            .and_then(|latest| latest.get_node_port(name))
+            .unwrap_or(0);
+        if port != 0 {
+            addr.set_port(port);
+            cfg.set_node_addr(name, addr)?;
+            return Ok(port);
+        }
+        if Instant::now() > deadline {
+            let logs = match cfg.node_log_paths(name) {
+                Some((_, stderr)) => format!(", see {}", stderr.display()),
+                None => String::new(),
+            };
+            return Err(crate::Error::new(
+                exitcode::UNAVAILABLE,
+                anyhow!("Node '{name}' did not start listening{logs}"),
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
 async fn spawn_background_node(
     ctx: Context,
     (opts, cmd, addr): (CommandGlobalOpts, CreateCommand, SocketAddr),
@@ -324,19 +503,15 @@ async fn spawn_background_node(
     let verbose = opts.global_args.verbose;
     let cfg = &opts.config;
 
-    // Check if the port is used by some other services or process
-    if !bind_to_port_check(&addr) || cfg.port_is_used(addr.port()) {
-        return Err(crate::Error::new(
-            exitcode::IOERR,
-            anyhow!("Another process is listening on the provided port!"),
-        ));
-    }
+    check_port_available(cfg, &addr)?;
 
     // First we create a new node in the configuration so that
     // we can ask it for the correct log path, as well as
     // making sure the watchdog can do its job later on.
     cfg.create_node(&cmd.node_name, addr, verbose)?;
     cfg.persist_config_updates()?;
+    store_node_setup(cfg, &cmd)?;
+    store_node_state(cfg, &cmd)?;
 
     create_default_identity_if_needed(&ctx, cfg).await?;
 
@@ -348,9 +523,11 @@ async fn spawn_background_node(
         cmd.skip_defaults,
         cmd.no_shared_identity,
         cmd.enable_credential_checks,
+        node_api(&cmd),
         &cmd.node_name,
         &cmd.tcp_listener_address,
         cmd.project.as_deref(),
+        cmd.max_restarts,
     )?;
 
     Ok(())
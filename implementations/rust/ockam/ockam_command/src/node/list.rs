@@ -1,5 +1,6 @@
 use crate::util::{connect_to, exitcode, verify_pids};
 use crate::{help, node::show::print_query_status, node::HELP_DETAIL, CommandGlobalOpts};
+use anyhow::anyhow;
 use clap::Args;
 
 /// List Nodes
@@ -8,14 +9,16 @@ use clap::Args;
 pub struct ListCommand {}
 
 impl ListCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         let cfg = &options.config;
         let node_names = {
             let inner = cfg.inner();
 
             if inner.nodes.is_empty() {
-                println!("No nodes registered on this system!");
-                std::process::exit(exitcode::IOERR);
+                return Err(crate::Error::new(
+                    exitcode::NOT_FOUND,
+                    anyhow!("No nodes registered on this system!"),
+                ));
             }
 
             // Before printing node state we have to verify it.  This
@@ -27,14 +30,17 @@ impl ListCommand {
             // and has been restarted by something that is not this CLI.
             inner.nodes.iter().map(|(name, _)| name.clone()).collect()
         };
-        verify_pids(cfg, node_names);
+        verify_pids(cfg, node_names)?;
 
-        cfg.inner().nodes.iter().for_each(|(node_name, node_cfg)| {
-            connect_to(
-                node_cfg.port(),
-                (cfg.clone(), node_name.clone(), false),
-                print_query_status,
-            )
-        });
+        let nodes: Vec<_> = cfg
+            .inner()
+            .nodes
+            .iter()
+            .map(|(name, node_cfg)| (name.clone(), node_cfg.port()))
+            .collect();
+        for (node_name, port) in nodes {
+            connect_to(port, (cfg.clone(), node_name, false), print_query_status)?;
+        }
+        Ok(())
     }
 }
@@ -1,22 +1,33 @@
 use clap::{Args, Subcommand};
 
+use apply::ApplyCommand;
+use config::ConfigCommand;
 pub(crate) use create::CreateCommand;
 use delete::DeleteCommand;
 use list::ListCommand;
+use ping::PingCommand;
 use run::RunCommand;
+use set_log_level::SetLogLevelCommand;
+pub(crate) use show::format_uptime;
 use show::ShowCommand;
 use start::StartCommand;
 use stop::StopCommand;
+use supervise::SuperviseCommand;
 
 use crate::{help, CommandGlobalOpts};
 
+mod apply;
+mod config;
 mod create;
 mod delete;
 mod list;
+mod ping;
 mod run;
+mod set_log_level;
 mod show;
 mod start;
 mod stop;
+mod supervise;
 pub mod util;
 
 const HELP_DETAIL: &str = "\
@@ -105,6 +116,20 @@ Examples:
     # List all created nodes
     $ ockam node list
 
+    # Check that a node is responding
+    $ ockam node ping n1
+
+    # Trace session handling on node n1, without restarting it
+    $ ockam node set-log-level n1 info,ockam_api::session=trace
+
+    # Create a node which sets up what its configuration file declares,
+    # and show the setup it applied
+    $ ockam node create n1 --setup node.yaml
+    $ ockam node config n1
+
+    # Converge the node to an updated configuration file
+    $ ockam node apply n1 --config node.yaml
+
     # Delete the node
     $ ockam node delete n1
 
@@ -139,23 +164,38 @@ pub enum NodeSubcommand {
     #[command(display_order = 800)]
     Show(ShowCommand),
     #[command(display_order = 800)]
+    Ping(PingCommand),
+    #[command(display_order = 800)]
+    Config(ConfigCommand),
+    #[command(display_order = 800)]
+    Apply(ApplyCommand),
+    #[command(display_order = 800)]
+    SetLogLevel(SetLogLevelCommand),
+    #[command(display_order = 800)]
     Run(RunCommand),
     #[command(display_order = 800)]
     Start(StartCommand),
     #[command(display_order = 800)]
     Stop(StopCommand),
+    #[command(display_order = 800)]
+    Supervise(SuperviseCommand),
 }
 
 impl NodeCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             NodeSubcommand::Create(c) => c.run(options),
             NodeSubcommand::Delete(c) => c.run(options),
             NodeSubcommand::List(c) => c.run(options),
+            NodeSubcommand::Ping(c) => c.run(options),
+            NodeSubcommand::Config(c) => c.run(options),
+            NodeSubcommand::Apply(c) => c.run(options),
+            NodeSubcommand::SetLogLevel(c) => c.run(options),
             NodeSubcommand::Run(c) => c.run(options),
             NodeSubcommand::Show(c) => c.run(options),
             NodeSubcommand::Start(c) => c.run(options),
             NodeSubcommand::Stop(c) => c.run(options),
+            NodeSubcommand::Supervise(c) => c.run(options),
         }
     }
 }
@@ -0,0 +1,59 @@
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use ockam::{Context, TcpTransport};
+use ockam_api::nodes::models::base::NodeHealth;
+
+use crate::util::{api, node_rpc, RpcBuilder};
+use crate::{help, node::HELP_DETAIL, CommandGlobalOpts};
+
+/// Check that a node is up and measure its response time
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(HELP_DETAIL))]
+pub struct PingCommand {
+    /// Name of the node.
+    #[arg(default_value = "default")]
+    node_name: String,
+
+    /// Number of health checks to send.
+    #[arg(long, short, default_value_t = 1)]
+    count: u32,
+
+    /// Seconds to wait for each reply.
+    #[arg(long, default_value_t = 5)]
+    timeout: u64,
+}
+
+impl PingCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, PingCommand)) -> crate::Result<()> {
+    let tcp = TcpTransport::create(&ctx).await?;
+    let mut rpc = RpcBuilder::new(&ctx, &opts, &cmd.node_name)
+        .tcp(&tcp)?
+        .build();
+    let timeout = Duration::from_secs(cmd.timeout);
+
+    for i in 0..cmd.count {
+        if i > 0 {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        let start = Instant::now();
+        rpc.request_with_timeout(api::node_health(), timeout)
+            .await?;
+        let health = rpc.parse_response::<NodeHealth>()?;
+        let latency = start.elapsed();
+        println!(
+            "Reply from node {}: heartbeat={} uptime={}s workers={} time={:.2}ms",
+            cmd.node_name,
+            health.heartbeat,
+            health.uptime,
+            health.workers,
+            latency.as_secs_f64() * 1000.0
+        );
+    }
+    Ok(())
+}
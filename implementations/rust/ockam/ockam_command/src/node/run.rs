@@ -1,7 +1,6 @@
 use anyhow::Context;
 use clap::Args;
 use std::path::PathBuf;
-use tracing::error;
 
 use crate::{help, CommandGlobalOpts};
 
@@ -15,12 +14,8 @@ pub struct RunCommand {
 }
 
 impl RunCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        if let Err(e) = self.run_impl(options) {
-            error!(%e);
-            eprintln!("{e:?}");
-            std::process::exit(e.code());
-        }
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        self.run_impl(options)
     }
 
     fn run_impl(self, _opts: CommandGlobalOpts) -> crate::Result<()> {
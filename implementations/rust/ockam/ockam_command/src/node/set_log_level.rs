@@ -0,0 +1,52 @@
+use clap::Args;
+use ockam::{Context, TcpTransport};
+use ockam_api::nodes::models::logging::LogFilterBody;
+
+use crate::util::{api, node_rpc, RpcBuilder};
+use crate::{help, node::HELP_DETAIL, CommandGlobalOpts};
+
+/// Set the log filter of a running node, or show it if no filter is given
+///
+/// The filter uses the syntax of `OCKAM_LOG`, e.g. `info,ockam_api::session=trace`.
+/// Changes last until the node stops.
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(HELP_DETAIL))]
+pub struct SetLogLevelCommand {
+    /// Name of the node.
+    #[arg(default_value = "default")]
+    node_name: String,
+
+    /// New log filter. The current one is shown if omitted.
+    filter: Option<String>,
+}
+
+impl SetLogLevelCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, SetLogLevelCommand),
+) -> crate::Result<()> {
+    let tcp = TcpTransport::create(&ctx).await?;
+    let mut rpc = RpcBuilder::new(&ctx, &opts, &cmd.node_name)
+        .tcp(&tcp)?
+        .build();
+    match &cmd.filter {
+        Some(filter) => {
+            rpc.request(api::set_log_filter(filter)).await?;
+            rpc.is_ok()?;
+            if !opts.global_args.quiet {
+                println!("Log filter of node {} set to {filter}", cmd.node_name);
+            }
+        }
+        None => {
+            rpc.request(api::get_log_filter()).await?;
+            let body = rpc.parse_response::<LogFilterBody>()?;
+            println!("{}", body.filter());
+        }
+    }
+    Ok(())
+}
@@ -1,16 +1,14 @@
+use crate::node::supervise::restart_events;
 use crate::util::{api, connect_to, exitcode, OckamConfig};
 use crate::{help, node::HELP_DETAIL, CommandGlobalOpts};
-use anyhow::Context;
+use anyhow::{anyhow, Context};
 use clap::Args;
 use colorful::Colorful;
-use minicbor::Decoder;
 use ockam_api::config::cli::NodeConfigOld;
-use ockam_api::nodes::models::portal::{InletList, OutletList};
-use ockam_api::nodes::models::services::ServiceList;
-use ockam_api::nodes::models::transport::TransportList;
+use ockam_api::nodes::models::base::NodeDetails;
+use ockam_api::nodes::models::portal::TrafficStatus;
 use ockam_api::nodes::NODEMANAGER_ADDR;
 use ockam_api::{addr_to_multiaddr, route_to_multiaddr};
-use ockam_core::api::{Response, Status};
 use ockam_core::{Result, Route};
 use ockam_multiaddr::proto::{DnsAddr, Node, Tcp};
 use ockam_multiaddr::MultiAddr;
@@ -30,36 +28,32 @@ pub struct ShowCommand {
 }
 
 impl ShowCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         let cfg = &options.config;
         let port = match cfg.inner().nodes.get(&self.node_name) {
             Some(cfg) => cfg.port(),
             None => {
-                eprintln!("No such node available.  Run `ockam node list` to list available nodes");
-                std::process::exit(exitcode::IOERR);
+                return Err(crate::Error::new(
+                    exitcode::NOT_FOUND,
+                    anyhow!(
+                        "No such node available.  Run `ockam node list` to list available nodes"
+                    ),
+                ));
             }
         };
         connect_to(
             port,
             (cfg.clone(), self.node_name, false),
             print_query_status,
-        );
+        )
     }
 }
 
-// TODO: This function should be replaced with a better system of
-// printing the node state in the future but for now we can just tell
-// clippy to stop complainaing about it.
-#[allow(clippy::too_many_arguments)]
 fn print_node_info(
     node_cfg: &NodeConfigOld,
     node_name: &str,
     status: &str,
-    default_id: &str,
-    services: Option<&ServiceList>,
-    tcp_listeners: Option<&TransportList>,
-    secure_channel_listeners: Option<&Vec<String>>,
-    inlets_outlets: Option<(&InletList, &OutletList)>,
+    details: Option<&NodeDetails>,
 ) {
     println!();
     println!("Node:");
@@ -73,6 +67,21 @@ fn print_node_info(
         }
     );
 
+    if let Some(dir) = node_cfg.state_dir() {
+        let restarts = restart_events(dir);
+        if let Some(last) = restarts.last() {
+            println!(
+                "  Restarts: {}",
+                restarts.iter().filter(|e| e.attempt > 0).count()
+            );
+            if last.attempt == 0 {
+                println!("  Last Crash: {} (gave up restarting)", last.reason);
+            } else {
+                println!("  Last Crash: {}", last.reason);
+            }
+        }
+    }
+
     println!("  Route To Node:");
     let mut m = MultiAddr::default();
     if m.push_back(Node::new(node_name)).is_ok() {
@@ -85,60 +94,121 @@ fn print_node_info(
     {
         println!("    Verbose: {}", m);
     }
-    println!("  Identity: {}", default_id);
 
-    if let Some(list) = tcp_listeners {
-        println!("  Transports:");
-        for e in &list.list {
-            println!("    Transport:");
-            println!("      Type: {}", e.tt);
-            println!("      Mode: {}", e.tm);
-            println!("      Address: {}", e.payload);
+    let details = match details {
+        Some(d) => d,
+        None => {
+            println!("  Identity: N/A");
+            return;
         }
+    };
+
+    match &details.identity {
+        Some(id) => println!("  Identity: {}", id),
+        None => println!("  Identity: NOT FOUND"),
     }
+    println!("  Pid: {}", details.status.pid);
+    println!("  Uptime: {}", format_uptime(details.uptime));
+    println!("  Workers: {}", details.status.workers);
 
-    if let Some(list) = secure_channel_listeners {
-        println!("  Secure Channel Listeners:");
-        for e in list {
-            println!("    Listener:");
-            if let Some(ma) = addr_to_multiaddr(e) {
-                println!("      Address: {}", ma);
-            }
+    println!("  Transports:");
+    for e in &details.transports {
+        println!("    Transport:");
+        println!("      Type: {}", e.tt);
+        println!("      Mode: {}", e.tm);
+        println!("      Address: {}", e.payload);
+    }
+
+    println!("  Secure Channel Listeners:");
+    for e in &details.secure_channel_listeners {
+        println!("    Listener:");
+        if let Some(ma) = addr_to_multiaddr(e) {
+            println!("      Address: {}", ma);
         }
     }
 
-    if let Some((inlets, outlets)) = inlets_outlets {
-        println!("  Inlets:");
-        for e in &inlets.list {
-            println!("    Inlet:");
-            println!("      Listen Address: {}", e.bind_addr);
-            if let Some(r) = Route::parse(e.outlet_route.as_ref()) {
-                if let Some(ma) = route_to_multiaddr(&r) {
-                    println!("      Route To Outlet: {}", ma);
-                }
-            }
+    println!("  Secure Channels:");
+    for e in &details.secure_channels {
+        println!("    Channel:");
+        if let Some(ma) = addr_to_multiaddr(e) {
+            println!("      Address: {}", ma);
         }
-        println!("  Outlets:");
-        for e in &outlets.list {
-            println!("    Outlet:");
-            println!("      Forward Address: {}", e.tcp_addr);
+    }
 
-            if let Some(ma) = addr_to_multiaddr(e.worker_addr.as_ref()) {
-                println!("      Address: {}", ma);
+    println!("  Inlets:");
+    for e in &details.inlets {
+        println!("    Inlet:");
+        println!("      Listen Address: {}", e.bind_addr);
+        if let Some(r) = Route::parse(e.outlet_route.as_ref()) {
+            if let Some(ma) = route_to_multiaddr(&r) {
+                println!("      Route To Outlet: {}", ma);
             }
         }
+        if let Some(t) = &e.traffic {
+            print_traffic(t);
+        }
     }
+    println!("  Outlets:");
+    for e in &details.outlets {
+        println!("    Outlet:");
+        println!("      Forward Address: {}", e.tcp_addr);
 
-    if let Some(list) = services {
-        println!("  Services:");
-        for e in &list.list {
-            println!("    Service:");
-            println!("      Type: {}", e.service_type);
-            if let Some(ma) = addr_to_multiaddr(e.addr.as_ref()) {
-                println!("      Address: {}", ma);
-            }
+        if let Some(ma) = addr_to_multiaddr(e.worker_addr.as_ref()) {
+            println!("      Address: {}", ma);
+        }
+        if let Some(t) = &e.traffic {
+            print_traffic(t);
         }
     }
+
+    println!("  Services:");
+    for e in &details.services {
+        println!("    Service:");
+        println!("      Type: {}", e.service_type);
+        if let Some(ma) = addr_to_multiaddr(e.addr.as_ref()) {
+            println!("      Address: {}", ma);
+        }
+        if e.restarts > 0 {
+            println!("      Restarts: {}", e.restarts);
+        }
+    }
+
+    println!("  Sessions:");
+    for e in &details.sessions {
+        println!("    Session:");
+        println!("      Address: {}", e.addr);
+        println!(
+            "      Status: {}",
+            match e.status.as_ref() {
+                "up" => e.status.as_ref().light_green(),
+                _ => e.status.as_ref().light_red(),
+            }
+        );
+        println!("      Pending Pings: {}", e.pending_pings);
+    }
+}
+
+fn print_traffic(t: &TrafficStatus) {
+    println!("      Bytes In: {}", t.bytes_in);
+    println!("      Bytes Out: {}", t.bytes_out);
+    if let (Some(rate), Some(burst)) = (t.bytes_per_sec, t.burst) {
+        println!("      Rate Limit: {rate} B/s (burst {burst} B)");
+        println!("      Throttled: {}", t.throttled);
+    }
+    if let Some(ratio) = t.compression_ratio() {
+        println!("      Compression Ratio: {ratio:.2}");
+    }
+}
+
+pub(crate) fn format_uptime(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if h > 0 {
+        format!("{h}h {m}m {s}s")
+    } else if m > 0 {
+        format!("{m}m {s}s")
+    } else {
+        format!("{s}s")
+    }
 }
 
 pub async fn print_query_status(
@@ -150,92 +220,19 @@ pub async fn print_query_status(
     let node_cfg = cfg.get_node(&node_name)?;
 
     if !is_node_up(&mut ctx, &route, wait_until_ready).await? {
-        print_node_info(&node_cfg, &node_name, "DOWN", "N/A", None, None, None, None);
+        print_node_info(&node_cfg, &node_name, "DOWN", None);
     } else {
-        // Get short id for the node
         let resp: Vec<u8> = ctx
             .send_and_receive_with_timeout(
                 route.clone(),
-                api::short_identity().to_vec()?,
+                api::node_details().to_vec()?,
                 SEND_RECEIVE_TIMEOUT_SECS,
             )
             .await
-            .context("Failed to get short identity from node")?;
-        let (response, result) = api::parse_short_identity_response(&resp)?;
-        let default_id = match response.status() {
-            Some(Status::Ok) => {
-                format!("{}", result.identity_id)
-            }
-            _ => String::from("NOT FOUND"),
-        };
+            .context("Failed to get details from node")?;
+        let details = api::parse_node_details(&resp)?;
 
-        // Get list of services for the node
-        let resp: Vec<u8> = ctx
-            .send_and_receive_with_timeout(
-                route.clone(),
-                api::list_services().to_vec()?,
-                SEND_RECEIVE_TIMEOUT_SECS,
-            )
-            .await
-            .context("Failed to get list of services from node")?;
-        let services = api::parse_list_services_response(&resp)?;
-
-        // Get list of TCP listeners for node
-        let resp: Vec<u8> = ctx
-            .send_and_receive_with_timeout(
-                route.clone(),
-                api::list_tcp_listeners().to_vec()?,
-                SEND_RECEIVE_TIMEOUT_SECS,
-            )
-            .await
-            .context("Failed to get list of tcp listeners from node")?;
-        let tcp_listeners = api::parse_tcp_list(&resp)?;
-
-        // Get list of Secure Channel Listeners
-        let resp: Vec<u8> = ctx
-            .send_and_receive_with_timeout(
-                route.clone(),
-                api::list_secure_channel_listener().to_vec()?,
-                SEND_RECEIVE_TIMEOUT_SECS,
-            )
-            .await
-            .context("Failed to get list of secure channel listeners from node")?;
-        let mut dec = Decoder::new(&resp);
-        let _ = dec.decode::<Response>()?;
-        let secure_channel_listeners = dec.decode::<Vec<String>>()?;
-
-        // Get list of inlets
-        let resp: Vec<u8> = ctx
-            .send_and_receive_with_timeout(
-                route.clone(),
-                api::list_inlets().to_vec()?,
-                SEND_RECEIVE_TIMEOUT_SECS,
-            )
-            .await
-            .context("Failed to get list of inlets from node")?;
-        let inlets = api::parse_list_inlets_response(&resp)?;
-
-        // Get list of outlets
-        let resp: Vec<u8> = ctx
-            .send_and_receive_with_timeout(
-                route.clone(),
-                api::list_outlets().to_vec()?,
-                SEND_RECEIVE_TIMEOUT_SECS,
-            )
-            .await
-            .context("Failed to get list of outlets from node")?;
-        let outlets = api::parse_list_outlets_response(&resp)?;
-
-        print_node_info(
-            &node_cfg,
-            &node_name,
-            "UP",
-            &default_id,
-            Some(&services),
-            Some(&tcp_listeners),
-            Some(&secure_channel_listeners),
-            Some((&inlets, &outlets)),
-        );
+        print_node_info(&node_cfg, &node_name, "UP", Some(&details));
     }
 
     Ok(())
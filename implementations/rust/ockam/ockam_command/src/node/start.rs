@@ -1,17 +1,20 @@
 use anyhow::{anyhow, Context as _};
 use clap::Args;
-use nix::unistd::Pid;
 use rand::prelude::random;
 
 use ockam::Context;
 
+use crate::node::create::wait_for_node_port;
 use crate::node::show::print_query_status;
 use crate::node::util::run::CommandsRunner;
 use crate::util::{connect_to, embedded_node};
 use crate::{
     help,
     node::HELP_DETAIL,
-    util::{exitcode, startup::spawn_node},
+    util::{
+        exitcode,
+        startup::{self, spawn_node, DEFAULT_MAX_RESTARTS},
+    },
     CommandGlobalOpts,
 };
 
@@ -25,11 +28,8 @@ pub struct StartCommand {
 }
 
 impl StartCommand {
-    pub fn run(self, opts: CommandGlobalOpts) {
-        if let Err(e) = run_impl(opts, self) {
-            eprintln!("{}", e);
-            std::process::exit(e.code());
-        }
+    pub fn run(self, opts: CommandGlobalOpts) -> crate::Result<()> {
+        run_impl(opts, self)
     }
 }
 
@@ -39,10 +39,7 @@ fn run_impl(opts: CommandGlobalOpts, cmd: StartCommand) -> crate::Result<()> {
 
     // First we check whether a PID was registered and if it is still alive.
     if let Some(pid) = cfg_node.pid() {
-        // Note: On CI machines where <defunct> processes can occur,
-        // the below `kill 0 pid` can imply a killed process is okay.
-        let res = nix::sys::signal::kill(Pid::from_raw(pid), None);
-        if res.is_ok() {
+        if startup::is_running(pid) {
             return Err(crate::Error::new(
                 exitcode::IOERR,
                 anyhow!(
@@ -55,11 +52,19 @@ fn run_impl(opts: CommandGlobalOpts, cmd: StartCommand) -> crate::Result<()> {
     }
 
     embedded_node(restart_background_node, (opts.clone(), cmd.clone()))?;
+    // A node which never got to bind its port picks one again
+    let port = match cfg_node.port() {
+        0 => {
+            let addr = cfg_node.addr().to_string().parse()?;
+            wait_for_node_port(cfg, &cmd.node_name, addr)?
+        }
+        port => port,
+    };
     connect_to(
-        cfg_node.port(),
+        port,
         (cfg.clone(), cmd.node_name.clone(), true),
         print_query_status,
-    );
+    )?;
     if let Ok(cfg) = cfg.node(&cmd.node_name) {
         CommandsRunner::run_node_startup(cfg.commands().config_path())
             .context("Failed to startup commands")?;
@@ -74,6 +79,7 @@ async fn restart_background_node(
 ) -> crate::Result<()> {
     let cfg = &opts.config;
     let cfg_node = cfg.get_node(&cmd.node_name)?;
+    let state = cfg.node(&cmd.node_name)?.state().read().clone();
 
     // Construct the arguments list and re-execute the ockam
     // CLI in foreground mode to start the newly created node
@@ -83,9 +89,11 @@ async fn restart_background_node(
         true,                         // skip-defaults because the node already exists
         false,                        // Default value. TODO: implement persistence of this option
         false,                        // Default value. TODO: implement persistence of this option
+        state.api,                    // How the node serves its API
         cfg_node.name(),              // The selected node name
         &cfg_node.addr().to_string(), // The selected node api address
         None,                         // No project information available
+        state.max_restarts.unwrap_or(DEFAULT_MAX_RESTARTS), // Previously user-chosen restarts
     )?;
 
     Ok(())
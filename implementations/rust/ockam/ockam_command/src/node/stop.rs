@@ -4,6 +4,7 @@ use crate::{
     util::{exitcode, startup},
     CommandGlobalOpts,
 };
+use anyhow::anyhow;
 use clap::Args;
 use rand::prelude::random;
 
@@ -20,36 +21,37 @@ pub struct StopCommand {
 }
 
 impl StopCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         let cfg = options.config;
         match cfg.get_node_pid(&self.node_name) {
             Ok(Some(pid)) => {
-                if let Err(e) = startup::stop(pid, self.force) {
-                    eprintln!("{e:?}");
-                    std::process::exit(exitcode::OSERR);
-                } else {
-                    // Clear pid in config, so StartCommand does not have to rely on
-                    // `kill 0 pid` to detect if a node is running.
-                    if let Err(e) = cfg.set_node_pid(&self.node_name, None) {
-                        eprintln!("Failed to update pid for node {}: {}", &self.node_name, e);
-                        std::process::exit(exitcode::IOERR);
-                    }
+                startup::stop_node(&cfg, &self.node_name, pid, self.force)
+                    .map_err(|e| crate::Error::new(exitcode::OSERR, e))?;
+                // Clear pid in config, so StartCommand does not have to rely on
+                // `kill 0 pid` to detect if a node is running.
+                cfg.set_node_pid(&self.node_name, None).map_err(|e| {
+                    crate::Error::new(
+                        exitcode::IOERR,
+                        anyhow!("Failed to update pid for node {}: {e}", &self.node_name),
+                    )
+                })?;
 
-                    // Save the config update
-                    if let Err(e) = cfg.persist_config_updates() {
-                        eprintln!("Failed to update configuration: {}", e);
-                        std::process::exit(exitcode::IOERR);
-                    }
-                }
+                // Save the config update
+                cfg.persist_config_updates().map_err(|e| {
+                    crate::Error::new(
+                        exitcode::IOERR,
+                        anyhow!("Failed to update configuration: {e}"),
+                    )
+                })
             }
-            Ok(_) => {
-                eprintln!("Node {} is not running!", &self.node_name);
-                std::process::exit(exitcode::IOERR);
-            }
-            Err(_) => {
-                eprintln!("Node {} does not exist!", &self.node_name);
-                std::process::exit(exitcode::IOERR);
-            }
-        };
+            Ok(_) => Err(crate::Error::new(
+                exitcode::CONNECTION_ERROR,
+                anyhow!("Node {} is not running!", &self.node_name),
+            )),
+            Err(_) => Err(crate::Error::new(
+                exitcode::NOT_FOUND,
+                anyhow!("Node {} does not exist!", &self.node_name),
+            )),
+        }
     }
 }
@@ -0,0 +1,158 @@
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::util::startup::{DEFAULT_MAX_RESTARTS, NODE_PID_FILE, NODE_STOPPED_FILE};
+use crate::util::{exitcode, OckamConfig};
+use crate::CommandGlobalOpts;
+
+/// File, in the node directory, where restart events are recorded
+const RESTARTS_FILE: &str = "restarts.json";
+
+/// A node that ran for at least this long is considered healthy again
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Run a background node and restart it when it crashes
+///
+/// This command is started by `ockam node create` and is not meant to be used directly.
+#[derive(Clone, Debug, Args)]
+#[command(hide = true)]
+pub struct SuperviseCommand {
+    /// Name of the supervised node.
+    node_name: String,
+
+    /// Number of consecutive restarts before giving up.
+    #[arg(long, default_value_t = DEFAULT_MAX_RESTARTS)]
+    max_restarts: u32,
+
+    /// Arguments used to run the node process.
+    #[arg(last = true, required = true)]
+    args: Vec<String>,
+}
+
+impl SuperviseCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        run_impl(options, self).map_err(|e| crate::Error::new(exitcode::OSERR, e))
+    }
+}
+
+/// A restart of a supervised node, as recorded in its state directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartEvent {
+    /// Seconds since the unix epoch.
+    pub time: u64,
+    /// Consecutive restart attempt, `0` if the supervisor gave up.
+    pub attempt: u32,
+    /// How the node process exited.
+    pub reason: String,
+}
+
+/// Read the restart events recorded for a node
+pub(crate) fn restart_events(node_dir: &Path) -> Vec<RestartEvent> {
+    std::fs::read_to_string(node_dir.join(RESTARTS_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn record_restart_event(node_dir: &Path, event: RestartEvent) -> anyhow::Result<()> {
+    let mut events = restart_events(node_dir);
+    events.push(event);
+    let json = serde_json::to_string_pretty(&events)?;
+    std::fs::write(node_dir.join(RESTARTS_FILE), json)
+        .context("failed to write node restart events")?;
+    Ok(())
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: SuperviseCommand) -> anyhow::Result<()> {
+    let node_dir = opts.config.get_node_dir(&cmd.node_name)?;
+    let ockam_exe = std::env::current_exe().unwrap_or_else(|_| "ockam".into());
+    let pid_file = node_dir.join(NODE_PID_FILE);
+    let stopped_file = node_dir.join(NODE_STOPPED_FILE);
+    let _ = std::fs::remove_file(&stopped_file);
+
+    let mut attempts = 0;
+    loop {
+        let started = Instant::now();
+        let mut child = Command::new(&ockam_exe)
+            .args(&cmd.args)
+            .spawn()
+            .context("failed to start node process")?;
+        std::fs::write(&pid_file, child.id().to_string())?;
+        let status = child.wait()?;
+        let _ = std::fs::remove_file(&pid_file);
+
+        // A node stopped by `ockam node stop`, even with `--force`, is not
+        // a crash, whatever signal ended it.
+        if !is_crash(&status) || stopped_file.exists() {
+            info!(node = %cmd.node_name, %status, "node stopped");
+            return Ok(());
+        }
+
+        // The node may have been deleted while its process was exiting.
+        let exists = OckamConfig::load()
+            .map(|cfg| cfg.get_node(&cmd.node_name).is_ok())
+            .unwrap_or(false);
+        if !exists {
+            return Ok(());
+        }
+
+        if started.elapsed() >= STABLE_AFTER {
+            attempts = 0;
+        }
+        if attempts >= cmd.max_restarts {
+            warn!(node = %cmd.node_name, %status, "node crashed too many times, giving up");
+            record_restart_event(&node_dir, event(0, &status))?;
+            return Ok(());
+        }
+        attempts += 1;
+
+        let backoff = INITIAL_BACKOFF
+            .saturating_mul(2u32.saturating_pow(attempts - 1))
+            .min(MAX_BACKOFF);
+        warn!(node = %cmd.node_name, %status, attempt = attempts, ?backoff, "node crashed, restarting");
+        record_restart_event(&node_dir, event(attempts, &status))?;
+        std::thread::sleep(backoff);
+    }
+}
+
+/// Whether the node process exited on its own rather than being stopped
+#[cfg(unix)]
+fn is_crash(status: &ExitStatus) -> bool {
+    use nix::sys::signal::Signal;
+    use std::os::unix::process::ExitStatusExt;
+
+    match status.signal() {
+        Some(s) => s != Signal::SIGTERM as i32 && s != Signal::SIGINT as i32,
+        None => !status.success(),
+    }
+}
+
+/// Whether the node process exited on its own rather than being stopped
+///
+/// Nodes are stopped by terminating their supervisor first, so any exit
+/// observed here that is not a success is a crash.
+#[cfg(windows)]
+fn is_crash(status: &ExitStatus) -> bool {
+    !status.success()
+}
+
+fn event(attempt: u32, status: &ExitStatus) -> RestartEvent {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    RestartEvent {
+        time,
+        attempt,
+        reason: status.to_string(),
+    }
+}
@@ -19,6 +19,7 @@ use ockam_vault::Vault;
 
 use crate::node::CreateCommand;
 use crate::project::ProjectInfo;
+use crate::util::api::CloudOpts;
 use crate::{project, OckamConfig};
 use crate::{util::startup, CommandGlobalOpts};
 
@@ -56,15 +57,19 @@ pub async fn start_embedded_node(ctx: &Context, cfg: &OckamConfig) -> Result<Str
     tcp.listen(&bind).await?;
     let node_dir = cfg.get_node_dir_raw(&cmd.node_name)?;
     let projects = cfg.inner().lookup().projects().collect();
+    let mut general_options = NodeManagerGeneralOptions::new(
+        cmd.node_name.clone(),
+        node_dir,
+        cmd.skip_defaults || cmd.launch_config.is_some(),
+        cmd.enable_credential_checks,
+        identity_override,
+    );
+    if let Some(policy) = CloudOpts::retry_policy()? {
+        general_options = general_options.with_controller_retry_policy(policy);
+    }
     let node_man = NodeManager::create(
         ctx,
-        NodeManagerGeneralOptions::new(
-            cmd.node_name.clone(),
-            node_dir,
-            cmd.skip_defaults || cmd.launch_config.is_some(),
-            cmd.enable_credential_checks,
-            identity_override,
-        ),
+        general_options,
         NodeManagerProjectsOptions::new(
             Some(&cfg.authorities(&cmd.node_name)?.snapshot()),
             project_id,
@@ -138,7 +143,7 @@ pub(super) async fn get_identity_override(
     })
 }
 
-pub(super) async fn add_project_authority(
+pub(crate) async fn add_project_authority(
     p: ProjectInfo<'_>,
     node: &str,
     cfg: &OckamConfig,
@@ -170,13 +175,30 @@ pub async fn delete_embedded_node(cfg: &OckamConfig, name: &str) {
 }
 
 pub fn delete_all_nodes(opts: CommandGlobalOpts, force: bool) -> anyhow::Result<()> {
+    stop_all_nodes(&opts, force)?;
+
+    // If force is enabled
+    if force {
+        // delete the config and nodes directories
+        opts.config.remove()?;
+        // and all dangling/orphan ockam processes
+        kill_orphan_processes();
+    } else if let Err(e) = opts.config.persist_config_updates() {
+        eprintln!("Failed to update config file. You might need to run the command with --force to delete all config directories");
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Stop all nodes and delete their state directories, keeping the CLI config.
+pub fn stop_all_nodes(opts: &CommandGlobalOpts, sigkill: bool) -> anyhow::Result<()> {
     // Try to delete all nodes found in the config file + their associated processes
     let nn: Vec<String> = {
         let inner = &opts.config.inner();
         inner.nodes.iter().map(|(name, _)| name.clone()).collect()
     };
     for node_name in nn.iter() {
-        delete_node(&opts, node_name, force)
+        delete_node(opts, node_name, sigkill)
     }
 
     // Try to delete dangling embedded nodes directories
@@ -195,25 +217,19 @@ pub fn delete_all_nodes(opts: CommandGlobalOpts, force: bool) -> anyhow::Result<
             }
         }
     }
+    Ok(())
+}
 
-    // If force is enabled
-    if force {
-        // delete the config and nodes directories
-        opts.config.remove()?;
-        // and all dangling/orphan ockam processes
-        if let Ok(cpid) = get_current_pid() {
-            let s = System::new_all();
-            for (pid, process) in s.processes() {
-                if pid != &cpid && process.name() == "ockam" {
-                    process.kill();
-                }
+/// Kill every `ockam` process other than the current one.
+pub fn kill_orphan_processes() {
+    if let Ok(cpid) = get_current_pid() {
+        let s = System::new_all();
+        for (pid, process) in s.processes() {
+            if pid != &cpid && process.name().trim_end_matches(".exe") == "ockam" {
+                process.kill();
             }
         }
-    } else if let Err(e) = opts.config.persist_config_updates() {
-        eprintln!("Failed to update config file. You might need to run the command with --force to delete all config directories");
-        return Err(e);
     }
-    Ok(())
 }
 
 pub fn delete_node(opts: &CommandGlobalOpts, node_name: &str, sigkill: bool) {
@@ -230,7 +246,7 @@ fn delete_node_pid(opts: &CommandGlobalOpts, node_name: &str, sigkill: bool) ->
     trace!(%node_name, "Deleting node pid");
     // Stop the process PID if it has one assigned in the config file
     if let Some(pid) = opts.config.get_node_pid(node_name)? {
-        startup::stop(pid, sigkill)?;
+        startup::stop_node(&opts.config, node_name, pid, sigkill)?;
         // Give some room for the process to stop
         std::thread::sleep(std::time::Duration::from_millis(100));
         // If it fails to bind, the port is still in use, so we try again to stop the process
@@ -314,7 +330,7 @@ pub mod run {
             let path = path.as_ref();
             let commands = if path.exists() {
                 let s = std::fs::read_to_string(path)?;
-                serde_json::from_str(&s)?
+                serde_yaml::from_str(&s)?
             } else {
                 Commands::default()
             };
@@ -387,7 +403,7 @@ pub mod run {
                 Some(r) => {
                     let args = r.args(&cr.path, cr.exe.to_str().expect("Invalid executable path"));
                     let cmd: OckamCommand = OckamCommand::parse_from(args);
-                    cmd.run();
+                    cmd.run()?;
                     Ok(())
                 }
             }
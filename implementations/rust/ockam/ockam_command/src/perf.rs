@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use clap::Args;
+
+use ockam::{Context, TcpTransport};
+use ockam_api::clean_multiaddr;
+use ockam_api::nodes::models::perf::{PerfReport, PerfRequest};
+use ockam_api::nodes::models::secure_channel::CredentialExchangeMode;
+use ockam_core::api::Request;
+use ockam_multiaddr::MultiAddr;
+
+use crate::node::util::{delete_embedded_node, start_embedded_node};
+use crate::util::api::CloudOpts;
+use crate::util::{extract_address_value, node_rpc, RpcBuilder};
+use crate::Result;
+use crate::{help, CommandGlobalOpts};
+
+const HELP_DETAIL: &str = "\
+About:
+    A perf service echoes pings and counts the data sent to it, which
+    measures a route without any external tooling: first the round trip
+    time of pings, then the throughput of the data sent during the given
+    duration. Nodes don't run one by default, start one with
+    `ockam service start perf`. Measurements are limited to payloads of
+    1 MiB, 300 seconds and 10000 pings.
+
+Examples:
+
+```sh
+    # Measure the route to node n2
+    $ ockam service start perf --api-node n2
+    $ ockam perf --to /node/n2/service/perf
+
+    # Measure a secure channel from n1 to n2, with 16 KiB payloads for 30 seconds
+    $ ockam secure-channel create --from /node/n1 --to /node/n2/service/api \\
+        | ockam perf --from /node/n1 --to -/service/perf --size 16384 --duration 30
+```
+";
+
+/// Measure the throughput and latency of a route
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, after_long_help = help::template(HELP_DETAIL))]
+pub struct PerfCommand {
+    /// The node to measure from
+    #[arg(short, long, value_name = "NODE")]
+    from: Option<String>,
+
+    /// The route to a perf service
+    #[arg(short, long, value_name = "ROUTE")]
+    to: MultiAddr,
+
+    /// Size of the payloads, in bytes
+    #[arg(long, value_name = "BYTES", default_value_t = 1024)]
+    size: u32,
+
+    /// How long to send data for, in seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = 10)]
+    duration: u64,
+
+    /// Number of pings measuring the latency
+    #[arg(long, value_name = "COUNT", default_value_t = 100)]
+    pings: u32,
+
+    #[command(flatten)]
+    cloud_opts: CloudOpts,
+}
+
+impl PerfCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(mut ctx: Context, (opts, cmd): (CommandGlobalOpts, PerfCommand)) -> Result<()> {
+    async fn go(ctx: &mut Context, opts: &CommandGlobalOpts, cmd: PerfCommand) -> Result<()> {
+        let (to, meta) = clean_multiaddr(&cmd.to, &opts.config.lookup())
+            .context("Argument '--to' is invalid")?;
+
+        let (api_node, tcp) = if let Some(node) = &cmd.from {
+            let api_node = extract_address_value(node)?;
+            let tcp = TcpTransport::create(ctx).await?;
+            (api_node, Some(tcp))
+        } else {
+            let api_node = start_embedded_node(ctx, &opts.config).await?;
+            (api_node, None)
+        };
+
+        let projects_sc = crate::project::util::get_projects_secure_channels_from_config_lookup(
+            ctx,
+            opts,
+            &meta,
+            &cmd.cloud_opts.route(),
+            &api_node,
+            tcp.as_ref(),
+            CredentialExchangeMode::None,
+        )
+        .await?;
+        let to = crate::project::util::clean_projects_multiaddr(to, projects_sc)?;
+
+        // The node replies once the measurement is done
+        let timeout = Duration::from_secs(cmd.duration + 60);
+        let req = PerfRequest::new(to.to_string(), cmd.size, cmd.duration * 1000, cmd.pings);
+        let mut rpc = RpcBuilder::new(ctx, opts, &api_node)
+            .tcp(tcp.as_ref())?
+            .build();
+        rpc.request_with_timeout(Request::post("v0/perf").body(req), timeout)
+            .await?;
+        rpc.parse_and_print_response::<PerfReport>()?;
+
+        if cmd.from.is_none() {
+            delete_embedded_node(&opts.config, rpc.node_name()).await;
+        }
+
+        Ok(())
+    }
+    go(&mut ctx, &opts, cmd).await
+}
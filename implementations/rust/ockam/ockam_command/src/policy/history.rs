@@ -0,0 +1,44 @@
+use clap::Args;
+
+use ockam::Context;
+use ockam_api::nodes::models::policy::PolicyHistory;
+
+use crate::node::NodeOpts;
+use crate::policy::PolicyTargetOpts;
+use crate::util::{api, node_rpc, Rpc};
+use crate::{CommandGlobalOpts, OutputFormat};
+
+/// Show the previous versions of the policy of a resource, oldest first
+#[derive(Clone, Debug, Args)]
+pub struct HistoryCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+
+    #[command(flatten)]
+    pub target: PolicyTargetOpts,
+}
+
+impl HistoryCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, HistoryCommand)) -> crate::Result<()> {
+    let mut rpc = Rpc::background(&ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request(api::get_policy_history(cmd.target.to_target()))
+        .await?;
+    let history = rpc.parse_response::<PolicyHistory>()?;
+    let versions = history.versions().iter().map(|p| p.condition());
+    match opts.global_args.output_format {
+        OutputFormat::Plain => {
+            for (i, condition) in versions.enumerate() {
+                println!("{}: {}", i + 1, condition)
+            }
+        }
+        OutputFormat::Json => {
+            println!("[{}]", versions.collect::<Vec<_>>().join(","))
+        }
+    }
+    Ok(())
+}
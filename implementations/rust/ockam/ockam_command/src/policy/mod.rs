@@ -0,0 +1,115 @@
+pub(crate) mod history;
+pub(crate) mod rollback;
+pub(crate) mod set;
+pub(crate) mod show;
+
+pub(crate) use history::HistoryCommand;
+pub(crate) use rollback::RollbackCommand;
+pub(crate) use set::SetCommand;
+pub(crate) use show::ShowCommand;
+
+use crate::help;
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+use ockam_api::nodes::models::policy::PolicyTarget;
+
+const HELP_DETAIL: &str = "\
+About:
+    Policies are ABAC conditions, encoded as JSON, which the credential
+    attributes of an identity have to satisfy to perform an action on a
+    resource, like sending messages through a tcp inlet or outlet.
+
+    Setting a policy keeps the one it replaces as a previous version, so
+    that a mistaken change can be rolled back.
+
+```sh
+    # Only allow identities with the `component=db` attribute at an outlet
+    $ ockam policy set --resource /outlet/db --condition '{\"Eq\":[\"component\",{\"S\":\"db\"}]}'
+
+    # Show the previous versions of the policy, oldest first
+    $ ockam policy history --resource /outlet/db
+
+    # Restore the previous version
+    $ ockam policy rollback --resource /outlet/db
+```
+
+    A policy set for a path ending with `*` applies to every resource one
+    segment below it, and one ending with `**` to every resource below it.
+    The policy of the most specific path applies.
+
+```sh
+    # Allow identities with the `component=db` attribute at every outlet
+    $ ockam policy set --resource '/outlet/*' --condition '{\"Eq\":[\"component\",{\"S\":\"db\"}]}'
+```
+
+    The API of a node is guarded by the policies of its endpoints, under
+    the `/api` resource, and the request method as action. Once a policy is
+    set for an endpoint, requests to it have to arrive over a secure channel
+    from an identity satisfying it. Requests over the API socket of a node
+    created with `--api-socket` are always allowed.
+
+    Over a secure channel, policies can't be written until a policy is set
+    for `/api/node/policy`. This includes node setups and portals which set
+    policies. Commands on the same host can manage them by default.
+
+```sh
+    # Only allow identities with the `role=admin` attribute to start services
+    $ ockam policy set --resource /api/node/services --action post --condition '{\"Eq\":[\"role\",{\"S\":\"admin\"}]}'
+
+    # Only allow them to use any endpoint, with any request method
+    $ ockam policy set --resource /api --actions @admin --condition '{\"Eq\":[\"role\",{\"S\":\"admin\"}]}'
+```
+";
+
+/// Manage the ABAC policies of a node
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    after_long_help = help::template(HELP_DETAIL)
+)]
+pub struct PolicyCommand {
+    #[command(subcommand)]
+    subcommand: PolicySubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum PolicySubcommand {
+    Set(SetCommand),
+    Show(ShowCommand),
+    History(HistoryCommand),
+    Rollback(RollbackCommand),
+}
+
+impl PolicyCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        match self.subcommand {
+            PolicySubcommand::Set(c) => c.run(options),
+            PolicySubcommand::Show(c) => c.run(options),
+            PolicySubcommand::History(c) => c.run(options),
+            PolicySubcommand::Rollback(c) => c.run(options),
+        }
+    }
+}
+
+/// The resource and action a policy applies to
+#[derive(Clone, Debug, Args)]
+pub struct PolicyTargetOpts {
+    /// Resource of the policy, e.g. `/inlet/<alias>` or `/outlet/<alias>`
+    #[arg(long, value_name = "RESOURCE")]
+    resource: String,
+
+    /// Action of the policy, defaults to `handle_message`
+    #[arg(long, value_name = "ACTION")]
+    action: Option<String>,
+}
+
+impl PolicyTargetOpts {
+    pub fn to_target(&self) -> PolicyTarget<'static> {
+        let target = PolicyTarget::new(self.resource.clone());
+        match &self.action {
+            Some(a) => target.with_action(a.clone()),
+            None => target,
+        }
+    }
+}
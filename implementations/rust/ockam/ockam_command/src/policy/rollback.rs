@@ -0,0 +1,34 @@
+use clap::Args;
+
+use ockam::Context;
+use ockam_api::nodes::models::policy::Policy;
+
+use crate::node::NodeOpts;
+use crate::policy::PolicyTargetOpts;
+use crate::util::{api, node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+
+/// Restore the previous version of the policy of a resource
+#[derive(Clone, Debug, Args)]
+pub struct RollbackCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+
+    #[command(flatten)]
+    pub target: PolicyTargetOpts,
+}
+
+impl RollbackCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, RollbackCommand)) -> crate::Result<()> {
+    let mut rpc = Rpc::background(&ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request(api::rollback_policy(cmd.target.to_target()))
+        .await?;
+    let policy = rpc.parse_response::<Policy>()?;
+    println!("{}", policy.condition());
+    Ok(())
+}
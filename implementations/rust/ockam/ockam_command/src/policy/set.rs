@@ -0,0 +1,56 @@
+use anyhow::anyhow;
+use clap::Args;
+
+use ockam::abac::Conditional;
+use ockam::Context;
+
+use crate::node::NodeOpts;
+use crate::policy::PolicyTargetOpts;
+use crate::util::{api, node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+
+/// Set the policy of a resource, keeping the replaced one as a previous version
+#[derive(Clone, Debug, Args)]
+pub struct SetCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+
+    #[command(flatten)]
+    pub target: PolicyTargetOpts,
+
+    /// The policy, as a JSON encoded condition
+    #[arg(long, value_name = "JSON")]
+    pub condition: String,
+
+    /// Actions to set the policy for, instead of the one of `--action`
+    ///
+    /// Action groups expand to several actions: `@read-only` to `get`, and
+    /// `@admin` to all the request methods of the node API.
+    #[arg(
+        long,
+        value_name = "ACTIONS",
+        value_delimiter = ',',
+        conflicts_with = "action"
+    )]
+    pub actions: Vec<String>,
+}
+
+impl SetCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, SetCommand)) -> crate::Result<()> {
+    serde_json::from_str::<Conditional>(&cmd.condition)
+        .map_err(|e| anyhow!("invalid policy: {e}"))?;
+    let mut rpc = Rpc::background(&ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request(api::set_policy(
+        cmd.target.to_target(),
+        cmd.condition,
+        cmd.actions,
+    ))
+    .await?;
+    rpc.is_ok()?;
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use clap::Args;
+
+use ockam::Context;
+use ockam_api::nodes::models::policy::Policy;
+
+use crate::node::NodeOpts;
+use crate::policy::PolicyTargetOpts;
+use crate::util::{api, node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+
+/// Show the current policy of a resource
+#[derive(Clone, Debug, Args)]
+pub struct ShowCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+
+    #[command(flatten)]
+    pub target: PolicyTargetOpts,
+}
+
+impl ShowCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, ShowCommand)) -> crate::Result<()> {
+    let mut rpc = Rpc::background(&ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request(api::get_policy(cmd.target.to_target())).await?;
+    let policy = rpc.parse_response::<Policy>()?;
+    println!("{}", policy.condition());
+    Ok(())
+}
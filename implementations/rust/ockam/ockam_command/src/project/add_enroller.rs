@@ -30,8 +30,8 @@ pub struct AddEnrollerCommand {
 }
 
 impl AddEnrollerCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        node_rpc(rpc, (options, self));
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
     }
 }
 
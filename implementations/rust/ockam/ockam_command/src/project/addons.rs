@@ -0,0 +1,139 @@
+use clap::{Args, Subcommand};
+
+use ockam::Context;
+use ockam_api::cloud::addon::Addon;
+
+use crate::help;
+use crate::node::util::delete_embedded_node;
+use crate::util::api::{self, CloudOpts};
+use crate::util::secret::parse_secret;
+use crate::util::{node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+
+/// Manage the addons of a project
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, subcommand_required = true)]
+#[command(hide = help::hide())]
+pub struct AddonCommand {
+    #[command(subcommand)]
+    subcommand: AddonSubcommand,
+
+    #[command(flatten)]
+    cloud_opts: CloudOpts,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum AddonSubcommand {
+    /// List the addons available to a project
+    List {
+        /// Id of the project.
+        #[arg(display_order = 1001)]
+        project_id: String,
+    },
+
+    /// Disable an addon of a project
+    Disable {
+        /// Id of the project.
+        #[arg(display_order = 1001)]
+        project_id: String,
+
+        /// Id of the addon to disable.
+        #[arg(display_order = 1002)]
+        addon_id: String,
+    },
+
+    /// Configure the Confluent addon of a project
+    Confluent(ConfluentCommand),
+
+    /// Configure the InfluxDB addon of a project
+    #[command(name = "influxdb")]
+    InfluxDB(InfluxDBCommand),
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct ConfluentCommand {
+    /// Id of the project.
+    #[arg(display_order = 1001)]
+    pub project_id: String,
+
+    /// Address of the Confluent Cloud bootstrap server.
+    #[arg(long, display_order = 1002)]
+    pub bootstrap_server: String,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct InfluxDBCommand {
+    /// Id of the project.
+    #[arg(display_order = 1001)]
+    pub project_id: String,
+
+    /// URL of the InfluxDB instance.
+    #[arg(long, display_order = 1002)]
+    pub endpoint_url: String,
+
+    /// InfluxDB token used to issue leased tokens.
+    /// Can be read from `@env:NAME` or `@file:PATH`.
+    #[arg(long, value_parser = parse_secret, display_order = 1003)]
+    pub token: String,
+
+    /// Id of the InfluxDB organization.
+    #[arg(long, display_order = 1004)]
+    pub org_id: String,
+
+    /// JSON-encoded permissions granted to the leased tokens.
+    #[arg(long, display_order = 1005)]
+    pub permissions: String,
+
+    /// Maximum lifetime of a leased token, in seconds.
+    #[arg(long, default_value_t = 3600, display_order = 1006)]
+    pub max_ttl: u64,
+}
+
+impl AddonCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(
+    mut ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, AddonCommand),
+) -> crate::Result<()> {
+    run_impl(&mut ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &mut Context,
+    opts: CommandGlobalOpts,
+    cmd: AddonCommand,
+) -> crate::Result<()> {
+    let route = cmd.cloud_opts.route();
+    let mut rpc = Rpc::embedded(ctx, &opts).await?;
+    match &cmd.subcommand {
+        AddonSubcommand::List { project_id } => {
+            rpc.request(api::project::list_addons(project_id, &route))
+                .await?;
+            rpc.parse_and_print_response::<Vec<Addon>>()?;
+        }
+        AddonSubcommand::Disable {
+            project_id,
+            addon_id,
+        } => {
+            rpc.request(api::project::disable_addon(project_id, addon_id, &route))
+                .await?;
+            rpc.is_ok()?;
+        }
+        AddonSubcommand::Confluent(c) => {
+            rpc.request(api::project::configure_confluent_addon(c, &route))
+                .await?;
+            rpc.is_ok()?;
+        }
+        AddonSubcommand::InfluxDB(c) => {
+            rpc.request(api::project::configure_influxdb_addon(c, &route))
+                .await?;
+            rpc.is_ok()?;
+        }
+    }
+    delete_embedded_node(&opts.config, rpc.node_name()).await;
+    Ok(())
+}
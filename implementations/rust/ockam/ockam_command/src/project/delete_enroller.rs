@@ -24,8 +24,8 @@ pub struct DeleteEnrollerCommand {
 }
 
 impl DeleteEnrollerCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        node_rpc(rpc, (options, self));
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
     }
 }
 
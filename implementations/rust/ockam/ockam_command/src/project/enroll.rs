@@ -14,8 +14,10 @@ use tracing::debug;
 
 use crate::node::util::{delete_embedded_node, start_embedded_node};
 use crate::node::NodeOpts;
+use crate::project::ticket::EnrollmentTicket;
 use crate::util::api::{self, CloudOpts};
-use crate::util::{node_rpc, RpcBuilder};
+use crate::util::secret::parse_secret_as;
+use crate::util::{node_rpc, parse_attribute, RpcBuilder};
 use crate::{help, CommandGlobalOpts, Result};
 
 /// An authorised enroller can add members to a project.
@@ -29,19 +31,28 @@ pub struct EnrollCommand {
     #[command(flatten)]
     node_opts: NodeOpts,
 
-    #[arg(long, short)]
-    member: IdentityIdentifier,
+    #[arg(long, short, required_unless_present = "ticket")]
+    member: Option<IdentityIdentifier>,
 
-    #[arg(long, short)]
-    to: MultiAddr,
+    #[arg(long, short, required_unless_present = "ticket")]
+    to: Option<MultiAddr>,
+
+    /// Attribute to assign to the member, as `key=value`.
+    #[arg(long = "attribute", value_name = "KEY=VALUE", value_parser = parse_attribute, conflicts_with = "ticket")]
+    attributes: Vec<(String, String)>,
+
+    /// Redeem a ticket created by `ockam project ticket` to become a member.
+    /// Can be read from `@env:NAME` or `@file:PATH`.
+    #[arg(long, conflicts_with_all = ["member", "to"], value_parser = parse_secret_as::<EnrollmentTicket>)]
+    ticket: Option<EnrollmentTicket>,
 }
 
 impl EnrollCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         node_rpc(
             |ctx, (opts, cmd)| Runner::new(ctx, opts, cmd).run(),
             (options, self),
-        );
+        )
     }
 }
 
@@ -59,21 +70,39 @@ impl Runner {
     async fn run(self) -> Result<()> {
         let node_name = start_embedded_node(&self.ctx, &self.opts.config).await?;
 
+        if let Some(t) = &self.cmd.ticket {
+            self.redeem(t, &node_name).await?;
+            delete_embedded_node(&self.opts.config, &node_name).await;
+            return Ok(());
+        }
+
+        // Both are required by clap when no ticket is given.
+        let (member, to) = match (&self.cmd.member, &self.cmd.to) {
+            (Some(m), Some(t)) => (m, t),
+            _ => return Err(anyhow!("--member and --to are required").into()),
+        };
+
         let map = self.opts.config.lookup();
-        let to = if let Some(a) = project_authority(&self.cmd.to, &map)? {
-            let mut addr = self.secure_channel(a, &node_name).await?;
-            for proto in self.cmd.to.iter().skip(1) {
+        let to = if let Some(a) = project_authority(to, &map)? {
+            let addr = replace_project(to, a.address())?;
+            let mut addr =
+                secure_channel(&self.ctx, &self.opts, &node_name, &addr, a.identity_id()).await?;
+            for proto in to.iter().skip(1) {
                 addr.push_back_value(&proto).map_err(anyhow::Error::from)?
             }
             addr
         } else {
-            self.cmd.to.clone()
+            to.clone()
         };
-        let req = Request::post("/members").body(AddMember::new(self.cmd.member.clone()));
+        let mut body = AddMember::new(member.clone());
+        for (k, v) in &self.cmd.attributes {
+            body = body.with_attribute(k, v)
+        }
+        let req = Request::post("/members").body(body);
         let mut rpc = RpcBuilder::new(&self.ctx, &self.opts, &node_name)
             .to(&to)?
             .build();
-        debug!(addr = %to, member = %self.cmd.member, "requesting to add member");
+        debug!(addr = %to, %member, "requesting to add member");
         rpc.request(req).await?;
         rpc.is_ok()?;
 
@@ -82,31 +111,55 @@ impl Runner {
         Ok(())
     }
 
-    async fn secure_channel(
-        &self,
-        auth: &ProjectAuthority,
-        node_name: &str,
-    ) -> anyhow::Result<MultiAddr> {
-        let mut rpc = RpcBuilder::new(&self.ctx, &self.opts, node_name).build();
-        let addr = replace_project(&self.cmd.to, auth.address())?;
-        debug!(%addr, "establishing secure channel to project authority");
-        let allowed = vec![auth.identity_id().clone()];
-        rpc.request(api::create_secure_channel(
-            &addr,
-            Some(allowed),
-            CredentialExchangeMode::None,
-        ))
+    async fn redeem(&self, ticket: &EnrollmentTicket, node_name: &str) -> Result<()> {
+        let mut to = secure_channel(
+            &self.ctx,
+            &self.opts,
+            node_name,
+            &ticket.authority_route,
+            &ticket.authority,
+        )
         .await?;
-        let res = rpc.parse_response::<CreateSecureChannelResponse>()?;
-        let addr = res.addr()?;
-        Ok(addr)
+        for proto in ticket.service.iter() {
+            to.push_back_value(&proto).map_err(anyhow::Error::from)?
+        }
+        let req = Request::post("/redeem").body(ticket.one_time_code);
+        let mut rpc = RpcBuilder::new(&self.ctx, &self.opts, node_name)
+            .to(&to)?
+            .build();
+        debug!(addr = %to, "redeeming enrollment ticket");
+        rpc.request(req).await?;
+        rpc.is_ok()?;
+        Ok(())
     }
 }
 
+/// Create a secure channel to the project authority.
+pub(crate) async fn secure_channel(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    node_name: &str,
+    addr: &MultiAddr,
+    authority: &IdentityIdentifier,
+) -> anyhow::Result<MultiAddr> {
+    let mut rpc = RpcBuilder::new(ctx, opts, node_name).build();
+    debug!(%addr, "establishing secure channel to project authority");
+    let allowed = vec![authority.clone()];
+    rpc.request(api::create_secure_channel(
+        addr,
+        Some(allowed),
+        CredentialExchangeMode::None,
+    ))
+    .await?;
+    let res = rpc.parse_response::<CreateSecureChannelResponse>()?;
+    let addr = res.addr()?;
+    Ok(addr)
+}
+
 /// Get the project authority from the first address protocol.
 ///
 /// If the first protocol is a `/project`, look up the project's config.
-fn project_authority<'a>(
+pub(crate) fn project_authority<'a>(
     input: &MultiAddr,
     map: &'a ConfigLookup,
 ) -> anyhow::Result<Option<&'a ProjectAuthority>> {
@@ -130,7 +183,7 @@ fn project_authority<'a>(
 /// Replaces the first `/project` with the given address.
 ///
 /// Assumes (and asserts!) that the first protocol is a `/project`.
-fn replace_project(input: &MultiAddr, with: &MultiAddr) -> anyhow::Result<MultiAddr> {
+pub(crate) fn replace_project(input: &MultiAddr, with: &MultiAddr) -> anyhow::Result<MultiAddr> {
     let mut iter = input.iter();
     let first = iter.next().map(|p| p.code());
     assert_eq!(first, Some(proto::Project::CODE));
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context as _};
+use clap::Args;
+
+use ockam_core::CowStr;
+
+use crate::project::ProjectInfo;
+use crate::CommandGlobalOpts;
+
+/// Export a project for `ockam project import` on another machine
+///
+/// The project is written as the JSON accepted by `ockam project import`
+/// and `ockam node create --project`, with its route and the identity and
+/// route of its authority. Nothing is read from the Orchestrator, so the
+/// project has to be known locally, e.g. after `ockam project list`.
+#[derive(Clone, Debug, Args)]
+pub struct ExportCommand {
+    /// Name of the project.
+    pub name: String,
+
+    /// File to write the project to, instead of the standard output.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+impl ExportCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        run_impl(options, self)
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: ExportCommand) -> crate::Result<()> {
+    let info = project_info(&opts, &cmd.name)?;
+    let json = serde_json::to_string_pretty(&info)?;
+    match &cmd.output {
+        Some(path) => std::fs::write(path, json)
+            .with_context(|| format!("Failed to write {}", path.display()))?,
+        None => println!("{json}"),
+    }
+    Ok(())
+}
+
+fn project_info(opts: &CommandGlobalOpts, name: &str) -> crate::Result<ProjectInfo<'static>> {
+    let lookup = opts.config.lookup();
+    let p = lookup.get_project(name).ok_or_else(|| {
+        anyhow!("Project '{name}' is not known locally, run `ockam project list` first")
+    })?;
+    let incomplete =
+        || anyhow!("Project '{name}' is not ready yet, run `ockam project list` to refresh it");
+    let route = p.node_route.as_ref().ok_or_else(incomplete)?;
+    let authority = p.authority.as_ref().ok_or_else(incomplete)?;
+    Ok(ProjectInfo {
+        id: CowStr::from(p.id.clone()),
+        name: CowStr::from(name.to_string()),
+        identity: Some(p.identity_id.clone().ok_or_else(incomplete)?),
+        access_route: CowStr::from(route.to_string()),
+        authority_access_route: Some(CowStr::from(authority.address().to_string())),
+        authority_identity: Some(CowStr::from(hex::encode(authority.identity()))),
+    })
+}
@@ -25,8 +25,8 @@ pub struct GetCredentialCommand {
 }
 
 impl GetCredentialCommand {
-    pub fn run(self, opts: CommandGlobalOpts) {
-        node_rpc(rpc, (opts, self));
+    pub fn run(self, opts: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (opts, self))
     }
 }
 
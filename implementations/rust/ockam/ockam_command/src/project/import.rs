@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use clap::Args;
+
+use ockam::Context;
+
+use crate::node::util::add_project_authority;
+use crate::project::util::config;
+use crate::project::ProjectInfo;
+use crate::util::node_rpc;
+use crate::CommandGlobalOpts;
+
+/// Import a project exported with `ockam project export`
+///
+/// The project is added to the local configuration, so that it can be
+/// referred to as `/project/<name>`, and its authority is trusted by the
+/// given nodes. The Orchestrator is not contacted.
+#[derive(Clone, Debug, Args)]
+pub struct ImportCommand {
+    /// Path of the exported project file.
+    pub file: PathBuf,
+
+    /// Node to trust the project authority of. May be repeated.
+    #[arg(long = "node", value_name = "NODE")]
+    pub nodes: Vec<String>,
+}
+
+impl ImportCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(_ctx: Context, (opts, cmd): (CommandGlobalOpts, ImportCommand)) -> crate::Result<()> {
+    let s = tokio::fs::read_to_string(&cmd.file)
+        .await
+        .with_context(|| format!("Failed to read {}", cmd.file.display()))?;
+    let p: ProjectInfo = serde_json::from_str(&s).context("Failed to parse the project")?;
+    config::set_project(&opts.config, &(&p).into()).await?;
+    for node in &cmd.nodes {
+        add_project_authority(p.clone(), node, &opts.config)
+            .await
+            .with_context(|| format!("Failed to configure node {node}"))?;
+    }
+    println!("Imported project {}", p.name);
+    Ok(())
+}
@@ -67,8 +67,8 @@ impl<'a> From<&ProjectInfo<'a>> for Project<'a> {
 }
 
 impl InfoCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        node_rpc(rpc, (options, self));
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
     }
 }
 
@@ -1,3 +1,4 @@
+use anyhow::Context as _;
 use clap::Args;
 use ockam::Context;
 
@@ -14,11 +15,14 @@ use crate::CommandGlobalOpts;
 pub struct ListCommand {
     #[command(flatten)]
     pub cloud_opts: CloudOpts,
+    /// Show the last successful listing if the controller is unreachable.
+    #[arg(long)]
+    pub cached: bool,
 }
 
 impl ListCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        node_rpc(rpc, (options, self));
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
     }
 }
 
@@ -32,10 +36,27 @@ async fn run_impl(
     cmd: ListCommand,
 ) -> crate::Result<()> {
     let mut rpc = Rpc::embedded(ctx, &opts).await?;
-    rpc.request(api::project::list(&cmd.cloud_opts.route()))
-        .await?;
-    let projects = rpc.parse_and_print_response::<Vec<Project>>()?;
-    config::set_projects(&opts.config, &projects).await?;
+    let res = rpc
+        .request(api::project::list(&cmd.cloud_opts.route()))
+        .await
+        .and_then(|_| rpc.parse_response::<Vec<Project>>());
+    match res {
+        Ok(projects) => {
+            let projects = rpc.print_response(projects)?;
+            config::set_projects(&opts.config, &projects).await?;
+        }
+        Err(err) if cmd.cached => {
+            let listing = opts.config.get_cached_projects().ok_or(err)?;
+            let projects: Vec<Project> = minicbor::decode(listing.data.as_slice())
+                .context("Failed to decode cached projects")?;
+            eprintln!(
+                "Could not reach the controller, showing projects as of {}s ago",
+                listing.age()
+            );
+            rpc.print_response(projects)?;
+        }
+        Err(err) => return Err(err.into()),
+    }
     delete_embedded_node(&opts.config, rpc.node_name()).await;
     Ok(())
 }
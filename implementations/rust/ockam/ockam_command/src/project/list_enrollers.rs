@@ -22,8 +22,8 @@ pub struct ListEnrollersCommand {
 }
 
 impl ListEnrollersCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        node_rpc(rpc, (options, self));
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
     }
 }
 
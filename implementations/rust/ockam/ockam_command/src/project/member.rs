@@ -0,0 +1,114 @@
+use anyhow::Context as _;
+use clap::{Args, Subcommand};
+
+use ockam::Context;
+use ockam_api::cloud::project::Project;
+
+use crate::node::util::{delete_embedded_node, start_embedded_node};
+use crate::project::util::config;
+use crate::util::api::{self, CloudOpts};
+use crate::util::{node_rpc, RpcBuilder};
+use crate::CommandGlobalOpts;
+
+/// Manage the members of a project
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, subcommand_required = true)]
+pub struct MemberCommand {
+    #[command(subcommand)]
+    subcommand: MemberSubcommand,
+
+    #[command(flatten)]
+    cloud_opts: CloudOpts,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum MemberSubcommand {
+    /// Add a member to a project
+    Add {
+        /// Name of the project.
+        #[arg(display_order = 1001)]
+        project_name: String,
+
+        /// Email address of the member.
+        #[arg(display_order = 1002)]
+        email: String,
+    },
+
+    /// Remove a member from a project
+    Remove {
+        /// Name of the project.
+        #[arg(display_order = 1001)]
+        project_name: String,
+
+        /// Email address of the member.
+        #[arg(display_order = 1002)]
+        email: String,
+    },
+
+    /// List the members of a project
+    List {
+        /// Name of the project.
+        #[arg(display_order = 1001)]
+        project_name: String,
+    },
+}
+
+impl MemberCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(
+    mut ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, MemberCommand),
+) -> crate::Result<()> {
+    run_impl(&mut ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &mut Context,
+    opts: CommandGlobalOpts,
+    cmd: MemberCommand,
+) -> crate::Result<()> {
+    let route = cmd.cloud_opts.route();
+    let node_name = start_embedded_node(ctx, &opts.config).await?;
+    let project_name = match &cmd.subcommand {
+        MemberSubcommand::Add { project_name, .. }
+        | MemberSubcommand::Remove { project_name, .. }
+        | MemberSubcommand::List { project_name } => project_name,
+    };
+
+    // Lookup project
+    let id = match config::get_project(&opts.config, project_name) {
+        Some(id) => id,
+        None => {
+            config::refresh_projects(ctx, &opts, &node_name, &route, None).await?;
+            config::get_project(&opts.config, project_name)
+                .context(format!("Project '{}' does not exist", project_name))?
+        }
+    };
+
+    let mut rpc = RpcBuilder::new(ctx, &opts, &node_name).build();
+    match &cmd.subcommand {
+        MemberSubcommand::Add { email, .. } => {
+            rpc.request(api::project::add_member(&id, email, &route))
+                .await?;
+            rpc.is_ok()?;
+        }
+        MemberSubcommand::Remove { email, .. } => {
+            rpc.request(api::project::remove_member(&id, email, &route))
+                .await?;
+            rpc.is_ok()?;
+        }
+        MemberSubcommand::List { .. } => {
+            rpc.request(api::project::show(&id, &route)).await?;
+            let project = rpc.parse_response::<Project>()?;
+            for user in &project.users {
+                println!("{user}");
+            }
+        }
+    }
+    delete_embedded_node(&opts.config, rpc.node_name()).await;
+    Ok(())
+}
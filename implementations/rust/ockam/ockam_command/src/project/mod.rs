@@ -1,12 +1,17 @@
 mod add_enroller;
+mod addons;
 mod create;
 mod delete;
 mod delete_enroller;
-mod enroll;
+pub(crate) mod enroll;
+mod export;
+mod import;
 mod info;
 mod list;
 mod list_enrollers;
+mod member;
 mod show;
+pub(crate) mod ticket;
 pub mod util;
 
 pub use info::ProjectInfo;
@@ -16,14 +21,19 @@ use clap::{Args, Subcommand};
 
 pub use crate::credential::get_credential::GetCredentialCommand;
 pub use add_enroller::AddEnrollerCommand;
+pub use addons::{AddonCommand, ConfluentCommand, InfluxDBCommand};
 pub use create::CreateCommand;
 pub use delete::DeleteCommand;
 pub use delete_enroller::DeleteEnrollerCommand;
 pub use enroll::EnrollCommand;
+pub use export::ExportCommand;
+pub use import::ImportCommand;
 pub use info::InfoCommand;
 pub use list::ListCommand;
 pub use list_enrollers::ListEnrollersCommand;
+pub use member::MemberCommand;
 pub use show::ShowCommand;
+pub use ticket::TicketCommand;
 
 use crate::CommandGlobalOpts;
 
@@ -45,11 +55,16 @@ pub enum ProjectSubcommand {
     AddEnroller(AddEnrollerCommand),
     ListEnrollers(ListEnrollersCommand),
     DeleteEnroller(DeleteEnrollerCommand),
+    Addon(AddonCommand),
+    Member(MemberCommand),
     Enroll(EnrollCommand),
+    Ticket(TicketCommand),
+    Export(ExportCommand),
+    Import(ImportCommand),
 }
 
 impl ProjectCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             ProjectSubcommand::Create(c) => c.run(options),
             ProjectSubcommand::Delete(c) => c.run(options),
@@ -58,8 +73,13 @@ impl ProjectCommand {
             ProjectSubcommand::AddEnroller(c) => c.run(options),
             ProjectSubcommand::ListEnrollers(c) => c.run(options),
             ProjectSubcommand::DeleteEnroller(c) => c.run(options),
+            ProjectSubcommand::Addon(c) => c.run(options),
+            ProjectSubcommand::Member(c) => c.run(options),
             ProjectSubcommand::Enroll(c) => c.run(options),
+            ProjectSubcommand::Ticket(c) => c.run(options),
             ProjectSubcommand::Info(c) => c.run(options),
+            ProjectSubcommand::Export(c) => c.run(options),
+            ProjectSubcommand::Import(c) => c.run(options),
         }
     }
 }
@@ -0,0 +1,121 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use clap::Args;
+use minicbor::{Decode, Encode};
+
+use ockam::identity::IdentityIdentifier;
+use ockam::Context;
+use ockam_api::authenticator::direct::types::{CreateToken, OneTimeCode};
+use ockam_core::api::Request;
+use ockam_multiaddr::MultiAddr;
+use tracing::debug;
+
+use crate::node::util::{delete_embedded_node, start_embedded_node};
+use crate::node::NodeOpts;
+use crate::project::enroll::{project_authority, replace_project, secure_channel};
+use crate::util::api::CloudOpts;
+use crate::util::{node_rpc, parse_attribute, RpcBuilder};
+use crate::{help, CommandGlobalOpts, Result};
+
+/// Create a one-time ticket that can be redeemed to become a project member.
+#[derive(Clone, Debug, Args)]
+#[command(hide = help::hide())]
+pub struct TicketCommand {
+    /// Orchestrator address to resolve projects present in the `at` argument
+    #[command(flatten)]
+    cloud_opts: CloudOpts,
+
+    #[command(flatten)]
+    node_opts: NodeOpts,
+
+    /// Address of the project authenticator, e.g. `/project/default/service/authenticator`.
+    #[arg(long, short)]
+    to: MultiAddr,
+
+    /// Attribute to assign to the member redeeming the ticket, as `key=value`.
+    #[arg(long = "attribute", value_name = "KEY=VALUE", value_parser = parse_attribute)]
+    attributes: Vec<(String, String)>,
+}
+
+impl TicketCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, TicketCommand)) -> Result<()> {
+    let node_name = start_embedded_node(&ctx, &opts.config).await?;
+
+    let map = opts.config.lookup();
+    let auth = project_authority(&cmd.to, &map)?
+        .ok_or_else(|| anyhow!("the address {} does not start with a /project", cmd.to))?;
+    let authority_route = replace_project(&cmd.to, auth.address())?;
+    let mut service = MultiAddr::default();
+    for proto in cmd.to.iter().skip(1) {
+        service.push_back_value(&proto)?
+    }
+
+    let mut to = secure_channel(
+        &ctx,
+        &opts,
+        &node_name,
+        &authority_route,
+        auth.identity_id(),
+    )
+    .await?;
+    for proto in service.iter() {
+        to.push_back_value(&proto)?
+    }
+
+    let mut body = CreateToken::new();
+    for (k, v) in &cmd.attributes {
+        body = body.with_attribute(k.as_str(), v.as_str())
+    }
+    let mut rpc = RpcBuilder::new(&ctx, &opts, &node_name).to(&to)?.build();
+    debug!(addr = %to, "requesting one-time code");
+    rpc.request(Request::post("/tokens").body(body)).await?;
+    let otc = rpc.parse_response::<OneTimeCode>()?;
+
+    let ticket = EnrollmentTicket {
+        one_time_code: otc,
+        authority: auth.identity_id().clone(),
+        authority_route,
+        service,
+    };
+    println!("{ticket}");
+
+    delete_embedded_node(&opts.config, &node_name).await;
+    Ok(())
+}
+
+/// A one-time code along with what is needed to reach the project authority.
+///
+/// It is printed as a hex-encoded CBOR map so it can be copied to another machine
+/// and redeemed with `ockam project enroll --ticket`.
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct EnrollmentTicket {
+    #[n(1)] pub one_time_code: OneTimeCode,
+    #[n(2)] pub authority: IdentityIdentifier,
+    #[n(3)] pub authority_route: MultiAddr,
+    #[n(4)] pub service: MultiAddr,
+}
+
+impl fmt::Display for EnrollmentTicket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = minicbor::to_vec(self).map_err(|_| fmt::Error)?;
+        f.write_str(&hex::encode(bytes))
+    }
+}
+
+impl FromStr for EnrollmentTicket {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|_| anyhow!("invalid ticket encoding"))?;
+        minicbor::decode(&bytes).map_err(|_| anyhow!("invalid ticket"))
+    }
+}
@@ -2,7 +2,7 @@ use std::io::Write;
 use std::str::FromStr;
 
 use anyhow::{anyhow, Context as _, Result};
-use tracing::debug;
+use tracing::{debug, warn};
 
 use ockam::identity::IdentityIdentifier;
 use ockam::TcpTransport;
@@ -151,7 +151,7 @@ pub async fn check_project_readiness<'a>(
             std::io::stdout().flush()?;
             tokio::time::sleep(std::time::Duration::from_secs(2)).await;
             let mut rpc = RpcBuilder::new(ctx, opts, api_node).build();
-            rpc.request(api::project::show(&project.id, cloud_route))
+            rpc.request(api::project::poll(&project.id, cloud_route))
                 .await?;
             let p = rpc.parse_response::<Project>()?;
             if p.is_ready() {
@@ -303,6 +303,11 @@ pub mod config {
     }
 
     pub async fn set_projects(config: &OckamConfig, projects: &[Project<'_>]) -> Result<()> {
+        config.set_cached_projects(projects)?;
+        set_projects_lookup(config, projects).await
+    }
+
+    async fn set_projects_lookup(config: &OckamConfig, projects: &[Project<'_>]) -> Result<()> {
         config.remove_projects_alias();
         for project in projects.iter() {
             set(config, project).await?;
@@ -330,9 +335,22 @@ pub mod config {
         tcp: Option<&TcpTransport>,
     ) -> Result<()> {
         let mut rpc = RpcBuilder::new(ctx, opts, api_node).tcp(tcp)?.build();
-        rpc.request(api::project::list(controller_route)).await?;
-        let projects = rpc.parse_response::<Vec<Project>>()?;
-        set_projects(&opts.config, &projects).await?;
-        Ok(())
+        let res = rpc
+            .request(api::project::list(controller_route))
+            .await
+            .and_then(|_| rpc.parse_response::<Vec<Project>>());
+        match res {
+            Ok(projects) => set_projects(&opts.config, &projects).await,
+            // Resolve projects from the last successful listing if the controller is unreachable.
+            Err(err) => match opts.config.get_cached_projects() {
+                Some(listing) => {
+                    let projects: Vec<Project> = minicbor::decode(listing.data.as_slice())
+                        .context("Failed to decode cached projects")?;
+                    warn!(%err, age = listing.age(), "Failed to list projects, using cached listing");
+                    set_projects_lookup(&opts.config, &projects).await
+                }
+                None => Err(err),
+            },
+        }
     }
 }
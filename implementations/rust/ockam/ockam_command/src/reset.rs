@@ -1,33 +1,66 @@
-use crate::node::util::delete_all_nodes;
+use crate::node::util::{kill_orphan_processes, stop_all_nodes};
 use crate::CommandGlobalOpts;
 use clap::Args;
 use std::io::{self, BufReader, Read, Write};
 
 /// Full Ockam Reset
+///
+/// Stops all nodes and removes their state, the default vault and the CLI configuration.
 #[derive(Clone, Debug, Args)]
 pub struct ResetCommand {
+    /// Do not ask for confirmation.
     #[arg(display_order = 901, long, short)]
     yes: bool,
+
+    /// Kill nodes instead of stopping them gracefully, along with any other ockam process.
+    /// Implies `--yes`.
+    #[arg(display_order = 902, long, short)]
+    force: bool,
+
+    /// Only stop and delete nodes, keeping the CLI configuration, identity and vault.
+    #[arg(display_order = 903, long)]
+    nodes_only: bool,
 }
 
 impl ResetCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        let _ = run_impl(options, self);
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        run_impl(options, self).map_err(|e| crate::Error::new(crate::util::exitcode::IOERR, e))
     }
 }
 
-fn run_impl(opts: CommandGlobalOpts, cmd: ResetCommand) -> crate::Result<()> {
-    if cmd.yes || get_user_confirmation() {
-        if let Err(e) = delete_all_nodes(opts, true) {
-            eprintln!("{}", e);
-            std::process::exit(crate::util::exitcode::IOERR);
+fn run_impl(opts: CommandGlobalOpts, cmd: ResetCommand) -> anyhow::Result<()> {
+    if !(cmd.yes || cmd.force || get_user_confirmation(cmd.nodes_only)) {
+        return Ok(());
+    }
+
+    stop_all_nodes(&opts, cmd.force)?;
+
+    if cmd.nodes_only {
+        opts.config.persist_config_updates()?;
+    } else {
+        // The default vault may have been configured outside of the config directory.
+        if let Some(path) = opts.config.get_default_vault_path() {
+            if let Err(e) = std::fs::remove_file(path) {
+                if e.kind() != io::ErrorKind::NotFound {
+                    return Err(e.into());
+                }
+            }
         }
+        opts.config.remove()?;
+    }
+
+    if cmd.force {
+        kill_orphan_processes();
     }
     Ok(())
 }
 
-fn get_user_confirmation() -> bool {
-    let prompt = "Please confirm the you really want a full reset (y/N) ";
+fn get_user_confirmation(nodes_only: bool) -> bool {
+    let prompt = if nodes_only {
+        "Please confirm that you really want to delete all nodes (y/N) "
+    } else {
+        "Please confirm the you really want a full reset (y/N) "
+    };
     print!("{}", prompt);
     if io::stdout().flush().is_err() {
         // If stdout wasn't flushed properly, fallback to println
@@ -0,0 +1,49 @@
+pub(crate) mod resolve;
+
+pub(crate) use resolve::ResolveCommand;
+
+use crate::help;
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+
+const HELP_DETAIL: &str = "\
+About:
+    Addresses can name nodes, projects and secure channel hops instead of
+    transport addresses and channel workers. Resolving an address asks a
+    node to create the secure channels it needs, to a project and through
+    every `/secure/` hop, and prints the address messages are sent to.
+
+```sh
+    # Resolve an address through a forwarder at a project
+    $ ockam route resolve /project/default/service/forward_to_n1/secure/api/service/echo
+    /service/2a4c5b1d/service/echo
+
+    # Pipe the result into other commands
+    $ ockam route resolve /node/n2/secure/api | ockam message send hello --to -/service/uppercase
+```
+";
+
+/// Resolve addresses into routes
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    after_long_help = help::template(HELP_DETAIL)
+)]
+pub struct RouteCommand {
+    #[command(subcommand)]
+    subcommand: RouteSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum RouteSubcommand {
+    Resolve(ResolveCommand),
+}
+
+impl RouteCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        match self.subcommand {
+            RouteSubcommand::Resolve(c) => c.run(options),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::anyhow;
+use clap::Args;
+
+use ockam::identity::IdentityIdentifier;
+use ockam::Context;
+use ockam_api::clean_multiaddr;
+use ockam_api::nodes::models::route::ResolvedRoute;
+use ockam_multiaddr::MultiAddr;
+
+use crate::node::NodeOpts;
+use crate::util::{api, node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+
+/// Resolve an address into the route a node sends messages along
+#[derive(Clone, Debug, Args)]
+pub struct ResolveCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+
+    /// Address to resolve
+    pub address: MultiAddr,
+
+    /// Authorized identity for the last secure channel hop
+    #[arg(long, value_name = "IDENTIFIER")]
+    pub authorized: Option<IdentityIdentifier>,
+}
+
+impl ResolveCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, ResolveCommand)) -> crate::Result<()> {
+    // Node names are only known to the command, projects are resolved by the node.
+    let (address, _) = clean_multiaddr(&cmd.address, &opts.config.lookup())
+        .ok_or_else(|| anyhow!("Address '{}' is invalid", cmd.address))?;
+    let mut rpc = Rpc::background(&ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request(api::resolve_route(address, cmd.authorized))
+        .await?;
+    rpc.parse_and_print_response::<ResolvedRoute>()?;
+    Ok(())
+}
@@ -4,14 +4,15 @@ use crate::{
     CommandGlobalOpts, OutputFormat, Result,
 };
 
-use anyhow::Context as _;
+use anyhow::{anyhow, Context as _};
 use atty::Stream;
 use clap::Args;
 use colorful::Colorful;
 use serde_json::json;
 
+use crate::secure_channel::listener::create::key_exchange;
 use crate::secure_channel::HELP_DETAIL;
-use crate::util::api::CloudOpts;
+use crate::util::api::{CloudOpts, IdempotencyOpts};
 use crate::util::RpcBuilder;
 use ockam::{identity::IdentityIdentifier, route, Context, TcpTransport};
 use ockam_api::config::lookup::ConfigLookup;
@@ -33,24 +34,39 @@ pub struct CreateCommand {
     #[arg(value_name = "ROUTE", long, display_order = 800)]
     pub to: MultiAddr,
 
+    /// Further routes to the same listener, e.g. over IPv6 or another relay.
+    /// All routes are attempted and the first one to succeed is kept
+    #[arg(value_name = "ROUTE", long = "alternative", display_order = 800)]
+    pub alternatives: Vec<MultiAddr>,
+
     /// Identifiers authorized to be presented by the listener
     #[arg(value_name = "IDENTIFIER", long, short, display_order = 801)]
     pub authorized: Option<Vec<IdentityIdentifier>>,
 
+    /// Offer the hybrid X25519 + Kyber768 key exchange, falling back to the
+    /// classic one if the listener doesn't support it. Requires a node built
+    /// with the `pq-hybrid` feature
+    #[arg(long, display_order = 802)]
+    pub hybrid_key_exchange: bool,
+
     /// Orchestrator address to resolve projects present in the `at` argument
     #[command(flatten)]
     cloud_opts: CloudOpts,
+
+    #[command(flatten)]
+    idempotency_opts: IdempotencyOpts,
 }
 
 impl CreateCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        node_rpc(rpc, (options, self));
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
     }
 
-    // Read the `to` argument and return a MultiAddr
-    // or exit with and error if `to` can't be parsed.
+    // Read the `to` argument, or one of its alternatives, and return a MultiAddr
+    // or exit with and error if it can't be parsed.
     async fn parse_to_route(
         &self,
+        addr: &MultiAddr,
         ctx: &Context,
         opts: &CommandGlobalOpts,
         cloud_addr: &MultiAddr,
@@ -58,8 +74,8 @@ impl CreateCommand {
         tcp: &TcpTransport,
     ) -> anyhow::Result<MultiAddr> {
         let config = &opts.config.lookup();
-        let (to, meta) = clean_multiaddr(&self.to, config)
-            .context(format!("Could not convert {} into route", &self.to))?;
+        let (to, meta) = clean_multiaddr(addr, config)
+            .context(format!("Could not convert {} into route", addr))?;
 
         let projects_sc = crate::project::util::get_projects_secure_channels_from_config_lookup(
             ctx,
@@ -85,7 +101,7 @@ impl CreateCommand {
         parsed_to: &MultiAddr,
         options: &CommandGlobalOpts,
         response: CreateSecureChannelResponse,
-    ) {
+    ) -> Result<()> {
         let route = &route![response.addr.to_string()];
         match route_to_multiaddr(route) {
             Some(multiaddr) => {
@@ -134,23 +150,18 @@ impl CreateCommand {
                 }
             }
             None => {
-                // if stderr is interactive/tty and we haven't been asked to be quiet
-                // and output format is plain then write a plain info to stderr.
-                if atty::is(Stream::Stderr)
-                    && !options.global_args.quiet
-                    && options.global_args.output_format == OutputFormat::Plain
-                {
-                    eprintln!(
+                // return the exitcode::API_ERROR since if things are going as expected
+                // a route in the response should be convertable to multiaddr.
+                return Err(crate::Error::new(
+                    exitcode::API_ERROR,
+                    anyhow!(
                         "Could not convert returned secure channel address {} into a multiaddr",
                         route
-                    );
-                }
-
-                // return the exitcode::PROTOCOL since if things are going as expected
-                // a route in the response should be convertable to multiaddr.
-                std::process::exit(exitcode::PROTOCOL);
+                    ),
+                ));
             }
         };
+        Ok(())
     }
 }
 
@@ -160,20 +171,31 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> R
     let config = &opts.config.lookup();
     let from = &cmd.parse_from_node(config);
     let to = &cmd
-        .parse_to_route(&ctx, &opts, &cmd.cloud_opts.route(), from, &tcp)
+        .parse_to_route(&cmd.to, &ctx, &opts, &cmd.cloud_opts.route(), from, &tcp)
         .await?;
+    let mut alternatives = Vec::new();
+    for addr in &cmd.alternatives {
+        alternatives.push(
+            cmd.parse_to_route(addr, &ctx, &opts, &cmd.cloud_opts.route(), from, &tcp)
+                .await?,
+        );
+    }
 
     let authorized_identifiers = cmd.authorized.clone();
 
     // Delegate the request to create a secure channel to the from node.
     let mut rpc = RpcBuilder::new(&ctx, &opts, from).tcp(&tcp)?.build();
-    let request =
-        api::create_secure_channel(to, authorized_identifiers, CredentialExchangeMode::Mutual);
+    let request = api::create_secure_channel_to_any(
+        to,
+        &alternatives,
+        authorized_identifiers,
+        CredentialExchangeMode::Mutual,
+        key_exchange(cmd.hybrid_key_exchange),
+    );
+    let request = cmd.idempotency_opts.apply(request);
 
     rpc.request(request).await?;
     let response = rpc.parse_response::<CreateSecureChannelResponse>()?;
 
-    cmd.print_output(from, to, &opts, response);
-
-    Ok(())
+    cmd.print_output(from, to, &opts, response)
 }
@@ -10,6 +10,7 @@ use atty::Stream;
 use colorful::Colorful;
 use serde_json::json;
 
+use anyhow::anyhow;
 use clap::Parser;
 use ockam::{route, Context};
 use ockam_api::{nodes::models::secure_channel::DeleteSecureChannelResponse, route_to_multiaddr};
@@ -29,8 +30,8 @@ pub struct DeleteCommand {
 }
 
 impl DeleteCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        node_rpc(rpc, (options, self));
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
     }
 
     // Read the `at` argument and return node name
@@ -44,7 +45,7 @@ impl DeleteCommand {
         address: &Address,
         options: &CommandGlobalOpts,
         response: DeleteSecureChannelResponse,
-    ) {
+    ) -> Result<()> {
         match response.channel {
             Some(address) => {
                 let route = &route![address.to_string()];
@@ -86,21 +87,15 @@ impl DeleteCommand {
                         }
                     }
                     None => {
-                        // if stderr is interactive/tty and we haven't been asked to be quiet
-                        // and output format is plain then write a plain info to stderr.
-                        if atty::is(Stream::Stderr)
-                            && !options.global_args.quiet
-                            && options.global_args.output_format == OutputFormat::Plain
-                        {
-                            eprintln!(
+                        // return the exitcode::API_ERROR since if things are going as expected
+                        // a route in the response should be convertable to multiaddr.
+                        return Err(crate::Error::new(
+                            exitcode::API_ERROR,
+                            anyhow!(
                                 "Could not convert returned secure channel route {} into a multiaddr",
                                 route
-                            );
-                        }
-
-                        // return the exitcode::PROTOCOL since if things are going as expected
-                        // a route in the response should be convertable to multiaddr.
-                        std::process::exit(exitcode::PROTOCOL);
+                            ),
+                        ));
                     }
                 }
             }
@@ -120,6 +115,7 @@ impl DeleteCommand {
                 println!("channel with address {} not found", address)
             }
         }
+        Ok(())
     }
 }
 
@@ -149,7 +145,5 @@ async fn rpc(ctx: Context, (options, command): (CommandGlobalOpts, DeleteCommand
     rpc.request(request).await?;
     let response = rpc.parse_response::<DeleteSecureChannelResponse>()?;
 
-    command.print_output(at, address, &options, response);
-
-    Ok(())
+    command.print_output(at, address, &options, response)
 }
@@ -1,3 +1,4 @@
+use anyhow::anyhow;
 use atty::Stream;
 use clap::Args;
 use colorful::Colorful;
@@ -27,8 +28,8 @@ pub struct ListCommand {
 }
 
 impl ListCommand {
-    pub fn run(self, opts: CommandGlobalOpts) {
-        node_rpc(rpc, (opts, self));
+    pub fn run(self, opts: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (opts, self))
     }
 
     fn print_output(
@@ -152,15 +153,7 @@ async fn rpc(
         .collect();
     let responses = results?;
 
-    if let Err(e) = command.print_output(&options, channel_identifiers, responses) {
-        if atty::is(Stream::Stderr)
-            && !options.global_args.quiet
-            && options.global_args.output_format == OutputFormat::Plain
-        {
-            eprintln!("{}", e);
-        }
-        std::process::exit(exitcode::PROTOCOL)
-    }
-
-    Ok(())
+    command
+        .print_output(&options, channel_identifiers, responses)
+        .map_err(|e| crate::Error::new(exitcode::API_ERROR, anyhow!(e)))
 }
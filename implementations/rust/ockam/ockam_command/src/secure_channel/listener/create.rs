@@ -1,11 +1,13 @@
 use crate::secure_channel::HELP_DETAIL;
-use crate::util::{api, connect_to, exitcode, extract_address_value};
+use crate::util::{api, connect_to, exitcode, extract_address_value, parse_attribute};
 use crate::{help, CommandGlobalOpts};
 
+use anyhow::anyhow;
 use clap::Args;
 
 use ockam::identity::IdentityIdentifier;
 
+use ockam_api::nodes::models::secure_channel::KeyExchange;
 use ockam_api::nodes::NODEMANAGER_ADDR;
 use ockam_core::api::Status;
 use ockam_core::{Address, Route};
@@ -21,8 +23,54 @@ pub struct CreateCommand {
     address: Address,
 
     /// Authorized Identifiers of secure channel initiators
-    #[arg(short, long, value_name = "IDENTIFIER")]
+    #[arg(
+        short,
+        long,
+        value_name = "IDENTIFIER",
+        conflicts_with = "sync_members"
+    )]
     authorized_identifier: Option<Vec<IdentityIdentifier>>,
+
+    /// Credential attribute required from secure channel initiators.
+    /// Repeating an attribute accepts any of its values. It is checked
+    /// during the handshake, so initiators have to present their
+    /// credential to the node beforehand
+    #[arg(long = "trusted-attribute", value_name = "KEY=VALUE", value_parser = parse_attribute)]
+    trusted_attributes: Vec<(String, String)>,
+
+    #[command(flatten)]
+    limits: ListenerLimitsOpts,
+
+    #[command(flatten)]
+    members: MemberSyncOpts,
+
+    /// Accept the hybrid X25519 + Kyber768 key exchange from initiators
+    /// offering it. Requires a node built with the `pq-hybrid` feature
+    #[arg(long)]
+    hybrid_key_exchange: bool,
+}
+
+#[derive(Clone, Debug, Default, Args)]
+pub struct ListenerLimitsOpts {
+    /// Maximum number of concurrent secure channels accepted by the listener
+    #[arg(long, value_name = "COUNT")]
+    pub max_channels: Option<u32>,
+
+    /// Maximum number of concurrent secure channels initiated by the same identity
+    #[arg(long, value_name = "COUNT")]
+    pub max_channels_per_identity: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, Args)]
+pub struct MemberSyncOpts {
+    /// Only accept the members of the project, as periodically fetched from
+    /// the project authority, instead of a fixed list of identifiers
+    #[arg(long)]
+    pub sync_members: bool,
+
+    /// Seconds between two fetches of the project members
+    #[arg(long, value_name = "SECONDS", requires = "sync_members")]
+    pub sync_interval: Option<u64>,
 }
 
 #[derive(Clone, Debug, Args)]
@@ -33,16 +81,26 @@ pub struct SecureChannelListenerNodeOpts {
 }
 
 impl CreateCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         let cfg = options.config;
         let node = extract_address_value(&self.node_opts.at).unwrap_or_else(|_| "".to_string());
-        let port = cfg.get_node_port(&node).unwrap();
+        let port = cfg.get_node_port(&node)?;
 
         connect_to(port, self, |ctx, cmd, rte| async {
-            create_listener(&ctx, cmd.address, cmd.authorized_identifier, rte).await?;
+            create_listener(
+                &ctx,
+                cmd.address,
+                cmd.authorized_identifier,
+                cmd.trusted_attributes,
+                cmd.limits,
+                cmd.members,
+                key_exchange(cmd.hybrid_key_exchange),
+                rte,
+            )
+            .await?;
             drop(ctx);
             Ok(())
-        });
+        })
     }
 }
 
@@ -50,12 +108,24 @@ pub async fn create_listener(
     ctx: &ockam::Context,
     addr: Address,
     authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+    trusted_attributes: Vec<(String, String)>,
+    limits: ListenerLimitsOpts,
+    members: MemberSyncOpts,
+    key_exchange: KeyExchange,
     mut base_route: Route,
 ) -> anyhow::Result<()> {
     let resp: Vec<u8> = ctx
         .send_and_receive(
             base_route.modify().append(NODEMANAGER_ADDR),
-            api::create_secure_channel_listener(&addr, authorized_identifiers)?,
+            api::create_secure_channel_listener(
+                &addr,
+                authorized_identifiers,
+                limits.max_channels,
+                limits.max_channels_per_identity,
+                trusted_attributes,
+                members.sync_members.then_some(members.sync_interval),
+                key_exchange,
+            )?,
         )
         .await?;
 
@@ -66,9 +136,18 @@ pub async fn create_listener(
             println!("/service/{}", addr.address());
             Ok(())
         }
-        _ => {
-            eprintln!("An error occurred while creating secure channel listener",);
-            std::process::exit(exitcode::CANTCREAT)
-        }
+        _ => Err(crate::Error::new(
+            exitcode::CANTCREAT,
+            anyhow!("An error occurred while creating secure channel listener"),
+        )
+        .into()),
+    }
+}
+
+pub fn key_exchange(hybrid: bool) -> KeyExchange {
+    if hybrid {
+        KeyExchange::Hybrid
+    } else {
+        KeyExchange::Classic
     }
 }
@@ -29,7 +29,7 @@ pub enum SecureChannelListenerSubcommand {
 }
 
 impl SecureChannelListenerCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             SecureChannelListenerSubcommand::Create(c) => c.run(options),
             SecureChannelListenerSubcommand::List(c) => c.run(options),
@@ -161,7 +161,7 @@ enum SecureChannelSubcommand {
 }
 
 impl SecureChannelCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             SecureChannelSubcommand::Create(c) => c.run(options),
             SecureChannelSubcommand::Delete(c) => c.run(options),
@@ -30,6 +30,21 @@ pub struct SecureChannelListenerConfig {
     #[serde(default)]
     pub(crate) authorized_identifiers: Option<Vec<IdentityIdentifier>>,
 
+    #[serde(default)]
+    pub(crate) max_channels: Option<u32>,
+
+    #[serde(default)]
+    pub(crate) max_channels_per_identity: Option<u32>,
+
+    #[serde(default)]
+    pub(crate) hybrid_key_exchange: bool,
+
+    #[serde(default)]
+    pub(crate) sync_members: bool,
+
+    #[serde(default)]
+    pub(crate) sync_interval: Option<u64>,
+
     #[serde(default)]
     pub(crate) disabled: bool,
 }
@@ -52,6 +67,18 @@ pub struct AuthenticatorConfig {
 
     pub(crate) project: String,
 
+    /// URL of a Redis server shared by the replicas of the authenticator
+    #[serde(default)]
+    pub(crate) redis: Option<String>,
+
+    /// Route to a signer service issuing the credentials
+    #[serde(default)]
+    pub(crate) signer: Option<String>,
+
+    /// Keep one-time codes in the member storage instead of in memory
+    #[serde(default)]
+    pub(crate) stateless: bool,
+
     #[serde(default)]
     pub(crate) disabled: bool,
 }
@@ -1,7 +1,9 @@
 pub(crate) mod config;
 pub(crate) mod start;
+pub(crate) mod stop;
 
 pub(crate) use start::StartCommand;
+pub(crate) use stop::StopCommand;
 
 use crate::help;
 use crate::CommandGlobalOpts;
@@ -18,12 +20,15 @@ pub struct ServiceCommand {
 pub enum ServiceSubcommand {
     #[command(display_order = 900)]
     Start(StartCommand),
+    #[command(display_order = 901)]
+    Stop(StopCommand),
 }
 
 impl ServiceCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             ServiceSubcommand::Start(c) => c.run(options),
+            ServiceSubcommand::Stop(c) => c.run(options),
         }
     }
 }
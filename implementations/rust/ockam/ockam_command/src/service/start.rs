@@ -1,13 +1,17 @@
 use crate::node::NodeOpts;
+use crate::service::config::AuthenticatorConfig;
 use crate::util::{api, node_rpc, RpcBuilder};
 use crate::CommandGlobalOpts;
 use anyhow::{anyhow, Result};
 use clap::{Args, Subcommand};
 use minicbor::Encode;
+use ockam::identity::IdentityIdentifier;
 use ockam::{Context, TcpTransport};
+use ockam_api::nodes::models::services::ServiceOptions;
 use ockam_api::DefaultAddress;
 use ockam_core::api::{RequestBuilder, Status};
-use std::path::{Path, PathBuf};
+use ockam_multiaddr::MultiAddr;
+use std::path::PathBuf;
 
 #[derive(Clone, Debug, Args)]
 pub struct StartCommand {
@@ -16,6 +20,18 @@ pub struct StartCommand {
 
     #[command(subcommand)]
     pub create_subcommand: StartSubCommand,
+
+    /// Guard the messages sent to the service with the ABAC policy of this resource
+    #[arg(long, global = true, value_name = "RESOURCE")]
+    pub resource: Option<String>,
+
+    /// Restart the service when its worker stops unexpectedly
+    #[arg(long, global = true)]
+    pub restart: bool,
+
+    /// Give up restarting the service after this many restarts
+    #[arg(long, global = true, value_name = "COUNT", requires = "restart")]
+    pub max_restarts: Option<u32>,
 }
 
 #[derive(Clone, Debug, Subcommand)]
@@ -36,6 +52,86 @@ pub enum StartSubCommand {
         #[arg(long, default_value_t = verifier_default_addr())]
         addr: String,
     },
+    /// Publish the revocation list of an authority node
+    Revocation {
+        #[arg(long, default_value_t = revocation_default_addr())]
+        addr: String,
+    },
+    /// Sign credentials on behalf of stateless authenticators
+    Signer {
+        #[arg(long, default_value_t = signer_default_addr())]
+        addr: String,
+
+        /// Identifier of an authenticator allowed to request signatures
+        #[arg(
+            long = "authorized-identifier",
+            value_name = "IDENTIFIER",
+            required = true
+        )]
+        authorized: Vec<IdentityIdentifier>,
+    },
+    /// Deliver the events published to topics to their subscribers
+    Pubsub {
+        #[arg(long, default_value_t = pubsub_default_addr())]
+        addr: String,
+
+        /// Number of events retained per topic and replayed to new subscribers
+        #[arg(long, value_name = "COUNT")]
+        retain: Option<u64>,
+
+        /// Keep the retained events in the node storage across restarts
+        #[arg(long, requires = "retain")]
+        persist: bool,
+    },
+    /// Let other nodes register forwarders at this node, to use it as a relay
+    ///
+    /// Nodes start one at /service/forwarding_service, which accepts all
+    /// registrations. Stop it to start one with --resource, whose policy
+    /// decides which identities may register aliases.
+    Forwarding {
+        #[arg(long, default_value_t = forwarding_default_addr())]
+        addr: String,
+    },
+    /// Echo messages back to their sender
+    ///
+    /// Nodes start one at /service/echo. Stop it to start one with limits.
+    Echo {
+        #[arg(long, default_value_t = echo_default_addr())]
+        addr: String,
+
+        /// Largest payload echoed back, in bytes
+        #[arg(long, value_name = "BYTES")]
+        max_payload: Option<u64>,
+
+        /// Number of messages echoed back per second and sender
+        #[arg(long, value_name = "COUNT")]
+        rate_limit: Option<u32>,
+
+        /// Number of messages echoed back per second across all senders
+        /// (1000 when only --rate-limit is given)
+        #[arg(long, value_name = "COUNT")]
+        total_rate_limit: Option<u32>,
+
+        /// Only echo back messages received over authenticated secure channels
+        #[arg(long)]
+        authenticated: bool,
+    },
+    /// Answer the measurements of `ockam perf`
+    ///
+    /// Nodes don't start one by default. Use --resource to decide with a
+    /// policy which identities may measure routes to it.
+    Perf {
+        #[arg(long, default_value_t = perf_default_addr())]
+        addr: String,
+    },
+    /// Keep the most recent messages sent to addresses which don't exist
+    ///
+    /// They can be inspected via `GET /node/dead_letters`.
+    DeadLetters {
+        /// Number of messages retained
+        #[arg(long, value_name = "COUNT")]
+        capacity: Option<u64>,
+    },
     Credentials {
         #[arg(long, default_value_t = credentials_default_addr())]
         addr: String,
@@ -52,6 +148,61 @@ pub enum StartSubCommand {
 
         #[arg(long)]
         project: String,
+
+        /// Store members in a Redis server shared with other authenticators
+        #[arg(long, value_name = "URL")]
+        redis: Option<String>,
+
+        /// Delegate the signing of credentials to the signer service at this route
+        #[arg(long, value_name = "ROUTE")]
+        signer: Option<MultiAddr>,
+
+        /// Keep one-time codes in the member storage, so that any
+        /// authenticator sharing it can redeem them
+        #[arg(long)]
+        stateless: bool,
+    },
+    /// Expose a Kafka cluster to remote Kafka inlets
+    KafkaOutlet {
+        #[arg(long, default_value_t = kafka_outlet_default_addr())]
+        addr: String,
+
+        /// Address of a Kafka bootstrap server
+        #[arg(long, value_name = "HOST:PORT")]
+        bootstrap_server: String,
+    },
+    /// Let local Kafka clients reach a remote Kafka outlet
+    KafkaInlet {
+        #[arg(long, default_value_t = kafka_inlet_default_addr())]
+        addr: String,
+
+        /// Local address Kafka clients bootstrap from
+        #[arg(long, value_name = "SOCKET_ADDRESS", default_value = "127.0.0.1:9092")]
+        from: String,
+
+        /// Route to the remote Kafka outlet service
+        #[arg(long, value_name = "ROUTE")]
+        to: MultiAddr,
+    },
+    /// Proxy SOCKS5 and HTTP CONNECT requests to the outlets of a remote node
+    ///
+    /// The host requested by the client is the address of the outlet to connect to,
+    /// which has to be one of the outlets given with `--outlet`.
+    Proxy {
+        #[arg(long, default_value_t = proxy_default_addr())]
+        addr: String,
+
+        /// Local address proxy clients connect to
+        #[arg(long, value_name = "SOCKET_ADDRESS", default_value = "127.0.0.1:1080")]
+        from: String,
+
+        /// Route to the node hosting the outlets
+        #[arg(long, value_name = "ROUTE")]
+        to: MultiAddr,
+
+        /// Address of an outlet clients may connect to (can be repeated)
+        #[arg(long = "outlet", value_name = "OUTLET_ADDRESS", required = true)]
+        outlets: Vec<String>,
     },
 }
 
@@ -71,6 +222,30 @@ fn verifier_default_addr() -> String {
     DefaultAddress::VERIFIER.to_string()
 }
 
+fn revocation_default_addr() -> String {
+    DefaultAddress::REVOCATION.to_string()
+}
+
+fn signer_default_addr() -> String {
+    DefaultAddress::SIGNER.to_string()
+}
+
+fn echo_default_addr() -> String {
+    DefaultAddress::ECHO_SERVICE.to_string()
+}
+
+fn perf_default_addr() -> String {
+    DefaultAddress::PERF_SERVICE.to_string()
+}
+
+fn pubsub_default_addr() -> String {
+    DefaultAddress::PUBSUB.to_string()
+}
+
+fn forwarding_default_addr() -> String {
+    DefaultAddress::FORWARDING_SERVICE.to_string()
+}
+
 fn credentials_default_addr() -> String {
     DefaultAddress::CREDENTIAL_SERVICE.to_string()
 }
@@ -79,9 +254,21 @@ fn authenticator_default_addr() -> String {
     DefaultAddress::AUTHENTICATOR.to_string()
 }
 
+fn kafka_outlet_default_addr() -> String {
+    DefaultAddress::KAFKA_OUTLET.to_string()
+}
+
+fn kafka_inlet_default_addr() -> String {
+    DefaultAddress::KAFKA_INLET.to_string()
+}
+
+fn proxy_default_addr() -> String {
+    DefaultAddress::PROXY_SERVICE.to_string()
+}
+
 impl StartCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        node_rpc(rpc, (options, self));
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
     }
 }
 
@@ -99,6 +286,29 @@ async fn run_impl(
 ) -> crate::Result<()> {
     let node_name = &cmd.node_opts.api_node;
     let tcp = TcpTransport::create(ctx).await?;
+
+    // Only the services of the node service catalog take these options
+    let mut options = ServiceOptions::new().with_restart(cmd.restart);
+    if let Some(r) = cmd.resource.as_deref() {
+        options = options.with_resource(r)
+    }
+    if let Some(n) = cmd.max_restarts {
+        options = options.with_max_restarts(n)
+    }
+    if (cmd.resource.is_some() || cmd.restart)
+        && matches!(
+            cmd.create_subcommand,
+            StartSubCommand::Vault { .. }
+                | StartSubCommand::Identity { .. }
+                | StartSubCommand::Authenticated { .. }
+                | StartSubCommand::KafkaOutlet { .. }
+                | StartSubCommand::KafkaInlet { .. }
+                | StartSubCommand::Proxy { .. }
+        )
+    {
+        return Err(anyhow!("--resource and --restart are not supported by this service").into());
+    }
+
     match cmd.create_subcommand {
         StartSubCommand::Vault { addr, .. } => {
             start_vault_service(ctx, &opts, node_name, &addr, Some(&tcp)).await?
@@ -120,29 +330,108 @@ async fn run_impl(
             .await?
         }
         StartSubCommand::Verifier { addr, .. } => {
-            start_verifier_service(ctx, &opts, node_name, &addr, Some(&tcp)).await?
+            start_verifier_service(ctx, &opts, node_name, &addr, options, Some(&tcp)).await?
+        }
+        StartSubCommand::Revocation { addr, .. } => {
+            let req = api::start_revocation_service(&addr, options);
+            start_service_impl(ctx, &opts, node_name, &addr, "Revocation", req, Some(&tcp)).await?
+        }
+        StartSubCommand::Signer { addr, authorized } => {
+            let req = api::start_signer_service(&addr, authorized, options);
+            start_service_impl(ctx, &opts, node_name, &addr, "Signer", req, Some(&tcp)).await?
+        }
+        StartSubCommand::Pubsub {
+            addr,
+            retain,
+            persist,
+        } => {
+            let req = api::start_pubsub_service(&addr, retain, persist, options);
+            start_service_impl(ctx, &opts, node_name, &addr, "Pub/sub", req, Some(&tcp)).await?
+        }
+        StartSubCommand::Forwarding { addr } => {
+            let req = api::start_forwarding_service(&addr, options);
+            start_service_impl(ctx, &opts, node_name, &addr, "Forwarding", req, Some(&tcp)).await?
+        }
+        StartSubCommand::Echo {
+            addr,
+            max_payload,
+            rate_limit,
+            total_rate_limit,
+            authenticated,
+        } => {
+            let req = api::start_echoer_service(
+                &addr,
+                max_payload,
+                rate_limit,
+                total_rate_limit,
+                authenticated,
+                options,
+            );
+            start_service_impl(ctx, &opts, node_name, &addr, "Echo", req, Some(&tcp)).await?
+        }
+        StartSubCommand::Perf { addr } => {
+            let req = api::start_perf_service(&addr, options);
+            start_service_impl(ctx, &opts, node_name, &addr, "Perf", req, Some(&tcp)).await?
+        }
+        StartSubCommand::DeadLetters { capacity } => {
+            let req = api::start_dead_letter_service(capacity, options);
+            let addr = ockam_api::dead_letters::DEAD_LETTER_ADDRESS;
+            start_service_impl(ctx, &opts, node_name, addr, "Dead letter", req, Some(&tcp)).await?
         }
         StartSubCommand::Credentials { addr, oneway, .. } => {
-            let req = api::start_credentials_service(&addr, oneway);
+            let req = api::start_credentials_service(&addr, oneway, options);
             start_service_impl(ctx, &opts, node_name, &addr, "Credentials", req, Some(&tcp)).await?
         }
         StartSubCommand::Authenticator {
             addr,
             enrollers,
             project,
-            ..
+            redis,
+            signer,
+            stateless,
         } => {
-            start_authenticator_service(
+            let cfg = AuthenticatorConfig {
+                address: addr,
+                enrollers,
+                project,
+                redis,
+                signer: signer.map(|s| s.to_string()),
+                stateless,
+                disabled: false,
+            };
+            start_authenticator_service(ctx, &opts, node_name, &cfg, options, Some(&tcp)).await?
+        }
+        StartSubCommand::KafkaOutlet {
+            addr,
+            bootstrap_server,
+        } => {
+            let req = api::start_kafka_outlet_service(&addr, &bootstrap_server);
+            start_service_impl(
                 ctx,
                 &opts,
                 node_name,
                 &addr,
-                &enrollers,
-                &project,
+                "Kafka outlet",
+                req,
                 Some(&tcp),
             )
             .await?
         }
+        StartSubCommand::KafkaInlet { addr, from, to } => {
+            let to = to.to_string();
+            let req = api::start_kafka_inlet_service(&addr, &from, &to);
+            start_service_impl(ctx, &opts, node_name, &addr, "Kafka inlet", req, Some(&tcp)).await?
+        }
+        StartSubCommand::Proxy {
+            addr,
+            from,
+            to,
+            outlets,
+        } => {
+            let to = to.to_string();
+            let req = api::start_proxy_service(&addr, &from, &to, &outlets);
+            start_service_impl(ctx, &opts, node_name, &addr, "Proxy", req, Some(&tcp)).await?
+        }
     }
 
     Ok(())
@@ -207,9 +496,10 @@ pub async fn start_verifier_service(
     opts: &CommandGlobalOpts,
     node_name: &str,
     serv_addr: &str,
+    options: ServiceOptions<'_>,
     tcp: Option<&'_ TcpTransport>,
 ) -> Result<()> {
-    let req = api::start_verifier_service(serv_addr);
+    let req = api::start_verifier_service(serv_addr, options);
     start_service_impl(ctx, opts, node_name, serv_addr, "Verifier", req, tcp).await
 }
 
@@ -218,11 +508,27 @@ pub async fn start_authenticator_service(
     ctx: &Context,
     opts: &CommandGlobalOpts,
     node_name: &str,
-    serv_addr: &str,
-    enrollers: &Path,
-    project: &str,
+    cfg: &AuthenticatorConfig,
+    options: ServiceOptions<'_>,
     tcp: Option<&'_ TcpTransport>,
 ) -> Result<()> {
-    let req = api::start_authenticator_service(serv_addr, enrollers, project);
-    start_service_impl(ctx, opts, node_name, serv_addr, "Authenticator", req, tcp).await
+    let req = api::start_authenticator_service(
+        &cfg.address,
+        &cfg.enrollers,
+        &cfg.project,
+        cfg.redis.as_deref(),
+        cfg.signer.as_deref(),
+        cfg.stateless,
+        options,
+    );
+    start_service_impl(
+        ctx,
+        opts,
+        node_name,
+        &cfg.address,
+        "Authenticator",
+        req,
+        tcp,
+    )
+    .await
 }
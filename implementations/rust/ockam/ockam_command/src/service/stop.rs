@@ -0,0 +1,33 @@
+use crate::node::NodeOpts;
+use crate::util::{api, node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+use clap::Args;
+use ockam::Context;
+
+/// Stop a service started on a node
+#[derive(Clone, Debug, Args)]
+pub struct StopCommand {
+    #[command(flatten)]
+    pub node_opts: NodeOpts,
+
+    /// Kind of the service, e.g. `echo` or `verifier`
+    pub kind: String,
+
+    /// Address of the service
+    #[arg(long)]
+    pub addr: String,
+}
+
+impl StopCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, StopCommand)) -> crate::Result<()> {
+    let mut rpc = Rpc::background(&ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request(api::stop_service(&cmd.kind, &cmd.addr)).await?;
+    rpc.is_ok()?;
+    println!("{} service stopped at address: {}", cmd.kind, cmd.addr);
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anyhow::Context as _;
+use clap::{Args, ValueEnum};
+
+use ockam::Context;
+use ockam_api::nodes::models::base::SessionGraph;
+
+use crate::node::NodeOpts;
+use crate::session::HELP_DETAIL;
+use crate::util::{api, node_rpc, Rpc};
+use crate::{help, CommandGlobalOpts, Result};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    Json,
+    Dot,
+}
+
+/// Print the dependency graph of the sessions of a node
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(HELP_DETAIL))]
+pub struct GraphCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+
+    /// Format of the graph
+    #[arg(long, value_enum, default_value_t = GraphFormat::Json)]
+    format: GraphFormat,
+}
+
+impl GraphCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, GraphCommand)) -> Result<()> {
+    let mut rpc = Rpc::background(&ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request(api::session_graph()).await?;
+    let graph = rpc.parse_response::<SessionGraph>()?;
+    let output = match cmd.format {
+        GraphFormat::Json => {
+            serde_json::to_string_pretty(&graph).context("Failed to serialize session graph")?
+        }
+        GraphFormat::Dot => graph.to_dot(),
+    };
+    println!("{output}");
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use clap::{Args, Subcommand};
+
+pub(crate) use graph::GraphCommand;
+
+use crate::{help, CommandGlobalOpts};
+
+mod graph;
+
+const HELP_DETAIL: &str = "\
+About:
+    Sessions are the connections a node supervises and re-establishes when
+    they fail, like the secure channels of forwarders and inlets. A session
+    depends on another one when its route goes through the route of the
+    other one, so that the failure of the latter takes the former down too.
+
+Examples:
+
+```sh
+    # Render the sessions of node n1 and their dependencies with Graphviz
+    $ ockam session graph --node n1 --format dot | dot -Tsvg > sessions.svg
+```
+";
+
+/// Inspect the sessions of a node
+#[derive(Clone, Debug, Args)]
+#[command(
+    arg_required_else_help = true,
+    subcommand_required = true,
+    after_long_help = help::template(HELP_DETAIL)
+)]
+pub struct SessionCommand {
+    #[command(subcommand)]
+    subcommand: SessionSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum SessionSubCommand {
+    Graph(GraphCommand),
+}
+
+impl SessionCommand {
+    pub fn run(self, opts: CommandGlobalOpts) -> crate::Result<()> {
+        match self.subcommand {
+            SessionSubCommand::Graph(c) => c.run(opts),
+        }
+    }
+}
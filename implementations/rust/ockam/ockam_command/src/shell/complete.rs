@@ -0,0 +1,133 @@
+use clap::CommandFactory;
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::Helper;
+
+use super::connection::{Connection, Query};
+use crate::{CommandGlobalOpts, OckamCommand};
+
+/// The commands of the shell which are not Ockam commands.
+const BUILTINS: &[&str] = &["exit", "help", "use"];
+
+/// Arguments whose values are node names.
+const NODE_ARGS: &[&str] = &["-n", "--node", "--from"];
+
+/// Arguments whose values are addresses.
+const ADDRESS_ARGS: &[&str] = &["-t", "--to", "--at", "--route"];
+
+/// Completes commands, flags, node names and addresses of the selected node
+pub struct ShellHelper {
+    opts: CommandGlobalOpts,
+    node_name: String,
+    connection: Connection,
+    command: clap::Command,
+}
+
+impl ShellHelper {
+    pub fn new(opts: CommandGlobalOpts, node_name: String, connection: Connection) -> Self {
+        let mut command = OckamCommand::command();
+        command.build();
+        Self {
+            opts,
+            node_name,
+            connection,
+            command,
+        }
+    }
+
+    pub fn node_name(&self) -> &str {
+        &self.node_name
+    }
+
+    pub fn set_node_name(&mut self, node_name: &str) {
+        self.node_name = node_name.to_string()
+    }
+
+    fn candidates(&self, words: &[&str], word: &str) -> Vec<String> {
+        let prev = words.last().copied();
+        if words == ["use"] || prev.map_or(false, |p| NODE_ARGS.contains(&p)) {
+            return self.node_names();
+        }
+        if prev.map_or(false, |p| ADDRESS_ARGS.contains(&p)) {
+            return self.addresses();
+        }
+
+        let mut cmd = &self.command;
+        for w in words.iter().take_while(|w| !w.starts_with('-')) {
+            match cmd.find_subcommand(w) {
+                Some(c) => cmd = c,
+                None => return Vec::new(),
+            }
+        }
+        if word.starts_with('-') {
+            return cmd
+                .get_arguments()
+                .filter(|a| !a.is_hide_set())
+                .filter_map(|a| a.get_long())
+                .map(|l| format!("--{l}"))
+                .collect();
+        }
+        let mut names: Vec<String> = cmd
+            .get_subcommands()
+            .filter(|c| !c.is_hide_set())
+            .map(|c| c.get_name().to_string())
+            .collect();
+        if words.is_empty() {
+            names.extend(BUILTINS.iter().map(|b| b.to_string()))
+        }
+        names
+    }
+
+    fn node_names(&self) -> Vec<String> {
+        self.opts.config.inner().nodes.keys().cloned().collect()
+    }
+
+    fn addresses(&self) -> Vec<String> {
+        let mut addrs: Vec<String> = self
+            .node_names()
+            .into_iter()
+            .map(|n| format!("/node/{n}"))
+            .collect();
+        for query in [Query::Services, Query::SecureChannels] {
+            let found = self.connection.query(&self.node_name, query);
+            addrs.extend(found.into_iter().map(|a| format!("/service/{a}")))
+        }
+        addrs
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &line[start..];
+        let words: Vec<&str> = line[..start].split_whitespace().collect();
+        let mut candidates: Vec<String> = self
+            .candidates(&words, word)
+            .into_iter()
+            .filter(|c| c.starts_with(word))
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
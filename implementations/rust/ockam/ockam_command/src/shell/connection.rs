@@ -0,0 +1,112 @@
+use core::time::Duration;
+
+use crossbeam_channel::{bounded, Sender};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tracing::debug;
+
+use ockam::{Context, TcpTransport};
+use ockam_api::nodes::models::services::ServiceList;
+
+use crate::util::{api, embedded_node, RpcBuilder};
+use crate::CommandGlobalOpts;
+
+/// How long completions wait for a node to answer.
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// What the shell asks a node for, to complete addresses
+#[derive(Debug, Clone, Copy)]
+pub enum Query {
+    Services,
+    SecureChannels,
+}
+
+struct Request {
+    node_name: String,
+    query: Query,
+    reply: Sender<Vec<String>>,
+}
+
+/// Connections to nodes, kept open for the lifetime of the shell
+///
+/// The connections are owned by an embedded node running on a thread of
+/// its own, which the shell sends its queries to.
+pub struct Connection {
+    tx: UnboundedSender<Request>,
+}
+
+impl Connection {
+    pub fn open(opts: CommandGlobalOpts) -> Self {
+        let (tx, rx) = unbounded_channel();
+        std::thread::spawn(move || {
+            if let Err(e) = embedded_node(serve, (opts, rx)) {
+                debug!(%e, "The shell lost its connection to nodes");
+            }
+        });
+        Self { tx }
+    }
+
+    /// Query a node, returning nothing if it does not answer in time.
+    pub fn query(&self, node_name: &str, query: Query) -> Vec<String> {
+        let (reply, rx) = bounded(1);
+        let req = Request {
+            node_name: node_name.to_string(),
+            query,
+            reply,
+        };
+        if self.tx.send(req).is_err() {
+            return Vec::new();
+        }
+        rx.recv_timeout(TIMEOUT).unwrap_or_default()
+    }
+}
+
+async fn serve(
+    ctx: Context,
+    (opts, mut rx): (CommandGlobalOpts, UnboundedReceiver<Request>),
+) -> crate::Result<()> {
+    // Ctrl-C cancels the requests of the command being run, but leaves the
+    // shell running. Listening for it keeps it from stopping the process.
+    tokio::spawn(async { while tokio::signal::ctrl_c().await.is_ok() {} });
+
+    // Errors are not returned, as they would terminate the shell.
+    let tcp = match TcpTransport::create(&ctx).await {
+        Ok(tcp) => tcp,
+        Err(e) => {
+            debug!(%e, "Failed to create a TCP transport for the shell");
+            return Ok(());
+        }
+    };
+    while let Some(req) = rx.recv().await {
+        let answer = match query(&ctx, &opts, &tcp, &req.node_name, req.query).await {
+            Ok(answer) => answer,
+            Err(e) => {
+                debug!(%e, node = %req.node_name, query = ?req.query, "Query failed");
+                Vec::new()
+            }
+        };
+        let _ = req.reply.try_send(answer);
+    }
+    Ok(())
+}
+
+async fn query(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    tcp: &TcpTransport,
+    node_name: &str,
+    query: Query,
+) -> crate::Result<Vec<String>> {
+    let mut rpc = RpcBuilder::new(ctx, opts, node_name).tcp(tcp)?.build();
+    let answer = match query {
+        Query::Services => {
+            rpc.request(api::list_services()).await?;
+            let services = rpc.parse_response::<ServiceList>()?;
+            services.list.iter().map(|s| s.addr.to_string()).collect()
+        }
+        Query::SecureChannels => {
+            rpc.request(api::list_secure_channels()).await?;
+            rpc.parse_response::<Vec<String>>()?
+        }
+    };
+    Ok(answer)
+}
@@ -0,0 +1,176 @@
+mod complete;
+mod connection;
+
+use std::path::PathBuf;
+
+use clap::{Args, CommandFactory, Parser};
+use rustyline::error::ReadlineError;
+use rustyline::Editor;
+use tracing::debug;
+
+use ockam_api::config::cli;
+
+use crate::util::{exitcode, keep_running_on_interrupt};
+use crate::{help, CommandGlobalOpts, OckamCommand, OckamSubcommand};
+use complete::ShellHelper;
+use connection::Connection;
+
+const HELP_DETAIL: &str = "\
+About:
+    Run Ockam commands interactively, against a selected node.
+
+    Commands are entered without the leading `ockam` and run against the
+    selected node unless they name another one with `--node`. Press <TAB>
+    to complete command names, flags, node names and the addresses of the
+    services and secure channels of the selected node, which the shell
+    queries over a connection it keeps open.
+
+    Commands run within the shell process. Press Ctrl-C to cancel the
+    request a command is waiting for.
+
+    Besides Ockam commands, the shell understands:
+
+    use <NODE>    select another node
+    help          show this help
+    exit          leave the shell
+
+Examples:
+
+```sh
+    $ ockam shell n1
+    n1> secure-channel list
+    n1> message send hello --to /service/<TAB>
+    n1> use n2
+    n2> exit
+```
+";
+
+/// Run Ockam commands in an interactive shell
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(HELP_DETAIL))]
+pub struct ShellCommand {
+    /// Name of the node to run commands against
+    #[arg(default_value = "default")]
+    node_name: String,
+}
+
+impl ShellCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        run_impl(options, self.node_name).map_err(|e| crate::Error::new(exitcode::SOFTWARE, e))
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts, node_name: String) -> anyhow::Result<()> {
+    opts.config.get_node(&node_name)?;
+    keep_running_on_interrupt();
+    let connection = Connection::open(opts.clone());
+
+    let mut editor = Editor::<ShellHelper>::new()?;
+    editor.set_helper(Some(ShellHelper::new(opts.clone(), node_name, connection)));
+    let history = history_path();
+    if let Err(e) = editor.load_history(&history) {
+        debug!(%e, "No shell history loaded");
+    }
+
+    loop {
+        let node_name = editor.helper().map(|h| h.node_name().to_string());
+        let prompt = format!("{}> ", node_name.as_deref().unwrap_or_default());
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        editor.add_history_entry(line.as_str());
+        let words = match shell_words::split(&line) {
+            Ok(words) => words,
+            Err(e) => {
+                eprintln!("{e}");
+                continue;
+            }
+        };
+        let helper = editor.helper_mut().expect("the shell helper is always set");
+        match words.first().map(String::as_str) {
+            None => {}
+            Some("exit" | "quit") => break,
+            Some("help") => println!("{}", help::template(HELP_DETAIL)),
+            Some("use") => match words.get(1) {
+                Some(n) if opts.config.get_node(n).is_ok() => helper.set_node_name(n),
+                Some(n) => eprintln!("No node named {n}"),
+                None => eprintln!("Usage: use <NODE>"),
+            },
+            Some(_) => {
+                if let Err(e) = execute(helper.node_name(), words) {
+                    eprintln!("{e:?}")
+                }
+            }
+        }
+    }
+
+    if let Err(e) = editor.save_history(&history) {
+        debug!(%e, "Failed to save the shell history");
+    }
+    Ok(())
+}
+
+/// Run an Ockam command against the selected node.
+///
+/// Commands run in the shell process, without the startup cost of a new
+/// one. They return their errors rather than exiting, so that a failing
+/// command leaves the shell running.
+fn execute(node_name: &str, mut words: Vec<String>) -> crate::Result<()> {
+    if takes_node(&words) && !words.iter().any(|w| w == "-n" || w == "--node") {
+        words.push("--node".to_string());
+        words.push(node_name.to_string());
+    }
+    let args = std::iter::once("ockam".to_string()).chain(words);
+    let command = match OckamCommand::try_parse_from(args) {
+        Ok(command) => command,
+        Err(e) => {
+            // Usage errors, but also the help and the version
+            let _ = e.print();
+            return Ok(());
+        }
+    };
+    if let OckamSubcommand::Shell(_) = command.subcommand {
+        eprintln!("Already in a shell");
+        return Ok(());
+    }
+    command.run()
+}
+
+/// Whether the (sub)command the words resolve to accepts `--node`.
+fn takes_node(words: &[String]) -> bool {
+    let mut cmd = OckamCommand::command();
+    cmd.build();
+    let mut cmd = &cmd;
+    for w in words.iter().take_while(|w| !w.starts_with('-')) {
+        match cmd.find_subcommand(w) {
+            Some(c) => cmd = c,
+            None => break,
+        }
+    }
+    cmd.get_arguments().any(|a| a.get_id() == "node")
+}
+
+fn history_path() -> PathBuf {
+    cli::OckamConfig::directories()
+        .config_dir()
+        .join("shell_history")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<String> {
+        shell_words::split(line).unwrap()
+    }
+
+    #[test]
+    fn node_is_passed_to_commands_that_take_it() {
+        assert!(takes_node(&words("vault list")));
+        assert!(takes_node(&words("service start vault")));
+        assert!(!takes_node(&words("completion --shell bash")));
+    }
+}
@@ -25,7 +25,7 @@ pub struct CreateCommand {
 }
 
 impl CreateCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         println!(
             "\n{}",
             "Creating a trial space for you (everything in it will be deleted in 15 days) ..."
@@ -35,7 +35,7 @@ impl CreateCommand {
             "{}",
             "To learn more about production ready spaces in Ockam Orchestrator, contact us at: hello@ockam.io".light_magenta()
         );
-        node_rpc(rpc, (options, self));
+        node_rpc(rpc, (options, self))
     }
 }
 
@@ -1,3 +1,4 @@
+use anyhow::Context as _;
 use clap::Args;
 
 use ockam::Context;
@@ -13,11 +14,14 @@ use crate::CommandGlobalOpts;
 pub struct ListCommand {
     #[command(flatten)]
     pub cloud_opts: CloudOpts,
+    /// Show the last successful listing if the controller is unreachable.
+    #[arg(long)]
+    pub cached: bool,
 }
 
 impl ListCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
-        node_rpc(rpc, (options, self));
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
     }
 }
 
@@ -31,10 +35,27 @@ async fn run_impl(
     cmd: ListCommand,
 ) -> crate::Result<()> {
     let mut rpc = Rpc::embedded(ctx, &opts).await?;
-    rpc.request(api::space::list(&cmd.cloud_opts.route()))
-        .await?;
-    let spaces = rpc.parse_and_print_response::<Vec<Space>>()?;
-    config::set_spaces(&opts.config, &spaces)?;
+    let res = rpc
+        .request(api::space::list(&cmd.cloud_opts.route()))
+        .await
+        .and_then(|_| rpc.parse_response::<Vec<Space>>());
+    match res {
+        Ok(spaces) => {
+            let spaces = rpc.print_response(spaces)?;
+            config::set_spaces(&opts.config, &spaces)?;
+        }
+        Err(err) if cmd.cached => {
+            let listing = opts.config.get_cached_spaces().ok_or(err)?;
+            let spaces: Vec<Space> = minicbor::decode(listing.data.as_slice())
+                .context("Failed to decode cached spaces")?;
+            eprintln!(
+                "Could not reach the controller, showing spaces as of {}s ago",
+                listing.age()
+            );
+            rpc.print_response(spaces)?;
+        }
+        Err(err) => return Err(err.into()),
+    }
     delete_embedded_node(&opts.config, rpc.node_name()).await;
     Ok(())
 }
@@ -0,0 +1,104 @@
+use clap::{Args, Subcommand};
+
+use ockam::Context;
+use ockam_api::cloud::space::Space;
+
+use crate::node::util::{delete_embedded_node, start_embedded_node};
+use crate::space::util::config;
+use crate::util::api::{self, CloudOpts};
+use crate::util::{node_rpc, RpcBuilder};
+use crate::CommandGlobalOpts;
+
+/// Manage the members of a space
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, subcommand_required = true)]
+pub struct MemberCommand {
+    #[command(subcommand)]
+    subcommand: MemberSubcommand,
+
+    #[command(flatten)]
+    cloud_opts: CloudOpts,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum MemberSubcommand {
+    /// Add a member to a space
+    Add {
+        /// Name of the space.
+        #[arg(display_order = 1001)]
+        space_name: String,
+
+        /// Email address of the member.
+        #[arg(display_order = 1002)]
+        email: String,
+    },
+
+    /// Remove a member from a space
+    Remove {
+        /// Name of the space.
+        #[arg(display_order = 1001)]
+        space_name: String,
+
+        /// Email address of the member.
+        #[arg(display_order = 1002)]
+        email: String,
+    },
+
+    /// List the members of a space
+    List {
+        /// Name of the space.
+        #[arg(display_order = 1001)]
+        space_name: String,
+    },
+}
+
+impl MemberCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(
+    mut ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, MemberCommand),
+) -> crate::Result<()> {
+    run_impl(&mut ctx, opts, cmd).await
+}
+
+async fn run_impl(
+    ctx: &mut Context,
+    opts: CommandGlobalOpts,
+    cmd: MemberCommand,
+) -> crate::Result<()> {
+    let route = cmd.cloud_opts.route();
+    let node_name = start_embedded_node(ctx, &opts.config).await?;
+    let space_name = match &cmd.subcommand {
+        MemberSubcommand::Add { space_name, .. }
+        | MemberSubcommand::Remove { space_name, .. }
+        | MemberSubcommand::List { space_name } => space_name,
+    };
+    let id = config::get_space(ctx, &opts, space_name, &node_name, &route).await?;
+
+    let mut rpc = RpcBuilder::new(ctx, &opts, &node_name).build();
+    match &cmd.subcommand {
+        MemberSubcommand::Add { email, .. } => {
+            rpc.request(api::space::add_member(&id, email, &route))
+                .await?;
+            rpc.is_ok()?;
+        }
+        MemberSubcommand::Remove { email, .. } => {
+            rpc.request(api::space::remove_member(&id, email, &route))
+                .await?;
+            rpc.is_ok()?;
+        }
+        MemberSubcommand::List { .. } => {
+            rpc.request(api::space::show(&id, &route)).await?;
+            let space = rpc.parse_response::<Space>()?;
+            for user in &space.users {
+                println!("{user}");
+            }
+        }
+    }
+    delete_embedded_node(&opts.config, rpc.node_name()).await;
+    Ok(())
+}
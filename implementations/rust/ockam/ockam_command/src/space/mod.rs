@@ -3,6 +3,7 @@ use clap::{Args, Subcommand};
 pub use create::CreateCommand;
 pub use delete::DeleteCommand;
 pub use list::ListCommand;
+pub use member::MemberCommand;
 pub use show::ShowCommand;
 pub use util::config;
 
@@ -11,6 +12,7 @@ use crate::CommandGlobalOpts;
 mod create;
 mod delete;
 mod list;
+mod member;
 mod show;
 pub mod util;
 
@@ -36,17 +38,22 @@ pub enum SpaceSubcommand {
     #[command(display_order = 800)]
     List(ListCommand),
 
+    /// Manage space members
+    #[command(display_order = 800)]
+    Member(MemberCommand),
+
     /// Show spaces
     #[command(display_order = 800)]
     Show(ShowCommand),
 }
 
 impl SpaceCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             SpaceSubcommand::Create(c) => c.run(options),
             SpaceSubcommand::Delete(c) => c.run(options),
             SpaceSubcommand::List(c) => c.run(options),
+            SpaceSubcommand::Member(c) => c.run(options),
             SpaceSubcommand::Show(c) => c.run(options),
         }
     }
@@ -2,6 +2,7 @@ use anyhow::{Context as _, Result};
 
 use ockam::Context;
 use ockam_api::cloud::space::Space;
+use tracing::warn;
 
 pub mod config {
     use crate::util::{api, RpcBuilder};
@@ -17,6 +18,11 @@ pub mod config {
     }
 
     pub fn set_spaces(config: &OckamConfig, spaces: &[Space]) -> Result<()> {
+        config.set_cached_spaces(spaces)?;
+        set_spaces_lookup(config, spaces)
+    }
+
+    fn set_spaces_lookup(config: &OckamConfig, spaces: &[Space]) -> Result<()> {
         config.remove_spaces_alias();
         for space in spaces.iter() {
             config.set_space_alias(&space.id, &space.name);
@@ -60,9 +66,22 @@ pub mod config {
         controller_route: &MultiAddr,
     ) -> Result<()> {
         let mut rpc = RpcBuilder::new(ctx, opts, api_node).build();
-        rpc.request(api::space::list(controller_route)).await?;
-        let spaces = rpc.parse_response::<Vec<Space>>()?;
-        set_spaces(&opts.config, &spaces)?;
-        Ok(())
+        let res = rpc
+            .request(api::space::list(controller_route))
+            .await
+            .and_then(|_| rpc.parse_response::<Vec<Space>>());
+        match res {
+            Ok(spaces) => set_spaces(&opts.config, &spaces),
+            // Resolve spaces from the last successful listing if the controller is unreachable.
+            Err(err) => match opts.config.get_cached_spaces() {
+                Some(listing) => {
+                    let spaces: Vec<Space> = minicbor::decode(listing.data.as_slice())
+                        .context("Failed to decode cached spaces")?;
+                    warn!(%err, age = listing.age(), "Failed to list spaces, using cached listing");
+                    set_spaces_lookup(&opts.config, &spaces)
+                }
+                None => Err(err),
+            },
+        }
     }
 }
@@ -0,0 +1,208 @@
+use crate::node::format_uptime;
+use crate::util::{api, node_rpc, RpcBuilder};
+use crate::{CommandGlobalOpts, OutputFormat};
+use anyhow::anyhow;
+use clap::Args;
+use cli_table::{print_stdout, Cell, Style, Table};
+use colorful::Colorful;
+use ockam::{Address, Context, TcpTransport};
+use ockam_api::nodes::models::base::{NodeDetails, NodeHealth};
+use serde::Serialize;
+use std::time::Duration;
+use tracing::debug;
+
+/// How long each node gets to answer a request.
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Show the status of all local nodes
+///
+/// Every node is queried for its health, identity and what runs on it.
+#[derive(Clone, Debug, Args)]
+pub struct StatusCommand {}
+
+impl StatusCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(run_impl, options)
+    }
+}
+
+/// What a node reported about itself.
+#[derive(Debug, Serialize)]
+struct NodeSummary {
+    name: String,
+    up: bool,
+    pid: Option<i32>,
+    identity: Option<String>,
+    uptime: Option<u64>,
+    workers: Option<u32>,
+    transports: usize,
+    secure_channels: usize,
+    services: usize,
+    inlets: usize,
+    outlets: usize,
+}
+
+impl NodeSummary {
+    fn down(name: String, pid: Option<i32>) -> Self {
+        Self {
+            name,
+            up: false,
+            pid,
+            identity: None,
+            uptime: None,
+            workers: None,
+            transports: 0,
+            secure_channels: 0,
+            services: 0,
+            inlets: 0,
+            outlets: 0,
+        }
+    }
+}
+
+/// The CLI configuration, along with the status of every node.
+#[derive(Debug, Serialize)]
+struct Status {
+    space: Option<String>,
+    project: Option<String>,
+    nodes: Vec<NodeSummary>,
+}
+
+async fn run_impl(ctx: Context, opts: CommandGlobalOpts) -> crate::Result<()> {
+    let names: Vec<String> = opts.config.inner().nodes.keys().cloned().collect();
+
+    // Query the nodes concurrently, each from its own context.
+    let mut queries = Vec::new();
+    for name in names {
+        let child = ctx.new_detached(Address::random_local()).await?;
+        let opts = opts.clone();
+        queries.push(tokio::spawn(async move {
+            query_node(&child, &opts, name).await
+        }));
+    }
+    let mut nodes = Vec::new();
+    for q in queries {
+        nodes.push(q.await.map_err(|e| anyhow!(e))?);
+    }
+
+    let (space, project) = {
+        let inner = opts.config.inner();
+        let lookup = inner.lookup();
+        (
+            default_name(lookup.spaces().map(|(name, _)| name)),
+            default_name(lookup.projects().map(|(name, _)| name)),
+        )
+    };
+    let status = Status {
+        space,
+        project,
+        nodes,
+    };
+    match opts.global_args.output_format {
+        OutputFormat::Plain if opts.global_args.quiet => {}
+        OutputFormat::Plain => print_status(&status)?,
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&status)?),
+    }
+    Ok(())
+}
+
+/// The space or project named "default", as picked by `ockam enroll`, or
+/// else the first one.
+fn default_name(names: impl Iterator<Item = String>) -> Option<String> {
+    let names: Vec<String> = names.collect();
+    names
+        .iter()
+        .find(|n| *n == "default")
+        .or_else(|| names.first())
+        .cloned()
+}
+
+async fn query_node(ctx: &Context, opts: &CommandGlobalOpts, name: String) -> NodeSummary {
+    match query_node_impl(ctx, opts, &name).await {
+        Ok(summary) => summary,
+        Err(e) => {
+            debug!(node = %name, %e, "Node did not answer");
+            let pid = opts.config.get_node_pid(&name).ok().flatten();
+            NodeSummary::down(name, pid)
+        }
+    }
+}
+
+async fn query_node_impl(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    name: &str,
+) -> anyhow::Result<NodeSummary> {
+    let tcp = TcpTransport::create(ctx).await?;
+    let mut rpc = RpcBuilder::new(ctx, opts, name).tcp(&tcp)?.build();
+    rpc.request_with_timeout(api::node_health(), TIMEOUT)
+        .await?;
+    let health = rpc.parse_response::<NodeHealth>()?;
+    let mut rpc = RpcBuilder::new(ctx, opts, name).tcp(&tcp)?.build();
+    rpc.request_with_timeout(api::node_details(), TIMEOUT)
+        .await?;
+    let details = rpc.parse_response::<NodeDetails>()?;
+    Ok(NodeSummary {
+        name: name.to_string(),
+        up: true,
+        pid: Some(details.status.pid),
+        identity: details.identity.as_ref().map(|i| i.to_string()),
+        uptime: Some(health.uptime),
+        workers: Some(health.workers),
+        transports: details.transports.len(),
+        secure_channels: details.secure_channels.len(),
+        services: details.services.len(),
+        inlets: details.inlets.len(),
+        outlets: details.outlets.len(),
+    })
+}
+
+fn print_status(status: &Status) -> anyhow::Result<()> {
+    let none = || "-".to_string();
+    println!("Space: {}", status.space.clone().unwrap_or_else(none));
+    println!("Project: {}", status.project.clone().unwrap_or_else(none));
+    println!();
+    if status.nodes.is_empty() {
+        println!("No nodes registered on this system!");
+        return Ok(());
+    }
+
+    let table = status
+        .nodes
+        .iter()
+        .map(|n| {
+            let state = if n.up {
+                "UP".light_green()
+            } else {
+                "DOWN".light_red()
+            };
+            vec![
+                n.name.clone().cell(),
+                state.to_string().cell(),
+                n.pid.map_or_else(none, |p| p.to_string()).cell(),
+                n.identity.clone().unwrap_or_else(none).cell(),
+                n.uptime.map_or_else(none, format_uptime).cell(),
+                n.transports.cell(),
+                n.secure_channels.cell(),
+                n.services.cell(),
+                n.inlets.cell(),
+                n.outlets.cell(),
+            ]
+        })
+        .collect::<Vec<_>>()
+        .table()
+        .title(vec![
+            "Node".cell().bold(true),
+            "Status".cell().bold(true),
+            "PID".cell().bold(true),
+            "Identity".cell().bold(true),
+            "Uptime".cell().bold(true),
+            "Transports".cell().bold(true),
+            "Secure Channels".cell().bold(true),
+            "Services".cell().bold(true),
+            "Inlets".cell().bold(true),
+            "Outlets".cell().bold(true),
+        ]);
+    print_stdout(table)?;
+    Ok(())
+}
@@ -49,8 +49,8 @@ pub enum SubscriptionSubcommand {
 }
 
 impl SubscriptionCommand {
-    pub fn run(self, opts: CommandGlobalOpts) {
-        node_rpc(run_impl, (opts, self));
+    pub fn run(self, opts: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(run_impl, (opts, self))
     }
 }
 
@@ -0,0 +1,21 @@
+use clap::ValueEnum;
+use ockam_api::nodes::models::portal::Compression;
+
+/// Compression algorithm of the payloads going through a portal
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum CompressionArg {
+    Lz4,
+    /// Only available if both nodes were built with the `zstd` feature
+    Zstd,
+}
+
+impl CompressionArg {
+    pub fn to_compression(args: &[CompressionArg]) -> Vec<Compression> {
+        args.iter()
+            .map(|c| match c {
+                CompressionArg::Lz4 => Compression::Lz4,
+                CompressionArg::Zstd => Compression::Zstd,
+            })
+            .collect()
+    }
+}
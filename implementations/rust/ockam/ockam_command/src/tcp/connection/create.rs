@@ -1,17 +1,21 @@
+use crate::error::{ConnectionError, Error};
 use crate::{
     util::{api, connect_to, exitcode, extract_address_value},
     CommandGlobalOpts, OutputFormat,
 };
+use anyhow::{anyhow, Context as _};
 use clap::Args;
 use colorful::Colorful;
-use ockam::{Context, Route, TCP};
+use ockam::{Address, Context, Route, TCP};
 use ockam_api::{
+    addr_to_multiaddr,
     nodes::{models::transport::TransportStatus, NODEMANAGER_ADDR},
     route_to_multiaddr,
 };
 use ockam_core::api::Status;
 use serde_json::json;
-use std::net::SocketAddrV4;
+use std::net::SocketAddr;
+use std::str::FromStr;
 
 #[derive(Clone, Debug, Args)]
 pub struct TcpConnectionNodeOpts {
@@ -31,18 +35,41 @@ pub struct CreateCommand {
     #[command(flatten)]
     node_opts: TcpConnectionNodeOpts,
 
-    /// The address to connect to (required)
-    #[arg(id = "to", short, long, value_name = "ADDRESS")]
+    /// The address to connect to (required), e.g. `127.0.0.1:4000`,
+    /// `[::1]:4000` or `localhost:4000`
+    #[arg(id = "to", short, long, value_name = "ADDRESS", value_parser = parse_address)]
     pub address: String,
 }
 
+/// Accept either a socket address or a `host:port` pair. IPv6 addresses
+/// must be enclosed in brackets so that the port can be told apart.
+fn parse_address(input: &str) -> Result<String, String> {
+    if SocketAddr::from_str(input).is_ok() {
+        return Ok(input.to_string());
+    }
+    match input.rsplit_once(':') {
+        Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {
+            if host.contains(':') {
+                Err(format!(
+                    "invalid address '{input}', IPv6 addresses must be written as '[address]:port'"
+                ))
+            } else {
+                Ok(input.to_string())
+            }
+        }
+        _ => Err(format!(
+            "invalid address '{input}', expected 'host:port' or 'ip:port'"
+        )),
+    }
+}
+
 impl CreateCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         let cfg = &options.config;
         let node = extract_address_value(&self.node_opts.from).unwrap_or_else(|_| "".to_string());
-        let port = cfg.get_node_port(&node).unwrap();
+        let port = cfg.get_node_port(&node)?;
 
-        connect_to(port, (self, options.clone()), create_connection);
+        connect_to(port, (self, options.clone()), create_connection)
     }
 }
 
@@ -51,19 +78,15 @@ pub async fn create_connection(
     (cmd, opts): (CreateCommand, CommandGlobalOpts),
     mut base_route: Route,
 ) -> anyhow::Result<()> {
-    let resp: Vec<u8> = match ctx
+    let resp: Vec<u8> = ctx
         .send_and_receive(
             base_route.modify().append(NODEMANAGER_ADDR),
             api::create_tcp_connection(&cmd)?,
         )
         .await
-    {
-        Ok(sr_msg) => sr_msg,
-        Err(e) => {
-            eprintln!("Wasn't able to send or receive `Message`: {}", e);
-            std::process::exit(exitcode::IOERR);
-        }
-    };
+        .context(ConnectionError::new(
+            "Wasn't able to send or receive `Message`",
+        ))?;
 
     let (response, TransportStatus { payload, .. }) = api::parse_transport_status(&resp)?;
 
@@ -77,13 +100,15 @@ pub async fn create_connection(
             let multiaddr = match route_to_multiaddr(&r) {
                 Some(addr) => addr,
                 None => {
-                    eprintln!("Couldn't convert given address into `MultiAddr`");
-                    std::process::exit(exitcode::SOFTWARE);
+                    return Err(anyhow!("Couldn't convert given address into `MultiAddr`"));
                 }
             };
 
             let from = cmd.node_opts.from;
-            let to = cmd.address.parse::<SocketAddrV4>().unwrap();
+            let to = cmd.address;
+            let to_multiaddr = addr_to_multiaddr(Address::new(TCP, to.clone()))
+                .map(|ma| ma.to_string())
+                .unwrap_or_default();
 
             // if output format is json, write json to stdout.
             match opts.global_args.output_format {
@@ -91,14 +116,13 @@ pub async fn create_connection(
                     if opts.global_args.no_color {
                         eprintln!("\n  Created TCP Connection:");
                         eprintln!("  • From: /node/{}", from);
-                        eprintln!("  •   To: {} (/ip4/{}/tcp/{})", to, to.ip(), to.port());
+                        eprintln!("  •   To: {} ({})", to, to_multiaddr);
                     } else {
                         eprintln!("\n  Created TCP Connection:");
                         eprintln!("{}", format!("  • From: /node/{}", from).light_magenta());
                         eprintln!(
                             "{}",
-                            format!("  •   To: {} (/ip4/{}/tcp/{})", to, to.ip(), to.port())
-                                .light_magenta()
+                            format!("  •   To: {} ({})", to, to_multiaddr).light_magenta()
                         );
                     }
                 }
@@ -109,12 +133,28 @@ pub async fn create_connection(
             }
         }
         _ => {
-            eprintln!(
-                "An error occurred while creating the tcp connection: {}",
-                payload
-            );
-            std::process::exit(exitcode::CANTCREAT);
+            return Err(Error::new(
+                exitcode::API_ERROR,
+                anyhow!("An error occurred while creating the tcp connection: {payload}"),
+            )
+            .into());
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_address;
+
+    #[test]
+    fn parse_connection_addresses() {
+        assert!(parse_address("127.0.0.1:4000").is_ok());
+        assert!(parse_address("[::1]:4000").is_ok());
+        assert!(parse_address("localhost:4000").is_ok());
+        assert!(parse_address("::1:4000").is_err());
+        assert!(parse_address("[::1]").is_err());
+        assert!(parse_address("localhost").is_err());
+        assert!(parse_address(":4000").is_err());
+    }
+}
@@ -1,9 +1,11 @@
+use anyhow::{anyhow, Context as _};
 use clap::Args;
 use ockam::{Context, Route};
 use ockam_api::nodes::NODEMANAGER_ADDR;
 use ockam_core::api::{Response, Status};
 
-use crate::util::extract_address_value;
+use crate::error::{ConnectionError, Error};
+use crate::util::{extract_address_value, node_rpc, Rpc};
 use crate::{
     node::NodeOpts,
     util::{api, connect_to, exitcode},
@@ -21,36 +23,54 @@ pub struct DeleteCommand {
     /// Force this operation: delete the API transport if requested
     #[arg(long)]
     pub force: bool,
+
+    /// Close the connection with the given address, as shown by `list --all`,
+    /// instead of a transport ID
+    #[arg(long)]
+    pub address: bool,
 }
 
 impl DeleteCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        if self.address {
+            return node_rpc(close_connection, (options, self));
+        }
         let cfg = &options.config;
         let node =
             extract_address_value(&self.node_opts.api_node).unwrap_or_else(|_| "".to_string());
-        let port = cfg.get_node_port(&node).unwrap();
-        connect_to(port, self, delete_connection);
+        let port = cfg.get_node_port(&node)?;
+        connect_to(port, self, delete_connection)
     }
 }
 
+async fn close_connection(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, DeleteCommand),
+) -> crate::Result<()> {
+    let mut rpc = Rpc::background(&ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request(api::close_tcp_transport_connection(&cmd.id))
+        .await?;
+    rpc.is_ok()?;
+    if !opts.global_args.quiet {
+        println!("Tcp connection `{}` successfully closed", cmd.id);
+    }
+    Ok(())
+}
+
 pub async fn delete_connection(
     ctx: Context,
     cmd: DeleteCommand,
     mut base_route: Route,
 ) -> anyhow::Result<()> {
-    let resp: Vec<u8> = match ctx
+    let resp: Vec<u8> = ctx
         .send_and_receive(
             base_route.modify().append(NODEMANAGER_ADDR),
             api::delete_tcp_connection(&cmd)?,
         )
         .await
-    {
-        Ok(sr_msg) => sr_msg,
-        Err(e) => {
-            eprintln!("Wasn't able to send or receive `Message`: {}", e);
-            std::process::exit(exitcode::IOERR);
-        }
-    };
+        .context(ConnectionError::new(
+            "Wasn't able to send or receive `Message`",
+        ))?;
     let r: Response = api::parse_response(&resp)?;
 
     match r.status() {
@@ -58,8 +78,11 @@ pub async fn delete_connection(
         _ => {
             eprintln!("Failed to delete tcp connection");
             if !cmd.force {
-                eprintln!("You may have to provide --force to delete the API transport");
-                std::process::exit(exitcode::UNAVAILABLE);
+                return Err(Error::new(
+                    exitcode::API_ERROR,
+                    anyhow!("You may have to provide --force to delete the API transport"),
+                )
+                .into());
             }
         }
     }
@@ -1,11 +1,13 @@
-use crate::node::NodeOpts;
-use crate::util::{api, connect_to, exitcode, extract_address_value};
+use crate::error::ConnectionError;
+use crate::node::{format_uptime, NodeOpts};
+use crate::util::{api, connect_to, exitcode, extract_address_value, node_rpc, Rpc};
 use crate::CommandGlobalOpts;
+use anyhow::{anyhow, Context as _};
 use clap::Args;
 use cli_table::{print_stdout, Cell, Style, Table};
 use ockam::{Context, Route};
 use ockam_api::nodes::{
-    models::transport::{TransportList, TransportStatus},
+    models::transport::{TcpConnectionList, TransportList, TransportStatus},
     NODEMANAGER_ADDR,
 };
 
@@ -13,33 +15,73 @@ use ockam_api::nodes::{
 pub struct ListCommand {
     #[command(flatten)]
     node_opts: NodeOpts,
+
+    /// List every open connection of the node, including incoming ones,
+    /// with their age and traffic
+    #[arg(long)]
+    all: bool,
 }
 
 impl ListCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        if self.all {
+            return node_rpc(list_all_connections, (options, self));
+        }
         let cfg = &options.config;
         let node =
             extract_address_value(&self.node_opts.api_node).unwrap_or_else(|_| "".to_string());
-        let port = cfg.get_node_port(&node).unwrap();
+        let port = cfg.get_node_port(&node)?;
 
-        connect_to(port, (), list_connections);
+        connect_to(port, (), list_connections)
     }
 }
 
+async fn list_all_connections(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ListCommand),
+) -> crate::Result<()> {
+    let mut rpc = Rpc::background(&ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request(api::list_tcp_transport_connections()).await?;
+    let res = rpc.parse_response::<TcpConnectionList>()?;
+
+    let table = res
+        .list
+        .iter()
+        .map(|c| {
+            let mode = if c.incoming { "Incoming" } else { "Outgoing" };
+            vec![
+                c.address.cell(),
+                c.peer.cell(),
+                mode.cell(),
+                format_uptime(c.age).cell(),
+                c.bytes_sent.cell(),
+                c.bytes_received.cell(),
+            ]
+        })
+        .collect::<Vec<_>>()
+        .table()
+        .title(vec![
+            "Address".cell().bold(true),
+            "Peer".cell().bold(true),
+            "Mode".cell().bold(true),
+            "Age".cell().bold(true),
+            "Bytes sent".cell().bold(true),
+            "Bytes received".cell().bold(true),
+        ]);
+    print_stdout(table)?;
+    Ok(())
+}
+
 pub async fn list_connections(ctx: Context, _: (), mut base_route: Route) -> anyhow::Result<()> {
-    let resp: Vec<u8> = match ctx
+    let resp: Vec<u8> = ctx
         .send_and_receive(
             base_route.modify().append(NODEMANAGER_ADDR),
             api::list_tcp_connections()?,
         )
         .await
-    {
-        Ok(sr_msg) => sr_msg,
-        Err(e) => {
-            eprintln!("Wasn't able to send or receive `Message`: {}", e);
-            std::process::exit(exitcode::IOERR);
-        }
-    };
+        .context(ConnectionError::new(
+            "Wasn't able to send or receive `Message`",
+        ))?;
 
     let TransportList { list, .. } = api::parse_tcp_list(&resp)?;
 
@@ -68,10 +110,9 @@ pub async fn list_connections(ctx: Context, _: (), mut base_route: Route) -> any
             "Address bind".cell().bold(true),
         ]);
 
-    if let Err(e) = print_stdout(table) {
-        eprintln!("failed to print node status: {}", e);
-        std::process::exit(exitcode::IOERR);
-    }
+    print_stdout(table).map_err(|e| {
+        crate::Error::new(exitcode::IOERR, anyhow!("failed to print node status: {e}"))
+    })?;
 
     Ok(())
 }
@@ -24,7 +24,7 @@ pub enum TcpConnectionSubCommand {
 }
 
 impl TcpConnectionCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             TcpConnectionSubCommand::Create(c) => c.run(options),
             TcpConnectionSubCommand::Delete(c) => c.run(options),
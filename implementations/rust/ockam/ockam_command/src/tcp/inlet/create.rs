@@ -1,3 +1,8 @@
+use crate::tcp::compression::CompressionArg;
+use crate::tcp::policy::PolicyOpts;
+use crate::tcp::rate_limit::RateLimitOpts;
+use crate::tcp::tls::InletTlsOpts;
+use crate::util::api::IdempotencyOpts;
 use crate::util::{bind_to_port_check, exitcode, extract_address_value, node_rpc, RpcBuilder};
 use crate::Result;
 use crate::{help, CommandGlobalOpts};
@@ -57,6 +62,28 @@ pub struct CreateCommand {
     /// Enable credentials authorization
     #[arg(long, short, display_order = 802)]
     check_credential: bool,
+
+    #[command(flatten)]
+    policy_opts: PolicyOpts,
+
+    #[command(flatten)]
+    rate_limit_opts: RateLimitOpts,
+
+    #[command(flatten)]
+    tls_opts: InletTlsOpts,
+
+    /// Compress the payloads with one of these algorithms, in order of preference,
+    /// if the outlet supports it
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "ALGORITHMS",
+        display_order = 812
+    )]
+    compression: Vec<CompressionArg>,
+
+    #[command(flatten)]
+    idempotency_opts: IdempotencyOpts,
 }
 
 impl CreateCommand {
@@ -83,13 +110,13 @@ impl CreateCommand {
 
         // Check if the port is used by some other services or process
         if !bind_to_port_check(&self.from) {
-            eprintln!("Another process is listening on the provided port!");
-            std::process::exit(exitcode::IOERR);
+            return Err(crate::Error::new(
+                exitcode::IOERR,
+                anyhow!("Another process is listening on the provided port!"),
+            ));
         }
 
-        node_rpc(rpc, (options, self));
-
-        Ok(())
+        node_rpc(rpc, (options, self))
     }
 }
 
@@ -98,7 +125,7 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> R
     let node = extract_address_value(&cmd.at)?;
 
     let req = {
-        let payload = if cmd.to.matches(0, &[Project::CODE.into()]) {
+        let mut payload = if cmd.to.matches(0, &[Project::CODE.into()]) {
             if cmd.authorized.is_some() {
                 return Err(anyhow!("--authorized can not be used with project addresses").into());
             }
@@ -106,7 +133,20 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> R
         } else {
             CreateInlet::to_node(cmd.from, cmd.to, cmd.check_credential, cmd.authorized)
         };
-        Request::post("/node/inlet").body(payload)
+        if let Some(policy) = cmd.policy_opts.to_policy()? {
+            payload.set_policy(policy)
+        }
+        if let Some(rate_limit) = cmd.rate_limit_opts.to_rate_limit() {
+            payload.set_rate_limit(rate_limit)
+        }
+        if let Some(tls) = cmd.tls_opts.to_tls()? {
+            payload.set_tls(tls)
+        }
+        if !cmd.compression.is_empty() {
+            payload.set_compression(CompressionArg::to_compression(&cmd.compression))
+        }
+        cmd.idempotency_opts
+            .apply(Request::post("/node/inlet").body(payload))
     };
 
     let mut rpc = RpcBuilder::new(&ctx, &opts, &node).tcp(&tcp)?.build();
@@ -17,9 +17,9 @@ pub enum TcpInletSubCommand {
 }
 
 impl TcpInletCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
-            TcpInletSubCommand::Create(c) => c.run(options).unwrap(),
+            TcpInletSubCommand::Create(c) => c.run(options),
         }
     }
 }
@@ -1,8 +1,10 @@
+use crate::error::{ConnectionError, Error};
 use crate::util::{bind_to_port_check, extract_address_value};
 use crate::{
     util::{api, connect_to, exitcode},
     CommandGlobalOpts,
 };
+use anyhow::{anyhow, Context as _};
 use clap::Args;
 use ockam::{Context, Route, TCP};
 use ockam_api::{
@@ -29,26 +31,23 @@ pub struct TCPListenerNodeOpts {
 }
 
 impl CreateCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         let cfg = &options.config;
         let node = extract_address_value(&self.node_opts.at).unwrap_or_else(|_| "".to_string());
-        let port = cfg.get_node_port(&node).unwrap();
+        let port = cfg.get_node_port(&node)?;
 
-        let input_addr = match std::net::SocketAddr::from_str(&self.address) {
-            Ok(value) => value,
-            _ => {
-                eprintln!("Invalid Input Address");
-                std::process::exit(exitcode::IOERR);
-            }
-        };
+        let input_addr = std::net::SocketAddr::from_str(&self.address)
+            .map_err(|_| Error::new(exitcode::IOERR, anyhow!("Invalid Input Address")))?;
 
         // Check if the port is used by some other services or process
         if !bind_to_port_check(&input_addr) {
-            eprintln!("Another process is listening on the provided port!");
-            std::process::exit(exitcode::IOERR);
+            return Err(Error::new(
+                exitcode::IOERR,
+                anyhow!("Another process is listening on the provided port!"),
+            ));
         }
 
-        connect_to(port, self, create_listener);
+        connect_to(port, self, create_listener)
     }
 }
 
@@ -57,19 +56,15 @@ pub async fn create_listener(
     cmd: CreateCommand,
     mut base_route: Route,
 ) -> anyhow::Result<()> {
-    let resp: Vec<u8> = match ctx
+    let resp: Vec<u8> = ctx
         .send_and_receive(
             base_route.modify().append(NODEMANAGER_ADDR),
             api::create_tcp_listener(&cmd)?,
         )
         .await
-    {
-        Ok(sr_msg) => sr_msg,
-        Err(e) => {
-            eprintln!("Wasn't able to send or receive `Message`: {}", e);
-            std::process::exit(exitcode::IOERR);
-        }
-    };
+        .context(ConnectionError::new(
+            "Wasn't able to send or receive `Message`",
+        ))?;
 
     let (response, TransportStatus { payload, .. }) = api::parse_transport_status(&resp)?;
 
@@ -83,8 +78,7 @@ pub async fn create_listener(
             let multiaddr = match route_to_multiaddr(&r) {
                 Some(addr) => addr,
                 None => {
-                    eprintln!("Couldn't convert given address into `MultiAddr`");
-                    std::process::exit(exitcode::SOFTWARE);
+                    return Err(anyhow!("Couldn't convert given address into `MultiAddr`"));
                 }
             };
 
@@ -94,11 +88,11 @@ pub async fn create_listener(
             )
         }
         _ => {
-            eprintln!(
-                "An error occurred while creating the tcp listener: {}",
-                payload
-            );
-            std::process::exit(exitcode::CANTCREAT);
+            return Err(Error::new(
+                exitcode::API_ERROR,
+                anyhow!("An error occurred while creating the tcp listener: {payload}"),
+            )
+            .into());
         }
     }
     Ok(())
@@ -1,9 +1,9 @@
 use clap::Args;
 use cli_table::{print_stdout, Cell, Style, Table};
 use ockam::Context;
-use ockam_api::nodes::models::transport::{TransportList, TransportStatus};
+use ockam_api::nodes::models::transport::{TcpListenerList, TransportList, TransportStatus};
 
-use crate::node::NodeOpts;
+use crate::node::{format_uptime, NodeOpts};
 use crate::util::{api, node_rpc, Rpc};
 use crate::CommandGlobalOpts;
 
@@ -11,11 +11,15 @@ use crate::CommandGlobalOpts;
 pub struct ListCommand {
     #[command(flatten)]
     node_opts: NodeOpts,
+
+    /// List every listener of the node, with the number of connections it accepted
+    #[arg(long)]
+    all: bool,
 }
 
 impl ListCommand {
-    pub fn run(self, opts: CommandGlobalOpts) {
-        node_rpc(rpc, (opts, self));
+    pub fn run(self, opts: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (opts, self))
     }
 }
 
@@ -29,6 +33,31 @@ async fn run_impl(
     cmd: ListCommand,
 ) -> crate::Result<()> {
     let mut rpc = Rpc::background(ctx, &opts, &cmd.node_opts.api_node)?;
+    if cmd.all {
+        rpc.request(api::list_tcp_transport_listeners()).await?;
+        let res = rpc.parse_response::<TcpListenerList>()?;
+        let table = res
+            .list
+            .iter()
+            .map(|l| {
+                vec![
+                    l.address.cell(),
+                    l.bind.cell(),
+                    format_uptime(l.age).cell(),
+                    l.accepted.cell(),
+                ]
+            })
+            .collect::<Vec<_>>()
+            .table()
+            .title(vec![
+                "Address".cell().bold(true),
+                "Address bind".cell().bold(true),
+                "Age".cell().bold(true),
+                "Accepted".cell().bold(true),
+            ]);
+        print_stdout(table)?;
+        return Ok(());
+    }
     rpc.request(api::list_tcp_listeners()).await?;
     let res = rpc.parse_response::<TransportList>()?;
 
@@ -29,7 +29,7 @@ pub enum TcpListenerSubCommand {
 }
 
 impl TcpListenerCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             TcpListenerSubCommand::Create(c) => c.run(options),
             TcpListenerSubCommand::Delete(c) => c.run(options),
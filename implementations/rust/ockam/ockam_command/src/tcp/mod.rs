@@ -1,4 +1,8 @@
+pub(crate) mod compression;
 pub(crate) mod connection;
 pub(crate) mod inlet;
 pub(crate) mod listener;
 pub(crate) mod outlet;
+pub(crate) mod policy;
+pub(crate) mod rate_limit;
+pub(crate) mod tls;
@@ -1,3 +1,8 @@
+use crate::tcp::compression::CompressionArg;
+use crate::tcp::policy::PolicyOpts;
+use crate::tcp::rate_limit::RateLimitOpts;
+use crate::tcp::tls::OutletTlsOpts;
+use crate::util::api::IdempotencyOpts;
 use crate::util::{extract_address_value, node_rpc, Rpc};
 use crate::{help, CommandGlobalOpts};
 use clap::Args;
@@ -52,10 +57,31 @@ pub struct CreateCommand {
     /// Enable credentials authorization
     #[arg(long, short, display_order = 802)]
     pub check_credential: bool,
+
+    #[command(flatten)]
+    policy_opts: PolicyOpts,
+
+    #[command(flatten)]
+    rate_limit_opts: RateLimitOpts,
+
+    #[command(flatten)]
+    tls_opts: OutletTlsOpts,
+
+    /// Only accept these compression algorithms from inlets, defaults to all of them
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "ALGORITHMS",
+        display_order = 812
+    )]
+    compression: Option<Vec<CompressionArg>>,
+
+    #[command(flatten)]
+    idempotency_opts: IdempotencyOpts,
 }
 
 impl CreateCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         node_rpc(run_impl, (options, self))
     }
 }
@@ -87,8 +113,21 @@ fn make_api_request<'a>(cmd: CreateCommand) -> crate::Result<RequestBuilder<'a,
     let tcp_addr = cmd.to.to_string();
     let worker_addr = cmd.from;
     let alias = (None::<String>).as_ref().map(|x| x.as_str().into());
-    let payload = CreateOutlet::new(tcp_addr, worker_addr, alias, cmd.check_credential);
+    let mut payload = CreateOutlet::new(tcp_addr, worker_addr, alias, cmd.check_credential);
+    if let Some(policy) = cmd.policy_opts.to_policy()? {
+        payload.set_policy(policy)
+    }
+    if let Some(rate_limit) = cmd.rate_limit_opts.to_rate_limit() {
+        payload.set_rate_limit(rate_limit)
+    }
+    if let Some(tls) = cmd.tls_opts.to_tls()? {
+        payload.set_tls(tls)
+    }
+    if let Some(compression) = &cmd.compression {
+        payload.set_compression(CompressionArg::to_compression(compression))
+    }
 
     let request = Request::post("/node/outlet").body(payload);
+    let request = cmd.idempotency_opts.apply(request);
     Ok(request)
 }
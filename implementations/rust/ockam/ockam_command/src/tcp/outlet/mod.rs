@@ -17,7 +17,7 @@ pub enum TcpOutletSubCommand {
 }
 
 impl TcpOutletCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             TcpOutletSubCommand::Create(c) => c.run(options),
         }
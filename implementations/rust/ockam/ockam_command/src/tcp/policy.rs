@@ -0,0 +1,47 @@
+use anyhow::anyhow;
+use clap::Args;
+use ockam::abac::{eq, string, Conditional};
+use ockam_api::nodes::models::portal::PortalPolicy;
+
+use crate::util::parse_attribute;
+use crate::Result;
+
+/// Options to guard a tcp inlet or outlet with an ABAC policy
+#[derive(Clone, Debug, Args)]
+pub struct PolicyOpts {
+    /// Only allow identities holding this credential attribute (repeatable)
+    #[arg(long = "allow", value_name = "KEY=VALUE", value_parser = parse_attribute, conflicts_with = "policy", display_order = 803)]
+    allow: Vec<(String, String)>,
+
+    /// ABAC policy the remote identity has to satisfy, as a JSON encoded condition
+    #[arg(long, value_name = "JSON", display_order = 804)]
+    policy: Option<String>,
+
+    /// Resource the policy is set for, defaults to `/inlet/<alias>` or `/outlet/<alias>`
+    #[arg(long, value_name = "RESOURCE", display_order = 805)]
+    policy_resource: Option<String>,
+}
+
+impl PolicyOpts {
+    pub fn to_policy(&self) -> Result<Option<PortalPolicy<'static>>> {
+        let condition = if let Some(json) = &self.policy {
+            serde_json::from_str::<Conditional>(json)
+                .map_err(|e| anyhow!("invalid policy: {e}"))?;
+            json.clone()
+        } else if !self.allow.is_empty() {
+            let all = self
+                .allow
+                .iter()
+                .map(|(k, v)| eq(k.as_str(), string(v.as_str())))
+                .collect();
+            serde_json::to_string(&Conditional::And(all))?
+        } else {
+            return Ok(None);
+        };
+        let mut policy = PortalPolicy::new(condition);
+        if let Some(r) = &self.policy_resource {
+            policy = policy.with_resource(r.clone());
+        }
+        Ok(Some(policy))
+    }
+}
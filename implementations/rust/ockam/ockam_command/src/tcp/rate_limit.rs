@@ -0,0 +1,26 @@
+use clap::Args;
+use ockam_api::nodes::models::portal::PortalRateLimit;
+
+/// Options to cap the bandwidth of a tcp inlet or outlet
+#[derive(Clone, Debug, Args)]
+pub struct RateLimitOpts {
+    /// Maximum average bandwidth, in bytes per second, shared by all the connections
+    #[arg(long, value_name = "BYTES_PER_SEC", display_order = 806)]
+    rate_limit: Option<u64>,
+
+    /// Maximum number of bytes sent at once, defaults to the rate limit
+    #[arg(
+        long,
+        value_name = "BYTES",
+        requires = "rate_limit",
+        display_order = 807
+    )]
+    burst: Option<u64>,
+}
+
+impl RateLimitOpts {
+    pub fn to_rate_limit(&self) -> Option<PortalRateLimit> {
+        self.rate_limit
+            .map(|bytes_per_sec| PortalRateLimit::new(bytes_per_sec, self.burst))
+    }
+}
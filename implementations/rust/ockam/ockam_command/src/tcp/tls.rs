@@ -0,0 +1,93 @@
+use crate::Result;
+use clap::Args;
+use ockam_api::nodes::models::portal::{InletTlsConfig, OutletTlsConfig};
+use ockam_core::CowStr;
+use std::path::{Path, PathBuf};
+
+/// Options to terminate TLS on a tcp inlet
+#[derive(Clone, Debug, Args)]
+pub struct InletTlsOpts {
+    /// PEM certificate chain presented to the tcp clients, enables TLS termination
+    #[arg(long, value_name = "PATH", requires = "tls_key", display_order = 808)]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM private key of the certificate
+    #[arg(long, value_name = "PATH", requires = "tls_cert", display_order = 809)]
+    tls_key: Option<PathBuf>,
+
+    /// PEM CA certificate the tcp clients must present a certificate from (mutual TLS)
+    #[arg(long, value_name = "PATH", requires = "tls_cert", display_order = 810)]
+    tls_client_ca: Option<PathBuf>,
+}
+
+impl InletTlsOpts {
+    pub fn to_tls(&self) -> Result<Option<InletTlsConfig<'static>>> {
+        let (cert, key) = match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => (cert, key),
+            _ => return Ok(None),
+        };
+        Ok(Some(InletTlsConfig::new(
+            node_path(cert)?,
+            node_path(key)?,
+            self.tls_client_ca.as_deref().map(node_path).transpose()?,
+        )))
+    }
+}
+
+/// Options to originate TLS on a tcp outlet
+#[derive(Clone, Debug, Args)]
+pub struct OutletTlsOpts {
+    /// Name the target certificate must be valid for, enables TLS origination
+    #[arg(long, value_name = "NAME", display_order = 808)]
+    tls_server_name: Option<String>,
+
+    /// PEM CA certificate of the target, defaults to the webpki roots
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "tls_server_name",
+        display_order = 809
+    )]
+    tls_ca: Option<PathBuf>,
+
+    /// PEM certificate chain presented to the target (mutual TLS)
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires_all = ["tls_server_name", "tls_client_key"],
+        display_order = 810
+    )]
+    tls_client_cert: Option<PathBuf>,
+
+    /// PEM private key of the client certificate
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "tls_client_cert",
+        display_order = 811
+    )]
+    tls_client_key: Option<PathBuf>,
+}
+
+impl OutletTlsOpts {
+    pub fn to_tls(&self) -> Result<Option<OutletTlsConfig<'static>>> {
+        let server_name = match &self.tls_server_name {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        let mut tls = OutletTlsConfig::new(
+            Some(server_name.clone().into()),
+            self.tls_ca.as_deref().map(node_path).transpose()?,
+        );
+        if let (Some(cert), Some(key)) = (&self.tls_client_cert, &self.tls_client_key) {
+            tls = tls.with_client_certificate(node_path(cert)?, node_path(key)?);
+        }
+        Ok(Some(tls))
+    }
+}
+
+/// The node runs in the background, with its own working directory
+fn node_path(path: &Path) -> Result<CowStr<'static>> {
+    let path = std::fs::canonicalize(path)?;
+    Ok(path.to_string_lossy().into_owned().into())
+}
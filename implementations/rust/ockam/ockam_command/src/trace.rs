@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use clap::Args;
+
+use ockam::{Context, TcpTransport};
+use ockam_api::clean_multiaddr;
+use ockam_api::nodes::models::secure_channel::CredentialExchangeMode;
+use ockam_api::nodes::models::trace::{TraceReport, TraceRequest};
+use ockam_core::api::Request;
+use ockam_multiaddr::MultiAddr;
+
+use crate::node::util::{delete_embedded_node, start_embedded_node};
+use crate::util::api::CloudOpts;
+use crate::util::{extract_address_value, node_rpc, RpcBuilder};
+use crate::Result;
+use crate::{help, CommandGlobalOpts};
+
+const HELP_DETAIL: &str = "\
+About:
+    Sends a traced probe along the route to the echo service at its end,
+    which sends it back. Every node forwarding the probe records its address
+    and the time, which gives the path taken by messages and where they
+    spend their time.
+
+    Times are given by the clocks of the nodes, hops on other hosts are only
+    as accurate as the synchronization of their clocks. Hops within secure
+    channels are not recorded, and nodes without tracing support, such as
+    Elixir nodes, forward the probe without recording a hop.
+
+Examples:
+
+```sh
+    # Trace the route to node n2
+    $ ockam trace /node/n2
+
+    # Trace the route from n1 to n2
+    $ ockam trace --from /node/n1 /node/n2
+```
+";
+
+/// Print the path and per-hop latencies of a route
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, after_long_help = help::template(HELP_DETAIL))]
+pub struct TraceCommand {
+    /// The route to trace, ending at a node running an echo service
+    #[arg(value_name = "ROUTE")]
+    to: MultiAddr,
+
+    /// The node to trace from
+    #[arg(short, long, value_name = "NODE")]
+    from: Option<String>,
+
+    /// How long to wait for the probe to come back, in seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = 10)]
+    timeout: u64,
+
+    #[command(flatten)]
+    cloud_opts: CloudOpts,
+}
+
+impl TraceCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(rpc, (options, self))
+    }
+}
+
+async fn rpc(mut ctx: Context, (opts, cmd): (CommandGlobalOpts, TraceCommand)) -> Result<()> {
+    async fn go(ctx: &mut Context, opts: &CommandGlobalOpts, cmd: TraceCommand) -> Result<()> {
+        let (to, meta) = clean_multiaddr(&cmd.to, &opts.config.lookup())
+            .context("Argument 'ROUTE' is invalid")?;
+
+        let (api_node, tcp) = if let Some(node) = &cmd.from {
+            let api_node = extract_address_value(node)?;
+            let tcp = TcpTransport::create(ctx).await?;
+            (api_node, Some(tcp))
+        } else {
+            let api_node = start_embedded_node(ctx, &opts.config).await?;
+            (api_node, None)
+        };
+
+        let projects_sc = crate::project::util::get_projects_secure_channels_from_config_lookup(
+            ctx,
+            opts,
+            &meta,
+            &cmd.cloud_opts.route(),
+            &api_node,
+            tcp.as_ref(),
+            CredentialExchangeMode::None,
+        )
+        .await?;
+        let to = crate::project::util::clean_projects_multiaddr(to, projects_sc)?;
+
+        let timeout = Duration::from_secs(cmd.timeout);
+        let req = TraceRequest::new(to.to_string(), timeout.as_millis() as u64);
+        let mut rpc = RpcBuilder::new(ctx, opts, &api_node)
+            .tcp(tcp.as_ref())?
+            .build();
+        rpc.request_with_timeout(Request::post("v0/trace").body(req), timeout * 2)
+            .await?;
+        rpc.parse_and_print_response::<TraceReport>()?;
+
+        if cmd.from.is_none() {
+            delete_embedded_node(&opts.config, rpc.node_name()).await;
+        }
+
+        Ok(())
+    }
+    go(&mut ctx, &opts, cmd).await
+}
@@ -1,6 +1,7 @@
 //! API shim to make it nicer to interact with the ockam messaging API
 
 use regex::Regex;
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::str::FromStr;
 
@@ -9,19 +10,28 @@ use clap::Args;
 // TODO: maybe we can remove this cross-dependency inside the CLI?
 use minicbor::Decoder;
 use ockam_api::nodes::models::services::{
-    StartAuthenticatedServiceRequest, StartAuthenticatorRequest, StartCredentialsService,
-    StartIdentityServiceRequest, StartVaultServiceRequest, StartVerifierService,
+    ServiceOptions, StartAuthenticatedServiceRequest, StartAuthenticatorRequest,
+    StartCredentialsService, StartDeadLetterService, StartEchoerServiceRequest,
+    StartFileTransferService, StartForwardingService, StartIdentityServiceRequest,
+    StartKafkaInletRequest, StartKafkaOutletRequest, StartPerfServiceRequest,
+    StartProxyServiceRequest, StartPubSubService, StartRevocationService, StartSignerService,
+    StartVaultServiceRequest, StartVerifierService, StopServiceRequest,
 };
 use tracing::trace;
 
 use ockam::identity::IdentityIdentifier;
 use ockam::Result;
-use ockam_api::cloud::{BareCloudRequestWrapper, CloudRequestWrapper};
-use ockam_api::nodes::models::secure_channel::CredentialExchangeMode;
+use ockam_api::cloud::{path_segment, BareCloudRequestWrapper, CloudRequestWrapper, RetryPolicy};
+use ockam_api::nodes::models::config::{ApplySetup, NodeSetup};
+use ockam_api::nodes::models::file_transfer::SendFile;
+use ockam_api::nodes::models::logging::LogFilterBody;
+use ockam_api::nodes::models::policy::{PolicyTarget, SetPolicy};
+use ockam_api::nodes::models::route::ResolveRoute;
+use ockam_api::nodes::models::secure_channel::{CredentialExchangeMode, KeyExchange};
 use ockam_api::nodes::*;
 use ockam_core::api::RequestBuilder;
 use ockam_core::api::{Request, Response};
-use ockam_core::Address;
+use ockam_core::{Address, CowStr};
 use ockam_multiaddr::MultiAddr;
 
 use crate::util::DEFAULT_CONTROLLER_ADDRESS;
@@ -35,6 +45,39 @@ pub(crate) fn query_status() -> Result<Vec<u8>> {
     Ok(buf)
 }
 
+/// Construct a request to check the health of a node
+pub(crate) fn node_health() -> RequestBuilder<'static, ()> {
+    Request::get("/node/health")
+}
+
+/// Construct a request to query everything running on a node
+pub(crate) fn node_details() -> RequestBuilder<'static, ()> {
+    Request::get("/node/details")
+}
+
+/// Construct a request to query the setup a node applied
+pub(crate) fn node_config() -> RequestBuilder<'static, ()> {
+    Request::get("/node/config")
+}
+
+/// Construct a request to query the log filter of a node
+pub(crate) fn get_log_filter() -> RequestBuilder<'static, ()> {
+    Request::get("/node/logging")
+}
+
+/// Construct a request to change the log filter of a node
+pub(crate) fn set_log_filter(filter: &str) -> RequestBuilder<'_, LogFilterBody<'_>> {
+    Request::put("/node/logging").body(LogFilterBody::new(filter))
+}
+
+/// Construct a request to converge a node to a setup
+pub(crate) fn apply_node_config(
+    setup: NodeSetup,
+    dry_run: bool,
+) -> RequestBuilder<'static, ApplySetup> {
+    Request::post("/node/config").body(ApplySetup::new(setup, dry_run))
+}
+
 /// Construct a request to query node tcp connections
 pub(crate) fn list_tcp_connections() -> Result<Vec<u8>> {
     let mut buf = vec![];
@@ -66,6 +109,21 @@ pub(crate) fn create_tcp_connection(
     Ok(buf)
 }
 
+/// Construct a request to list every open connection of the node's tcp transport
+pub(crate) fn list_tcp_transport_connections() -> RequestBuilder<'static, ()> {
+    Request::get("/node/tcp/connections")
+}
+
+/// Construct a request to force-close a connection of the node's tcp transport
+pub(crate) fn close_tcp_transport_connection(address: &str) -> RequestBuilder<'static, ()> {
+    Request::delete(format!("/node/tcp/connections/{address}"))
+}
+
+/// Construct a request to list the listeners of the node's tcp transport
+pub(crate) fn list_tcp_transport_listeners() -> RequestBuilder<'static, ()> {
+    Request::get("/node/tcp/listeners")
+}
+
 /// Construct a request to create node tcp listener
 pub(crate) fn create_tcp_listener(cmd: &crate::tcp::listener::CreateCommand) -> Result<Vec<u8>> {
     let (tt, addr) = (
@@ -106,19 +164,62 @@ pub(crate) fn short_identity() -> RequestBuilder<'static, ()> {
     Request::post("/node/identity/actions/show/short")
 }
 
-/// Construct a request to print a list of services for the given node
+/// Construct a request builder to list the services of a node
 pub(crate) fn list_services() -> RequestBuilder<'static, ()> {
     Request::get("/node/services")
 }
 
-/// Construct a request to print a list of inlets for the given node
-pub(crate) fn list_inlets() -> RequestBuilder<'static, ()> {
-    Request::get("/node/inlet")
+/// Construct a request to resolve an address into a route
+pub(crate) fn resolve_route(
+    address: MultiAddr,
+    authorized: Option<IdentityIdentifier>,
+) -> RequestBuilder<'static, ResolveRoute> {
+    Request::post("/node/route/resolve").body(ResolveRoute::new(address, authorized))
+}
+
+/// Construct a request to set the policy of a resource, for the action of
+/// the target or else for the given actions
+pub(crate) fn set_policy(
+    target: PolicyTarget<'static>,
+    condition: String,
+    actions: Vec<String>,
+) -> RequestBuilder<'static, SetPolicy<'static>> {
+    let mut body = SetPolicy::new(target, condition);
+    if !actions.is_empty() {
+        body = body.with_actions(actions)
+    }
+    Request::post("/node/policy").body(body)
+}
+
+/// Construct a request to get the policy of a resource
+pub(crate) fn get_policy(
+    target: PolicyTarget<'static>,
+) -> RequestBuilder<'static, PolicyTarget<'static>> {
+    Request::get("/node/policy").body(target)
+}
+
+/// Construct a request to get the previous versions of the policy of a resource
+pub(crate) fn get_policy_history(
+    target: PolicyTarget<'static>,
+) -> RequestBuilder<'static, PolicyTarget<'static>> {
+    Request::get("/node/policy/history").body(target)
+}
+
+/// Construct a request to restore the previous version of the policy of a resource
+pub(crate) fn rollback_policy(
+    target: PolicyTarget<'static>,
+) -> RequestBuilder<'static, PolicyTarget<'static>> {
+    Request::post("/node/policy/rollback").body(target)
 }
 
-/// Construct a request to print a list of outlets for the given node
-pub(crate) fn list_outlets() -> RequestBuilder<'static, ()> {
-    Request::get("/node/outlet")
+/// Construct a request to show a forwarder of a node
+pub(crate) fn show_forwarder(remote_address: &str) -> RequestBuilder<'static, ()> {
+    Request::get(format!("/node/forwarder/{remote_address}"))
+}
+
+/// Construct a request for the dependency graph of the sessions of a node
+pub(crate) fn session_graph() -> RequestBuilder<'static, ()> {
+    Request::get("/node/sessions/graph")
 }
 
 /// Construct a request builder to list all secure channels on the given node
@@ -140,6 +241,25 @@ pub(crate) fn create_secure_channel(
     Request::post("/node/secure_channel").body(payload)
 }
 
+/// Construct a request to create a Secure Channel over the first of several
+/// routes to the same listener which succeeds
+pub(crate) fn create_secure_channel_to_any(
+    addr: &MultiAddr,
+    alternatives: &[MultiAddr],
+    authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+    credential_exchange_mode: CredentialExchangeMode,
+    key_exchange: KeyExchange,
+) -> RequestBuilder<'static, models::secure_channel::CreateSecureChannelRequest<'static>> {
+    let payload = models::secure_channel::CreateSecureChannelRequest::new(
+        addr,
+        authorized_identifiers,
+        credential_exchange_mode,
+    )
+    .with_alternatives(alternatives)
+    .with_key_exchange(key_exchange);
+    Request::post("/node/secure_channel").body(payload)
+}
+
 pub(crate) fn delete_secure_channel(
     addr: &Address,
 ) -> RequestBuilder<'static, models::secure_channel::DeleteSecureChannelRequest<'static>> {
@@ -158,11 +278,34 @@ pub(crate) fn show_secure_channel(
 pub(crate) fn create_secure_channel_listener(
     addr: &Address,
     authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+    max_channels: Option<u32>,
+    max_channels_per_identity: Option<u32>,
+    trusted_attributes: Vec<(String, String)>,
+    sync_members: Option<Option<u64>>,
+    key_exchange: KeyExchange,
 ) -> Result<Vec<u8>> {
-    let payload = models::secure_channel::CreateSecureChannelListenerRequest::new(
+    let mut payload = models::secure_channel::CreateSecureChannelListenerRequest::new(
         addr,
         authorized_identifiers,
-    );
+    )
+    .with_limits(max_channels, max_channels_per_identity)
+    .with_key_exchange(key_exchange);
+    if let Some(interval) = sync_members {
+        payload = payload.with_synced_members(interval)
+    }
+    if !trusted_attributes.is_empty() {
+        // Values given for the same attribute are alternatives
+        let mut attributes: BTreeMap<String, Vec<CowStr>> = BTreeMap::new();
+        for (k, v) in trusted_attributes {
+            attributes.entry(k).or_default().push(v.into());
+        }
+        payload = payload.with_trusted_attributes(
+            attributes
+                .into_iter()
+                .map(|(k, vs)| models::secure_channel::TrustedAttribute::new(k, vs))
+                .collect(),
+        );
+    }
 
     let mut buf = vec![];
     Request::post("/node/secure_channel_listener")
@@ -176,6 +319,32 @@ pub(crate) fn list_secure_channel_listener() -> RequestBuilder<'static, ()> {
     Request::get("/node/secure_channel_listener")
 }
 
+/// Construct a request to list the keys of a node's vault
+pub(crate) fn list_vault_keys() -> RequestBuilder<'static, ()> {
+    Request::get("/node/vault/keys")
+}
+
+/// Construct a request to show a key of a node's vault
+pub(crate) fn show_vault_key(key_id: &str) -> RequestBuilder<'static, ()> {
+    Request::get(format!("/node/vault/keys/{key_id}"))
+}
+
+/// Construct a request to export an encrypted backup of a node's vault
+pub(crate) fn export_vault(
+    password: &str,
+) -> RequestBuilder<'_, models::vault::ExportVaultRequest> {
+    Request::post("/node/vault/export").body(models::vault::ExportVaultRequest::new(password))
+}
+
+/// Construct a request to import a vault backup into a node's vault
+pub(crate) fn import_vault<'a>(
+    password: &'a str,
+    backup: models::vault::VaultBackup<'a>,
+) -> RequestBuilder<'a, models::vault::ImportVaultRequest<'a>> {
+    Request::post("/node/vault/import")
+        .body(models::vault::ImportVaultRequest::new(password, backup))
+}
+
 /// Construct a request to start a Vault Service
 pub(crate) fn start_vault_service(addr: &str) -> RequestBuilder<'static, StartVaultServiceRequest> {
     let payload = StartVaultServiceRequest::new(addr);
@@ -199,17 +368,139 @@ pub(crate) fn start_authenticated_service(
 }
 
 /// Construct a request to start a Verifier Service
-pub(crate) fn start_verifier_service(addr: &str) -> RequestBuilder<'static, StartVerifierService> {
-    let payload = StartVerifierService::new(addr);
+pub(crate) fn start_verifier_service<'a>(
+    addr: &'a str,
+    options: ServiceOptions<'a>,
+) -> RequestBuilder<'static, StartVerifierService<'a>> {
+    let payload = StartVerifierService::new(addr).with_options(options);
     Request::post("/node/services/verifier").body(payload)
 }
 
+/// Construct a request to start a Forwarding Service
+pub(crate) fn start_forwarding_service<'a>(
+    addr: &'a str,
+    options: ServiceOptions<'a>,
+) -> RequestBuilder<'static, StartForwardingService<'a>> {
+    let payload = StartForwardingService::new(addr).with_options(options);
+    Request::post("/node/services/forwarding").body(payload)
+}
+
+/// Construct a request to start a Revocation Service
+pub(crate) fn start_revocation_service<'a>(
+    addr: &'a str,
+    options: ServiceOptions<'a>,
+) -> RequestBuilder<'static, StartRevocationService<'a>> {
+    let payload = StartRevocationService::new(addr).with_options(options);
+    Request::post("/node/services/revocation").body(payload)
+}
+
+/// Construct a request to start a Signer Service
+pub(crate) fn start_signer_service<'a>(
+    addr: &'a str,
+    authorized: Vec<IdentityIdentifier>,
+    options: ServiceOptions<'a>,
+) -> RequestBuilder<'static, StartSignerService<'a>> {
+    let payload = StartSignerService::new(addr, authorized).with_options(options);
+    Request::post("/node/services/signer").body(payload)
+}
+
+/// Construct a request to start a File Transfer Service
+pub(crate) fn start_file_transfer_service(
+    payload: StartFileTransferService<'_>,
+) -> RequestBuilder<'static, StartFileTransferService<'_>> {
+    Request::post("/node/services/file_transfer").body(payload)
+}
+
+/// Construct a request to start a Pub/Sub Service
+pub(crate) fn start_pubsub_service<'a>(
+    addr: &'a str,
+    retain: Option<u64>,
+    persist: bool,
+    options: ServiceOptions<'a>,
+) -> RequestBuilder<'static, StartPubSubService<'a>> {
+    let mut payload = StartPubSubService::new(addr)
+        .with_persist(persist)
+        .with_options(options);
+    if let Some(n) = retain {
+        payload = payload.with_retain(n)
+    }
+    Request::post("/node/services/pubsub").body(payload)
+}
+
+/// Construct a request to start an Echo Service
+pub(crate) fn start_echoer_service<'a>(
+    addr: &'a str,
+    max_payload: Option<u64>,
+    rate_limit: Option<u32>,
+    total_rate_limit: Option<u32>,
+    authenticated: bool,
+    options: ServiceOptions<'a>,
+) -> RequestBuilder<'static, StartEchoerServiceRequest<'a>> {
+    let mut payload = StartEchoerServiceRequest::new(addr)
+        .with_authenticated(authenticated)
+        .with_options(options);
+    if let Some(n) = max_payload {
+        payload = payload.with_max_payload(n)
+    }
+    if let Some(n) = rate_limit {
+        payload = payload.with_rate_limit(n)
+    }
+    if let Some(n) = total_rate_limit {
+        payload = payload.with_total_rate_limit(n)
+    }
+    Request::post("/node/services/echo").body(payload)
+}
+
+/// Construct a request to start a Perf Service
+pub(crate) fn start_perf_service<'a>(
+    addr: &'a str,
+    options: ServiceOptions<'a>,
+) -> RequestBuilder<'static, StartPerfServiceRequest<'a>> {
+    let payload = StartPerfServiceRequest::new(addr).with_options(options);
+    Request::post("/node/services/perf").body(payload)
+}
+
+/// Construct a request to start the Dead Letter Service
+pub(crate) fn start_dead_letter_service(
+    capacity: Option<u64>,
+    options: ServiceOptions<'_>,
+) -> RequestBuilder<'static, StartDeadLetterService<'_>> {
+    let mut payload = StartDeadLetterService::new().with_options(options);
+    if let Some(n) = capacity {
+        payload = payload.with_capacity(n)
+    }
+    Request::post("/node/services/dead_letters").body(payload)
+}
+
+/// Construct a request to stop a service
+pub(crate) fn stop_service<'a>(
+    kind: &str,
+    addr: &'a str,
+) -> RequestBuilder<'static, StopServiceRequest<'a>> {
+    let payload = StopServiceRequest::new(addr);
+    Request::delete(format!("/node/services/{kind}")).body(payload)
+}
+
+/// Construct a request to send a file to another node
+pub(crate) fn send_file<'a>(
+    path: &'a str,
+    to: &MultiAddr,
+    name: Option<&'a str>,
+) -> RequestBuilder<'static, SendFile<'a>> {
+    let mut payload = SendFile::new(path, to.to_string());
+    if let Some(name) = name {
+        payload = payload.with_name(name)
+    }
+    Request::post("/node/files").body(payload)
+}
+
 /// Construct a request to start a Credentials Service
-pub(crate) fn start_credentials_service(
-    addr: &str,
+pub(crate) fn start_credentials_service<'a>(
+    addr: &'a str,
     oneway: bool,
-) -> RequestBuilder<'static, StartCredentialsService> {
-    let payload = StartCredentialsService::new(addr, oneway);
+    options: ServiceOptions<'a>,
+) -> RequestBuilder<'static, StartCredentialsService<'a>> {
+    let payload = StartCredentialsService::new(addr, oneway).with_options(options);
     Request::post("/node/services/credentials").body(payload)
 }
 
@@ -218,13 +509,57 @@ pub(crate) fn start_authenticator_service<'a>(
     addr: &'a str,
     enrollers: &'a Path,
     project: &'a str,
+    redis: Option<&'a str>,
+    signer: Option<&'a str>,
+    stateless: bool,
+    options: ServiceOptions<'a>,
 ) -> RequestBuilder<'static, StartAuthenticatorRequest<'a>> {
-    let payload = StartAuthenticatorRequest::new(addr, enrollers, project.as_bytes());
+    let mut payload = StartAuthenticatorRequest::new(addr, enrollers, project.as_bytes())
+        .with_stateless(stateless)
+        .with_options(options);
+    if let Some(url) = redis {
+        payload = payload.with_redis(url)
+    }
+    if let Some(route) = signer {
+        payload = payload.with_signer(route)
+    }
     Request::post("/node/services/authenticator").body(payload)
 }
 
+/// Construct a request to start a Kafka outlet service
+pub(crate) fn start_kafka_outlet_service<'a>(
+    addr: &'a str,
+    bootstrap_server: &'a str,
+) -> RequestBuilder<'static, StartKafkaOutletRequest<'a>> {
+    let payload = StartKafkaOutletRequest::new(addr, bootstrap_server);
+    Request::post("/node/services/kafka_outlet").body(payload)
+}
+
+/// Construct a request to start a Kafka inlet service
+pub(crate) fn start_kafka_inlet_service<'a>(
+    addr: &'a str,
+    bind_addr: &'a str,
+    to: &'a str,
+) -> RequestBuilder<'static, StartKafkaInletRequest<'a>> {
+    let payload = StartKafkaInletRequest::new(addr, bind_addr, to);
+    Request::post("/node/services/kafka_inlet").body(payload)
+}
+
+/// Construct a request to start a SOCKS5 / HTTP CONNECT proxy service
+pub(crate) fn start_proxy_service<'a>(
+    addr: &'a str,
+    bind_addr: &'a str,
+    to: &'a str,
+    outlets: &'a [String],
+) -> RequestBuilder<'static, StartProxyServiceRequest<'a>> {
+    let outlets = outlets.iter().map(|o| o.as_str().into()).collect();
+    let payload = StartProxyServiceRequest::new(addr, bind_addr, to, outlets);
+    Request::post("/node/services/proxy").body(payload)
+}
+
 pub(crate) mod credentials {
     use ockam_api::nodes::models::credentials::{GetCredentialRequest, PresentCredentialRequest};
+    use ockam_api::revocation::types::{Revoke, SyncRevocations};
 
     use super::*;
 
@@ -240,6 +575,26 @@ pub(crate) mod credentials {
         let b = GetCredentialRequest::new(overwrite);
         Request::post("/node/credentials/actions/get").body(b)
     }
+
+    pub(crate) fn revoke(identity: IdentityIdentifier) -> RequestBuilder<'static, Revoke> {
+        Request::post("/node/revocations").body(Revoke::new(identity))
+    }
+
+    pub(crate) fn sync_revocations(
+        from: &MultiAddr,
+        interval: Option<u64>,
+        authority: Option<IdentityIdentifier>,
+        max_staleness: Option<u64>,
+    ) -> RequestBuilder<'static, SyncRevocations<'static>> {
+        let mut b = SyncRevocations::new(from.to_string(), interval);
+        if let Some(authority) = authority {
+            b = b.with_authority(authority)
+        }
+        if let Some(secs) = max_staleness {
+            b = b.with_max_staleness(secs)
+        }
+        Request::post("/node/revocations/sync").body(b)
+    }
 }
 
 /// Helpers to create enroll API requests
@@ -290,10 +645,29 @@ pub(crate) mod space {
     ) -> RequestBuilder<'a, BareCloudRequestWrapper<'a>> {
         Request::delete(format!("v0/spaces/{}", id)).body(CloudRequestWrapper::bare(cloud_route))
     }
+
+    pub(crate) fn add_member<'a>(
+        id: &str,
+        email: &str,
+        cloud_route: &'a MultiAddr,
+    ) -> RequestBuilder<'a, BareCloudRequestWrapper<'a>> {
+        Request::put(format!("v0/space-members/{}/{}", id, path_segment(email)))
+            .body(CloudRequestWrapper::bare(cloud_route))
+    }
+
+    pub(crate) fn remove_member<'a>(
+        id: &str,
+        email: &str,
+        cloud_route: &'a MultiAddr,
+    ) -> RequestBuilder<'a, BareCloudRequestWrapper<'a>> {
+        Request::delete(format!("v0/space-members/{}/{}", id, path_segment(email)))
+            .body(CloudRequestWrapper::bare(cloud_route))
+    }
 }
 
 /// Helpers to create projects API requests
 pub(crate) mod project {
+    use ockam_api::cloud::addon::{ConfluentConfig, InfluxDBConfig};
     use ockam_api::cloud::project::*;
 
     use crate::project::*;
@@ -322,6 +696,17 @@ pub(crate) mod project {
         Request::get(format!("v0/projects/{}", id)).body(CloudRequestWrapper::bare(cloud_route))
     }
 
+    /// Like [`show`], for callers polling the project: a failed attempt is
+    /// not retried by the node, the next poll will do.
+    pub(crate) fn poll<'a>(
+        id: &str,
+        cloud_route: &'a MultiAddr,
+    ) -> RequestBuilder<'a, BareCloudRequestWrapper<'a>> {
+        let policy = RetryPolicy::no_retry(RetryPolicy::default().timeout());
+        Request::get(format!("v0/projects/{}", id))
+            .body(CloudRequestWrapper::bare(cloud_route).with_retry_policy(policy))
+    }
+
     pub(crate) fn delete<'a>(
         space_id: &'a str,
         project_id: &'a str,
@@ -331,6 +716,32 @@ pub(crate) mod project {
             .body(CloudRequestWrapper::bare(cloud_route))
     }
 
+    pub(crate) fn add_member<'a>(
+        project_id: &str,
+        email: &str,
+        cloud_route: &'a MultiAddr,
+    ) -> RequestBuilder<'a, BareCloudRequestWrapper<'a>> {
+        Request::put(format!(
+            "v0/project-members/{}/{}",
+            project_id,
+            path_segment(email)
+        ))
+        .body(CloudRequestWrapper::bare(cloud_route))
+    }
+
+    pub(crate) fn remove_member<'a>(
+        project_id: &str,
+        email: &str,
+        cloud_route: &'a MultiAddr,
+    ) -> RequestBuilder<'a, BareCloudRequestWrapper<'a>> {
+        Request::delete(format!(
+            "v0/project-members/{}/{}",
+            project_id,
+            path_segment(email)
+        ))
+        .body(CloudRequestWrapper::bare(cloud_route))
+    }
+
     pub(crate) fn add_enroller(
         cmd: &AddEnrollerCommand,
     ) -> RequestBuilder<CloudRequestWrapper<AddEnroller>> {
@@ -358,6 +769,47 @@ pub(crate) mod project {
         ))
         .body(CloudRequestWrapper::bare(&cmd.cloud_opts.route()))
     }
+
+    pub(crate) fn list_addons<'a>(
+        project_id: &str,
+        cloud_route: &'a MultiAddr,
+    ) -> RequestBuilder<'a, BareCloudRequestWrapper<'a>> {
+        Request::get(format!("v0/project-addons/{}", project_id))
+            .body(CloudRequestWrapper::bare(cloud_route))
+    }
+
+    pub(crate) fn disable_addon<'a>(
+        project_id: &str,
+        addon_id: &str,
+        cloud_route: &'a MultiAddr,
+    ) -> RequestBuilder<'a, BareCloudRequestWrapper<'a>> {
+        Request::delete(format!("v0/project-addons/{}/{}", project_id, addon_id))
+            .body(CloudRequestWrapper::bare(cloud_route))
+    }
+
+    pub(crate) fn configure_confluent_addon<'a>(
+        cmd: &'a ConfluentCommand,
+        cloud_route: &'a MultiAddr,
+    ) -> RequestBuilder<'a, CloudRequestWrapper<'a, ConfluentConfig<'a>>> {
+        let b = ConfluentConfig::new(cmd.bootstrap_server.as_str());
+        Request::put(format!("v0/project-addons/{}/confluent", cmd.project_id))
+            .body(CloudRequestWrapper::new(b, cloud_route))
+    }
+
+    pub(crate) fn configure_influxdb_addon<'a>(
+        cmd: &'a InfluxDBCommand,
+        cloud_route: &'a MultiAddr,
+    ) -> RequestBuilder<'a, CloudRequestWrapper<'a, InfluxDBConfig<'a>>> {
+        let b = InfluxDBConfig::new(
+            cmd.endpoint_url.as_str(),
+            cmd.token.as_str(),
+            cmd.org_id.as_str(),
+            cmd.permissions.as_str(),
+            cmd.max_ttl,
+        );
+        Request::put(format!("v0/project-addons/{}/influxdb", cmd.project_id))
+            .body(CloudRequestWrapper::new(b, cloud_route))
+    }
 }
 
 ////////////// !== parsers
@@ -375,6 +827,13 @@ pub(crate) fn parse_status(resp: &[u8]) -> Result<models::base::NodeStatus> {
     Ok(dec.decode::<models::base::NodeStatus>()?)
 }
 
+/// Parse the returned node details response
+pub(crate) fn parse_node_details(resp: &[u8]) -> Result<models::base::NodeDetails> {
+    let mut dec = Decoder::new(resp);
+    let _ = dec.decode::<Response>()?;
+    Ok(dec.decode::<models::base::NodeDetails>()?)
+}
+
 /// Parse the returned status response
 pub(crate) fn parse_tcp_list(resp: &[u8]) -> Result<models::transport::TransportList> {
     let mut dec = Decoder::new(resp);
@@ -416,24 +875,6 @@ pub(crate) fn parse_short_identity_response(
     ))
 }
 
-pub(crate) fn parse_list_services_response(resp: &[u8]) -> Result<models::services::ServiceList> {
-    let mut dec = Decoder::new(resp);
-    let _ = dec.decode::<Response>()?;
-    Ok(dec.decode::<models::services::ServiceList>()?)
-}
-
-pub(crate) fn parse_list_inlets_response(resp: &[u8]) -> Result<models::portal::InletList> {
-    let mut dec = Decoder::new(resp);
-    let _ = dec.decode::<Response>()?;
-    Ok(dec.decode::<models::portal::InletList>()?)
-}
-
-pub(crate) fn parse_list_outlets_response(resp: &[u8]) -> Result<models::portal::OutletList> {
-    let mut dec = Decoder::new(resp);
-    let _ = dec.decode::<Response>()?;
-    Ok(dec.decode::<models::portal::OutletList>()?)
-}
-
 pub(crate) fn parse_create_secure_channel_listener_response(resp: &[u8]) -> Result<Response> {
     let mut dec = Decoder::new(resp);
     let response = dec.decode::<Response>()?;
@@ -443,6 +884,7 @@ pub(crate) fn parse_create_secure_channel_listener_response(resp: &[u8]) -> Resu
 ////////////// !== share CLI args
 
 pub(crate) const OCKAM_CONTROLLER_ADDR: &str = "OCKAM_CONTROLLER_ADDR";
+pub(crate) const OCKAM_CONTROLLER_RETRIES: &str = "OCKAM_CONTROLLER_RETRIES";
 
 #[derive(Clone, Debug, Args)]
 pub struct CloudOpts;
@@ -459,6 +901,42 @@ impl CloudOpts {
             .context(format!("invalid Controller route: {route}"))
             .unwrap()
     }
+
+    /// The policy of the nodes for retrying failed requests to the controller,
+    /// if the number of retries is set with `OCKAM_CONTROLLER_RETRIES`.
+    pub fn retry_policy() -> anyhow::Result<Option<RetryPolicy>> {
+        match std::env::var(OCKAM_CONTROLLER_RETRIES) {
+            Ok(s) => {
+                let retries = s
+                    .parse()
+                    .with_context(|| format!("invalid {OCKAM_CONTROLLER_RETRIES}: {s}"))?;
+                Ok(Some(RetryPolicy {
+                    retries,
+                    ..RetryPolicy::default()
+                }))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// Options to make creating a resource at a node safe to retry
+#[derive(Clone, Debug, Args)]
+pub struct IdempotencyOpts {
+    /// Key identifying this creation across retries. The node replies to a
+    /// request with a key it has seen recently with the original response,
+    /// instead of creating another resource
+    #[arg(long, value_name = "KEY", display_order = 899)]
+    idempotency_key: Option<String>,
+}
+
+impl IdempotencyOpts {
+    pub fn apply<'a, T>(&self, req: RequestBuilder<'a, T>) -> RequestBuilder<'a, T> {
+        match &self.idempotency_key {
+            Some(key) => req.idempotency_key(key.clone()),
+            None => req,
+        }
+    }
 }
 
 ////////////// !== validators
@@ -477,6 +955,24 @@ pub(crate) fn validate_cloud_resource_name(s: &str) -> anyhow::Result<()> {
 mod test {
 
     use crate::util::api::validate_cloud_resource_name;
+    use crate::util::api::{project, space};
+    use ockam_multiaddr::MultiAddr;
+
+    #[test]
+    fn member_emails_are_encoded_in_paths() {
+        let route = MultiAddr::default();
+        let email = "a+b/../c@example.com";
+        let req = space::add_member("s", email, &route);
+        assert_eq!(
+            req.header().path(),
+            "v0/space-members/s/a%2Bb%2F..%2Fc%40example.com"
+        );
+        let req = project::remove_member("p", email, &route);
+        assert_eq!(
+            req.header().path(),
+            "v0/project-members/p/a%2Bb%2F..%2Fc%40example.com"
+        );
+    }
 
     #[test]
     fn test_validate_cloud_resource_name() {
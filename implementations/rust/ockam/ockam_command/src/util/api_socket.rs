@@ -0,0 +1,211 @@
+//! Access to the API of a local node over a Unix domain socket.
+//!
+//! A node created with `--api-socket` listens on `api.sock` in its state
+//! directory. Unlike its TCP listener, which any local user can connect
+//! to, the socket can only be used by the user who owns the node. With
+//! `--no-tcp-api` as well, the node refuses API requests from local
+//! clients over TCP.
+//!
+//! Frames on the socket are a big-endian `u32` length followed by an
+//! encoded [`TransportMessage`]. The node delivers each request to its
+//! onward route, marked with [`LocalApiInfo`], and writes back the payload
+//! of the reply.
+
+use core::time::Duration;
+use std::fs::{self, Permissions};
+use std::io::ErrorKind;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context as _, Result};
+use ockam::{route, Address, Any, Context, LocalMessage, Route, Routed, TransportMessage, Worker};
+use ockam_api::nodes::service::LocalApiInfo;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Decodable, Encodable};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use tracing::{debug, error};
+
+/// Name of the API socket in the state directory of a node.
+pub const API_SOCKET: &str = "api.sock";
+
+/// Largest frame accepted on the socket.
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// How long the node waits for a worker to reply to a request.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub fn socket_path(node_dir: &Path) -> PathBuf {
+    node_dir.join(API_SOCKET)
+}
+
+/// Serve the API of the node on the socket in `node_dir`.
+///
+/// Connections are accepted in the background, until the node stops.
+pub async fn serve(ctx: &Context, node_dir: &Path) -> Result<()> {
+    // Bind under a temporary name and only move the socket in place once
+    // its permissions are restricted, so that others never get to use it.
+    let tmp = node_dir.join(format!("{API_SOCKET}.tmp"));
+    let _ = fs::remove_file(&tmp);
+    let listener = UnixListener::bind(&tmp)
+        .with_context(|| format!("Failed to bind API socket {}", tmp.display()))?;
+    fs::set_permissions(&tmp, Permissions::from_mode(0o600))?;
+    let path = socket_path(node_dir);
+    fs::rename(&tmp, &path)?;
+    debug!(path = %path.display(), "Serving node API over Unix domain socket");
+    let ctx = ctx.new_detached(Address::random_local()).await?;
+    tokio::spawn(accept(ctx, listener));
+    Ok(())
+}
+
+async fn accept(ctx: Context, listener: UnixListener) {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                error!(%e, "Failed to accept API socket connection");
+                return;
+            }
+        };
+        let child = match ctx.new_detached(Address::random_local()).await {
+            Ok(child) => child,
+            Err(e) => {
+                error!(%e, "Failed to create context for API socket connection");
+                return;
+            }
+        };
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(child, stream).await {
+                debug!(%e, "API socket connection closed");
+            }
+        });
+    }
+}
+
+async fn handle_connection(mut ctx: Context, mut stream: UnixStream) -> Result<()> {
+    while let Some(frame) = read_frame(&mut stream).await? {
+        let mut msg = TransportMessage::decode(&frame)?;
+        msg.return_route = route![ctx.address()];
+        // Only the owner of the node can use the socket, so its requests
+        // are not subject to the policies guarding the API.
+        let local_info = vec![LocalApiInfo.to_local_info()];
+        ctx.forward(LocalMessage::new(msg, local_info)).await?;
+        let reply = ctx.receive_duration_timeout::<Any>(REPLY_TIMEOUT).await?;
+        write_frame(&mut stream, reply.take().payload()).await?;
+    }
+    Ok(())
+}
+
+async fn read_frame(stream: &mut UnixStream) -> Result<Option<Vec<u8>>> {
+    let len = match stream.read_u32().await {
+        Ok(len) => len as usize,
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if len > MAX_FRAME_LEN {
+        return Err(anyhow!("API socket frame of {len} bytes is too large"));
+    }
+    let mut buf = vec![0; len];
+    stream.read_exact(&mut buf).await?;
+    Ok(Some(buf))
+}
+
+async fn write_frame(stream: &mut UnixStream, data: &[u8]) -> Result<()> {
+    stream.write_u32(u32::try_from(data.len())?).await?;
+    stream.write_all(data).await?;
+    Ok(())
+}
+
+/// Relays messages to a node through its API socket.
+///
+/// Messages sent to the bridge are delivered to the rest of their onward
+/// route on the node, and replies are sent back to their return route.
+pub struct ApiSocketBridge {
+    stream: UnixStream,
+}
+
+impl ApiSocketBridge {
+    /// Connect to the API socket of the node in `node_dir`.
+    ///
+    /// Returns `None` if the node doesn't serve its API on a socket.
+    pub async fn connect(node_dir: &Path) -> Option<Self> {
+        let stream = UnixStream::connect(socket_path(node_dir)).await.ok()?;
+        Some(ApiSocketBridge { stream })
+    }
+
+    /// Start the bridge and return the route to the node through it.
+    pub async fn start(self, ctx: &Context) -> Result<Route> {
+        let addr = Address::random_local();
+        ctx.start_worker(addr.clone(), self).await?;
+        Ok(route![addr])
+    }
+
+    async fn relay(&mut self, msg: Routed<Any>) -> Result<TransportMessage> {
+        let mut req = msg.into_transport_message();
+        req.onward_route.step()?;
+        let return_route = req.return_route.clone();
+        write_frame(&mut self.stream, &req.encode()?).await?;
+        let payload = read_frame(&mut self.stream)
+            .await?
+            .ok_or_else(|| anyhow!("Node closed the API socket"))?;
+        Ok(TransportMessage::v1(return_route, route![], payload))
+    }
+}
+
+#[ockam::worker]
+impl Worker for ApiSocketBridge {
+    type Context = Context;
+    type Message = Any;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> ockam::Result<()> {
+        let reply = self
+            .relay(msg)
+            .await
+            .map_err(|e| ockam::Error::new(Origin::Transport, Kind::Io, e))?;
+        ctx.forward(LocalMessage::new(reply, Vec::new())).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Replies whether requests are marked as coming from the node's owner.
+    struct Marked;
+
+    #[ockam::worker]
+    impl Worker for Marked {
+        type Context = Context;
+        type Message = String;
+
+        async fn handle_message(
+            &mut self,
+            ctx: &mut Context,
+            msg: Routed<String>,
+        ) -> ockam::Result<()> {
+            let marked = LocalApiInfo::find_info(msg.local_message()).is_some();
+            let reply = format!("{}, marked: {marked}", msg.as_body());
+            ctx.send(msg.return_route(), reply).await
+        }
+    }
+
+    #[ockam::test(crate = "ockam")]
+    async fn api_answers_over_the_socket(ctx: &mut Context) -> ockam::Result<()> {
+        let node_dir = tempfile::tempdir().unwrap();
+        ctx.start_worker("marked", Marked).await?;
+        serve(ctx, node_dir.path()).await.unwrap();
+        let mode = fs::metadata(socket_path(node_dir.path()))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let bridge = ApiSocketBridge::connect(node_dir.path()).await.unwrap();
+        let mut socket = bridge.start(ctx).await.unwrap();
+        let route: Route = socket.modify().append("marked").into();
+        let reply: String = ctx.send_and_receive(route, "hello".to_string()).await?;
+        assert_eq!(reply, "hello, marked: true");
+
+        ctx.stop().await
+    }
+}
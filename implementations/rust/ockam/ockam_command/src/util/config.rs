@@ -7,7 +7,9 @@ use slug::slugify;
 use tracing::{error, trace};
 
 use ockam::identity::IdentityIdentifier;
-use ockam_api::config::cli::NodeConfigOld;
+use ockam_api::cloud::project::Project;
+use ockam_api::cloud::space::Space;
+use ockam_api::config::cli::{CachedListing, NodeConfigOld};
 use ockam_api::config::lookup::ProjectLookup;
 use ockam_api::config::{cli, lookup::ConfigLookup, lookup::InternetAddress, Config};
 use ockam_api::nodes::config::NodeConfig;
@@ -145,12 +147,44 @@ impl OckamConfig {
             .pid())
     }
 
-    /// Check whether another node has been registered with this API
-    /// port.  This doesn't catch all port collision errors, but will
-    /// get us most of the way there in terms of starting a new node.
-    pub fn port_is_used(&self, port: u16) -> bool {
+    /// Get the name of the node registered with this API port, if any.
+    /// This doesn't catch all port collision errors, but will get us
+    /// most of the way there in terms of starting a new node.
+    pub fn get_node_by_port(&self, port: u16) -> Option<String> {
         let inner = self.inner.read();
-        inner.nodes.iter().any(|(_, n)| n.port() == port)
+        let (name, _) = inner.nodes.iter().find(|(_, n)| n.port() == port)?;
+        Some(name.clone())
+    }
+
+    /// Update the API address of a node, e.g. once it bound a port picked
+    /// by the system
+    pub fn set_node_addr(&self, name: &str, addr: SocketAddr) -> Result<()> {
+        let mut inner = self.inner.write();
+        inner
+            .nodes
+            .get_mut(name)
+            .ok_or_else(|| ConfigError::NotFound(name.to_string()))?
+            .set_addr(addr.into());
+        inner.lookup.set_node(name, addr.into());
+        Ok(())
+    }
+
+    /// Update the API address of a node and persist it
+    ///
+    /// The configuration is reloaded from disk first, so that changes
+    /// made by other processes since this one loaded it are kept.
+    pub fn persist_node_addr(&self, name: &str, addr: SocketAddr) -> Result<()> {
+        let latest = OckamConfig::load()?;
+        latest.set_node_addr(name, addr)?;
+        latest.persist_config_updates()?;
+        self.set_node_addr(name, addr)
+    }
+
+    /// Get the state directory of the local node with this API port
+    pub fn get_node_dir_by_port(&self, port: u16) -> Option<PathBuf> {
+        let inner = self.inner.read();
+        let (_, n) = inner.nodes.iter().find(|(_, n)| n.port() == port)?;
+        n.state_dir().map(PathBuf::from)
     }
 
     /// Get only a single node configuration
@@ -319,6 +353,30 @@ impl OckamConfig {
         inner.lookup.remove_projects();
     }
 
+    pub fn set_cached_spaces(&self, spaces: &[Space]) -> Result<()> {
+        let data = minicbor::to_vec(spaces)?;
+        let mut inner = self.inner.write();
+        inner.cache.spaces = Some(CachedListing::new(data));
+        Ok(())
+    }
+
+    pub fn get_cached_spaces(&self) -> Option<CachedListing> {
+        let inner = self.inner.read();
+        inner.cache.spaces.clone()
+    }
+
+    pub fn set_cached_projects(&self, projects: &[Project]) -> Result<()> {
+        let data = minicbor::to_vec(projects)?;
+        let mut inner = self.inner.write();
+        inner.cache.projects = Some(CachedListing::new(data));
+        Ok(())
+    }
+
+    pub fn get_cached_projects(&self) -> Option<CachedListing> {
+        let inner = self.inner.read();
+        inner.cache.projects.clone()
+    }
+
     pub fn set_default_node(&self, name: &String) {
         let mut inner = self.inner.write();
         inner.default = Some(name.to_string());
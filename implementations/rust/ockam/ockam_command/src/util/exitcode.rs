@@ -76,3 +76,20 @@ pub const NOPERM: ExitCode = 77;
 
 /// Something was found in an unconfigured or misconfigured state.
 pub const CONFIG: ExitCode = 78;
+
+// Exit codes of failed commands, by kind of failure, for scripts to act on.
+
+/// The CLI configuration is missing or invalid.
+pub const CONFIG_ERROR: ExitCode = CONFIG;
+
+/// The node could not be reached.
+pub const CONNECTION_ERROR: ExitCode = UNAVAILABLE;
+
+/// The node failed to process a request or returned an unexpected response.
+pub const API_ERROR: ExitCode = PROTOCOL;
+
+/// The node, or what a request is about, does not exist.
+pub const NOT_FOUND: ExitCode = NOINPUT;
+
+/// The request was not allowed.
+pub const FORBIDDEN: ExitCode = NOPERM;
@@ -1,32 +1,47 @@
 use core::time::Duration;
 use std::{
     env,
-    net::{SocketAddr, TcpListener},
+    net::SocketAddr,
     path::Path,
     str::FromStr,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    sync::Arc,
 };
 
 use anyhow::{anyhow, Context as _, Result};
 use crossbeam_channel::{bounded, Sender};
 use minicbor::{data::Type, Decode, Decoder, Encode};
-use tracing::{debug, error, trace};
+use tracing::{debug, error, trace, Instrument};
 use tracing_subscriber::prelude::*;
-use tracing_subscriber::{filter::LevelFilter, fmt, EnvFilter};
+use tracing_subscriber::{filter::LevelFilter, fmt, reload, EnvFilter, Registry};
 
 pub use addon::AddonCommand;
 pub use config::*;
-use ockam::{route, Address, Context, NodeBuilder, Route, TcpTransport, TCP};
+use ockam::tcp::MAX_MESSAGE_SIZE;
+use ockam::{
+    route, Address, Context, LocalMessage, NodeBuilder, Route, TcpTransport, TransportMessage, TCP,
+};
 use ockam_api::config::cli::NodeConfigOld;
+use ockam_api::nodes::service::{LocalApiInfo, LogFilter};
 use ockam_api::nodes::NODEMANAGER_ADDR;
-use ockam_core::api::{RequestBuilder, Response, Status};
+use ockam_api::telemetry;
+use ockam_core::api::{
+    fragment, is_fragment, Reassembly, Request, RequestBuilder, Response, Status,
+};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::Encodable;
 use ockam_multiaddr::{proto, MultiAddr, Protocol};
 
+use crate::error::{ApiError, ConnectionError};
 use crate::node::util::start_embedded_node;
 use crate::util::output::Output;
 use crate::{CommandGlobalOpts, OutputFormat};
 
 pub mod api;
+#[cfg(unix)]
+pub mod api_socket;
 pub mod exitcode;
+pub mod secret;
 pub mod startup;
 
 mod addon;
@@ -143,15 +158,33 @@ impl<'a> Rpc<'a> {
         T: Encode<()>,
     {
         let route = self.route_impl(self.ctx).await?;
-        self.buf = self
-            .ctx
-            .send_and_receive(route.clone(), req.to_vec()?)
-            .await
-            .context("Failed to receive response from node")?;
+        let span = rpc_span(&req);
+        let id = req.header().id();
+        let req = req.max_message_size(MAX_MESSAGE_SIZE as u32);
+        let req = span.in_scope(|| telemetry::inject_trace_context(req));
+        let ctx = self.ctx;
+        let local = self.is_local();
+        watch_interrupts();
+        PENDING_REQUESTS.fetch_add(1, Ordering::SeqCst);
+        let res = tokio::select! {
+            res = send_and_reassemble(ctx, route.clone(), req.to_vec()?, local).instrument(span) => res,
+            _ = tokio::signal::ctrl_c() => {
+                // Ask the node to stop working on the request as well, and
+                // wait a little for the cancellation to reach it.
+                let cancel = Request::post(format!("/node/cancel/{id}")).to_vec()?;
+                let cancel = ctx.send_and_receive::<_, _, Vec<u8>>(route, cancel);
+                if let Err(e) = tokio::time::timeout(Duration::from_secs(2), cancel).await {
+                    debug!(%e, "Failed to cancel request");
+                }
+                PENDING_REQUESTS.fetch_sub(1, Ordering::SeqCst);
+                return Err(anyhow!("Request cancelled"));
+            }
+        };
+        PENDING_REQUESTS.fetch_sub(1, Ordering::SeqCst);
+        self.buf = res.context(ConnectionError::new("Failed to receive response from node"))?;
         Ok(())
     }
 
-    #[allow(unused)]
     pub async fn request_with_timeout<T>(
         &mut self,
         req: RequestBuilder<'_, T>,
@@ -162,33 +195,48 @@ impl<'a> Rpc<'a> {
     {
         let mut ctx = self.ctx.new_detached(Address::random_local()).await?;
         let route = self.route_impl(&ctx).await?;
-        ctx.send(route.clone(), req.to_vec()?).await?;
+        let span = rpc_span(&req);
+        let req = span.in_scope(|| telemetry::inject_trace_context(req));
+        send(&ctx, route.clone(), req.to_vec()?, self.is_local()).await?;
         self.buf = ctx
             .receive_duration_timeout::<Vec<u8>>(timeout)
+            .instrument(span)
             .await
-            .context("Failed to receive response from node")?
+            .context(ConnectionError::new("Failed to receive response from node"))?
             .take()
             .body();
         Ok(())
     }
 
+    /// Whether requests go to the node running in this process.
+    fn is_local(&self) -> bool {
+        matches!(self.mode, RpcMode::Embedded)
+    }
+
     async fn route_impl(&mut self, ctx: &Context) -> Result<Route> {
         let route = match self.mode {
             RpcMode::Embedded => self.to.clone(),
             RpcMode::Background { ref cfg, ref tcp } => {
-                let addr = Address::from((TCP, format!("localhost:{}", cfg.port())));
-                let addr_str = addr.address();
-                match tcp {
-                    None => {
-                        let tcp = TcpTransport::create(ctx).await?;
-                        tcp.connect(addr_str).await?;
-                    }
-                    Some(tcp) => {
-                        // Ignore "already connected" error.
-                        let _ = tcp.connect(addr_str).await;
+                // Prefer the API socket of the node when it serves one.
+                if let Some(socket) = api_socket_route(ctx, cfg.state_dir()).await? {
+                    self.to.modify().prepend_route(socket).into()
+                } else {
+                    let addr = Address::from((TCP, format!("localhost:{}", cfg.port())));
+                    let addr_str = addr.address();
+                    match tcp {
+                        None => {
+                            let tcp = TcpTransport::create(ctx).await?;
+                            tcp.connect(addr_str)
+                                .await
+                                .context(ConnectionError::new("Failed to connect to node"))?;
+                        }
+                        Some(tcp) => {
+                            // Ignore "already connected" error.
+                            let _ = tcp.connect(addr_str).await;
+                        }
                     }
+                    self.to.modify().prepend_route(addr.into()).into()
                 }
-                self.to.modify().prepend_route(addr.into()).into()
             }
         };
         debug!(%route, "Sending request");
@@ -233,18 +281,23 @@ impl<'a> Rpc<'a> {
         if hdr.status() == Some(Status::Ok) {
             Ok(dec)
         } else {
-            let msg = self.parse_err_msg(hdr, dec);
-            Err(anyhow!(msg))
+            Err(self.parse_err(hdr, dec).into())
         }
     }
 
-    pub fn parse_err_msg(&self, hdr: Response, mut dec: Decoder) -> String {
+    pub fn parse_err_msg(&self, hdr: Response, dec: Decoder) -> String {
+        self.parse_err(hdr, dec).to_string()
+    }
+
+    /// Parse an error response into an [`ApiError`].
+    pub fn parse_err(&self, hdr: Response, mut dec: Decoder) -> ApiError {
         trace! {
             dec = %minicbor::display(&self.buf),
             hex = %hex::encode(&self.buf),
             "Received CBOR message"
         };
-        match hdr.status() {
+        let mut code = None;
+        let msg = match hdr.status() {
             Some(status) if hdr.has_body() => {
                 let err = if matches!(dec.datatype(), Ok(Type::String)) {
                     dec.decode::<String>()
@@ -253,9 +306,15 @@ impl<'a> Rpc<'a> {
                 } else {
                     dec.decode::<ockam_core::api::Error>()
                         .map(|e| {
-                            e.message()
-                                .map(|msg| format!("Message: {msg}"))
-                                .unwrap_or_default()
+                            code = e.code();
+                            let code = e.code().map(|c| format!("Code: {c}. ")).unwrap_or_default();
+                            match (e.message(), e.field()) {
+                                (Some(msg), Some(field)) => {
+                                    format!("{code}Field: {field}. Message: {msg}")
+                                }
+                                (Some(msg), None) => format!("{code}Message: {msg}"),
+                                _ => code,
+                            }
                         })
                         .unwrap_or_default()
                 };
@@ -267,7 +326,8 @@ impl<'a> Rpc<'a> {
                 format!("An error occurred while processing the request. Status code: {status}")
             }
             None => "No status code found in response".to_string(),
-        }
+        };
+        ApiError::new(code, msg)
     }
 
     /// Parse the response body and print it.
@@ -284,6 +344,7 @@ impl<'a> Rpc<'a> {
         T: Output + serde::Serialize,
     {
         let o = match self.opts.global_args.output_format {
+            OutputFormat::Plain if self.opts.global_args.quiet => return Ok(b),
             OutputFormat::Plain => b.output().context("Failed to serialize response body")?,
             OutputFormat::Json => {
                 serde_json::to_string_pretty(&b).context("Failed to serialize response body")?
@@ -306,6 +367,40 @@ pub async fn stop_node(mut ctx: Context) -> Result<()> {
     Ok(())
 }
 
+/// Number of requests waiting for a response, which cancel themselves
+/// when the user hits Ctrl-C.
+static PENDING_REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether [`watch_interrupts`] has been called.
+static WATCHING_INTERRUPTS: AtomicBool = AtomicBool::new(false);
+
+/// Whether commands run in the shell, which Ctrl-C must not exit.
+static IN_SHELL: AtomicBool = AtomicBool::new(false);
+
+/// Keep the process running on Ctrl-C, which then only cancels the pending
+/// requests of the command being run.
+pub(crate) fn keep_running_on_interrupt() {
+    IN_SHELL.store(true, Ordering::SeqCst);
+}
+
+/// Exit on Ctrl-C, unless requests are pending.
+///
+/// Once a task listens for Ctrl-C, the signal no longer stops the process,
+/// so this restores that for the rest of the command.
+fn watch_interrupts() {
+    if IN_SHELL.load(Ordering::SeqCst) || WATCHING_INTERRUPTS.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if PENDING_REQUESTS.load(Ordering::SeqCst) == 0 {
+                // 128 + SIGINT, like a shell does.
+                std::process::exit(130)
+            }
+        }
+    });
+}
+
 /// Connect to a remote node (on localhost for now)
 ///
 /// This function requires the "remote" port, some command payload,
@@ -313,64 +408,74 @@ pub async fn stop_node(mut ctx: Context) -> Result<()> {
 /// while also configuring a TcpTransport and connecting to another
 /// node.
 ///
-pub fn connect_to<A, F, Fut>(port: u16, a: A, lambda: F)
+pub fn connect_to<A, F, Fut>(port: u16, a: A, lambda: F) -> crate::Result<()>
 where
     A: Send + Sync + 'static,
     F: FnOnce(Context, A, Route) -> Fut + Send + Sync + 'static,
     Fut: core::future::Future<Output = Result<()>> + Send + 'static,
 {
-    let res = embedded_node(
+    embedded_node(
         move |ctx, a| async move {
-            let tcp = match TcpTransport::create(&ctx).await {
-                Ok(tcp) => tcp,
-                Err(e) => {
-                    eprintln!("Failed to create TcpTransport. {e}");
-                    error!(%e);
-                    std::process::exit(exitcode::CANTCREAT);
+            // Prefer the API socket of the node when it serves one.
+            let node_dir = OckamConfig::load()
+                .ok()
+                .and_then(|cfg| cfg.get_node_dir_by_port(port));
+            let route = match api_socket_route(&ctx, node_dir.as_deref()).await {
+                Ok(Some(route)) => route,
+                _ => {
+                    let tcp = TcpTransport::create(&ctx).await.map_err(|e| {
+                        crate::Error::new(
+                            exitcode::CANTCREAT,
+                            anyhow!("Failed to create TcpTransport. {e}"),
+                        )
+                    })?;
+                    tcp.connect(format!("localhost:{}", port))
+                        .await
+                        .map_err(|e| {
+                            crate::Error::new(
+                                exitcode::CONNECTION_ERROR,
+                                anyhow!("Failed to connect to node. {e}"),
+                            )
+                        })?;
+                    route![(TCP, format!("localhost:{}", port))]
                 }
             };
-            if let Err(e) = tcp.connect(format!("localhost:{}", port)).await {
-                eprintln!("Failed to connect to node. {e}");
-                error!(%e);
-                std::process::exit(exitcode::IOERR);
-            }
-            let route = route![(TCP, format!("localhost:{}", port))];
-            if let Err(e) = lambda(ctx, a, route).await {
-                eprintln!("Encountered an error in command handler code. {e}");
-                error!(%e);
-                std::process::exit(exitcode::IOERR);
-            }
+            lambda(ctx, a, route).await?;
             Ok(())
         },
         a,
-    );
-    if let Err(e) = res {
-        eprintln!("Ockam node failed: {:?}", e,);
+    )
+}
+
+/// Start a bridge to the API socket of the node in `node_dir`, if
+/// the node serves one, and return the route through it.
+#[cfg(unix)]
+async fn api_socket_route(ctx: &Context, node_dir: Option<&Path>) -> Result<Option<Route>> {
+    let bridge = match node_dir {
+        Some(dir) => api_socket::ApiSocketBridge::connect(dir).await,
+        None => None,
+    };
+    match bridge {
+        Some(bridge) => Ok(Some(bridge.start(ctx).await?)),
+        None => Ok(None),
     }
 }
 
-pub fn node_rpc<A, F, Fut>(f: F, a: A)
+#[cfg(not(unix))]
+async fn api_socket_route(_ctx: &Context, _node_dir: Option<&Path>) -> Result<Option<Route>> {
+    Ok(None)
+}
+
+/// Run a command on an embedded node, returning its result.
+///
+/// Errors are reported, with their exit code, by the caller of the command.
+pub fn node_rpc<A, F, Fut>(f: F, a: A) -> crate::Result<()>
 where
     A: Send + Sync + 'static,
     F: FnOnce(Context, A) -> Fut + Send + Sync + 'static,
     Fut: core::future::Future<Output = crate::Result<()>> + Send + 'static,
 {
-    let res = embedded_node(
-        |ctx, a| async {
-            let res = f(ctx, a).await;
-            if let Err(e) = res {
-                error!(%e);
-                eprintln!("{e:?}");
-                std::process::exit(e.code());
-            }
-            Ok(())
-        },
-        a,
-    );
-    if let Err(e) = res {
-        eprintln!("Ockam node failed: {e}");
-        std::process::exit(exitcode::SOFTWARE);
-    }
+    embedded_node(f, a)
 }
 
 pub fn embedded_node<A, F, Fut, T>(f: F, a: A) -> crate::Result<T>
@@ -388,16 +493,9 @@ where
             .expect("Embedded node child ctx can't be created");
         let r = f(child_ctx, a).await;
         stop_node(ctx).await.unwrap();
-        match r {
-            Err(e) => {
-                error!(%e);
-                eprintln!("{e:?}");
-                std::process::exit(e.code());
-            }
-            Ok(v) => v,
-        }
+        r
     })?;
-    Ok(r)
+    r
 }
 
 pub fn embedded_node_that_is_not_stopped<A, F, Fut, T>(f: F, a: A) -> crate::Result<T>
@@ -413,27 +511,83 @@ where
             .new_detached(Address::random_local())
             .await
             .expect("Embedded node child ctx can't be created");
-        match f(child_ctx, a).await {
-            Err(e) => {
-                error!(%e);
-                eprintln!("{e:?}");
-                std::process::exit(e.code());
-            }
-            Ok(v) => v,
-        }
+        f(child_ctx, a).await
     })?;
-    Ok(r)
+    r
+}
+
+/// Span covering a request to a node, which becomes the parent of the spans
+/// created by the node while handling it.
+fn rpc_span<T>(req: &RequestBuilder<'_, T>) -> tracing::Span {
+    tracing::info_span! {
+        "rpc",
+        method = ?req.header().method(),
+        path   = %req.header().path(),
+    }
 }
 
-pub fn find_available_port() -> Result<u16> {
-    let listener = TcpListener::bind("127.0.0.1:0").context("Unable to bind to an open port")?;
-    let address = listener
-        .local_addr()
-        .context("Unable to get local address")?;
-    Ok(address.port())
+/// Send a request, marked with [`LocalApiInfo`] if `local` is set.
+///
+/// A node running in this process belongs to the user running the command,
+/// so its API lets the requests of the command through without policies.
+/// Requests larger than what the TCP transport accepts are split into
+/// fragments, which the node puts back together.
+async fn send(ctx: &Context, route: Route, req: Vec<u8>, local: bool) -> ockam::Result<()> {
+    let id = Decoder::new(&req).decode::<Request>()?.id();
+    for m in fragment(id, &req, MAX_MESSAGE_SIZE)? {
+        if !local {
+            ctx.send(route.clone(), m).await?;
+            continue;
+        }
+        let msg = TransportMessage::v1(route.clone(), route![ctx.address()], m.encode()?);
+        ctx.forward(LocalMessage::new(msg, vec![LocalApiInfo.to_local_info()]))
+            .await?
+    }
+    Ok(())
+}
+
+/// Send a request and wait for its response, putting it back together if
+/// the node split it into fragments.
+async fn send_and_reassemble(
+    ctx: &Context,
+    route: Route,
+    req: Vec<u8>,
+    local: bool,
+) -> ockam::Result<Vec<u8>> {
+    let mut ctx = ctx.new_detached(Address::random_local()).await?;
+    send(&ctx, route, req, local).await?;
+    let mut reassembly = Reassembly::new();
+    loop {
+        let msg = ctx.receive::<Vec<u8>>().await?.take().body();
+        if !is_fragment(&msg) {
+            return Ok(msg);
+        }
+        if let Some(res) = reassembly.push(&msg)? {
+            return Ok(res);
+        }
+    }
 }
 
-pub fn setup_logging(verbose: u8, no_color: bool) {
+/// The tracing filter installed by [`setup_logging`], which nodes let
+/// users change at runtime.
+struct ReloadableLogFilter(reload::Handle<EnvFilter, Registry>);
+
+impl LogFilter for ReloadableLogFilter {
+    fn get(&self) -> String {
+        self.0.with_current(|f| f.to_string()).unwrap_or_default()
+    }
+
+    fn set(&self, filter: &str) -> ockam::Result<()> {
+        let invalid = |e: String| ockam::Error::new(Origin::Application, Kind::Invalid, e);
+        let filter = EnvFilter::builder()
+            .parse(filter)
+            .map_err(|e| invalid(e.to_string()))?;
+        self.0.reload(filter).map_err(|e| invalid(e.to_string()))
+    }
+}
+
+/// Set up logging, and return a handle to change its filter when it is enabled.
+pub fn setup_logging(verbose: u8, no_color: bool) -> Option<Arc<dyn LogFilter>> {
     let ockam_crates = [
         "ockam",
         "ockam_node",
@@ -452,7 +606,7 @@ pub fn setup_logging(verbose: u8, no_color: bool) {
     let filter = match verbose {
         0 => match env::var("OCKAM_LOG") {
             Ok(s) if !s.is_empty() => builder.with_env_var("OCKAM_LOG").from_env_lossy(),
-            _ => return,
+            _ => return None,
         },
         1 => builder
             .with_default_directive(LevelFilter::INFO.into())
@@ -464,15 +618,20 @@ pub fn setup_logging(verbose: u8, no_color: bool) {
             .with_default_directive(LevelFilter::TRACE.into())
             .parse_lossy(ockam_crates.map(|c| format!("{c}=trace")).join(",")),
     };
+    let (filter, handle) = reload::Layer::new(filter);
     let fmt = fmt::Layer::default().with_ansi(!no_color);
-    let result = tracing_subscriber::registry()
+    let registry = tracing_subscriber::registry()
         .with(filter)
         .with(tracing_error::ErrorLayer::default())
-        .with(fmt)
-        .try_init();
+        .with(fmt);
+    #[cfg(feature = "telemetry")]
+    let registry = registry.with(telemetry::layer("ockam"));
+    let result = registry.try_init();
     if result.is_err() {
         eprintln!("Failed to initialise tracing logging.");
+        return None;
     }
+    Some(Arc::new(ReloadableLogFilter(handle)))
 }
 
 #[allow(unused)]
@@ -523,6 +682,14 @@ pub fn extract_address_value(input: &str) -> anyhow::Result<String> {
     Ok(addr)
 }
 
+/// Parse a `key=value` attribute given on the command line.
+pub fn parse_attribute(input: &str) -> anyhow::Result<(String, String)> {
+    match input.split_once('=') {
+        Some((k, v)) if !k.is_empty() => Ok((k.to_string(), v.to_string())),
+        _ => Err(anyhow!("expected an attribute of the form `key=value`")),
+    }
+}
+
 pub fn comma_separated<T: AsRef<str>>(data: &[T]) -> String {
     use itertools::Itertools;
 
@@ -533,30 +700,52 @@ pub fn comma_separated<T: AsRef<str>>(data: &[T]) -> String {
 pub fn bind_to_port_check(address: &SocketAddr) -> bool {
     let port = address.port();
     let ip = address.ip();
+    // Windows allows binding a specific address while another socket
+    // listens on the wildcard address for the same port, so check both.
+    if cfg!(windows) && !ip.is_unspecified() {
+        let any: std::net::IpAddr = if ip.is_ipv4() {
+            std::net::Ipv4Addr::UNSPECIFIED.into()
+        } else {
+            std::net::Ipv6Addr::UNSPECIFIED.into()
+        };
+        if std::net::TcpListener::bind((any, port)).is_err() {
+            return false;
+        }
+    }
     std::net::TcpListener::bind((ip, port)).is_ok()
 }
 
-pub fn verify_pids(cfg: &OckamConfig, nodes: Vec<String>) {
+pub fn verify_pids(cfg: &OckamConfig, nodes: Vec<String>) -> crate::Result<()> {
     for node_name in nodes {
-        let node_cfg = cfg.get_node(&node_name).unwrap();
+        let node_cfg = cfg.get_node(&node_name)?;
 
         let (tx, rx) = bounded(1);
 
-        connect_to(node_cfg.port(), tx, query_pid);
+        connect_to(node_cfg.port(), tx, query_pid)?;
         let verified_pid = rx.recv().unwrap();
 
+        // Supervised nodes are registered with the PID of their supervisor,
+        // which `stop_node` signals first, not the PID of the node process.
+        if verified_pid.is_some() && verified_pid == startup::supervised_pid(cfg, &node_name) {
+            continue;
+        }
+
         if node_cfg.pid() != verified_pid {
-            if let Err(e) = cfg.set_node_pid(&node_name, verified_pid) {
-                eprintln!("Failed to update pid for node {}: {}", node_name, e);
-                std::process::exit(exitcode::IOERR);
-            }
+            cfg.set_node_pid(&node_name, verified_pid).map_err(|e| {
+                crate::Error::new(
+                    exitcode::IOERR,
+                    anyhow!("Failed to update pid for node {node_name}: {e}"),
+                )
+            })?;
         }
     }
 
-    if cfg.persist_config_updates().is_err() {
-        eprintln!("Failed to update PID information in config!");
-        std::process::exit(exitcode::IOERR);
-    }
+    cfg.persist_config_updates().map_err(|e| {
+        crate::Error::new(
+            exitcode::IOERR,
+            anyhow!("Failed to update PID information in config: {e}"),
+        )
+    })
 }
 
 pub async fn query_pid(
@@ -2,15 +2,20 @@ use anyhow::Context;
 use cli_table::{Cell, Style, Table};
 use core::fmt::Write;
 use ockam::identity::credential::Credential;
+use ockam_api::cloud::addon::Addon;
 use ockam_api::cloud::project::{Enroller, Project};
 
 use crate::project::ProjectInfo;
 use crate::util::comma_separated;
 use colorful::Colorful;
 use ockam_api::cloud::space::Space;
+use ockam_api::nodes::models::perf::PerfReport;
+use ockam_api::nodes::models::route::ResolvedRoute;
 use ockam_api::nodes::models::secure_channel::{
     CreateSecureChannelResponse, ShowSecureChannelResponse,
 };
+use ockam_api::nodes::models::trace::TraceReport;
+use ockam_api::nodes::models::vault::{KeyInfo, KeyList};
 use ockam_api::route_to_multiaddr;
 use ockam_core::route;
 
@@ -182,7 +187,7 @@ impl Output for ShowSecureChannelResponse<'_> {
         let s = match &self.channel {
             Some(addr) => {
                 format!(
-                    "\n  Secure Channel:\n{} {}\n{} {}\n{} {}",
+                    "\n  Secure Channel:\n{} {}\n{} {}\n{} {}\n{} {}",
                     "  •         At: ".light_magenta(),
                     route_to_multiaddr(&route![addr.to_string()])
                         .context("Invalid Secure Channel Address")?
@@ -197,7 +202,15 @@ impl Output for ShowSecureChannelResponse<'_> {
                         .iter()
                         .map(|id| id.light_yellow().to_string())
                         .collect::<Vec<String>>()
-                        .join("\n\t")
+                        .join("\n\t"),
+                    "  •   Rejected: ".light_magenta(),
+                    format!(
+                        "{} decrypt failures, {} out of window, {} duplicates",
+                        self.decrypt_failures.unwrap_or(0),
+                        self.out_of_window.unwrap_or(0),
+                        self.duplicates.unwrap_or(0)
+                    )
+                    .light_yellow()
                 )
             }
             None => format!("{}", "Channel not found".red()),
@@ -207,6 +220,12 @@ impl Output for ShowSecureChannelResponse<'_> {
     }
 }
 
+impl Output for ResolvedRoute<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        Ok(self.address().to_string())
+    }
+}
+
 impl Output for Enroller<'_> {
     fn output(&self) -> anyhow::Result<String> {
         let mut w = String::new();
@@ -243,8 +262,135 @@ impl Output for Vec<Enroller<'_>> {
     }
 }
 
+impl Output for Vec<Addon<'_>> {
+    fn output(&self) -> anyhow::Result<String> {
+        if self.is_empty() {
+            return Ok("No addons found".to_string());
+        }
+        let mut rows = vec![];
+        for Addon {
+            id,
+            description,
+            enabled,
+            ..
+        } in self
+        {
+            rows.push([id.cell(), description.cell(), enabled.cell()]);
+        }
+        let table = rows
+            .table()
+            .title([
+                "Id".cell().bold(true),
+                "Description".cell().bold(true),
+                "Enabled".cell().bold(true),
+            ])
+            .display()?
+            .to_string();
+        Ok(table)
+    }
+}
+
+impl Output for KeyInfo<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        let mut w = String::new();
+        write!(w, "Key")?;
+        write!(w, "\n  Id: {}", self.key_id)?;
+        write!(w, "\n  Type: {:?}", self.stype)?;
+        if let Some(public_key) = &self.public_key {
+            write!(w, "\n  Public key: {}", public_key)?;
+        }
+        Ok(w)
+    }
+}
+
+impl Output for KeyList<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        if self.keys.is_empty() {
+            return Ok("No keys found".to_string());
+        }
+        let mut rows = vec![];
+        for KeyInfo {
+            key_id,
+            stype,
+            public_key,
+            ..
+        } in &self.keys
+        {
+            let public_key = public_key.as_deref().unwrap_or("-");
+            rows.push([
+                key_id.cell(),
+                format!("{stype:?}").cell(),
+                public_key.cell(),
+            ]);
+        }
+        let table = rows
+            .table()
+            .title([
+                "Id".cell().bold(true),
+                "Type".cell().bold(true),
+                "Public Key".cell().bold(true),
+            ])
+            .display()?
+            .to_string();
+        Ok(table)
+    }
+}
+
 impl Output for Credential<'_> {
     fn output(&self) -> anyhow::Result<String> {
         Ok(self.to_string())
     }
 }
+
+impl Output for PerfReport {
+    fn output(&self) -> anyhow::Result<String> {
+        let ms = |micros: u64| micros as f64 / 1000.0;
+        let mut w = String::new();
+        write!(w, "Throughput")?;
+        write!(w, "\n  Messages: {}", self.messages())?;
+        write!(w, "\n  Bytes: {}", self.bytes())?;
+        let secs = ms(self.elapsed_micros()) / 1000.0;
+        let mbps = self.throughput_bps() / 1_000_000.0;
+        write!(w, "\n  Duration: {secs:.3} s")?;
+        write!(w, "\n  Rate: {mbps:.3} Mbit/s")?;
+        write!(w, "\nLatency ({} pings)", self.pings())?;
+        write!(w, "\n  p50: {:.3} ms", ms(self.p50_micros()))?;
+        write!(w, "\n  p90: {:.3} ms", ms(self.p90_micros()))?;
+        write!(w, "\n  p99: {:.3} ms", ms(self.p99_micros()))?;
+        write!(w, "\n  max: {:.3} ms", ms(self.max_micros()))?;
+        Ok(w)
+    }
+}
+
+impl Output for TraceReport<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        let ms = |micros: i64| micros as f64 / 1000.0;
+        let mut w = String::new();
+        let mut previous = 0;
+        for (i, hop) in self.hops.iter().enumerate() {
+            let elapsed = hop.elapsed_micros;
+            if i > 0 {
+                writeln!(w)?;
+            }
+            write!(
+                w,
+                "{:>3}  {} => {}  {:.3} ms (+{:.3} ms)",
+                i + 1,
+                hop.address,
+                hop.next,
+                ms(elapsed),
+                ms(elapsed - previous)
+            )?;
+            previous = elapsed;
+        }
+        if !self.hops.is_empty() {
+            writeln!(w)?;
+        }
+        write!(
+            w,
+            "Round trip: {:.3} ms",
+            self.round_trip_micros as f64 / 1000.0
+        )?;
+        Ok(w)
+    }
+}
@@ -0,0 +1,142 @@
+//! Values which are read from the environment or from files
+//!
+//! Tokens, passwords and identities given on the command line end up in the
+//! shell history and in process listings. Arguments parsed with
+//! [`parse_secret`] and the string values of node configuration files can
+//! instead refer to where the value is kept:
+//!
+//! - `@env:NAME` is the value of the environment variable `NAME`
+//! - `@file:PATH` is the content of the file at `PATH`, without the
+//!   trailing newline
+//! - `@@...` is the literal value `@...`
+//!
+//! Any other value is used as is, except by [`parse_secret_reference`]
+//! which only accepts references. In configuration files, only `@env:` and
+//! `@file:` values are resolved, and any other value is used as is, so that
+//! values like the `@admin` action group keep their meaning.
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context as _, Result};
+use serde::de::DeserializeOwned;
+use serde_yaml::Value;
+
+const ENV: &str = "@env:";
+const FILE: &str = "@file:";
+
+/// Argument parser for values which may refer to an environment variable
+/// or a file.
+pub fn parse_secret(input: &str) -> Result<String> {
+    if let Some(name) = input.strip_prefix(ENV) {
+        return std::env::var(name)
+            .with_context(|| format!("Failed to read the environment variable {name}"));
+    }
+    if let Some(path) = input.strip_prefix(FILE) {
+        let s = std::fs::read_to_string(path).with_context(|| format!("Failed to read {path}"))?;
+        return Ok(s.trim_end_matches(&['\r', '\n'][..]).to_string());
+    }
+    if let Some(literal) = input.strip_prefix('@') {
+        if literal.starts_with('@') {
+            return Ok(literal.to_string());
+        }
+        return Err(anyhow!(
+            "invalid reference {input}, expected {ENV}NAME or {FILE}PATH"
+        ));
+    }
+    Ok(input.to_string())
+}
+
+/// Like [`parse_secret`], but refuses values given directly on the command
+/// line. Used for arguments which must never be passed in plain text.
+pub fn parse_secret_reference(input: &str) -> Result<String> {
+    if input.starts_with(ENV) || input.starts_with(FILE) {
+        return parse_secret(input);
+    }
+    Err(anyhow!(
+        "the value must be read from {ENV}NAME or {FILE}PATH, not given in plain text"
+    ))
+}
+
+/// Like [`parse_secret`], for arguments of other types.
+pub fn parse_secret_as<T>(input: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    let s = parse_secret(input)?;
+    T::from_str(&s).map_err(|e| anyhow!("{e}"))
+}
+
+/// Parse a YAML document, resolving its `@env:` and `@file:` values first.
+pub fn from_yaml<T: DeserializeOwned>(s: &str) -> Result<T> {
+    let mut value: Value = serde_yaml::from_str(s)?;
+    interpolate(&mut value)?;
+    Ok(serde_yaml::from_value(value)?)
+}
+
+fn interpolate(value: &mut Value) -> Result<()> {
+    match value {
+        Value::String(s) if s.starts_with(ENV) || s.starts_with(FILE) => *s = parse_secret(s)?,
+        Value::String(_) => {}
+        Value::Sequence(seq) => {
+            for v in seq {
+                interpolate(v)?
+            }
+        }
+        Value::Mapping(map) => {
+            for (_, v) in map.iter_mut() {
+                interpolate(v)?
+            }
+        }
+        Value::Tagged(t) => interpolate(&mut t.value)?,
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn values_are_resolved() {
+        std::env::set_var("OCKAM_SECRET_TEST", "from-env");
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "from-file").unwrap();
+        let path = file.path().display();
+
+        assert_eq!(parse_secret("plain").unwrap(), "plain");
+        assert_eq!(parse_secret("@env:OCKAM_SECRET_TEST").unwrap(), "from-env");
+        assert_eq!(parse_secret(&format!("@file:{path}")).unwrap(), "from-file");
+        assert_eq!(parse_secret("@@env:X").unwrap(), "@env:X");
+        assert!(parse_secret("@env:OCKAM_SECRET_TEST_MISSING").is_err());
+        assert!(parse_secret("@file:/does/not/exist").is_err());
+        assert!(parse_secret("@other").is_err());
+        assert_eq!(
+            parse_secret_reference("@env:OCKAM_SECRET_TEST").unwrap(),
+            "from-env"
+        );
+        assert!(parse_secret_reference("plain").is_err());
+        assert!(parse_secret_reference("@@env:X").is_err());
+        std::env::set_var("OCKAM_SECRET_PORT_TEST", "4000");
+        assert_eq!(
+            parse_secret_as::<u16>("@env:OCKAM_SECRET_PORT_TEST").unwrap(),
+            4000
+        );
+    }
+
+    #[test]
+    fn yaml_values_are_resolved() {
+        std::env::set_var("OCKAM_SECRET_YAML_TEST", "127.0.0.1:4000");
+        let doc = "outlets:\n  - to: \"@env:OCKAM_SECRET_YAML_TEST\"\n    retries: 3\n";
+        let value: Value = from_yaml(doc).unwrap();
+        assert_eq!(value["outlets"][0]["to"], "127.0.0.1:4000");
+        assert_eq!(value["outlets"][0]["retries"], 3);
+
+        let doc = "policies:\n  - action: \"@admin\"\n    resource: \"@@env:X\"\n";
+        let value: Value = from_yaml(doc).unwrap();
+        assert_eq!(value["policies"][0]["action"], "@admin");
+        assert_eq!(value["policies"][0]["resource"], "@@env:X");
+    }
+}
@@ -5,20 +5,33 @@
 use crate::exitcode;
 use crate::util::OckamConfig;
 use anyhow::Context;
-use nix::sys::signal::{self, Signal};
-use nix::unistd::Pid;
+use ockam_api::nodes::config::NodeApiConfig;
 use std::collections::VecDeque;
 use std::io::Stdout;
 use std::process::Stdio;
 use std::{
     env::current_exe,
-    fs::OpenOptions,
+    fs::{File, OpenOptions},
     path::{Path, PathBuf},
     process::Command,
 };
 
+/// File, in the node directory, holding the PID of a supervised node process
+pub const NODE_PID_FILE: &str = "node.pid";
+
+/// File, in the node directory, telling the supervisor that its node was
+/// stopped on purpose and must not be restarted
+pub const NODE_STOPPED_FILE: &str = "node.stopped";
+
+/// Default number of times a crashed background node is restarted
+pub const DEFAULT_MAX_RESTARTS: u32 = 5;
+
 /// Stop a node without deleting its state directory
+#[cfg(unix)]
 pub fn stop(pid: i32, sigkill: bool) -> anyhow::Result<()> {
+    use nix::sys::signal::{self, Signal};
+    use nix::unistd::Pid;
+
     signal::kill(
         Pid::from_raw(pid),
         if sigkill {
@@ -31,10 +44,114 @@ pub fn stop(pid: i32, sigkill: bool) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Stop a node without deleting its state directory
+///
+/// Windows has no equivalent of `SIGTERM` for console processes, so the
+/// process is always terminated, regardless of `sigkill`.
+#[cfg(windows)]
+pub fn stop(pid: i32, _sigkill: bool) -> anyhow::Result<()> {
+    use sysinfo::{Pid, ProcessExt, System, SystemExt};
+
+    let pid = Pid::from(pid as usize);
+    let mut sys = System::new();
+    if !sys.refresh_process(pid) {
+        return Err(anyhow::anyhow!(
+            "Failed to kill process with PID {pid}: not found"
+        ));
+    }
+    match sys.process(pid) {
+        Some(p) if p.kill() => Ok(()),
+        _ => Err(anyhow::anyhow!("Failed to kill process with PID {pid}")),
+    }
+}
+
+/// Check whether a process is still alive
+#[cfg(unix)]
+pub fn is_running(pid: i32) -> bool {
+    // Note: On CI machines where <defunct> processes can occur,
+    // `kill 0 pid` can imply a killed process is okay.
+    nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid), None).is_ok()
+}
+
+/// Check whether a process is still alive
+#[cfg(windows)]
+pub fn is_running(pid: i32) -> bool {
+    use sysinfo::{Pid, System, SystemExt};
+
+    System::new().refresh_process(Pid::from(pid as usize))
+}
+
+/// Detach a background process from the console of the current command
+///
+/// On Unix the process simply outlives its parent.  On Windows it
+/// would otherwise be terminated when the console window is closed.
+fn detach(cmd: &mut Command) -> &mut Command {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP | CREATE_NO_WINDOW);
+    }
+    cmd
+}
+
+/// Stop a background node and the supervisor which restarts it
+///
+/// `pid` is the PID registered in the config, which is the one of the
+/// supervisor for nodes started by [`spawn_node`].
+pub fn stop_node(cfg: &OckamConfig, name: &str, pid: i32, sigkill: bool) -> anyhow::Result<()> {
+    // Tell the supervisor not to restart the node, even if it outlives the
+    // signal or sees its node killed first
+    if let Ok(dir) = cfg.get_node_dir_raw(name) {
+        std::fs::write(dir.join(NODE_STOPPED_FILE), "")
+            .context("failed to mark the node as stopped")?;
+    }
+    stop(pid, sigkill)?;
+    if let Some(child) = supervised_pid(cfg, name).filter(|child| *child != pid) {
+        // The node process may already be gone
+        let _ = stop(child, sigkill);
+    }
+    Ok(())
+}
+
+/// PID of the node process run by the supervisor of a background node
+pub fn supervised_pid(cfg: &OckamConfig, name: &str) -> Option<i32> {
+    cfg.get_node_dir_raw(name)
+        .ok()
+        .and_then(|dir| std::fs::read_to_string(dir.join(NODE_PID_FILE)).ok())
+        .and_then(|s| s.trim().parse().ok())
+}
+
+/// Open the stdout and stderr log files of a node
+pub fn open_log_files(cfg: &OckamConfig, name: &str) -> anyhow::Result<(File, File)> {
+    let (mlog, elog) = cfg
+        .node_log_paths(name)
+        .context("failed to get log paths")?;
+
+    let main_log_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(mlog)
+        .context("failed to open log path")?;
+
+    let stderr_log_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(elog)
+        .context("failed to open stderr log path")?;
+
+    Ok((main_log_file, stderr_log_file))
+}
+
 /// A utility function to spawn a new node into foreground mode
 ///
 /// This function is used by `ockam node create` as well as `ockam
-/// node start`, which attempts to re-use an existing node config
+/// node start`, which attempts to re-use an existing node config.
+///
+/// The node process is started by `ockam node supervise`, which
+/// restarts it if it crashes, up to `max_restarts` times.
 #[allow(clippy::too_many_arguments)]
 pub fn spawn_node(
     cfg: &OckamConfig,
@@ -42,29 +159,63 @@ pub fn spawn_node(
     skip_defaults: bool,
     no_shared_identity: bool,
     enable_credential_checks: bool,
+    api: NodeApiConfig,
     name: &str,
     address: &str,
     project: Option<&Path>,
+    max_restarts: u32,
 ) -> crate::Result<()> {
     // On systems with non-obvious path setups (or during
     // development) re-executing the current binary is a more
     // deterministic way of starting a node.
     let ockam_exe = current_exe().unwrap_or_else(|_| "ockam".into());
 
-    let (mlog, elog) = cfg.node_log_paths(name).unwrap();
+    let (main_log_file, stderr_log_file) = open_log_files(cfg, name)?;
 
-    let main_log_file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(mlog)
-        .context("failed to open log path")?;
+    let mut args = vec![
+        "--no-color".to_string(),
+        "node".to_string(),
+        "supervise".to_string(),
+        "--max-restarts".to_string(),
+        max_restarts.to_string(),
+        name.to_owned(),
+        "--".to_string(),
+    ];
+    args.extend(node_args(
+        verbose,
+        skip_defaults,
+        no_shared_identity,
+        enable_credential_checks,
+        api,
+        name,
+        address,
+        project,
+    ));
 
-    let stderr_log_file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(elog)
-        .context("failed to open stderr log path")?;
+    let child = detach(&mut Command::new(ockam_exe))
+        .args(args)
+        .stdout(main_log_file)
+        .stderr(stderr_log_file)
+        .spawn()?;
+
+    // Update the pid in the config (should we remove this?)
+    cfg.set_node_pid(name, child.id() as i32)?;
+    cfg.persist_config_updates()?;
+
+    Ok(())
+}
 
+/// Arguments to re-execute the ockam CLI in foreground mode for a node
+fn node_args(
+    verbose: u8,
+    skip_defaults: bool,
+    no_shared_identity: bool,
+    enable_credential_checks: bool,
+    api: NodeApiConfig,
+    name: &str,
+    address: &str,
+    project: Option<&Path>,
+) -> Vec<String> {
     let mut args = vec![
         match verbose {
             0 => "-vv".to_string(),
@@ -99,17 +250,14 @@ pub fn spawn_node(
         args.push("--enable-credential-checks".to_string());
     }
 
-    args.push(name.to_owned());
-
-    let child = Command::new(ockam_exe)
-        .args(args)
-        .stdout(main_log_file)
-        .stderr(stderr_log_file)
-        .spawn()?;
+    if api.socket {
+        args.push("--api-socket".to_string());
+    }
 
-    // Update the pid in the config (should we remove this?)
-    cfg.set_node_pid(name, child.id() as i32)?;
-    cfg.persist_config_updates()?;
+    if api.no_tcp {
+        args.push("--no-tcp-api".to_string());
+    }
 
-    Ok(())
+    args.push(name.to_owned());
+    args
 }
@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use crate::node::NodeOpts;
+use crate::util::secret::parse_secret_reference;
+use crate::util::{api, node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+use anyhow::Context as _;
+use clap::Args;
+use dialoguer::Password;
+use ockam::Context;
+use ockam_api::nodes::models::vault::VaultBackup;
+
+/// Export an encrypted backup of a node's vault
+#[derive(Clone, Debug, Args)]
+pub struct ExportCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+
+    /// Path of the file to write the backup to
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Password used to encrypt the backup. Prompted for if not given.
+    /// Must be read from `@env:NAME` or `@file:PATH`, plain values are refused.
+    #[arg(long, value_parser = parse_secret_reference)]
+    pub password: Option<String>,
+}
+
+impl ExportCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (options, cmd): (CommandGlobalOpts, ExportCommand),
+) -> crate::Result<()> {
+    let password = match cmd.password {
+        Some(p) => p,
+        None => Password::new()
+            .with_prompt("Backup password")
+            .with_confirmation("Confirm password", "Passwords don't match")
+            .interact()?,
+    };
+
+    let mut rpc = Rpc::background(&ctx, &options, &cmd.node_opts.api_node)?;
+    rpc.request(api::export_vault(&password)).await?;
+    let backup = rpc.parse_response::<VaultBackup>()?;
+
+    let bytes = minicbor::to_vec(&backup).context("failed to encode the vault backup")?;
+    std::fs::write(&cmd.output, bytes)
+        .with_context(|| format!("failed to write {}", cmd.output.display()))?;
+
+    println!("Vault exported to {}", cmd.output.display());
+    Ok(())
+}
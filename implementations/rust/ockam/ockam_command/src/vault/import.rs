@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use crate::node::NodeOpts;
+use crate::util::secret::parse_secret_reference;
+use crate::util::{api, node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+use anyhow::Context as _;
+use clap::Args;
+use dialoguer::Password;
+use ockam::Context;
+use ockam_api::nodes::models::vault::{KeyList, VaultBackup};
+
+/// Import the keys of a vault backup created with `ockam vault export`
+#[derive(Clone, Debug, Args)]
+pub struct ImportCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+
+    /// Path of the backup file
+    pub input: PathBuf,
+
+    /// Password the backup was encrypted with. Prompted for if not given.
+    /// Must be read from `@env:NAME` or `@file:PATH`, plain values are refused.
+    #[arg(long, value_parser = parse_secret_reference)]
+    pub password: Option<String>,
+}
+
+impl ImportCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (options, cmd): (CommandGlobalOpts, ImportCommand),
+) -> crate::Result<()> {
+    let bytes = std::fs::read(&cmd.input)
+        .with_context(|| format!("failed to read {}", cmd.input.display()))?;
+    let backup: VaultBackup = minicbor::decode(&bytes).context("invalid vault backup")?;
+
+    let password = match cmd.password {
+        Some(p) => p,
+        None => Password::new().with_prompt("Backup password").interact()?,
+    };
+
+    let mut rpc = Rpc::background(&ctx, &options, &cmd.node_opts.api_node)?;
+    rpc.request(api::import_vault(&password, backup)).await?;
+    rpc.parse_and_print_response::<KeyList>()?;
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use crate::node::NodeOpts;
+use crate::util::{api, node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::vault::KeyList;
+
+/// List the keys stored in a node's vault
+#[derive(Clone, Debug, Args)]
+pub struct ListCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+}
+
+impl ListCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (options, cmd): (CommandGlobalOpts, ListCommand),
+) -> crate::Result<()> {
+    let mut rpc = Rpc::background(&ctx, &options, &cmd.node_opts.api_node)?;
+    rpc.request(api::list_vault_keys()).await?;
+    rpc.parse_and_print_response::<KeyList>()?;
+    Ok(())
+}
@@ -1,6 +1,14 @@
 mod create;
+mod export;
+mod import;
+mod list;
+mod show;
 
 pub(crate) use create::CreateCommand;
+use export::ExportCommand;
+use import::ImportCommand;
+use list::ListCommand;
+use show::ShowCommand;
 
 use crate::help;
 use crate::CommandGlobalOpts;
@@ -16,12 +24,20 @@ pub struct VaultCommand {
 #[derive(Clone, Debug, Subcommand)]
 pub enum VaultSubcommand {
     Create(CreateCommand),
+    List(ListCommand),
+    Show(ShowCommand),
+    Export(ExportCommand),
+    Import(ImportCommand),
 }
 
 impl VaultCommand {
-    pub fn run(self, options: CommandGlobalOpts) {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
         match self.subcommand {
             VaultSubcommand::Create(c) => c.run(options),
+            VaultSubcommand::List(c) => c.run(options),
+            VaultSubcommand::Show(c) => c.run(options),
+            VaultSubcommand::Export(c) => c.run(options),
+            VaultSubcommand::Import(c) => c.run(options),
         }
     }
 }
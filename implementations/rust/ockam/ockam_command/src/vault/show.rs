@@ -0,0 +1,32 @@
+use crate::node::NodeOpts;
+use crate::util::{api, node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::vault::KeyInfo;
+
+/// Show a key stored in a node's vault, along with its public key
+#[derive(Clone, Debug, Args)]
+pub struct ShowCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+
+    /// Id of the key
+    pub key_id: String,
+}
+
+impl ShowCommand {
+    pub fn run(self, options: CommandGlobalOpts) -> crate::Result<()> {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (options, cmd): (CommandGlobalOpts, ShowCommand),
+) -> crate::Result<()> {
+    let mut rpc = Rpc::background(&ctx, &options, &cmd.node_opts.api_node)?;
+    rpc.request(api::show_vault_key(&cmd.key_id)).await?;
+    rpc.parse_and_print_response::<KeyInfo>()?;
+    Ok(())
+}
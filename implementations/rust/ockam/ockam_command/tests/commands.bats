@@ -127,6 +127,38 @@ teardown() {
   assert_output --partial "/service/"
 }
 
+@test "create a node serving its API only on a socket, restart it and show it" {
+  run $OCKAM node create n1 --api-socket --no-tcp-api
+  assert_success
+
+  run $OCKAM node show n1
+  assert_success
+  assert_output --partial "/service/api"
+
+  # The node keeps serving its API on the socket after a restart
+  $OCKAM node stop n1
+  run --separate-stderr $OCKAM node start n1
+  assert_success
+
+  run $OCKAM node show n1
+  assert_success
+  assert_output --partial "/service/api"
+}
+
+@test "create node with a declarative setup and show it" {
+  printf 'services:\n  - kind: uppercase\n    address: shout\n' > "$BATS_TMPDIR/node.yaml"
+  $OCKAM node create n1 --setup "$BATS_TMPDIR/node.yaml"
+  sleep 1
+
+  run --separate-stderr $OCKAM message send "hello" --to /node/n1/service/shout
+  assert_success
+  assert_output "HELLO"
+
+  run --separate-stderr $OCKAM node config n1
+  assert_success
+  assert_output --partial "address: shout"
+}
+
 @test "create a secure channel between two nodes and send message through it" {
   $OCKAM node create n1
   $OCKAM node create n2
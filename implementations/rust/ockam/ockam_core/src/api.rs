@@ -2,10 +2,12 @@
 
 use crate::compat::borrow::Cow;
 use crate::compat::rand;
+use crate::compat::string::String;
 use crate::compat::vec::Vec;
 use crate::errcode::{Kind, Origin};
-use crate::Result;
+use crate::{CowBytes, Result};
 use core::fmt::{self, Display, Formatter};
+use minicbor::data::Type;
 use minicbor::encode::{self, Encoder, Write};
 use minicbor::{Decode, Decoder, Encode};
 use tinyvec::ArrayVec;
@@ -39,7 +41,29 @@ pub struct Request<'a> {
     /// how to handle unknown methods.
     #[n(3)] method: Option<Method>,
     /// Indicator if a request body is expected after this header.
-    #[n(4)] has_body: bool
+    #[n(4)] has_body: bool,
+    /// Trace context of the caller.
+    ///
+    /// A W3C `traceparent` value, used to attach the spans created while
+    /// handling the request to the trace of the caller.
+    #[b(5)] trace_context: Option<Cow<'a, str>>,
+    /// Revision of the resource the caller already has.
+    ///
+    /// If the resource did not change since, the response has status
+    /// [`Status::NotModified`] and no body.
+    #[b(6)] revision: Option<Cow<'a, str>>,
+    /// Key identifying the operation of this request across retries.
+    ///
+    /// Services supporting it reply to requests with a key they have
+    /// already seen with the original response, instead of repeating the
+    /// operation.
+    #[b(7)] idempotency_key: Option<Cow<'a, str>>,
+    /// Largest message the caller accepts.
+    ///
+    /// Services supporting it split larger responses into [`Fragment`]s
+    /// which the caller puts back together with a [`Reassembly`]. Such
+    /// services also accept requests sent in fragments.
+    #[n(8)] max_message_size: Option<u32>
 }
 
 /// The response header.
@@ -66,23 +90,29 @@ pub struct Response {
     /// how to handle unknown codes.
     #[n(3)] status: Option<Status>,
     /// Indicator if a response body is expected after this header.
-    #[n(4)] has_body: bool
+    #[n(4)] has_body: bool,
+    /// Revision of the resource in the response body, if it has any.
+    #[n(5)] revision: Option<String>
 }
 
 /// Create an error response because the request path was unknown.
 pub fn unknown_path<'a>(r: &'a Request) -> ResponseBuilder<Error<'a>> {
-    bad_request(r, "unknown path")
+    bad_request(r, "unknown path").with_code(ErrorCode::UnknownPath)
 }
 
 /// Create an error response because the request method was unknown or not allowed.
 pub fn invalid_method<'a>(r: &'a Request) -> ResponseBuilder<Error<'a>> {
     match r.method() {
         Some(m) => {
-            let e = Error::new(r.path()).with_method(m);
+            let e = Error::new(r.path())
+                .with_method(m)
+                .with_code(ErrorCode::InvalidMethod);
             Response::builder(r.id(), Status::MethodNotAllowed).body(e)
         }
         None => {
-            let e = Error::new(r.path()).with_message("unknown method");
+            let e = Error::new(r.path())
+                .with_message("unknown method")
+                .with_code(ErrorCode::InvalidMethod);
             Response::not_implemented(r.id()).body(e)
         }
     }
@@ -90,7 +120,9 @@ pub fn invalid_method<'a>(r: &'a Request) -> ResponseBuilder<Error<'a>> {
 
 /// Create an error response with status forbidden and the given message.
 pub fn forbidden<'a>(r: &'a Request, m: &'a str) -> ResponseBuilder<Error<'a>> {
-    let mut e = Error::new(r.path()).with_message(m);
+    let mut e = Error::new(r.path())
+        .with_message(m)
+        .with_code(ErrorCode::Unauthorized);
     if let Some(m) = r.method() {
         e = e.with_method(m)
     }
@@ -99,16 +131,34 @@ pub fn forbidden<'a>(r: &'a Request, m: &'a str) -> ResponseBuilder<Error<'a>> {
 
 /// Create a generic bad request response.
 pub fn bad_request<'a>(r: &'a Request, msg: &'a str) -> ResponseBuilder<Error<'a>> {
-    let mut e = Error::new(r.path()).with_message(msg);
+    let mut e = Error::new(r.path())
+        .with_message(msg)
+        .with_code(ErrorCode::BadRequest);
     if let Some(m) = r.method() {
         e = e.with_method(m)
     }
     Response::bad_request(r.id()).body(e)
 }
 
+/// Create a bad request response for a body that doesn't match its schema.
+pub fn invalid_body<'a>(r: &'a Request, m: &'a SchemaMismatch) -> ResponseBuilder<Error<'a>> {
+    let mut e = Error::new(r.path())
+        .with_message(m.reason())
+        .with_code(ErrorCode::InvalidBody);
+    if let Some(m) = r.method() {
+        e = e.with_method(m)
+    }
+    if let Some(f) = m.field() {
+        e = e.with_field(f)
+    }
+    Response::bad_request(r.id()).body(e)
+}
+
 /// Create an internal server error response
 pub fn internal_error<'a>(r: &'a Request, msg: &'a str) -> ResponseBuilder<Error<'a>> {
-    let mut e = Error::new(r.path()).with_message(msg);
+    let mut e = Error::new(r.path())
+        .with_message(msg)
+        .with_code(ErrorCode::Internal);
     if let Some(m) = r.method() {
         e = e.with_method(m)
     }
@@ -151,6 +201,7 @@ impl Display for Method {
 #[cbor(index_only)]
 pub enum Status {
     #[n(200)] Ok,
+    #[n(304)] NotModified,
     #[n(400)] BadRequest,
     #[n(401)] Unauthorized,
     #[n(403)] Forbidden,
@@ -165,6 +216,7 @@ impl Display for Status {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         f.write_str(match self {
             Status::Ok => "200 Ok",
+            Status::NotModified => "304 NotModified",
             Status::BadRequest => "400 BadRequest",
             Status::Unauthorized => "401 Unauthorized",
             Status::Forbidden => "403 Forbidden",
@@ -184,6 +236,12 @@ impl Id {
     }
 }
 
+impl From<u32> for Id {
+    fn from(n: u32) -> Self {
+        Id(n)
+    }
+}
+
 impl From<Id> for u32 {
     fn from(n: Id) -> Self {
         n.0
@@ -205,6 +263,10 @@ impl<'a> Request<'a> {
             method: Some(method),
             path: path.into(),
             has_body,
+            trace_context: None,
+            revision: None,
+            idempotency_key: None,
+            max_message_size: None,
         }
     }
 
@@ -254,6 +316,22 @@ impl<'a> Request<'a> {
     pub fn has_body(&self) -> bool {
         self.has_body
     }
+
+    pub fn trace_context(&self) -> Option<&str> {
+        self.trace_context.as_deref()
+    }
+
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+
+    pub fn idempotency_key(&self) -> Option<&str> {
+        self.idempotency_key.as_deref()
+    }
+
+    pub fn max_message_size(&self) -> Option<u32> {
+        self.max_message_size
+    }
 }
 
 impl Response {
@@ -265,6 +343,7 @@ impl Response {
             re,
             status: Some(status),
             has_body,
+            revision: None,
         }
     }
 
@@ -287,6 +366,10 @@ impl Response {
         Response::builder(re, Status::NotFound)
     }
 
+    pub fn not_modified(re: Id) -> ResponseBuilder {
+        Response::builder(re, Status::NotModified)
+    }
+
     pub fn not_implemented(re: Id) -> ResponseBuilder {
         Response::builder(re, Status::NotImplemented)
     }
@@ -318,6 +401,10 @@ impl Response {
     pub fn has_body(&self) -> bool {
         self.has_body
     }
+
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
 }
 
 /// An error type used in response bodies.
@@ -339,6 +426,10 @@ pub struct Error<'a> {
     #[n(2)] method: Option<Method>,
     /// The actual error message.
     #[b(3)] message: Option<Cow<'a, str>>,
+    /// The request body field this error is about.
+    #[b(4)] field: Option<Cow<'a, str>>,
+    /// The numeric [`ErrorCode`] of this error.
+    #[n(5)] code: Option<u16>,
 }
 
 impl<'a> Error<'a> {
@@ -349,6 +440,8 @@ impl<'a> Error<'a> {
             method: None,
             path: Some(path.into()),
             message: None,
+            field: None,
+            code: None,
         }
     }
 
@@ -362,6 +455,16 @@ impl<'a> Error<'a> {
         self
     }
 
+    pub fn with_field<S: Into<Cow<'a, str>>>(mut self, f: S) -> Self {
+        self.field = Some(f.into());
+        self
+    }
+
+    pub fn with_code(mut self, c: ErrorCode) -> Self {
+        self.code = Some(c.as_u16());
+        self
+    }
+
     pub fn path(&self) -> Option<&str> {
         self.path.as_deref()
     }
@@ -373,6 +476,105 @@ impl<'a> Error<'a> {
     pub fn message(&self) -> Option<&str> {
         self.message.as_deref()
     }
+
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    /// The error code, unless missing or unknown to this version.
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.code.and_then(ErrorCode::from_u16)
+    }
+}
+
+/// Stable codes identifying the cause of an [`Error`].
+///
+/// Messages may change between releases, codes don't. Clients should
+/// use them to handle specific failures. Codes are grouped by tens:
+/// request errors, access errors, credential errors and server errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    BadRequest,
+    UnknownPath,
+    InvalidMethod,
+    InvalidBody,
+    NotFound,
+    SecureChannelRequired,
+    Unauthorized,
+    UnknownOneTimeCode,
+    ExpiredOneTimeCode,
+    UnknownIssuer,
+    InvalidCredential,
+    AttributeMismatch,
+    RevokedSubject,
+    Internal,
+    Cancelled,
+}
+
+impl ErrorCode {
+    pub fn as_u16(self) -> u16 {
+        match self {
+            ErrorCode::BadRequest => 10,
+            ErrorCode::UnknownPath => 11,
+            ErrorCode::InvalidMethod => 12,
+            ErrorCode::InvalidBody => 13,
+            ErrorCode::NotFound => 14,
+            ErrorCode::SecureChannelRequired => 20,
+            ErrorCode::Unauthorized => 21,
+            ErrorCode::UnknownOneTimeCode => 22,
+            ErrorCode::ExpiredOneTimeCode => 23,
+            ErrorCode::UnknownIssuer => 30,
+            ErrorCode::InvalidCredential => 31,
+            ErrorCode::AttributeMismatch => 32,
+            ErrorCode::RevokedSubject => 33,
+            ErrorCode::Internal => 50,
+            ErrorCode::Cancelled => 51,
+        }
+    }
+
+    pub fn from_u16(n: u16) -> Option<Self> {
+        Some(match n {
+            10 => ErrorCode::BadRequest,
+            11 => ErrorCode::UnknownPath,
+            12 => ErrorCode::InvalidMethod,
+            13 => ErrorCode::InvalidBody,
+            14 => ErrorCode::NotFound,
+            20 => ErrorCode::SecureChannelRequired,
+            21 => ErrorCode::Unauthorized,
+            22 => ErrorCode::UnknownOneTimeCode,
+            23 => ErrorCode::ExpiredOneTimeCode,
+            30 => ErrorCode::UnknownIssuer,
+            31 => ErrorCode::InvalidCredential,
+            32 => ErrorCode::AttributeMismatch,
+            33 => ErrorCode::RevokedSubject,
+            50 => ErrorCode::Internal,
+            51 => ErrorCode::Cancelled,
+            _ => return None,
+        })
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ErrorCode::BadRequest => "bad-request",
+            ErrorCode::UnknownPath => "unknown-path",
+            ErrorCode::InvalidMethod => "invalid-method",
+            ErrorCode::InvalidBody => "invalid-body",
+            ErrorCode::NotFound => "not-found",
+            ErrorCode::SecureChannelRequired => "secure-channel-required",
+            ErrorCode::Unauthorized => "unauthorized",
+            ErrorCode::UnknownOneTimeCode => "unknown-one-time-code",
+            ErrorCode::ExpiredOneTimeCode => "expired-one-time-code",
+            ErrorCode::UnknownIssuer => "unknown-issuer",
+            ErrorCode::InvalidCredential => "invalid-credential",
+            ErrorCode::AttributeMismatch => "attribute-mismatch",
+            ErrorCode::RevokedSubject => "revoked-subject",
+            ErrorCode::Internal => "internal",
+            ErrorCode::Cancelled => "cancelled",
+        })
+    }
 }
 
 /// Path segments, i.e. '/'-separated string slices.
@@ -414,6 +616,26 @@ impl<'a, T> RequestBuilder<'a, T> {
         self
     }
 
+    pub fn trace_context<S: Into<Cow<'a, str>>>(mut self, tc: S) -> Self {
+        self.header.trace_context = Some(tc.into());
+        self
+    }
+
+    pub fn revision<S: Into<Cow<'a, str>>>(mut self, rev: S) -> Self {
+        self.header.revision = Some(rev.into());
+        self
+    }
+
+    pub fn idempotency_key<S: Into<Cow<'a, str>>>(mut self, key: S) -> Self {
+        self.header.idempotency_key = Some(key.into());
+        self
+    }
+
+    pub fn max_message_size(mut self, n: u32) -> Self {
+        self.header.max_message_size = Some(n);
+        self
+    }
+
     pub fn header(&self) -> &Request<'a> {
         &self.header
     }
@@ -477,6 +699,11 @@ impl<T> ResponseBuilder<T> {
         self
     }
 
+    pub fn revision<S: Into<String>>(mut self, rev: S) -> Self {
+        self.header.revision = Some(rev.into());
+        self
+    }
+
     pub fn header(&self) -> &Response {
         &self.header
     }
@@ -486,6 +713,14 @@ impl<T> ResponseBuilder<T> {
     }
 }
 
+impl<'a> ResponseBuilder<Error<'a>> {
+    /// Set the code of the error in the response body.
+    pub fn with_code(mut self, c: ErrorCode) -> Self {
+        self.body = self.body.map(|e| e.with_code(c));
+        self
+    }
+}
+
 impl ResponseBuilder<()> {
     pub fn body<T: Encode<()>>(self, b: T) -> ResponseBuilder<T> {
         let mut b = ResponseBuilder {
@@ -560,6 +795,306 @@ pub fn assert_response_match<'a>(struct_name: impl Into<Option<&'a str>>, cbor:
     }
 }
 
+/// A request body that doesn't match its schema rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaMismatch {
+    field: Option<String>,
+    reason: String,
+}
+
+impl SchemaMismatch {
+    /// The field of the body that doesn't match, if it could be found.
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl Display for SchemaMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(field) = &self.field {
+            write!(f, "invalid field `{}`: ", field)?
+        }
+        f.write_str(&self.reason)
+    }
+}
+
+/// Check that the next item of `dec` matches the schema rule `struct_name`.
+///
+/// This is a no-op unless the "schema-validation" feature is enabled, so
+/// that API services can validate incoming bodies in release builds and
+/// reply with a [`SchemaMismatch`] instead of a decoding error.
+#[allow(unused_variables)]
+pub fn validate_body(struct_name: &str, dec: &Decoder<'_>) -> Result<(), SchemaMismatch> {
+    #[cfg(feature = "schema-validation")]
+    {
+        use crate::compat::string::ToString;
+        use cddl_cat::validate_cbor_bytes;
+
+        let body = &dec.input()[dec.position()..];
+        if let Err(e) = validate_cbor_bytes(struct_name, SCHEMA, body) {
+            return Err(SchemaMismatch {
+                field: schema::mismatched_field(struct_name, body),
+                reason: e.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "schema-validation")]
+mod schema {
+    use super::SCHEMA;
+    use crate::compat::string::{String, ToString};
+    use crate::compat::vec::Vec;
+    use cddl_cat::validate_cbor_bytes;
+    use minicbor::Decoder;
+
+    /// A field of a map rule, e.g. ` 1: space_id` or `?2: text`.
+    struct Field<'a> {
+        key: u64,
+        rule: &'a str,
+        optional: bool,
+    }
+
+    impl Field<'_> {
+        /// Fields are named after their rule if it is defined in the schema.
+        fn name(&self) -> String {
+            if is_rule(self.rule) {
+                self.rule.to_string()
+            } else {
+                self.key.to_string()
+            }
+        }
+    }
+
+    /// Find the field of a map body that makes it mismatch its rule.
+    ///
+    /// A field mismatches if it is required and missing, or if its value
+    /// doesn't match the rule it refers to.
+    pub(super) fn mismatched_field(struct_name: &str, body: &[u8]) -> Option<String> {
+        let fields = fields(struct_name)?;
+        let mut dec = Decoder::new(body);
+        let len = dec.map().ok()??;
+        let mut present = Vec::new();
+        for _ in 0..len {
+            let key = dec.u64().ok()?;
+            let start = dec.position();
+            dec.skip().ok()?;
+            let value = &body[start..dec.position()];
+            if let Some(f) = fields.iter().find(|f| f.key == key) {
+                if is_rule(f.rule) && validate_cbor_bytes(f.rule, SCHEMA, value).is_err() {
+                    return Some(f.name());
+                }
+            }
+            present.push(key)
+        }
+        fields
+            .iter()
+            .find(|f| !f.optional && !present.contains(&f.key))
+            .map(Field::name)
+    }
+
+    /// The fields of the map rule `name`, as written in the schema.
+    fn fields(name: &str) -> Option<Vec<Field<'static>>> {
+        let mut lines = SCHEMA.lines().skip_while(|l| !is_definition(l, name));
+        if !lines.next()?.trim_end().ends_with('{') {
+            return None;
+        }
+        let fields = lines
+            .take_while(|l| !l.trim_start().starts_with('}'))
+            .filter_map(|l| {
+                let l = l.split(";;").next()?.trim().trim_end_matches(',');
+                let (optional, l) = match l.strip_prefix('?') {
+                    Some(l) => (true, l),
+                    None => (false, l),
+                };
+                let (key, rule) = l.split_once(':')?;
+                Some(Field {
+                    key: key.trim().parse().ok()?,
+                    rule: rule.trim(),
+                    optional,
+                })
+            })
+            .collect();
+        Some(fields)
+    }
+
+    fn is_rule(name: &str) -> bool {
+        SCHEMA.lines().any(|l| is_definition(l, name))
+    }
+
+    fn is_definition(line: &str, name: &str) -> bool {
+        match line.strip_prefix(name) {
+            Some(rest) => rest.trim_start().starts_with('='),
+            None => false,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::super::validate_body;
+        use minicbor::{Decoder, Encoder};
+
+        #[test]
+        fn names_mismatched_field() {
+            let mut buf = Vec::new();
+            Encoder::new(&mut buf)
+                .map(1)
+                .unwrap()
+                .u8(3)
+                .unwrap()
+                .u8(1)
+                .unwrap();
+            let e = validate_body("error", &Decoder::new(&buf)).unwrap_err();
+            assert_eq!(Some("message"), e.field());
+
+            let mut buf = Vec::new();
+            Encoder::new(&mut buf)
+                .map(1)
+                .unwrap()
+                .u8(3)
+                .unwrap()
+                .str("oops")
+                .unwrap();
+            assert!(validate_body("error", &Decoder::new(&buf)).is_ok());
+        }
+    }
+}
+
+/// Address an encoded response to the request `re`.
+///
+/// This is used to replay a response to a retried request.
+pub fn readdress(data: &[u8], re: Id) -> Result<Vec<u8>> {
+    let mut dec = Decoder::new(data);
+    let mut hdr: Response = dec.decode()?;
+    hdr.id = Id::fresh();
+    hdr.re = re;
+    let mut buf = Vec::new();
+    Encoder::new(&mut buf).encode(&hdr)?;
+    buf.extend_from_slice(&data[dec.position()..]);
+    Ok(buf)
+}
+
+/// Room left in each fragment for its own fields and for the routing
+/// information of the transport message carrying it.
+pub const FRAGMENT_OVERHEAD: usize = 1024;
+
+/// Largest number of fragments a request or response may be split into.
+pub const MAX_FRAGMENTS: usize = 4096;
+
+/// A piece of an encoded request or response that is too large to be sent
+/// at once.
+///
+/// Contrary to request and response headers fragments are CBOR arrays,
+/// which is how receivers tell them apart from complete messages (cf.
+/// [`is_fragment`]).
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+pub struct Fragment<'a> {
+    /// The identifier of the request, or of the request corresponding to
+    /// the response.
+    #[n(0)] re: Id,
+    /// The position of this fragment.
+    #[n(1)] index: u32,
+    /// The number of fragments of the message.
+    #[n(2)] count: u32,
+    /// The bytes of the message in this fragment.
+    #[b(3)] data: CowBytes<'a>
+}
+
+/// Split an encoded request or response into messages of at most
+/// `max_message_size` bytes.
+///
+/// A message that fits is returned as it is, otherwise it is split into
+/// [`Fragment`]s.
+pub fn fragment(re: Id, data: &[u8], max_message_size: usize) -> Result<Vec<Vec<u8>>> {
+    if data.len() <= max_message_size {
+        return Ok(vec![data.to_vec()]);
+    }
+    let size = max_message_size.saturating_sub(FRAGMENT_OVERHEAD).max(64);
+    let count = (data.len() + size - 1) / size;
+    if count > MAX_FRAGMENTS {
+        let msg = "message is too large to be fragmented";
+        return Err(crate::Error::new(Origin::Application, Kind::Invalid, msg));
+    }
+    data.chunks(size)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let f = Fragment {
+                re,
+                index: i as u32,
+                count: count as u32,
+                data: chunk.into(),
+            };
+            let mut buf = Vec::new();
+            Encoder::new(&mut buf).encode(&f)?;
+            Ok(buf)
+        })
+        .collect()
+}
+
+/// Check if a received message is a [`Fragment`] instead of a request or
+/// response.
+pub fn is_fragment(data: &[u8]) -> bool {
+    matches!(Decoder::new(data).datatype(), Ok(Type::Array))
+}
+
+/// The fragments of a request or response received so far.
+#[derive(Debug, Default)]
+pub struct Reassembly {
+    re: Option<Id>,
+    parts: Vec<Option<Vec<u8>>>,
+    missing: usize,
+}
+
+impl Reassembly {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a fragment and return the message once it is complete.
+    pub fn push(&mut self, data: &[u8]) -> Result<Option<Vec<u8>>> {
+        let f: Fragment = Decoder::new(data).decode()?;
+        let count = f.count as usize;
+        match self.re {
+            None if count == 0 || count > MAX_FRAGMENTS => {
+                let msg = "invalid number of fragments";
+                return Err(crate::Error::new(Origin::Application, Kind::Protocol, msg));
+            }
+            None => {
+                self.re = Some(f.re);
+                self.parts = vec![None; count];
+                self.missing = count
+            }
+            Some(re) if re != f.re || self.parts.len() != count => {
+                let msg = "fragment of another message";
+                return Err(crate::Error::new(Origin::Application, Kind::Protocol, msg));
+            }
+            Some(_) => {}
+        }
+        let part = match self.parts.get_mut(f.index as usize) {
+            Some(p) => p,
+            None => {
+                let msg = "fragment index out of range";
+                return Err(crate::Error::new(Origin::Application, Kind::Protocol, msg));
+            }
+        };
+        if part.is_none() {
+            *part = Some(f.data.into_owned());
+            self.missing -= 1
+        }
+        if self.missing > 0 {
+            return Ok(None);
+        }
+        self.re = None;
+        Ok(Some(self.parts.drain(..).flatten().flatten().collect()))
+    }
+}
+
 /// Decode response header only, without processing the message body.
 pub fn is_ok(label: &str, buf: &[u8]) -> Result<()> {
     let mut d = Decoder::new(buf);
@@ -648,3 +1183,38 @@ impl<C> Encode<C> for Cbor<'_> {
             .map_err(encode::Error::write)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fragments_are_reassembled() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+        let re = Id::fresh();
+
+        let unsplit = fragment(re, &data, data.len()).unwrap();
+        assert_eq!(unsplit, vec![data.clone()]);
+
+        let fragments = fragment(re, &data, FRAGMENT_OVERHEAD + 1000).unwrap();
+        assert_eq!(fragments.len(), 10);
+        assert!(fragments.iter().all(|f| is_fragment(f)));
+
+        let mut reassembly = Reassembly::new();
+        let (last, rest) = fragments.split_last().unwrap();
+        for f in rest.iter().rev() {
+            assert!(reassembly.push(f).unwrap().is_none())
+        }
+        assert_eq!(reassembly.push(last).unwrap(), Some(data));
+
+        let other = fragment(Id::fresh(), &[0; 3000], FRAGMENT_OVERHEAD + 1000).unwrap();
+        reassembly.push(&fragments[0]).unwrap();
+        assert!(reassembly.push(&other[0]).is_err())
+    }
+
+    #[test]
+    fn responses_are_not_fragments() {
+        let r = Response::ok(Id::fresh()).body("hello").to_vec().unwrap();
+        assert!(!is_fragment(&r))
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    compat::vec::Vec, errcode::Kind, errcode::Origin, Address, Decodable, Encodable, Error,
+    Message, Result, TransportMessage,
+};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the payload of a traced message, followed by its
+/// [`TracedPayload`].
+///
+/// Tracing is marked in the payload rather than in the message version, so
+/// that traced messages remain v1 messages for nodes which don't know about
+/// tracing, like the Elixir implementation, which pass them on unchanged.
+/// Every node forwarding a traced message appends a [`TraceHop`] to it.
+pub const TRACE_MARKER: [u8; 16] = *b"\0ockam.trace.v1\0";
+
+/// A hop recorded by a node forwarding a traced message.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub struct TraceHop {
+    /// Address of the worker which forwarded the message.
+    pub address: Address,
+    /// Address the message was forwarded to.
+    pub next: Address,
+    /// Time of forwarding, in microseconds since the Unix epoch, as given
+    /// by the clock of the forwarding node.
+    pub timestamp_micros: u64,
+}
+
+/// Payload of a traced message, after the [`TRACE_MARKER`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq, Message)]
+pub struct TracedPayload {
+    /// Hops recorded so far, in order.
+    pub hops: Vec<TraceHop>,
+    /// The actual payload of the message.
+    pub payload: Vec<u8>,
+}
+
+impl TracedPayload {
+    /// Read the traced payload of a message payload.
+    pub fn from_payload(payload: &[u8]) -> Result<Self> {
+        match payload.strip_prefix(&TRACE_MARKER[..]) {
+            Some(traced) => Self::decode(traced),
+            None => Err(Error::new_without_cause(Origin::Core, Kind::Invalid)),
+        }
+    }
+
+    /// The message payload carrying this traced payload.
+    pub fn to_payload(&self) -> Result<Vec<u8>> {
+        let mut payload = TRACE_MARKER.to_vec();
+        payload.extend(self.encode()?);
+        Ok(payload)
+    }
+}
+
+impl TransportMessage {
+    /// Create a new v1 transport message recording the hops it goes through.
+    pub fn v1_traced(
+        onward_route: impl Into<crate::Route>,
+        return_route: impl Into<crate::Route>,
+        payload: Vec<u8>,
+    ) -> Result<Self> {
+        let traced = TracedPayload {
+            hops: Vec::new(),
+            payload,
+        };
+        Ok(Self::v1(onward_route, return_route, traced.to_payload()?))
+    }
+
+    /// Whether this message records the hops it goes through.
+    pub fn is_traced(&self) -> bool {
+        self.payload.starts_with(&TRACE_MARKER)
+    }
+
+    /// Append a hop to the payload of a traced message.
+    ///
+    /// Messages which aren't traced are left unchanged.
+    pub fn record_hop(&mut self, hop: TraceHop) -> Result<()> {
+        if !self.is_traced() {
+            return Ok(());
+        }
+        let mut traced = TracedPayload::from_payload(&self.payload)?;
+        traced.hops.push(hop);
+        self.payload = traced.to_payload()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route;
+
+    #[test]
+    fn hops_are_only_recorded_on_traced_messages() {
+        let hop = TraceHop {
+            address: "a".into(),
+            next: "b".into(),
+            timestamp_micros: 42,
+        };
+
+        let mut plain = TransportMessage::v1(route!["b"], route!["a"], b"hello".to_vec());
+        plain.record_hop(hop.clone()).unwrap();
+        assert!(!plain.is_traced());
+        assert_eq!(plain.payload, b"hello".to_vec());
+
+        let mut traced =
+            TransportMessage::v1_traced(route!["b"], route!["a"], b"hello".to_vec()).unwrap();
+        traced.record_hop(hop.clone()).unwrap();
+        assert!(traced.is_traced());
+        assert_eq!(traced.version, 1);
+        let payload = TracedPayload::from_payload(&traced.payload).unwrap();
+        assert_eq!(payload.hops, vec![hop]);
+        assert_eq!(payload.payload, b"hello".to_vec());
+    }
+}
@@ -26,6 +26,11 @@ pub const AES128_SECRET_LENGTH_U32: u32 = 16;
 /// AES128 private key length.
 pub const AES128_SECRET_LENGTH_USIZE: usize = 16;
 
+/// NIST P-256 private key length.
+pub const NIST_P256_SECRET_LENGTH_U32: u32 = 32;
+/// NIST P-256 uncompressed public key length.
+pub const NIST_P256_PUBLIC_LENGTH_USIZE: usize = 65;
+
 cfg_if! {
     if #[cfg(not(feature = "alloc"))] {
         /// Secret Key Vector. The maximum size is 32 bytes.
@@ -187,6 +192,8 @@ pub enum SecretType {
     /// BLS key
     #[cfg(feature = "bls")]
     #[n(5)] Bls,
+    /// NIST P-256 key, held outside of the vault, e.g. in AWS KMS
+    #[n(6)] NistP256,
 }
 
 /// All possible [`SecretKey`] persistence types
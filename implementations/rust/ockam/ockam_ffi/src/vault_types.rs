@@ -73,6 +73,7 @@ impl From<SecretAttributes> for FfiSecretAttributes {
             SecretType::Ed25519 => 3,
             #[cfg(feature = "bls")]
             SecretType::Bls => 4,
+            SecretType::NistP256 => 5,
         };
 
         let persistence = match attrs.persistence() {
@@ -95,6 +96,7 @@ impl TryFrom<FfiSecretAttributes> for SecretAttributes {
             3 => Ok(SecretType::Ed25519),
             #[cfg(feature = "bls")]
             4 => Ok(SecretType::Bls),
+            5 => Ok(SecretType::NistP256),
             _ => Err(FfiError::InvalidParam),
         }?;
 
@@ -13,7 +13,16 @@ pub trait AuthenticatedStorage: AsyncTryClone + Send + Sync + 'static {
 
     /// Delete entry
     async fn del(&self, id: &str, key: &str) -> Result<()>;
+
+    /// Delete entry, returning its value
+    ///
+    /// Must be atomic: of concurrent calls for the same entry, at most one
+    /// gets the value.
+    async fn take(&self, id: &str, key: &str) -> Result<Option<Vec<u8>>>;
 }
 
 /// In-memory impl
 pub mod mem;
+
+/// Encrypting wrapper
+pub mod encrypted;
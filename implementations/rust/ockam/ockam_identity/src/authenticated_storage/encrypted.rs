@@ -0,0 +1,192 @@
+use super::AuthenticatedStorage;
+use crate::{IdentityError, IdentityVault};
+use hmac::{Hmac, Mac, NewMac};
+use ockam_core::compat::{boxed::Box, rand::random, string::String, sync::Arc, vec::Vec};
+use ockam_core::vault::KeyId;
+use ockam_core::{async_trait, AsyncTryClone, Result};
+use sha2::Sha256;
+
+const NONCE_LEN: usize = 12;
+
+/// Storage encrypting the values of another [`AuthenticatedStorage`]
+///
+/// Values are encrypted with AES-GCM using a key held by the vault, and are
+/// bound to their id and key so that they cannot be swapped around in the
+/// underlying storage. Ids and keys can be hidden as well with
+/// [`EncryptedStorage::with_hashed_keys`].
+///
+/// The AES key can be created with
+/// `SecretAttributes::new(SecretType::Aes, SecretPersistence::Persistent, AES256_SECRET_LENGTH_U32)`.
+/// Its [`KeyId`] must be kept to read the storage again.
+#[derive(AsyncTryClone)]
+#[async_try_clone(crate = "ockam_core")]
+pub struct EncryptedStorage<S: AuthenticatedStorage, V: IdentityVault> {
+    storage: S,
+    vault: V,
+    key_id: KeyId,
+    /// HMAC-SHA256 keyed with the hash key, ready to hash ids and keys.
+    hmac: Option<Arc<Hmac<Sha256>>>,
+}
+
+impl<S: AuthenticatedStorage, V: IdentityVault> EncryptedStorage<S, V> {
+    /// Encrypt the values of `storage` with the AES key `key_id` of `vault`.
+    pub fn new(storage: S, vault: V, key_id: KeyId) -> Self {
+        Self {
+            storage,
+            vault,
+            key_id,
+            hmac: None,
+        }
+    }
+
+    /// Replace ids and keys with their HMAC-SHA256, keyed with the secret
+    /// `hash_key_id` of the vault.
+    ///
+    /// The secret must be exportable, e.g. a 32 bytes `SecretType::Buffer`.
+    /// It is exported once, here, and kept in memory by the storage.
+    pub async fn with_hashed_keys(mut self, hash_key_id: &KeyId) -> Result<Self> {
+        let secret = self.vault.secret_export(hash_key_id).await?;
+        // HMAC accepts keys of any length, this does not fail.
+        let hmac = Hmac::<Sha256>::new_from_slice(secret.as_ref())
+            .map_err(|_| IdentityError::InvalidInternalState)?;
+        self.hmac = Some(Arc::new(hmac));
+        Ok(self)
+    }
+
+    /// The id and key used in the underlying storage.
+    fn location(&self, id: &str, key: &str) -> (String, String) {
+        match &self.hmac {
+            Some(hmac) => {
+                let id_hash = digest(hmac, id.as_bytes());
+                // The key is hashed together with its id, so that equal keys
+                // of different ids cannot be correlated.
+                let key_hash = digest(hmac, &aad(id, key));
+                (hex::encode(id_hash), hex::encode(key_hash))
+            }
+            None => (id.into(), key.into()),
+        }
+    }
+
+    /// Decrypt a value read from the underlying storage.
+    async fn decrypt(&self, id: &str, key: &str, data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < NONCE_LEN {
+            return Err(IdentityError::InvalidEncryptedValue.into());
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        self.vault
+            .aead_aes_gcm_decrypt(&self.key_id, ciphertext, nonce, &aad(id, key))
+            .await
+    }
+}
+
+fn digest(hmac: &Hmac<Sha256>, data: &[u8]) -> [u8; 32] {
+    let mut mac = hmac.as_ref().clone();
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+/// Associated data binding a value to its location.
+fn aad(id: &str, key: &str) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(id.len() + key.len() + 1);
+    aad.extend_from_slice(id.as_bytes());
+    aad.push(0);
+    aad.extend_from_slice(key.as_bytes());
+    aad
+}
+
+#[async_trait]
+impl<S: AuthenticatedStorage, V: IdentityVault> AuthenticatedStorage for EncryptedStorage<S, V> {
+    async fn get(&self, id: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        let (sid, skey) = self.location(id, key);
+        match self.storage.get(&sid, &skey).await? {
+            Some(data) => self.decrypt(id, key, &data).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn set(&self, id: &str, key: String, val: Vec<u8>) -> Result<()> {
+        let (sid, skey) = self.location(id, &key);
+        let nonce: [u8; NONCE_LEN] = random();
+        let ciphertext = self
+            .vault
+            .aead_aes_gcm_encrypt(&self.key_id, &val, &nonce, &aad(id, &key))
+            .await?;
+        let mut data = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+        self.storage.set(&sid, skey, data).await
+    }
+
+    async fn del(&self, id: &str, key: &str) -> Result<()> {
+        let (sid, skey) = self.location(id, key);
+        self.storage.del(&sid, &skey).await
+    }
+
+    async fn take(&self, id: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        let (sid, skey) = self.location(id, key);
+        match self.storage.take(&sid, &skey).await? {
+            Some(data) => self.decrypt(id, key, &data).await.map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::authenticated_storage::mem::InMemoryStorage;
+    use ockam_core::vault::{
+        SecretAttributes, SecretPersistence, SecretType, SecretVault, AES256_SECRET_LENGTH_U32,
+    };
+    use ockam_vault::Vault;
+
+    #[tokio::test]
+    async fn values_are_encrypted() {
+        let vault = Vault::create();
+        let key_id = vault
+            .secret_generate(SecretAttributes::new(
+                SecretType::Aes,
+                SecretPersistence::Ephemeral,
+                AES256_SECRET_LENGTH_U32,
+            ))
+            .await
+            .unwrap();
+        let hash_key_id = vault
+            .secret_generate(SecretAttributes::new(
+                SecretType::Buffer,
+                SecretPersistence::Ephemeral,
+                32,
+            ))
+            .await
+            .unwrap();
+
+        let inner = InMemoryStorage::new();
+        let storage = EncryptedStorage::new(inner.clone(), vault, key_id);
+        storage
+            .set("alice", "role".into(), b"admin".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.get("alice", "role").await.unwrap().as_deref(),
+            Some(&b"admin"[..])
+        );
+        let raw = inner.get("alice", "role").await.unwrap().unwrap();
+        assert!(!raw.windows(5).any(|w| w == b"admin"));
+
+        // A value copied to another location cannot be decrypted
+        inner.set("bob", "role".into(), raw).await.unwrap();
+        assert!(storage.get("bob", "role").await.is_err());
+
+        let storage = storage.with_hashed_keys(&hash_key_id).await.unwrap();
+        storage
+            .set("alice", "role".into(), b"member".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.get("alice", "role").await.unwrap().as_deref(),
+            Some(&b"member"[..])
+        );
+        storage.del("alice", "role").await.unwrap();
+        assert!(storage.get("alice", "role").await.unwrap().is_none());
+    }
+}
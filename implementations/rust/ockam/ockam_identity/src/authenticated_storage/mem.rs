@@ -57,4 +57,19 @@ impl AuthenticatedStorage for InMemoryStorage {
         }
         Ok(())
     }
+
+    async fn take(&self, id: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        let mut m = self.map.write().unwrap();
+        let val = match m.get_mut(id) {
+            Some(a) => {
+                let val = a.remove(key);
+                if a.is_empty() {
+                    m.remove(id);
+                }
+                val
+            }
+            None => None,
+        };
+        Ok(val)
+    }
 }
@@ -2,6 +2,10 @@ mod encryptor;
 pub(crate) use encryptor::*;
 mod decryptor;
 pub(crate) use decryptor::*;
+mod key_exchange;
+pub use key_exchange::*;
+mod limits;
+pub use limits::*;
 mod listener;
 pub(crate) use listener::*;
 mod messages;
@@ -12,8 +16,10 @@ pub mod access_control;
 mod local_info;
 pub use local_info::*;
 
+pub use ockam_channel::SecureChannelCounters;
+
 use crate::authenticated_storage::AuthenticatedStorage;
-use crate::{Identity, IdentityVault};
+use crate::{Identity, IdentityError, IdentityVault};
 use core::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{Address, AsyncTryClone, Result, Route};
@@ -27,11 +33,69 @@ impl<V: IdentityVault> Identity<V> {
     ) -> Result<()> {
         let identity_clone = self.async_try_clone().await?;
         let storage_clone = storage.async_try_clone().await?;
-        let listener = IdentityChannelListener::new(trust_policy, identity_clone, storage_clone);
+        let listener = IdentityChannelListener::new(
+            trust_policy,
+            identity_clone,
+            storage_clone,
+            SecureChannelListenerLimits::default(),
+            KeyExchangeMode::Classic,
+        );
         self.ctx.start_worker(address.into(), listener).await?;
         Ok(())
     }
 
+    /// Create a secure channel listener which rejects new channels
+    /// exceeding the given limits.
+    pub async fn create_secure_channel_listener_extended(
+        &self,
+        address: impl Into<Address>,
+        trust_policy: impl TrustPolicy,
+        storage: &impl AuthenticatedStorage,
+        limits: SecureChannelListenerLimits,
+    ) -> Result<()> {
+        let identity_clone = self.async_try_clone().await?;
+        let storage_clone = storage.async_try_clone().await?;
+        let listener = IdentityChannelListener::new(
+            trust_policy,
+            identity_clone,
+            storage_clone,
+            limits,
+            KeyExchangeMode::Classic,
+        );
+        self.ctx.start_worker(address.into(), listener).await?;
+        Ok(())
+    }
+
+    /// Create a secure channel listener accepting the given key exchange.
+    /// Initiators which don't support it fall back to the classic one.
+    ///
+    /// Returns the channels accepted by the listener, to close the ones of
+    /// an identity which is no longer trusted.
+    pub async fn create_secure_channel_listener_with_key_exchange(
+        &self,
+        address: impl Into<Address>,
+        trust_policy: impl TrustPolicy,
+        storage: &impl AuthenticatedStorage,
+        limits: SecureChannelListenerLimits,
+        key_exchange: KeyExchangeMode,
+    ) -> Result<ListenerChannels> {
+        if !key_exchange.is_supported() {
+            return Err(IdentityError::KeyExchangeUnsupported.into());
+        }
+        let identity_clone = self.async_try_clone().await?;
+        let storage_clone = storage.async_try_clone().await?;
+        let listener = IdentityChannelListener::new(
+            trust_policy,
+            identity_clone,
+            storage_clone,
+            limits,
+            key_exchange,
+        );
+        let channels = listener.channels();
+        self.ctx.start_worker(address.into(), listener).await?;
+        Ok(channels)
+    }
+
     pub async fn create_secure_channel(
         &self,
         route: impl Into<Route>,
@@ -48,6 +112,8 @@ impl<V: IdentityVault> Identity<V> {
             storage_clone,
             Arc::new(trust_policy),
             Duration::from_secs(120),
+            Default::default(),
+            KeyExchangeMode::Classic,
         )
         .await
     }
@@ -69,6 +135,64 @@ impl<V: IdentityVault> Identity<V> {
             storage_clone,
             Arc::new(trust_policy),
             timeout,
+            Default::default(),
+            KeyExchangeMode::Classic,
+        )
+        .await
+    }
+
+    /// Create a secure channel counting the messages it rejects, e.g.
+    /// replayed or tampered with, in `counters`.
+    pub async fn create_secure_channel_with_counters(
+        &self,
+        route: impl Into<Route>,
+        trust_policy: impl TrustPolicy,
+        storage: &impl AuthenticatedStorage,
+        timeout: Duration,
+        counters: Arc<SecureChannelCounters>,
+    ) -> Result<Address> {
+        let identity_clone = self.async_try_clone().await?;
+        let storage_clone = storage.async_try_clone().await?;
+
+        DecryptorWorker::create_initiator(
+            &self.ctx,
+            route.into(),
+            identity_clone,
+            storage_clone,
+            Arc::new(trust_policy),
+            timeout,
+            counters,
+            KeyExchangeMode::Classic,
+        )
+        .await
+    }
+
+    /// Create a secure channel offering the given key exchange. The
+    /// classic key exchange is used if the listener doesn't support it.
+    pub async fn create_secure_channel_with_key_exchange(
+        &self,
+        route: impl Into<Route>,
+        trust_policy: impl TrustPolicy,
+        storage: &impl AuthenticatedStorage,
+        timeout: Duration,
+        counters: Arc<SecureChannelCounters>,
+        key_exchange: KeyExchangeMode,
+    ) -> Result<Address> {
+        if !key_exchange.is_supported() {
+            return Err(IdentityError::KeyExchangeUnsupported.into());
+        }
+        let identity_clone = self.async_try_clone().await?;
+        let storage_clone = storage.async_try_clone().await?;
+
+        DecryptorWorker::create_initiator(
+            &self.ctx,
+            route.into(),
+            identity_clone,
+            storage_clone,
+            Arc::new(trust_policy),
+            timeout,
+            counters,
+            key_exchange,
         )
         .await
     }
@@ -86,8 +210,11 @@ mod test {
     use crate::Identity;
     use core::sync::atomic::{AtomicU8, Ordering};
     use core::time::Duration;
+    use ockam_channel::SecureChannel;
     use ockam_core::compat::sync::Arc;
-    use ockam_core::{route, Any, Result, Routed, Worker};
+    use ockam_core::{route, Any, Encodable, Result, Routed, Worker};
+    use ockam_key_exchange_core::NewKeyExchanger;
+    use ockam_key_exchange_xx::XXNewKeyExchanger;
     use ockam_node::{Context, WorkerBuilder};
     use ockam_vault::Vault;
     use tokio::time::sleep;
@@ -335,6 +462,146 @@ mod test {
         }
     }
 
+    #[ockam_macros::test]
+    async fn test_listener_limits(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        let limits = SecureChannelListenerLimits::new().with_max_channels_per_identity(1);
+        bob.create_secure_channel_listener_extended(
+            "listener",
+            TrustEveryonePolicy,
+            &bob_storage,
+            limits,
+        )
+        .await?;
+
+        alice
+            .create_secure_channel("listener", TrustEveryonePolicy, &alice_storage)
+            .await?;
+
+        // The initiator is told why the listener refused the channel
+        let res = alice
+            .create_secure_channel_extended(
+                "listener",
+                TrustEveryonePolicy,
+                &alice_storage,
+                Duration::from_secs(10),
+            )
+            .await;
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            IdentityError::SecureChannelIdentityLimitReached.to_string()
+        );
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_abandoned_handshakes_are_not_counted(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        let limits = SecureChannelListenerLimits::new().with_max_channels(1);
+        bob.create_secure_channel_listener_extended(
+            "listener",
+            TrustEveryonePolicy,
+            &bob_storage,
+            limits,
+        )
+        .await?;
+
+        // Complete the key exchange, but never authenticate
+        for _ in 0..3 {
+            let initiator = XXNewKeyExchanger::new(vault.clone()).initiator().await?;
+            SecureChannel::create_extended(
+                ctx,
+                route!["listener"],
+                Some(Address::random_local().encode()?),
+                initiator,
+                vault.clone(),
+                Default::default(),
+            )
+            .await?;
+        }
+
+        alice
+            .create_secure_channel("listener", TrustEveryonePolicy, &alice_storage)
+            .await?;
+
+        let res = alice
+            .create_secure_channel_extended(
+                "listener",
+                TrustEveryonePolicy,
+                &alice_storage,
+                Duration::from_secs(10),
+            )
+            .await;
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            IdentityError::SecureChannelLimitReached.to_string()
+        );
+
+        ctx.stop().await
+    }
+
+    #[cfg(feature = "pq-hybrid")]
+    #[ockam_macros::test]
+    async fn test_hybrid_key_exchange_falls_back(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        bob.create_secure_channel_listener_with_key_exchange(
+            "hybrid_listener",
+            TrustEveryonePolicy,
+            &bob_storage,
+            SecureChannelListenerLimits::default(),
+            KeyExchangeMode::Hybrid,
+        )
+        .await?;
+        bob.create_secure_channel_listener("classic_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+
+        for (listener, key_exchange) in [
+            ("hybrid_listener", KeyExchangeMode::Hybrid),
+            ("hybrid_listener", KeyExchangeMode::Classic),
+            ("classic_listener", KeyExchangeMode::Hybrid),
+        ] {
+            let channel = alice
+                .create_secure_channel_with_key_exchange(
+                    listener,
+                    TrustEveryonePolicy,
+                    &alice_storage,
+                    Duration::from_secs(10),
+                    Default::default(),
+                    key_exchange,
+                )
+                .await?;
+
+            ctx.send(route![channel, ctx.address()], "Hello, Bob!".to_string())
+                .await?;
+            let msg = ctx.receive::<String>().await?.take();
+            assert_eq!("Hello, Bob!", msg.body());
+        }
+
+        ctx.stop().await
+    }
+
     #[allow(non_snake_case)]
     #[ockam_macros::test]
     async fn access_control__known_participant__should_pass_messages(
@@ -1,15 +1,15 @@
 use crate::authenticated_storage::AuthenticatedStorage;
 use crate::{
-    EncryptorWorker, Identity, IdentityChannelMessage, IdentityError, IdentityIdentifier,
-    IdentitySecureChannelLocalInfo, IdentityVault, PublicIdentity, SecureChannelTrustInfo,
-    TrustPolicy,
+    ChannelRefusal, ChannelSlot, ChannelSlots, EncryptorWorker, Identity, IdentityChannelMessage,
+    IdentityError, IdentityIdentifier, IdentitySecureChannelLocalInfo, IdentityVault,
+    KeyExchangeMode, PublicIdentity, SecureChannelTrustInfo, TrustPolicy,
 };
 use core::future::Future;
 use core::pin::Pin;
 use core::time::Duration;
 use ockam_channel::{
-    CreateResponderChannelMessage, KeyExchangeCompleted, SecureChannel, SecureChannelDecryptor,
-    SecureChannelInfo,
+    CreateResponderChannelMessage, KeyExchangeCompleted, SecureChannel, SecureChannelCounters,
+    SecureChannelDecryptor, SecureChannelInfo, SecureChannelKeyExchanger,
 };
 use ockam_core::async_trait;
 use ockam_core::compat::rand::random;
@@ -17,16 +17,33 @@ use ockam_core::compat::{boxed::Box, sync::Arc, vec::Vec};
 use ockam_core::vault::Signature;
 use ockam_core::{
     route, Address, Any, Decodable, Encodable, LocalMessage, Message, Result, Route, Routed,
-    TransportMessage, Worker,
+    TransportMessage, Worker, LOCAL,
 };
 use ockam_key_exchange_core::NewKeyExchanger;
+#[cfg(feature = "pq-hybrid")]
+use ockam_key_exchange_xx::HybridNewKeyExchanger;
 use ockam_key_exchange_xx::XXNewKeyExchanger;
 use ockam_node::Context;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
+/// Prefix of the address at which initiators run their handshake, telling
+/// responders that the initiator waits for a confirmation of the channel.
+///
+/// The address is sent in the first handshake message in any case, so
+/// responders which don't confirm channels, such as older Rust nodes or the
+/// Elixir implementation, accept these initiators unchanged and answer them
+/// with a plain [`IdentityChannelMessage::Request`].
+const CONFIRM_ADDRESS_PREFIX: &str = "confirm.";
+
+/// Outcome of the handshake, reported by an initiator to its creator.
 #[derive(Serialize, Deserialize, Message)]
-pub(crate) struct AuthenticationConfirmation(pub Address);
+pub(crate) enum AuthenticationOutcome {
+    /// The channel is ready, with its encryptor at the given address.
+    Confirmed(Address),
+    /// The responder refused the channel.
+    Refused(ChannelRefusal),
+}
 
 trait StartSecureChannelFuture: Future<Output = Result<SecureChannelInfo>> + Send + 'static {}
 
@@ -35,6 +52,40 @@ impl<T> StartSecureChannelFuture for T where
 {
 }
 
+async fn start_channel<V: IdentityVault>(
+    ctx: &Context,
+    route: Route,
+    custom_payload: Vec<u8>,
+    initiator: impl SecureChannelKeyExchanger,
+    vault: V,
+    counters: Arc<SecureChannelCounters>,
+) -> Result<Pin<Box<dyn StartSecureChannelFuture>>> {
+    let temp_ctx = ctx.new_detached(Address::random_local()).await?;
+    Ok(Box::pin(async move {
+        SecureChannel::create_extended(
+            &temp_ctx,
+            route,
+            Some(custom_payload),
+            initiator,
+            vault,
+            counters,
+        )
+        .await
+    }))
+}
+
+async fn start_responder<V: IdentityVault>(
+    ctx: &Context,
+    address: Address,
+    responder: impl SecureChannelKeyExchanger,
+    kex_callback_address: Address,
+    vault: V,
+) -> Result<()> {
+    let regular_decryptor =
+        SecureChannelDecryptor::new_responder(responder, Some(kex_callback_address), vault).await?;
+    ctx.start_worker(vec![address], regular_decryptor).await
+}
+
 struct InitiatorStartChannel {
     channel_future: Pin<Box<dyn StartSecureChannelFuture>>, // TODO: Replace with generic
     callback_address: Address,
@@ -42,6 +93,8 @@ struct InitiatorStartChannel {
 
 struct ResponderWaitForKex {
     first_responder_address: Address,
+    /// Whether the initiator waits for a confirmation
+    confirm: bool,
 }
 
 struct InitiatorSendIdentity {
@@ -52,6 +105,14 @@ struct InitiatorSendIdentity {
 struct ResponderWaitForIdentity {
     auth_hash: [u8; 32],
     local_secure_channel_address: Address,
+    confirm: bool,
+}
+
+struct InitiatorWaitForConfirm {
+    local_secure_channel_address: Address,
+    remote_identity_secure_channel_address: Address,
+    their_identity_id: IdentityIdentifier,
+    callback_address: Address,
 }
 
 #[derive(Clone)]
@@ -66,6 +127,7 @@ enum State {
     ResponderWaitForKex(ResponderWaitForKex),
     InitiatorSendIdentity(InitiatorSendIdentity),
     ResponderWaitForIdentity(ResponderWaitForIdentity),
+    InitiatorWaitForConfirm(InitiatorWaitForConfirm),
     Initialized(Initialized),
 }
 
@@ -76,6 +138,10 @@ pub(crate) struct DecryptorWorker<V: IdentityVault, S: AuthenticatedStorage> {
     identity: Identity<V>,
     storage: S,
     trust_policy: Arc<dyn TrustPolicy>,
+    /// Set for responders started by a listener with limits
+    slots: Option<ChannelSlots>,
+    /// Slot taken by the channel once its initiator is authenticated
+    slot: Option<ChannelSlot>,
     state: Option<State>,
 }
 
@@ -87,23 +153,43 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
         storage: S,
         trust_policy: Arc<dyn TrustPolicy>,
         timeout: Duration,
+        counters: Arc<SecureChannelCounters>,
+        key_exchange: KeyExchangeMode,
     ) -> Result<Address> {
         let child_address = Address::random_local();
         let mut child_ctx = ctx.new_detached(child_address.clone()).await?;
 
-        let self_address: Address = random();
+        let self_address = Address::new(
+            LOCAL,
+            format!(
+                "{}{}",
+                CONFIRM_ADDRESS_PREFIX,
+                random::<Address>().address()
+            ),
+        );
 
         let vault = identity.vault.async_try_clone().await?;
-        let initiator = XXNewKeyExchanger::new(vault.async_try_clone().await?)
-            .initiator()
-            .await?;
         // Create regular secure channel and set self address as first responder
         let custom_payload = self_address.encode()?;
-        let temp_ctx = ctx.new_detached(Address::random_local()).await?;
-        let channel_future = Box::pin(async move {
-            SecureChannel::create_extended(&temp_ctx, route, Some(custom_payload), initiator, vault)
-                .await
-        });
+        let channel_future = match key_exchange {
+            KeyExchangeMode::Classic => {
+                let initiator = XXNewKeyExchanger::new(vault.async_try_clone().await?)
+                    .initiator()
+                    .await?;
+                start_channel(ctx, route, custom_payload, initiator, vault, counters).await?
+            }
+            #[cfg(feature = "pq-hybrid")]
+            KeyExchangeMode::Hybrid => {
+                let initiator = HybridNewKeyExchanger::new(vault.async_try_clone().await?)
+                    .initiator()
+                    .await?;
+                start_channel(ctx, route, custom_payload, initiator, vault, counters).await?
+            }
+            #[cfg(not(feature = "pq-hybrid"))]
+            KeyExchangeMode::Hybrid => {
+                return Err(IdentityError::KeyExchangeUnsupported.into());
+            }
+        };
 
         let state = State::InitiatorStartChannel(InitiatorStartChannel {
             channel_future,
@@ -117,6 +203,8 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             identity,
             trust_policy,
             storage,
+            slots: None,
+            slot: None,
             state: Some(state),
         };
 
@@ -127,14 +215,16 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             &self_address
         );
 
-        let encryptor_address = child_ctx
-            .receive_timeout::<AuthenticationConfirmation>(timeout.as_secs())
+        let outcome = child_ctx
+            .receive_timeout::<AuthenticationOutcome>(timeout.as_secs())
             .await?
             .take()
-            .body()
-            .0;
+            .body();
 
-        Ok(encryptor_address)
+        match outcome {
+            AuthenticationOutcome::Confirmed(encryptor_address) => Ok(encryptor_address),
+            AuthenticationOutcome::Refused(refusal) => Err(IdentityError::from(refusal).into()),
+        }
     }
 
     pub(crate) async fn create_responder(
@@ -142,6 +232,8 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
         identity: Identity<V>,
         storage: S,
         trust_policy: Arc<dyn TrustPolicy>,
+        slots: Option<ChannelSlots>,
+        key_exchange: KeyExchangeMode,
         msg: Routed<CreateResponderChannelMessage>,
     ) -> Result<()> {
         let return_route = msg.return_route();
@@ -153,12 +245,16 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             .as_ref()
             .ok_or(IdentityError::SecureChannelCannotBeAuthenticated)?;
         let first_responder_address = Address::decode(custom_payload)?;
+        let confirm = first_responder_address
+            .address()
+            .starts_with(CONFIRM_ADDRESS_PREFIX);
 
         let self_address: Address = random();
 
         let vault = identity.vault.async_try_clone().await?;
         let state = State::ResponderWaitForKex(ResponderWaitForKex {
             first_responder_address,
+            confirm,
         });
 
         let kex_callback_address = Address::random_local();
@@ -168,6 +264,8 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             identity,
             trust_policy,
             storage,
+            slots,
+            slot: None,
             kex_callback_address: Some(kex_callback_address.clone()),
             state: Some(state),
         };
@@ -185,17 +283,39 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
 
         let regular_responder_address = Address::random_local();
 
-        let responder = XXNewKeyExchanger::new(vault.async_try_clone().await?)
-            .responder()
-            .await?;
-
-        let vault = vault.async_try_clone().await?;
-        let regular_decryptor =
-            SecureChannelDecryptor::new_responder(responder, Some(kex_callback_address), vault)
+        match key_exchange {
+            KeyExchangeMode::Classic => {
+                let responder = XXNewKeyExchanger::new(vault.async_try_clone().await?)
+                    .responder()
+                    .await?;
+                start_responder(
+                    ctx,
+                    regular_responder_address.clone(),
+                    responder,
+                    kex_callback_address,
+                    vault,
+                )
                 .await?;
-
-        ctx.start_worker(vec![regular_responder_address.clone()], regular_decryptor)
-            .await?;
+            }
+            #[cfg(feature = "pq-hybrid")]
+            KeyExchangeMode::Hybrid => {
+                let responder = HybridNewKeyExchanger::new(vault.async_try_clone().await?)
+                    .responder()
+                    .await?;
+                start_responder(
+                    ctx,
+                    regular_responder_address.clone(),
+                    responder,
+                    kex_callback_address,
+                    vault,
+                )
+                .await?;
+            }
+            #[cfg(not(feature = "pq-hybrid"))]
+            KeyExchangeMode::Hybrid => {
+                return Err(IdentityError::KeyExchangeUnsupported.into());
+            }
+        }
 
         onward_route.step()?;
         onward_route.modify().prepend(regular_responder_address);
@@ -221,9 +341,17 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             .create_signature(&kex_msg.auth_hash(), None)
             .await?;
         let identity = self.identity.export().await?;
-        let msg = IdentityChannelMessage::Request {
-            identity,
-            signature: signature.as_ref().to_vec(),
+        let signature = signature.as_ref().to_vec();
+        let msg = if state.confirm {
+            IdentityChannelMessage::ConfirmingRequest {
+                identity,
+                signature,
+            }
+        } else {
+            IdentityChannelMessage::Request {
+                identity,
+                signature,
+            }
         };
         ctx.send_from_address(
             route![kex_msg.address().clone(), state.first_responder_address],
@@ -236,6 +364,7 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
         self.state = Some(State::ResponderWaitForIdentity(ResponderWaitForIdentity {
             auth_hash: kex_msg.auth_hash(),
             local_secure_channel_address: kex_msg.address().clone(),
+            confirm: state.confirm,
         }));
 
         Ok(())
@@ -254,105 +383,161 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             return Err(IdentityError::UnknownChannelMsgDestination.into());
         }
 
-        let body = IdentityChannelMessage::decode(msg.payload())?;
-
         // Wait for responder to send us his Identity and Identity Proof.
         // In case of using Noise XX this is m4 message.
-        if let IdentityChannelMessage::Request {
-            identity,
-            signature,
-        } = body
+        // Responders confirming channels answer with a ConfirmingRequest.
+        let (identity, signature, confirming) = match IdentityChannelMessage::decode(msg.payload())?
         {
-            debug!("Received Authentication request");
+            IdentityChannelMessage::Request {
+                identity,
+                signature,
+            } => (identity, signature, false),
+            IdentityChannelMessage::ConfirmingRequest {
+                identity,
+                signature,
+            } => (identity, signature, true),
+            _ => return Err(IdentityError::InvalidSecureChannelInternalState.into()),
+        };
+        debug!("Received Authentication request");
+
+        let their_identity = PublicIdentity::import(&identity, &self.identity.vault).await?;
+        let their_identity_id = their_identity.identifier();
+
+        // Verify responder posses their Identity key
+        let verified = their_identity
+            .verify_signature(
+                &Signature::new(signature),
+                &state.channel.auth_hash(),
+                None,
+                &self.identity.vault,
+            )
+            .await?;
 
-            let their_identity = PublicIdentity::import(&identity, &self.identity.vault).await?;
-            let their_identity_id = their_identity.identifier();
+        if !verified {
+            return Err(IdentityError::SecureChannelVerificationFailed.into());
+        }
 
-            // Verify responder posses their Identity key
-            let verified = their_identity
-                .verify_signature(
-                    &Signature::new(signature),
-                    &state.channel.auth_hash(),
-                    None,
-                    &self.identity.vault,
-                )
-                .await?;
+        self.identity
+            .update_known_identity(their_identity_id, &their_identity, &self.storage)
+            .await?;
 
-            if !verified {
-                return Err(IdentityError::SecureChannelVerificationFailed.into());
-            }
+        info!(
+            "Initiator verified SecureChannel from: {}",
+            their_identity_id
+        );
 
-            self.identity
-                .update_known_identity(their_identity_id, &their_identity, &self.storage)
-                .await?;
+        // Check our TrustPolicy
+        let trust_info = SecureChannelTrustInfo::new(their_identity_id.clone());
+        let trusted = self.trust_policy.check(&trust_info).await?;
+        if !trusted {
+            // TODO: Shutdown? Communicate error?
+            return Err(IdentityError::SecureChannelTrustCheckFailed.into());
+        }
+        info!(
+            "Initiator checked trust policy for SecureChannel from: {}",
+            their_identity_id
+        );
 
-            info!(
-                "Initiator verified SecureChannel from: {}",
-                their_identity_id
-            );
+        // Prove we posses our Identity key
+        let identity = self.identity.export().await?;
+        let signature = self
+            .identity
+            .create_signature(&state.channel.auth_hash(), None)
+            .await?;
 
-            // Check our TrustPolicy
-            let trust_info = SecureChannelTrustInfo::new(their_identity_id.clone());
-            let trusted = self.trust_policy.check(&trust_info).await?;
-            if !trusted {
-                // TODO: Shutdown? Communicate error?
-                return Err(IdentityError::SecureChannelTrustCheckFailed.into());
-            }
-            info!(
-                "Initiator checked trust policy for SecureChannel from: {}",
-                their_identity_id
-            );
+        let auth_msg = IdentityChannelMessage::Response {
+            identity,
+            signature: signature.as_ref().to_vec(),
+        };
 
-            // Prove we posses our Identity key
-            let identity = self.identity.export().await?;
-            let signature = self
-                .identity
-                .create_signature(&state.channel.auth_hash(), None)
-                .await?;
+        let remote_identity_secure_channel_address = return_route.recipient();
 
-            let auth_msg = IdentityChannelMessage::Response {
-                identity,
-                signature: signature.as_ref().to_vec(),
-            };
+        ctx.send_from_address(return_route, auth_msg, self.self_address.clone())
+            .await?;
+        debug!("Sent Authentication response");
 
-            let remote_identity_secure_channel_address = return_route.recipient();
+        let state = InitiatorWaitForConfirm {
+            local_secure_channel_address: state.channel.address(),
+            remote_identity_secure_channel_address,
+            their_identity_id: their_identity_id.clone(),
+            callback_address: state.callback_address,
+        };
 
-            ctx.send_from_address(return_route, auth_msg, self.self_address.clone())
-                .await?;
-            debug!("Sent Authentication response");
+        if confirming {
+            self.state = Some(State::InitiatorWaitForConfirm(state));
+            Ok(())
+        } else {
+            // The responder doesn't confirm channels, the channel is ready
+            self.initialize_initiator(ctx, state).await
+        }
+    }
 
-            let encryptor_address = Address::random_local();
+    async fn handle_confirm(
+        &mut self,
+        ctx: &mut <Self as Worker>::Context,
+        msg: Routed<<Self as Worker>::Message>,
+        state: InitiatorWaitForConfirm,
+    ) -> Result<()> {
+        // Ensure message came from dedicated SecureChannel
+        if msg.return_route().next()? != &state.local_secure_channel_address {
+            return Err(IdentityError::UnknownChannelMsgDestination.into());
+        }
 
-            self.state = Some(State::Initialized(Initialized {
-                local_secure_channel_address: state.channel.address(),
-                their_identity_id: their_identity_id.clone(),
-                encryptor_address: encryptor_address.clone(),
-            }));
+        match IdentityChannelMessage::decode(msg.payload())? {
+            IdentityChannelMessage::Confirm => {
+                debug!("Received Authentication confirmation");
+                self.initialize_initiator(ctx, state).await
+            }
+            IdentityChannelMessage::Refuse(refusal) => {
+                warn!(?refusal, "Responder refused SecureChannel");
+                ctx.send(
+                    state.callback_address,
+                    AuthenticationOutcome::Refused(refusal),
+                )
+                .await?;
+                ctx.stop_worker(self.self_address.clone()).await
+            }
+            _ => Err(IdentityError::InvalidSecureChannelInternalState.into()),
+        }
+    }
 
-            let encryptor = EncryptorWorker::new(
-                self.is_initiator,
-                remote_identity_secure_channel_address,
-                state.channel.address(),
-            );
+    async fn initialize_initiator(
+        &mut self,
+        ctx: &mut <Self as Worker>::Context,
+        state: InitiatorWaitForConfirm,
+    ) -> Result<()> {
+        let encryptor_address = Address::random_local();
 
-            ctx.start_worker(encryptor_address.clone(), encryptor)
-                .await?;
+        self.state = Some(State::Initialized(Initialized {
+            local_secure_channel_address: state.local_secure_channel_address.clone(),
+            their_identity_id: state.their_identity_id,
+            encryptor_address: encryptor_address.clone(),
+        }));
 
-            info!(
-                "Initialized IdentitySecureChannel Initiator at local: {}, remote: {}",
-                &encryptor_address, &self.self_address
-            );
+        let encryptor = EncryptorWorker::new(
+            self.is_initiator,
+            state.remote_identity_secure_channel_address,
+            state.local_secure_channel_address,
+        );
 
-            ctx.send(
-                state.callback_address,
-                AuthenticationConfirmation(encryptor_address),
-            )
+        ctx.start_worker(encryptor_address.clone(), encryptor)
             .await?;
 
-            Ok(())
-        } else {
-            Err(IdentityError::InvalidSecureChannelInternalState.into())
+        info!(
+            "Initialized IdentitySecureChannel Initiator at local: {}, remote: {}",
+            &encryptor_address, &self.self_address
+        );
+
+        let outcome = AuthenticationOutcome::Confirmed(encryptor_address.clone());
+        if let Err(e) = ctx.send(state.callback_address, outcome).await {
+            // Whoever created the channel gave up on it, e.g. because it
+            // timed out or another channel was used instead
+            debug!("Stopping abandoned IdentitySecureChannel: {}", e);
+            ctx.stop_worker(encryptor_address).await?;
+            return ctx.stop_worker(self.self_address.clone()).await;
         }
+
+        Ok(())
     }
 
     async fn handle_receive_identity(
@@ -417,9 +602,37 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
                 their_identity_id
             );
 
+            let encryptor_address = Address::random_local();
+
+            if let Some(slots) = &self.slots {
+                let addresses = vec![self.self_address.clone(), encryptor_address.clone()];
+                match slots.acquire(their_identity_id, addresses) {
+                    Ok(slot) => self.slot = Some(slot),
+                    Err(refusal) => {
+                        // Initiators which don't wait for a confirmation
+                        // only see the channel being closed
+                        if state.confirm {
+                            let msg = IdentityChannelMessage::Refuse(refusal);
+                            ctx.send_from_address(return_route, msg, self.self_address.clone())
+                                .await?;
+                        }
+                        ctx.stop_worker(self.self_address.clone()).await?;
+                        return Err(IdentityError::from(refusal).into());
+                    }
+                }
+            }
+
             let remote_identity_secure_channel_address = return_route.recipient();
 
-            let encryptor_address = Address::random_local();
+            if state.confirm {
+                ctx.send_from_address(
+                    return_route,
+                    IdentityChannelMessage::Confirm,
+                    self.self_address.clone(),
+                )
+                .await?;
+                debug!("Sent Authentication confirmation");
+            }
 
             self.state = Some(State::Initialized(Initialized {
                 local_secure_channel_address: state.local_secure_channel_address.clone(),
@@ -574,6 +787,13 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Worker for DecryptorWorker<V, S>
                     return Err(IdentityError::UnknownChannelMsgDestination.into());
                 }
             }
+            State::InitiatorWaitForConfirm(s) => {
+                if msg_addr == self.self_address {
+                    self.handle_confirm(ctx, msg, s).await?;
+                } else {
+                    return Err(IdentityError::UnknownChannelMsgDestination.into());
+                }
+            }
             State::Initialized(s) => {
                 if msg_addr == self.self_address {
                     self.handle_decrypt(ctx, msg, s).await?;
@@ -0,0 +1,26 @@
+/// Key exchange performed by the secure channel handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyExchangeMode {
+    /// Noise XX over X25519.
+    Classic,
+    /// Noise XX over X25519 with a Kyber768 key encapsulation mixed into
+    /// the channel keys. Falls back to `Classic` when the other side does
+    /// not support it. Requires the `pq-hybrid` feature.
+    Hybrid,
+}
+
+impl Default for KeyExchangeMode {
+    fn default() -> Self {
+        KeyExchangeMode::Classic
+    }
+}
+
+impl KeyExchangeMode {
+    /// Whether this build can perform the key exchange.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            KeyExchangeMode::Classic => true,
+            KeyExchangeMode::Hybrid => cfg!(feature = "pq-hybrid"),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{ChannelRefusal, IdentityIdentifier};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::compat::vec::Vec;
+use ockam_core::Address;
+use tracing::warn;
+
+/// Limits on the secure channels a listener accepts
+///
+/// Channels count towards the limits once the listener authenticated their
+/// initiator, until the channel is stopped, so that handshakes abandoned
+/// halfway never take a slot. Initiators exceeding a limit are refused.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SecureChannelListenerLimits {
+    max_channels: Option<usize>,
+    max_channels_per_identity: Option<usize>,
+}
+
+impl SecureChannelListenerLimits {
+    /// No limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of concurrent channels of the listener.
+    pub fn with_max_channels(mut self, max: usize) -> Self {
+        self.max_channels = Some(max);
+        self
+    }
+
+    /// Limit the number of concurrent channels initiated by the same identity.
+    pub fn with_max_channels_per_identity(mut self, max: usize) -> Self {
+        self.max_channels_per_identity = Some(max);
+        self
+    }
+
+    pub fn max_channels(&self) -> Option<usize> {
+        self.max_channels
+    }
+
+    pub fn max_channels_per_identity(&self) -> Option<usize> {
+        self.max_channels_per_identity
+    }
+}
+
+#[derive(Default)]
+struct Counts {
+    total: usize,
+    /// Worker addresses of the channels of each identity.
+    per_identity: BTreeMap<IdentityIdentifier, Vec<Vec<Address>>>,
+}
+
+/// Channel counters of a listener, shared with its responders.
+#[derive(Clone)]
+pub(crate) struct ChannelSlots {
+    limits: SecureChannelListenerLimits,
+    counts: Arc<Mutex<Counts>>,
+}
+
+impl ChannelSlots {
+    pub(crate) fn new(limits: SecureChannelListenerLimits) -> Self {
+        Self {
+            limits,
+            counts: Arc::new(Mutex::new(Counts::default())),
+        }
+    }
+
+    /// Take a slot for a new channel initiated by `identity`, made of the
+    /// workers at `addresses`, if neither the listener nor the identity
+    /// reached their limit.
+    pub(crate) fn acquire(
+        &self,
+        identity: &IdentityIdentifier,
+        addresses: Vec<Address>,
+    ) -> Result<ChannelSlot, ChannelRefusal> {
+        let mut counts = self.counts.lock().unwrap();
+        if let Some(max) = self.limits.max_channels {
+            if counts.total >= max {
+                warn!(%max, "secure channel listener is full");
+                return Err(ChannelRefusal::ListenerFull);
+            }
+        }
+        let n = counts.per_identity.get(identity).map_or(0, Vec::len);
+        if let Some(max) = self.limits.max_channels_per_identity {
+            if n >= max {
+                warn!(%identity, %max, "too many secure channels from identity");
+                return Err(ChannelRefusal::IdentityLimitReached);
+            }
+        }
+        counts.total += 1;
+        counts
+            .per_identity
+            .entry(identity.clone())
+            .or_default()
+            .push(addresses.clone());
+        Ok(ChannelSlot {
+            slots: self.clone(),
+            identity: identity.clone(),
+            addresses,
+        })
+    }
+}
+
+/// A channel counted by a listener, released on drop.
+pub(crate) struct ChannelSlot {
+    slots: ChannelSlots,
+    identity: IdentityIdentifier,
+    addresses: Vec<Address>,
+}
+
+impl Drop for ChannelSlot {
+    fn drop(&mut self) {
+        let mut counts = self.slots.counts.lock().unwrap();
+        counts.total = counts.total.saturating_sub(1);
+        if let Some(channels) = counts.per_identity.get_mut(&self.identity) {
+            if let Some(i) = channels.iter().position(|a| a == &self.addresses) {
+                channels.swap_remove(i);
+            }
+            if channels.is_empty() {
+                counts.per_identity.remove(&self.identity);
+            }
+        }
+    }
+}
+
+/// The channels currently open on a secure channel listener
+///
+/// Lets the owner of the listener close the channels of an identity it no
+/// longer trusts, e.g. once that identity is revoked.
+#[derive(Clone)]
+pub struct ListenerChannels {
+    slots: ChannelSlots,
+}
+
+impl ListenerChannels {
+    pub(crate) fn new(slots: ChannelSlots) -> Self {
+        Self { slots }
+    }
+
+    /// Worker addresses of the open channels initiated by `identity`.
+    ///
+    /// Stopping all of them closes these channels.
+    pub fn addresses_of(&self, identity: &IdentityIdentifier) -> Vec<Address> {
+        let counts = self.slots.counts.lock().unwrap();
+        counts
+            .per_identity
+            .get(identity)
+            .map(|channels| channels.iter().flatten().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_are_limited_and_released() {
+        let slots = ChannelSlots::new(SecureChannelListenerLimits::new().with_max_channels(2));
+        let alice = IdentityIdentifier::from_key_id("alice");
+        let a = slots.acquire(&alice, Vec::new()).unwrap();
+        let _b = slots.acquire(&alice, Vec::new()).unwrap();
+        assert!(slots.acquire(&alice, Vec::new()).is_err());
+        drop(a);
+        assert!(slots.acquire(&alice, Vec::new()).is_ok());
+    }
+
+    #[test]
+    fn slots_are_limited_per_identity() {
+        let limits = SecureChannelListenerLimits::new().with_max_channels_per_identity(1);
+        let slots = ChannelSlots::new(limits);
+        let alice = IdentityIdentifier::from_key_id("alice");
+        let bob = IdentityIdentifier::from_key_id("bob");
+
+        let a = slots.acquire(&alice, Vec::new()).unwrap();
+        assert!(slots.acquire(&alice, Vec::new()).is_err());
+        let _b = slots.acquire(&bob, Vec::new()).unwrap();
+        drop(a);
+        slots.acquire(&alice, Vec::new()).unwrap();
+    }
+
+    #[test]
+    fn channels_are_listed_per_identity() {
+        let slots = ChannelSlots::new(SecureChannelListenerLimits::new());
+        let channels = ListenerChannels::new(slots.clone());
+        let alice = IdentityIdentifier::from_key_id("alice");
+        let bob = IdentityIdentifier::from_key_id("bob");
+
+        let a = slots
+            .acquire(&alice, vec!["a1".into(), "a2".into()])
+            .unwrap();
+        let _b = slots.acquire(&bob, vec!["b1".into()]).unwrap();
+        assert_eq!(
+            channels.addresses_of(&alice),
+            vec!["a1".into(), "a2".into()]
+        );
+        drop(a);
+        assert!(channels.addresses_of(&alice).is_empty());
+        assert_eq!(channels.addresses_of(&bob), vec![Address::from("b1")]);
+    }
+}
@@ -1,5 +1,8 @@
 use crate::authenticated_storage::AuthenticatedStorage;
-use crate::{DecryptorWorker, Identity, IdentityVault, TrustPolicy};
+use crate::{
+    ChannelSlots, DecryptorWorker, Identity, IdentityVault, KeyExchangeMode, ListenerChannels,
+    SecureChannelListenerLimits, TrustPolicy,
+};
 use ockam_channel::CreateResponderChannelMessage;
 use ockam_core::compat::{boxed::Box, sync::Arc};
 use ockam_core::{AsyncTryClone, Result, Routed, Worker};
@@ -9,16 +12,30 @@ pub(crate) struct IdentityChannelListener<V: IdentityVault, S: AuthenticatedStor
     trust_policy: Arc<dyn TrustPolicy>,
     identity: Identity<V>,
     storage: S,
+    slots: ChannelSlots,
+    key_exchange: KeyExchangeMode,
 }
 
 impl<V: IdentityVault, S: AuthenticatedStorage> IdentityChannelListener<V, S> {
-    pub fn new(trust_policy: impl TrustPolicy, identity: Identity<V>, storage: S) -> Self {
+    pub fn new(
+        trust_policy: impl TrustPolicy,
+        identity: Identity<V>,
+        storage: S,
+        limits: SecureChannelListenerLimits,
+        key_exchange: KeyExchangeMode,
+    ) -> Self {
         IdentityChannelListener {
             trust_policy: Arc::new(trust_policy),
             identity,
             storage,
+            slots: ChannelSlots::new(limits),
+            key_exchange,
         }
     }
+
+    pub fn channels(&self) -> ListenerChannels {
+        ListenerChannels::new(self.slots.clone())
+    }
 }
 
 #[ockam_core::worker]
@@ -38,6 +55,8 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Worker for IdentityChannelListen
             identity,
             self.storage.async_try_clone().await?,
             trust_policy,
+            Some(self.slots.clone()),
+            self.key_exchange,
             msg,
         )
         .await
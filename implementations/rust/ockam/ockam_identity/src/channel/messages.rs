@@ -1,3 +1,4 @@
+use crate::IdentityError;
 use ockam_core::compat::vec::Vec;
 use ockam_core::Message;
 use serde::{Deserialize, Serialize};
@@ -12,5 +13,36 @@ pub(crate) enum IdentityChannelMessage {
         identity: Vec<u8>,
         signature: Vec<u8>,
     },
+    /// Sent by the responder once it accepted the channel.
     Confirm,
+    /// Sent by the responder instead of [`IdentityChannelMessage::Confirm`]
+    /// when it refuses the channel.
+    Refuse(ChannelRefusal),
+    /// Sent by the responder instead of [`IdentityChannelMessage::Request`]
+    /// to initiators asking for a confirmation, announcing that it answers
+    /// their `Response` with `Confirm` or `Refuse`.
+    ConfirmingRequest {
+        identity: Vec<u8>,
+        signature: Vec<u8>,
+    },
+}
+
+/// Why a listener refused a channel after authenticating its initiator.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ChannelRefusal {
+    /// The listener reached its limit of channels.
+    ListenerFull,
+    /// The initiator reached its limit of channels at the listener.
+    IdentityLimitReached,
+}
+
+impl From<ChannelRefusal> for IdentityError {
+    fn from(refusal: ChannelRefusal) -> Self {
+        match refusal {
+            ChannelRefusal::ListenerFull => IdentityError::SecureChannelLimitReached,
+            ChannelRefusal::IdentityLimitReached => {
+                IdentityError::SecureChannelIdentityLimitReached
+            }
+        }
+    }
 }
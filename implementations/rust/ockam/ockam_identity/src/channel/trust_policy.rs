@@ -18,6 +18,8 @@ mod trust_everyone_policy;
 pub use trust_everyone_policy::*;
 mod trust_public_key_policy;
 pub use trust_public_key_policy::*;
+mod trust_attribute_policy;
+pub use trust_attribute_policy::*;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SecureChannelTrustInfo {
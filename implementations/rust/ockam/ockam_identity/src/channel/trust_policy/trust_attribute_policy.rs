@@ -0,0 +1,132 @@
+use crate::authenticated_storage::AuthenticatedStorage;
+use crate::credential::AttributesStorageUtils;
+use crate::{SecureChannelTrustInfo, TrustPolicy};
+use ockam_core::compat::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    vec::Vec,
+};
+use ockam_core::{async_trait, Result};
+use tracing::debug;
+
+/// Trust the identities whose credential attributes satisfy every condition
+///
+/// Each condition names an attribute and the set of accepted values, e.g.
+/// `project == X` and `role in [admin, member]`. Attributes are read from
+/// the [`AuthenticatedStorage`], where they are put when a credential is
+/// presented, so identities which did not present a valid credential are
+/// never trusted. The conditions are checked during the handshake, so
+/// initiators have to present their credential to the listening node
+/// before they create the channel, e.g. over another secure channel.
+#[derive(Clone)]
+pub struct TrustAttributePolicy<S: AuthenticatedStorage> {
+    conditions: BTreeMap<String, BTreeSet<Vec<u8>>>,
+    storage: S,
+}
+
+impl<S: AuthenticatedStorage> TrustAttributePolicy<S> {
+    /// A policy without conditions, trusting any identity with attributes.
+    pub fn new(storage: S) -> Self {
+        Self {
+            conditions: BTreeMap::new(),
+            storage,
+        }
+    }
+
+    /// Require the attribute `name` to be equal to `value`.
+    ///
+    /// Calling this again for the same `name` adds `value` to the values accepted.
+    pub fn with_attribute(mut self, name: impl Into<String>, value: impl Into<Vec<u8>>) -> Self {
+        self.conditions
+            .entry(name.into())
+            .or_default()
+            .insert(value.into());
+        self
+    }
+
+    /// Require the attribute `name` to be one of `values`.
+    pub fn with_attribute_in<V>(mut self, name: impl Into<String>, values: V) -> Self
+    where
+        V: IntoIterator,
+        V::Item: Into<Vec<u8>>,
+    {
+        self.conditions
+            .entry(name.into())
+            .or_default()
+            .extend(values.into_iter().map(Into::into));
+        self
+    }
+}
+
+#[async_trait]
+impl<S: AuthenticatedStorage> TrustPolicy for TrustAttributePolicy<S> {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        let attributes = match AttributesStorageUtils::get_attributes(
+            trust_info.their_identity_id(),
+            &self.storage,
+        )
+        .await?
+        {
+            Some(a) => a,
+            None => {
+                debug!(identity = %trust_info.their_identity_id(), "no credential attributes");
+                return Ok(false);
+            }
+        };
+        let trusted = self.conditions.iter().all(|(name, values)| {
+            attributes
+                .get(name)
+                .map(|v| values.contains(v))
+                .unwrap_or(false)
+        });
+        if !trusted {
+            debug!(identity = %trust_info.their_identity_id(), "credential attributes not trusted");
+        }
+        Ok(trusted)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::authenticated_storage::mem::InMemoryStorage;
+    use crate::credential::{Attributes, AttributesEntry, Timestamp};
+    use crate::IdentityIdentifier;
+
+    #[tokio::test]
+    async fn test() {
+        let storage = InMemoryStorage::new();
+        let alice = IdentityIdentifier::random();
+        let bob = IdentityIdentifier::random();
+
+        let never: Timestamp = minicbor::decode(&minicbor::to_vec(u64::MAX).unwrap()).unwrap();
+        let mut attrs = Attributes::new();
+        attrs.put("project", b"p1").put("role", b"member");
+        AttributesStorageUtils::put_attributes(
+            &alice,
+            AttributesEntry::new(attrs, never),
+            &storage,
+        )
+        .await
+        .unwrap();
+
+        let policy = TrustAttributePolicy::new(storage.clone())
+            .with_attribute("project", "p1")
+            .with_attribute_in("role", ["admin", "member"]);
+        assert!(policy
+            .check(&SecureChannelTrustInfo::new(alice.clone()))
+            .await
+            .unwrap());
+        assert!(!policy
+            .check(&SecureChannelTrustInfo::new(bob))
+            .await
+            .unwrap());
+
+        let policy = TrustAttributePolicy::new(storage).with_attribute("role", "admin");
+        assert!(!policy
+            .check(&SecureChannelTrustInfo::new(alice))
+            .await
+            .unwrap());
+    }
+}
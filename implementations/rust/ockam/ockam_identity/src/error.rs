@@ -20,6 +20,10 @@ pub enum IdentityError {
     InvalidCredentialFormat,
     UnknownAuthority,
     CredentialVerificationFailed,
+    SecureChannelLimitReached,
+    SecureChannelIdentityLimitReached,
+    InvalidEncryptedValue,
+    KeyExchangeUnsupported,
 }
 
 impl ockam_core::compat::error::Error for IdentityError {}
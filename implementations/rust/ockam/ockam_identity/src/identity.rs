@@ -93,9 +93,6 @@ impl<V: IdentityVault> Identity<V> {
 
     /// Create Identity
     pub async fn create(ctx: &Context, vault: &V) -> Result<Self> {
-        let child_ctx = ctx.new_detached(Address::random_local()).await?;
-        let initial_change_id = ChangeIdentifier::initial(vault).await;
-
         let key_attribs = KeyAttributes::new(
             IdentityStateConst::ROOT_LABEL.to_string(),
             SecretAttributes::new(
@@ -104,9 +101,31 @@ impl<V: IdentityVault> Identity<V> {
                 CURVE25519_SECRET_LENGTH_U32,
             ),
         );
+        Self::create_impl(ctx, vault, None, key_attribs).await
+    }
+
+    /// Create Identity whose root key is an existing secret of the vault,
+    /// e.g. a [`SecretType::NistP256`] key held in AWS KMS or a PKCS#11 token
+    pub async fn create_with_root_key(ctx: &Context, vault: &V, root_key: &KeyId) -> Result<Self> {
+        let secret_attributes = vault.secret_attributes_get(root_key).await?;
+        let key_attribs = KeyAttributes::new(
+            IdentityStateConst::ROOT_LABEL.to_string(),
+            secret_attributes,
+        );
+        Self::create_impl(ctx, vault, Some(root_key), key_attribs).await
+    }
+
+    async fn create_impl(
+        ctx: &Context,
+        vault: &V,
+        root_key: Option<&KeyId>,
+        key_attribs: KeyAttributes,
+    ) -> Result<Self> {
+        let child_ctx = ctx.new_detached(Address::random_local()).await?;
+        let initial_change_id = ChangeIdentifier::initial(vault).await;
 
         let create_key_change = Self::make_create_key_change_static(
-            None,
+            root_key,
             initial_change_id,
             key_attribs.clone(),
             None,
@@ -293,7 +312,7 @@ impl<V: IdentityVault> Identity<V> {
 mod test {
     use super::*;
     use ockam_core::errcode::{Kind, Origin};
-    use ockam_core::vault::PublicKey;
+    use ockam_core::vault::{PublicKey, SecretVault};
     use ockam_core::Error;
     use ockam_vault::Vault;
 
@@ -376,4 +395,30 @@ mod test {
 
         Ok(())
     }
+
+    #[ockam_macros::test]
+    async fn test_create_with_root_key(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let root_key = vault
+            .secret_generate(SecretAttributes::new(
+                SecretType::Ed25519,
+                SecretPersistence::Persistent,
+                CURVE25519_SECRET_LENGTH_U32,
+            ))
+            .await?;
+
+        let identity = Identity::create_with_root_key(ctx, &vault, &root_key).await?;
+
+        if !identity.verify_changes().await? {
+            return test_error("verify_changes failed");
+        }
+
+        if identity.get_root_secret_key().await? != root_key {
+            return test_error("root key is not the given secret");
+        }
+
+        ctx.stop().await?;
+
+        Ok(())
+    }
 }
@@ -46,7 +46,8 @@ impl PublicIdentity {
         &self.id
     }
 
-    pub(crate) fn get_root_public_key(&self) -> Result<PublicKey> {
+    /// Public key of the current root key
+    pub fn get_root_public_key(&self) -> Result<PublicKey> {
         self.change_history.get_root_public_key()
     }
 
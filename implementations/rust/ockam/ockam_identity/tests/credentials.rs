@@ -4,7 +4,7 @@ use ockam_core::{route, Result, Routed, Worker};
 use ockam_identity::authenticated_storage::mem::InMemoryStorage;
 use ockam_identity::credential::access_control::CredentialAccessControl;
 use ockam_identity::credential::{AttributesStorageUtils, Credential};
-use ockam_identity::{Identity, TrustEveryonePolicy, TrustIdentifierPolicy};
+use ockam_identity::{Identity, TrustAttributePolicy, TrustEveryonePolicy, TrustIdentifierPolicy};
 use ockam_node::{Context, WorkerBuilder};
 use ockam_vault::Vault;
 use std::sync::atomic::{AtomicI8, Ordering};
@@ -223,3 +223,66 @@ async fn access_control(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+#[ockam_macros::test]
+async fn listener_trusts_presented_attributes(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+
+    let authority = Identity::create(ctx, &vault).await?;
+
+    let server = Identity::create(ctx, &vault).await?;
+    let server_storage = InMemoryStorage::new();
+    let policy = TrustAttributePolicy::new(server_storage.clone())
+        .with_attribute_in("role", ["admin", "member"]);
+    server
+        .create_secure_channel_listener("members", policy, &server_storage)
+        .await?;
+    server
+        .create_secure_channel_listener("anyone", TrustEveryonePolicy, &server_storage)
+        .await?;
+    let authorities = vec![authority.to_public().await?];
+    server
+        .start_credentials_exchange_worker(
+            authorities,
+            "credential_exchange",
+            false,
+            server_storage.clone(),
+        )
+        .await?;
+
+    // Initiators without attributes are refused during the handshake
+    let client = Identity::create(ctx, &vault).await?;
+    let client_storage = InMemoryStorage::new();
+    let timeout = Duration::from_secs(1);
+    assert!(client
+        .create_secure_channel_extended(
+            route!["members"],
+            TrustEveryonePolicy,
+            &client_storage,
+            timeout
+        )
+        .await
+        .is_err());
+
+    // Once they presented a credential with a trusted attribute, they are accepted
+    let credential =
+        Credential::builder(client.identifier().clone()).with_attribute("role", b"member");
+    let credential = authority.issue_credential(credential).await?;
+    client.set_credential(Some(credential)).await;
+    let channel = client
+        .create_secure_channel(route!["anyone"], TrustEveryonePolicy, &client_storage)
+        .await?;
+    client
+        .present_credential(route![channel, "credential_exchange"])
+        .await?;
+    client
+        .create_secure_channel_extended(
+            route!["members"],
+            TrustEveryonePolicy,
+            &client_storage,
+            timeout,
+        )
+        .await?;
+
+    ctx.stop().await
+}
@@ -14,6 +14,8 @@ pub enum XXError {
     InternalVaultError,
     /// A message had an unexpected length.
     MessageLenMismatch,
+    /// The post-quantum key encapsulation failed.
+    KemFailure,
 }
 
 impl StdError for XXError {}
@@ -24,6 +26,7 @@ impl fmt::Display for XXError {
             Self::InvalidState => write!(f, "invalid state"),
             Self::InternalVaultError => write!(f, "internal vault error"),
             Self::MessageLenMismatch => write!(f, "message length mismatch"),
+            Self::KemFailure => write!(f, "key encapsulation failed"),
         }
     }
 }
@@ -35,6 +38,7 @@ impl From<XXError> for Error {
             XXError::InvalidState => Kind::Invalid,
             XXError::InternalVaultError => Kind::Internal,
             XXError::MessageLenMismatch => Kind::Misuse,
+            XXError::KemFailure => Kind::Invalid,
         };
 
         Error::new(Origin::KeyExchange, kind, err)
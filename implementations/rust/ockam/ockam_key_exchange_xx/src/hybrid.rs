@@ -0,0 +1,333 @@
+//! Hybrid X25519 + Kyber768 key exchange.
+//!
+//! The Kyber public key is carried in the payload of the first XX message
+//! and the ciphertext in the payload of the second one. Both payloads are
+//! mixed into the handshake hash, so a peer stripping the Kyber data is
+//! detected when the handshake hash is authenticated. The Kyber shared
+//! secret is mixed into the chaining key before the transport keys are
+//! derived.
+//!
+//! A peer which doesn't recognize the Kyber data returns it as an opaque
+//! payload and answers with a plain second message, in which case both
+//! sides complete a classic XX handshake.
+use crate::state::State;
+use crate::{Initiator, Responder, XXError, XXVault};
+use ockam_core::compat::{
+    rand::thread_rng,
+    string::{String, ToString},
+    vec::Vec,
+};
+use ockam_core::{async_trait, compat::boxed::Box, AsyncTryClone, Result};
+use ockam_key_exchange_core::{CompletedKeyExchange, KeyExchanger, NewKeyExchanger};
+use pqc_kyber::{
+    decapsulate, encapsulate, keypair, Keypair, KYBER_CIPHERTEXTBYTES, KYBER_PUBLICKEYBYTES,
+};
+
+/// Marks a payload carrying Kyber768 key material
+const HYBRID_TAG: &[u8] = b"OCKAM_PQ_KYBER768";
+
+/// Split a payload prefixed with [`HYBRID_TAG`] into the key material of
+/// length `len` and the remaining payload
+fn split_tagged(payload: &[u8], len: usize) -> Option<(&[u8], &[u8])> {
+    let rest = payload.strip_prefix(HYBRID_TAG)?;
+    if rest.len() < len {
+        return None;
+    }
+    Some(rest.split_at(len))
+}
+
+fn tagged(material: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut output = HYBRID_TAG.to_vec();
+    output.extend_from_slice(material);
+    output.extend_from_slice(payload);
+    output
+}
+
+/// Represents a NewKeyExchanger for hybrid X25519 + Kyber768 XX handshakes
+#[derive(AsyncTryClone)]
+#[async_try_clone(crate = "ockam_core")]
+pub struct HybridNewKeyExchanger<V: XXVault> {
+    vault: V,
+}
+
+impl<V: XXVault> HybridNewKeyExchanger<V> {
+    /// Create a new HybridNewKeyExchanger
+    pub fn new(vault: V) -> Self {
+        Self { vault }
+    }
+}
+
+#[async_trait]
+impl<V: XXVault> NewKeyExchanger for HybridNewKeyExchanger<V> {
+    type Initiator = HybridInitiator<V>;
+    type Responder = HybridResponder<V>;
+
+    /// Create a new initiator using the provided backing vault
+    async fn initiator(&self) -> Result<HybridInitiator<V>> {
+        let ss = State::new(&self.vault).await?;
+        Ok(HybridInitiator::new(Initiator::new(ss)))
+    }
+
+    /// Create a new responder using the provided backing vault
+    async fn responder(&self) -> Result<HybridResponder<V>> {
+        let ss = State::new(&self.vault).await?;
+        Ok(HybridResponder::new(Responder::new(ss)))
+    }
+}
+
+/// Represents an XX initiator offering a Kyber768 key encapsulation
+pub struct HybridInitiator<V: XXVault> {
+    inner: Initiator<V>,
+    keypair: Option<Keypair>,
+    shared_secret: Option<Vec<u8>>,
+}
+
+impl<V: XXVault> core::fmt::Debug for HybridInitiator<V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("HybridInitiator")
+            .field("inner", &self.inner)
+            .field("hybrid", &self.is_hybrid())
+            .finish()
+    }
+}
+
+impl<V: XXVault> HybridInitiator<V> {
+    fn new(inner: Initiator<V>) -> Self {
+        Self {
+            inner,
+            keypair: None,
+            shared_secret: None,
+        }
+    }
+
+    /// Whether the responder accepted the Kyber768 key encapsulation
+    pub fn is_hybrid(&self) -> bool {
+        self.shared_secret.is_some()
+    }
+}
+
+#[async_trait]
+impl<V: XXVault> KeyExchanger for HybridInitiator<V> {
+    async fn name(&self) -> Result<String> {
+        Ok("NOISE_XX_KYBER768".to_string())
+    }
+
+    async fn generate_request(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        if self.keypair.is_none() && !self.inner.is_complete().await? {
+            let keys = keypair(&mut thread_rng()).map_err(|_| XXError::KemFailure)?;
+            let payload = tagged(&keys.public, payload);
+            let msg = self.inner.generate_request(&payload).await?;
+            self.keypair = Some(keys);
+            Ok(msg)
+        } else {
+            self.inner.generate_request(payload).await
+        }
+    }
+
+    async fn handle_response(&mut self, response: &[u8]) -> Result<Vec<u8>> {
+        let payload = self.inner.handle_response(response).await?;
+        match (&self.keypair, split_tagged(&payload, KYBER_CIPHERTEXTBYTES)) {
+            (Some(keys), Some((ciphertext, rest))) => {
+                let shared_secret =
+                    decapsulate(ciphertext, &keys.secret).map_err(|_| XXError::KemFailure)?;
+                self.shared_secret = Some(shared_secret.to_vec());
+                Ok(rest.to_vec())
+            }
+            // The responder only supports the classic handshake
+            _ => Ok(payload),
+        }
+    }
+
+    async fn is_complete(&self) -> Result<bool> {
+        self.inner.is_complete().await
+    }
+
+    async fn finalize(mut self) -> Result<CompletedKeyExchange> {
+        if !self.inner.is_complete().await? {
+            return Err(XXError::InvalidState.into());
+        }
+        if let Some(shared_secret) = self.shared_secret.take() {
+            self.inner.state_data.mix_key(&shared_secret).await?;
+        }
+        self.inner.finalize().await
+    }
+}
+
+/// Represents an XX responder accepting a Kyber768 key encapsulation
+pub struct HybridResponder<V: XXVault> {
+    inner: Responder<V>,
+    received_message_1: bool,
+    remote_public_key: Option<Vec<u8>>,
+    shared_secret: Option<Vec<u8>>,
+}
+
+impl<V: XXVault> core::fmt::Debug for HybridResponder<V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("HybridResponder")
+            .field("inner", &self.inner)
+            .field("hybrid", &self.is_hybrid())
+            .finish()
+    }
+}
+
+impl<V: XXVault> HybridResponder<V> {
+    fn new(inner: Responder<V>) -> Self {
+        Self {
+            inner,
+            received_message_1: false,
+            remote_public_key: None,
+            shared_secret: None,
+        }
+    }
+
+    /// Whether the initiator offered a Kyber768 key encapsulation
+    pub fn is_hybrid(&self) -> bool {
+        self.remote_public_key.is_some()
+    }
+}
+
+#[async_trait]
+impl<V: XXVault> KeyExchanger for HybridResponder<V> {
+    async fn name(&self) -> Result<String> {
+        Ok("NOISE_XX_KYBER768".to_string())
+    }
+
+    async fn generate_request(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        match self.remote_public_key.take() {
+            Some(public_key) => {
+                let (ciphertext, shared_secret) =
+                    encapsulate(&public_key, &mut thread_rng()).map_err(|_| XXError::KemFailure)?;
+                let payload = tagged(&ciphertext, payload);
+                let msg = self.inner.generate_request(&payload).await?;
+                self.remote_public_key = Some(public_key);
+                self.shared_secret = Some(shared_secret.to_vec());
+                Ok(msg)
+            }
+            // The initiator only supports the classic handshake
+            None => self.inner.generate_request(payload).await,
+        }
+    }
+
+    async fn handle_response(&mut self, response: &[u8]) -> Result<Vec<u8>> {
+        let payload = self.inner.handle_response(response).await?;
+        if !self.received_message_1 {
+            self.received_message_1 = true;
+            if let Some((public_key, rest)) = split_tagged(&payload, KYBER_PUBLICKEYBYTES) {
+                self.remote_public_key = Some(public_key.to_vec());
+                return Ok(rest.to_vec());
+            }
+        }
+        Ok(payload)
+    }
+
+    async fn is_complete(&self) -> Result<bool> {
+        self.inner.is_complete().await
+    }
+
+    async fn finalize(mut self) -> Result<CompletedKeyExchange> {
+        if !self.inner.is_complete().await? {
+            return Err(XXError::InvalidState.into());
+        }
+        if let Some(shared_secret) = self.shared_secret.take() {
+            self.inner.state_data.mix_key(&shared_secret).await?;
+        }
+        self.inner.finalize().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::XXNewKeyExchanger;
+    use ockam_core::vault::SecretVault;
+    use ockam_vault::Vault;
+
+    async fn run<I: KeyExchanger, R: KeyExchanger>(
+        mut initiator: I,
+        mut responder: R,
+    ) -> (CompletedKeyExchange, CompletedKeyExchange) {
+        loop {
+            if !initiator.is_complete().await.unwrap() {
+                let m = initiator.generate_request(&[]).await.unwrap();
+                let p = responder.handle_response(&m).await.unwrap();
+                assert!(p.is_empty() || p.starts_with(HYBRID_TAG));
+            }
+
+            if !responder.is_complete().await.unwrap() {
+                let m = responder.generate_request(&[]).await.unwrap();
+                let p = initiator.handle_response(&m).await.unwrap();
+                assert!(p.is_empty());
+            }
+
+            if initiator.is_complete().await.unwrap() && responder.is_complete().await.unwrap() {
+                break;
+            }
+        }
+        (
+            initiator.finalize().await.unwrap(),
+            responder.finalize().await.unwrap(),
+        )
+    }
+
+    async fn assert_keys_match(
+        vault: &Vault,
+        initiator: &CompletedKeyExchange,
+        responder: &CompletedKeyExchange,
+    ) {
+        assert_eq!(initiator.h(), responder.h());
+
+        let s1 = vault.secret_export(initiator.encrypt_key()).await.unwrap();
+        let s2 = vault.secret_export(responder.decrypt_key()).await.unwrap();
+        assert_eq!(s1, s2);
+
+        let s1 = vault.secret_export(initiator.decrypt_key()).await.unwrap();
+        let s2 = vault.secret_export(responder.encrypt_key()).await.unwrap();
+        assert_eq!(s1, s2);
+    }
+
+    #[test]
+    fn hybrid_and_fallback_keys_should_match() {
+        let (mut ctx, mut exec) = ockam_node::NodeBuilder::without_access_control().build();
+        exec.execute(async move {
+            let vault = Vault::create();
+            let hybrid = HybridNewKeyExchanger::new(vault.async_try_clone().await.unwrap());
+            let classic = XXNewKeyExchanger::new(vault.async_try_clone().await.unwrap());
+
+            // Both sides support the hybrid handshake
+            let (i, r) = run(
+                hybrid.initiator().await.unwrap(),
+                hybrid.responder().await.unwrap(),
+            )
+            .await;
+            assert_keys_match(&vault, &i, &r).await;
+
+            let mut initiator = hybrid.initiator().await.unwrap();
+            let mut responder = hybrid.responder().await.unwrap();
+            let m = initiator.generate_request(&[]).await.unwrap();
+            responder.handle_response(&m).await.unwrap();
+            let m = responder.generate_request(&[]).await.unwrap();
+            initiator.handle_response(&m).await.unwrap();
+            assert!(initiator.is_hybrid());
+            assert!(responder.is_hybrid());
+
+            // Hybrid initiator, classic responder
+            let (i, r) = run(
+                hybrid.initiator().await.unwrap(),
+                classic.responder().await.unwrap(),
+            )
+            .await;
+            assert_keys_match(&vault, &i, &r).await;
+
+            // Classic initiator, hybrid responder
+            let (i, r) = run(
+                classic.initiator().await.unwrap(),
+                hybrid.responder().await.unwrap(),
+            )
+            .await;
+            assert_keys_match(&vault, &i, &r).await;
+
+            ctx.stop().await.unwrap();
+        })
+        .unwrap();
+    }
+}
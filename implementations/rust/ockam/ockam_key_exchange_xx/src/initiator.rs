@@ -19,7 +19,7 @@ enum InitiatorState {
 #[derive(Debug)]
 pub struct Initiator<V: XXVault> {
     state: InitiatorState,
-    state_data: State<V>,
+    pub(crate) state_data: State<V>,
 }
 
 impl<V: XXVault> Initiator<V> {
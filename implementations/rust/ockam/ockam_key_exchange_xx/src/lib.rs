@@ -62,6 +62,10 @@ mod responder;
 pub use responder::*;
 mod new_key_exchanger;
 pub use new_key_exchanger::*;
+#[cfg(feature = "pq-hybrid")]
+mod hybrid;
+#[cfg(feature = "pq-hybrid")]
+pub use hybrid::*;
 use ockam_core::vault::{AsymmetricVault, Hasher, SecretVault, SymmetricVault};
 
 #[cfg(test)]
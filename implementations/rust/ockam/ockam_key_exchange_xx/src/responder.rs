@@ -19,7 +19,7 @@ enum ResponderState {
 #[derive(Debug)]
 pub struct Responder<V: XXVault> {
     state: ResponderState,
-    state_data: State<V>,
+    pub(crate) state_data: State<V>,
 }
 
 impl<V: XXVault> Responder<V> {
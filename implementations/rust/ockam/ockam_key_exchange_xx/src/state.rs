@@ -184,6 +184,20 @@ impl<V: XXVault> State<V> {
         Ok((res0, res1))
     }
 
+    /// Mix a secret agreed outside of the handshake patterns into the
+    /// chaining key, so that it contributes to the keys returned by `split`
+    #[cfg(feature = "pq-hybrid")]
+    pub(crate) async fn mix_key(&mut self, secret: &[u8]) -> Result<()> {
+        let attributes = SecretAttributes::new(
+            SecretType::Buffer,
+            SecretPersistence::Ephemeral,
+            secret.len() as u32,
+        );
+        let secret_handle = self.vault.secret_import(secret, attributes).await?;
+        self.dh_state.mix_key(&secret_handle).await?;
+        self.vault.secret_destroy(secret_handle).await
+    }
+
     /// Set this state up to send and receive messages
     fn finalize(self, encrypt_key: KeyId, decrypt_key: KeyId) -> Result<CompletedKeyExchange> {
         let h = self.h.ok_or(XXError::InvalidState)?;
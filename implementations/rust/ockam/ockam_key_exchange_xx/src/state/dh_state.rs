@@ -95,4 +95,50 @@ impl<V: XXVault> DhState<V> {
 
         Ok(())
     }
+
+    /// Mix an additional shared secret into the chaining key
+    #[cfg(feature = "pq-hybrid")]
+    pub(crate) async fn mix_key(&mut self, secret_handle: &KeyId) -> Result<()> {
+        let ck = self.ck.as_ref().ok_or(XXError::InvalidState)?;
+
+        let attributes_ck = SecretAttributes::new(
+            SecretType::Buffer,
+            SecretPersistence::Ephemeral,
+            SHA256_SIZE_U32,
+        );
+
+        let symmetric_secret_info = self.get_symmetric_key_type_and_length();
+
+        let attributes_k = SecretAttributes::new(
+            symmetric_secret_info.0,
+            SecretPersistence::Ephemeral,
+            symmetric_secret_info.1,
+        );
+
+        let mut hkdf_output = self
+            .vault
+            .hkdf_sha256(
+                ck,
+                b"",
+                Some(secret_handle),
+                vec![attributes_ck, attributes_k],
+            )
+            .await?;
+
+        if hkdf_output.len() != 2 {
+            return Err(XXError::InternalVaultError.into());
+        }
+
+        if let Some(key) = self.key.take() {
+            self.vault.secret_destroy(key).await?;
+        }
+        self.key = Some(hkdf_output.pop().unwrap());
+
+        if let Some(ck) = self.ck.take() {
+            self.vault.secret_destroy(ck).await?;
+        }
+        self.ck = Some(hkdf_output.pop().unwrap());
+
+        Ok(())
+    }
 }
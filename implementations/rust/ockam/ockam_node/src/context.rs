@@ -1,5 +1,7 @@
 use crate::async_drop::AsyncDrop;
-use crate::channel_types::{message_channel, small_channel, SmallReceiver, SmallSender};
+use crate::channel_types::{
+    message_channel, small_channel, MessageSender, SmallReceiver, SmallSender,
+};
 use crate::tokio::{self, runtime::Handle, time::timeout};
 use crate::{
     error::*,
@@ -16,13 +18,17 @@ use ockam_core::compat::{boxed::Box, string::String, sync::Arc, vec::Vec};
 use ockam_core::{
     errcode::{Kind, Origin},
     Address, AddressSet, AllowAll, AsyncTryClone, Error, LocalMessage, Mailbox, Mailboxes, Message,
-    Processor, Result, Route, TransportMessage, TransportType, Worker,
+    Processor, Result, Route, TraceHop, TransportMessage, TransportType, Worker,
 };
 use ockam_core::{AccessControl, LocalInfo};
 
 /// A default timeout in seconds
 pub const DEFAULT_TIMEOUT: u64 = 30;
 
+/// Address of the worker receiving the messages sent to local addresses
+/// which don't exist, if one is started
+pub const DEAD_LETTER_ADDRESS: &str = "_dead_letters";
+
 enum AddressType {
     Worker,
     Processor,
@@ -571,20 +577,6 @@ impl Context {
             return Err(Error::new_without_cause(Origin::Node, Kind::Invalid));
         }
 
-        // First resolve the next hop in the route
-        let (reply_tx, mut reply_rx) = small_channel();
-        let next = route.next().unwrap(); // TODO: communicate bad routes
-        let req = NodeMessage::SenderReq(next.clone(), reply_tx);
-        self.sender
-            .send(req)
-            .await
-            .map_err(NodeError::from_send_err)?;
-        let (addr, sender, needs_wrapping) = reply_rx
-            .recv()
-            .await
-            .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??
-            .take_sender()?;
-
         // Pack the payload into a TransportMessage
         let payload = msg.encode().unwrap();
         let mut transport_msg = TransportMessage::v1(route.clone(), Route::new(), payload);
@@ -593,6 +585,13 @@ impl Context {
         // Pack transport message into a LocalMessage wrapper
         let local_msg = LocalMessage::new(transport_msg, local_info);
 
+        // First resolve the next hop in the route
+        let next = route.next().unwrap(); // TODO: communicate bad routes
+        let (addr, sender, needs_wrapping) = match self.resolve_sender(next).await {
+            Ok(resolved) => resolved,
+            Err(err) => return Err(self.dead_letter(err, local_msg).await),
+        };
+
         // Pack local message into a RelayMessage wrapper
         let msg = RelayMessage::new(addr, local_msg, route, needs_wrapping);
 
@@ -614,20 +613,15 @@ impl Context {
     ///
     /// [`Context::send`]: crate::Context::send
     /// [`TransportMessage`]: ockam_core::TransportMessage
-    pub async fn forward(&self, local_msg: LocalMessage) -> Result<()> {
+    pub async fn forward(&self, mut local_msg: LocalMessage) -> Result<()> {
+        self.record_hop(&mut local_msg);
+
         // First resolve the next hop in the route
-        let (reply_tx, mut reply_rx) = small_channel();
         let next = local_msg.transport().onward_route.next().unwrap(); // TODO: communicate bad routes
-        let req = NodeMessage::SenderReq(next.clone(), reply_tx);
-        self.sender
-            .send(req)
-            .await
-            .map_err(NodeError::from_send_err)?;
-        let (addr, sender, needs_wrapping) = reply_rx
-            .recv()
-            .await
-            .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??
-            .take_sender()?;
+        let (addr, sender, needs_wrapping) = match self.resolve_sender(next).await {
+            Ok(resolved) => resolved,
+            Err(err) => return Err(self.dead_letter(err, local_msg).await),
+        };
 
         // Pack the transport message into a relay message
         let onward = local_msg.transport().onward_route.clone();
@@ -640,6 +634,75 @@ impl Context {
         Ok(())
     }
 
+    /// Append this context and the next hop to the hops of a traced
+    /// message, see [`TransportMessage::is_traced`]
+    fn record_hop(&self, local_msg: &mut LocalMessage) {
+        let transport = local_msg.transport_mut();
+        if !transport.is_traced() {
+            return;
+        }
+        let next = match transport.onward_route.next() {
+            Ok(next) => next.clone(),
+            Err(_) => return,
+        };
+        #[cfg(feature = "std")]
+        let timestamp_micros = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or_default();
+        #[cfg(not(feature = "std"))]
+        let timestamp_micros = 0;
+        let hop = TraceHop {
+            address: self.address(),
+            next,
+            timestamp_micros,
+        };
+        if let Err(err) = transport.record_hop(hop) {
+            debug!(%err, "failed to record the hop of a traced message");
+        }
+    }
+
+    /// Resolve the address of the next hop to the sender of its relay
+    async fn resolve_sender(
+        &self,
+        next: &Address,
+    ) -> Result<(Address, MessageSender<RelayMessage>, bool)> {
+        let (reply_tx, mut reply_rx) = small_channel();
+        let req = NodeMessage::SenderReq(next.clone(), reply_tx);
+        self.sender
+            .send(req)
+            .await
+            .map_err(NodeError::from_send_err)?;
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??
+            .take_sender()
+    }
+
+    /// Hand a message whose next hop doesn't exist over to the dead
+    /// letter worker, if there is one, and return the resolution error
+    ///
+    /// The message keeps its onward route, starting with the missing
+    /// address, and its return route.
+    async fn dead_letter(&self, err: Error, local_msg: LocalMessage) -> Error {
+        if err.code().kind != Kind::NotFound {
+            return err;
+        }
+        let dead_letters = Address::from_string(DEAD_LETTER_ADDRESS);
+        let onward = local_msg.transport().onward_route.clone();
+        if onward.next().ok() == Some(&dead_letters) {
+            return err;
+        }
+        if let Ok((addr, sender, needs_wrapping)) = self.resolve_sender(&dead_letters).await {
+            let msg = RelayMessage::new(addr, local_msg, onward, needs_wrapping);
+            if sender.send(msg).await.is_err() {
+                debug!("failed to hand a message over to the dead letter worker");
+            }
+        }
+        err
+    }
+
     /// Block the current worker to wait for a typed message
     ///
     /// **Warning** this function will wait until its running ockam
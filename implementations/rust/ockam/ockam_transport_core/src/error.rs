@@ -38,6 +38,10 @@ pub enum TransportError {
     PortalInvalidState,
     /// InvalidRouterResponseType
     InvalidRouterResponseType,
+    /// Invalid TLS certificate, key or server name
+    InvalidTlsConfig,
+    /// The TLS handshake didn't complete in time
+    TlsHandshakeTimeout,
 }
 
 impl ockam_core::compat::error::Error for TransportError {}
@@ -59,6 +63,8 @@ impl core::fmt::Display for TransportError {
             Self::GenericIo => write!(f, "generic I/O failure"),
             Self::PortalInvalidState => write!(f, "portal entered invalid state"),
             Self::InvalidRouterResponseType => write!(f, "router responded with invalid type"),
+            Self::InvalidTlsConfig => write!(f, "invalid TLS configuration"),
+            Self::TlsHandshakeTimeout => write!(f, "TLS handshake timed out"),
         }
     }
 }
@@ -83,6 +89,8 @@ impl From<TransportError> for Error {
             GenericIo => Kind::Io,
             PortalInvalidState => Kind::Invalid,
             InvalidRouterResponseType => Kind::Invalid,
+            InvalidTlsConfig => Kind::Invalid,
+            TlsHandshakeTimeout => Kind::Timeout,
         };
 
         Error::new(Origin::Transport, kind, err)
@@ -26,10 +26,16 @@ extern crate core;
 extern crate alloc;
 
 mod portal;
+mod registry;
 mod router;
 mod workers;
 
 pub(crate) use portal::*;
+#[cfg(feature = "tls")]
+pub use portal::{InletTls, OutletTls};
+pub use portal::{PortalCompression, PortalMessage, PortalTraffic, RateLimit};
+pub use registry::{TcpConnectionInfo, TcpConnectionMode, TcpListenerInfo, MAX_MESSAGE_SIZE};
+pub(crate) use registry::{TcpConnectionStats, TcpListenerStats, TcpRegistry};
 pub(crate) use router::*;
 pub(crate) use workers::*;
 
@@ -106,5 +112,19 @@ mod test {
 
         let result = parse_socket_addr("127.0.0.1:8080");
         assert!(result.is_ok());
+
+        let result = parse_socket_addr("::1:8080");
+        assert!(result.is_err());
+        assert_transport_error(result, TransportError::InvalidAddress);
+
+        let result = parse_socket_addr("[::1]");
+        assert!(result.is_err());
+        assert_transport_error(result, TransportError::InvalidAddress);
+
+        let result = parse_socket_addr("[::1]:0");
+        assert!(result.is_ok());
+
+        let result = parse_socket_addr("[::1]:8080");
+        assert!(result.is_ok());
     }
 }
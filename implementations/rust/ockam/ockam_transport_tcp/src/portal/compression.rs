@@ -0,0 +1,114 @@
+use crate::MAX_PAYLOAD_SIZE;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+use serde::{Deserialize, Serialize};
+
+/// Compression of the payloads going through a portal
+///
+/// The inlet offers the algorithms it wants to use, in order of
+/// preference, and the outlet picks the first one it accepts. Payloads
+/// which don't shrink are sent uncompressed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortalCompression {
+    /// LZ4 block format, fast with a moderate ratio
+    Lz4,
+    /// Zstandard, better ratio at a higher CPU cost, requires the `zstd` feature
+    Zstd,
+}
+
+impl PortalCompression {
+    /// The algorithms supported by this build, in order of preference
+    pub fn supported() -> Vec<Self> {
+        let mut supported = Vec::new();
+        #[cfg(feature = "zstd")]
+        supported.push(Self::Zstd);
+        supported.push(Self::Lz4);
+        supported
+    }
+
+    /// Return the first offered algorithm which is accepted and supported
+    pub fn negotiate(offered: &[Self], accepted: &[Self]) -> Option<Self> {
+        let supported = Self::supported();
+        offered
+            .iter()
+            .find(|c| accepted.contains(c) && supported.contains(c))
+            .copied()
+    }
+
+    pub(crate) fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Lz4 => Ok(lz4_flex::block::compress_prepend_size(data)),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => {
+                zstd::bulk::compress(data, ZSTD_LEVEL).map_err(|_| TransportError::Encoding.into())
+            }
+            #[cfg(not(feature = "zstd"))]
+            Self::Zstd => Err(TransportError::Encoding.into()),
+        }
+    }
+
+    /// Decompress a payload, which can't be bigger than a portal chunk
+    pub(crate) fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Lz4 => {
+                // The size is prepended as a little endian u32, checked
+                // before allocating the output
+                let size = match data {
+                    [a, b, c, d, ..] => u32::from_le_bytes([*a, *b, *c, *d]) as usize,
+                    _ => return Err(TransportError::RecvBadMessage.into()),
+                };
+                if size > MAX_PAYLOAD_SIZE {
+                    return Err(TransportError::RecvBadMessage.into());
+                }
+                lz4_flex::block::decompress_size_prepended(data)
+                    .map_err(|_| TransportError::RecvBadMessage.into())
+            }
+            #[cfg(feature = "zstd")]
+            Self::Zstd => zstd::bulk::decompress(data, MAX_PAYLOAD_SIZE)
+                .map_err(|_| TransportError::RecvBadMessage.into()),
+            #[cfg(not(feature = "zstd"))]
+            Self::Zstd => Err(TransportError::RecvBadMessage.into()),
+        }
+    }
+}
+
+/// A low level keeps the latency of interactive protocols down
+#[cfg(feature = "zstd")]
+const ZSTD_LEVEL: i32 = 3;
+
+#[cfg(test)]
+mod tests {
+    use super::PortalCompression;
+    use crate::MAX_PAYLOAD_SIZE;
+
+    #[test]
+    fn round_trip() {
+        let data = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n".repeat(100);
+        for c in PortalCompression::supported() {
+            let compressed = c.compress(&data).unwrap();
+            assert!(compressed.len() < data.len());
+            assert_eq!(c.decompress(&compressed).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn oversized_payload_is_rejected() {
+        let data = vec![0u8; MAX_PAYLOAD_SIZE + 1];
+        for c in PortalCompression::supported() {
+            let compressed = c.compress(&data).unwrap();
+            assert!(c.decompress(&compressed).is_err());
+        }
+    }
+
+    #[test]
+    fn negotiation_follows_the_offer() {
+        use PortalCompression::*;
+        assert_eq!(
+            PortalCompression::negotiate(&[Lz4], &[Zstd, Lz4]),
+            Some(Lz4)
+        );
+        assert_eq!(PortalCompression::negotiate(&[Lz4], &[Zstd]), None);
+        assert_eq!(PortalCompression::negotiate(&[], &[Lz4]), None);
+    }
+}
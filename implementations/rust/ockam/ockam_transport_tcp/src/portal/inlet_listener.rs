@@ -1,4 +1,4 @@
-use crate::TcpPortalWorker;
+use crate::{PortalCompression, PortalTls, PortalTraffic, TcpPortalWorker};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{async_trait, compat::boxed::Box, AccessControl};
@@ -17,6 +17,9 @@ pub(crate) struct TcpInletListenProcessor {
     inner: TcpListener,
     outlet_listener_route: Route,
     access_control: Arc<dyn AccessControl>,
+    traffic: Arc<PortalTraffic>,
+    tls: Option<PortalTls>,
+    compression: Vec<PortalCompression>,
 }
 
 impl TcpInletListenProcessor {
@@ -26,6 +29,9 @@ impl TcpInletListenProcessor {
         outlet_listener_route: Route,
         addr: SocketAddr,
         access_control: Arc<dyn AccessControl>,
+        traffic: Arc<PortalTraffic>,
+        tls: Option<PortalTls>,
+        compression: Vec<PortalCompression>,
     ) -> Result<(Address, SocketAddr)> {
         let waddr = Address::random_local();
 
@@ -42,6 +48,9 @@ impl TcpInletListenProcessor {
             inner,
             outlet_listener_route,
             access_control,
+            traffic,
+            tls,
+            compression,
         };
         ctx.start_processor(waddr.clone(), processor).await?;
         Ok((waddr, saddr))
@@ -60,6 +69,9 @@ impl Processor for TcpInletListenProcessor {
             peer,
             self.outlet_listener_route.clone(),
             self.access_control.clone(),
+            self.traffic.clone(),
+            self.tls.clone(),
+            self.compression.clone(),
         )
         .await?;
 
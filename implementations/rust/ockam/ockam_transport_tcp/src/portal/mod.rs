@@ -1,11 +1,21 @@
+mod compression;
 mod inlet_listener;
 mod outlet_listener;
 mod portal_message;
 mod portal_receiver;
 mod portal_worker;
+mod tls;
+mod traffic;
 
 pub(crate) use inlet_listener::*;
 pub(crate) use outlet_listener::*;
 pub(crate) use portal_message::*;
 pub(crate) use portal_receiver::*;
 pub(crate) use portal_worker::*;
+pub(crate) use tls::*;
+
+pub use compression::PortalCompression;
+pub use portal_message::PortalMessage;
+#[cfg(feature = "tls")]
+pub use tls::{InletTls, OutletTls};
+pub use traffic::{PortalTraffic, RateLimit};
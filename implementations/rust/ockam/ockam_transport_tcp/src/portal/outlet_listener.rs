@@ -1,4 +1,6 @@
-use crate::{PortalMessage, TcpPortalWorker, TcpRouterHandle};
+use crate::{
+    PortalCompression, PortalMessage, PortalTls, PortalTraffic, TcpPortalWorker, TcpRouterHandle,
+};
 use ockam_core::{async_trait, AccessControl, Result, Routed, Worker};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
@@ -13,14 +15,26 @@ use tracing::debug;
 pub(crate) struct TcpOutletListenWorker {
     peer: String,
     access_control: Arc<dyn AccessControl>,
+    traffic: Arc<PortalTraffic>,
+    tls: Option<PortalTls>,
+    compression: Vec<PortalCompression>,
 }
 
 impl TcpOutletListenWorker {
     /// Create a new `TcpOutletListenWorker`
-    pub(crate) fn new(peer: String, access_control: Arc<dyn AccessControl>) -> Self {
+    pub(crate) fn new(
+        peer: String,
+        access_control: Arc<dyn AccessControl>,
+        traffic: Arc<PortalTraffic>,
+        tls: Option<PortalTls>,
+        compression: Vec<PortalCompression>,
+    ) -> Self {
         Self {
             peer,
             access_control,
+            traffic,
+            tls,
+            compression,
         }
     }
 }
@@ -37,10 +51,13 @@ impl Worker for TcpOutletListenWorker {
     ) -> Result<()> {
         let return_route = msg.return_route();
 
-        if let PortalMessage::Ping = msg.body() {
-        } else {
-            return Err(TransportError::Protocol.into());
-        }
+        let compression = match msg.body() {
+            PortalMessage::Ping => None,
+            PortalMessage::CompressedPing(offer) => {
+                PortalCompression::negotiate(&offer, &self.compression)
+            }
+            _ => return Err(TransportError::Protocol.into()),
+        };
 
         let (peer_addr, _) = TcpRouterHandle::resolve_peer(self.peer.clone())?;
 
@@ -49,6 +66,9 @@ impl Worker for TcpOutletListenWorker {
             peer_addr,
             return_route.clone(),
             self.access_control.clone(),
+            self.traffic.clone(),
+            self.tls.clone(),
+            compression,
         )
         .await?;
 
@@ -1,5 +1,6 @@
+use crate::PortalCompression;
 use ockam_core::Message;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 
 /// A command message type for a Portal
 #[derive(Serialize, Deserialize, Message)]
@@ -13,11 +14,75 @@ pub enum PortalMessage {
     Disconnect,
     /// Message with binary payload
     Payload(Vec<u8>),
+    /// Message with a binary payload compressed with the negotiated
+    /// [`PortalCompression`]
+    CompressedPayload(Vec<u8>),
+    /// First message that Inlet sends to the Outlet, offering to compress
+    /// payloads with one of the given algorithms
+    CompressedPing(Vec<PortalCompression>),
+    /// First message that Outlet sends to the Inlet, accepting to compress
+    /// payloads with the given algorithm
+    CompressedPong(PortalCompression),
+}
+
+/// A borrowed [`PortalMessage`] used when sending payloads
+///
+/// It encodes to the same bytes as [`PortalMessage`], but lets the
+/// receiver encode straight from its read buffer instead of copying
+/// every chunk into an owned `Vec` first. The wire format is unchanged,
+/// so portals interoperate with nodes which only use [`PortalMessage`].
+pub(crate) enum PortalMessageRef<'a> {
+    Disconnect,
+    Payload(&'a [u8]),
+    CompressedPayload(&'a [u8]),
+}
+
+impl Serialize for PortalMessageRef<'_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        // Variant indices of the same variants in `PortalMessage`
+        const NAME: &str = "PortalMessage";
+        match self {
+            Self::Disconnect => s.serialize_unit_variant(NAME, 2, "Disconnect"),
+            Self::Payload(p) => s.serialize_newtype_variant(NAME, 3, "Payload", p),
+            Self::CompressedPayload(p) => {
+                s.serialize_newtype_variant(NAME, 4, "CompressedPayload", p)
+            }
+        }
+    }
 }
 
 /// An internal message type for a Portal
-#[derive(Serialize, Deserialize, Message)]
+#[derive(Clone, Serialize, Deserialize, Message)]
 pub enum PortalInternalMessage {
     /// Connection was dropped
     Disconnect,
+    /// The outlet didn't answer the compressed ping in time
+    PongTimeout,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PortalMessage, PortalMessageRef};
+    use ockam_core::Encodable;
+
+    #[test]
+    fn borrowed_message_has_same_encoding() {
+        let payload: Vec<u8> = (0..=255).collect();
+        assert_eq!(
+            PortalMessage::Payload(payload.clone()).encode().unwrap(),
+            PortalMessageRef::Payload(&payload).encode().unwrap()
+        );
+        assert_eq!(
+            PortalMessage::CompressedPayload(payload.clone())
+                .encode()
+                .unwrap(),
+            PortalMessageRef::CompressedPayload(&payload)
+                .encode()
+                .unwrap()
+        );
+        assert_eq!(
+            PortalMessage::Disconnect.encode().unwrap(),
+            PortalMessageRef::Disconnect.encode().unwrap()
+        );
+    }
 }
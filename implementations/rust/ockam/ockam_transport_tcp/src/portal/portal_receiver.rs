@@ -1,12 +1,14 @@
-use crate::{PortalInternalMessage, PortalMessage};
-use ockam_core::compat::vec::Vec;
+use crate::{
+    PortalCompression, PortalInternalMessage, PortalMessageRef, PortalReadHalf, PortalTraffic,
+};
+use ockam_core::compat::{sync::Arc, vec::Vec};
 use ockam_core::{async_trait, Encodable, LocalMessage, Route, TransportMessage};
 use ockam_core::{route, Address, Processor, Result};
 use ockam_node::Context;
-use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
+use tokio::io::AsyncReadExt;
 use tracing::{error, warn};
 
-const MAX_PAYLOAD_SIZE: usize = 48 * 1024;
+pub(crate) const MAX_PAYLOAD_SIZE: usize = 48 * 1024;
 
 /// A TCP Portal receiving message processor
 ///
@@ -15,19 +17,29 @@ const MAX_PAYLOAD_SIZE: usize = 48 * 1024;
 /// [`TcpPortalWorker::start_receiver`](crate::TcpPortalWorker::start_receiver)
 pub(crate) struct TcpPortalRecvProcessor {
     buf: Vec<u8>,
-    rx: OwnedReadHalf,
+    rx: PortalReadHalf,
     sender_address: Address,
     onward_route: Route,
+    traffic: Arc<PortalTraffic>,
+    compression: Option<PortalCompression>,
 }
 
 impl TcpPortalRecvProcessor {
     /// Create a new `TcpPortalRecvProcessor`
-    pub fn new(rx: OwnedReadHalf, sender_address: Address, onward_route: Route) -> Self {
+    pub fn new(
+        rx: PortalReadHalf,
+        sender_address: Address,
+        onward_route: Route,
+        traffic: Arc<PortalTraffic>,
+        compression: Option<PortalCompression>,
+    ) -> Self {
         Self {
             buf: Vec::with_capacity(MAX_PAYLOAD_SIZE),
             rx,
             sender_address,
             onward_route,
+            traffic,
+            compression,
         }
     }
 }
@@ -65,19 +77,39 @@ impl Processor for TcpPortalRecvProcessor {
             let msg = TransportMessage::v1(
                 self.onward_route.clone(),
                 self.sender_address.clone(),
-                PortalMessage::Disconnect.encode()?,
+                PortalMessageRef::Disconnect.encode()?,
             );
             ctx.forward(LocalMessage::new(msg, vec![])).await?;
 
             return Ok(false);
         }
 
+        let delay = self.traffic.on_read(self.buf.len());
+        if !delay.is_zero() {
+            ctx.sleep(delay).await;
+        }
+
         // Loop just in case buf was extended (should not happen though)
         for chunk in self.buf.chunks(MAX_PAYLOAD_SIZE) {
+            let compressed = match self.compression {
+                Some(compression) => Some(compression.compress(chunk)?),
+                None => None,
+            };
+            let payload = match &compressed {
+                // Payloads which don't shrink are sent as is
+                Some(compressed) if compressed.len() < chunk.len() => {
+                    self.traffic.on_portal_send(compressed.len());
+                    PortalMessageRef::CompressedPayload(compressed)
+                }
+                _ => {
+                    self.traffic.on_portal_send(chunk.len());
+                    PortalMessageRef::Payload(chunk)
+                }
+            };
             let msg = TransportMessage::v1(
                 self.onward_route.clone(),
                 self.sender_address.clone(),
-                PortalMessage::Payload(chunk.to_vec()).encode()?,
+                payload.encode()?,
             );
             ctx.forward(LocalMessage::new(msg, vec![])).await?;
         }
@@ -1,13 +1,15 @@
-use crate::{PortalInternalMessage, PortalMessage, TcpPortalRecvProcessor};
+use crate::{
+    split_portal_stream, PortalCompression, PortalInternalMessage, PortalMessage, PortalReadHalf,
+    PortalTls, PortalTraffic, PortalWriteHalf, TcpPortalRecvProcessor,
+};
 use core::time::Duration;
-use ockam_core::compat::{boxed::Box, net::SocketAddr};
+use ockam_core::compat::{boxed::Box, net::SocketAddr, vec::Vec};
 use ockam_core::{async_trait, AccessControl, AllowAll, Decodable, Mailbox, Mailboxes};
 use ockam_core::{Address, Any, Result, Route, Routed, Worker};
-use ockam_node::{Context, WorkerBuilder};
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
 use ockam_transport_core::TransportError;
 use std::sync::Arc;
 use tokio::io::AsyncWriteExt;
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
 use tracing::{debug, info, trace, warn};
 
@@ -17,14 +19,31 @@ use tracing::{debug, info, trace, warn};
 ///
 /// `Outlet`: `SendPong` -> `Initialized`
 /// `Inlet`: `SendPing` -> `ReceivePong` -> `Initialized`
+///
+/// The inlet carries the compression algorithms it offers until the
+/// outlet picks one of them. Outlets which don't support compression
+/// can't decode the compressed ping and never answer it, so the inlet pings
+/// again without an offer after [`COMPRESSED_PONG_TIMEOUT`].
 #[derive(Clone)]
 enum State {
-    SendPing { ping_route: Route },
-    SendPong { pong_route: Route },
-    ReceivePong,
+    SendPing {
+        ping_route: Route,
+        offer: Vec<PortalCompression>,
+    },
+    SendPong {
+        pong_route: Route,
+    },
+    ReceivePong {
+        ping_route: Route,
+        offer: Vec<PortalCompression>,
+    },
     Initialized,
 }
 
+/// How long the inlet waits for the answer to a compressed ping before it
+/// falls back to a plain ping
+const COMPRESSED_PONG_TIMEOUT: Duration = Duration::from_secs(3);
+
 /// Enumerate all portal types
 #[derive(Debug)]
 enum TypeName {
@@ -40,8 +59,10 @@ enum TypeName {
 /// after a new connection has been accepted.
 pub(crate) struct TcpPortalWorker {
     state: State,
-    tx: Option<OwnedWriteHalf>,
-    rx: Option<OwnedReadHalf>,
+    stream: Option<TcpStream>,
+    tx: Option<PortalWriteHalf>,
+    rx: Option<PortalReadHalf>,
+    tls: Option<PortalTls>,
     peer: SocketAddr,
     internal_address: Address,
     remote_address: Address,
@@ -49,24 +70,37 @@ pub(crate) struct TcpPortalWorker {
     remote_route: Option<Route>,
     is_disconnecting: bool,
     type_name: TypeName,
+    traffic: Arc<PortalTraffic>,
+    compression: Option<PortalCompression>,
+    pong_timeout: Option<DelayedEvent<PortalInternalMessage>>,
 }
 
 impl TcpPortalWorker {
     /// Start a new `TcpPortalWorker` of type [`TypeName::Inlet`]
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn start_new_inlet(
         ctx: &Context,
         stream: TcpStream,
         peer: SocketAddr,
         ping_route: Route,
         access_control: Arc<dyn AccessControl>,
+        traffic: Arc<PortalTraffic>,
+        tls: Option<PortalTls>,
+        compression: Vec<PortalCompression>,
     ) -> Result<Address> {
         Self::start(
             ctx,
             peer,
-            State::SendPing { ping_route },
+            State::SendPing {
+                ping_route,
+                offer: compression,
+            },
             Some(stream),
             TypeName::Inlet,
             access_control,
+            traffic,
+            tls,
+            None,
         )
         .await
     }
@@ -77,6 +111,9 @@ impl TcpPortalWorker {
         peer: SocketAddr,
         pong_route: Route,
         access_control: Arc<dyn AccessControl>,
+        traffic: Arc<PortalTraffic>,
+        tls: Option<PortalTls>,
+        compression: Option<PortalCompression>,
     ) -> Result<Address> {
         Self::start(
             ctx,
@@ -85,11 +122,15 @@ impl TcpPortalWorker {
             None,
             TypeName::Outlet,
             access_control,
+            traffic,
+            tls,
+            compression,
         )
         .await
     }
 
     /// Start a new `TcpPortalWorker`
+    #[allow(clippy::too_many_arguments)]
     async fn start(
         ctx: &Context,
         peer: SocketAddr,
@@ -97,6 +138,9 @@ impl TcpPortalWorker {
         stream: Option<TcpStream>,
         type_name: TypeName,
         access_control: Arc<dyn AccessControl>,
+        traffic: Arc<PortalTraffic>,
+        tls: Option<PortalTls>,
+        compression: Option<PortalCompression>,
     ) -> Result<Address> {
         let internal_addr = Address::random_local();
         let remote_addr = Address::random_local();
@@ -107,18 +151,12 @@ impl TcpPortalWorker {
             type_name, internal_addr, remote_addr
         );
 
-        let (rx, tx) = match stream {
-            Some(s) => {
-                let (rx, tx) = s.into_split();
-                (Some(rx), Some(tx))
-            }
-            None => (None, None),
-        };
-
         let sender = Self {
             state,
-            tx,
-            rx,
+            stream,
+            tx: None,
+            rx: None,
+            tls,
             peer,
             internal_address: internal_addr.clone(),
             remote_address: remote_addr.clone(),
@@ -126,6 +164,9 @@ impl TcpPortalWorker {
             receiver_address,
             is_disconnecting: false,
             type_name,
+            traffic,
+            compression,
+            pong_timeout: None,
         };
 
         let main_internal_mailbox = Mailbox::new(
@@ -156,8 +197,13 @@ impl TcpPortalWorker {
     /// Start a `TcpPortalRecvProcessor`
     async fn start_receiver(&mut self, ctx: &Context, onward_route: Route) -> Result<()> {
         if let Some(rx) = self.rx.take() {
-            let receiver =
-                TcpPortalRecvProcessor::new(rx, self.internal_address.clone(), onward_route);
+            let receiver = TcpPortalRecvProcessor::new(
+                rx,
+                self.internal_address.clone(),
+                onward_route,
+                self.traffic.clone(),
+                self.compression,
+            );
             ctx.start_processor(self.receiver_address.clone(), receiver)
                 .await
         } else {
@@ -244,30 +290,93 @@ impl TcpPortalWorker {
         Ok(())
     }
 
-    async fn handle_send_ping(&self, ctx: &Context, ping_route: Route) -> Result<State> {
-        // Force creation of Outlet on the other side
-        ctx.send_from_address(ping_route, PortalMessage::Ping, self.remote_address.clone())
+    async fn handle_send_ping(
+        &mut self,
+        ctx: &Context,
+        ping_route: Route,
+        offer: Vec<PortalCompression>,
+    ) -> Result<State> {
+        // The TLS handshake, if any, happens here rather than in the listener
+        // so that a slow client doesn't hold back the other connections
+        if let Some(stream) = self.stream.take() {
+            let (rx, tx) = split_portal_stream(stream, self.tls.as_ref()).await?;
+            self.rx = Some(rx);
+            self.tx = Some(tx);
+        }
+
+        // Force creation of Outlet on the other side. Outlets which don't
+        // support compression only understand the plain ping.
+        let ping = if offer.is_empty() {
+            PortalMessage::Ping
+        } else {
+            let mut timeout = DelayedEvent::create(
+                ctx,
+                self.internal_address.clone(),
+                PortalInternalMessage::PongTimeout,
+            )
+            .await?;
+            timeout.schedule(COMPRESSED_PONG_TIMEOUT).await?;
+            self.pong_timeout = Some(timeout);
+            PortalMessage::CompressedPing(offer.clone())
+        };
+        ctx.send_from_address(ping_route.clone(), ping, self.remote_address.clone())
             .await?;
 
         debug!("Inlet at: {} sent ping", self.internal_address);
 
-        Ok(State::ReceivePong)
+        Ok(State::ReceivePong { ping_route, offer })
     }
 
-    async fn handle_send_pong(&mut self, ctx: &Context, pong_route: Route) -> Result<State> {
-        // Respond to Inlet
+    /// Ping the outlet again without offering compression, after the
+    /// compressed ping went unanswered.
+    async fn handle_pong_timeout(&mut self, ctx: &Context, ping_route: Route) -> Result<State> {
         ctx.send_from_address(
-            pong_route.clone(),
-            PortalMessage::Pong,
+            ping_route.clone(),
+            PortalMessage::Ping,
             self.remote_address.clone(),
         )
         .await?;
 
+        debug!(
+            "Inlet at: {} received no pong, sent ping without compression",
+            self.internal_address
+        );
+
+        Ok(State::ReceivePong {
+            ping_route,
+            offer: Vec::new(),
+        })
+    }
+
+    /// Shut down the outlet which answered a ping after another outlet did,
+    /// when the ping was sent again.
+    async fn reject_pong(&self, ctx: &Context, pong_route: Route) -> Result<()> {
+        debug!(
+            "Inlet at: {} received a late pong, disconnecting its outlet",
+            self.internal_address
+        );
+        ctx.send_from_address(
+            pong_route,
+            PortalMessage::Disconnect,
+            self.remote_address.clone(),
+        )
+        .await
+    }
+
+    async fn handle_send_pong(&mut self, ctx: &Context, pong_route: Route) -> Result<State> {
+        // Respond to Inlet
+        let pong = match self.compression {
+            Some(compression) => PortalMessage::CompressedPong(compression),
+            None => PortalMessage::Pong,
+        };
+        ctx.send_from_address(pong_route.clone(), pong, self.remote_address.clone())
+            .await?;
+
         if self.tx.is_none() {
             let stream = TcpStream::connect(self.peer)
                 .await
                 .map_err(TransportError::from)?;
-            let (rx, tx) = stream.into_split();
+            let (rx, tx) = split_portal_stream(stream, self.tls.as_ref()).await?;
             self.tx = Some(tx);
             self.rx = Some(rx);
 
@@ -295,13 +404,13 @@ impl Worker for TcpPortalWorker {
         let state = self.clone_state();
 
         match state {
-            State::SendPing { ping_route } => {
-                self.state = self.handle_send_ping(ctx, ping_route.clone()).await?;
+            State::SendPing { ping_route, offer } => {
+                self.state = self.handle_send_ping(ctx, ping_route, offer).await?;
             }
             State::SendPong { pong_route } => {
                 self.state = self.handle_send_pong(ctx, pong_route.clone()).await?;
             }
-            State::ReceivePong | State::Initialized { .. } => {
+            State::ReceivePong { .. } | State::Initialized { .. } => {
                 return Err(TransportError::PortalInvalidState.into())
             }
         }
@@ -330,17 +439,30 @@ impl Worker for TcpPortalWorker {
         let state = self.clone_state();
 
         match state {
-            State::ReceivePong => {
+            State::ReceivePong { ping_route, offer } => {
                 if recipient == self.internal_address {
-                    return Err(TransportError::PortalInvalidState.into());
+                    return match PortalInternalMessage::decode(msg.payload())? {
+                        PortalInternalMessage::PongTimeout if !offer.is_empty() => {
+                            self.state = self.handle_pong_timeout(ctx, ping_route).await?;
+                            Ok(())
+                        }
+                        _ => Err(TransportError::PortalInvalidState.into()),
+                    };
                 }
 
                 let msg = PortalMessage::decode(msg.payload())?;
 
-                if let PortalMessage::Pong = msg {
-                } else {
-                    return Err(TransportError::Protocol.into());
-                }
+                self.compression = match msg {
+                    PortalMessage::Pong => None,
+                    PortalMessage::CompressedPong(c) if offer.contains(&c) => Some(c),
+                    // The answer to the compressed ping came after the
+                    // plain ping was sent
+                    PortalMessage::CompressedPong(_) if offer.is_empty() => {
+                        return self.reject_pong(ctx, return_route).await;
+                    }
+                    _ => return Err(TransportError::Protocol.into()),
+                };
+                self.pong_timeout = None;
 
                 self.start_receiver(ctx, return_route.clone()).await?;
 
@@ -368,6 +490,8 @@ impl Worker for TcpPortalWorker {
                             self.start_disconnection(ctx, DisconnectionReason::FailedRx)
                                 .await?;
                         }
+                        // The pong came just in time
+                        PortalInternalMessage::PongTimeout => {}
                     }
                 } else {
                     trace!(
@@ -379,34 +503,56 @@ impl Worker for TcpPortalWorker {
                     // Send to Tcp stream
                     let msg = PortalMessage::decode(msg.payload())?;
 
-                    match msg {
+                    let payload = match msg {
                         PortalMessage::Payload(payload) => {
-                            if let Some(tx) = &mut self.tx {
-                                match tx.write_all(&payload).await {
-                                    Ok(()) => {}
-                                    Err(err) => {
-                                        warn!(
-                                            "Failed to send message to peer {} with error: {}",
-                                            self.peer, err
-                                        );
-                                        self.start_disconnection(
-                                            ctx,
-                                            DisconnectionReason::FailedTx,
-                                        )
-                                        .await?;
-                                    }
-                                }
-                            } else {
-                                return Err(TransportError::PortalInvalidState.into());
-                            }
+                            self.traffic.on_portal_receive(payload.len());
+                            payload
+                        }
+                        PortalMessage::CompressedPayload(payload) => {
+                            let compression = self.compression.ok_or(TransportError::Protocol)?;
+                            self.traffic.on_portal_receive(payload.len());
+                            compression.decompress(&payload)?
                         }
                         PortalMessage::Disconnect => {
                             self.start_disconnection(ctx, DisconnectionReason::Remote)
                                 .await?;
+                            return Ok(());
                         }
-                        PortalMessage::Ping | PortalMessage::Pong => {
+                        PortalMessage::Pong | PortalMessage::CompressedPong(_)
+                            if self.remote_route.as_ref() != Some(&return_route) =>
+                        {
+                            // Both the compressed and the plain ping were
+                            // answered, by two outlets
+                            return self.reject_pong(ctx, return_route).await;
+                        }
+                        PortalMessage::Ping
+                        | PortalMessage::Pong
+                        | PortalMessage::CompressedPing(_)
+                        | PortalMessage::CompressedPong(_) => {
                             return Err(TransportError::Protocol.into());
                         }
+                    };
+
+                    let delay = self.traffic.on_write(payload.len());
+                    if !delay.is_zero() {
+                        ctx.sleep(delay).await;
+                    }
+                    if let Some(tx) = &mut self.tx {
+                        // TLS streams buffer written data until flushed
+                        let res = match tx.write_all(&payload).await {
+                            Ok(()) => tx.flush().await,
+                            err => err,
+                        };
+                        if let Err(err) = res {
+                            warn!(
+                                "Failed to send message to peer {} with error: {}",
+                                self.peer, err
+                            );
+                            self.start_disconnection(ctx, DisconnectionReason::FailedTx)
+                                .await?;
+                        }
+                    } else {
+                        return Err(TransportError::PortalInvalidState.into());
                     }
                 }
             }
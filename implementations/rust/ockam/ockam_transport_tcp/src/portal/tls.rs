@@ -0,0 +1,257 @@
+use ockam_core::compat::boxed::Box;
+use ockam_core::Result;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+
+/// Read half of a portal connection, plain or TLS
+pub(crate) type PortalReadHalf = Box<dyn AsyncRead + Send + Unpin>;
+/// Write half of a portal connection, plain or TLS
+pub(crate) type PortalWriteHalf = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// TLS applied by a portal to its TCP connection
+#[derive(Clone)]
+pub(crate) enum PortalTls {
+    /// The inlet terminates TLS, the plaintext goes through the portal
+    #[cfg(feature = "tls")]
+    Inlet(InletTls),
+    /// The outlet originates TLS to its target
+    #[cfg(feature = "tls")]
+    Outlet(OutletTls),
+}
+
+/// Split a portal connection, after the TLS handshake if any
+pub(crate) async fn split_portal_stream(
+    stream: TcpStream,
+    tls: Option<&PortalTls>,
+) -> Result<(PortalReadHalf, PortalWriteHalf)> {
+    match tls {
+        #[cfg(feature = "tls")]
+        Some(PortalTls::Inlet(tls)) => tls.accept(stream).await,
+        #[cfg(feature = "tls")]
+        Some(PortalTls::Outlet(tls)) => tls.connect(stream).await,
+        _ => {
+            let (rx, tx) = stream.into_split();
+            Ok((Box::new(rx), Box::new(tx)))
+        }
+    }
+}
+
+#[cfg(feature = "tls")]
+pub use with_tls::*;
+
+#[cfg(feature = "tls")]
+mod with_tls {
+    use super::{PortalReadHalf, PortalWriteHalf};
+    use ockam_core::compat::sync::Arc;
+    use ockam_core::Result;
+    use ockam_transport_core::TransportError;
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::path::Path;
+    use std::time::Duration;
+    use tokio::net::TcpStream;
+    use tokio_rustls::rustls::server::AllowAnyAuthenticatedClient;
+    use tokio_rustls::rustls::{
+        Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey, RootCertStore, ServerConfig,
+        ServerName,
+    };
+    use tokio_rustls::{TlsAcceptor, TlsConnector};
+    use tracing::{error, warn};
+
+    /// How long a peer has to complete the TLS handshake by default
+    const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// TLS termination by a portal inlet
+    #[derive(Clone)]
+    pub struct InletTls {
+        acceptor: TlsAcceptor,
+        handshake_timeout: Duration,
+    }
+
+    impl InletTls {
+        /// Terminate TLS with the PEM encoded certificate chain and private key.
+        ///
+        /// If a PEM encoded client CA is given, clients have to present a
+        /// certificate issued by it (mutual TLS).
+        pub fn new(
+            certificate_chain: impl AsRef<Path>,
+            private_key: impl AsRef<Path>,
+            client_ca: Option<&Path>,
+        ) -> Result<Self> {
+            let builder = ServerConfig::builder().with_safe_defaults();
+            let builder = match client_ca {
+                Some(ca) => builder
+                    .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(load_roots(ca)?)),
+                None => builder.with_no_client_auth(),
+            };
+            let config = builder
+                .with_single_cert(
+                    load_certificates(certificate_chain.as_ref())?,
+                    load_private_key(private_key.as_ref())?,
+                )
+                .map_err(|err| {
+                    error!(%err, "invalid inlet TLS certificate");
+                    TransportError::InvalidTlsConfig
+                })?;
+            Ok(Self {
+                acceptor: TlsAcceptor::from(Arc::new(config)),
+                handshake_timeout: HANDSHAKE_TIMEOUT,
+            })
+        }
+
+        /// Drop clients which don't complete the handshake within `timeout`
+        pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+            self.handshake_timeout = timeout;
+            self
+        }
+
+        pub(crate) async fn accept(
+            &self,
+            stream: TcpStream,
+        ) -> Result<(PortalReadHalf, PortalWriteHalf)> {
+            let handshake = self.acceptor.accept(stream);
+            let stream = tokio::time::timeout(self.handshake_timeout, handshake)
+                .await
+                .map_err(|_| {
+                    warn!("inlet TLS handshake timed out");
+                    TransportError::TlsHandshakeTimeout
+                })?
+                .map_err(|err| {
+                    warn!(%err, "inlet TLS handshake failed");
+                    TransportError::from(err)
+                })?;
+            let (rx, tx) = tokio::io::split(stream);
+            Ok((Box::new(rx), Box::new(tx)))
+        }
+    }
+
+    /// TLS origination by a portal outlet
+    #[derive(Clone)]
+    pub struct OutletTls {
+        connector: TlsConnector,
+        server_name: ServerName,
+        handshake_timeout: Duration,
+    }
+
+    impl OutletTls {
+        /// Originate TLS to the target, whose certificate must be valid for
+        /// `server_name` and issued by the PEM encoded CA, or by one of the
+        /// webpki roots if none is given.
+        ///
+        /// If a PEM encoded client certificate chain and private key are
+        /// given, they are presented to the target (mutual TLS).
+        pub fn new(
+            server_name: &str,
+            ca: Option<&Path>,
+            client_certificate: Option<(&Path, &Path)>,
+        ) -> Result<Self> {
+            let roots = match ca {
+                Some(ca) => load_roots(ca)?,
+                None => {
+                    let mut roots = RootCertStore::empty();
+                    roots.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(
+                        |ta| {
+                            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                                ta.subject,
+                                ta.spki,
+                                ta.name_constraints,
+                            )
+                        },
+                    ));
+                    roots
+                }
+            };
+            let builder = ClientConfig::builder()
+                .with_safe_defaults()
+                .with_root_certificates(roots);
+            let config = match client_certificate {
+                Some((chain, key)) => builder
+                    .with_single_cert(load_certificates(chain)?, load_private_key(key)?)
+                    .map_err(|err| {
+                        error!(%err, "invalid outlet TLS client certificate");
+                        TransportError::InvalidTlsConfig
+                    })?,
+                None => builder.with_no_client_auth(),
+            };
+            let server_name = ServerName::try_from(server_name).map_err(|err| {
+                error!(%err, %server_name, "invalid outlet TLS server name");
+                TransportError::InvalidTlsConfig
+            })?;
+            Ok(Self {
+                connector: TlsConnector::from(Arc::new(config)),
+                server_name,
+                handshake_timeout: HANDSHAKE_TIMEOUT,
+            })
+        }
+
+        /// Give up on targets which don't complete the handshake within
+        /// `timeout`
+        pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+            self.handshake_timeout = timeout;
+            self
+        }
+
+        pub(crate) async fn connect(
+            &self,
+            stream: TcpStream,
+        ) -> Result<(PortalReadHalf, PortalWriteHalf)> {
+            let handshake = self.connector.connect(self.server_name.clone(), stream);
+            let stream = tokio::time::timeout(self.handshake_timeout, handshake)
+                .await
+                .map_err(|_| {
+                    warn!("outlet TLS handshake timed out");
+                    TransportError::TlsHandshakeTimeout
+                })?
+                .map_err(|err| {
+                    warn!(%err, "outlet TLS handshake failed");
+                    TransportError::from(err)
+                })?;
+            let (rx, tx) = tokio::io::split(stream);
+            Ok((Box::new(rx), Box::new(tx)))
+        }
+    }
+
+    fn open(path: &Path) -> Result<BufReader<File>> {
+        File::open(path).map(BufReader::new).map_err(|err| {
+            error!(%err, path = %path.display(), "could not read TLS file");
+            TransportError::InvalidTlsConfig.into()
+        })
+    }
+
+    fn load_certificates(path: &Path) -> Result<Vec<Certificate>> {
+        let certificates = rustls_pemfile::certs(&mut open(path)?)
+            .map_err(|_| TransportError::InvalidTlsConfig)?;
+        if certificates.is_empty() {
+            error!(path = %path.display(), "no certificate found");
+            return Err(TransportError::InvalidTlsConfig.into());
+        }
+        Ok(certificates.into_iter().map(Certificate).collect())
+    }
+
+    fn load_private_key(path: &Path) -> Result<PrivateKey> {
+        use rustls_pemfile::Item;
+        let items = rustls_pemfile::read_all(&mut open(path)?)
+            .map_err(|_| TransportError::InvalidTlsConfig)?;
+        items
+            .into_iter()
+            .find_map(|item| match item {
+                Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key) => Some(PrivateKey(key)),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                error!(path = %path.display(), "no private key found");
+                TransportError::InvalidTlsConfig.into()
+            })
+    }
+
+    fn load_roots(path: &Path) -> Result<RootCertStore> {
+        let mut roots = RootCertStore::empty();
+        for certificate in load_certificates(path)? {
+            roots.add(&certificate).map_err(|err| {
+                error!(%err, path = %path.display(), "invalid CA certificate");
+                TransportError::InvalidTlsConfig
+            })?;
+        }
+        Ok(roots)
+    }
+}
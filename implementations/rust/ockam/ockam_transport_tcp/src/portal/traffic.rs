@@ -0,0 +1,222 @@
+use core::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Bandwidth cap of a portal
+///
+/// The cap applies to the traffic of all the connections of an inlet or
+/// outlet, in both directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    bytes_per_sec: u64,
+    burst: u64,
+}
+
+impl RateLimit {
+    /// Allow `bytes_per_sec` on average, and up to `burst` bytes at once.
+    ///
+    /// The burst is raised to `bytes_per_sec` if it is lower.
+    pub fn new(bytes_per_sec: u64, burst: u64) -> Self {
+        Self {
+            bytes_per_sec: bytes_per_sec.max(1),
+            burst: burst.max(bytes_per_sec),
+        }
+    }
+
+    /// Average number of bytes per second allowed.
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.bytes_per_sec
+    }
+
+    /// Number of bytes allowed at once.
+    pub fn burst(&self) -> u64 {
+        self.burst
+    }
+}
+
+/// Traffic of a portal
+///
+/// Shared by all the connections of an inlet or outlet, it enforces the
+/// optional [`RateLimit`] and counts the bytes going through.
+#[derive(Debug, Default)]
+pub struct PortalTraffic {
+    bucket: Option<Mutex<TokenBucket>>,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    throttled: AtomicU64,
+    portal_bytes_sent: AtomicU64,
+    portal_bytes_received: AtomicU64,
+}
+
+impl PortalTraffic {
+    /// Create a new `PortalTraffic`, capped by the given [`RateLimit`].
+    pub fn new(limit: Option<RateLimit>) -> Self {
+        Self {
+            bucket: limit.map(|l| Mutex::new(TokenBucket::new(l, Instant::now()))),
+            ..Default::default()
+        }
+    }
+
+    /// The [`RateLimit`] of the portal, if any.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        let bucket = self.bucket.as_ref()?;
+        let bucket = bucket.lock().unwrap_or_else(|e| e.into_inner());
+        Some(bucket.limit)
+    }
+
+    /// Bytes read from the tcp peers and sent to the other side of the portal.
+    pub fn bytes_in(&self) -> u64 {
+        self.bytes_in.load(Ordering::Relaxed)
+    }
+
+    /// Bytes received from the other side of the portal and written to the tcp peers.
+    pub fn bytes_out(&self) -> u64 {
+        self.bytes_out.load(Ordering::Relaxed)
+    }
+
+    /// Number of times a connection had to wait for the rate limit.
+    pub fn throttled(&self) -> u64 {
+        self.throttled.load(Ordering::Relaxed)
+    }
+
+    /// Payload bytes sent to the other side of the portal, after compression.
+    pub fn portal_bytes_sent(&self) -> u64 {
+        self.portal_bytes_sent.load(Ordering::Relaxed)
+    }
+
+    /// Payload bytes received from the other side of the portal, before decompression.
+    pub fn portal_bytes_received(&self) -> u64 {
+        self.portal_bytes_received.load(Ordering::Relaxed)
+    }
+
+    /// Ratio of the tcp bytes to the payload bytes going through the portal,
+    /// `1.0` when nothing is compressed. `None` until some traffic went through.
+    pub fn compression_ratio(&self) -> Option<f64> {
+        let portal = self.portal_bytes_sent() + self.portal_bytes_received();
+        if portal == 0 {
+            return None;
+        }
+        Some((self.bytes_in() + self.bytes_out()) as f64 / portal as f64)
+    }
+
+    /// Account for `n` bytes read from a tcp peer, returning how long to
+    /// wait before forwarding them.
+    pub(crate) fn on_read(&self, n: usize) -> Duration {
+        self.bytes_in.fetch_add(n as u64, Ordering::Relaxed);
+        self.reserve(n)
+    }
+
+    /// Account for `n` bytes to write to a tcp peer, returning how long to
+    /// wait before writing them.
+    pub(crate) fn on_write(&self, n: usize) -> Duration {
+        self.bytes_out.fetch_add(n as u64, Ordering::Relaxed);
+        self.reserve(n)
+    }
+
+    /// Account for `n` payload bytes sent to the other side of the portal.
+    pub(crate) fn on_portal_send(&self, n: usize) {
+        self.portal_bytes_sent
+            .fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    /// Account for `n` payload bytes received from the other side of the portal.
+    pub(crate) fn on_portal_receive(&self, n: usize) {
+        self.portal_bytes_received
+            .fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    fn reserve(&self, n: usize) -> Duration {
+        let delay = match &self.bucket {
+            Some(bucket) => {
+                let mut bucket = bucket.lock().unwrap_or_else(|e| e.into_inner());
+                bucket.reserve(n as u64, Instant::now())
+            }
+            None => Duration::ZERO,
+        };
+        if !delay.is_zero() {
+            self.throttled.fetch_add(1, Ordering::Relaxed);
+        }
+        delay
+    }
+}
+
+/// A token bucket which lets callers go into debt.
+///
+/// Bytes are always accepted, the returned delay is the time needed for
+/// the bucket to be refilled up to the bytes taken.
+#[derive(Debug)]
+struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst as f64,
+            last: now,
+        }
+    }
+
+    fn reserve(&mut self, n: u64, now: Instant) -> Duration {
+        let rate = self.limit.bytes_per_sec as f64;
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.tokens = (self.tokens + elapsed * rate).min(self.limit.burst as f64);
+        self.tokens -= n as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_is_not_delayed() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(RateLimit::new(1000, 4000), now);
+        assert_eq!(bucket.reserve(4000, now), Duration::ZERO);
+        assert_eq!(bucket.reserve(500, now), Duration::from_millis(500));
+        // The debt is carried over to the next caller.
+        assert_eq!(bucket.reserve(500, now), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn bucket_refills_up_to_burst() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(RateLimit::new(1000, 1000), now);
+        assert_eq!(bucket.reserve(1000, now), Duration::ZERO);
+        let later = now + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(1000, later), Duration::ZERO);
+        assert_eq!(bucket.reserve(1000, later), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn unlimited_traffic_is_counted() {
+        let traffic = PortalTraffic::new(None);
+        assert_eq!(traffic.on_read(10), Duration::ZERO);
+        assert_eq!(traffic.on_write(20), Duration::ZERO);
+        assert_eq!(traffic.bytes_in(), 10);
+        assert_eq!(traffic.bytes_out(), 20);
+        assert_eq!(traffic.throttled(), 0);
+    }
+
+    #[test]
+    fn compression_ratio() {
+        let traffic = PortalTraffic::new(None);
+        assert_eq!(traffic.compression_ratio(), None);
+        traffic.on_read(300);
+        traffic.on_portal_send(100);
+        traffic.on_write(100);
+        traffic.on_portal_receive(100);
+        assert_eq!(traffic.compression_ratio(), Some(2.0));
+    }
+}
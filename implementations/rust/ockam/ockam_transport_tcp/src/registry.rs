@@ -0,0 +1,298 @@
+use core::time::Duration;
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::Address;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Largest message the TCP framing can carry
+///
+/// Messages are prefixed with their length as a big-endian `u16`.
+pub const MAX_MESSAGE_SIZE: usize = u16::MAX as usize;
+
+/// Which side opened a TCP connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpConnectionMode {
+    /// Opened by this node
+    Outgoing,
+    /// Accepted by one of the listeners of this node
+    Incoming,
+}
+
+/// Snapshot of an open TCP connection
+#[derive(Debug, Clone)]
+pub struct TcpConnectionInfo {
+    address: Address,
+    peer: SocketAddr,
+    mode: TcpConnectionMode,
+    age: Duration,
+    bytes_sent: u64,
+    bytes_received: u64,
+    max_message_size: usize,
+}
+
+impl TcpConnectionInfo {
+    /// Address of the worker sending messages over the connection.
+    ///
+    /// Pass it to [`TcpTransport::close_connection`](crate::TcpTransport::close_connection)
+    /// to close the connection.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Address of the remote peer.
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// Which side opened the connection.
+    pub fn mode(&self) -> TcpConnectionMode {
+        self.mode
+    }
+
+    /// Time since the connection was established.
+    pub fn age(&self) -> Duration {
+        self.age
+    }
+
+    /// Bytes written to the connection, including framing and heartbeats.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    /// Bytes read from the connection, including framing and heartbeats.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    /// Largest encoded message sent or accepted over the connection.
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+}
+
+/// Snapshot of a TCP listener
+#[derive(Debug, Clone)]
+pub struct TcpListenerInfo {
+    address: Address,
+    bind: SocketAddr,
+    age: Duration,
+    accepted: u64,
+}
+
+impl TcpListenerInfo {
+    /// Address of the processor accepting connections.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Socket address the listener is bound to.
+    pub fn bind(&self) -> SocketAddr {
+        self.bind
+    }
+
+    /// Time since the listener was bound.
+    pub fn age(&self) -> Duration {
+        self.age
+    }
+
+    /// Number of connections accepted so far.
+    pub fn accepted(&self) -> u64 {
+        self.accepted
+    }
+}
+
+/// Counters of a connection, shared by its sender and receiver.
+#[derive(Debug)]
+pub(crate) struct TcpConnectionStats {
+    peer: SocketAddr,
+    mode: TcpConnectionMode,
+    started: Instant,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    max_message_size: usize,
+}
+
+impl TcpConnectionStats {
+    pub(crate) fn new(peer: SocketAddr, mode: TcpConnectionMode, max_message_size: usize) -> Self {
+        Self {
+            peer,
+            mode,
+            started: Instant::now(),
+            bytes_sent: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            max_message_size,
+        }
+    }
+
+    pub(crate) fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    pub(crate) fn on_send(&self, n: usize) {
+        self.bytes_sent.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn on_receive(&self, n: usize) {
+        self.bytes_received.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    fn info(&self, address: &Address) -> TcpConnectionInfo {
+        TcpConnectionInfo {
+            address: address.clone(),
+            peer: self.peer,
+            mode: self.mode,
+            age: self.started.elapsed(),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            max_message_size: self.max_message_size,
+        }
+    }
+}
+
+/// Counters of a listener.
+#[derive(Debug)]
+pub(crate) struct TcpListenerStats {
+    bind: SocketAddr,
+    started: Instant,
+    accepted: AtomicU64,
+}
+
+impl TcpListenerStats {
+    pub(crate) fn new(bind: SocketAddr) -> Self {
+        Self {
+            bind,
+            started: Instant::now(),
+            accepted: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn on_accept(&self) {
+        self.accepted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn info(&self, address: &Address) -> TcpListenerInfo {
+        TcpListenerInfo {
+            address: address.clone(),
+            bind: self.bind,
+            age: self.started.elapsed(),
+            accepted: self.accepted.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The open connections and listeners of a transport, by worker address
+#[derive(Debug)]
+pub(crate) struct TcpRegistry {
+    connections: Mutex<BTreeMap<Address, Arc<TcpConnectionStats>>>,
+    listeners: Mutex<BTreeMap<Address, Arc<TcpListenerStats>>>,
+    max_message_size: AtomicUsize,
+}
+
+impl Default for TcpRegistry {
+    fn default() -> Self {
+        Self {
+            connections: Mutex::default(),
+            listeners: Mutex::default(),
+            max_message_size: AtomicUsize::new(MAX_MESSAGE_SIZE),
+        }
+    }
+}
+
+impl TcpRegistry {
+    /// Largest encoded message sent or accepted by the connections.
+    pub(crate) fn max_message_size(&self) -> usize {
+        self.max_message_size.load(Ordering::Relaxed)
+    }
+
+    /// Set the message size limit of new connections, capped by
+    /// [`MAX_MESSAGE_SIZE`].
+    ///
+    /// Returns the limit that was applied.
+    pub(crate) fn set_max_message_size(&self, n: usize) -> usize {
+        let n = n.min(MAX_MESSAGE_SIZE);
+        self.max_message_size.store(n, Ordering::Relaxed);
+        n
+    }
+
+    pub(crate) fn add_connection(&self, address: Address, stats: Arc<TcpConnectionStats>) {
+        lock(&self.connections).insert(address, stats);
+    }
+
+    pub(crate) fn remove_connection(&self, address: &Address) {
+        lock(&self.connections).remove(address);
+    }
+
+    pub(crate) fn has_connection(&self, address: &Address) -> bool {
+        lock(&self.connections).contains_key(address)
+    }
+
+    pub(crate) fn connections(&self) -> Vec<TcpConnectionInfo> {
+        lock(&self.connections)
+            .iter()
+            .map(|(a, s)| s.info(a))
+            .collect()
+    }
+
+    pub(crate) fn add_listener(&self, address: Address, stats: Arc<TcpListenerStats>) {
+        lock(&self.listeners).insert(address, stats);
+    }
+
+    pub(crate) fn remove_listener(&self, address: &Address) {
+        lock(&self.listeners).remove(address);
+    }
+
+    pub(crate) fn listeners(&self) -> Vec<TcpListenerInfo> {
+        lock(&self.listeners)
+            .iter()
+            .map(|(a, s)| s.info(a))
+            .collect()
+    }
+}
+
+fn lock<T>(m: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connections_are_counted_until_removed() {
+        let registry = TcpRegistry::default();
+        let peer: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        let stats = Arc::new(TcpConnectionStats::new(
+            peer,
+            TcpConnectionMode::Outgoing,
+            MAX_MESSAGE_SIZE,
+        ));
+        let address = Address::random_local();
+        registry.add_connection(address.clone(), stats.clone());
+
+        stats.on_send(10);
+        stats.on_send(5);
+        stats.on_receive(7);
+        let connections = registry.connections();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].address(), &address);
+        assert_eq!(connections[0].peer(), peer);
+        assert_eq!(connections[0].bytes_sent(), 15);
+        assert_eq!(connections[0].bytes_received(), 7);
+
+        assert!(registry.has_connection(&address));
+        registry.remove_connection(&address);
+        assert!(!registry.has_connection(&address));
+        assert!(registry.connections().is_empty());
+    }
+
+    #[test]
+    fn max_message_size_is_capped_by_the_framing() {
+        let registry = TcpRegistry::default();
+        assert_eq!(registry.max_message_size(), MAX_MESSAGE_SIZE);
+        assert_eq!(registry.set_max_message_size(1024), 1024);
+        assert_eq!(registry.max_message_size(), 1024);
+        assert_eq!(registry.set_max_message_size(usize::MAX), MAX_MESSAGE_SIZE);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    parse_socket_addr, TcpInletListenProcessor, TcpListenProcessor, TcpRouterRequest,
-    TcpRouterResponse, WorkerPair, TCP,
+    parse_socket_addr, PortalCompression, PortalTls, PortalTraffic, TcpInletListenProcessor,
+    TcpListenProcessor, TcpRegistry, TcpRouterRequest, TcpRouterResponse, WorkerPair, TCP,
 };
 use ockam_core::compat::net::{SocketAddr, ToSocketAddrs};
 use ockam_core::{async_trait, compat::boxed::Box, AccessControl};
@@ -16,33 +16,60 @@ use tracing::debug;
 pub(crate) struct TcpRouterHandle {
     ctx: Context,
     api_addr: Address,
+    registry: Arc<TcpRegistry>,
 }
 
 #[async_trait]
 impl AsyncTryClone for TcpRouterHandle {
     async fn async_try_clone(&self) -> Result<Self> {
         let child_ctx = self.ctx.new_detached(Address::random_local()).await?;
-        Ok(Self::new(child_ctx, self.api_addr.clone()))
+        Ok(Self::new(
+            child_ctx,
+            self.api_addr.clone(),
+            self.registry.clone(),
+        ))
     }
 }
 
 impl TcpRouterHandle {
     /// Create a new `TcpRouterHandle` with the given address
-    pub(crate) fn new(ctx: Context, api_addr: Address) -> Self {
-        TcpRouterHandle { ctx, api_addr }
+    pub(crate) fn new(ctx: Context, api_addr: Address, registry: Arc<TcpRegistry>) -> Self {
+        TcpRouterHandle {
+            ctx,
+            api_addr,
+            registry,
+        }
     }
 
     /// Return a reference to the router handle's [`Context`]
     pub fn ctx(&self) -> &Context {
         &self.ctx
     }
+
+    /// Return the open connections and listeners of the router
+    pub(crate) fn registry(&self) -> &Arc<TcpRegistry> {
+        &self.registry
+    }
 }
 
 impl TcpRouterHandle {
     /// Bind an incoming connection listener for this router
-    pub async fn bind(&self, addr: impl Into<SocketAddr>) -> Result<SocketAddr> {
+    ///
+    /// Connections accepted by the listener use `max_message_size` when
+    /// given, and the limit of the transport otherwise.
+    pub async fn bind(
+        &self,
+        addr: impl Into<SocketAddr>,
+        max_message_size: Option<usize>,
+    ) -> Result<SocketAddr> {
         let socket_addr = addr.into();
-        TcpListenProcessor::start(&self.ctx, self.async_try_clone().await?, socket_addr).await
+        TcpListenProcessor::start(
+            &self.ctx,
+            self.async_try_clone().await?,
+            socket_addr,
+            max_message_size,
+        )
+        .await
     }
 
     /// Establish an outgoing TCP connection on an existing transport
@@ -83,6 +110,15 @@ impl TcpRouterHandle {
         }
     }
 
+    /// Close the connection of the given sender worker
+    pub async fn close(&self, self_addr: &Address) -> Result<()> {
+        if !self.registry.has_connection(self_addr) {
+            return Err(TransportError::PeerNotFound.into());
+        }
+        self.unregister(self_addr.clone()).await?;
+        self.ctx.stop_worker(self_addr.clone()).await
+    }
+
     /// Register a new connection worker with this router
     pub async fn register(&self, pair: &WorkerPair) -> Result<()> {
         let tcp_address: Address = format!("{}#{}", TCP, pair.peer()).into();
@@ -144,10 +180,12 @@ impl TcpRouterHandle {
             hostnames = vec![];
         }
         // Try to resolve hostname
-        else if let Ok(mut iter) = peer_str.to_socket_addrs() {
-            // FIXME: We only take ipv4 for now
-            if let Some(p) = iter.find(|x| x.is_ipv4()) {
-                peer_addr = p;
+        else if let Ok(iter) = peer_str.to_socket_addrs() {
+            // Prefer an ipv4 address, but fall back to ipv6 for hosts
+            // which only resolve to ipv6 addresses
+            let addrs: Vec<SocketAddr> = iter.collect();
+            if let Some(p) = addrs.iter().find(|x| x.is_ipv4()).or_else(|| addrs.first()) {
+                peer_addr = *p;
             } else {
                 return Err(TransportError::InvalidAddress.into());
             }
@@ -170,6 +208,9 @@ impl TcpRouterHandle {
         outlet_listener_route: impl Into<Route>,
         addr: impl Into<SocketAddr>,
         access_control: Arc<dyn AccessControl>,
+        traffic: Arc<PortalTraffic>,
+        tls: Option<PortalTls>,
+        compression: Vec<PortalCompression>,
     ) -> Result<(Address, SocketAddr)> {
         let socket_addr = addr.into();
         TcpInletListenProcessor::start(
@@ -177,6 +218,9 @@ impl TcpRouterHandle {
             outlet_listener_route.into(),
             socket_addr,
             access_control,
+            traffic,
+            tls,
+            compression,
         )
         .await
     }
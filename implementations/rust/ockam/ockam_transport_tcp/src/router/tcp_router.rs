@@ -1,10 +1,14 @@
-use crate::{TcpRouterHandle, TcpRouterRequest, TcpRouterResponse, TcpSendWorker, TCP};
+use crate::{
+    TcpConnectionMode, TcpRegistry, TcpRouterHandle, TcpRouterRequest, TcpRouterResponse,
+    TcpSendWorker, TCP,
+};
 use core::ops::Deref;
 use ockam_core::{async_trait, Any};
 use ockam_core::{Address, Decodable, LocalMessage, Result, Routed, Worker};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
 use std::collections::BTreeMap;
+use std::sync::Arc;
 use tracing::{debug, error, trace};
 
 /// A TCP address router and connection listener
@@ -21,6 +25,7 @@ pub(crate) struct TcpRouter {
     api_addr: Address,
     map: BTreeMap<Address, Address>,
     allow_auto_connection: bool,
+    registry: Arc<TcpRegistry>,
 }
 
 impl TcpRouter {
@@ -38,6 +43,7 @@ impl TcpRouter {
             api_addr: api_addr.clone(),
             map: BTreeMap::new(),
             allow_auto_connection: true,
+            registry: Arc::new(TcpRegistry::default()),
         };
 
         let handle = router.create_self_handle().await?;
@@ -53,7 +59,7 @@ impl TcpRouter {
     /// Create a new `TcpRouterHandle` representing this router
     async fn create_self_handle(&self) -> Result<TcpRouterHandle> {
         let handle_ctx = self.ctx.new_detached(Address::random_local()).await?;
-        let handle = TcpRouterHandle::new(handle_ctx, self.api_addr.clone());
+        let handle = TcpRouterHandle::new(handle_ctx, self.api_addr.clone(), self.registry.clone());
         Ok(handle)
     }
 }
@@ -111,9 +117,16 @@ impl TcpRouter {
         // Start a new `WorkerPair` for the given peer containing a
         // `TcpSendWorker` and `TcpRecvprocessor`
         let router_handle = self.create_self_handle().await?;
-        let pair =
-            TcpSendWorker::start_pair(&self.ctx, router_handle, None, peer_addr, hostnames.clone())
-                .await?;
+        let pair = TcpSendWorker::start_pair(
+            &self.ctx,
+            router_handle,
+            None,
+            peer_addr,
+            hostnames.clone(),
+            TcpConnectionMode::Outgoing,
+            self.registry.max_message_size(),
+        )
+        .await?;
 
         // Send this `TcpRouter` a `TcpRouterRequest::Register` message
         // containing the registration request
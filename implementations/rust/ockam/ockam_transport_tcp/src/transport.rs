@@ -2,9 +2,15 @@ use ockam_core::access_control::AccessControl;
 use ockam_core::compat::{boxed::Box, net::SocketAddr};
 use ockam_core::{Address, AllowAll, AsyncTryClone, Result, Route};
 use ockam_node::Context;
+use ockam_transport_core::TransportError;
 use std::sync::Arc;
+use tokio::net::TcpStream;
 
-use crate::{parse_socket_addr, TcpOutletListenWorker, TcpRouter, TcpRouterHandle};
+use crate::{
+    parse_socket_addr, PortalCompression, PortalTls, PortalTraffic, TcpConnectionInfo,
+    TcpListenerInfo, TcpOutletListenWorker, TcpPortalWorker, TcpRouter, TcpRouterHandle,
+    MAX_MESSAGE_SIZE,
+};
 
 /// High level management interface for TCP transports
 ///
@@ -92,6 +98,12 @@ impl TcpTransport {
         self.router_handle.disconnect(peer.as_ref()).await
     }
 
+    /// Resolve a peer, which may be a host name, to the socket address
+    /// a new connection to it would be made to
+    pub fn resolve<S: AsRef<str>>(&self, peer: S) -> Result<SocketAddr> {
+        TcpRouterHandle::resolve_peer(peer.as_ref()).map(|(addr, _)| addr)
+    }
+
     /// Start listening to incoming connections on an existing transport
     ///
     /// Returns the local address that this transport is bound to.
@@ -109,7 +121,59 @@ impl TcpTransport {
     /// # Ok(()) }
     pub async fn listen<S: AsRef<str>>(&self, bind_addr: S) -> Result<SocketAddr> {
         let bind_addr = parse_socket_addr(bind_addr.as_ref())?;
-        self.router_handle.bind(bind_addr).await
+        self.router_handle.bind(bind_addr, None).await
+    }
+
+    /// Start listening like [`listen`](crate::TcpTransport::listen), with
+    /// a message size limit for the accepted connections that overrides
+    /// the one of the transport
+    ///
+    /// The limit is capped by [`MAX_MESSAGE_SIZE`](crate::MAX_MESSAGE_SIZE).
+    pub async fn listen_with_max_message_size<S: AsRef<str>>(
+        &self,
+        bind_addr: S,
+        max_message_size: usize,
+    ) -> Result<SocketAddr> {
+        let bind_addr = parse_socket_addr(bind_addr.as_ref())?;
+        let max_message_size = max_message_size.min(MAX_MESSAGE_SIZE);
+        self.router_handle
+            .bind(bind_addr, Some(max_message_size))
+            .await
+    }
+
+    /// Largest encoded message new connections send or accept
+    ///
+    /// Larger messages are dropped with [`TransportError::Capacity`]
+    /// rather than sent truncated. Defaults to
+    /// [`MAX_MESSAGE_SIZE`](crate::MAX_MESSAGE_SIZE).
+    pub fn max_message_size(&self) -> usize {
+        self.router_handle.registry().max_message_size()
+    }
+
+    /// Set the message size limit of the connections opened from now on
+    ///
+    /// Returns the limit applied, which is capped by
+    /// [`MAX_MESSAGE_SIZE`](crate::MAX_MESSAGE_SIZE).
+    pub fn set_max_message_size(&self, max_message_size: usize) -> usize {
+        self.router_handle
+            .registry()
+            .set_max_message_size(max_message_size)
+    }
+
+    /// The open connections of this transport, both outgoing and incoming
+    pub fn connections(&self) -> Vec<TcpConnectionInfo> {
+        self.router_handle.registry().connections()
+    }
+
+    /// The listeners of this transport
+    pub fn listeners(&self) -> Vec<TcpListenerInfo> {
+        self.router_handle.registry().listeners()
+    }
+
+    /// Close an open connection, given the address of its sending worker
+    /// as found in [`connections`](crate::TcpTransport::connections)
+    pub async fn close_connection(&self, address: &Address) -> Result<()> {
+        self.router_handle.close(address).await
     }
 }
 
@@ -118,6 +182,9 @@ pub struct InletOptions {
     bind_addr: String,
     outlet_route: Route,
     access_control: Arc<dyn AccessControl>,
+    traffic: Arc<PortalTraffic>,
+    tls: Option<PortalTls>,
+    compression: Vec<PortalCompression>,
 }
 
 impl InletOptions {
@@ -132,8 +199,33 @@ impl InletOptions {
             bind_addr,
             outlet_route,
             access_control,
+            traffic: Arc::new(PortalTraffic::default()),
+            tls: None,
+            compression: Vec::new(),
         }
     }
+
+    /// Account the traffic of the inlet, and enforce its rate limit, with the given
+    /// [`PortalTraffic`]
+    pub fn with_traffic(mut self, traffic: Arc<PortalTraffic>) -> Self {
+        self.traffic = traffic;
+        self
+    }
+
+    /// Terminate TLS on the accepted connections, only the plaintext goes
+    /// through the portal
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: crate::InletTls) -> Self {
+        self.tls = Some(PortalTls::Inlet(tls));
+        self
+    }
+
+    /// Offer to compress the payloads with one of the given algorithms, in
+    /// order of preference. The outlet must support compression too.
+    pub fn with_compression(mut self, compression: Vec<PortalCompression>) -> Self {
+        self.compression = compression;
+        self
+    }
 }
 
 /// Args to start an Outlet
@@ -141,6 +233,9 @@ pub struct OutletOptions {
     address: Address,
     peer: String,
     access_control: Arc<dyn AccessControl>,
+    traffic: Arc<PortalTraffic>,
+    tls: Option<PortalTls>,
+    compression: Vec<PortalCompression>,
 }
 
 impl OutletOptions {
@@ -151,8 +246,32 @@ impl OutletOptions {
             address,
             peer,
             access_control,
+            traffic: Arc::new(PortalTraffic::default()),
+            tls: None,
+            compression: PortalCompression::supported(),
         }
     }
+
+    /// Account the traffic of the outlet, and enforce its rate limit, with the given
+    /// [`PortalTraffic`]
+    pub fn with_traffic(mut self, traffic: Arc<PortalTraffic>) -> Self {
+        self.traffic = traffic;
+        self
+    }
+
+    /// Originate TLS on the connections to the peer
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: crate::OutletTls) -> Self {
+        self.tls = Some(PortalTls::Outlet(tls));
+        self
+    }
+
+    /// Only accept the given compression algorithms from inlets, all the
+    /// [`PortalCompression::supported`] ones are accepted by default
+    pub fn with_compression(mut self, compression: Vec<PortalCompression>) -> Self {
+        self.compression = compression;
+        self
+    }
 }
 
 impl TcpTransport {
@@ -163,7 +282,14 @@ impl TcpTransport {
     ) -> Result<(Address, SocketAddr)> {
         let bind_addr = parse_socket_addr(options.bind_addr)?;
         self.router_handle
-            .bind_inlet(options.outlet_route, bind_addr, options.access_control)
+            .bind_inlet(
+                options.outlet_route,
+                bind_addr,
+                options.access_control,
+                options.traffic,
+                options.tls,
+                options.compression,
+            )
             .await
     }
 
@@ -195,6 +321,28 @@ impl TcpTransport {
         self.create_inlet_extended(options).await
     }
 
+    /// Create a Tcp Inlet for a single, already accepted, Tcp stream. No listener is
+    /// started: the stream is forwarded to the Outlet at outlet_route until either side
+    /// disconnects. Returns the address of the portal worker.
+    pub async fn create_inlet_for_stream(
+        &self,
+        stream: TcpStream,
+        outlet_route: impl Into<Route>,
+    ) -> Result<Address> {
+        let peer = stream.peer_addr().map_err(TransportError::from)?;
+        TcpPortalWorker::start_new_inlet(
+            self.router_handle.ctx(),
+            stream,
+            peer,
+            outlet_route.into(),
+            Arc::new(AllowAll),
+            Arc::new(PortalTraffic::default()),
+            None,
+            Vec::new(),
+        )
+        .await
+    }
+
     /// Stop inlet at addr
     ///
     /// ```rust
@@ -218,7 +366,13 @@ impl TcpTransport {
 
     /// Create an Outlet
     pub async fn create_outlet_extended(&self, options: OutletOptions) -> Result<()> {
-        let worker = TcpOutletListenWorker::new(options.peer, options.access_control);
+        let worker = TcpOutletListenWorker::new(
+            options.peer,
+            options.access_control,
+            options.traffic,
+            options.tls,
+            options.compression,
+        );
         self.router_handle
             .ctx()
             .start_worker(options.address, worker)
@@ -1,8 +1,9 @@
-use crate::{TcpRouterHandle, TcpSendWorker};
+use crate::{TcpConnectionMode, TcpListenerStats, TcpRouterHandle, TcpSendWorker};
 use ockam_core::{async_trait, compat::net::SocketAddr, AsyncTryClone};
 use ockam_core::{Address, Processor, Result};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
+use std::sync::Arc;
 use tokio::net::TcpListener;
 use tracing::{debug, trace};
 
@@ -14,6 +15,8 @@ use tracing::{debug, trace};
 pub(crate) struct TcpListenProcessor {
     inner: TcpListener,
     router_handle: TcpRouterHandle,
+    stats: Arc<TcpListenerStats>,
+    max_message_size: Option<usize>,
 }
 
 impl TcpListenProcessor {
@@ -21,18 +24,26 @@ impl TcpListenProcessor {
         ctx: &Context,
         router_handle: TcpRouterHandle,
         addr: SocketAddr,
+        max_message_size: Option<usize>,
     ) -> Result<SocketAddr> {
         debug!("Binding TcpListener to {}", addr);
         let inner = TcpListener::bind(addr)
             .await
             .map_err(TransportError::from)?;
         let saddr = inner.local_addr().map_err(TransportError::from)?;
+        let stats = Arc::new(TcpListenerStats::new(saddr));
+        let address = Address::random_local();
+        router_handle
+            .registry()
+            .add_listener(address.clone(), stats.clone());
         let worker = Self {
             inner,
             router_handle,
+            stats,
+            max_message_size,
         };
 
-        ctx.start_processor(Address::random_local(), worker).await?;
+        ctx.start_processor(address, worker).await?;
 
         Ok(saddr)
     }
@@ -46,17 +57,37 @@ impl Processor for TcpListenProcessor {
         ctx.set_cluster(crate::CLUSTER_NAME).await
     }
 
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.router_handle
+            .registry()
+            .remove_listener(&ctx.address());
+        Ok(())
+    }
+
     async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
         debug!("Waiting for incoming TCP connection...");
 
         // Wait for an incoming connection
         let (stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
         debug!("TCP connection accepted");
+        self.stats.on_accept();
 
         let handle_clone = self.router_handle.async_try_clone().await?;
+        // Connections follow the limit of the listener, if it has one
+        let max_message_size = self
+            .max_message_size
+            .unwrap_or_else(|| self.router_handle.registry().max_message_size());
         // And create a connection worker for it
-        let (worker, pair) =
-            TcpSendWorker::new_pair(ctx, handle_clone, Some(stream), peer, Vec::new()).await?;
+        let (worker, pair) = TcpSendWorker::new_pair(
+            ctx,
+            handle_clone,
+            Some(stream),
+            peer,
+            Vec::new(),
+            TcpConnectionMode::Incoming,
+            max_message_size,
+        )
+        .await?;
 
         // Register the connection with the local TcpRouter
         self.router_handle.register(&pair).await?;
@@ -1,10 +1,11 @@
-use crate::{TcpSendWorkerMsg, TCP};
+use crate::{TcpConnectionStats, TcpSendWorkerMsg, TCP};
 use ockam_core::async_trait;
 use ockam_core::{Address, Decodable, LocalMessage, Processor, Result, TransportMessage};
 use ockam_node::{Context, ExternalLocalInfo};
 use ockam_transport_core::TransportError;
+use std::sync::Arc;
 use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
-use tracing::{error, info, trace};
+use tracing::{error, info, trace, warn};
 
 /// A TCP receiving message processor
 ///
@@ -18,15 +19,22 @@ pub(crate) struct TcpRecvProcessor {
     rx: OwnedReadHalf,
     peer_addr: Address,
     sender_internal_address: Address,
+    stats: Arc<TcpConnectionStats>,
 }
 
 impl TcpRecvProcessor {
     /// Create a new `TcpRecvProcessor`
-    pub fn new(rx: OwnedReadHalf, peer_addr: Address, sender_internal_address: Address) -> Self {
+    pub fn new(
+        rx: OwnedReadHalf,
+        peer_addr: Address,
+        sender_internal_address: Address,
+        stats: Arc<TcpConnectionStats>,
+    ) -> Self {
         Self {
             rx,
             peer_addr,
             sender_internal_address,
+            stats,
         }
     }
 }
@@ -85,6 +93,18 @@ impl Processor for TcpRecvProcessor {
                 return Ok(true);
             }
         }
+        // Count the length header along with the message
+        self.stats.on_receive(2 + buf.len());
+
+        if buf.len() > self.stats.max_message_size() {
+            warn! {
+                peer  = %self.peer_addr,
+                size  = buf.len(),
+                limit = self.stats.max_message_size(),
+                "Dropping message above the size limit"
+            };
+            return Ok(true);
+        }
 
         // Deserialize the message now
         let mut msg = TransportMessage::decode(&buf).map_err(|_| TransportError::RecvBadMessage)?;
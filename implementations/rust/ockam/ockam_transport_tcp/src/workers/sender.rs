@@ -1,10 +1,11 @@
-use crate::{TcpRecvProcessor, TcpRouterHandle};
+use crate::{TcpConnectionMode, TcpConnectionStats, TcpRecvProcessor, TcpRouterHandle};
 use core::time::Duration;
 use ockam_core::{async_trait, compat::net::SocketAddr, route, Any, Decodable, LocalMessage};
 use ockam_core::{Address, Encodable, Message, Result, Routed, TransportMessage, Worker};
 use ockam_node::{Context, DelayedEvent};
 use ockam_transport_core::TransportError;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tokio::io::AsyncWriteExt;
 use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
@@ -58,6 +59,7 @@ pub(crate) struct TcpSendWorker {
     rx_addr: Option<Address>,
     heartbeat: DelayedEvent<TcpSendWorkerMsg>,
     heartbeat_interval: Option<Duration>,
+    stats: Arc<TcpConnectionStats>,
 }
 
 impl TcpSendWorker {
@@ -68,6 +70,8 @@ impl TcpSendWorker {
         peer: SocketAddr,
         internal_addr: Address,
         heartbeat: DelayedEvent<TcpSendWorkerMsg>,
+        mode: TcpConnectionMode,
+        max_message_size: usize,
     ) -> Self {
         let (rx, tx) = match stream {
             Some(s) => {
@@ -86,6 +90,7 @@ impl TcpSendWorker {
             rx_addr: None,
             heartbeat,
             heartbeat_interval: Some(Duration::from_secs(5 * 60)),
+            stats: Arc::new(TcpConnectionStats::new(peer, mode, max_message_size)),
         }
     }
 
@@ -100,6 +105,8 @@ impl TcpSendWorker {
         stream: Option<TcpStream>,
         peer: SocketAddr,
         hostnames: Vec<String>,
+        mode: TcpConnectionMode,
+        max_message_size: usize,
     ) -> Result<(Self, WorkerPair)> {
         let tx_addr = Address::random_local();
         let int_addr = Address::random_local();
@@ -109,6 +116,8 @@ impl TcpSendWorker {
             peer,
             int_addr.clone(),
             DelayedEvent::create(ctx, int_addr.clone(), TcpSendWorkerMsg::Heartbeat).await?,
+            mode,
+            max_message_size,
         );
         Ok((
             sender,
@@ -128,9 +137,20 @@ impl TcpSendWorker {
         stream: Option<TcpStream>,
         peer: SocketAddr,
         hostnames: Vec<String>,
+        mode: TcpConnectionMode,
+        max_message_size: usize,
     ) -> Result<WorkerPair> {
         trace!("Creating new TCP worker pair");
-        let (worker, pair) = Self::new_pair(ctx, router_handle, stream, peer, hostnames).await?;
+        let (worker, pair) = Self::new_pair(
+            ctx,
+            router_handle,
+            stream,
+            peer,
+            hostnames,
+            mode,
+            max_message_size,
+        )
+        .await?;
         ctx.start_worker(vec![pair.tx_addr(), worker.internal_addr().clone()], worker)
             .await?;
         Ok(pair)
@@ -189,10 +209,14 @@ impl Worker for TcpSendWorker {
             rx,
             format!("{}#{}", crate::TCP, self.peer).into(),
             self.internal_addr.clone(),
+            self.stats.clone(),
         );
         ctx.start_processor(rx_addr.clone(), receiver).await?;
 
         self.rx_addr = Some(rx_addr);
+        self.router_handle
+            .registry()
+            .add_connection(ctx.address(), self.stats.clone());
 
         self.schedule_heartbeat().await?;
 
@@ -200,6 +224,9 @@ impl Worker for TcpSendWorker {
     }
 
     async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.router_handle
+            .registry()
+            .remove_connection(&ctx.address());
         if let Some(rx_addr) = self.rx_addr.take() {
             let _ = ctx.stop_processor(rx_addr).await;
         }
@@ -236,6 +263,7 @@ impl Worker for TcpSendWorker {
 
                         return Ok(());
                     }
+                    self.stats.on_send(msg.len());
 
                     debug!("Sent heartbeat to peer {}", self.peer);
                 }
@@ -255,7 +283,14 @@ impl Worker for TcpSendWorker {
             // knows what to do with the incoming message
             msg.onward_route.step()?;
             // Create a message buffer with pre-pended length
-            let msg = prepare_message(msg)?;
+            let msg = match prepare_message(msg, self.stats.max_message_size()) {
+                Ok(msg) => msg,
+                Err(e) => {
+                    // The connection is still usable, keep it alive
+                    self.schedule_heartbeat().await?;
+                    return Err(e);
+                }
+            };
 
             if tx.write_all(msg.as_slice()).await.is_err() {
                 warn!("Failed to send message to peer {}", self.peer);
@@ -263,6 +298,7 @@ impl Worker for TcpSendWorker {
 
                 return Ok(());
             }
+            self.stats.on_send(msg.len());
         }
 
         self.schedule_heartbeat().await?;
@@ -275,10 +311,21 @@ impl Worker for TcpSendWorker {
 /// `TransportMessage`'s payload
 ///
 /// The length-prefix is encoded as a big-endian 16-bit unsigned
-/// integer.
-fn prepare_message(msg: TransportMessage) -> Result<Vec<u8>> {
+/// integer. Messages larger than `max_message_size` are rejected instead
+/// of having their length truncated.
+fn prepare_message(msg: TransportMessage, max_message_size: usize) -> Result<Vec<u8>> {
     let mut msg_buf = msg.encode().map_err(|_| TransportError::SendBadMessage)?;
 
+    if msg_buf.len() > max_message_size {
+        warn! {
+            size  = msg_buf.len(),
+            limit = max_message_size,
+            onward_route = %msg.onward_route,
+            "Message is too large to be sent"
+        };
+        return Err(TransportError::Capacity.into());
+    }
+
     // Create a buffer that includes the message length in big endian
     let mut len = (msg_buf.len() as u16).to_be_bytes().to_vec();
 
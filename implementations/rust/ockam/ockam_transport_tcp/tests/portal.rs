@@ -1,26 +1,33 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 
 use ockam_core::compat::rand::random;
-use ockam_core::{route, Result};
+use ockam_core::{route, AllowAll, Any, Decodable, Result, Routed, Worker};
 use ockam_node::Context;
-use ockam_transport_tcp::TcpTransport;
+use ockam_transport_tcp::{
+    InletOptions, OutletOptions, PortalCompression, PortalMessage, PortalTraffic, TcpTransport,
+};
 
 const LENGTH: usize = 32;
 
 async fn setup(ctx: &Context) -> Result<(String, TcpListener)> {
+    setup_at(ctx, "127.0.0.1:0").await
+}
+
+async fn setup_at(ctx: &Context, bind_addr: &str) -> Result<(String, TcpListener)> {
     let tcp = TcpTransport::create(ctx).await?;
 
     let listener = {
-        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let listener = TcpListener::bind(bind_addr).await.unwrap();
         let bind_address = listener.local_addr().unwrap().to_string();
         tcp.create_outlet("outlet", bind_address.clone()).await?;
         listener
     };
 
-    let (_, inlet_saddr) = tcp.create_inlet("127.0.0.1:0", route!["outlet"]).await?;
+    let (_, inlet_saddr) = tcp.create_inlet(bind_addr, route!["outlet"]).await?;
 
     Ok((inlet_saddr.to_string(), listener))
 }
@@ -101,3 +108,155 @@ async fn portal__reverse_flow__should_succeed(ctx: &mut Context) -> Result<()> {
 
     Ok(())
 }
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__ipv6_loopback__should_succeed(ctx: &mut Context) -> Result<()> {
+    let payload1 = generate_binary();
+    let payload2 = generate_binary();
+
+    let (inlet_addr, listener) = setup_at(ctx, "[::1]:0").await?;
+    assert!(inlet_addr.starts_with("[::1]:"));
+
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        read_assert_binary(&mut stream, payload1).await;
+        write_binary(&mut stream, payload2).await;
+    });
+
+    // Wait till listener is up
+    tokio::time::sleep(Duration::new(0, 250_000)).await;
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    write_binary(&mut stream, payload1).await;
+    read_assert_binary(&mut stream, payload2).await;
+
+    tokio::time::sleep(Duration::new(0, 250_000)).await;
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__compression__should_succeed(ctx: &mut Context) -> Result<()> {
+    let payload = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n".repeat(50);
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let outlet_traffic = Arc::new(PortalTraffic::default());
+    tcp.create_outlet_extended(
+        OutletOptions::new(
+            "outlet".into(),
+            listener.local_addr().unwrap().to_string(),
+            Arc::new(AllowAll),
+        )
+        .with_traffic(outlet_traffic.clone()),
+    )
+    .await?;
+    let inlet_traffic = Arc::new(PortalTraffic::default());
+    let (_, inlet_addr) = tcp
+        .create_inlet_extended(
+            InletOptions::new("127.0.0.1:0".into(), route!["outlet"], Arc::new(AllowAll))
+                .with_traffic(inlet_traffic.clone())
+                .with_compression(vec![PortalCompression::Lz4]),
+        )
+        .await?;
+
+    let expected = payload.clone();
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut received = vec![0u8; expected.len()];
+        stream.read_exact(&mut received).await.unwrap();
+        assert_eq!(received, expected);
+        stream.write_all(&received).await.unwrap();
+    });
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    stream.write_all(&payload).await.unwrap();
+    let mut received = vec![0u8; payload.len()];
+    stream.read_exact(&mut received).await.unwrap();
+    assert_eq!(received, payload);
+    server.await.unwrap();
+
+    // Both directions were compressed
+    assert!(inlet_traffic.portal_bytes_sent() < inlet_traffic.bytes_in());
+    assert!(inlet_traffic.portal_bytes_received() < inlet_traffic.bytes_out());
+    assert!(inlet_traffic.compression_ratio().unwrap() > 1.0);
+    assert!(outlet_traffic.compression_ratio().unwrap() > 1.0);
+
+    tokio::time::sleep(Duration::new(0, 250_000)).await;
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+/// Stands for an outlet of an implementation without compression, which
+/// can't decode the compressed ping and drops it.
+struct LegacyOutlet;
+
+#[ockam_core::worker]
+impl Worker for LegacyOutlet {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        if let Ok(PortalMessage::CompressedPing(_)) = PortalMessage::decode(msg.payload()) {
+            return Ok(());
+        }
+        let mut msg = msg.into_local_message();
+        let onward = &mut msg.transport_mut().onward_route;
+        onward.step()?;
+        onward.modify().prepend("outlet");
+        ctx.forward(msg).await
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 10000)]
+async fn portal__outlet_without_compression__should_fall_back(ctx: &mut Context) -> Result<()> {
+    let payload = generate_binary();
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+    tcp.create_outlet("outlet", bind_address).await?;
+    ctx.start_worker("legacy", LegacyOutlet).await?;
+    let inlet_traffic = Arc::new(PortalTraffic::default());
+    let (_, inlet_addr) = tcp
+        .create_inlet_extended(
+            InletOptions::new("127.0.0.1:0".into(), route!["legacy"], Arc::new(AllowAll))
+                .with_traffic(inlet_traffic.clone())
+                .with_compression(vec![PortalCompression::Lz4]),
+        )
+        .await?;
+
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        read_assert_binary(&mut stream, payload).await;
+        write_binary(&mut stream, payload).await;
+    });
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    write_binary(&mut stream, payload).await;
+    read_assert_binary(&mut stream, payload).await;
+    server.await.unwrap();
+
+    // Nothing was compressed
+    assert_eq!(inlet_traffic.portal_bytes_sent(), inlet_traffic.bytes_in());
+
+    tokio::time::sleep(Duration::new(0, 250_000)).await;
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
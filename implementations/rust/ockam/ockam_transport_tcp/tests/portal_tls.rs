@@ -0,0 +1,163 @@
+#![cfg(feature = "tls")]
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rcgen::{BasicConstraints, Certificate, CertificateParams, IsCa};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls::{self, ClientConfig, RootCertStore, ServerConfig, ServerName};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+use ockam_core::{route, AllowAll, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{InletOptions, InletTls, OutletOptions, OutletTls, TcpTransport};
+
+const PAYLOAD: &[u8] = b"tls through the portal";
+
+struct Pki {
+    ca: Certificate,
+    leaf: Certificate,
+}
+
+impl Pki {
+    fn new() -> Self {
+        let mut params = CertificateParams::new(vec![]);
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = Certificate::from_params(params).unwrap();
+        let leaf =
+            Certificate::from_params(CertificateParams::new(vec!["localhost".into()])).unwrap();
+        Self { ca, leaf }
+    }
+
+    fn write(&self, dir: &Path) {
+        std::fs::write(dir.join("ca.pem"), self.ca.serialize_pem().unwrap()).unwrap();
+        std::fs::write(
+            dir.join("cert.pem"),
+            self.leaf.serialize_pem_with_signer(&self.ca).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dir.join("key.pem"), self.leaf.serialize_private_key_pem()).unwrap();
+    }
+
+    fn server_config(&self) -> ServerConfig {
+        ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![rustls::Certificate(
+                    self.leaf.serialize_der_with_signer(&self.ca).unwrap(),
+                )],
+                rustls::PrivateKey(self.leaf.serialize_private_key_der()),
+            )
+            .unwrap()
+    }
+
+    fn client_config(&self) -> ClientConfig {
+        let mut roots = RootCertStore::empty();
+        roots
+            .add(&rustls::Certificate(self.ca.serialize_der().unwrap()))
+            .unwrap();
+        ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth()
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__tls_both_ends__should_succeed(ctx: &mut Context) -> Result<()> {
+    let pki = Pki::new();
+    let dir = tempfile::tempdir().unwrap();
+    pki.write(dir.path());
+
+    // The target only accepts TLS connections
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let target_addr = listener.local_addr().unwrap().to_string();
+    let acceptor = TlsAcceptor::from(Arc::new(pki.server_config()));
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut stream = acceptor.accept(stream).await.unwrap();
+        let mut payload = [0u8; PAYLOAD.len()];
+        stream.read_exact(&mut payload).await.unwrap();
+        assert_eq!(payload, PAYLOAD);
+        stream.write_all(&payload).await.unwrap();
+        stream.flush().await.unwrap();
+    });
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let outlet_tls = OutletTls::new("localhost", Some(&dir.path().join("ca.pem")), None)?;
+    tcp.create_outlet_extended(
+        OutletOptions::new("outlet".into(), target_addr, Arc::new(AllowAll)).with_tls(outlet_tls),
+    )
+    .await?;
+    let inlet_tls = InletTls::new(
+        dir.path().join("cert.pem"),
+        dir.path().join("key.pem"),
+        None,
+    )?;
+    let (_, inlet_addr) = tcp
+        .create_inlet_extended(
+            InletOptions::new("127.0.0.1:0".into(), route!["outlet"], Arc::new(AllowAll))
+                .with_tls(inlet_tls),
+        )
+        .await?;
+
+    // The client only talks TLS to the inlet
+    let connector = TlsConnector::from(Arc::new(pki.client_config()));
+    let stream = TcpStream::connect(inlet_addr).await.unwrap();
+    let mut stream = connector
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+    stream.write_all(PAYLOAD).await.unwrap();
+    stream.flush().await.unwrap();
+    let mut payload = [0u8; PAYLOAD.len()];
+    stream.read_exact(&mut payload).await.unwrap();
+    assert_eq!(payload, PAYLOAD);
+
+    tokio::time::sleep(Duration::new(0, 250_000)).await;
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__tls_handshake_never_completed__should_time_out(ctx: &mut Context) -> Result<()> {
+    let pki = Pki::new();
+    let dir = tempfile::tempdir().unwrap();
+    pki.write(dir.path());
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let inlet_tls = InletTls::new(
+        dir.path().join("cert.pem"),
+        dir.path().join("key.pem"),
+        None,
+    )?
+    .with_handshake_timeout(Duration::from_millis(200));
+    let (_, inlet_addr) = tcp
+        .create_inlet_extended(
+            InletOptions::new("127.0.0.1:0".into(), route!["outlet"], Arc::new(AllowAll))
+                .with_tls(inlet_tls),
+        )
+        .await?;
+
+    // The client connects but never starts the handshake, so the inlet
+    // closes the connection
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    let mut buf = [0u8; 1];
+    let read = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut buf)).await;
+    assert!(matches!(read, Ok(Ok(0)) | Ok(Err(_))));
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -1,8 +1,9 @@
+use core::time::Duration;
 use ockam_core::compat::rand::{self, Rng};
 use ockam_core::{route, Address, Result, Routed, Worker};
 use ockam_node::Context;
 
-use ockam_transport_tcp::{TcpTransport, TCP};
+use ockam_transport_tcp::{TcpConnectionMode, TcpTransport, TCP};
 
 #[ockam_macros::test]
 async fn send_receive(ctx: &mut Context) -> Result<()> {
@@ -32,6 +33,35 @@ async fn send_receive(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+#[ockam_macros::test]
+async fn send_receive_ipv6(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let listener_address = transport.listen("[::1]:0").await?;
+    assert!(listener_address.is_ipv6());
+    ctx.start_worker("echoer", Echoer).await?;
+
+    // Sender
+    {
+        let msg: String = rand::thread_rng()
+            .sample_iter(&rand::distributions::Alphanumeric)
+            .take(256)
+            .map(char::from)
+            .collect();
+
+        let r = route![(TCP, listener_address.to_string()), "echoer"];
+
+        let reply = ctx.send_and_receive::<_, _, String>(r, msg.clone()).await?;
+
+        assert_eq!(reply, msg, "Should receive the same message");
+    };
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
 pub struct Echoer;
 
 #[ockam_core::worker]
@@ -93,3 +123,79 @@ async fn tcp_lifecycle__reconnect__should_not_error(ctx: &mut Context) -> Result
 
     Ok(())
 }
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_lifecycle__close_connection__should_drop_it(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener_address = transport.listen("127.0.0.1:0").await?.to_string();
+    let tx_address = transport.connect(&listener_address).await?;
+
+    let r = route![(TCP, listener_address.clone()), "echoer"];
+    let reply = ctx
+        .send_and_receive::<_, _, String>(r, "hello".to_string())
+        .await?;
+    assert_eq!(reply, "hello");
+
+    let listeners = transport.listeners();
+    assert_eq!(listeners.len(), 1);
+    assert_eq!(listeners[0].accepted(), 1);
+
+    // Both ends of the connection belong to this node
+    let connections = transport.connections();
+    assert_eq!(connections.len(), 2);
+    let outgoing = connections
+        .iter()
+        .find(|c| c.mode() == TcpConnectionMode::Outgoing)
+        .unwrap();
+    assert_eq!(outgoing.address(), &tx_address);
+    assert!(outgoing.bytes_sent() > 0);
+    assert!(outgoing.bytes_received() > 0);
+
+    transport.close_connection(&tx_address).await?;
+    assert!(transport
+        .connections()
+        .iter()
+        .all(|c| c.address() != &tx_address));
+    assert!(transport.close_connection(&tx_address).await.is_err());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_lifecycle__message_above_limit__should_not_be_sent(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    assert_eq!(transport.set_max_message_size(1024), 1024);
+    let listener_address = transport.listen("127.0.0.1:0").await?.to_string();
+    let r = route![(TCP, listener_address), "echoer"];
+
+    ctx.send(r.clone(), "x".repeat(4096)).await?;
+    let reply = ctx
+        .receive_duration_timeout::<String>(Duration::from_millis(500))
+        .await;
+    assert!(reply.is_err(), "Oversized message should be dropped");
+
+    let reply = ctx
+        .send_and_receive::<_, _, String>(r, "hello".to_string())
+        .await?;
+    assert_eq!(reply, "hello");
+    assert!(transport
+        .connections()
+        .iter()
+        .all(|c| c.max_message_size() == 1024));
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+//! AES-GCM throughput of the software vault.
+//!
+//! Message sizes cover small control messages up to the largest chunks
+//! a TCP portal forwards through a secure channel.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ockam_core::vault::{
+    SecretAttributes, SecretPersistence, SecretType, SecretVault, SymmetricVault,
+    AES128_SECRET_LENGTH_U32, AES256_SECRET_LENGTH_U32,
+};
+use ockam_vault::{aes_hardware_acceleration, Vault};
+use tokio::runtime::Runtime;
+
+const SIZES: &[usize] = &[64, 1024, 16 * 1024, 48 * 1024];
+
+fn aead(c: &mut Criterion) {
+    println!("aes hardware acceleration: {}", aes_hardware_acceleration());
+
+    let rt = Runtime::new().unwrap();
+    let vault = Vault::create();
+    let nonce = [0u8; 12];
+    let aad = [0u8; 8];
+
+    for (name, length) in [
+        ("aes128-gcm", AES128_SECRET_LENGTH_U32),
+        ("aes256-gcm", AES256_SECRET_LENGTH_U32),
+    ] {
+        let attrs = SecretAttributes::new(SecretType::Aes, SecretPersistence::Ephemeral, length);
+        let key_id = rt.block_on(vault.secret_generate(attrs)).unwrap();
+
+        let mut group = c.benchmark_group(name);
+        for &size in SIZES {
+            let plaintext = vec![0u8; size];
+            let ciphertext = rt
+                .block_on(vault.aead_aes_gcm_encrypt(&key_id, &plaintext, &nonce, &aad))
+                .unwrap();
+
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(BenchmarkId::new("encrypt", size), &plaintext, |b, p| {
+                b.iter(|| {
+                    rt.block_on(vault.aead_aes_gcm_encrypt(&key_id, p, &nonce, &aad))
+                        .unwrap()
+                })
+            });
+            group.bench_with_input(BenchmarkId::new("decrypt", size), &ciphertext, |b, c| {
+                b.iter(|| {
+                    rt.block_on(vault.aead_aes_gcm_decrypt(&key_id, c, &nonce, &aad))
+                        .unwrap()
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, aead);
+criterion_main!(benches);
@@ -34,7 +34,7 @@ impl Vault {
             }
             #[cfg(feature = "bls")]
             SecretType::Bls => Err(VaultError::UnknownEcdhKeyType.into()),
-            SecretType::Buffer | SecretType::Aes | SecretType::Ed25519 => {
+            SecretType::Buffer | SecretType::Aes | SecretType::Ed25519 | SecretType::NistP256 => {
                 Err(VaultError::UnknownEcdhKeyType.into())
             }
         }
@@ -0,0 +1,106 @@
+//! Signing keys held in AWS KMS, see [`KeyStore`].
+use crate::{KeyStore, VaultError};
+use aws_sdk_kms::model::{KeySpec, KeyUsageType, MessageType, SigningAlgorithmSpec};
+use aws_sdk_kms::types::Blob;
+use aws_sdk_kms::Client;
+use ockam_core::compat::{
+    boxed::Box,
+    string::{String, ToString},
+};
+use ockam_core::vault::{PublicKey, SecretType, Signature};
+use ockam_core::{async_trait, Result};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::pkcs8::DecodePublicKey;
+use tracing::error;
+
+/// AWS KMS client used by a [`crate::Vault`] for [`SecretType::NistP256`] keys
+#[derive(Clone)]
+pub struct AwsKms {
+    client: Client,
+}
+
+impl AwsKms {
+    /// Prefix of the references to KMS keys in the vault
+    pub const SCHEME: &'static str = "aws-kms";
+
+    /// Create a KMS client from the given one
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// Create a KMS client configured from the environment, e.g. with
+    /// `AWS_REGION` and `AWS_PROFILE` or the instance credentials
+    pub async fn from_env() -> Self {
+        let config = aws_config::load_from_env().await;
+        Self::new(Client::new(&config))
+    }
+}
+
+#[async_trait]
+impl KeyStore for AwsKms {
+    fn scheme(&self) -> &'static str {
+        Self::SCHEME
+    }
+
+    /// Create a new ECC_NIST_P256 signing key and return its KMS key id
+    async fn generate_key(&self) -> Result<String> {
+        let output = self
+            .client
+            .create_key()
+            .key_spec(KeySpec::EccNistP256)
+            .key_usage(KeyUsageType::SignVerify)
+            .description("Ockam identity key")
+            .send()
+            .await
+            .map_err(|e| {
+                error!(%e, "failed to create a KMS key");
+                VaultError::AwsKms
+            })?;
+        output
+            .key_metadata()
+            .and_then(|m| m.key_id())
+            .map(|id| id.to_string())
+            .ok_or_else(|| VaultError::AwsKms.into())
+    }
+
+    async fn public_key(&self, kms_key_id: &str) -> Result<PublicKey> {
+        let output = self
+            .client
+            .get_public_key()
+            .key_id(kms_key_id)
+            .send()
+            .await
+            .map_err(|e| {
+                error!(%e, %kms_key_id, "failed to get a KMS public key");
+                VaultError::AwsKms
+            })?;
+        let der = output.public_key().ok_or(VaultError::AwsKms)?;
+        let public_key = p256::PublicKey::from_public_key_der(der.as_ref())
+            .map_err(|_| VaultError::InvalidPublicKey)?;
+        let point = public_key.to_encoded_point(false);
+        Ok(PublicKey::new(
+            point.as_bytes().to_vec(),
+            SecretType::NistP256,
+        ))
+    }
+
+    async fn sign(&self, kms_key_id: &str, data: &[u8]) -> Result<Signature> {
+        let output = self
+            .client
+            .sign()
+            .key_id(kms_key_id)
+            .message(Blob::new(data))
+            .message_type(MessageType::Raw)
+            .signing_algorithm(SigningAlgorithmSpec::EcdsaSha256)
+            .send()
+            .await
+            .map_err(|e| {
+                error!(%e, %kms_key_id, "failed to sign with a KMS key");
+                VaultError::AwsKms
+            })?;
+        let der = output.signature().ok_or(VaultError::AwsKms)?;
+        let signature =
+            p256::ecdsa::Signature::from_der(der.as_ref()).map_err(|_| VaultError::AwsKms)?;
+        Ok(Signature::new(signature.as_ref().to_vec()))
+    }
+}
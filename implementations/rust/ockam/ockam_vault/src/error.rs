@@ -47,6 +47,10 @@ pub enum VaultError {
     StorageError,
     /// Invalid Storage data
     InvalidStorageData,
+    /// AWS KMS request failed
+    AwsKms,
+    /// PKCS#11 token request failed
+    Pkcs11,
 }
 
 impl ockam_core::compat::error::Error for VaultError {}
@@ -73,6 +77,8 @@ impl core::fmt::Display for VaultError {
             Self::InvalidSecretAttributes => write!(f, "invalid secret attributes"),
             Self::StorageError => write!(f, "invalid storage"),
             Self::InvalidStorageData => write!(f, "invalid storage data"),
+            Self::AwsKms => write!(f, "AWS KMS request failed"),
+            Self::Pkcs11 => write!(f, "PKCS#11 token request failed"),
         }
     }
 }
@@ -0,0 +1,147 @@
+//! Signing keys held outside of the vault.
+//!
+//! The vault only stores a reference to such keys, as the secret of a
+//! [`SecretType::NistP256`] entry. The reference is prefixed with the
+//! [`KeyStore::scheme`] of the store holding the key, e.g. `aws-kms:<key id>`,
+//! so entries loaded from storage can be resolved to their store.
+//! Key generation, public key retrieval and signing are delegated to the
+//! store, the private key never leaves it.
+use crate::vault::Vault;
+use crate::VaultError;
+use ockam_core::compat::{boxed::Box, string::String, sync::Arc};
+use ockam_core::vault::{
+    AsymmetricVault, KeyId, PublicKey, SecretAttributes, SecretKey, SecretPersistence, Signature,
+    VaultEntry,
+};
+use ockam_core::{async_trait, Result};
+
+/// A store of NIST P-256 signing keys which can't be exported
+#[async_trait]
+pub trait KeyStore: Send + Sync + 'static {
+    /// Prefix of the references to the keys of this store
+    fn scheme(&self) -> &'static str;
+
+    /// Create a new signing key and return its reference in the store
+    async fn generate_key(&self) -> Result<String>;
+
+    /// Return the uncompressed SEC1 public key of the given key
+    async fn public_key(&self, key_ref: &str) -> Result<PublicKey>;
+
+    /// Sign the data with the given key, returning a fixed size `r || s` signature
+    async fn sign(&self, key_ref: &str, data: &[u8]) -> Result<Signature>;
+}
+
+impl Vault {
+    /// Use the given store for [`SecretType::NistP256`] keys.
+    ///
+    /// The first store set is the one where new keys are generated. If
+    /// none is set, a store configured from the environment is created the
+    /// first time such a key is used.
+    pub async fn with_key_store(self, store: impl KeyStore) -> Self {
+        self.data.key_stores.write().await.push(Arc::new(store));
+        self
+    }
+
+    /// Return the store with the given scheme, creating it from the
+    /// environment if it wasn't set
+    async fn key_store(&self, scheme: &str) -> Result<Arc<dyn KeyStore>> {
+        if let Some(store) = find(&self.data.key_stores.read().await, scheme) {
+            return Ok(store);
+        }
+        let mut stores = self.data.key_stores.write().await;
+        if let Some(store) = find(&stores, scheme) {
+            return Ok(store);
+        }
+        let store: Arc<dyn KeyStore> = match scheme {
+            #[cfg(feature = "aws-kms")]
+            crate::AwsKms::SCHEME => Arc::new(crate::AwsKms::from_env().await),
+            #[cfg(feature = "pkcs11")]
+            crate::Pkcs11::SCHEME => Arc::new(crate::Pkcs11::from_env().await?),
+            _ => return Err(VaultError::InvalidKeyType.into()),
+        };
+        stores.push(store.clone());
+        Ok(store)
+    }
+
+    /// Return the store where new keys are generated
+    async fn default_key_store(&self) -> Result<Arc<dyn KeyStore>> {
+        if let Some(store) = self.data.key_stores.read().await.first() {
+            return Ok(store.clone());
+        }
+        #[cfg(feature = "aws-kms")]
+        let scheme = crate::AwsKms::SCHEME;
+        #[cfg(not(feature = "aws-kms"))]
+        let scheme = crate::Pkcs11::SCHEME;
+        self.key_store(scheme).await
+    }
+
+    /// Create a key in the store with the given scheme and store its
+    /// reference in a new vault entry
+    pub async fn key_store_secret_generate(
+        &self,
+        scheme: &str,
+        attributes: SecretAttributes,
+    ) -> Result<KeyId> {
+        let store = self.key_store(scheme).await?;
+        self.secret_generate_in(store, attributes).await
+    }
+
+    pub(crate) async fn default_key_store_secret_generate(
+        &self,
+        attributes: SecretAttributes,
+    ) -> Result<KeyId> {
+        let store = self.default_key_store().await?;
+        self.secret_generate_in(store, attributes).await
+    }
+
+    async fn secret_generate_in(
+        &self,
+        store: Arc<dyn KeyStore>,
+        attributes: SecretAttributes,
+    ) -> Result<KeyId> {
+        if attributes.persistence() != SecretPersistence::Persistent {
+            return Err(VaultError::InvalidSecretAttributes.into());
+        }
+        let key_ref = store.generate_key().await?;
+        let public_key = store.public_key(&key_ref).await?;
+        let key_id = self.compute_key_id_for_public_key(&public_key).await?;
+
+        let reference = [store.scheme(), ":", &key_ref].concat();
+        let entry = VaultEntry::new(attributes, SecretKey::new(reference.into_bytes()));
+        self.store_secret(&key_id, &entry).await?;
+
+        self.data
+            .entries
+            .write()
+            .await
+            .insert(key_id.clone(), entry);
+
+        Ok(key_id)
+    }
+
+    pub(crate) async fn key_store_public_key(&self, entry: &VaultEntry) -> Result<PublicKey> {
+        let (scheme, key_ref) = key_reference(entry)?;
+        self.key_store(scheme).await?.public_key(key_ref).await
+    }
+
+    pub(crate) async fn key_store_sign(
+        &self,
+        entry: &VaultEntry,
+        data: &[u8],
+    ) -> Result<Signature> {
+        let (scheme, key_ref) = key_reference(entry)?;
+        self.key_store(scheme).await?.sign(key_ref, data).await
+    }
+}
+
+fn find(stores: &[Arc<dyn KeyStore>], scheme: &str) -> Option<Arc<dyn KeyStore>> {
+    stores.iter().find(|s| s.scheme() == scheme).cloned()
+}
+
+/// Split the secret of an entry into the scheme of its store and the key reference
+fn key_reference(entry: &VaultEntry) -> Result<(&str, &str)> {
+    core::str::from_utf8(entry.key().as_ref())
+        .ok()
+        .and_then(|r| r.split_once(':'))
+        .ok_or_else(|| VaultError::InvalidStorageData.into())
+}
@@ -22,8 +22,14 @@ extern crate alloc;
 pub use ockam_core;
 
 mod asymmetric_impl;
+#[cfg(feature = "aws-kms")]
+mod aws_kms;
 mod error;
 mod hasher_impl;
+#[cfg(feature = "key-store")]
+mod key_store;
+#[cfg(feature = "pkcs11")]
+mod pkcs11;
 mod secret_impl;
 mod signer_impl;
 
@@ -42,8 +48,14 @@ pub use ockam_core::vault::{
 };
 
 pub use asymmetric_impl::*;
+#[cfg(feature = "aws-kms")]
+pub use aws_kms::AwsKms;
 pub use error::*;
 pub use hasher_impl::*;
+#[cfg(feature = "key-store")]
+pub use key_store::KeyStore;
+#[cfg(feature = "pkcs11")]
+pub use pkcs11::Pkcs11;
 pub use secret_impl::*;
 pub use signer_impl::*;
 pub use symmetric_impl::*;
@@ -0,0 +1,211 @@
+//! Signing keys held in a PKCS#11 token, see [`KeyStore`].
+use crate::{KeyStore, VaultError};
+use cryptoki::context::{self, CInitializeArgs};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::types::AuthPin;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::{boxed::Box, string::String, sync::Arc, vec::Vec};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::vault::{PublicKey, SecretType, Signature, NIST_P256_PUBLIC_LENGTH_USIZE};
+use ockam_core::{async_trait, Error, Result};
+use ockam_node::tokio::task::{self, JoinError};
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use tracing::error;
+
+/// DER encoded OID of the NIST P-256 curve, `1.2.840.10045.3.1.7`
+const P256_PARAMS: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+
+/// Session on a PKCS#11 token used by a [`crate::Vault`] for
+/// [`SecretType::NistP256`] keys, e.g. on a YubiHSM or a TPM.
+///
+/// Keys are referenced by their hex encoded `CKA_ID`. Token calls are
+/// blocking, they run on the blocking thread pool of the runtime.
+pub struct Pkcs11 {
+    session: Arc<Mutex<Session>>,
+}
+
+impl Pkcs11 {
+    /// Prefix of the references to token keys in the vault
+    pub const SCHEME: &'static str = "pkcs11";
+
+    /// Load the PKCS#11 module at the given path and log into the token of
+    /// the given slot, or the first slot with a token if none is given
+    ///
+    /// This blocks on the token, see [`Pkcs11::from_env`] from async code.
+    pub fn new(module: &str, slot: Option<u64>, pin: &str) -> Result<Self> {
+        let ctx = context::Pkcs11::new(module).map_err(log_err("load module"))?;
+        ctx.initialize(CInitializeArgs::OsThreads)
+            .map_err(log_err("initialize module"))?;
+        let slot = ctx
+            .get_slots_with_token()
+            .map_err(log_err("list slots"))?
+            .into_iter()
+            .find(|s| slot.map(|id| s.id() == id).unwrap_or(true))
+            .ok_or(VaultError::Pkcs11)?;
+        let session = ctx.open_rw_session(slot).map_err(log_err("open session"))?;
+        session
+            .login(UserType::User, Some(&AuthPin::new(pin.into())))
+            .map_err(log_err("log in"))?;
+        Ok(Self {
+            session: Arc::new(Mutex::new(session)),
+        })
+    }
+
+    /// Log into a token configured with the `OCKAM_PKCS11_MODULE` and
+    /// `OCKAM_PKCS11_SLOT` (optional) variables
+    ///
+    /// The PIN is read from the file at `OCKAM_PKCS11_PIN_FILE`, e.g. a
+    /// mounted secret. `OCKAM_PKCS11_PIN` is still accepted when no file is
+    /// given, but the environment of a process can be read by other
+    /// processes of the same user and is inherited by its children.
+    pub async fn from_env() -> Result<Self> {
+        let var = |name| std::env::var(name).map_err(|_| VaultError::Pkcs11);
+        let module = var("OCKAM_PKCS11_MODULE")?;
+        let slot = match std::env::var("OCKAM_PKCS11_SLOT") {
+            Ok(slot) => Some(slot.parse().map_err(|_| VaultError::Pkcs11)?),
+            Err(_) => None,
+        };
+        let pin_file = std::env::var("OCKAM_PKCS11_PIN_FILE").ok();
+        let t = move || {
+            let pin = match pin_file {
+                Some(path) => read_pin(&path)?,
+                None => var("OCKAM_PKCS11_PIN")?,
+            };
+            Self::new(&module, slot, &pin)
+        };
+        task::spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
+    /// Run a call on the token session on the blocking thread pool
+    async fn call<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Session) -> Result<T> + Send + 'static,
+    {
+        let session = self.session.clone();
+        let t = move || {
+            let session = session.lock().map_err(|_| VaultError::Pkcs11)?;
+            f(&session)
+        };
+        task::spawn_blocking(t).await.map_err(map_join_err)?
+    }
+
+    fn find_key(session: &Session, class: ObjectClass, key_ref: &str) -> Result<ObjectHandle> {
+        let id = hex::decode(key_ref).map_err(|_| VaultError::InvalidStorageData)?;
+        session
+            .find_objects(&[Attribute::Class(class), Attribute::Id(id)])
+            .map_err(log_err("find key"))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| VaultError::EntryNotFound.into())
+    }
+}
+
+#[async_trait]
+impl KeyStore for Pkcs11 {
+    fn scheme(&self) -> &'static str {
+        Self::SCHEME
+    }
+
+    /// Create a new P-256 key pair on the token and return its `CKA_ID`
+    async fn generate_key(&self) -> Result<String> {
+        let mut id = [0u8; 16];
+        thread_rng().fill_bytes(&mut id);
+
+        let public_template = [
+            Attribute::Token(true),
+            Attribute::KeyType(KeyType::EC),
+            Attribute::EcParams(P256_PARAMS.to_vec()),
+            Attribute::Verify(true),
+            Attribute::Id(id.to_vec()),
+        ];
+        let private_template = [
+            Attribute::Token(true),
+            Attribute::Private(true),
+            Attribute::Sensitive(true),
+            Attribute::Extractable(false),
+            Attribute::Sign(true),
+            Attribute::Id(id.to_vec()),
+        ];
+        self.call(move |session| {
+            session
+                .generate_key_pair(
+                    &Mechanism::EccKeyPairGen,
+                    &public_template,
+                    &private_template,
+                )
+                .map_err(log_err("generate key pair"))?;
+            Ok(())
+        })
+        .await?;
+
+        Ok(hex::encode(id))
+    }
+
+    async fn public_key(&self, key_ref: &str) -> Result<PublicKey> {
+        let key_ref = key_ref.to_string();
+        let attributes = self
+            .call(move |session| {
+                let handle = Self::find_key(session, ObjectClass::PUBLIC_KEY, &key_ref)?;
+                let attributes = session
+                    .get_attributes(handle, &[AttributeType::EcPoint])
+                    .map_err(log_err("read public key"))?;
+                Ok(attributes)
+            })
+            .await?;
+        let point = match attributes.as_slice() {
+            [Attribute::EcPoint(point)] => point,
+            _ => return Err(VaultError::InvalidPublicKey.into()),
+        };
+        // CKA_EC_POINT is a DER encoded OCTET STRING wrapping the SEC1 point
+        match point.as_slice() {
+            [0x04, len, sec1 @ ..]
+                if *len as usize == NIST_P256_PUBLIC_LENGTH_USIZE
+                    && sec1.len() == *len as usize =>
+            {
+                Ok(PublicKey::new(sec1.to_vec(), SecretType::NistP256))
+            }
+            _ => Err(VaultError::InvalidPublicKey.into()),
+        }
+    }
+
+    /// Sign the SHA-256 digest of the data, CKM_ECDSA returns a fixed size
+    /// `r || s` signature
+    async fn sign(&self, key_ref: &str, data: &[u8]) -> Result<Signature> {
+        let digest = Sha256::digest(data);
+        let key_ref = key_ref.to_string();
+        let signature: Vec<u8> = self
+            .call(move |session| {
+                let handle = Self::find_key(session, ObjectClass::PRIVATE_KEY, &key_ref)?;
+                let signature = session
+                    .sign(&Mechanism::Ecdsa, handle, &digest)
+                    .map_err(log_err("sign"))?;
+                Ok(signature)
+            })
+            .await?;
+        Ok(Signature::new(signature))
+    }
+}
+
+fn log_err(op: &'static str) -> impl Fn(cryptoki::error::Error) -> VaultError {
+    move |e| {
+        error!(%e, "PKCS#11 token failed to {}", op);
+        VaultError::Pkcs11
+    }
+}
+
+/// Read a PIN from a file, without its trailing line break
+fn read_pin(path: &str) -> Result<String> {
+    let pin = std::fs::read_to_string(path).map_err(|e| {
+        error!(%e, %path, "failed to read the PKCS#11 PIN file");
+        VaultError::Pkcs11
+    })?;
+    Ok(pin.trim_end_matches(&['\r', '\n'][..]).to_string())
+}
+
+fn map_join_err(err: JoinError) -> Error {
+    Error::new(Origin::Vault, Kind::Io, err)
+}
@@ -55,6 +55,7 @@ impl Vault {
                 );
                 self.compute_key_id_for_public_key(&public_key).await?
             }
+            SecretType::NistP256 => return Err(VaultError::InvalidKeyType.into()),
             SecretType::Buffer | SecretType::Aes => {
                 // NOTE: Buffer and Aes secrets in the system are ephemeral and it should be fine,
                 // that every time we import the same secret - it gets different KeyId value.
@@ -82,6 +83,8 @@ impl Vault {
                     return Err(VaultError::InvalidBlsSecret.into());
                 }
             }
+            // NIST P-256 keys can't be imported, they only live in a key store
+            SecretType::NistP256 => return Err(VaultError::InvalidKeyType.into()),
             SecretType::Buffer | SecretType::Aes | SecretType::X25519 | SecretType::Ed25519 => {
                 // Avoid unused variable warning
                 let _ = secret;
@@ -90,7 +93,11 @@ impl Vault {
         Ok(())
     }
 
-    async fn store_secret(&self, key_id: &KeyId, vault_entry: &VaultEntry) -> Result<()> {
+    pub(crate) async fn store_secret(
+        &self,
+        key_id: &KeyId,
+        vault_entry: &VaultEntry,
+    ) -> Result<()> {
         if vault_entry.key_attributes().persistence() == SecretPersistence::Persistent {
             if let Some(storage) = &self.storage {
                 storage.store(key_id, vault_entry).await?;
@@ -154,6 +161,12 @@ impl SecretVault for Vault {
 
                 SecretKey::new(bls_secret_key.to_bytes().to_vec())
             }
+            #[cfg(feature = "key-store")]
+            SecretType::NistP256 => {
+                return self.default_key_store_secret_generate(attributes).await
+            }
+            #[cfg(not(feature = "key-store"))]
+            SecretType::NistP256 => return Err(VaultError::InvalidKeyType.into()),
         };
         let key_id = self.compute_key_id(key.as_ref(), &attributes).await?;
 
@@ -191,15 +204,13 @@ impl SecretVault for Vault {
     async fn secret_export(&self, key_id: &KeyId) -> Result<SecretKey> {
         self.preload_from_storage(key_id).await;
 
-        Ok(self
-            .data
-            .entries
-            .read()
-            .await
-            .get(key_id)
-            .ok_or(VaultError::EntryNotFound)?
-            .key()
-            .clone())
+        let entries = self.data.entries.read().await;
+        let entry = entries.get(key_id).ok_or(VaultError::EntryNotFound)?;
+        // The private key of a NIST P-256 entry never leaves its key store
+        if entry.key_attributes().stype() == SecretType::NistP256 {
+            return Err(VaultError::InvalidKeyType.into());
+        }
+        Ok(entry.key().clone())
     }
 
     async fn secret_attributes_get(&self, key_id: &KeyId) -> Result<SecretAttributes> {
@@ -255,6 +266,14 @@ impl SecretVault for Vault {
                     SecretType::Bls,
                 ))
             }
+            #[cfg(feature = "key-store")]
+            SecretType::NistP256 => {
+                let entry = entry.clone();
+                drop(entries);
+                self.key_store_public_key(&entry).await
+            }
+            #[cfg(not(feature = "key-store"))]
+            SecretType::NistP256 => Err(VaultError::InvalidKeyType.into()),
             SecretType::Buffer | SecretType::Aes => Err(VaultError::InvalidKeyType.into()),
         }
     }
@@ -281,6 +300,8 @@ impl SecretVault for Vault {
             Some(_) => {}
         }
 
+        self.data.ciphers.write().await.remove(&key_id);
+
         res
     }
 }
@@ -64,6 +64,14 @@ impl Signer for Vault {
                     Err(VaultError::InvalidKeyType.into())
                 }
             }
+            #[cfg(feature = "key-store")]
+            SecretType::NistP256 => {
+                let entry = entry.clone();
+                drop(entries);
+                self.key_store_sign(&entry, data).await
+            }
+            #[cfg(not(feature = "key-store"))]
+            SecretType::NistP256 => Err(VaultError::InvalidKeyType.into()),
             SecretType::Buffer | SecretType::Aes => Err(VaultError::InvalidKeyType.into()),
         }
     }
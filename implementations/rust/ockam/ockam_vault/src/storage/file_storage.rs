@@ -162,6 +162,30 @@ impl FileStorage {
         Ok(s)
     }
 
+    /// Ids of the persistent keys in the Storage
+    pub async fn key_ids(&self) -> Vec<KeyId> {
+        self.data.read().await.keys().cloned().collect()
+    }
+
+    /// Serialize all keys, in the same format as the storage file
+    pub async fn export(&self) -> Result<Vec<u8>> {
+        self.serialize().await
+    }
+
+    /// Add keys previously serialized with [`FileStorage::export()`]
+    ///
+    /// Existing keys with the same id are overwritten. Returns the ids of
+    /// the imported keys.
+    pub async fn import(&self, vault_bytes: &[u8]) -> Result<Vec<KeyId>> {
+        let imported = Self::deserialize(vault_bytes).await?.into_inner();
+        let key_ids = imported.keys().cloned().collect();
+        self.data.write().await.extend(imported);
+
+        self.flush_to_file().await?;
+
+        Ok(key_ids)
+    }
+
     /// Clear the Storage
     pub async fn clear(&self) {
         if self.path.exists() {
@@ -252,4 +276,38 @@ mod tests {
         let attributes31 = vault.secret_attributes_get(&key_id3).await;
         assert!(attributes31.is_err());
     }
+
+    #[tokio::test]
+    async fn export_import() {
+        let mut rng = thread_rng();
+        let mut rand_id = [0u8; 32];
+        let dir = std::env::temp_dir();
+
+        rng.fill_bytes(&mut rand_id);
+        let storage1 = FileStorage::create(dir.join(hex::encode(&rand_id)))
+            .await
+            .unwrap();
+        let storage1 = Arc::new(storage1);
+        let vault1 = Vault::new(Some(storage1.clone()));
+
+        let attributes =
+            SecretAttributes::new(SecretType::Ed25519, SecretPersistence::Persistent, 0);
+        let key_id = vault1.secret_generate(attributes).await.unwrap();
+
+        rng.fill_bytes(&mut rand_id);
+        let storage2 = FileStorage::create(dir.join(hex::encode(&rand_id)))
+            .await
+            .unwrap();
+        let storage2 = Arc::new(storage2);
+        let vault2 = Vault::new(Some(storage2.clone()));
+
+        let exported = storage1.export().await.unwrap();
+        let imported = storage2.import(&exported).await.unwrap();
+        assert_eq!(imported, vec![key_id.clone()]);
+        assert_eq!(storage2.key_ids().await, vec![key_id.clone()]);
+
+        let public1 = vault1.secret_public_key_get(&key_id).await.unwrap();
+        let public2 = vault2.secret_public_key_get(&key_id).await.unwrap();
+        assert_eq!(public1, public2);
+    }
 }
@@ -1,12 +1,120 @@
 use crate::{Vault, VaultError};
 use aes_gcm::aead::{generic_array::GenericArray, Aead, NewAead, Payload};
 use aes_gcm::{Aes128Gcm, Aes256Gcm};
+use ockam_core::compat::sync::Arc;
 use ockam_core::vault::{
     Buffer, KeyId, SecretType, SymmetricVault, AES128_SECRET_LENGTH_U32,
     AES128_SECRET_LENGTH_USIZE, AES256_SECRET_LENGTH_U32, AES256_SECRET_LENGTH_USIZE,
 };
 use ockam_core::{async_trait, compat::boxed::Box, Result};
 
+/// An initialised AES-GCM cipher.
+///
+/// Creating a cipher runs the AES key schedule, which dominates the cost
+/// of encrypting the small messages exchanged over secure channels. We
+/// keep one instance per key so that it is only paid once.
+pub(crate) enum AesGen {
+    Aes128(Box<Aes128Gcm>),
+    Aes256(Box<Aes256Gcm>),
+}
+
+impl AesGen {
+    fn new(stype: SecretType, length: u32, key: &[u8]) -> Result<Self> {
+        if stype != SecretType::Aes {
+            return Err(VaultError::AeadAesGcmEncrypt.into());
+        }
+        match length {
+            AES128_SECRET_LENGTH_U32 if key.len() == AES128_SECRET_LENGTH_USIZE => Ok(
+                AesGen::Aes128(Box::new(Aes128Gcm::new(GenericArray::from_slice(key)))),
+            ),
+            AES256_SECRET_LENGTH_U32 if key.len() == AES256_SECRET_LENGTH_USIZE => Ok(
+                AesGen::Aes256(Box::new(Aes256Gcm::new(GenericArray::from_slice(key)))),
+            ),
+            _ => Err(VaultError::AeadAesGcmEncrypt.into()),
+        }
+    }
+
+    fn encrypt(&self, nonce: &[u8], payload: Payload) -> Result<Buffer<u8>> {
+        let nonce = GenericArray::from_slice(nonce);
+        match self {
+            AesGen::Aes128(c) => c.encrypt(nonce, payload),
+            AesGen::Aes256(c) => c.encrypt(nonce, payload),
+        }
+        .map_err(|_| VaultError::AeadAesGcmEncrypt.into())
+    }
+
+    fn decrypt(&self, nonce: &[u8], payload: Payload) -> Result<Buffer<u8>> {
+        let nonce = GenericArray::from_slice(nonce);
+        match self {
+            AesGen::Aes128(c) => c.decrypt(nonce, payload),
+            AesGen::Aes256(c) => c.decrypt(nonce, payload),
+        }
+        .map_err(|_| VaultError::AeadAesGcmEncrypt.into())
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "std"))] {
+        cpufeatures::new!(aes_intrinsics, "aes", "pclmulqdq");
+
+        /// Returns `true` if AES-GCM runs on hardware instructions.
+        ///
+        /// The `aes` crate detects AES-NI and CLMUL at runtime and picks the
+        /// accelerated backend when they are available, falling back to the
+        /// constant-time software implementation otherwise.
+        pub fn aes_hardware_acceleration() -> bool {
+            aes_intrinsics::get()
+        }
+    } else if #[cfg(all(target_arch = "aarch64", feature = "aes-armv8"))] {
+        cpufeatures::new!(aes_intrinsics, "aes");
+
+        /// Returns `true` if AES-GCM runs on hardware instructions.
+        ///
+        /// On aarch64 the ARMv8 cryptography extensions are only used when
+        /// the `aes-armv8` feature is enabled.
+        pub fn aes_hardware_acceleration() -> bool {
+            aes_intrinsics::get()
+        }
+    } else {
+        /// Returns `true` if AES-GCM runs on hardware instructions.
+        ///
+        /// Always `false` on this target, the software backend is used.
+        pub fn aes_hardware_acceleration() -> bool {
+            false
+        }
+    }
+}
+
+impl Vault {
+    /// Get the cached cipher for the given key, creating it if necessary.
+    async fn aes_gen(&self, key_id: &KeyId) -> Result<Arc<AesGen>> {
+        if let Some(c) = self.data.ciphers.read().await.get(key_id) {
+            return Ok(c.clone());
+        }
+
+        self.preload_from_storage(key_id).await;
+
+        // The entries lock is held until the cipher is cached so that a
+        // concurrent `secret_destroy` can not leave a stale cipher behind.
+        let entries = self.data.entries.read().await;
+        let entry = entries.get(key_id).ok_or(VaultError::EntryNotFound)?;
+        let attrs = entry.key_attributes();
+        let cipher = Arc::new(AesGen::new(
+            attrs.stype(),
+            attrs.length(),
+            entry.key().as_ref(),
+        )?);
+
+        self.data
+            .ciphers
+            .write()
+            .await
+            .insert(key_id.clone(), cipher.clone());
+
+        Ok(cipher)
+    }
+}
+
 #[async_trait]
 impl SymmetricVault for Vault {
     async fn aead_aes_gcm_encrypt(
@@ -16,45 +124,11 @@ impl SymmetricVault for Vault {
         nonce: &[u8],
         aad: &[u8],
     ) -> Result<Buffer<u8>> {
-        self.preload_from_storage(key_id).await;
-
-        let entries = self.data.entries.read().await;
-        let entry = entries.get(key_id).ok_or(VaultError::EntryNotFound)?;
-
-        if entry.key_attributes().stype() != SecretType::Aes {
-            return Err(VaultError::AeadAesGcmEncrypt.into());
-        }
-
-        let nonce = GenericArray::from_slice(nonce);
         let payload = Payload {
             aad,
             msg: plaintext,
         };
-
-        let key = entry.key().as_ref();
-        match entry.key_attributes().length() {
-            AES128_SECRET_LENGTH_U32 => {
-                if key.len() != AES128_SECRET_LENGTH_USIZE {
-                    return Err(VaultError::AeadAesGcmEncrypt.into());
-                }
-
-                let key = GenericArray::from_slice(key);
-                Aes128Gcm::new(key)
-                    .encrypt(nonce, payload)
-                    .map_err(|_| VaultError::AeadAesGcmEncrypt.into())
-            }
-            AES256_SECRET_LENGTH_U32 => {
-                if key.len() != AES256_SECRET_LENGTH_USIZE {
-                    return Err(VaultError::AeadAesGcmEncrypt.into());
-                }
-
-                let key = GenericArray::from_slice(key);
-                Aes256Gcm::new(key)
-                    .encrypt(nonce, payload)
-                    .map_err(|_| VaultError::AeadAesGcmEncrypt.into())
-            }
-            _ => Err(VaultError::AeadAesGcmEncrypt.into()),
-        }
+        self.aes_gen(key_id).await?.encrypt(nonce, payload)
     }
 
     async fn aead_aes_gcm_decrypt(
@@ -64,53 +138,51 @@ impl SymmetricVault for Vault {
         nonce: &[u8],
         aad: &[u8],
     ) -> Result<Buffer<u8>> {
-        self.preload_from_storage(key_id).await;
-
-        let entries = self.data.entries.read().await;
-        let entry = entries.get(key_id).ok_or(VaultError::EntryNotFound)?;
-
-        if entry.key_attributes().stype() != SecretType::Aes {
-            return Err(VaultError::AeadAesGcmEncrypt.into());
-        }
-
-        let nonce = GenericArray::from_slice(nonce);
         let payload = Payload {
             aad,
             msg: cipher_text,
         };
-
-        let key = entry.key().as_ref();
-        match entry.key_attributes().length() {
-            AES128_SECRET_LENGTH_U32 => {
-                if key.len() != AES128_SECRET_LENGTH_USIZE {
-                    return Err(VaultError::AeadAesGcmEncrypt.into());
-                }
-                let key = GenericArray::from_slice(key);
-                Aes128Gcm::new(key)
-                    .decrypt(nonce, payload)
-                    .map_err(|_| VaultError::AeadAesGcmEncrypt.into())
-            }
-            AES256_SECRET_LENGTH_U32 => {
-                if key.len() != AES256_SECRET_LENGTH_USIZE {
-                    return Err(VaultError::AeadAesGcmEncrypt.into());
-                }
-                let key = GenericArray::from_slice(key);
-                Aes256Gcm::new(key)
-                    .decrypt(nonce, payload)
-                    .map_err(|_| VaultError::AeadAesGcmEncrypt.into())
-            }
-            _ => Err(VaultError::AeadAesGcmEncrypt.into()),
-        }
+        self.aes_gen(key_id).await?.decrypt(nonce, payload)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::Vault;
+    use ockam_core::vault::{
+        SecretAttributes, SecretPersistence, SecretType, SecretVault, SymmetricVault,
+        AES128_SECRET_LENGTH_U32,
+    };
+
     fn new_vault() -> Vault {
         Vault::default()
     }
 
     #[ockam_macros::vault_test]
     fn encryption() {}
+
+    #[tokio::test]
+    async fn destroyed_secret_is_not_cached() {
+        let vault = new_vault();
+        let attrs = SecretAttributes::new(
+            SecretType::Aes,
+            SecretPersistence::Ephemeral,
+            AES128_SECRET_LENGTH_U32,
+        );
+        let key_id = vault.secret_generate(attrs).await.unwrap();
+        let nonce = [0u8; 12];
+
+        let c = vault
+            .aead_aes_gcm_encrypt(&key_id, b"hello", &nonce, b"")
+            .await
+            .unwrap();
+        assert!(vault.data.ciphers.read().await.contains_key(&key_id));
+
+        vault.secret_destroy(key_id.clone()).await.unwrap();
+        assert!(!vault.data.ciphers.read().await.contains_key(&key_id));
+        assert!(vault
+            .aead_aes_gcm_decrypt(&key_id, &c, &nonce, b"")
+            .await
+            .is_err());
+    }
 }
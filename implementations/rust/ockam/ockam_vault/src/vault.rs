@@ -1,3 +1,4 @@
+use crate::symmetric_impl::AesGen;
 use ockam_core::compat::{collections::BTreeMap, sync::Arc};
 use ockam_core::vault::storage::Storage;
 use ockam_core::vault::{KeyId, VaultEntry};
@@ -40,6 +41,9 @@ pub struct Vault {
 #[derive(Default, Clone)]
 pub(crate) struct VaultData {
     pub(crate) entries: Arc<RwLock<BTreeMap<KeyId, VaultEntry>>>,
+    pub(crate) ciphers: Arc<RwLock<BTreeMap<KeyId, Arc<AesGen>>>>,
+    #[cfg(feature = "key-store")]
+    pub(crate) key_stores: Arc<RwLock<Vec<Arc<dyn crate::KeyStore>>>>,
 }
 
 impl Vault {
@@ -56,6 +60,20 @@ impl Vault {
         Self::new(None)
     }
 
+    /// Drop the cached copies of the given keys
+    ///
+    /// The keys are loaded again from the Storage on their next use. Needed
+    /// when keys are replaced in the Storage behind the Vault's back, e.g.
+    /// when a backup is imported.
+    pub async fn evict(&self, key_ids: &[KeyId]) {
+        let mut entries = self.data.entries.write().await;
+        let mut ciphers = self.data.ciphers.write().await;
+        for key_id in key_ids {
+            entries.remove(key_id);
+            ciphers.remove(key_id);
+        }
+    }
+
     pub(crate) async fn preload_from_storage(&self, key_id: &KeyId) {
         // Do nothing if there is no Storage
         let storage = match &self.storage {
@@ -2,6 +2,7 @@ use crate::vault::Vault;
 use crate::VaultError;
 use ockam_core::vault::{
     PublicKey, SecretType, Signature, Verifier, CURVE25519_PUBLIC_LENGTH_USIZE,
+    NIST_P256_PUBLIC_LENGTH_USIZE,
 };
 use ockam_core::{async_trait, compat::boxed::Box, Result};
 
@@ -66,7 +67,55 @@ impl Verifier for Vault {
                 let res = signature_bbs.verify(&bls_public_key, &generators, messages.as_ref());
                 Ok(res.unwrap_u8() == 1)
             }
+            SecretType::NistP256 => {
+                if public_key.data().len() != NIST_P256_PUBLIC_LENGTH_USIZE
+                    || signature.as_ref().len() != 64
+                {
+                    return Err(VaultError::InvalidPublicKey.into());
+                }
+                use p256::ecdsa::signature::Verifier;
+
+                let public_key = p256::ecdsa::VerifyingKey::from_sec1_bytes(public_key.data())
+                    .map_err(|_| VaultError::InvalidPublicKey)?;
+                let signature = match p256::ecdsa::Signature::try_from(signature.as_ref()) {
+                    Ok(signature) => signature,
+                    Err(_) => return Ok(false),
+                };
+                Ok(public_key.verify(data.as_ref(), &signature).is_ok())
+            }
             SecretType::Buffer | SecretType::Aes => Err(VaultError::InvalidPublicKey.into()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::Vault;
+    use ockam_core::compat::rand::thread_rng;
+    use ockam_core::vault::{PublicKey, SecretType, Signature, Verifier};
+    use p256::ecdsa::{signature::Signer, SigningKey};
+
+    #[tokio::test]
+    async fn verify_nist_p256() {
+        let vault = Vault::create();
+        let signing_key = SigningKey::random(&mut thread_rng());
+        let public_key = PublicKey::new(
+            signing_key
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec(),
+            SecretType::NistP256,
+        );
+
+        let data = b"Very important stuff";
+        let signature: p256::ecdsa::Signature = signing_key.sign(data);
+        let signature = Signature::new(signature.as_ref().to_vec());
+
+        assert!(vault.verify(&signature, &public_key, data).await.unwrap());
+        assert!(!vault
+            .verify(&signature, &public_key, b"Other stuff")
+            .await
+            .unwrap());
+    }
+}